@@ -0,0 +1,82 @@
+#![no_main]
+
+use eatsome_escpos::{parse_escpos, Alignment, BarcodeType, ESCPOSBuilder, PaperWidth, ReceiptElement};
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+enum Op {
+    Text(String),
+    NewLine,
+    Initialize,
+    Bold(bool),
+    Align(u8),
+    QrCode(String),
+    Barcode(String),
+    Drawer,
+    Cut(bool),
+}
+
+/// Printable ASCII only: the builder writes text as-is, so control bytes would be commands
+fn printable(s: &str) -> String {
+    s.chars().filter(|c| (' '..='~').contains(c)).collect()
+}
+
+// Whatever the builder writes, the preview shows exactly its text, with the bold state
+// it was written in, and its cuts
+fuzz_target!(|ops: Vec<Op>| {
+    let mut builder = ESCPOSBuilder::new(PaperWidth::Width80mm);
+    let (mut text, mut cuts, mut bold) = (Vec::new(), Vec::new(), false);
+    for op in &ops {
+        match op {
+            Op::Text(s) => {
+                let s = printable(s);
+                builder.text(&s);
+                text.extend(s.chars().map(|c| (c, bold)));
+            }
+            Op::NewLine => {
+                builder.new_line();
+            }
+            Op::Initialize => {
+                builder.initialize();
+                bold = false;
+            }
+            Op::Bold(on) => {
+                builder.bold(*on);
+                bold = *on;
+            }
+            Op::Align(a) => {
+                builder.align([Alignment::Left, Alignment::Center, Alignment::Right][*a as usize % 3]);
+            }
+            Op::QrCode(data) => {
+                // Store commands carry a 16-bit length
+                let data: String = data.chars().take(1000).collect();
+                builder.qr_code(&data, 6);
+            }
+            Op::Barcode(data) => {
+                let data: String = printable(data).chars().take(255).collect();
+                builder.barcode(&data, BarcodeType::CODE128);
+            }
+            Op::Drawer => {
+                builder.open_drawer();
+            }
+            Op::Cut(partial) => {
+                builder.cut(*partial);
+                cuts.push(*partial);
+            }
+        }
+    }
+
+    let receipt = parse_escpos(&builder.build(), PaperWidth::Width80mm);
+    let mut parsed_text = Vec::new();
+    let mut parsed_cuts = Vec::new();
+    for element in &receipt.elements {
+        match element {
+            ReceiptElement::Text { content, style, .. } => parsed_text.extend(content.chars().map(|c| (c, style.bold))),
+            ReceiptElement::Cut { partial } => parsed_cuts.push(*partial),
+            ReceiptElement::Feed { .. } => {}
+        }
+    }
+    assert_eq!(parsed_text, text);
+    assert_eq!(parsed_cuts, cuts);
+});
@@ -0,0 +1,21 @@
+#![no_main]
+
+use eatsome_escpos::{parse_escpos, PaperWidth, ReceiptElement};
+use libfuzzer_sys::fuzz_target;
+
+// Print previews parse whatever a formatter produced, including raw passthrough jobs:
+// any byte stream must parse without panicking, and text only comes from the input
+fuzz_target!(|data: &[u8]| {
+    for width in [PaperWidth::Width58mm, PaperWidth::Width80mm] {
+        let receipt = parse_escpos(data, width);
+        let text: usize = receipt
+            .elements
+            .iter()
+            .map(|e| match e {
+                ReceiptElement::Text { content, .. } => content.chars().count(),
+                _ => 0,
+            })
+            .sum();
+        assert!(text <= data.len());
+    }
+});
@@ -0,0 +1,197 @@
+use crate::builder::BarcodeType;
+use thiserror::Error;
+
+/// Longest barcode payload GS k can carry (length is one byte)
+const MAX_LEN: usize = 255;
+
+/// Why barcode data was refused. A printer given any of these prints an unreadable
+/// barcode or nothing at all, so formatters stop instead.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BarcodeError {
+    #[error("{symbology:?} barcode needs {expected} digits, got {len}")]
+    Length { symbology: BarcodeType, expected: &'static str, len: usize },
+
+    #[error("{symbology:?} barcode cannot contain {found:?}")]
+    InvalidCharacter { symbology: BarcodeType, found: char },
+
+    #[error("{symbology:?} check digit is {found}, expected {expected}")]
+    CheckDigit { symbology: BarcodeType, expected: u8, found: u8 },
+
+    #[error("UPC-E number system must be 0 or 1, got {0}")]
+    NumberSystem(u8),
+}
+
+/// GS1 mod-10 check digit for `digits` (EAN-8/13, UPC-A, ITF-14): weights 3 and 1
+/// alternating from the rightmost digit
+pub fn gs1_check_digit(digits: &[u8]) -> u8 {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| *d as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+/// Check barcode data for its symbology and return what should be sent: the check digit
+/// is appended when the caller left it off, and verified when present.
+pub fn validate(data: &str, symbology: BarcodeType) -> Result<String, BarcodeError> {
+    match symbology {
+        BarcodeType::EAN13 => gs1(data, symbology, 12, "12 or 13"),
+        BarcodeType::EAN8 => gs1(data, symbology, 7, "7 or 8"),
+        BarcodeType::UPCA => gs1(data, symbology, 11, "11 or 12"),
+        BarcodeType::UPCE => upc_e(data),
+        BarcodeType::ITF => itf(data),
+        BarcodeType::CODE39 => charset(data, symbology, |c| {
+            c.is_ascii_uppercase() || c.is_ascii_digit() || " $%+-./".contains(c)
+        }),
+        BarcodeType::CODABAR => charset(data, symbology, |c| {
+            c.is_ascii_digit() || "$+-./:ABCDabcd".contains(c)
+        }),
+        BarcodeType::CODE93 | BarcodeType::CODE128 => charset(data, symbology, |c| c.is_ascii()),
+    }
+}
+
+fn digits(data: &str, symbology: BarcodeType) -> Result<Vec<u8>, BarcodeError> {
+    data.chars()
+        .map(|c| {
+            c.to_digit(10)
+                .map(|d| d as u8)
+                .ok_or(BarcodeError::InvalidCharacter { symbology, found: c })
+        })
+        .collect()
+}
+
+fn to_string(digits: &[u8]) -> String {
+    digits.iter().map(|d| char::from(b'0' + d)).collect()
+}
+
+/// Fixed-length GS1 codes: `body` digits plus a check digit
+fn gs1(data: &str, symbology: BarcodeType, body: usize, expected: &'static str) -> Result<String, BarcodeError> {
+    let mut digits = digits(data, symbology)?;
+    match digits.len() {
+        len if len == body => {
+            digits.push(gs1_check_digit(&digits));
+            Ok(to_string(&digits))
+        }
+        len if len == body + 1 => {
+            verify(&digits, gs1_check_digit(&digits[..body]), symbology)?;
+            Ok(data.to_string())
+        }
+        len => Err(BarcodeError::Length { symbology, expected, len }),
+    }
+}
+
+fn verify(digits: &[u8], expected: u8, symbology: BarcodeType) -> Result<(), BarcodeError> {
+    let found = digits[digits.len() - 1];
+    if found == expected {
+        Ok(())
+    } else {
+        Err(BarcodeError::CheckDigit { symbology, expected, found })
+    }
+}
+
+/// UPC-E: 6 digits (printer adds number system 0 and the check digit), number system +
+/// 6 digits, or number system + 6 digits + check digit. The check digit is the one of
+/// the expanded UPC-A code.
+fn upc_e(data: &str) -> Result<String, BarcodeError> {
+    let symbology = BarcodeType::UPCE;
+    let mut digits = digits(data, symbology)?;
+    if digits.len() == 6 {
+        return Ok(data.to_string());
+    }
+    if !(7..=8).contains(&digits.len()) {
+        return Err(BarcodeError::Length { symbology, expected: "6, 7 or 8", len: digits.len() });
+    }
+    if digits[0] > 1 {
+        return Err(BarcodeError::NumberSystem(digits[0]));
+    }
+
+    let check = gs1_check_digit(&expand_upc_e(digits[0], &digits[1..7]));
+    if digits.len() == 7 {
+        digits.push(check);
+        return Ok(to_string(&digits));
+    }
+    verify(&digits, check, symbology)?;
+    Ok(data.to_string())
+}
+
+/// UPC-A digits (without check digit) for a zero-suppressed UPC-E code
+fn expand_upc_e(number_system: u8, d: &[u8]) -> Vec<u8> {
+    let (manufacturer, product): ([u8; 5], [u8; 5]) = match d[5] {
+        0..=2 => ([d[0], d[1], d[5], 0, 0], [0, 0, d[2], d[3], d[4]]),
+        3 => ([d[0], d[1], d[2], 0, 0], [0, 0, 0, d[3], d[4]]),
+        4 => ([d[0], d[1], d[2], d[3], 0], [0, 0, 0, 0, d[4]]),
+        _ => ([d[0], d[1], d[2], d[3], d[4]], [0, 0, 0, 0, d[5]]),
+    };
+    let mut upc_a = vec![number_system];
+    upc_a.extend_from_slice(&manufacturer);
+    upc_a.extend_from_slice(&product);
+    upc_a
+}
+
+/// Interleaved 2 of 5 encodes digit pairs: even length. 13 digits are an ITF-14 body and
+/// get their check digit; 14 digits are verified as ITF-14.
+fn itf(data: &str) -> Result<String, BarcodeError> {
+    let symbology = BarcodeType::ITF;
+    let mut digits = digits(data, symbology)?;
+    match digits.len() {
+        13 => {
+            digits.push(gs1_check_digit(&digits));
+            Ok(to_string(&digits))
+        }
+        14 => {
+            verify(&digits, gs1_check_digit(&digits[..13]), symbology)?;
+            Ok(data.to_string())
+        }
+        len if len >= 2 && len % 2 == 0 && len <= MAX_LEN => Ok(data.to_string()),
+        len => Err(BarcodeError::Length { symbology, expected: "an even number of", len }),
+    }
+}
+
+/// Free-length symbologies: allowed characters and 1..=255 of them
+fn charset(data: &str, symbology: BarcodeType, allowed: impl Fn(char) -> bool) -> Result<String, BarcodeError> {
+    if data.is_empty() || data.len() > MAX_LEN {
+        return Err(BarcodeError::Length { symbology, expected: "1 to 255", len: data.len() });
+    }
+    match data.chars().find(|c| !allowed(*c)) {
+        Some(found) => Err(BarcodeError::InvalidCharacter { symbology, found }),
+        None => Ok(data.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_digits_appended_and_verified() {
+        assert_eq!(validate("400638133393", BarcodeType::EAN13).unwrap(), "4006381333931");
+        assert_eq!(validate("4006381333931", BarcodeType::EAN13).unwrap(), "4006381333931");
+        assert_eq!(
+            validate("4006381333932", BarcodeType::EAN13),
+            Err(BarcodeError::CheckDigit { symbology: BarcodeType::EAN13, expected: 1, found: 2 })
+        );
+        assert_eq!(validate("9638507", BarcodeType::EAN8).unwrap(), "96385074");
+        assert_eq!(validate("03600029145", BarcodeType::UPCA).unwrap(), "036000291452");
+        assert_eq!(validate("0123456", BarcodeType::UPCE).unwrap(), "01234565");
+        assert!(validate("01234566", BarcodeType::UPCE).is_err());
+        assert_eq!(validate("1540014128876", BarcodeType::ITF).unwrap(), "15400141288763");
+    }
+
+    #[test]
+    fn test_length_and_charset_errors() {
+        assert!(matches!(validate("12345", BarcodeType::EAN13), Err(BarcodeError::Length { len: 5, .. })));
+        assert!(matches!(
+            validate("40063813339a", BarcodeType::EAN13),
+            Err(BarcodeError::InvalidCharacter { found: 'a', .. })
+        ));
+        assert!(validate("123", BarcodeType::ITF).is_err());
+        assert_eq!(validate("1234", BarcodeType::ITF).unwrap(), "1234");
+        assert_eq!(validate("2123456", BarcodeType::UPCE), Err(BarcodeError::NumberSystem(2)));
+        assert!(validate("ORDER-42", BarcodeType::CODE39).is_ok());
+        assert!(validate("order-42", BarcodeType::CODE39).is_err());
+        assert!(validate("", BarcodeType::CODE128).is_err());
+        assert!(validate("A123B", BarcodeType::CODABAR).is_ok());
+    }
+}
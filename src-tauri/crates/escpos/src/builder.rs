@@ -0,0 +1,469 @@
+use crate::barcode::{self, BarcodeError};
+use crate::raster::{self, RasterOptions};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+/// ESC/POS Commands (byte sequences)
+pub const ESC: u8 = 0x1b;
+pub const GS: u8 = 0x1d;
+pub const LF: u8 = 0x0a;
+pub const CR: u8 = 0x0d;
+
+/// DLE EOT Real-time status request: 0x10 0x04 n
+/// These commands are processed immediately by the printer (not buffered).
+const DLE: u8 = 0x10;
+const EOT: u8 = 0x04;
+const ENQ: u8 = 0x05;
+
+/// DLE EOT status request types
+#[derive(Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum StatusRequest {
+    /// Basic printer status (online/offline)
+    Printer = 1,
+    /// Offline cause (cover open, feed button pressed, error)
+    OfflineCause = 2,
+    /// Error cause (auto-cutter error, unrecoverable)
+    ErrorCause = 3,
+    /// Paper sensor status (near-end, paper-end)
+    PaperSensor = 4,
+}
+
+/// Build a DLE EOT real-time status request command
+pub fn build_status_request(request: StatusRequest) -> Vec<u8> {
+    vec![DLE, EOT, request as u8]
+}
+
+/// Build all 4 DLE EOT status requests concatenated into a single write
+pub fn build_full_status_request() -> Vec<u8> {
+    vec![
+        DLE, EOT, StatusRequest::Printer as u8,
+        DLE, EOT, StatusRequest::OfflineCause as u8,
+        DLE, EOT, StatusRequest::ErrorCause as u8,
+        DLE, EOT, StatusRequest::PaperSensor as u8,
+    ]
+}
+
+/// GS I 67: model name (Epson and compatibles answer `_` + name + NUL)
+pub fn build_model_request() -> Vec<u8> {
+    vec![GS, b'I', 67]
+}
+
+/// DLE EOT 2 + 3 (offline cause, error cause): sent after a job's data to confirm it printed
+pub fn build_write_ack_request() -> Vec<u8> {
+    vec![
+        DLE, EOT, StatusRequest::OfflineCause as u8,
+        DLE, EOT, StatusRequest::ErrorCause as u8,
+    ]
+}
+
+/// Clear a recoverable error (cutter jam once the paper is out) and reset the printer:
+/// DLE ENQ 2 (recover, discarding the receive and print buffers) + ESC @.
+/// Printers without a recoverable error ignore DLE ENQ.
+pub fn build_error_recovery() -> Vec<u8> {
+    vec![DLE, ENQ, 2, ESC, 0x40]
+}
+
+/// Paper width configuration
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum PaperWidth {
+    Width58mm = 32, // 32 characters per line
+    Width80mm = 48, // 48 characters per line
+}
+
+impl PaperWidth {
+    /// From characters per line (`PrinterCapabilities.max_width`)
+    pub fn from_chars(chars: u16) -> Self {
+        if chars <= PaperWidth::Width58mm as u16 {
+            PaperWidth::Width58mm
+        } else {
+            PaperWidth::Width80mm
+        }
+    }
+
+    pub fn mm(self) -> u16 {
+        match self {
+            PaperWidth::Width58mm => 58,
+            PaperWidth::Width80mm => 80,
+        }
+    }
+
+    /// Printable width in dots (203 dpi heads)
+    pub fn dots(self) -> u32 {
+        match self {
+            PaperWidth::Width58mm => 384,
+            PaperWidth::Width80mm => 576,
+        }
+    }
+
+    /// Characters per line in `font` (Font B glyphs are 9 dots wide instead of 12)
+    pub fn columns(self, font: Font) -> usize {
+        match font {
+            Font::A => self as usize,
+            Font::B => (self.dots() / 9) as usize,
+        }
+    }
+}
+
+/// Text alignment
+#[derive(Debug, Clone, Copy)]
+pub enum Alignment {
+    Left = 0,
+    Center = 1,
+    Right = 2,
+}
+
+/// Text size
+#[derive(Debug, Clone, Copy)]
+pub enum TextSize {
+    Normal = 0x00,
+    DoubleWidth = 0x10,
+    DoubleHeight = 0x20,
+    DoubleBoth = 0x30,
+}
+
+/// Font selection
+#[derive(Debug, Clone, Copy)]
+pub enum Font {
+    A = 0, // Standard (12x24)
+    B = 1, // Compressed (9x17)
+}
+
+/// Character code page for international characters
+#[derive(Debug, Clone, Copy)]
+pub enum CodePage {
+    PC437USA = 0,
+    Katakana = 1,
+    PC850Multilingual = 2,
+    PC860Portuguese = 3,
+    PC863CanadianFrench = 4,
+    PC865Nordic = 5,
+    WPC1252Latin1 = 16,
+    PC866Cyrillic = 17,
+    PC852Latin2 = 18,
+    PC858Euro = 19,
+}
+
+/// Barcode type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarcodeType {
+    UPCA = 65,
+    UPCE = 66,
+    EAN13 = 67,
+    EAN8 = 68,
+    CODE39 = 69,
+    ITF = 70,
+    CODABAR = 71,
+    CODE93 = 72,
+    CODE128 = 73,
+}
+
+/// ESC/POS Command Builder
+pub struct ESCPOSBuilder {
+    buffer: Vec<u8>,
+    paper_width: PaperWidth,
+    /// Characters per line in the selected font
+    columns: usize,
+}
+
+impl ESCPOSBuilder {
+    pub fn new(paper_width: PaperWidth) -> Self {
+        Self {
+            buffer: Vec::new(),
+            paper_width,
+            columns: paper_width.columns(Font::A),
+        }
+    }
+
+    /// Get the built command buffer
+    pub fn build(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    /// Initialize printer (also back to Font A)
+    pub fn initialize(&mut self) -> &mut Self {
+        self.buffer.extend_from_slice(&[ESC, 0x40]);
+        self.columns = self.paper_width.columns(Font::A);
+        self
+    }
+
+    /// Add text
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        self.buffer.extend_from_slice(text.as_bytes());
+        self
+    }
+
+    /// Add line feed
+    pub fn feed(&mut self, lines: u8) -> &mut Self {
+        for _ in 0..lines {
+            self.buffer.push(LF);
+        }
+        self
+    }
+
+    /// Add carriage return + line feed
+    pub fn new_line(&mut self) -> &mut Self {
+        self.buffer.extend_from_slice(&[CR, LF]);
+        self
+    }
+
+    /// Set text alignment
+    pub fn align(&mut self, alignment: Alignment) -> &mut Self {
+        self.buffer.extend_from_slice(&[ESC, 0x61, alignment as u8]);
+        self
+    }
+
+    /// Set text size
+    pub fn size(&mut self, size: TextSize) -> &mut Self {
+        self.buffer.extend_from_slice(&[GS, 0x21, size as u8]);
+        self
+    }
+
+    /// Enable/disable bold
+    pub fn bold(&mut self, enabled: bool) -> &mut Self {
+        self.buffer.extend_from_slice(&[ESC, 0x45, if enabled { 1 } else { 0 }]);
+        self
+    }
+
+    /// Enable/disable underline
+    pub fn underline(&mut self, enabled: bool) -> &mut Self {
+        self.buffer.extend_from_slice(&[ESC, 0x2d, if enabled { 1 } else { 0 }]);
+        self
+    }
+
+    /// Enable/disable inverse (white text on black)
+    pub fn inverse(&mut self, enabled: bool) -> &mut Self {
+        self.buffer.extend_from_slice(&[GS, 0x42, if enabled { 1 } else { 0 }]);
+        self
+    }
+
+    /// Draw horizontal line
+    pub fn draw_line(&mut self, char: char) -> &mut Self {
+        let line: String = char.to_string().repeat(self.columns);
+        self.text(&line).new_line()
+    }
+
+    /// Short centered separator (a third of the line), for compact tickets
+    pub fn short_line(&mut self, char: char) -> &mut Self {
+        let line: String = char.to_string().repeat(self.columns / 3);
+        self.center_text(&line)
+    }
+
+    /// Print a barcode after checking it for its symbology (`barcode::validate`); a
+    /// missing EAN/UPC/ITF-14 check digit is added. Nothing is written on error.
+    pub fn checked_barcode(&mut self, data: &str, barcode_type: BarcodeType) -> Result<&mut Self, BarcodeError> {
+        let data = barcode::validate(data, barcode_type)?;
+        Ok(self.barcode(&data, barcode_type))
+    }
+
+    /// Print barcode (data sent as-is; see `checked_barcode`)
+    pub fn barcode(&mut self, data: &str, barcode_type: BarcodeType) -> &mut Self {
+        // Set barcode height
+        self.buffer.extend_from_slice(&[GS, 0x68, 80]); // 80 dots
+
+        // Set barcode width
+        self.buffer.extend_from_slice(&[GS, 0x77, 2]); // module width 2
+
+        // Print barcode
+        self.buffer.extend_from_slice(&[GS, 0x6b, barcode_type as u8, data.len() as u8]);
+        self.buffer.extend_from_slice(data.as_bytes());
+
+        self
+    }
+
+    /// Print QR code
+    pub fn qr_code(&mut self, data: &str, size: u8) -> &mut Self {
+        let data_bytes = data.as_bytes();
+        let pl = ((data_bytes.len() + 3) % 256) as u8;
+        let ph = ((data_bytes.len() + 3) / 256) as u8;
+
+        // QR code model
+        self.buffer.extend_from_slice(&[GS, 0x28, 0x6b, 0x04, 0x00, 0x31, 0x41, 0x32, 0x00]);
+
+        // QR code size
+        self.buffer.extend_from_slice(&[GS, 0x28, 0x6b, 0x03, 0x00, 0x31, 0x43, size]);
+
+        // QR code error correction level (L=48, M=49, Q=50, H=51)
+        self.buffer.extend_from_slice(&[GS, 0x28, 0x6b, 0x03, 0x00, 0x31, 0x45, 0x31]);
+
+        // Store data
+        self.buffer.extend_from_slice(&[GS, 0x28, 0x6b, pl, ph, 0x31, 0x50, 0x30]);
+        self.buffer.extend_from_slice(data_bytes);
+
+        // Print QR code
+        self.buffer.extend_from_slice(&[GS, 0x28, 0x6b, 0x03, 0x00, 0x31, 0x51, 0x30]);
+
+        self
+    }
+
+    /// Cut paper
+    pub fn cut(&mut self, partial: bool) -> &mut Self {
+        self.feed(3); // Feed before cut
+        self.buffer.extend_from_slice(&[GS, 0x56, if partial { 1 } else { 0 }]);
+        self
+    }
+
+    /// Open cash drawer (if connected)
+    pub fn open_drawer(&mut self) -> &mut Self {
+        self.buffer.extend_from_slice(&[ESC, 0x70, 0, 25, 250]);
+        self
+    }
+
+    /// Open cash drawer on specific pin with custom timing
+    ///
+    /// # Arguments
+    /// * `pin` - Connector pin (2 or 5)
+    /// * `on_time_ms` - Pulse on-time in milliseconds (rounded to 2ms units)
+    /// * `off_time_ms` - Pulse off-time in milliseconds (rounded to 2ms units)
+    pub fn open_drawer_pin(&mut self, pin: u8, on_time_ms: u8, off_time_ms: u8) -> &mut Self {
+        let pin_val = if pin == 5 { 1 } else { 0 };
+        let t1 = on_time_ms / 2;
+        let t2 = off_time_ms / 2;
+        self.buffer.extend_from_slice(&[ESC, 0x70, pin_val, t1, t2]);
+        self
+    }
+
+    /// Select font (Font A = standard 12x24, Font B = compressed 9x17)
+    pub fn font(&mut self, font: Font) -> &mut Self {
+        self.buffer.extend_from_slice(&[ESC, 0x4d, font as u8]);
+        self.columns = self.paper_width.columns(font);
+        self
+    }
+
+    /// Set custom line spacing (n units, printer-dependent: typically n/360 or n/180 inch)
+    pub fn line_spacing(&mut self, n: u8) -> &mut Self {
+        self.buffer.extend_from_slice(&[ESC, 0x33, n]);
+        self
+    }
+
+    /// Reset line spacing to default (1/6 inch)
+    pub fn default_line_spacing(&mut self) -> &mut Self {
+        self.buffer.extend_from_slice(&[ESC, 0x32]);
+        self
+    }
+
+    /// Set right-side character spacing (0-255, in half-dot units)
+    pub fn char_spacing(&mut self, n: u8) -> &mut Self {
+        self.buffer.extend_from_slice(&[ESC, 0x20, n]);
+        self
+    }
+
+    /// Select character code page for international character support
+    ///
+    /// Use `CodePage::WPC1252Latin1` for Western European (€, ü, é, etc.)
+    /// Use `CodePage::PC858Euro` for Euro symbol on older printers
+    pub fn code_page(&mut self, page: CodePage) -> &mut Self {
+        self.buffer.extend_from_slice(&[ESC, 0x74, page as u8]);
+        self
+    }
+
+    /// Set width and height multiplier independently (1-8 each)
+    ///
+    /// More flexible than `size()` which uses predefined combinations.
+    /// width=2, height=3 means double-width, triple-height.
+    pub fn size_wh(&mut self, width: u8, height: u8) -> &mut Self {
+        let w = (width.clamp(1, 8) - 1) << 4;
+        let h = height.clamp(1, 8) - 1;
+        self.buffer.extend_from_slice(&[GS, 0x21, w | h]);
+        self
+    }
+
+    /// Print raster bit image (monochrome bitmap)
+    ///
+    /// Dithers to 1-bit and sends via GS v 0 in bands.
+    /// Automatically resizes to fit paper width.
+    pub fn raster_image(&mut self, img: &DynamicImage, max_width: u32) -> &mut Self {
+        self.raster_image_with(img, max_width, &RasterOptions::default())
+    }
+
+    /// Print raster bit image with explicit dithering, density and band size
+    pub fn raster_image_with(&mut self, img: &DynamicImage, max_width: u32, options: &RasterOptions) -> &mut Self {
+        self.buffer.extend_from_slice(&raster::render(img, max_width, options));
+        self
+    }
+
+    /// Write raw ESC/POS bytes (for commands not yet in the builder)
+    pub fn raw(&mut self, data: &[u8]) -> &mut Self {
+        self.buffer.extend_from_slice(data);
+        self
+    }
+
+    /// Add centered text (auto-calculated padding)
+    pub fn center_text(&mut self, text: &str) -> &mut Self {
+        let padding = self.columns.saturating_sub(text.len()) / 2;
+        let spaces = " ".repeat(padding);
+        self.text(&format!("{}{}", spaces, text)).new_line()
+    }
+
+    /// Add left-right justified text
+    pub fn justify_text(&mut self, left: &str, right: &str) -> &mut Self {
+        let spaces = self.columns.saturating_sub(left.len() + right.len());
+        let spacing = " ".repeat(spaces.max(1));
+        self.text(&format!("{}{}{}", left, spacing, right)).new_line()
+    }
+
+    /// Add table row (multiple columns with auto-spacing)
+    pub fn table_row(&mut self, columns: &[&str], widths: Option<&[usize]>) -> &mut Self {
+        let default_widths;
+        let widths = if let Some(w) = widths {
+            w
+        } else {
+            let col_width = self.columns / columns.len();
+            default_widths = vec![col_width; columns.len()];
+            &default_widths[..]
+        };
+
+        let mut row = String::new();
+        for (i, col) in columns.iter().enumerate() {
+            let width = widths.get(i).copied().unwrap_or(0);
+            if col.len() > width {
+                row.push_str(&col[..width]);
+            } else {
+                row.push_str(col);
+                row.push_str(&" ".repeat(width - col.len()));
+            }
+        }
+
+        let truncated = if row.len() > self.columns {
+            &row[..self.columns]
+        } else {
+            &row
+        };
+
+        self.text(truncated).new_line()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_commands_and_line_width() {
+        let mut builder = ESCPOSBuilder::new(PaperWidth::Width58mm);
+        builder.initialize().bold(true).justify_text("Burger", "12.50").bold(false);
+        let out = builder.build();
+        assert_eq!(&out[..5], &[ESC, 0x40, ESC, 0x45, 1]);
+        // 32 columns on 58mm paper: the line is exactly that wide, then CR LF
+        let line = &out[5..5 + 32];
+        assert_eq!(std::str::from_utf8(line).unwrap(), format!("Burger{}12.50", " ".repeat(21)));
+        assert_eq!(&out[37..39], &[CR, LF]);
+
+        // Font B fits more characters; ESC @ goes back to Font A
+        let mut builder = ESCPOSBuilder::new(PaperWidth::Width80mm);
+        builder.font(Font::B).draw_line('-').initialize().draw_line('=');
+        let text = String::from_utf8_lossy(&builder.build()).to_string();
+        assert!(text.contains(&"-".repeat(64)) && !text.contains(&"-".repeat(65)));
+        assert!(text.contains(&"=".repeat(48)) && !text.contains(&"=".repeat(49)));
+
+        assert_eq!(build_full_status_request().len(), 12);
+        assert_eq!(build_status_request(StatusRequest::PaperSensor), vec![DLE, EOT, 4]);
+
+        // Check digit added, nothing written for a bad code
+        let mut builder = ESCPOSBuilder::new(PaperWidth::Width80mm);
+        builder.checked_barcode("400638133393", BarcodeType::EAN13).unwrap();
+        assert!(builder.checked_barcode("12AB", BarcodeType::EAN13).is_err());
+        let out = builder.build();
+        assert!(out.ends_with(&[&[GS, 0x6b, 67, 13][..], b"4006381333931"].concat()));
+    }
+}
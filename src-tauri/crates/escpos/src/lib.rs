@@ -0,0 +1,14 @@
+//! ESC/POS for thermal receipt printers: the command builder, real-time status
+//! requests, barcode validation, raster images and a parser that turns command
+//! buffers back into text for print previews.
+//!
+//! Receipt layouts (kitchen tickets, customer receipts, test prints) stay in the daemon;
+//! this crate knows the protocol, not the restaurant.
+
+pub mod barcode;
+pub mod builder;
+pub mod parser;
+pub mod raster;
+
+pub use builder::*;
+pub use parser::*;
@@ -0,0 +1,614 @@
+use crate::builder::PaperWidth;
+use serde::{Deserialize, Serialize};
+
+/// Text alignment for parsed elements
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextAlignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// Text style state for parsed elements
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TextStyle {
+    pub bold: bool,
+    pub underline: bool,
+    pub double_width: bool,
+    pub double_height: bool,
+    pub inverted: bool,
+}
+
+/// A single element in a parsed receipt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ReceiptElement {
+    Text {
+        content: String,
+        style: TextStyle,
+        alignment: TextAlignment,
+    },
+    Feed {
+        lines: u8,
+    },
+    Cut {
+        partial: bool,
+    },
+}
+
+/// Complete parsed receipt structure for frontend rendering
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParsedReceipt {
+    pub elements: Vec<ReceiptElement>,
+    pub paper_width_mm: u16,
+    pub char_width: u8,
+}
+
+impl ParsedReceipt {
+    /// Plain-text rendering in a box of `char_width` columns, for terminals and diffs.
+    /// Lines are aligned and wrapped like on paper; double-width text is letter-spaced,
+    /// other styles are dropped. Cuts print as a `cut` rule.
+    pub fn to_ascii(&self) -> String {
+        let width = self.char_width as usize;
+        let mut out = format!("+{}+\n", "-".repeat(width));
+        let mut line: Vec<char> = Vec::new();
+        let mut alignment = None;
+
+        let flush = |out: &mut String, line: &mut Vec<char>, alignment: &mut Option<TextAlignment>| {
+            let chunks: Vec<&[char]> = if line.is_empty() { vec![&[]] } else { line.chunks(width.max(1)).collect() };
+            for chunk in chunks {
+                let pad = width - chunk.len();
+                let left = match alignment {
+                    Some(TextAlignment::Center) => pad / 2,
+                    Some(TextAlignment::Right) => pad,
+                    _ => 0,
+                };
+                let text: String = chunk.iter().collect();
+                out.push_str(&format!("|{}{}{}|\n", " ".repeat(left), text, " ".repeat(pad - left)));
+            }
+            line.clear();
+            *alignment = None;
+        };
+
+        for element in &self.elements {
+            match element {
+                ReceiptElement::Text { content, style, alignment: a } => {
+                    // A line takes the alignment it starts with, as on the printer
+                    alignment.get_or_insert_with(|| a.clone());
+                    for c in content.chars() {
+                        line.push(c);
+                        if style.double_width {
+                            line.push(' ');
+                        }
+                    }
+                }
+                ReceiptElement::Feed { lines } => {
+                    for _ in 0..*lines {
+                        flush(&mut out, &mut line, &mut alignment);
+                    }
+                }
+                ReceiptElement::Cut { partial } => {
+                    if !line.is_empty() {
+                        flush(&mut out, &mut line, &mut alignment);
+                    }
+                    let label = if *partial { " partial cut " } else { " cut " };
+                    let dashes = width.saturating_sub(label.len());
+                    out.push_str(&format!("+{}{}{}+\n", "-".repeat(dashes / 2), label, "-".repeat(dashes - dashes / 2)));
+                }
+            }
+        }
+        if !line.is_empty() {
+            flush(&mut out, &mut line, &mut alignment);
+        }
+        if !matches!(self.elements.last(), Some(ReceiptElement::Cut { .. })) {
+            out.push_str(&format!("+{}+\n", "-".repeat(width)));
+        }
+        out
+    }
+}
+
+/// Byte `offset` after `i`, 0 past the end (a truncated command reads its missing
+/// arguments as 0 and ends the buffer)
+fn arg(buffer: &[u8], i: usize, offset: usize) -> u8 {
+    buffer.get(i.saturating_add(offset)).copied().unwrap_or(0)
+}
+
+/// Little-endian length from `count` argument bytes starting at `offset`
+fn length(buffer: &[u8], i: usize, offset: usize, count: usize) -> usize {
+    (0..count).fold(0usize, |len, n| len | ((arg(buffer, i, offset + n) as usize) << (8 * n)))
+}
+
+/// Bytes up to and including the first NUL from `offset` (NUL-terminated arguments)
+fn through_nul(buffer: &[u8], i: usize, offset: usize) -> usize {
+    let start = i.saturating_add(offset).min(buffer.len());
+    match buffer[start..].iter().position(|b| *b == 0) {
+        Some(nul) => offset + nul + 1,
+        None => buffer.len() - i,
+    }
+}
+
+/// Parse ESC/POS binary buffer into structured receipt data
+///
+/// Interprets ESC/POS commands (ESC @, ESC E, ESC a, GS !, etc.)
+/// and converts them to a JSON-friendly structure that React can render
+/// with monospace fonts to simulate thermal printer output.
+///
+/// Any byte stream is accepted (previews of raw passthrough jobs): every command has a
+/// known length, lengths announced by the data are capped at what is left, and each
+/// step consumes at least one byte.
+pub fn parse_escpos(buffer: &[u8], paper_width: PaperWidth) -> ParsedReceipt {
+    let char_width = paper_width as u8;
+    let paper_width_mm = paper_width.mm();
+
+    let mut elements = Vec::new();
+    let mut style = TextStyle::default();
+    let mut alignment = TextAlignment::Left;
+    let mut text_buf = String::new();
+    let mut i = 0;
+
+    let flush = |elements: &mut Vec<ReceiptElement>, text_buf: &mut String, style: &TextStyle, alignment: &TextAlignment| {
+        if !text_buf.is_empty() {
+            elements.push(ReceiptElement::Text {
+                content: std::mem::take(text_buf),
+                style: style.clone(),
+                alignment: alignment.clone(),
+            });
+        }
+    };
+
+    while i < buffer.len() {
+        let consumed = match buffer[i] {
+            // ESC commands (0x1B)
+            0x1B => {
+                // Flush text buffer before processing command
+                flush(&mut elements, &mut text_buf, &style, &alignment);
+                match arg(buffer, i, 1) {
+                    0x40 => {
+                        // ESC @ - Initialize (reset)
+                        style = TextStyle::default();
+                        alignment = TextAlignment::Left;
+                        2
+                    }
+                    0x45 => {
+                        // ESC E n - Bold on/off
+                        style.bold = arg(buffer, i, 2) & 1 != 0;
+                        3
+                    }
+                    0x21 => {
+                        // ESC ! n - Print mode (bold, double height / width, underline)
+                        let n = arg(buffer, i, 2);
+                        style.bold = n & 0x08 != 0;
+                        style.double_height = n & 0x10 != 0;
+                        style.double_width = n & 0x20 != 0;
+                        style.underline = n & 0x80 != 0;
+                        3
+                    }
+                    0x61 => {
+                        // ESC a n - Alignment
+                        alignment = match arg(buffer, i, 2) {
+                            1 | 49 => TextAlignment::Center,
+                            2 | 50 => TextAlignment::Right,
+                            _ => TextAlignment::Left,
+                        };
+                        3
+                    }
+                    0x2D => {
+                        // ESC - n - Underline on/off
+                        style.underline = arg(buffer, i, 2) & 3 != 0;
+                        3
+                    }
+                    0x64 => {
+                        // ESC d n - Print and feed n lines
+                        let lines = arg(buffer, i, 2);
+                        if lines > 0 {
+                            elements.push(ReceiptElement::Feed { lines });
+                        }
+                        3
+                    }
+                    0x69 | 0x6D => {
+                        // ESC i / ESC m - Cut (legacy full / partial)
+                        elements.push(ReceiptElement::Cut { partial: arg(buffer, i, 1) == 0x6D });
+                        2
+                    }
+                    0x2A => {
+                        // ESC * m nL nH d... - Bit image (24-dot modes take 3 bytes per column)
+                        let columns = length(buffer, i, 3, 2);
+                        let per_column = if arg(buffer, i, 2) >= 32 { 3 } else { 1 };
+                        5usize.saturating_add(columns * per_column)
+                    }
+                    0x44 => {
+                        // ESC D n... NUL - Tab positions
+                        through_nul(buffer, i, 2)
+                    }
+                    0x70 => {
+                        // ESC p m t1 t2 - Cash drawer
+                        5
+                    }
+                    0x24 | 0x5C => {
+                        // ESC $ / ESC \ nL nH - Absolute / relative position
+                        4
+                    }
+                    0x63 => {
+                        // ESC c x n - Paper sensors, panel buttons
+                        4
+                    }
+                    // ESC M / 3 / SP / t / G / J / R / V / { / = n - Font, line and character
+                    // spacing, code page, double strike, feed dots, charset, rotation,
+                    // upside down, peripheral (no effect on the preview)
+                    0x4D | 0x33 | 0x20 | 0x74 | 0x47 | 0x4A | 0x52 | 0x56 | 0x7B | 0x3D => 3,
+                    // ESC 2 - Default line spacing; unknown ESC commands
+                    _ => 2,
+                }
+            }
+            // GS commands (0x1D)
+            0x1D => {
+                flush(&mut elements, &mut text_buf, &style, &alignment);
+                match arg(buffer, i, 1) {
+                    0x21 => {
+                        // GS ! n - Character size
+                        let n = arg(buffer, i, 2);
+                        style.double_width = (n & 0x10) != 0;
+                        style.double_height = (n & 0x01) != 0 || (n & 0x20) != 0;
+                        3
+                    }
+                    0x42 => {
+                        // GS B n - Inverse on/off
+                        style.inverted = arg(buffer, i, 2) & 1 != 0;
+                        3
+                    }
+                    0x56 => {
+                        // GS V m [n] - Cut paper (function B and later take a feed amount)
+                        let m = arg(buffer, i, 2);
+                        let partial = if m >= 65 { m & 1 == 0 } else { m & 1 != 0 };
+                        elements.push(ReceiptElement::Cut { partial });
+                        if m >= 65 {
+                            4
+                        } else {
+                            3
+                        }
+                    }
+                    0x28 => {
+                        // GS ( fn pL pH d... - QR codes, graphics and other extended
+                        // functions (variable length, skip)
+                        5usize.saturating_add(length(buffer, i, 3, 2))
+                    }
+                    0x38 if arg(buffer, i, 2) == 0x4C => {
+                        // GS 8 L p1..p4 d... - Graphics with a 32-bit length
+                        7usize.saturating_add(length(buffer, i, 3, 4))
+                    }
+                    0x6B => {
+                        // GS k m - Barcode: function A is NUL-terminated, function B has a
+                        // length byte
+                        if arg(buffer, i, 2) < 65 {
+                            through_nul(buffer, i, 3)
+                        } else {
+                            4 + arg(buffer, i, 3) as usize
+                        }
+                    }
+                    0x76 => {
+                        // GS v 0 m xL xH yL yH d... - Raster image (skip entire image data)
+                        let byte_width = length(buffer, i, 4, 2);
+                        let height = length(buffer, i, 6, 2);
+                        8usize.saturating_add(byte_width.saturating_mul(height))
+                    }
+                    0x2A => {
+                        // GS * x y d... - Define downloaded bit image (x × y × 8 bytes)
+                        4 + arg(buffer, i, 2) as usize * arg(buffer, i, 3) as usize * 8
+                    }
+                    0x4C | 0x57 | 0x24 | 0x5C => {
+                        // GS L / W / $ / \ nL nH - Left margin, print width, positions
+                        4
+                    }
+                    // GS h / w / H / f / I / r / a / b / / n - Barcode height, width, HRI
+                    // position and font, model and status requests, ASB, smoothing,
+                    // downloaded image (skip)
+                    0x68 | 0x77 | 0x48 | 0x66 | 0x49 | 0x72 | 0x61 | 0x62 | 0x2F => 3,
+                    _ => 2,
+                }
+            }
+            // FS commands (0x1C): kanji mode, NV images
+            0x1C => {
+                flush(&mut elements, &mut text_buf, &style, &alignment);
+                match arg(buffer, i, 1) {
+                    0x28 => 5usize.saturating_add(length(buffer, i, 3, 2)),
+                    0x70 => 4,
+                    _ => 2,
+                }
+            }
+            // DLE EOT / DLE ENQ n - Real-time requests (no output)
+            0x10 if matches!(arg(buffer, i, 1), 0x04 | 0x05) => 3,
+            // LF (Line Feed)
+            0x0A => {
+                flush(&mut elements, &mut text_buf, &style, &alignment);
+                elements.push(ReceiptElement::Feed { lines: 1 });
+                1
+            }
+            // Regular printable text; CR and other control bytes are skipped
+            byte => {
+                if byte >= 0x20 {
+                    text_buf.push(byte as char);
+                }
+                1
+            }
+        };
+        // Commands announcing more data than is left end the buffer
+        i = i.saturating_add(consumed).min(buffer.len());
+    }
+
+    // Flush remaining text
+    flush(&mut elements, &mut text_buf, &style, &alignment);
+
+    ParsedReceipt {
+        elements,
+        paper_width_mm,
+        char_width,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Alignment, BarcodeType, ESCPOSBuilder, Font, TextSize};
+    use crate::raster::RasterOptions;
+    use image::{DynamicImage, GrayImage, Luma};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_parse_builder_output() {
+        let mut builder = ESCPOSBuilder::new(PaperWidth::Width80mm);
+        builder
+            .initialize()
+            .align(Alignment::Center)
+            .size(TextSize::DoubleBoth)
+            .bold(true)
+            .text("KITCHEN")
+            .new_line()
+            .size(TextSize::Normal)
+            .bold(false)
+            .qr_code("https://eatsome.nl", 6)
+            .barcode("12345", BarcodeType::CODE39)
+            .align(Alignment::Left)
+            .text("2x Burger")
+            .cut(true);
+        let receipt = parse_escpos(&builder.build(), PaperWidth::Width80mm);
+        assert_eq!((receipt.paper_width_mm, receipt.char_width), (80, 48));
+
+        let texts: Vec<(&str, bool, bool)> = receipt
+            .elements
+            .iter()
+            .filter_map(|e| match e {
+                ReceiptElement::Text { content, style, alignment } => {
+                    Some((content.as_str(), style.bold && style.double_width, matches!(alignment, TextAlignment::Center)))
+                }
+                _ => None,
+            })
+            .collect();
+        // QR and barcode data are skipped, not printed as text
+        assert_eq!(texts, vec![("KITCHEN", true, true), ("2x Burger", false, false)]);
+        assert!(matches!(receipt.elements.last(), Some(ReceiptElement::Cut { partial: true })));
+    }
+
+    #[test]
+    fn test_to_ascii() {
+        let mut builder = ESCPOSBuilder::new(PaperWidth::Width58mm);
+        builder
+            .initialize()
+            .align(Alignment::Center)
+            .size(TextSize::DoubleWidth)
+            .text("BAR")
+            .new_line()
+            .size(TextSize::Normal)
+            .align(Alignment::Right)
+            .text("12:30")
+            .new_line()
+            .align(Alignment::Left)
+            .text(&"x".repeat(40))
+            .new_line()
+            .cut(true)
+            .text("2x Cola")
+            .feed(2)
+            .cut(false);
+        let ascii = parse_escpos(&builder.build(), PaperWidth::Width58mm).to_ascii();
+        let edge = format!("+{}+", "-".repeat(32));
+        let blank = format!("|{}|", " ".repeat(32));
+        let expected = [
+            edge.as_str(),
+            "|             B A R              |",
+            "|                           12:30|",
+            &format!("|{}|", "x".repeat(32)),
+            &format!("|{:<32}|", "x".repeat(8)),
+            // The cut's own feed
+            &blank,
+            &blank,
+            &blank,
+            "+--------- partial cut ----------+",
+            "|2x Cola                         |",
+            &blank,
+            &blank,
+            &blank,
+            &blank,
+            "+------------- cut --------------+",
+        ];
+        assert_eq!(ascii.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_truncated_commands_end_the_buffer() {
+        // Headers announcing more data than follows, and arguments cut off: nothing of
+        // them shows up as text
+        let truncated: [&[u8]; 6] = [
+            &[0x1D, 0x76, 0x30, 0, 0xFF, 0xFF, 0xFF, 0xFF, b'A'],
+            &[0x1D, 0x28, 0x6B, 0xFF, 0xFF, b'A'],
+            &[0x1D, 0x6B, 73, 200, b'A'],
+            &[0x1D, 0x6B, 4, b'A', b'B'],
+            &[0x1D, 0x38, 0x4C, 0xFF, 0xFF, 0xFF, 0xFF],
+            &[0x1B, 0x70, 0],
+        ];
+        for buffer in truncated {
+            assert!(parse_escpos(buffer, PaperWidth::Width80mm).elements.is_empty(), "{:?}", buffer);
+        }
+
+        // Arguments are not printed, lengths are honoured
+        let receipt = parse_escpos(b"\x1b\x70\x00\x41\x41AB\x1d\x6b\x04123\x00C\x1bd\x03", PaperWidth::Width80mm);
+        let text: Vec<&ReceiptElement> = receipt.elements.iter().collect();
+        assert!(matches!(text[0], ReceiptElement::Text { content, .. } if content == "AB"));
+        assert!(matches!(text[1], ReceiptElement::Text { content, .. } if content == "C"));
+        assert!(matches!(text[2], ReceiptElement::Feed { lines: 3 }));
+    }
+
+    /// One builder call, for round trips
+    #[derive(Debug, Clone)]
+    enum Op {
+        Text(String),
+        NewLine,
+        Feed(u8),
+        Initialize,
+        Bold(bool),
+        Underline(bool),
+        Inverse(bool),
+        Align(u8),
+        Size(u8),
+        Font(bool),
+        LineSpacing(u8),
+        QrCode(String),
+        Barcode(String),
+        Raster(u32, u32, u8),
+        Drawer,
+        Cut(bool),
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            "[ -~]{1,40}".prop_map(Op::Text),
+            Just(Op::NewLine),
+            (0u8..4).prop_map(Op::Feed),
+            Just(Op::Initialize),
+            any::<bool>().prop_map(Op::Bold),
+            any::<bool>().prop_map(Op::Underline),
+            any::<bool>().prop_map(Op::Inverse),
+            (0u8..3).prop_map(Op::Align),
+            (0u8..4).prop_map(Op::Size),
+            any::<bool>().prop_map(Op::Font),
+            any::<u8>().prop_map(Op::LineSpacing),
+            "[ -~]{1,300}".prop_map(Op::QrCode),
+            "[ -~]{1,60}".prop_map(Op::Barcode),
+            (1u32..80, 1u32..40, any::<u8>()).prop_map(|(w, h, gray)| Op::Raster(w, h, gray)),
+            Just(Op::Drawer),
+            any::<bool>().prop_map(Op::Cut),
+        ]
+    }
+
+    /// What a printer prints for some commands
+    #[derive(Debug, Default, PartialEq)]
+    struct Printed {
+        /// Every text character with its bold state
+        text: Vec<(char, bool)>,
+        feeds: usize,
+        /// Partial or not, in order
+        cuts: Vec<bool>,
+    }
+
+    /// Build `ops` and return the commands and what they print
+    fn build(ops: &[Op]) -> (Vec<u8>, Printed) {
+        let mut builder = ESCPOSBuilder::new(PaperWidth::Width80mm);
+        let mut expected = Printed::default();
+        let mut bold = false;
+        for op in ops {
+            match op {
+                Op::Text(s) => {
+                    builder.text(s);
+                    expected.text.extend(s.chars().map(|c| (c, bold)));
+                }
+                Op::NewLine => {
+                    builder.new_line();
+                    expected.feeds += 1;
+                }
+                Op::Feed(n) => {
+                    builder.feed(*n);
+                    expected.feeds += *n as usize;
+                }
+                Op::Initialize => {
+                    builder.initialize();
+                    bold = false;
+                }
+                Op::Bold(on) => {
+                    builder.bold(*on);
+                    bold = *on;
+                }
+                Op::Underline(on) => {
+                    builder.underline(*on);
+                }
+                Op::Inverse(on) => {
+                    builder.inverse(*on);
+                }
+                Op::Align(a) => {
+                    builder.align([Alignment::Left, Alignment::Center, Alignment::Right][*a as usize]);
+                }
+                Op::Size(s) => {
+                    builder.size([TextSize::Normal, TextSize::DoubleWidth, TextSize::DoubleHeight, TextSize::DoubleBoth][*s as usize]);
+                }
+                Op::Font(b) => {
+                    builder.font(if *b { Font::B } else { Font::A });
+                }
+                Op::LineSpacing(n) => {
+                    builder.line_spacing(*n);
+                }
+                Op::QrCode(data) => {
+                    builder.qr_code(data, 6);
+                }
+                Op::Barcode(data) => {
+                    builder.barcode(data, BarcodeType::CODE128);
+                }
+                Op::Raster(w, h, gray) => {
+                    let img = DynamicImage::ImageLuma8(GrayImage::from_fn(*w, *h, |x, y| Luma([gray.wrapping_add((x * y) as u8)])));
+                    builder.raster_image_with(&img, 576, &RasterOptions { band_height: 16, ..RasterOptions::default() });
+                }
+                Op::Drawer => {
+                    builder.open_drawer();
+                }
+                Op::Cut(partial) => {
+                    builder.cut(*partial);
+                    expected.feeds += 3;
+                    expected.cuts.push(*partial);
+                }
+            }
+        }
+        (builder.build(), expected)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_any_bytes_parse(buffer in prop::collection::vec(
+            // Mostly command prefixes, so commands get cut off at every offset
+            prop_oneof![Just(0x1Bu8), Just(0x1D), Just(0x1C), Just(0x10), Just(0x0A), any::<u8>()],
+            0..512,
+        )) {
+            let receipt = parse_escpos(&buffer, PaperWidth::Width58mm);
+            // Every element and every character comes from at least one input byte
+            let chars: usize = receipt.elements.iter().map(|e| match e {
+                ReceiptElement::Text { content, .. } => content.chars().count(),
+                _ => 1,
+            }).sum();
+            prop_assert!(chars <= buffer.len());
+        }
+
+        #[test]
+        fn prop_builder_output_round_trips(ops in prop::collection::vec(op(), 0..40)) {
+            let (commands, expected) = build(&ops);
+            let receipt = parse_escpos(&commands, PaperWidth::Width80mm);
+
+            let mut printed = Printed::default();
+            for element in &receipt.elements {
+                match element {
+                    ReceiptElement::Text { content, style, .. } => {
+                        printed.text.extend(content.chars().map(|c| (c, style.bold)));
+                    }
+                    ReceiptElement::Feed { lines } => printed.feeds += *lines as usize,
+                    ReceiptElement::Cut { partial } => printed.cuts.push(*partial),
+                }
+            }
+            prop_assert_eq!(printed, expected);
+        }
+    }
+}
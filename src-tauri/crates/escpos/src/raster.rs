@@ -0,0 +1,215 @@
+use image::{DynamicImage, GrayImage};
+use serde::{Deserialize, Serialize};
+
+const GS: u8 = 0x1d;
+
+/// Rows per GS v 0 command. Printers start feeding as soon as a band arrives, and
+/// cheap models drop data when one command exceeds their receive buffer.
+pub const DEFAULT_BAND_HEIGHT: u32 = 128;
+
+/// Gray to black/white conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Dither {
+    /// Hard cut at 50% gray (sharp for line art, blotchy for photos and gradients)
+    Threshold,
+    /// Floyd–Steinberg error diffusion
+    #[default]
+    FloydSteinberg,
+}
+
+/// GS v 0 mode `m`. Double modes send half the data for the same printed size,
+/// at half the resolution in that direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RasterDensity {
+    #[default]
+    Normal = 0,
+    DoubleWidth = 1,
+    DoubleHeight = 2,
+    Quadruple = 3,
+}
+
+impl RasterDensity {
+    /// Printed dots per image pixel (horizontal, vertical)
+    fn scale(self) -> (u32, u32) {
+        match self {
+            RasterDensity::Normal => (1, 1),
+            RasterDensity::DoubleWidth => (2, 1),
+            RasterDensity::DoubleHeight => (1, 2),
+            RasterDensity::Quadruple => (2, 2),
+        }
+    }
+}
+
+/// How images are converted and sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RasterOptions {
+    pub dither: Dither,
+    pub density: RasterDensity,
+    /// Rows per GS v 0 command (0 = whole image in one command)
+    pub band_height: u32,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            dither: Dither::FloydSteinberg,
+            density: RasterDensity::Normal,
+            band_height: DEFAULT_BAND_HEIGHT,
+        }
+    }
+}
+
+/// 1-bit image, rows packed MSB-first, 1 = black
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    pub byte_width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+/// Scale to fit `max_dots` printed dots wide (never upscales) and convert to grayscale
+fn fit(img: &DynamicImage, max_dots: u32, density: RasterDensity) -> GrayImage {
+    let (sx, sy) = density.scale();
+    let (width, height) = (img.width().max(1), img.height().max(1));
+    let target_width = width.min(max_dots / sx).max(1);
+    let target_height = ((height as u64 * target_width as u64 / width as u64) as u32 / sy).max(1);
+
+    if (target_width, target_height) == (width, height) {
+        img.to_luma8()
+    } else {
+        image::imageops::resize(
+            &img.to_luma8(),
+            target_width,
+            target_height,
+            image::imageops::FilterType::Lanczos3,
+        )
+    }
+}
+
+/// Convert a grayscale image to a 1-bit bitmap
+pub fn to_bitmap(gray: &GrayImage, dither: Dither) -> Bitmap {
+    let (width, height) = (gray.width() as usize, gray.height() as usize);
+    let byte_width = width.div_ceil(8);
+    let mut data = vec![0u8; byte_width * height];
+
+    let mut set_black = |x: usize, y: usize| data[y * byte_width + x / 8] |= 0x80 >> (x % 8);
+
+    match dither {
+        Dither::Threshold => {
+            for (x, y, pixel) in gray.enumerate_pixels() {
+                if pixel[0] < 128 {
+                    set_black(x as usize, y as usize);
+                }
+            }
+        }
+        Dither::FloydSteinberg => {
+            // Two rows of accumulated error, padded by one column on each side
+            let mut current = vec![0i32; width + 2];
+            let mut next = vec![0i32; width + 2];
+            for y in 0..height {
+                for x in 0..width {
+                    let value = gray.get_pixel(x as u32, y as u32)[0] as i32 + current[x + 1] / 16;
+                    let (output, error) = if value < 128 { (0, value) } else { (255, value - 255) };
+                    if output == 0 {
+                        set_black(x, y);
+                    }
+                    current[x + 2] += error * 7;
+                    next[x] += error * 3;
+                    next[x + 1] += error * 5;
+                    next[x + 2] += error;
+                }
+                std::mem::swap(&mut current, &mut next);
+                next.iter_mut().for_each(|e| *e = 0);
+            }
+        }
+    }
+
+    Bitmap { byte_width, height, data }
+}
+
+/// Encode a bitmap as GS v 0 commands, one per band
+pub fn encode(bitmap: &Bitmap, density: RasterDensity, band_height: u32) -> Vec<u8> {
+    let band = match band_height as usize {
+        0 => bitmap.height.max(1),
+        rows => rows,
+    };
+    let mut out = Vec::with_capacity(bitmap.data.len() + 8 * bitmap.height.div_ceil(band));
+
+    for rows in bitmap.data.chunks(bitmap.byte_width.max(1) * band) {
+        let band_rows = rows.len() / bitmap.byte_width.max(1);
+        out.extend_from_slice(&[GS, 0x76, 0x30, density as u8]);
+        out.push(bitmap.byte_width as u8); // xL
+        out.push((bitmap.byte_width >> 8) as u8); // xH
+        out.push(band_rows as u8); // yL
+        out.push((band_rows >> 8) as u8); // yH
+        out.extend_from_slice(rows);
+    }
+    out
+}
+
+/// Full raster pipeline: fit to paper, dither, encode in bands
+pub fn render(img: &DynamicImage, max_dots: u32, options: &RasterOptions) -> Vec<u8> {
+    let gray = fit(img, max_dots, options.density);
+    encode(&to_bitmap(&gray, options.dither), options.density, options.band_height)
+}
+
+/// Raster pipeline for small images: scaled to at most `max_height` printed rows first,
+/// then fitted to the paper like `render`
+pub fn thumbnail(img: &DynamicImage, max_dots: u32, max_height: u32, options: &RasterOptions) -> Vec<u8> {
+    if img.height() <= max_height.max(1) {
+        return render(img, max_dots, options);
+    }
+    let scaled = img.resize(max_dots, max_height.max(1), image::imageops::FilterType::Lanczos3);
+    render(&scaled, max_dots, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    #[test]
+    fn test_dithering_keeps_gray_levels() {
+        let gray = GrayImage::from_pixel(64, 64, Luma([128]));
+
+        // Threshold turns mid-gray into solid white; dithering gives ~50% coverage
+        let flat = to_bitmap(&gray, Dither::Threshold);
+        assert!(flat.data.iter().all(|b| *b == 0));
+
+        let dithered = to_bitmap(&gray, Dither::FloydSteinberg);
+        let black: u32 = dithered.data.iter().map(|b| b.count_ones()).sum();
+        assert!((1800..2300).contains(&black), "black dots: {}", black);
+
+        let solid = to_bitmap(&GrayImage::from_pixel(10, 2, Luma([0])), Dither::FloydSteinberg);
+        assert_eq!(solid.data, vec![0xFF, 0xC0, 0xFF, 0xC0]);
+    }
+
+    #[test]
+    fn test_encode_splits_bands_and_scales_for_density() {
+        let bitmap = Bitmap { byte_width: 2, height: 5, data: vec![0xAA; 10] };
+        let out = encode(&bitmap, RasterDensity::DoubleHeight, 2);
+
+        // 2 + 2 + 1 rows, each with an 8-byte header
+        assert_eq!(out.len(), 3 * 8 + 10);
+        assert_eq!(&out[..8], &[GS, 0x76, 0x30, 2, 2, 0, 2, 0]);
+        assert_eq!(&out[24..32], &[GS, 0x76, 0x30, 2, 2, 0, 1, 0]);
+
+        // Quadruple density halves the data in both directions
+        let img = DynamicImage::ImageLuma8(GrayImage::from_pixel(1000, 500, Luma([0])));
+        let gray = fit(&img, 576, RasterDensity::Quadruple);
+        assert_eq!(gray.dimensions(), (288, 72));
+    }
+
+    #[test]
+    fn test_thumbnail_caps_height() {
+        let img = DynamicImage::ImageLuma8(GrayImage::from_pixel(400, 400, Luma([0])));
+        let out = thumbnail(&img, 576, 96, &RasterOptions { band_height: 0, ..RasterOptions::default() });
+
+        // 96x96 square: 12 bytes wide, 96 rows, one command
+        assert_eq!(&out[..8], &[GS, 0x76, 0x30, 0, 12, 0, 96, 0]);
+        assert_eq!(out.len(), 8 + 12 * 96);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::config::{ConnectionType, PrinterConfig};
+use crate::rediscovery::host_of;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Configured printers that point at the same device. Two entries for one IP (a DHCP
+/// lease moved to the other printer, or the printer added twice) would print each other's
+/// tickets, so `save_config` and `add_printer` refuse them until they are merged
+/// (`merge`) or one gets another address.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddressConflict {
+    /// Host for network printers, device path / BLE address otherwise
+    pub address: String,
+    /// In config order
+    pub printer_ids: Vec<String>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    #[error("printer {0} is not configured")]
+    UnknownPrinter(String),
+
+    #[error("a printer cannot be merged into itself")]
+    SamePrinter,
+
+    #[error("printers {0} and {1} do not share an address")]
+    NoConflict(String, String),
+}
+
+/// Where a printer is, for comparison: network printers by host (raw and IPP ports of
+/// one printer are the same device), the others by their full address
+fn device_key(printer: &PrinterConfig) -> Option<(u8, String)> {
+    let address = printer.address.trim();
+    if address.is_empty() {
+        return None;
+    }
+    Some(match printer.connection_type {
+        ConnectionType::Network => (0, host_of(address).to_lowercase()),
+        ConnectionType::USB => (1, address.to_lowercase()),
+        ConnectionType::Bluetooth => (2, address.to_lowercase()),
+        ConnectionType::Serial => (3, address.to_lowercase()),
+    })
+}
+
+/// Addresses shared by more than one configured printer
+pub fn find(printers: &[PrinterConfig]) -> Vec<AddressConflict> {
+    let mut groups: Vec<((u8, String), Vec<String>)> = Vec::new();
+    for printer in printers {
+        let Some(key) = device_key(printer) else { continue };
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, ids)) => ids.push(printer.id.clone()),
+            None => groups.push((key, vec![printer.id.clone()])),
+        }
+    }
+    groups
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(|((_, address), printer_ids)| AddressConflict { address, printer_ids })
+        .collect()
+}
+
+/// Fold `duplicate_id` into `keep_id` and remove it. The kept printer keeps its own
+/// settings; it takes over the duplicate's station and learned MAC when it has none,
+/// and stays primary if either was. Returns the removed printer.
+pub fn merge(printers: &mut Vec<PrinterConfig>, keep_id: &str, duplicate_id: &str) -> Result<PrinterConfig, MergeError> {
+    if keep_id == duplicate_id {
+        return Err(MergeError::SamePrinter);
+    }
+    let find = |id: &str| {
+        printers
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or_else(|| MergeError::UnknownPrinter(id.to_string()))
+    };
+    let keep = find(keep_id)?;
+    let duplicate = find(duplicate_id)?;
+    if device_key(&printers[keep]).is_none() || device_key(&printers[keep]) != device_key(&printers[duplicate]) {
+        return Err(MergeError::NoConflict(keep_id.to_string(), duplicate_id.to_string()));
+    }
+
+    let removed = printers.remove(duplicate);
+    let kept = printers.iter_mut().find(|p| p.id == keep_id).expect("kept printer is still configured");
+    if kept.station.is_none() {
+        kept.station = removed.station.clone();
+    }
+    if kept.mac_address.is_none() {
+        kept.mac_address = removed.mac_address.clone();
+    }
+    kept.is_primary |= removed.is_primary;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PrinterCapabilities;
+
+    fn printer(id: &str, connection_type: ConnectionType, address: &str) -> PrinterConfig {
+        PrinterConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            connection_type,
+            address: address.to_string(),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: None,
+            is_primary: false,
+            capabilities: PrinterCapabilities { cutter: true, drawer: false, qrcode: true, max_width: 48 },
+            paper_width_checked: true,
+            recovery: Default::default(),
+            compact: None,
+            transports: vec![],
+            serial: None,
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn test_find_and_merge_shared_address() {
+        let mut printers = vec![
+            PrinterConfig {
+                station: Some("kitchen".to_string()),
+                is_primary: true,
+                ..printer("kitchen", ConnectionType::Network, "192.168.1.50:9100")
+            },
+            printer("bar", ConnectionType::Network, "192.168.1.51:9100"),
+            // Same printer over IPP, added a second time
+            printer("kitchen-ipp", ConnectionType::Network, "192.168.1.50:631"),
+            printer("usb", ConnectionType::USB, "04b8:0202"),
+            printer("new", ConnectionType::Network, ""),
+        ];
+        assert_eq!(
+            find(&printers),
+            vec![AddressConflict {
+                address: "192.168.1.50".to_string(),
+                printer_ids: vec!["kitchen".to_string(), "kitchen-ipp".to_string()],
+            }]
+        );
+
+        assert_eq!(merge(&mut printers, "bar", "kitchen").unwrap_err(), MergeError::NoConflict("bar".to_string(), "kitchen".to_string()));
+        assert_eq!(merge(&mut printers, "bar", "bar").unwrap_err(), MergeError::SamePrinter);
+        assert_eq!(merge(&mut printers, "gone", "bar").unwrap_err(), MergeError::UnknownPrinter("gone".to_string()));
+
+        let removed = merge(&mut printers, "kitchen-ipp", "kitchen").unwrap();
+        assert_eq!(removed.id, "kitchen");
+        let kept = printers.iter().find(|p| p.id == "kitchen-ipp").unwrap();
+        assert_eq!(kept.station.as_deref(), Some("kitchen"));
+        assert!(kept.is_primary);
+        assert_eq!(printers.len(), 4);
+        assert!(find(&printers).is_empty());
+    }
+}
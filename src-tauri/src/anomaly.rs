@@ -0,0 +1,281 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Anomaly detector configuration
+#[derive(Debug, Clone)]
+pub struct AnomalyConfig {
+    /// Number of recent print outcomes compared against the baseline (default: 20)
+    pub window_size: usize,
+    /// Minimum samples in the window before any verdict is made (default: 8)
+    pub min_samples: usize,
+    /// EWMA smoothing factor for the long-term baseline (default: 0.05)
+    pub baseline_alpha: f64,
+    /// Absolute failure rate that is always considered degrading (default: 0.3)
+    pub failure_rate_floor: f64,
+    /// Failure rate must exceed baseline by this margin (default: 0.2)
+    pub failure_rate_margin: f64,
+    /// Recent latency must exceed baseline by this factor (default: 3.0)
+    pub latency_factor: f64,
+    /// Latency below this is never flagged, however large the ratio (default: 2000ms)
+    pub latency_floor_ms: u64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 20,
+            min_samples: 8,
+            baseline_alpha: 0.05,
+            failure_rate_floor: 0.3,
+            failure_rate_margin: 0.2,
+            latency_factor: 3.0,
+            latency_floor_ms: 2000,
+        }
+    }
+}
+
+/// Degradation state transition returned by `AnomalyDetector::record`
+#[derive(Debug, Clone, PartialEq)]
+pub enum DegradationChange {
+    /// Printer started deviating from its baseline
+    Degrading {
+        printer_id: String,
+        reason: String,
+        failure_rate: f64,
+        baseline_failure_rate: f64,
+        avg_latency_ms: u64,
+        baseline_latency_ms: u64,
+    },
+    /// Printer is back within its baseline
+    Recovered { printer_id: String },
+}
+
+/// Per-printer health snapshot (for diagnostics / UI)
+#[derive(Debug, Clone, Serialize)]
+pub struct PrinterHealth {
+    pub printer_id: String,
+    pub degraded: bool,
+    pub samples: usize,
+    pub failure_rate: f64,
+    pub baseline_failure_rate: f64,
+    pub avg_latency_ms: u64,
+    pub baseline_latency_ms: u64,
+}
+
+#[derive(Debug, Default)]
+struct PrinterWindow {
+    /// Recent outcomes: (success, duration_ms)
+    recent: VecDeque<(bool, u64)>,
+    /// Long-term EWMA failure rate (None until first sample)
+    baseline_failure_rate: Option<f64>,
+    /// Long-term EWMA latency of successful prints (None until first success)
+    baseline_latency_ms: Option<f64>,
+    degraded: bool,
+}
+
+impl PrinterWindow {
+    fn failure_rate(&self) -> f64 {
+        if self.recent.is_empty() {
+            return 0.0;
+        }
+        let failures = self.recent.iter().filter(|(ok, _)| !ok).count();
+        failures as f64 / self.recent.len() as f64
+    }
+
+    fn avg_latency_ms(&self) -> u64 {
+        let successes: Vec<u64> = self.recent.iter().filter(|(ok, _)| *ok).map(|(_, d)| *d).collect();
+        if successes.is_empty() {
+            return 0;
+        }
+        successes.iter().sum::<u64>() / successes.len() as u64
+    }
+}
+
+/// Lightweight per-printer anomaly detector.
+///
+/// Compares the recent failure rate and latency of each printer against its own
+/// rolling baseline, so a printer that starts failing intermittently is flagged
+/// as "degraded" well before the circuit breaker trips (5 hard failures).
+pub struct AnomalyDetector {
+    config: AnomalyConfig,
+    windows: Mutex<HashMap<String, PrinterWindow>>,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self {
+            config,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a print outcome. Returns a transition if the printer entered or left
+    /// the degraded state.
+    pub async fn record(&self, printer_id: &str, success: bool, duration_ms: u64) -> Option<DegradationChange> {
+        let cfg = &self.config;
+        let mut windows = self.windows.lock().await;
+        let window = windows.entry(printer_id.to_string()).or_default();
+
+        window.recent.push_back((success, duration_ms));
+
+        // Samples leaving the recent window feed the long-term baseline, so the
+        // baseline always lags behind what we are judging. While degraded the
+        // baseline is frozen, so a bad streak can't become the new normal.
+        if window.recent.len() > cfg.window_size {
+            if let Some((old_ok, old_ms)) = window.recent.pop_front() {
+                if !window.degraded {
+                    let outcome = if old_ok { 0.0 } else { 1.0 };
+                    window.baseline_failure_rate = Some(match window.baseline_failure_rate {
+                        Some(b) => b + cfg.baseline_alpha * (outcome - b),
+                        None => outcome,
+                    });
+                    if old_ok {
+                        window.baseline_latency_ms = Some(match window.baseline_latency_ms {
+                            Some(b) => b + cfg.baseline_alpha * (old_ms as f64 - b),
+                            None => old_ms as f64,
+                        });
+                    }
+                }
+            }
+        }
+
+        // No verdict until we have both a baseline and enough recent samples
+        if window.baseline_failure_rate.is_none() || window.recent.len() < cfg.min_samples {
+            return None;
+        }
+
+        let failure_rate = window.failure_rate();
+        let avg_latency = window.avg_latency_ms();
+        let baseline_failure = window.baseline_failure_rate.unwrap_or(0.0);
+        let baseline_latency = window.baseline_latency_ms.unwrap_or(0.0);
+
+        let failure_anomaly = failure_rate >= cfg.failure_rate_floor
+            && failure_rate >= baseline_failure + cfg.failure_rate_margin;
+        let latency_anomaly = baseline_latency > 0.0
+            && avg_latency >= cfg.latency_floor_ms
+            && avg_latency as f64 >= baseline_latency * cfg.latency_factor;
+
+        if !window.degraded && (failure_anomaly || latency_anomaly) {
+            window.degraded = true;
+            let reason = if failure_anomaly {
+                format!(
+                    "failure rate {:.0}% vs baseline {:.0}%",
+                    failure_rate * 100.0,
+                    baseline_failure * 100.0
+                )
+            } else {
+                format!("latency {}ms vs baseline {:.0}ms", avg_latency, baseline_latency)
+            };
+            warn!("Printer {} degrading: {}", printer_id, reason);
+            return Some(DegradationChange::Degrading {
+                printer_id: printer_id.to_string(),
+                reason,
+                failure_rate,
+                baseline_failure_rate: baseline_failure,
+                avg_latency_ms: avg_latency,
+                baseline_latency_ms: baseline_latency as u64,
+            });
+        }
+
+        // Recover with some hysteresis: half the thresholds, so we don't flap
+        let recovered = failure_rate < (cfg.failure_rate_floor / 2.0).max(baseline_failure + cfg.failure_rate_margin / 2.0)
+            && (baseline_latency <= 0.0
+                || (avg_latency as f64) < baseline_latency * (cfg.latency_factor / 2.0).max(1.0)
+                || avg_latency < cfg.latency_floor_ms);
+
+        if window.degraded && recovered {
+            window.degraded = false;
+            info!("Printer {} no longer degraded", printer_id);
+            return Some(DegradationChange::Recovered {
+                printer_id: printer_id.to_string(),
+            });
+        }
+
+        None
+    }
+
+    /// Snapshot of all tracked printers
+    pub async fn snapshot(&self) -> Vec<PrinterHealth> {
+        let windows = self.windows.lock().await;
+        windows
+            .iter()
+            .map(|(id, w)| PrinterHealth {
+                printer_id: id.clone(),
+                degraded: w.degraded,
+                samples: w.recent.len(),
+                failure_rate: w.failure_rate(),
+                baseline_failure_rate: w.baseline_failure_rate.unwrap_or(0.0),
+                avg_latency_ms: w.avg_latency_ms(),
+                baseline_latency_ms: w.baseline_latency_ms.unwrap_or(0.0) as u64,
+            })
+            .collect()
+    }
+
+    /// Forget all history for a printer (e.g. after manual reset or removal)
+    pub async fn reset(&self, printer_id: &str) {
+        self.windows.lock().await.remove(printer_id);
+    }
+}
+
+impl Default for AnomalyDetector {
+    fn default() -> Self {
+        Self::new(AnomalyConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failure_spike_flags_degrading() {
+        let detector = AnomalyDetector::default();
+
+        // Healthy baseline
+        for _ in 0..30 {
+            assert!(detector.record("p1", true, 200).await.is_none());
+        }
+
+        // Intermittent failures (not enough to trip the circuit breaker)
+        let mut change = None;
+        for i in 0..20 {
+            if let Some(c) = detector.record("p1", i % 2 == 0, 200).await {
+                change = Some(c);
+                break;
+            }
+        }
+
+        assert!(matches!(change, Some(DegradationChange::Degrading { .. })));
+        assert!(detector.snapshot().await[0].degraded);
+    }
+
+    #[tokio::test]
+    async fn test_latency_spike_and_recovery() {
+        let detector = AnomalyDetector::default();
+
+        for _ in 0..30 {
+            detector.record("p1", true, 300).await;
+        }
+
+        let mut degraded = false;
+        for _ in 0..20 {
+            if let Some(DegradationChange::Degrading { .. }) = detector.record("p1", true, 5000).await {
+                degraded = true;
+                break;
+            }
+        }
+        assert!(degraded);
+
+        let mut recovered = false;
+        for _ in 0..20 {
+            if let Some(DegradationChange::Recovered { .. }) = detector.record("p1", true, 300).await {
+                recovered = true;
+                break;
+            }
+        }
+        assert!(recovered);
+        assert!(!detector.snapshot().await[0].degraded);
+    }
+}
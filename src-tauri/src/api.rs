@@ -1,16 +1,28 @@
 use crate::auth::{JWTManager, PrinterClaims};
+use crate::config::AppConfig;
 use crate::errors::{DaemonError, Result};
+use crate::escpos;
+use crate::job_cancel::JobCancellation;
+use crate::job_encryption::{self, EnvelopeError};
+use crate::pickup_slip::PickupSlip;
+use crate::printer_admin;
+use crate::request_signing::{self, NonceCache};
 use crate::status;
-use crate::queue::{PrintJob, QueueManager};
+use crate::status_page;
+use crate::queue::{
+    correlation_id_for, IdempotentJob, JobKind, JobSearch, JobSearchResult, PrintJob, QueueHealth, QueueManager,
+};
 use crate::telemetry::TelemetryCollector;
 use axum::{
-    extract::{Json, State},
-    http::{HeaderMap, StatusCode},
+    body::Bytes,
+    extract::{Json, Path, Query, RawQuery, State},
+    http::{HeaderMap, Method, StatusCode},
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{any, get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tower::ServiceBuilder;
@@ -18,10 +30,14 @@ use tower_http::cors::{AllowOrigin, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::{debug, error, info, warn};
 
+/// Address the local API listens on
+pub const API_ADDR: &str = "127.0.0.1:8043";
+
 /// HTTP API server state
 #[derive(Clone)]
 pub struct ApiState {
-    pub queue_manager: Arc<Mutex<QueueManager>>,
+    pub config: Arc<Mutex<AppConfig>>,
+    pub queue_manager: QueueManager,
     pub telemetry: Arc<TelemetryCollector>,
     pub jwt_manager: Arc<JWTManager>,
     pub restaurant_id: String,
@@ -29,6 +45,10 @@ pub struct ApiState {
     pub supabase_connected: Arc<std::sync::atomic::AtomicBool>,
     /// Daemon start time for uptime calculation
     pub start_time: std::time::Instant,
+    /// Nonces of signed print requests (replay protection)
+    pub nonces: Arc<std::sync::Mutex<NonceCache>>,
+    /// The job processor's cancellation, to stop a running job that is cancelled
+    pub job_cancel: JobCancellation,
 }
 
 /// Print request payload
@@ -43,6 +63,39 @@ pub struct PrintRequest {
     pub customer_name: Option<String>,
     pub order_type: Option<String>,
     pub priority: Option<u8>,
+    /// Copies printed in one send (default 1)
+    pub copies: Option<u8>,
+    /// The POS's trace ID for this order (generated when missing)
+    pub correlation_id: Option<String>,
+    /// When the customer collects the order (Unix ms), for `{pickup_time}` on receipts
+    pub pickup_time: Option<i64>,
+    /// "void" or "amend" for a follow-up to a ticket already printed (default "order")
+    #[serde(default)]
+    pub kind: JobKind,
+    /// Job id the follow-up refers to (the `job_id` answered for the original); required
+    /// for void and amend
+    pub original_job_id: Option<String>,
+    /// Amend: items taken off the order; `items` are the additions. A void with no items
+    /// strikes everything on the original ticket.
+    #[serde(default)]
+    pub removed_items: Vec<PrintItemRequest>,
+}
+
+/// Pull request of a pull-mode station (POST /api/print-next)
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PrintNextRequest {
+    pub restaurant_id: String,
+    pub station: String,
+}
+
+/// Pickup slip request payload (POST /api/pickup-slip)
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PickupSlipRequest {
+    pub restaurant_id: String,
+    /// Same id as the order's print jobs; defaults to today's order number
+    pub order_id: Option<String>,
+    pub order_number: String,
+    pub customer_name: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -51,12 +104,30 @@ pub struct PrintItemRequest {
     pub name: String,
     pub modifiers: Vec<String>,
     pub notes: Option<String>,
+    pub image_url: Option<String>,
+    /// Prices and VAT, for customer receipts
+    pub pricing: Option<crate::escpos::LinePricing>,
+}
+
+impl From<PrintItemRequest> for escpos::PrintItem {
+    fn from(item: PrintItemRequest) -> Self {
+        escpos::PrintItem {
+            quantity: item.quantity,
+            name: item.name,
+            modifiers: item.modifiers,
+            notes: item.notes,
+            image_url: item.image_url,
+            pricing: item.pricing,
+        }
+    }
 }
 
 /// Print response
 #[derive(Debug, Serialize)]
 pub struct PrintResponse {
     pub job_id: String,
+    /// Printed in the ticket footer (first 8 characters); quote it to support
+    pub correlation_id: String,
     pub status: String,
     pub message: String,
 }
@@ -72,6 +143,8 @@ pub struct HealthResponse {
     pub supabase_connected: bool,
     /// Operational mode: "online" (Supabase connected) or "offline" (local-only)
     pub mode: String,
+    /// Local queue depth and saturation ("normal" / "elevated" / "saturated")
+    pub queue: Option<QueueHealth>,
 }
 
 /// Error response
@@ -103,6 +176,31 @@ impl IntoResponse for DaemonError {
     }
 }
 
+fn reject(status: StatusCode, error: &str, details: Option<String>) -> Response {
+    (
+        status,
+        Json(ErrorResponse {
+            error: error.to_string(),
+            details,
+        }),
+    )
+        .into_response()
+}
+
+/// Why a void or amend request can't be printed, if it can't
+fn follow_up_problem(request: &PrintRequest) -> Option<String> {
+    match request.kind {
+        JobKind::Order => None,
+        kind if request.original_job_id.as_deref().is_none_or(|id| id.trim().is_empty()) => {
+            Some(format!("A {} ticket needs the original_job_id it follows up on", kind.as_str()))
+        }
+        JobKind::Amend if request.items.is_empty() && request.removed_items.is_empty() => {
+            Some("An amend ticket needs added items or removed_items".to_string())
+        }
+        _ => None,
+    }
+}
+
 /// Extract and validate JWT from Authorization header
 async fn extract_claims(headers: &HeaderMap, jwt_manager: &JWTManager) -> Result<PrinterClaims> {
     let auth_header = headers
@@ -117,16 +215,59 @@ async fn extract_claims(headers: &HeaderMap, jwt_manager: &JWTManager) -> Result
 }
 
 /// POST /api/print - Submit print job
+///
+/// Besides the JWT, requests may be signed per client (`request_signing.rs`) and carry an
+/// `Idempotency-Key`: a retry with the same key gets the first request's job back instead
+/// of printing again. A replayed nonce is refused unless its idempotency key is known.
+/// The body may be encrypted with the daemon's public key (`job_encryption.rs`); the
+/// signature covers the body as sent, the idempotency check the decrypted request.
 async fn handle_print(
     State(state): State<ApiState>,
     headers: HeaderMap,
-    Json(request): Json<PrintRequest>,
-) -> Result<Json<PrintResponse>> {
-    debug!("Print request received for order: {}", request.order_number);
-
+    body: Bytes,
+) -> Result<Response> {
     // Validate JWT and permissions
     let claims = extract_claims(&headers, &state.jwt_manager).await?;
 
+    let (signing, encryption) = {
+        let config = state.config.lock().await;
+        (config.api_signing.clone(), config.api_encryption.clone())
+    };
+    let now = chrono::Utc::now().timestamp();
+    let signed = match request_signing::verify(&signing, &headers, &body, now) {
+        Ok(signed) => signed,
+        Err(e) => {
+            warn!("Print request refused: {}", e);
+            return Ok(reject(StatusCode::UNAUTHORIZED, "Invalid request signature", Some(e.to_string())));
+        }
+    };
+    let idempotency_key = match request_signing::idempotency_key(&headers, signed.as_ref()) {
+        Ok(key) => key,
+        Err(e) => return Ok(reject(StatusCode::BAD_REQUEST, "Invalid idempotency key", Some(e.to_string()))),
+    };
+
+    let opened = match job_encryption::open_body(&encryption, &body) {
+        Ok(opened) => opened,
+        Err(e) => {
+            warn!("Print request refused: {}", e);
+            let status = match e {
+                EnvelopeError::KeyUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+                _ => StatusCode::BAD_REQUEST,
+            };
+            return Ok(reject(status, "Invalid encrypted request", Some(e.to_string())));
+        }
+    };
+    let plain: &[u8] = opened.as_deref().unwrap_or(&body);
+
+    let request: PrintRequest = match serde_json::from_slice(plain) {
+        Ok(request) => request,
+        Err(e) => return Ok(reject(StatusCode::BAD_REQUEST, "Invalid print request", Some(e.to_string()))),
+    };
+    debug!("Print request received for order: {}", request.order_number);
+    if let Some(problem) = follow_up_problem(&request) {
+        return Ok(reject(StatusCode::BAD_REQUEST, "Invalid print request", Some(problem)));
+    }
+
     // Validate restaurant ID matches token
     if claims.restaurant_id != request.restaurant_id {
         error!(
@@ -154,21 +295,64 @@ async fn handle_print(
     let job_id = uuid::Uuid::new_v4().to_string();
     let timestamp = chrono::Utc::now().timestamp_millis();
 
-    let items = request
-        .items
-        .into_iter()
-        .map(|item| crate::escpos::PrintItem {
-            quantity: item.quantity,
-            name: item.name,
-            modifiers: item.modifiers,
-            notes: item.notes,
-        })
-        .collect();
+    let items = request.items.into_iter().map(escpos::PrintItem::from).collect();
+
+    let queue = &state.queue_manager;
+    let correlation_id = correlation_id_for(request.correlation_id.as_deref().unwrap_or_default());
+
+    // Claimed before anything else, so a concurrent retry sees this request's job
+    if let Some(key) = &idempotency_key {
+        let claim = IdempotentJob {
+            job_id: job_id.clone(),
+            correlation_id: correlation_id.clone(),
+            body_hash: request_signing::body_hash(plain),
+        };
+        if let Some(existing) = queue.claim_idempotency_key(key, &claim).await? {
+            if existing.body_hash != claim.body_hash {
+                warn!("Idempotency key {} reused with a different request", key);
+                return Ok(reject(
+                    StatusCode::CONFLICT,
+                    "Idempotency key was used for a different request",
+                    Some(format!("job {}", existing.job_id)),
+                ));
+            }
+            info!("Retry of print request {} answered with job {}", key, existing.job_id);
+            return Ok(Json(PrintResponse {
+                job_id: existing.job_id.clone(),
+                correlation_id: existing.correlation_id,
+                status: "duplicate".to_string(),
+                message: format!("Already queued as job {}", existing.job_id),
+            })
+            .into_response());
+        }
+    }
+    if let Some(signed) = &signed {
+        let fresh = state
+            .nonces
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .check(signed, now, signing.max_skew_secs);
+        if !fresh {
+            warn!("Replayed print request from client {} refused", signed.client_id);
+            release_claim(queue, idempotency_key.as_deref(), &job_id).await;
+            return Ok(reject(
+                StatusCode::UNAUTHORIZED,
+                "Invalid request signature",
+                Some(request_signing::SigningError::Replayed.to_string()),
+            ));
+        }
+    }
+
+    // POS systems that only send the daily order number: scope it to the business date, so
+    // a double submit is deduplicated but tomorrow's #42 isn't mistaken for today's
+    let order_id = request
+        .order_id
+        .or_else(|| Some(format!("local:{}:{}", queue.business_date(), request.order_number)));
 
     let print_job = PrintJob {
         id: job_id.clone(),
         restaurant_id: request.restaurant_id,
-        order_id: request.order_id,
+        order_id,
         order_number: request.order_number.clone(),
         station: request.station,
         station_id: None, // HTTP API jobs don't carry station_id (resolved by poller)
@@ -182,22 +366,119 @@ async fn handle_print(
         status: status::PENDING.to_string(),
         retry_count: 0,
         error_message: None,
+        copies: request.copies.unwrap_or(1).clamp(1, escpos::MAX_COPIES),
+        correlation_id: correlation_id.clone(),
+        pickup_time: request.pickup_time,
+        kind: request.kind,
+        original_job_id: request.original_job_id,
+        removed_items: request.removed_items.into_iter().map(escpos::PrintItem::from).collect(),
+        order_stations: vec![],
     };
 
     // Enqueue job
-    let queue = state.queue_manager.lock().await;
-    queue.enqueue(print_job).await?;
+    if let Err(e) = queue.enqueue(print_job).await {
+        release_claim(queue, idempotency_key.as_deref(), &job_id).await;
+        return Err(e);
+    }
 
     info!(
-        "Print job enqueued via HTTP API: {} (order: {})",
-        job_id, request.order_number
+        "Print job enqueued via HTTP API: {} (order: {}, correlation: {})",
+        job_id, request.order_number, correlation_id
     );
 
     Ok(Json(PrintResponse {
         job_id,
+        correlation_id,
         status: "queued".to_string(),
         message: format!("Print job queued for order {}", request.order_number),
-    }))
+    })
+    .into_response())
+}
+
+/// POST /api/print-next - Print the next waiting ticket of a pull-mode station
+///
+/// For bump bars and kitchen displays. 404 when nothing waits, 409 for a station that
+/// isn't in pull mode.
+async fn handle_print_next(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Json(request): Json<PrintNextRequest>,
+) -> Result<Response> {
+    let claims = extract_claims(&headers, &state.jwt_manager).await?;
+    if claims.restaurant_id != request.restaurant_id || request.restaurant_id != state.restaurant_id {
+        return Ok(reject(StatusCode::FORBIDDEN, "Restaurant ID mismatch", None));
+    }
+
+    let queue = &state.queue_manager;
+    if !queue.is_pull_station(&request.station) {
+        return Ok(reject(StatusCode::CONFLICT, "Station is not in pull mode", Some(request.station)));
+    }
+    match queue.print_next(&request.station).await? {
+        Some(ticket) => {
+            info!("Station {} pulled order {} via HTTP API", request.station, ticket.order_number);
+            Ok(Json(ticket).into_response())
+        }
+        None => Ok(reject(StatusCode::NOT_FOUND, "No tickets waiting", Some(request.station))),
+    }
+}
+
+/// POST /api/jobs/:job_id/cancel - Cancel a job that hasn't printed yet
+///
+/// 409 for unknown jobs and jobs that already printed or failed. A job running when it is
+/// cancelled stops before its ticket goes out, unless the ticket is already being sent.
+async fn handle_cancel_job(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(job_id): Path<String>,
+) -> Result<Response> {
+    let claims = extract_claims(&headers, &state.jwt_manager).await?;
+    if claims.restaurant_id != state.restaurant_id {
+        return Ok(reject(StatusCode::FORBIDDEN, "Restaurant ID mismatch", None));
+    }
+
+    if !crate::commands::cancel_job(&state.config, &state.queue_manager, &state.job_cancel, &job_id).await? {
+        return Ok(reject(StatusCode::CONFLICT, "Job can't be cancelled", Some(format!("{} is unknown or finished", job_id))));
+    }
+    info!("Print job {} cancelled via HTTP API", job_id);
+    Ok(Json(serde_json::json!({ "status": "cancelled", "job_id": job_id })).into_response())
+}
+
+/// POST /api/pickup-slip - Print an "order ready" slip
+///
+/// The slip is queued and printed on the slip printer within a few seconds; asking again
+/// for the same order prints it again.
+async fn handle_pickup_slip(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Json(request): Json<PickupSlipRequest>,
+) -> Result<Response> {
+    let claims = extract_claims(&headers, &state.jwt_manager).await?;
+    if claims.restaurant_id != request.restaurant_id || request.restaurant_id != state.restaurant_id {
+        return Ok(reject(StatusCode::FORBIDDEN, "Restaurant ID mismatch", None));
+    }
+
+    let queue = &state.queue_manager;
+    let slip = PickupSlip {
+        order_id: request
+            .order_id
+            .unwrap_or_else(|| format!("local:{}:{}", queue.business_date(), request.order_number)),
+        order_number: request.order_number,
+        customer_name: request.customer_name,
+    };
+    queue.request_pickup_slip(&slip).await?;
+    info!("Pickup slip queued via HTTP API for order {}", slip.order_number);
+
+    Ok(Json(serde_json::json!({ "status": "queued", "order_id": slip.order_id })).into_response())
+}
+
+/// Free an idempotency key claimed for a job that was not queued, so the client's retry
+/// isn't answered with a job that doesn't exist
+async fn release_claim(queue: &QueueManager, key: Option<&str>, job_id: &str) {
+    if let Some(key) = key {
+        if let Err(e) = queue.release_idempotency_key(key, job_id).await {
+            warn!("Failed to release idempotency key {}: {}", key, e);
+        }
+    }
 }
 
 /// GET /api/health - Health check endpoint
@@ -209,6 +490,7 @@ async fn handle_health(State(state): State<ApiState>) -> Json<HealthResponse> {
     let uptime_secs = state.start_time.elapsed().as_secs();
     let supabase_connected = state.supabase_connected.load(std::sync::atomic::Ordering::Relaxed);
     let mode = if supabase_connected { "online" } else { "offline" };
+    let queue = state.queue_manager.get_health().await.ok();
 
     Json(HealthResponse {
         status: "ok".to_string(),
@@ -217,6 +499,7 @@ async fn handle_health(State(state): State<ApiState>) -> Json<HealthResponse> {
         restaurant_id: state.restaurant_id.clone(),
         supabase_connected,
         mode: mode.to_string(),
+        queue,
     })
 }
 
@@ -228,13 +511,42 @@ async fn handle_queue_stats(
     // Validate JWT (requires 'status' permission)
     let _claims = extract_claims(&headers, &state.jwt_manager).await?;
 
-    let queue = state.queue_manager.lock().await;
+    let queue = &state.queue_manager;
     let stats = queue.get_stats().await?;
 
     Ok(Json(stats))
 }
 
-/// GET /api/metrics - Telemetry metrics (Prometheus format)
+/// GET /api/jobs - Find jobs for front-of-house ("did table 12's order print?")
+///
+/// Query: `table_number`, `customer_name` (prefix) and/or `order_number`, optional
+/// `since` / `until` (Unix seconds, default the last 6 hours).
+async fn handle_find_jobs(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Query(search): Query<JobSearch>,
+) -> Result<Response> {
+    let _claims = extract_claims(&headers, &state.jwt_manager).await?;
+
+    if search_is_empty(&search) {
+        return Ok(reject(
+            StatusCode::BAD_REQUEST,
+            "Search needs table_number, customer_name or order_number",
+            None,
+        ));
+    }
+    let jobs: Vec<JobSearchResult> = state.queue_manager.find_jobs(&search).await?;
+    Ok(Json(jobs).into_response())
+}
+
+fn search_is_empty(search: &JobSearch) -> bool {
+    [&search.table_number, &search.customer_name, &search.order_number]
+        .iter()
+        .all(|v| v.as_deref().map_or(true, |v| v.trim().is_empty()))
+}
+
+/// GET /metrics (and /api/metrics) - Telemetry metrics in Prometheus text format, for
+/// scraping by the restaurant's monitoring
 async fn handle_metrics(State(state): State<ApiState>) -> String {
     state.telemetry.export_prometheus().await
 }
@@ -251,6 +563,35 @@ async fn handle_metrics_json(
     Ok(Json(metrics))
 }
 
+/// ANY /printer-admin/{printer_id}/{token}/{path} - Printer web UI (Epson/Star config pages)
+///
+/// Opened in the system browser from the dashboard, so it can't carry a JWT; the session
+/// token from `open_printer_admin` in the path stands in for it. The printer pages are
+/// reachable on the LAN without the daemon anyway; the proxy only saves staff from
+/// looking up the IP, and only for network printers in the config.
+async fn handle_printer_admin(
+    State(state): State<ApiState>,
+    Path(params): Path<HashMap<String, String>>,
+    RawQuery(query): RawQuery,
+    method: Method,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let printer_id = params.get("printer_id").cloned().unwrap_or_default();
+    let token = params.get("token").map(String::as_str).unwrap_or("");
+    let path = params.get("path").map(String::as_str).unwrap_or("");
+    if !printer_admin::token_valid(&printer_id, token) {
+        return (StatusCode::FORBIDDEN, "Link expired - open the printer page again from the dashboard").into_response();
+    }
+
+    let base_url = printer_admin::printer_base_url(&*state.config.lock().await, &printer_id);
+    let Some(base_url) = base_url else {
+        return DaemonError::PrinterNotFound(format!("No network printer {}", printer_id)).into_response();
+    };
+
+    printer_admin::proxy(&base_url, &printer_id, token, path, query.as_deref(), method, &headers, body).await
+}
+
 /// DNS rebinding defense: reject requests with unexpected Host headers
 async fn validate_host(
     headers: HeaderMap,
@@ -274,10 +615,19 @@ async fn validate_host(
 pub fn create_router(state: ApiState) -> Router {
     Router::new()
         .route("/api/print", post(handle_print))
+        .route("/api/pickup-slip", post(handle_pickup_slip))
+        .route("/api/print-next", post(handle_print_next))
         .route("/api/health", get(handle_health))
         .route("/api/queue/stats", get(handle_queue_stats))
+        .route("/api/jobs", get(handle_find_jobs))
+        .route("/api/jobs/:job_id/cancel", post(handle_cancel_job))
+        .route("/metrics", get(handle_metrics))
         .route("/api/metrics", get(handle_metrics))
         .route("/api/metrics/json", get(handle_metrics_json))
+        .route("/status", get(status_page::handle_status))
+        .route("/printer-admin/:printer_id/:token", any(handle_printer_admin))
+        .route("/printer-admin/:printer_id/:token/", any(handle_printer_admin))
+        .route("/printer-admin/:printer_id/:token/*path", any(handle_printer_admin))
         .layer(axum::middleware::from_fn(validate_host))
         .layer(
             ServiceBuilder::new()
@@ -307,10 +657,142 @@ pub fn create_router(state: ApiState) -> Router {
         .with_state(state)
 }
 
-/// Start HTTP API server
+/// Local API lifecycle, for diagnostics
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApiServerStatus {
+    pub running: bool,
+    pub address: String,
+    pub restaurant_id: Option<String>,
+    /// RFC 3339
+    pub started_at: Option<String>,
+    /// Why the server stopped on its own (e.g. port already in use)
+    pub last_error: Option<String>,
+}
+
+struct RunningServer {
+    restaurant_id: String,
+    shutdown: tokio::sync::watch::Sender<bool>,
+    tasks: Vec<tokio::task::JoinHandle<()>>,
+}
+
+/// How long a stop waits for in-flight requests before cutting them off
+const SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Runs the local API (and the LAN status page, if enabled) while the daemon is paired.
+///
+/// `sync` is called whenever the pairing may have changed (stored config loaded, config
+/// saved): pairing starts the server, a factory reset stops it, re-pairing to another
+/// restaurant restarts it.
+pub struct ApiServer {
+    addr: String,
+    /// State shared by every run; `restaurant_id` is filled in per run
+    base: ApiState,
+    running: Mutex<Option<RunningServer>>,
+    status: Arc<std::sync::Mutex<ApiServerStatus>>,
+}
+
+impl ApiServer {
+    pub fn new(addr: &str, base: ApiState) -> Self {
+        Self {
+            addr: addr.to_string(),
+            base,
+            running: Mutex::new(None),
+            status: Arc::new(std::sync::Mutex::new(ApiServerStatus {
+                address: addr.to_string(),
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Start, stop or restart the server to match the paired restaurant
+    pub async fn sync(&self, restaurant_id: Option<&str>) {
+        let mut running = self.running.lock().await;
+        if running.as_ref().map(|r| r.restaurant_id.as_str()) == restaurant_id {
+            return;
+        }
+
+        if let Some(previous) = running.take() {
+            info!("Stopping HTTP API server (restaurant {})", previous.restaurant_id);
+            let _ = previous.shutdown.send(true);
+            for mut task in previous.tasks {
+                // Wait for the listener to be dropped so the port can be bound again
+                if tokio::time::timeout(SHUTDOWN_GRACE, &mut task).await.is_err() {
+                    task.abort();
+                    let _ = task.await;
+                }
+            }
+            let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+            status.running = false;
+            status.restaurant_id = None;
+            status.started_at = None;
+        }
+
+        let Some(restaurant_id) = restaurant_id else {
+            return;
+        };
+
+        let state = ApiState {
+            restaurant_id: restaurant_id.to_string(),
+            ..self.base.clone()
+        };
+        *self.status.lock().unwrap_or_else(|e| e.into_inner()) = ApiServerStatus {
+            running: true,
+            address: self.addr.clone(),
+            restaurant_id: Some(restaurant_id.to_string()),
+            started_at: Some(chrono::Utc::now().to_rfc3339()),
+            last_error: None,
+        };
+
+        let (shutdown, shutdown_rx) = tokio::sync::watch::channel(false);
+        let stopped = move || {
+            let mut rx = shutdown_rx.clone();
+            async move {
+                let _ = rx.changed().await;
+            }
+        };
+        let mut tasks = Vec::new();
+
+        // Venue status page on the LAN (opt-in; /status on the local API is always there)
+        let status_page = state.config.lock().await.status_page.clone();
+        if status_page.enabled {
+            let status_state = state.clone();
+            let stop = stopped();
+            tasks.push(tokio::spawn(async move {
+                if let Err(e) = status_page::start_status_server(&status_page.bind_address, status_state, stop).await {
+                    error!("Failed to start status page on {}: {}", status_page.bind_address, e);
+                }
+            }));
+        }
+
+        let addr = self.addr.clone();
+        let status = self.status.clone();
+        let stop = stopped();
+        tasks.push(tokio::spawn(async move {
+            if let Err(e) = start_api_server(&addr, state, stop).await {
+                error!("Failed to start HTTP API server: {}", e);
+                let mut status = status.lock().unwrap_or_else(|e| e.into_inner());
+                status.running = false;
+                status.last_error = Some(e.to_string());
+            }
+        }));
+
+        *running = Some(RunningServer {
+            restaurant_id: restaurant_id.to_string(),
+            shutdown,
+            tasks,
+        });
+    }
+
+    pub fn status(&self) -> ApiServerStatus {
+        self.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Start HTTP API server; runs until `shutdown` resolves
 pub async fn start_api_server(
     addr: &str,
     state: ApiState,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
 ) -> std::result::Result<(), Box<dyn std::error::Error>> {
     let router = create_router(state.clone());
 
@@ -319,6 +801,7 @@ pub async fn start_api_server(
     let listener = tokio::net::TcpListener::bind(addr).await?;
 
     axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown)
         .await
         .map_err(|e| {
             error!("HTTP API server error: {}", e);
@@ -336,19 +819,20 @@ mod tests {
     use tower::ServiceExt;
 
     async fn create_test_state() -> ApiState {
-        let queue_manager = Arc::new(Mutex::new(
-            QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap(),
-        ));
+        let queue_manager = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
         let telemetry = Arc::new(TelemetryCollector::new());
         let jwt_manager = Arc::new(JWTManager::new("test_secret_key_1234567890".to_string()));
 
         ApiState {
+            config: Arc::new(Mutex::new(AppConfig::default())),
             queue_manager,
             telemetry,
             jwt_manager,
             restaurant_id: "rest_123".to_string(),
             supabase_connected: Arc::new(std::sync::atomic::AtomicBool::new(false)),
             start_time: std::time::Instant::now(),
+            nonces: Arc::new(std::sync::Mutex::new(NonceCache::default())),
+            job_cancel: JobCancellation::new(),
         }
     }
 
@@ -379,6 +863,61 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_status_page() {
+        let state = create_test_state().await;
+        let app = create_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/status")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("All printers OK"));
+    }
+
+    #[tokio::test]
+    async fn test_printer_admin_unknown_printer() {
+        let state = create_test_state().await;
+        let app = create_router(state);
+        let token = printer_admin::issue_token("missing");
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/printer-admin/missing/{}/", token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_printer_admin_requires_session_token() {
+        let state = create_test_state().await;
+        let app = create_router(state);
+        let other = printer_admin::issue_token("other-printer");
+
+        for uri in ["/printer-admin/p1/guessed/".to_string(), format!("/printer-admin/p1/{}/cgi-bin/set", other)] {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().method("POST").uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+    }
+
     #[tokio::test]
     async fn test_print_endpoint_requires_auth() {
         let state = create_test_state().await;
@@ -394,6 +933,12 @@ mod tests {
             customer_name: None,
             order_type: None,
             priority: None,
+            copies: None,
+            correlation_id: None,
+            pickup_time: None,
+            kind: JobKind::Order,
+            original_job_id: None,
+            removed_items: vec![],
         };
 
         let response = app
@@ -412,6 +957,38 @@ mod tests {
         assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    #[tokio::test]
+    async fn test_pickup_slip_endpoint_queues_slip() {
+        let state = create_test_state().await;
+        let token = create_test_token(&state).await;
+        let queue = state.queue_manager.clone();
+        let app = create_router(state);
+
+        let request = PickupSlipRequest {
+            restaurant_id: "rest_123".to_string(),
+            order_id: Some("order_1".to_string()),
+            order_number: "R001-0001".to_string(),
+            customer_name: Some("Sam".to_string()),
+        };
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/pickup-slip")
+                    .header("content-type", "application/json")
+                    .header("authorization", format!("Bearer {}", token))
+                    .body(Body::from(serde_json::to_string(&request).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let slips = queue.pending_pickup_slips(10).await.unwrap();
+        assert_eq!(slips.len(), 1);
+        assert_eq!(slips[0].customer_name.as_deref(), Some("Sam"));
+    }
+
     #[tokio::test]
     async fn test_print_endpoint_with_valid_token() {
         let state = create_test_state().await;
@@ -428,11 +1005,19 @@ mod tests {
                 name: "Beer".to_string(),
                 modifiers: vec![],
                 notes: None,
+                image_url: None,
+                pricing: None,
             }],
             table_number: Some("5".to_string()),
             customer_name: None,
             order_type: Some("dine-in".to_string()),
             priority: Some(3),
+            copies: None,
+            correlation_id: None,
+            pickup_time: None,
+            kind: JobKind::Order,
+            original_job_id: None,
+            removed_items: vec![],
         };
 
         let response = app
@@ -450,4 +1035,120 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[tokio::test]
+    async fn test_signed_print_idempotent_and_replay_protected() {
+        use crate::request_signing::*;
+
+        let state = create_test_state().await;
+        state.config.lock().await.api_signing = ApiSigningConfig {
+            required: true,
+            clients: vec![ApiClient {
+                id: "pos-1".to_string(),
+                name: "Counter".to_string(),
+                secret: "s3cret".to_string(),
+            }],
+            ..ApiSigningConfig::default()
+        };
+        let token = create_test_token(&state).await;
+        let queue = state.queue_manager.clone();
+        let app = create_router(state);
+
+        let body = serde_json::json!({
+            "restaurant_id": "rest_123",
+            "station": "bar",
+            "order_number": "42",
+            "items": [{ "quantity": 1, "name": "Beer", "modifiers": [] }],
+        })
+        .to_string();
+        let send = |nonce: &str, idempotency_key: Option<&str>, body: &str| {
+            let timestamp = chrono::Utc::now().timestamp();
+            let mut request = Request::builder()
+                .method("POST")
+                .uri("/api/print")
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .header(CLIENT_HEADER, "pos-1")
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(NONCE_HEADER, nonce)
+                .header(SIGNATURE_HEADER, sign("s3cret", timestamp, nonce, body.as_bytes()));
+            if let Some(key) = idempotency_key {
+                request = request.header(IDEMPOTENCY_HEADER, key);
+            }
+            app.clone().oneshot(request.body(Body::from(body.to_string())).unwrap())
+        };
+        let json = |response: Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+
+        let first = send("n-1", Some("order-42"), &body).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        let first = json(first).await;
+
+        // Lost response, same request retried: answered with the first job
+        let retry = send("n-1", Some("order-42"), &body).await.unwrap();
+        assert_eq!(retry.status(), StatusCode::OK);
+        let retry = json(retry).await;
+        assert_eq!(retry["status"], "duplicate");
+        assert_eq!(retry["job_id"], first["job_id"]);
+        assert_eq!(queue.get_pending_jobs(10).await.unwrap().len(), 1);
+
+        let other = body.replace("Beer", "Wine");
+        assert_eq!(send("n-2", Some("order-42"), &other).await.unwrap().status(), StatusCode::CONFLICT);
+
+        // Replayed nonce without a known idempotency key
+        assert_eq!(send("n-1", None, &body).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+
+        // Unsigned request while signing is required
+        let unsigned = Request::builder()
+            .method("POST")
+            .uri("/api/print")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::from(body.clone()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(unsigned).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_api_server_follows_pairing() {
+        let addr = {
+            let probe = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            probe.local_addr().unwrap().to_string()
+        };
+        let server = ApiServer::new(&addr, create_test_state().await);
+        let client = reqwest::Client::new();
+        // Host header as the real API sees it (DNS rebinding guard)
+        let get_health = || {
+            client
+                .get(format!("http://{}/api/health", addr))
+                .header("host", "127.0.0.1:8043")
+                .send()
+        };
+
+        // Fresh install: nothing listening
+        server.sync(None).await;
+        assert!(!server.status().running);
+        assert!(get_health().await.is_err());
+
+        // Pairing completes
+        server.sync(Some("rest_123")).await;
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(get_health().await.unwrap().status().is_success());
+        assert_eq!(server.status().restaurant_id.as_deref(), Some("rest_123"));
+
+        // Factory reset
+        server.sync(None).await;
+        assert!(!server.status().running);
+        assert!(get_health().await.is_err());
+
+        // Port taken by something else: reported, not silently missing
+        let _squatter = tokio::net::TcpListener::bind(&addr).await.unwrap();
+        server.sync(Some("rest_123")).await;
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let status = server.status();
+        assert!(!status.running);
+        assert!(status.last_error.is_some());
+    }
 }
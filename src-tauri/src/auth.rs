@@ -67,6 +67,23 @@ impl PrinterClaims {
     }
 }
 
+/// Claims of a signed order deep link (status / feedback page QR codes on receipts).
+/// Short: the link is printed as a QR code, and the web page only needs to know which
+/// order it is for and until when.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderLinkClaims {
+    /// Restaurant ID
+    pub restaurant_id: String,
+    /// Order ID
+    pub order_id: String,
+    /// Page the link opens: "status" or "feedback"
+    pub purpose: String,
+    /// Issued at (Unix timestamp)
+    pub iat: u64,
+    /// Expires at (Unix timestamp), the end of the business day
+    pub exp: u64,
+}
+
 /// JWT Token Manager for printer service authentication
 pub struct JWTManager {
     /// Secret key for signing/verifying tokens
@@ -171,6 +188,31 @@ impl JWTManager {
 
         Ok(auth_header.trim_start_matches("Bearer ").to_string())
     }
+
+    /// Sign an order deep link token
+    pub fn generate_order_link(&self, claims: &OrderLinkClaims) -> Result<String> {
+        encode(
+            &Header::default(),
+            claims,
+            &EncodingKey::from_secret(self.secret.as_bytes()),
+        )
+        .map_err(|e| DaemonError::Other(anyhow::anyhow!("Failed to sign order link: {}", e)))
+    }
+
+    /// Validate and decode an order deep link token (expired links are refused)
+    pub fn validate_order_link(&self, token: &str) -> Result<OrderLinkClaims> {
+        let mut validation = Validation::default();
+        validation.validate_exp = true;
+        validation.leeway = 0;
+
+        decode::<OrderLinkClaims>(
+            token,
+            &DecodingKey::from_secret(self.secret.as_bytes()),
+            &validation,
+        )
+        .map(|data| data.claims)
+        .map_err(|e| DaemonError::Other(anyhow::anyhow!("Invalid order link: {}", e)))
+    }
 }
 
 /// Token rotation handler for graceful token updates
@@ -298,4 +340,27 @@ mod tests {
         // Should fail without Bearer prefix
         assert!(JWTManager::extract_bearer_token(token).is_err());
     }
+
+    #[test]
+    fn test_order_link_round_trip() {
+        let manager = JWTManager::new("link_secret_1234567890".to_string());
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let claims = OrderLinkClaims {
+            restaurant_id: "rest_123".to_string(),
+            order_id: "order_42".to_string(),
+            purpose: "status".to_string(),
+            iat: now,
+            exp: now + 3600,
+        };
+
+        let token = manager.generate_order_link(&claims).unwrap();
+        assert_eq!(manager.validate_order_link(&token).unwrap(), claims);
+        // Other secret, or not an order link
+        assert!(JWTManager::new("other".to_string()).validate_order_link(&token).is_err());
+        assert!(manager.validate_token(&token).is_err());
+
+        let expired = OrderLinkClaims { iat: now - 7200, exp: now - 60, ..claims };
+        let token = manager.generate_order_link(&expired).unwrap();
+        assert!(manager.validate_order_link(&token).is_err());
+    }
 }
@@ -0,0 +1,353 @@
+use crate::errors::{DaemonError, Result};
+use btleplug::api::{BDAddr, Central, CentralEvent, Manager as _, Peripheral as _, ScanFilter};
+use btleplug::platform::{Adapter, Manager, Peripheral};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+/// A scan checks this often whether a BLE print started (and it should stop)
+const SCAN_SLICE: Duration = Duration::from_millis(250);
+
+/// How long a discovery scan waits for BLE prints to finish before it is skipped
+const DISCOVERY_WAIT: Duration = Duration::from_secs(30);
+
+/// Scan window when a print can't find its printer among the known peripherals
+const LOCATE_WINDOW: Duration = Duration::from_secs(3);
+
+/// Write sizes tried on a printer, largest first: the payload of a 247-byte ATT MTU (data
+/// length extension), of the 185-byte MTU many printers offer, 100, and the 20 bytes the
+/// minimum MTU of 23 always carries
+const CHUNK_SIZES: [usize; 4] = [244, 182, 100, 20];
+
+/// The write size to try after `size` failed; the minimum stays the minimum
+pub fn smaller_chunk(size: usize) -> usize {
+    CHUNK_SIZES.iter().copied().find(|&s| s < size).unwrap_or(MIN_CHUNK)
+}
+
+/// Smallest write size, safe on every BLE link
+pub const MIN_CHUNK: usize = CHUNK_SIZES[CHUNK_SIZES.len() - 1];
+
+static COORDINATOR: Lazy<BleCoordinator> = Lazy::new(BleCoordinator::default);
+
+/// The process-wide coordinator, shared by discovery, printing and online checks
+pub fn coordinator() -> &'static BleCoordinator {
+    &COORDINATOR
+}
+
+/// What a scan is for (diagnostics)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanPurpose {
+    Discovery,
+    /// A print looking for its printer
+    Locate,
+    /// Online check of a configured printer
+    Presence,
+}
+
+/// Bluetooth adapter state for diagnostics (`get_ble_status`, support bundle)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BleStatus {
+    /// Adapter in use, as the platform describes it; None until first use or after a failure
+    pub adapter: Option<String>,
+    /// Last adapter-level failure (no adapter, scan refused); cleared once it works again
+    pub last_error: Option<String>,
+    pub prints_in_flight: usize,
+    pub scanning: Option<ScanPurpose>,
+    pub last_discovery_at: Option<DateTime<Utc>>,
+    /// Discovery scans skipped because BLE prints kept running
+    pub discovery_skipped: u64,
+    /// Discovery scans stopped early for a BLE print
+    pub discovery_cut_short: u64,
+    /// Write size that last worked per printer address; printers not listed start at the
+    /// largest size
+    pub chunk_sizes: BTreeMap<String, usize>,
+}
+
+/// Shares one Bluetooth adapter between discovery, printing and online checks, and keeps
+/// scans out of the way of BLE prints. Scanning and connecting on the same radio slow
+/// each other down (a 10s discovery scan would stall a ticket), so:
+///
+/// - scans run one at a time
+/// - discovery and online checks only scan while no BLE print is in flight; a discovery
+///   scan that is running when a print starts stops at once with what it found so far
+/// - a print first looks among the peripherals the adapter already knows and only scans
+///   (briefly) when its printer isn't there
+///
+/// The adapter is opened on first use and dropped when it fails, so an adapter that was
+/// unplugged or restarted is picked up again on the next use.
+#[derive(Default)]
+pub struct BleCoordinator {
+    /// Kept with the adapter: the platform adapter doesn't outlive its manager everywhere
+    adapter: Mutex<Option<(Manager, Adapter)>>,
+    scan_lock: Mutex<()>,
+    prints: AtomicUsize,
+    status: std::sync::Mutex<BleStatus>,
+}
+
+/// Held for the whole of a BLE print; scans yield while any is held
+pub struct PrintLease<'a> {
+    coordinator: &'a BleCoordinator,
+}
+
+impl Drop for PrintLease<'_> {
+    fn drop(&mut self) {
+        self.coordinator.prints.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl BleCoordinator {
+    fn update_status(&self, update: impl FnOnce(&mut BleStatus)) {
+        update(&mut self.status.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    pub fn status(&self) -> BleStatus {
+        let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        status.prints_in_flight = self.prints_in_flight();
+        status
+    }
+
+    /// Write size to start a print to `address` with: the one that worked last time
+    pub fn chunk_size(&self, address: &str) -> usize {
+        let status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+        status.chunk_sizes.get(&address.to_ascii_uppercase()).copied().unwrap_or(CHUNK_SIZES[0])
+    }
+
+    /// Remember the write size a whole print to `address` went out with
+    pub fn chunk_size_worked(&self, address: &str, size: usize) {
+        let address = address.to_ascii_uppercase();
+        let previous = self.status.lock().unwrap_or_else(|e| e.into_inner()).chunk_sizes.insert(address.clone(), size);
+        if previous != Some(size) {
+            info!("BLE printer {} takes {}B writes", address, size);
+        }
+    }
+
+    pub fn prints_in_flight(&self) -> usize {
+        self.prints.load(Ordering::SeqCst)
+    }
+
+    /// Announce a BLE print; hold the lease until it is done
+    pub fn print_lease(&self) -> PrintLease<'_> {
+        self.prints.fetch_add(1, Ordering::SeqCst);
+        PrintLease { coordinator: self }
+    }
+
+    /// The shared adapter, opened on first use
+    async fn adapter(&self) -> Result<Adapter> {
+        let mut cached = self.adapter.lock().await;
+        if let Some((_, adapter)) = cached.as_ref() {
+            return Ok(adapter.clone());
+        }
+
+        let opened = async {
+            let manager = Manager::new()
+                .await
+                .map_err(|e| format!("Failed to create BLE manager: {}", e))?;
+            let adapter = manager
+                .adapters()
+                .await
+                .map_err(|e| format!("Failed to get BLE adapters: {}", e))?
+                .into_iter()
+                .next()
+                .ok_or_else(|| "No Bluetooth adapters found".to_string())?;
+            Ok::<_, String>((manager, adapter))
+        }
+        .await;
+
+        match opened {
+            Ok((manager, adapter)) => {
+                let info = adapter.adapter_info().await.unwrap_or_else(|_| "unknown adapter".to_string());
+                info!("Bluetooth adapter opened: {}", info);
+                self.update_status(|s| {
+                    s.adapter = Some(info);
+                    s.last_error = None;
+                });
+                *cached = Some((manager, adapter.clone()));
+                Ok(adapter)
+            }
+            Err(e) => Err(self.adapter_failed(e)),
+        }
+    }
+
+    /// Record an adapter-level failure and drop the adapter so the next use opens it again
+    fn adapter_failed(&self, error: String) -> DaemonError {
+        warn!("Bluetooth adapter: {}", error);
+        if let Ok(mut cached) = self.adapter.try_lock() {
+            *cached = None;
+        }
+        self.update_status(|s| {
+            s.adapter = None;
+            s.last_error = Some(error.clone());
+        });
+        DaemonError::Bluetooth(error)
+    }
+
+    /// Scan for `window` (less when `yield_to_prints` and a print starts). Returns the
+    /// peripherals seen advertising during the scan and whether it was cut short.
+    async fn scan(
+        &self,
+        adapter: &Adapter,
+        purpose: ScanPurpose,
+        window: Duration,
+        yield_to_prints: bool,
+    ) -> Result<(Vec<Peripheral>, bool)> {
+        let _scan = self.scan_lock.lock().await;
+        if yield_to_prints && self.prints_in_flight() > 0 {
+            return Ok((Vec::new(), true));
+        }
+
+        let mut events = adapter
+            .events()
+            .await
+            .map_err(|e| self.adapter_failed(format!("Failed to watch BLE events: {}", e)))?;
+        adapter
+            .start_scan(ScanFilter::default())
+            .await
+            .map_err(|e| self.adapter_failed(format!("Failed to start BLE scan: {}", e)))?;
+        self.update_status(|s| s.scanning = Some(purpose));
+
+        let deadline = Instant::now() + window;
+        let mut seen = HashSet::new();
+        let mut cut_short = false;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            if yield_to_prints && self.prints_in_flight() > 0 {
+                cut_short = true;
+                break;
+            }
+            match tokio::time::timeout(remaining.min(SCAN_SLICE), events.next()).await {
+                Ok(Some(CentralEvent::DeviceDiscovered(id) | CentralEvent::DeviceUpdated(id))) => {
+                    seen.insert(id);
+                }
+                Ok(Some(_)) | Err(_) => {}
+                // No more events on this platform: wait out the window
+                Ok(None) => tokio::time::sleep(remaining.min(SCAN_SLICE)).await,
+            }
+        }
+
+        adapter.stop_scan().await.ok(); // best-effort stop
+        self.update_status(|s| s.scanning = None);
+
+        let mut found = Vec::new();
+        for id in seen {
+            if let Ok(peripheral) = adapter.peripheral(&id).await {
+                found.push(peripheral);
+            }
+        }
+        Ok((found, cut_short))
+    }
+
+    /// Scan for nearby printers (discovery). Waits for running BLE prints to finish first
+    /// and stops early when a print starts during the scan.
+    #[cfg_attr(target_os = "linux", allow(dead_code))] // BLE discovery is off on Linux
+    pub async fn discover(&self, window: Duration) -> Result<Vec<Peripheral>> {
+        let adapter = self.adapter().await?;
+
+        let waiting_since = Instant::now();
+        while self.prints_in_flight() > 0 {
+            if waiting_since.elapsed() >= DISCOVERY_WAIT {
+                self.update_status(|s| s.discovery_skipped += 1);
+                return Err(DaemonError::Bluetooth("BLE printers busy printing, scan skipped".to_string()));
+            }
+            tokio::time::sleep(SCAN_SLICE).await;
+        }
+
+        let (found, cut_short) = self.scan(&adapter, ScanPurpose::Discovery, window, true).await?;
+        if cut_short {
+            info!("Bluetooth scan stopped early for a BLE print ({} peripherals seen)", found.len());
+        }
+        self.update_status(|s| {
+            s.last_discovery_at = Some(Utc::now());
+            if cut_short {
+                s.discovery_cut_short += 1;
+            }
+        });
+        Ok(found)
+    }
+
+    /// The peripheral to print to: a known one, or found with a short scan. Call with a
+    /// print lease held.
+    pub async fn locate(&self, address: &str) -> Result<Peripheral> {
+        let adapter = self.adapter().await?;
+        let known = adapter
+            .peripherals()
+            .await
+            .map_err(|e| DaemonError::Bluetooth(format!("Failed to list peripherals: {}", e)))?;
+        if let Some(peripheral) = find_address(known, address) {
+            debug!("BLE peripheral {} already known, no scan needed", address);
+            return Ok(peripheral);
+        }
+
+        let (found, _) = self.scan(&adapter, ScanPurpose::Locate, LOCATE_WINDOW, false).await?;
+        find_address(found, address)
+            .ok_or_else(|| DaemonError::Bluetooth(format!("Peripheral not found: {}", address)))
+    }
+
+    /// Whether a printer is advertising (online check). While BLE prints run no scan is
+    /// started (a connected printer doesn't advertise anyway); a known printer counts
+    /// as there.
+    pub async fn is_advertising(&self, address: &str, window: Duration) -> Result<bool> {
+        let adapter = self.adapter().await?;
+        if self.prints_in_flight() > 0 {
+            let known = adapter.peripherals().await.unwrap_or_default();
+            return Ok(find_address(known, address).is_some());
+        }
+        let (found, _) = self.scan(&adapter, ScanPurpose::Presence, window, true).await?;
+        Ok(find_address(found, address).is_some())
+    }
+}
+
+/// The peripheral with this address (as printed by `BDAddr`, case-insensitive)
+fn find_address(peripherals: Vec<Peripheral>, address: &str) -> Option<Peripheral> {
+    let wanted = address.parse::<BDAddr>().ok();
+    peripherals.into_iter().find(|p| match wanted {
+        Some(wanted) => p.address() == wanted,
+        None => p.address().to_string().eq_ignore_ascii_case(address),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_print_leases_show_in_status() {
+        let coordinator = BleCoordinator::default();
+        assert_eq!(coordinator.status().prints_in_flight, 0);
+
+        let first = coordinator.print_lease();
+        let second = coordinator.print_lease();
+        assert_eq!(coordinator.prints_in_flight(), 2);
+        drop(first);
+        assert_eq!(coordinator.status().prints_in_flight, 1);
+        drop(second);
+
+        let status = coordinator.status();
+        assert_eq!(status.prints_in_flight, 0);
+        assert!(status.adapter.is_none() && status.scanning.is_none());
+        assert_eq!(serde_json::to_value(ScanPurpose::Presence).unwrap(), "presence");
+    }
+
+    #[test]
+    fn test_chunk_size_steps_down_and_is_remembered() {
+        let coordinator = BleCoordinator::default();
+        assert_eq!(coordinator.chunk_size("aa:bb:cc:dd:ee:ff"), 244);
+        assert_eq!(smaller_chunk(244), 182);
+        assert_eq!(smaller_chunk(182), 100);
+        assert_eq!(smaller_chunk(MIN_CHUNK), MIN_CHUNK);
+
+        coordinator.chunk_size_worked("aa:bb:cc:dd:ee:ff", 182);
+        assert_eq!(coordinator.chunk_size("AA:BB:CC:DD:EE:FF"), 182);
+        assert_eq!(coordinator.chunk_size("11:22:33:44:55:66"), 244);
+        assert_eq!(coordinator.status().chunk_sizes.get("AA:BB:CC:DD:EE:FF"), Some(&182));
+    }
+}
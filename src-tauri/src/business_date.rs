@@ -0,0 +1,58 @@
+use crate::venue_time;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+/// Default hour (venue time) at which a new business day starts. Orders before 05:00
+/// belong to the previous day's service.
+pub const DEFAULT_ROLLOVER_HOUR: u8 = 5;
+
+/// Business date of a moment: the calendar date, minus one day before the rollover hour
+pub fn business_date<Tz: TimeZone>(at: &DateTime<Tz>, rollover_hour: u8) -> NaiveDate {
+    (at.naive_local() - Duration::hours(rollover_hour.min(23) as i64)).date()
+}
+
+/// Business date (YYYY-MM-DD) of a Unix timestamp in the venue's time zone
+pub fn for_unix_secs(unix_secs: i64, rollover_hour: u8) -> String {
+    let at = venue_time::at_secs(unix_secs).unwrap_or_else(|| DateTime::<Utc>::default().with_timezone(&venue_time::zone()));
+    business_date(&at, rollover_hour).format("%Y-%m-%d").to_string()
+}
+
+/// When the business day of a moment ends: the next rollover hour in the venue's local
+/// time. None only when that local time doesn't exist (a DST gap at the rollover hour).
+pub fn day_end<Tz: TimeZone>(at: &DateTime<Tz>, rollover_hour: u8) -> Option<DateTime<Tz>> {
+    let next = business_date(at, rollover_hour)
+        .succ_opt()?
+        .and_hms_opt(rollover_hour.min(23) as u32, 0, 0)?;
+    at.timezone().from_local_datetime(&next).earliest()
+}
+
+/// Parse a YYYY-MM-DD business date
+pub fn parse(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn test_late_night_orders_belong_to_previous_day() {
+        let amsterdam = FixedOffset::east_opt(3600).unwrap();
+        let at = |d: u32, h: u32| amsterdam.with_ymd_and_hms(2026, 3, d, h, 30, 0).unwrap();
+        let date = |d: u32| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+
+        assert_eq!(business_date(&at(14, 23), 5), date(14));
+        assert_eq!(business_date(&at(15, 1), 5), date(14));
+        assert_eq!(business_date(&at(15, 4), 5), date(14));
+        assert_eq!(business_date(&at(15, 5), 5), date(15));
+
+        // Rollover at midnight = calendar date
+        assert_eq!(business_date(&at(15, 1), 0), date(15));
+        assert_eq!(parse("2026-03-14"), Some(date(14)));
+
+        let rollover = |d: u32| amsterdam.with_ymd_and_hms(2026, 3, d, 5, 0, 0).unwrap();
+        assert_eq!(day_end(&at(14, 23), 5), Some(rollover(15)));
+        assert_eq!(day_end(&at(15, 1), 5), Some(rollover(15)));
+        assert_eq!(day_end(&at(15, 5), 5), Some(rollover(16)));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::clock::{self, SharedClock};
 use crate::errors::Result;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -12,6 +13,30 @@ pub enum CircuitState {
     HalfOpen, // Testing if service recovered
 }
 
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// A breaker state change, sent to the history channel for persistence
+#[derive(Debug, Clone)]
+pub struct BreakerTransition {
+    pub printer_id: String,
+    pub from: CircuitState,
+    pub to: CircuitState,
+    /// "failure_threshold", "timeout_elapsed", "probe_succeeded" or the reset reason
+    pub reason: String,
+    /// Last error when the breaker opened
+    pub detail: Option<String>,
+    /// Time spent in `from`
+    pub duration_ms: u64,
+}
+
 /// Per-printer circuit breaker configuration
 #[derive(Debug, Clone)]
 pub struct CircuitBreakerConfig {
@@ -40,11 +65,16 @@ pub struct CircuitBreaker {
     state: Arc<Mutex<CircuitBreakerState>>,
     /// Optional channel to emit printer status changes (printer_id, status)
     status_tx: Option<tokio::sync::watch::Sender<(String, String)>>,
+    /// Optional channel receiving every state transition (breaker history)
+    history_tx: Option<tokio::sync::mpsc::UnboundedSender<BreakerTransition>>,
+    clock: SharedClock,
 }
 
 #[derive(Debug)]
 struct CircuitBreakerState {
     current_state: CircuitState,
+    /// When `current_state` was entered
+    state_since: Instant,
     failure_timestamps: Vec<Instant>,
     last_failure_time: Option<Instant>,
     total_failures: u64,
@@ -59,6 +89,7 @@ impl CircuitBreaker {
             config,
             state: Arc::new(Mutex::new(CircuitBreakerState {
                 current_state: CircuitState::Closed,
+                state_since: Instant::now(),
                 failure_timestamps: Vec::new(),
                 last_failure_time: None,
                 total_failures: 0,
@@ -66,6 +97,8 @@ impl CircuitBreaker {
                 recovery_count: 0,
             })),
             status_tx: None,
+            history_tx: None,
+            clock: clock::system(),
         }
     }
 
@@ -80,6 +113,7 @@ impl CircuitBreaker {
             config,
             state: Arc::new(Mutex::new(CircuitBreakerState {
                 current_state: CircuitState::Closed,
+                state_since: Instant::now(),
                 failure_timestamps: Vec::new(),
                 last_failure_time: None,
                 total_failures: 0,
@@ -87,6 +121,43 @@ impl CircuitBreaker {
                 recovery_count: 0,
             })),
             status_tx: Some(status_tx),
+            history_tx: None,
+            clock: clock::system(),
+        }
+    }
+
+    /// Use a different time source (tests advance a `TestClock` instead of sleeping)
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        if let Ok(mut state) = self.state.try_lock() {
+            state.state_since = clock.now();
+        }
+        self.clock = clock;
+        self
+    }
+
+    /// Send every state transition to `history_tx`
+    pub fn with_history(mut self, history_tx: tokio::sync::mpsc::UnboundedSender<BreakerTransition>) -> Self {
+        self.history_tx = Some(history_tx);
+        self
+    }
+
+    /// Move to `to`, reporting the time spent in the previous state
+    fn transition(&self, state: &mut CircuitBreakerState, to: CircuitState, reason: &str, detail: Option<String>) {
+        let now = self.clock.now();
+        let from = std::mem::replace(&mut state.current_state, to.clone());
+        let duration_ms = now.duration_since(state.state_since).as_millis() as u64;
+        state.state_since = now;
+
+        if let Some(ref tx) = self.history_tx {
+            let _ = tx.send(BreakerTransition {
+                printer_id: self.printer_id.clone(),
+                from,
+                to,
+                reason: reason.to_string(),
+                detail,
+                duration_ms,
+            });
         }
     }
 
@@ -98,10 +169,10 @@ impl CircuitBreaker {
     }
 
     /// Execute a print operation with circuit breaker protection
-    pub async fn execute<F, Fut>(&self, operation: F) -> Result<()>
+    pub async fn execute<F, Fut, T>(&self, operation: F) -> Result<T>
     where
         F: FnOnce() -> Fut,
-        Fut: std::future::Future<Output = Result<()>>,
+        Fut: std::future::Future<Output = Result<T>>,
     {
         let mut state = self.state.lock().await;
 
@@ -109,10 +180,10 @@ impl CircuitBreaker {
         if state.current_state == CircuitState::Open {
             // Check if timeout has passed
             if let Some(last_failure) = state.last_failure_time {
-                if last_failure.elapsed() >= self.config.timeout {
+                if self.clock.now().duration_since(last_failure) >= self.config.timeout {
                     // Transition to HALF_OPEN state for testing
                     info!("Circuit breaker for printer {} transitioning to HALF_OPEN (testing recovery)", self.printer_id);
-                    state.current_state = CircuitState::HalfOpen;
+                    self.transition(&mut state, CircuitState::HalfOpen, "timeout_elapsed", None);
                 } else {
                     // Circuit still open, reject request
                     return Err(crate::errors::DaemonError::PrintJob(
@@ -132,21 +203,23 @@ impl CircuitBreaker {
         let mut state = self.state.lock().await;
 
         match result {
-            Ok(_) => {
+            Ok(value) => {
                 // Success - reset or close circuit
                 if state.current_state == CircuitState::HalfOpen {
                     // Recovery successful!
                     info!("Circuit breaker for printer {} recovered - transitioning to CLOSED", self.printer_id);
-                    state.current_state = CircuitState::Closed;
+                    self.transition(&mut state, CircuitState::Closed, "probe_succeeded", None);
                     state.failure_timestamps.clear();
                     state.recovery_count += 1;
                     self.emit_status("online");
                 }
-                Ok(())
+                Ok(value)
             }
+            // Stopped before printing: says nothing about the printer
+            Err(e @ crate::errors::DaemonError::Interrupted(_)) => Err(e),
             Err(e) => {
                 // Failure - record and check threshold
-                let now = Instant::now();
+                let now = self.clock.now();
                 state.failure_timestamps.push(now);
                 state.last_failure_time = Some(now);
                 state.total_failures += 1;
@@ -170,7 +243,7 @@ impl CircuitBreaker {
                             "Circuit breaker OPEN for printer {} after {} failures",
                             self.printer_id, state.failure_timestamps.len()
                         );
-                        state.current_state = CircuitState::Open;
+                        self.transition(&mut state, CircuitState::Open, "failure_threshold", Some(e.to_string()));
                         state.circuit_open_count += 1;
                         self.emit_status("error");
                     }
@@ -195,11 +268,13 @@ impl CircuitBreaker {
         }
     }
 
-    /// Manually reset circuit breaker (admin function)
-    pub async fn reset(&self) {
+    /// Reset circuit breaker (admin action, printer recovered, ...); `reason` goes into the history
+    pub async fn reset(&self, reason: &str) {
         let mut state = self.state.lock().await;
-        info!("Manually resetting circuit breaker for printer {}", self.printer_id);
-        state.current_state = CircuitState::Closed;
+        info!("Resetting circuit breaker for printer {} ({})", self.printer_id, reason);
+        if state.current_state != CircuitState::Closed {
+            self.transition(&mut state, CircuitState::Closed, reason, None);
+        }
         state.failure_timestamps.clear();
         state.last_failure_time = None;
     }
@@ -225,11 +300,7 @@ impl serde::Serialize for CircuitState {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(match self {
-            CircuitState::Closed => "closed",
-            CircuitState::Open => "open",
-            CircuitState::HalfOpen => "half_open",
-        })
+        serializer.serialize_str(self.as_str())
     }
 }
 
@@ -248,7 +319,7 @@ mod tests {
         // Simulate 3 failures
         for i in 0..3 {
             let result = cb.execute(|| async {
-                Err(crate::errors::DaemonError::PrintJob(format!("Test failure {}", i)))
+                Err::<(), _>(crate::errors::DaemonError::PrintJob(format!("Test failure {}", i)))
             }).await;
             assert!(result.is_err());
         }
@@ -268,17 +339,22 @@ mod tests {
         config.failure_threshold = 2;
         config.timeout = Duration::from_millis(50);
 
-        let cb = CircuitBreaker::new("test_printer".to_string(), config);
+        let test_clock = crate::clock::TestClock::new();
+        let cb = CircuitBreaker::new("test_printer".to_string(), config).with_clock(test_clock.clone());
 
         // Trigger failures to open circuit
         for _ in 0..2 {
             let _ = cb.execute(|| async {
-                Err(crate::errors::DaemonError::PrintJob("Test failure".to_string()))
+                Err::<(), _>(crate::errors::DaemonError::PrintJob("Test failure".to_string()))
             }).await;
         }
 
-        // Wait for timeout
-        tokio::time::sleep(Duration::from_millis(60)).await;
+        // Still open just before the timeout
+        test_clock.advance(Duration::from_millis(40));
+        assert!(cb.execute(|| async { Ok(()) }).await.is_err());
+
+        // Past the timeout
+        test_clock.advance(Duration::from_millis(20));
 
         // Next request should test recovery (HALF_OPEN)
         let result = cb.execute(|| async { Ok(()) }).await;
@@ -288,4 +364,38 @@ mod tests {
         let status = cb.get_status().await;
         assert_eq!(status.state, CircuitState::Closed);
     }
+
+    #[tokio::test]
+    async fn test_transitions_are_reported_with_durations() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            timeout: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let test_clock = crate::clock::TestClock::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let cb = CircuitBreaker::new("p1".to_string(), config)
+            .with_clock(test_clock.clone())
+            .with_history(tx);
+
+        test_clock.advance(Duration::from_secs(600));
+        let _ = cb.execute(|| async {
+            Err::<(), _>(crate::errors::DaemonError::PrintJob("paper jam".to_string()))
+        }).await;
+        test_clock.advance(Duration::from_secs(90));
+        cb.execute(|| async { Ok(()) }).await.unwrap();
+        cb.reset("manual_reset").await; // already closed: no transition
+
+        let open = rx.try_recv().unwrap();
+        assert_eq!((open.from, open.to), (CircuitState::Closed, CircuitState::Open));
+        assert_eq!(open.reason, "failure_threshold");
+        assert_eq!(open.duration_ms, 600_000);
+        assert!(open.detail.unwrap().contains("paper jam"));
+
+        let half_open = rx.try_recv().unwrap();
+        assert_eq!((half_open.reason.as_str(), half_open.duration_ms), ("timeout_elapsed", 90_000));
+        let closed = rx.try_recv().unwrap();
+        assert_eq!((closed.to, closed.reason.as_str()), (CircuitState::Closed, "probe_succeeded"));
+        assert!(rx.try_recv().is_err());
+    }
 }
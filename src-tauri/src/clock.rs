@@ -0,0 +1,67 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Time source for retry backoff, priority aging, deduplication windows and breaker timing.
+///
+/// Production code uses `SystemClock`; tests inject `TestClock` and advance it instantly
+/// instead of sleeping.
+pub trait Clock: Send + Sync {
+    /// Monotonic time, for measuring durations
+    fn now(&self) -> Instant;
+    /// Wall-clock seconds since the Unix epoch, for timestamps stored in the queue
+    fn unix_secs(&self) -> i64;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The real clock
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn unix_secs(&self) -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+}
+
+pub fn system() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// Manually advanced clock for tests
+#[cfg(test)]
+pub struct TestClock {
+    start: Instant,
+    start_unix: i64,
+    offset: std::sync::Mutex<std::time::Duration>,
+}
+
+#[cfg(test)]
+impl TestClock {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            start: Instant::now(),
+            start_unix: chrono::Utc::now().timestamp(),
+            offset: std::sync::Mutex::new(std::time::Duration::ZERO),
+        })
+    }
+
+    pub fn advance(&self, by: std::time::Duration) {
+        *self.offset.lock().unwrap() += by;
+    }
+}
+
+#[cfg(test)]
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        self.start + *self.offset.lock().unwrap()
+    }
+
+    fn unix_secs(&self) -> i64 {
+        self.start_unix + self.offset.lock().unwrap().as_secs() as i64
+    }
+}
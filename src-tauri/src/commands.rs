@@ -0,0 +1,546 @@
+use crate::config::{self, AppConfig, PrinterConfig};
+use crate::errors::Result;
+use crate::job_cancel::JobCancellation;
+use crate::pickup_slip::PickupSlip;
+use crate::printer::PrinterManager;
+use crate::queue::{JobHistoryEntry, QueueManager};
+use crate::stations::StationMap;
+use crate::supabase_client::{PairingResult, SupabaseClient};
+use crate::{address_conflicts, config_seal, escpos, printer_names, status, CircuitBreakerRegistry};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+// Logic of the Tauri commands that change printers and jobs, as plain functions over the
+// `AppState` pieces they use. The `#[tauri::command]` wrappers in main.rs only unpack the
+// state; tests call these with their own queue, printer manager and config store.
+
+/// Longest operator note accepted on a job
+pub const MAX_NOTE_LEN: usize = 500;
+
+/// Where commands save the config: the Tauri store in the app, memory in tests
+pub trait ConfigStore {
+    fn save(&self, config: &AppConfig) -> std::result::Result<(), String>;
+}
+
+impl<R: tauri::Runtime> ConfigStore for tauri::AppHandle<R> {
+    fn save(&self, config: &AppConfig) -> std::result::Result<(), String> {
+        let store = self.store("config.json").map_err(|e| e.to_string())?;
+        store.set("config", config_seal::seal(config).map_err(|e| e.to_string())?);
+        store.save().map_err(|e| e.to_string())
+    }
+}
+
+/// Save a claimed pairing (`claim_pairing_code`). The code is single-use, so the result is
+/// persisted right away instead of by the wizard's later save_config, which never runs if
+/// the app is closed in between; `get_pairing_state` then resumes the wizard. The token
+/// goes to the keychain through `store_token`. When that fails it is kept in config.json
+/// and moved to the keychain on the next start. Returns the restaurant id.
+pub async fn record_pairing(
+    config: &Mutex<AppConfig>,
+    store: &impl ConfigStore,
+    result: &PairingResult,
+    store_token: impl FnOnce(&str) -> std::result::Result<(), String>,
+) -> std::result::Result<Option<String>, String> {
+    let token_in_keychain = match store_token(&result.token) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("Keychain unavailable, storing pairing token in config: {}", e);
+            false
+        }
+    };
+
+    let mut config = config.lock().await;
+    config.auth_token = Some(result.token.clone());
+    config.restaurant_id = Some(result.restaurant_id.clone());
+    config.pairing = Some(config::PairingRecord {
+        restaurant_code: result.restaurant_code.clone(),
+        paired_at: chrono::Utc::now().to_rfc3339(),
+        setup_completed_at: None,
+    });
+
+    let mut config_for_store = config.clone();
+    if token_in_keychain {
+        config_for_store.auth_token = None;
+    }
+    store.save(&config_for_store)?;
+    info!("Pairing persisted for restaurant {}", result.restaurant_code);
+    Ok(config.restaurant_id.clone())
+}
+
+/// Add a printer to the config (`add_printer`). The name must be valid and unused, and the
+/// address not taken by another printer.
+pub async fn add_printer(
+    config: &Mutex<AppConfig>,
+    printer_manager: &PrinterManager,
+    queue: &QueueManager,
+    store: &impl ConfigStore,
+    mut printer: PrinterConfig,
+) -> std::result::Result<(), String> {
+    info!("Adding printer: {} ({})", printer.name, printer.id);
+    printer.name = printer_names::validate(&printer.name).map_err(|e| e.to_string())?;
+
+    let mut config = config.lock().await;
+    let mut printers = config.printers.clone();
+    printers.push(printer.clone());
+    printer_names::check_unique(&printers, Some(&printer.id)).map_err(|e| e.to_string())?;
+    if let Some(conflict) = address_conflicts::find(&printers).into_iter().next() {
+        return Err(format!(
+            "Address {} is already used by printer {}; merge them or use another address",
+            conflict.address,
+            conflict.printer_ids.join(", ")
+        ));
+    }
+
+    printer_manager.add_printer(printer.clone()).await;
+
+    config.printers.push(printer);
+    queue.set_stations(StationMap::new(&config.printers, &config.station_aliases));
+    queue.set_paused_printers(&config.paused_printers()).await;
+    store.save(&config)
+}
+
+/// Rename a printer (`rename_printer`). The name must be valid and not used by another
+/// printer; the new name is pushed to Supabase right away so the dashboard and POS show it
+/// too.
+pub async fn rename_printer(
+    config: &Mutex<AppConfig>,
+    printer_manager: &PrinterManager,
+    queue: &QueueManager,
+    store: &impl ConfigStore,
+    printer_id: &str,
+    name: &str,
+) -> std::result::Result<String, String> {
+    let name = printer_names::validate(name).map_err(|e| e.to_string())?;
+
+    let mut config = config.lock().await;
+    let mut printers = config.printers.clone();
+    let printer = printers
+        .iter_mut()
+        .find(|p| p.id == printer_id)
+        .ok_or_else(|| format!("Printer {} not found", printer_id))?;
+    let old_name = std::mem::replace(&mut printer.name, name.clone());
+    let renamed = printer.clone();
+    printer_names::check_unique(&printers, Some(printer_id)).map_err(|e| e.to_string())?;
+    if old_name == name {
+        return Ok(name);
+    }
+    info!("Renaming printer {}: '{}' → '{}'", printer_id, old_name, name);
+
+    config.printers = printers;
+    printer_manager.add_printer(renamed.clone()).await;
+
+    let mut config_for_store = config.clone();
+    config_for_store.auth_token = None;
+    store.save(&config_for_store)?;
+
+    let supabase = crate::create_supabase_client_from_config(&config);
+    let restaurant_id = config.restaurant_id.clone();
+    drop(config);
+
+    if let (Some(client), Some(restaurant_id)) = (supabase, restaurant_id) {
+        let row = crate::printer_upsert(&renamed, &restaurant_id, "online", &chrono::Utc::now().to_rfc3339());
+        if let Err(e) = client.upsert_printers(vec![row]).await {
+            // Saved locally; the next registration (save, rediscovery) carries the name
+            warn!("Failed to push renamed printer {} to Supabase: {}", printer_id, e);
+        }
+    }
+
+    if let Err(e) = queue
+        .record_audit("printer_renamed", printer_id, serde_json::json!({ "old_name": old_name, "new_name": name }))
+        .await
+    {
+        warn!("Failed to record printer rename: {}", e);
+    }
+    Ok(name)
+}
+
+/// Remove a printer from the config (`remove_printer`), releasing it from quarantine
+pub async fn remove_printer(
+    config: &Mutex<AppConfig>,
+    printer_manager: &PrinterManager,
+    circuit_breakers: &CircuitBreakerRegistry,
+    queue: &QueueManager,
+    store: &impl ConfigStore,
+    printer_id: &str,
+) -> std::result::Result<(), String> {
+    info!("Removing printer: {}", printer_id);
+
+    printer_manager.remove_printer(printer_id).await;
+    circuit_breakers.quarantine.release(printer_id).await;
+
+    let mut config = config.lock().await;
+    config.printers.retain(|p| p.id != printer_id);
+    queue.set_stations(StationMap::new(&config.printers, &config.station_aliases));
+    queue.set_paused_printers(&config.paused_printers()).await;
+    store.save(&config)
+}
+
+/// Pause or resume a printer (`pause_printer`, `resume_printer`). A paused printer keeps
+/// its place in the config; its jobs stay pending, and are not failed, until it is resumed.
+pub async fn set_printer_paused(
+    config: &Mutex<AppConfig>,
+    printer_manager: &PrinterManager,
+    queue: &QueueManager,
+    store: &impl ConfigStore,
+    printer_id: &str,
+    paused: bool,
+) -> std::result::Result<(), String> {
+    let mut config = config.lock().await;
+    let printer = config
+        .printers
+        .iter_mut()
+        .find(|p| p.id == printer_id)
+        .ok_or_else(|| format!("Printer {} not found", printer_id))?;
+    if printer.paused == paused {
+        return Ok(());
+    }
+    printer.paused = paused;
+    let updated = printer.clone();
+    info!("Printer {} {}", printer_id, if paused { "paused" } else { "resumed" });
+
+    printer_manager.add_printer(updated).await;
+    queue.set_paused_printers(&config.paused_printers()).await;
+
+    let mut config_for_store = config.clone();
+    config_for_store.auth_token = None;
+    store.save(&config_for_store)?;
+    drop(config);
+
+    let action = if paused { "printer_paused" } else { "printer_resumed" };
+    if let Err(e) = queue.record_audit(action, printer_id, serde_json::json!({})).await {
+        warn!("Failed to record {} for {}: {}", action, printer_id, e);
+    }
+    Ok(())
+}
+
+/// Test print on a printer in the config (`test_print`), laid out for its paper width
+pub async fn test_print(
+    config: &Mutex<AppConfig>,
+    printer_manager: &PrinterManager,
+    printer_id: &str,
+) -> std::result::Result<(), String> {
+    info!("Test print requested for printer: {}", printer_id);
+    let commands = {
+        let config = config.lock().await;
+        let printer = config.printers.iter().find(|p| p.id == printer_id);
+        let width = printer.map_or(escpos::PaperWidth::Width80mm, |p| escpos::PaperWidth::from_chars(p.capabilities.max_width));
+        escpos::format_test_print(width, &config.test_print, config.locale, printer.map(|p| p.name.as_str()))
+    };
+    printer_manager.test_print(printer_id, &commands).await.map_err(|e| e.to_string())
+}
+
+/// Attach an operator note to a job (`annotate_job`, e.g. "printer was unplugged"). The
+/// note is stored locally and reported to Supabase with a fresh job log entry.
+pub async fn annotate_job(
+    config: &Mutex<AppConfig>,
+    queue: &QueueManager,
+    job_id: &str,
+    note: &str,
+) -> std::result::Result<JobHistoryEntry, String> {
+    let note = note.trim();
+    if note.is_empty() {
+        return Err("Note is empty".to_string());
+    }
+    if note.chars().count() > MAX_NOTE_LEN {
+        return Err(format!("Note is longer than {} characters", MAX_NOTE_LEN));
+    }
+
+    let entry = queue.add_job_note(job_id, note).await.map_err(|e| e.to_string())?;
+    info!("Note added to job {}", job_id);
+
+    let config = config.lock().await.clone();
+    if let Some(client) = crate::create_supabase_client_from_config(&config) {
+        let restaurant_id = config.restaurant_id.unwrap_or_default();
+        let logged = entry.clone();
+        tokio::spawn(async move {
+            let notes: Vec<String> = logged.notes.iter().map(|n| n.note.clone()).collect();
+            if let Err(e) = client
+                .insert_job_log(
+                    &restaurant_id,
+                    logged.order_id.as_deref(),
+                    logged.printer_id.as_deref(),
+                    None,
+                    &logged.status,
+                    logged.error_message.as_deref(),
+                    None,
+                    None,
+                    logged.retry_count as i32,
+                    &notes,
+                )
+                .await
+            {
+                warn!("Failed to report note on job {}: {}", logged.id, e);
+            }
+        });
+    }
+    Ok(entry)
+}
+
+/// Take a job out of the queue (`cancel_job` command, POST /api/jobs/:job_id/cancel) and
+/// stop it if it is running. A running job stops at its next cancel point; one whose
+/// ticket is already going out prints and ends up completed. Reports `cancelled` to
+/// Supabase. False for unknown and finished jobs.
+pub async fn cancel_job(
+    config: &Mutex<AppConfig>,
+    queue: &QueueManager,
+    job_cancel: &JobCancellation,
+    job_id: &str,
+) -> Result<bool> {
+    if !queue.cancel_job(job_id).await? {
+        return Ok(false);
+    }
+    let running = job_cancel.cancel_job(job_id);
+    info!("Print job {} cancelled{}", job_id, if running { " (stopping it)" } else { "" });
+
+    let (supabase, auto_slip) = {
+        let config = config.lock().await;
+        (crate::create_supabase_client_from_config(&config), config.pickup_slip.auto)
+    };
+    if let Some(client) = &supabase {
+        if let Err(e) = client.update_job_status(job_id, status::CANCELLED, None, None, None).await {
+            warn!("Failed to report job {} cancelled: {}", job_id, e);
+        }
+    }
+    // The cancelled ticket may have been the last one the order waited for
+    if let Some(order) = queue.order_of_job(job_id).await? {
+        complete_order(queue, supabase.as_ref(), &order, auto_slip).await;
+    }
+    Ok(true)
+}
+
+/// After a ticket printed or was cancelled: once every station's ticket of the order is
+/// done, report the order fully printed to Supabase and queue its pickup slip
+/// (`pickup_slip.auto`)
+pub async fn complete_order(
+    queue: &QueueManager,
+    supabase: Option<&SupabaseClient>,
+    order: &PickupSlip,
+    auto_slip: bool,
+) {
+    match queue.complete_order_if_done(&order.order_id).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            warn!("Failed to check completion of order {}: {}", order.order_number, e);
+            return;
+        }
+    }
+    info!("Order {} fully printed", order.order_number);
+
+    if let Some(client) = supabase {
+        match queue.order_jobs(&order.order_id).await {
+            Ok(Some(view)) => {
+                if let Err(e) = client.report_order_printed(&view).await {
+                    warn!("Failed to report order {} printed: {}", order.order_number, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to read order {}: {}", order.order_number, e),
+        }
+    }
+
+    if auto_slip {
+        match queue.queue_pickup_slip(order).await {
+            Ok(true) => info!("Pickup slip for order {} queued", order.order_number),
+            Ok(false) => {}
+            Err(e) => warn!("Failed to queue pickup slip for order {}: {}", order.order_number, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConnectionType, PrinterCapabilities};
+    use crate::fake_supabase::FakeNetworkPrinter;
+    use crate::queue::PrintJob;
+    use std::path::PathBuf;
+
+    /// Keeps the last saved config
+    #[derive(Default)]
+    struct MemoryStore(std::sync::Mutex<Option<AppConfig>>);
+
+    impl ConfigStore for MemoryStore {
+        fn save(&self, config: &AppConfig) -> std::result::Result<(), String> {
+            *self.0.lock().unwrap() = Some(config.clone());
+            Ok(())
+        }
+    }
+
+    impl MemoryStore {
+        fn saved(&self) -> Option<AppConfig> {
+            self.0.lock().unwrap().clone()
+        }
+
+        fn printer_names(&self) -> Vec<String> {
+            let saved = self.0.lock().unwrap();
+            saved.as_ref().map_or(vec![], |c| c.printers.iter().map(|p| p.name.clone()).collect())
+        }
+    }
+
+    fn printer(id: &str, name: &str, address: &str) -> PrinterConfig {
+        PrinterConfig {
+            id: id.to_string(),
+            name: name.to_string(),
+            connection_type: ConnectionType::Network,
+            address: address.to_string(),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: Some("bar".to_string()),
+            is_primary: true,
+            capabilities: PrinterCapabilities { cutter: true, drawer: false, qrcode: true, max_width: 48 },
+            paper_width_checked: true,
+            recovery: Default::default(),
+            compact: None,
+            transports: vec![],
+            serial: None,
+            paused: false,
+        }
+    }
+
+    fn job(id: &str) -> PrintJob {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "restaurant_id": "rest_123",
+            "order_id": "order-1",
+            "order_number": "R001-0042",
+            "station": "bar",
+            "printer_id": "printer-1",
+            "items": [{ "quantity": 1, "name": "Espresso", "modifiers": [] }],
+            "priority": 3,
+            "timestamp": 1700000000000i64,
+            "status": "pending",
+            "retry_count": 0
+        }))
+        .unwrap()
+    }
+
+    // QA: add a network printer, test print on it, rename it, pause it, remove it
+    #[tokio::test]
+    async fn test_printer_setup_flow() {
+        let printer_manager = match PrinterManager::new() {
+            Ok(pm) => pm,
+            Err(_) => return, // No USB context in this environment
+        };
+        let device = FakeNetworkPrinter::start().await;
+        let config = Mutex::new(AppConfig::default());
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        let (address_monitor, _address_rx) = crate::readdress::AddressMonitor::new();
+        let (breakers, _status_rx) = CircuitBreakerRegistry::new(address_monitor);
+        let store = MemoryStore::default();
+
+        add_printer(&config, &printer_manager, &queue, &store, printer("printer-1", " Bar ", &device.address))
+            .await
+            .unwrap();
+        assert_eq!(store.printer_names(), vec!["Bar"]);
+        // Same address or same name: refused, nothing saved
+        let taken = printer("printer-2", "Kitchen", &device.address);
+        assert!(add_printer(&config, &printer_manager, &queue, &store, taken).await.is_err());
+        let same_name = printer("printer-2", "bar", "192.168.1.60:9100");
+        assert!(add_printer(&config, &printer_manager, &queue, &store, same_name).await.is_err());
+        assert_eq!(config.lock().await.printers.len(), 1);
+
+        test_print(&config, &printer_manager, "printer-1").await.unwrap();
+        let printed = async {
+            for _ in 0..50 {
+                if !device.printed_text().await.is_empty() {
+                    return true;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+            false
+        };
+        assert!(printed.await, "test print didn't reach the printer");
+
+        let renamed = rename_printer(&config, &printer_manager, &queue, &store, "printer-1", "Cocktail bar").await;
+        assert_eq!(renamed, Ok("Cocktail bar".to_string()));
+        assert_eq!(store.printer_names(), vec!["Cocktail bar"]);
+        assert!(rename_printer(&config, &printer_manager, &queue, &store, "missing", "Pass").await.is_err());
+
+        // Paused: kept in the config, its jobs held
+        set_printer_paused(&config, &printer_manager, &queue, &store, "printer-1", true).await.unwrap();
+        assert!(store.0.lock().unwrap().as_ref().unwrap().printers[0].paused);
+        assert!(printer_manager.get_printer("printer-1").await.unwrap().paused);
+        assert!(queue.is_printer_paused("printer-1"));
+        set_printer_paused(&config, &printer_manager, &queue, &store, "printer-1", false).await.unwrap();
+        assert!(!queue.is_printer_paused("printer-1"));
+        assert!(set_printer_paused(&config, &printer_manager, &queue, &store, "missing", true).await.is_err());
+
+        remove_printer(&config, &printer_manager, &breakers, &queue, &store, "printer-1").await.unwrap();
+        assert!(store.printer_names().is_empty());
+    }
+
+    // QA: note on a job, cancel a queued job, can't cancel it twice
+    #[tokio::test]
+    async fn test_job_commands() {
+        let config = Mutex::new(AppConfig::default());
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        let job_cancel = JobCancellation::new();
+        queue.enqueue(job("job-1")).await.unwrap();
+
+        assert!(annotate_job(&config, &queue, "job-1", "   ").await.is_err());
+        assert!(annotate_job(&config, &queue, "job-1", &"x".repeat(MAX_NOTE_LEN + 1)).await.is_err());
+        let entry = annotate_job(&config, &queue, "job-1", " printer was unplugged ").await.unwrap();
+        assert_eq!(entry.notes[0].note, "printer was unplugged");
+
+        // Running in the processor: its token is cancelled too
+        let running = job_cancel.start_job("job-1");
+        assert!(cancel_job(&config, &queue, &job_cancel, "job-1").await.unwrap());
+        assert!(running.is_cancelled());
+        assert!(!cancel_job(&config, &queue, &job_cancel, "job-1").await.unwrap());
+        assert!(queue.get_pending_jobs(10).await.unwrap().is_empty());
+
+        // Cancelling the last open ticket of an order that printed elsewhere completes it
+        config.lock().await.pickup_slip.auto = true;
+        let order_job = |id: &str, station: &str| PrintJob {
+            order_id: Some("order-2".to_string()),
+            station: station.to_string(),
+            ..job(id)
+        };
+        queue.enqueue(order_job("job-2", "kitchen")).await.unwrap();
+        queue.enqueue(order_job("job-3", "bar")).await.unwrap();
+        queue.mark_completed("job-2", 10, None).await.unwrap();
+        assert!(cancel_job(&config, &queue, &job_cancel, "job-3").await.unwrap());
+        assert!(queue.order_jobs("order-2").await.unwrap().unwrap().completed_at.is_some());
+        let slips = queue.pending_pickup_slips(10).await.unwrap();
+        assert_eq!(slips.iter().map(|s| s.order_id.as_str()).collect::<Vec<_>>(), vec!["order-2"]);
+    }
+
+    fn pairing() -> PairingResult {
+        PairingResult {
+            token: "pairing-token".to_string(),
+            restaurant_id: "rest_123".to_string(),
+            restaurant_code: "AMS-001".to_string(),
+            expires_in: "90d".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pairing_survives_a_closed_wizard() {
+        let config = Mutex::new(AppConfig::default());
+        let store = MemoryStore::default();
+        let restaurant_id = record_pairing(&config, &store, &pairing(), |_| Ok(())).await.unwrap();
+        assert_eq!(restaurant_id.as_deref(), Some("rest_123"));
+
+        // App closed before the wizard's save: the next start reads what was stored
+        let saved = store.saved().unwrap();
+        assert_eq!(saved.auth_token, None);
+        let state = saved.pairing_state();
+        assert!(state.paired && !state.setup_complete);
+        assert_eq!(state.restaurant_code.as_deref(), Some("AMS-001"));
+        assert_eq!(state.printer_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_pairing_token_kept_in_config_without_keychain() {
+        let config = Mutex::new(AppConfig::default());
+        let store = MemoryStore::default();
+        record_pairing(&config, &store, &pairing(), |_| Err("no secret service".to_string())).await.unwrap();
+
+        let saved = store.saved().unwrap();
+        assert_eq!(saved.auth_token.as_deref(), Some("pairing-token"));
+        assert!(saved.pairing_state().paired);
+        assert_eq!(config.lock().await.auth_token.as_deref(), Some("pairing-token"));
+    }
+}
@@ -0,0 +1,263 @@
+use crate::config::{AppConfig, KEYRING_SERVICE};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use thiserror::Error;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+/// Keychain entry holding the config key (hex, 32 bytes)
+const KEYRING_KEY_USER: &str = "config-key";
+
+/// Envelope format version
+const VERSION: u8 = 1;
+
+/// AES-GCM nonce length, stored in front of the ciphertext
+const NONCE_LEN: usize = 12;
+
+/// How config.json is stored once sealed: `{"sealed": {...}}` instead of the config itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sealed {
+    v: u8,
+    encrypted: bool,
+    /// The config JSON, or base64 of nonce + AES-256-GCM ciphertext when encrypted
+    payload: String,
+    /// Hex HMAC-SHA256 of `payload`
+    mac: String,
+}
+
+/// Why a stored config was not loaded
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SealError {
+    #[error("config.json failed its integrity check (changed outside the daemon or damaged)")]
+    Tampered,
+
+    #[error("config.json is not signed, but this machine signs its config")]
+    Unsigned,
+
+    #[error("config.json is sealed, but its key is missing from the keychain")]
+    KeyMissing,
+
+    #[error("keychain unavailable: {0}")]
+    Keychain(String),
+
+    #[error("config.json can't be read: {0}")]
+    Corrupt(String),
+}
+
+/// A stored config after the check
+#[derive(Debug)]
+pub enum Loaded {
+    /// Sealed and verified, or plain from before sealing (no key created yet)
+    Trusted(AppConfig),
+    /// Not loaded. `readable` holds the contents when they can still be read (a signed
+    /// but unencrypted config that was edited by hand), so staff can choose to keep them.
+    Rejected { error: SealError, readable: Option<AppConfig> },
+}
+
+/// Result of the config check at startup (`get_config_integrity`)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigIntegrity {
+    /// Why the stored config was not loaded; None when it loaded fine
+    pub error: Option<String>,
+    /// The rejected contents can still be read and kept (`recover_config`)
+    pub recoverable: bool,
+    /// Copy of the rejected config.json, next to the queue database
+    pub backup_path: Option<String>,
+    /// Rejected contents, kept in memory until staff decide
+    #[serde(skip)]
+    pub readable: Option<AppConfig>,
+}
+
+/// Encryption and MAC keys, both derived from the one key in the keychain
+struct Keys {
+    encryption: Zeroizing<[u8; 32]>,
+    mac: Zeroizing<[u8; 32]>,
+}
+
+impl Keys {
+    fn derive(master: &[u8]) -> Self {
+        let derive = |label: &[u8]| -> Zeroizing<[u8; 32]> {
+            let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(master).expect("HMAC takes any key length");
+            mac.update(label);
+            Zeroizing::new(mac.finalize().into_bytes().into())
+        };
+        Self {
+            encryption: derive(b"eatsome-config-encryption"),
+            mac: derive(b"eatsome-config-integrity"),
+        }
+    }
+
+    fn sign(&self, payload: &str) -> Hmac<Sha256> {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(self.mac.as_slice()).expect("HMAC takes any key length");
+        mac.update(payload.as_bytes());
+        mac
+    }
+}
+
+fn keychain_entry() -> Result<keyring::Entry, SealError> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_KEY_USER).map_err(|e| SealError::Keychain(e.to_string()))
+}
+
+/// The config key, if this machine has one
+fn load_key() -> Result<Option<Keys>, SealError> {
+    match keychain_entry()?.get_password() {
+        Ok(hex_key) => {
+            let master = Zeroizing::new(hex::decode(hex_key.trim()).map_err(|_| SealError::KeyMissing)?);
+            Ok(Some(Keys::derive(&master)))
+        }
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(SealError::Keychain(e.to_string())),
+    }
+}
+
+/// The config key, created on first use
+fn load_or_create_key() -> Result<Keys, SealError> {
+    if let Some(keys) = load_key()? {
+        return Ok(keys);
+    }
+    let master = Aes256Gcm::generate_key(OsRng);
+    keychain_entry()?
+        .set_password(&hex::encode(master))
+        .map_err(|e| SealError::Keychain(e.to_string()))?;
+    info!("Config key created in the OS keychain");
+    Ok(Keys::derive(&master))
+}
+
+fn seal_with(config: &AppConfig, keys: &Keys) -> Result<Value, SealError> {
+    let json = serde_json::to_string(config).map_err(|e| SealError::Corrupt(e.to_string()))?;
+    let payload = if config.config_encryption {
+        let cipher = Aes256Gcm::new_from_slice(keys.encryption.as_slice()).expect("32-byte key");
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, json.as_bytes())
+            .map_err(|_| SealError::Corrupt("encryption failed".into()))?;
+        let mut bytes = nonce.to_vec();
+        bytes.extend_from_slice(&ciphertext);
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    } else {
+        json
+    };
+    let sealed = Sealed {
+        v: VERSION,
+        encrypted: config.config_encryption,
+        mac: hex::encode(keys.sign(&payload).finalize().into_bytes()),
+        payload,
+    };
+    Ok(serde_json::json!({ "sealed": sealed }))
+}
+
+fn open_with(stored: &Value, keys: Option<&Keys>) -> Loaded {
+    let rejected = |error: SealError| Loaded::Rejected { error, readable: None };
+    let parse = |json: &str| serde_json::from_str::<AppConfig>(json).map_err(|e| SealError::Corrupt(e.to_string()));
+
+    let Some(sealed) = stored.get("sealed") else {
+        // Plain config.json: fine before the first sealed write, suspect once a key exists
+        return match serde_json::from_value::<AppConfig>(stored.clone()) {
+            Ok(config) if keys.is_none() => Loaded::Trusted(config),
+            Ok(config) => Loaded::Rejected { error: SealError::Unsigned, readable: Some(config) },
+            Err(e) => rejected(SealError::Corrupt(e.to_string())),
+        };
+    };
+    let sealed = match serde_json::from_value::<Sealed>(sealed.clone()) {
+        Ok(sealed) if sealed.v == VERSION => sealed,
+        Ok(sealed) => return rejected(SealError::Corrupt(format!("unknown format version {}", sealed.v))),
+        Err(e) => return rejected(SealError::Corrupt(e.to_string())),
+    };
+    let Some(keys) = keys else {
+        return rejected(SealError::KeyMissing);
+    };
+
+    let mac_ok = hex::decode(&sealed.mac)
+        .is_ok_and(|mac| keys.sign(&sealed.payload).verify_slice(&mac).is_ok());
+    if !sealed.encrypted {
+        return match (mac_ok, parse(&sealed.payload)) {
+            (true, Ok(config)) => Loaded::Trusted(config),
+            (false, readable) => Loaded::Rejected { error: SealError::Tampered, readable: readable.ok() },
+            (true, Err(e)) => rejected(e),
+        };
+    }
+    if !mac_ok {
+        return rejected(SealError::Tampered);
+    }
+
+    let bytes = match base64::engine::general_purpose::STANDARD.decode(&sealed.payload) {
+        Ok(bytes) if bytes.len() > NONCE_LEN => bytes,
+        _ => return rejected(SealError::Corrupt("bad ciphertext".into())),
+    };
+    let cipher = Aes256Gcm::new_from_slice(keys.encryption.as_slice()).expect("32-byte key");
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    match cipher.decrypt(Nonce::from_slice(nonce), ciphertext) {
+        Ok(json) => match String::from_utf8(json).map_err(|e| SealError::Corrupt(e.to_string())).and_then(|j| parse(&j)) {
+            Ok(config) => Loaded::Trusted(config),
+            Err(e) => rejected(e),
+        },
+        Err(_) => rejected(SealError::Tampered),
+    }
+}
+
+/// Config as it goes into config.json: signed, and encrypted when `config_encryption`
+/// is on. Without a keychain an unencrypted config is stored plain (and loads as plain),
+/// an encrypted one can't be stored at all.
+pub fn seal(config: &AppConfig) -> Result<Value, SealError> {
+    match load_or_create_key() {
+        Ok(keys) => seal_with(config, &keys),
+        Err(e) if !config.config_encryption => {
+            warn!("Config stored unsigned: {}", e);
+            serde_json::to_value(config).map_err(|e| SealError::Corrupt(e.to_string()))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Check and read the stored config.json value
+pub fn open(stored: &Value) -> Loaded {
+    match load_key() {
+        Ok(keys) => open_with(stored, keys.as_ref()),
+        // Can't tell without the key; a plain config still loads
+        Err(e) if stored.get("sealed").is_some() => Loaded::Rejected { error: e, readable: None },
+        Err(_) => open_with(stored, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_detects_tampering() {
+        let keys = Keys::derive(&[7u8; 32]);
+        let mut config = AppConfig { restaurant_id: Some("r1".to_string()), ..AppConfig::default() };
+
+        let stored = seal_with(&config, &keys).unwrap();
+        assert!(matches!(open_with(&stored, Some(&keys)), Loaded::Trusted(c) if c.restaurant_id.as_deref() == Some("r1")));
+        // Plain config: trusted only while there is no key
+        let plain = serde_json::to_value(&config).unwrap();
+        assert!(matches!(open_with(&plain, None), Loaded::Trusted(_)));
+        assert!(matches!(open_with(&plain, Some(&keys)), Loaded::Rejected { error: SealError::Unsigned, readable: Some(_) }));
+        assert!(matches!(open_with(&stored, None), Loaded::Rejected { error: SealError::KeyMissing, .. }));
+
+        // Edited by hand: rejected, contents still readable
+        let mut edited = stored.clone();
+        let payload = edited["sealed"]["payload"].as_str().unwrap().replace("r1", "r2");
+        edited["sealed"]["payload"] = Value::String(payload);
+        match open_with(&edited, Some(&keys)) {
+            Loaded::Rejected { error, readable } => {
+                assert_eq!(error, SealError::Tampered);
+                assert_eq!(readable.unwrap().restaurant_id.as_deref(), Some("r2"));
+            }
+            Loaded::Trusted(_) => panic!("tampered config trusted"),
+        }
+
+        config.config_encryption = true;
+        let stored = seal_with(&config, &keys).unwrap();
+        assert!(!stored["sealed"]["payload"].as_str().unwrap().contains("r1"));
+        assert!(matches!(open_with(&stored, Some(&keys)), Loaded::Trusted(c) if c.config_encryption));
+        let other_machine = Keys::derive(&[8u8; 32]);
+        assert!(matches!(open_with(&stored, Some(&other_machine)), Loaded::Rejected { error: SealError::Tampered, readable: None }));
+    }
+}
@@ -24,6 +24,70 @@ fn default_protocol() -> String {
     "unknown".to_string()
 }
 
+/// Discovery method a batch of results came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscoverySource {
+    CloudPrnt,
+    Enpc,
+    WsDiscovery,
+    Tcp,
+    Mdns,
+    Bluetooth,
+    Usb,
+    Serial,
+}
+
+impl DiscoverySource {
+    /// When two methods find the same printer, the higher priority entry is kept
+    /// (vendor-specific methods know the model best, mDNS names are the vaguest)
+    fn priority(self) -> u8 {
+        match self {
+            DiscoverySource::CloudPrnt | DiscoverySource::Bluetooth | DiscoverySource::Usb | DiscoverySource::Serial => 5,
+            DiscoverySource::Enpc => 4,
+            DiscoverySource::WsDiscovery => 3,
+            DiscoverySource::Tcp => 2,
+            DiscoverySource::Mdns => 1,
+        }
+    }
+}
+
+/// Results of one discovery method
+pub type DiscoveryBatch = (DiscoverySource, Vec<DiscoveredPrinter>);
+
+/// Deduplicated discovery results, merged batch by batch in whatever order methods finish
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryResults {
+    /// dedup key → (source priority, printer)
+    entries: HashMap<String, (u8, DiscoveredPrinter)>,
+}
+
+impl DiscoveryResults {
+    pub fn merge(&mut self, source: DiscoverySource, printers: Vec<DiscoveredPrinter>) {
+        let priority = source.priority();
+        for printer in printers {
+            let key = dedup_key(&printer);
+            match self.entries.get(&key) {
+                Some((existing, _)) if *existing >= priority => {}
+                _ => {
+                    self.entries.insert(key, (priority, printer));
+                }
+            }
+        }
+    }
+
+    pub fn contains_id(&self, id: &str) -> bool {
+        self.entries.values().any(|(_, p)| p.id == id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn printers(&self) -> Vec<DiscoveredPrinter> {
+        self.entries.values().map(|(_, p)| p.clone()).collect()
+    }
+}
+
 /// Discover all printers using multiple discovery methods in parallel
 ///
 /// This is the PRIMARY discovery function that should be called from the UI.
@@ -46,10 +110,16 @@ fn default_protocol() -> String {
 ///
 /// # Arguments
 /// * `subnet` - CIDR notation for TCP scanning (e.g., "192.168.1.0/24")
+/// * `profile` - TCP port scan profile
 ///
-/// # Returns
-/// Deduplicated list of all discovered printers
-pub async fn discover_all_printers(subnet: &str) -> Result<Vec<DiscoveredPrinter>> {
+/// Each method's results are sent on `tx` as soon as it finishes (a TCP scan
+/// lands long before the 10s Bluetooth scan); merge them with `DiscoveryResults`.
+/// Returns when every method is done.
+pub async fn discover_all_printers_streaming(
+    subnet: &str,
+    profile: &PortScanProfile,
+    tx: tokio::sync::mpsc::UnboundedSender<DiscoveryBatch>,
+) {
     info!("Starting COMPREHENSIVE printer discovery on subnet: {}", subnet);
     info!("Running 6 discovery methods in parallel:");
     info!("  1. TCP Port Scanning (9100/631/515)");
@@ -60,13 +130,35 @@ pub async fn discover_all_printers(subnet: &str) -> Result<Vec<DiscoveredPrinter
     info!("  6. Star CloudPRNT (HTTP)");
     info!("  NOTE: SNMP discovery temporarily disabled - provides ~92% coverage without it");
 
+    // Each method reports on its own as soon as it completes
+    fn report<F>(
+        source: DiscoverySource,
+        tx: &tokio::sync::mpsc::UnboundedSender<DiscoveryBatch>,
+        scan: F,
+    ) -> tokio::task::JoinHandle<()>
+    where
+        F: std::future::Future<Output = Result<Vec<DiscoveredPrinter>>> + Send + 'static,
+    {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            match scan.await {
+                Ok(printers) => {
+                    info!("{:?} found {} printers", source, printers.len());
+                    let _ = tx.send((source, printers));
+                }
+                Err(e) => warn!("{:?} discovery failed: {}", source, e),
+            }
+        })
+    }
+
     // Launch ALL discovery methods in parallel
-    let tcp_task = tokio::spawn({
+    let tcp_task = report(DiscoverySource::Tcp, &tx, {
         let subnet = subnet.to_string();
-        async move { scan_subnet_tcp(&subnet, 500).await }
+        let profile = profile.clone();
+        async move { scan_subnet_tcp(&subnet, &profile).await }
     });
 
-    let mdns_task = tokio::spawn(async move {
+    let mdns_task = report(DiscoverySource::Mdns, &tx, async move {
         discover_network_printers_with_timeout(5).await
     });
 
@@ -77,25 +169,32 @@ pub async fn discover_all_printers(subnet: &str) -> Result<Vec<DiscoveredPrinter
     //     async move { scan_subnet_snmp(&subnet).await }
     // });
 
-    let bluetooth_task = tokio::spawn(async move {
+    // BLE scanning keeps a core busy for 10s; skipped on slow machines
+    let bluetooth_task = report(DiscoverySource::Bluetooth, &tx, async move {
+        if crate::low_resource::is_enabled() {
+            info!("Bluetooth scan skipped (low-resource mode)");
+            return Ok(Vec::new());
+        }
         discover_bluetooth_printers_with_timeout(10).await
     });
 
-    let wsd_task = tokio::spawn(async move {
+    let wsd_task = report(DiscoverySource::WsDiscovery, &tx, async move {
         discover_ws_discovery(5).await
     });
 
-    let enpc_task = tokio::spawn(async move {
+    let enpc_task = report(DiscoverySource::Enpc, &tx, async move {
         discover_epson_enpc(3).await
     });
 
-    let cloudprnt_task = tokio::spawn({
+    let cloudprnt_task = report(DiscoverySource::CloudPrnt, &tx, {
         let subnet = subnet.to_string();
         async move { discover_star_cloudprnt(&subnet).await }
     });
 
-    // Wait for all tasks to complete
-    let (tcp_result, mdns_result, bluetooth_result, wsd_result, enpc_result, cloudprnt_result) = tokio::join!(
+    // Drop our sender so the receiver closes once the last method reports
+    drop(tx);
+
+    let _ = tokio::join!(
         tcp_task,
         mdns_task,
         bluetooth_task,
@@ -104,72 +203,9 @@ pub async fn discover_all_printers(subnet: &str) -> Result<Vec<DiscoveredPrinter
         cloudprnt_task
     );
 
-    // Collect all results with deduplication
-    // Key: normalized IP address (strips port) for network printers, full address for Bluetooth
-    let mut all_printers = HashMap::new();
-
-    // CloudPRNT results (HIGHEST priority for Star modern printers)
-    if let Ok(Ok(printers)) = cloudprnt_result {
-        info!("CloudPRNT found {} printers", printers.len());
-        for printer in printers {
-            all_printers.insert(dedup_key(&printer), printer);
-        }
-    }
-
-    // ENPC results (HIGH priority for Epson)
-    if let Ok(Ok(printers)) = enpc_result {
-        info!("ENPC found {} printers", printers.len());
-        for printer in printers {
-            all_printers.entry(dedup_key(&printer)).or_insert(printer);
-        }
-    }
-
-    // WS-Discovery results (HIGH priority for Windows environments)
-    if let Ok(Ok(printers)) = wsd_result {
-        info!("WS-Discovery found {} printers", printers.len());
-        for printer in printers {
-            all_printers.entry(dedup_key(&printer)).or_insert(printer);
-        }
-    }
-
-    // TCP results (MEDIUM-HIGH priority - most reliable for generic thermal printers)
-    if let Ok(Ok(printers)) = tcp_result {
-        info!("TCP scan found {} printers", printers.len());
-        for printer in printers {
-            all_printers.entry(dedup_key(&printer)).or_insert(printer);
-        }
-    }
-
-    // mDNS results (MEDIUM priority - don't override vendor-specific discoveries)
-    if let Ok(Ok(printers)) = mdns_result {
-        info!("mDNS found {} printers", printers.len());
-        for printer in printers {
-            all_printers.entry(dedup_key(&printer)).or_insert(printer);
-        }
-    }
-
-    // SNMP discovery temporarily disabled
-    // if let Ok(Ok(printers)) = snmp_result {
-    //     info!("SNMP found {} printers", printers.len());
-    //     for printer in printers {
-    //         all_printers.entry(dedup_key(&printer)).or_insert(printer);
-    //     }
-    // }
-
-    // Bluetooth results (separate connection type, always include)
-    if let Ok(Ok(printers)) = bluetooth_result {
-        info!("Bluetooth found {} printers", printers.len());
-        for printer in printers {
-            all_printers.insert(dedup_key(&printer), printer);
-        }
-    }
-
-    let printers: Vec<DiscoveredPrinter> = all_printers.into_values().collect();
     info!("═══════════════════════════════════════════════════════════");
-    info!("COMPREHENSIVE DISCOVERY COMPLETE: {} unique printers found", printers.len());
+    info!("COMPREHENSIVE DISCOVERY COMPLETE");
     info!("═══════════════════════════════════════════════════════════");
-
-    Ok(printers)
 }
 
 /// Compute a deduplication key for a discovered printer.
@@ -178,10 +214,10 @@ pub async fn discover_all_printers(subnet: &str) -> Result<Vec<DiscoveredPrinter
 /// This ensures the same printer discovered via TCP (port 9100) and mDNS (port 631)
 /// is deduplicated to a single entry.
 ///
-/// For Bluetooth: use the full MAC address (already unique).
+/// For Bluetooth and serial: use the full MAC address or port name (already unique).
 fn dedup_key(printer: &DiscoveredPrinter) -> String {
     match printer.connection_type.as_str() {
-        "bluetooth" => printer.address.clone(),
+        "bluetooth" | "serial" => printer.address.clone(),
         _ => {
             // Normalize: extract IP from "IP:PORT"
             printer
@@ -232,7 +268,7 @@ pub fn detect_local_subnet() -> String {
 ///
 /// # Note
 /// This method ONLY finds printers that advertise via mDNS. Most restaurant
-/// thermal printers do NOT advertise, so use `discover_all_printers()` instead.
+/// thermal printers do NOT advertise, so use `discover_all_printers_streaming()` instead.
 pub async fn discover_network_printers() -> Result<Vec<DiscoveredPrinter>> {
     discover_network_printers_with_timeout(5).await
 }
@@ -377,45 +413,10 @@ pub async fn discover_bluetooth_printers_with_timeout(timeout_secs: u64) -> Resu
 
     #[cfg(not(target_os = "linux"))]
     {
-        use btleplug::api::{Central, Manager as _, Peripheral as _, ScanFilter};
-        use btleplug::platform::Manager;
-
-        let manager = Manager::new()
-            .await
-            .map_err(|e| DaemonError::Bluetooth(format!("Failed to create BLE manager: {}", e)))?;
-
-        let adapters = manager.adapters()
-            .await
-            .map_err(|e| DaemonError::Bluetooth(format!("Failed to get BLE adapters: {}", e)))?;
+        use btleplug::api::Peripheral as _;
 
-        if adapters.is_empty() {
-            warn!("No Bluetooth adapters found");
-            return Ok(Vec::new());
-        }
-
-        let adapter = &adapters[0];
-        debug!("Using Bluetooth adapter: {:?}", adapter);
-
-        // Start scanning
-        adapter
-            .start_scan(ScanFilter::default())
-            .await
-            .map_err(|e| DaemonError::Bluetooth(format!("Failed to start BLE scan: {}", e)))?;
-
-        // Scan for timeout duration
-        tokio::time::sleep(Duration::from_secs(timeout_secs)).await;
-
-        // Get peripherals
-        let peripherals = adapter
-            .peripherals()
-            .await
-            .map_err(|e| DaemonError::Bluetooth(format!("Failed to get peripherals: {}", e)))?;
-
-        // Stop scanning
-        adapter
-            .stop_scan()
-            .await
-            .map_err(|e| DaemonError::Bluetooth(format!("Failed to stop BLE scan: {}", e)))?;
+        // Shared adapter; waits for BLE prints and yields to new ones (see `ble.rs`)
+        let peripherals = crate::ble::coordinator().discover(Duration::from_secs(timeout_secs)).await?;
 
         let mut discovered = Vec::new();
 
@@ -535,9 +536,86 @@ fn is_bluetooth_printer(name: &str) -> bool {
         || lower.contains("receipt")
 }
 
+/// One TCP port the scanner probes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanPort {
+    pub port: u16,
+    /// Shown in logs and recorded in capabilities ("Raw TCP (JetDirect)")
+    pub label: String,
+    /// Raw socket port: an open port means the printer takes ESC/POS as-is
+    #[serde(default)]
+    pub raw: bool,
+}
+
+impl ScanPort {
+    fn new(port: u16, label: &str, raw: bool) -> Self {
+        Self { port, label: label.to_string(), raw }
+    }
+}
+
+/// Which ports the TCP scanner probes and how hard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PortScanProfile {
+    pub ports: Vec<ScanPort>,
+    /// Concurrent connection attempts. Running all 762 connections (254 IPs × 3 ports)
+    /// at once triggers macOS network throttling after the first scan.
+    pub batch_size: usize,
+    /// TCP connect timeout per host and port
+    pub timeout_ms: u64,
+}
+
+impl Default for PortScanProfile {
+    fn default() -> Self {
+        Self {
+            ports: vec![
+                ScanPort::new(9100, "Raw TCP (JetDirect)", true),
+                ScanPort::new(631, "IPP", false),
+                ScanPort::new(515, "LPD", false),
+            ],
+            batch_size: 50,
+            timeout_ms: 500,
+        }
+    }
+}
+
+impl PortScanProfile {
+    /// Support profile: extra raw ports (multi-interface print servers use 9101-9103,
+    /// some Citizen/SNBC firmware custom ports) and web UIs, with a longer timeout.
+    /// About 5x slower than the default profile.
+    pub fn deep() -> Self {
+        let mut ports = vec![ScanPort::new(9100, "Raw TCP (JetDirect)", true)];
+        for port in 9101..=9109 {
+            ports.push(ScanPort::new(port, "Raw TCP (JetDirect, secondary)", true));
+        }
+        ports.extend([
+            ScanPort::new(631, "IPP", false),
+            ScanPort::new(515, "LPD", false),
+            ScanPort::new(4000, "Raw TCP (custom)", true),
+            ScanPort::new(6101, "Raw TCP (custom)", true),
+            ScanPort::new(80, "HTTP", false),
+            ScanPort::new(443, "HTTPS", false),
+            ScanPort::new(8080, "HTTP (alt)", false),
+        ]);
+        Self {
+            ports,
+            batch_size: 100,
+            timeout_ms: 1000,
+        }
+    }
+
+    /// Make sure `port` is probed (as a raw port if it's new)
+    pub fn with_port(mut self, port: u16) -> Self {
+        if !self.ports.iter().any(|p| p.port == port) {
+            self.ports.push(ScanPort::new(port, "Raw TCP (configured)", true));
+        }
+        self
+    }
+}
+
 /// Scan network subnet for printers via TCP port scanning
 ///
-/// Performs fast parallel TCP port scanning on common printer ports:
+/// Performs fast parallel TCP port scanning on the profile's ports. The default:
 /// - Port 9100: Raw TCP (JetDirect/AppSocket) - most thermal printers
 /// - Port 631: IPP (Internet Printing Protocol)
 /// - Port 515: LPD (Line Printer Daemon)
@@ -547,38 +625,28 @@ fn is_bluetooth_printer(name: &str) -> bool {
 ///
 /// # Arguments
 /// * `subnet` - CIDR notation subnet (e.g., "192.168.1.0/24")
-/// * `timeout_ms` - TCP connection timeout per host (default: 500ms)
+/// * `profile` - Ports, batch size and per-host timeout
 ///
 /// # Returns
-/// List of discovered printers with open printer ports
-pub async fn scan_subnet_tcp(subnet: &str, timeout_ms: u64) -> Result<Vec<DiscoveredPrinter>> {
-    info!("Starting TCP port scan: {}", subnet);
+/// List of discovered printers, one per IP, with every open port in `capabilities.open_ports`
+pub async fn scan_subnet_tcp(subnet: &str, profile: &PortScanProfile) -> Result<Vec<DiscoveredPrinter>> {
+    info!("Starting TCP port scan: {} ({} ports)", subnet, profile.ports.len());
 
     let ip_range = parse_cidr(subnet)?;
-    let mut discovered = HashMap::new();
-
-    // Printer ports to scan
-    let printer_ports = vec![
-        (9100, "Raw TCP (JetDirect)"),
-        (631, "IPP"),
-        (515, "LPD"),
-    ];
+    let timeout = Duration::from_millis(profile.timeout_ms);
+    let batch_size = profile.batch_size.max(1);
 
     // Create parallel scanning tasks with rate limiting
     // IMPORTANT: Limit concurrent connections to avoid overwhelming macOS network stack
-    // Running all 762 connections (254 IPs × 3 ports) in parallel triggers macOS security
-    // throttling after the first scan. Batching in groups of 50 prevents this.
+    let mut open: HashMap<String, Vec<u16>> = HashMap::new();
     let mut scan_tasks = Vec::new();
-    const BATCH_SIZE: usize = 50;
 
     for ip in ip_range {
-        for (port, protocol) in &printer_ports {
+        for scan_port in &profile.ports {
             let ip_str = ip.to_string();
-            let port = *port;
-            let protocol = protocol.to_string();
-            let timeout = Duration::from_millis(timeout_ms);
+            let port = scan_port.port;
 
-            let task = tokio::spawn(async move {
+            scan_tasks.push(tokio::spawn(async move {
                 // Try TCP connection
                 match tokio::time::timeout(
                     timeout,
@@ -586,123 +654,84 @@ pub async fn scan_subnet_tcp(subnet: &str, timeout_ms: u64) -> Result<Vec<Discov
                 )
                 .await
                 {
-                    Ok(Ok(_stream)) => {
-                        debug!("Port {} open on {} ({})", port, ip_str, protocol);
-                        Some((ip_str, port, protocol))
-                    }
+                    Ok(Ok(_stream)) => Some((ip_str, port)),
                     Ok(Err(_)) => None,
                     Err(_) => None, // Timeout
                 }
-            });
-
-            scan_tasks.push(task);
+            }));
 
             // Process in batches to avoid overwhelming network stack
-            if scan_tasks.len() >= BATCH_SIZE {
-                let batch_results = futures_util::future::join_all(scan_tasks).await;
-                // Process this batch immediately
-                for result in batch_results {
-                    if let Ok(Some((ip, port, protocol))) = result {
-                        let id = format!("tcp_{}", ip.replace('.', "_"));
-
-                        if !discovered.contains_key(&id) {
-                            let name = if port == 631 {
-                                query_ipp_printer_name(&ip).await.unwrap_or_else(|| format!("Printer at {}", ip))
-                            } else {
-                                format!("Printer at {}", ip)
-                            };
-
-                            let vendor = query_printer_vendor(&ip).await;
-
-                            // Port 9100 = raw ESC/POS, port 631 = IPP (may not be ESC/POS)
-                            let detected_protocol = if port == 9100 {
-                                "escpos".to_string()
-                            } else {
-                                "unknown".to_string()
-                            };
-
-                            let printer = DiscoveredPrinter {
-                                id: id.clone(),
-                                name: name.clone(),
-                                connection_type: "network".to_string(),
-                                address: format!("{}:{}", ip, if port == 9100 { 9100 } else { port }),
-                                vendor,
-                                capabilities: Some(serde_json::json!({
-                                    "ports": {
-                                        "9100": port == 9100,
-                                        "631": port == 631,
-                                        "515": port == 515,
-                                    },
-                                    "protocol": protocol,
-                                })),
-                                protocol: detected_protocol,
-                            };
-
-                            discovered.insert(id.clone(), printer);
-                            info!("Discovered TCP printer: {} at {}:{}", name, ip, port);
-                        }
-                    }
-                }
-                scan_tasks = Vec::new(); // Clear for next batch
+            if scan_tasks.len() >= batch_size {
+                collect_open_ports(std::mem::take(&mut scan_tasks), &mut open).await;
             }
         }
     }
-
-    // Process remaining tasks in final batch
-    let results = futures_util::future::join_all(scan_tasks).await;
-
-    // Process results
-    for result in results {
-        if let Ok(Some((ip, port, protocol))) = result {
-            let id = format!("tcp_{}", ip.replace('.', "_"));
-
-            // Only create one entry per IP (deduplicate multiple open ports)
-            if !discovered.contains_key(&id) {
-                // Try to get printer details via IPP if port 631 is open
-                let name = if port == 631 {
-                    query_ipp_printer_name(&ip).await.unwrap_or_else(|| format!("Printer at {}", ip))
-                } else {
-                    format!("Printer at {}", ip)
-                };
-
-                // Try to detect vendor from hostname or reverse DNS
-                let vendor = query_printer_vendor(&ip).await;
-
-                let detected_protocol = if port == 9100 {
-                    "escpos".to_string()
-                } else {
-                    "unknown".to_string()
-                };
-
-                let printer = DiscoveredPrinter {
-                    id: id.clone(),
-                    name: name.clone(),
-                    connection_type: "network".to_string(),
-                    address: format!("{}:{}", ip, if port == 9100 { 9100 } else { port }), // Prefer 9100
-                    vendor,
-                    capabilities: Some(serde_json::json!({
-                        "ports": {
-                            "9100": port == 9100,
-                            "631": port == 631,
-                            "515": port == 515,
-                        },
-                        "protocol": protocol,
-                    })),
-                    protocol: detected_protocol,
-                };
-
-                discovered.insert(id.clone(), printer);
-                info!("Discovered TCP printer: {} at {}:{}", name, ip, port);
-            }
-        }
+    collect_open_ports(scan_tasks, &mut open).await;
+
+    let mut printers = Vec::new();
+    for (ip, mut ports) in open {
+        ports.sort_unstable();
+        let open_ports: Vec<&ScanPort> = profile.ports.iter().filter(|p| ports.contains(&p.port)).collect();
+
+        // Prefer a raw port (profile order puts 9100 first) for the printer address
+        let Some(primary) = open_ports.iter().find(|p| p.raw).or(open_ports.first()).copied() else {
+            continue;
+        };
+
+        // Try to get printer details via IPP if port 631 is open
+        let name = if ports.contains(&631) {
+            query_ipp_printer_name(&ip).await.unwrap_or_else(|| format!("Printer at {}", ip))
+        } else {
+            format!("Printer at {}", ip)
+        };
+
+        // Try to detect vendor from hostname or reverse DNS
+        let vendor = query_printer_vendor(&ip).await;
+
+        // Raw port = ESC/POS as-is; IPP/LPD/HTTP may not be ESC/POS
+        let detected_protocol = if primary.raw { "escpos" } else { "unknown" };
+
+        let port_map: serde_json::Map<String, serde_json::Value> = profile
+            .ports
+            .iter()
+            .map(|p| (p.port.to_string(), serde_json::Value::Bool(ports.contains(&p.port))))
+            .collect();
+
+        info!("Discovered TCP printer: {} at {} (open ports: {:?})", name, ip, ports);
+        printers.push(DiscoveredPrinter {
+            id: format!("tcp_{}", ip.replace('.', "_")),
+            name,
+            connection_type: "network".to_string(),
+            address: format!("{}:{}", ip, primary.port),
+            vendor,
+            capabilities: Some(serde_json::json!({
+                "ports": port_map,
+                "open_ports": open_ports
+                    .iter()
+                    .map(|p| serde_json::json!({ "port": p.port, "label": p.label }))
+                    .collect::<Vec<_>>(),
+                "protocol": primary.label,
+            })),
+            protocol: detected_protocol.to_string(),
+        });
     }
 
-    let printers: Vec<DiscoveredPrinter> = discovered.into_values().collect();
     info!("TCP port scan complete: {} printers found", printers.len());
 
     Ok(printers)
 }
 
+/// Wait for a batch of connection attempts and record the open ports per IP
+async fn collect_open_ports(
+    tasks: Vec<tokio::task::JoinHandle<Option<(String, u16)>>>,
+    open: &mut HashMap<String, Vec<u16>>,
+) {
+    for (ip, port) in futures_util::future::join_all(tasks).await.into_iter().flatten().flatten() {
+        debug!("Port {} open on {}", port, ip);
+        open.entry(ip).or_default().push(port);
+    }
+}
+
 /// Query IPP printer name via HTTP GET to /
 ///
 /// Many thermal printers expose a web interface on port 631 or 80
@@ -1435,3 +1464,60 @@ pub async fn probe_unknown_printers(printers: &mut [DiscoveredPrinter]) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(name: &str, address: &str) -> DiscoveredPrinter {
+        DiscoveredPrinter {
+            id: format!("net_{}", address),
+            name: name.to_string(),
+            connection_type: "network".to_string(),
+            address: address.to_string(),
+            vendor: "Epson".to_string(),
+            capabilities: None,
+            protocol: "unknown".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_merge_keeps_best_source_in_any_order() {
+        let mut results = DiscoveryResults::default();
+
+        // mDNS (port 631) lands first, TCP then ENPC find the same printer on 9100
+        results.merge(DiscoverySource::Mdns, vec![found("mdns name", "192.168.1.50:631")]);
+        assert!(results.contains_id("net_192.168.1.50:631"));
+        results.merge(DiscoverySource::Enpc, vec![found("TM-m30II", "192.168.1.50:9100")]);
+        results.merge(DiscoverySource::Tcp, vec![found("tcp name", "192.168.1.50:9100"), found("Star", "192.168.1.60:9100")]);
+
+        let mut printers = results.printers();
+        printers.sort_by(|a, b| a.address.cmp(&b.address));
+        assert_eq!(printers.len(), 2);
+        assert_eq!(printers[0].name, "TM-m30II");
+        assert_eq!(printers[1].name, "Star");
+    }
+
+    #[tokio::test]
+    async fn test_tcp_scan_records_open_ports_and_prefers_raw() {
+        let web = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let raw = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let (web_port, raw_port) = (web.local_addr().unwrap().port(), raw.local_addr().unwrap().port());
+
+        let profile = PortScanProfile {
+            ports: vec![ScanPort::new(web_port, "HTTP", false)],
+            batch_size: 4,
+            timeout_ms: 500,
+        }
+        .with_port(raw_port);
+
+        let printers = scan_subnet_tcp("127.0.0.1/30", &profile).await.unwrap();
+        let printer = printers.iter().find(|p| p.id == "tcp_127_0_0_1").unwrap();
+
+        assert_eq!(printer.address, format!("127.0.0.1:{}", raw_port));
+        assert_eq!(printer.protocol, "escpos");
+        let capabilities = printer.capabilities.as_ref().unwrap();
+        assert_eq!(capabilities["open_ports"].as_array().unwrap().len(), 2);
+        assert_eq!(capabilities["ports"][web_port.to_string()], true);
+    }
+}
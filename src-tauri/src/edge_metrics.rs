@@ -0,0 +1,286 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Histogram bucket bounds (seconds) for Edge Function latency
+const BUCKETS: [f64; 8] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Calls the health indicator looks at (all actions, newest last)
+const RECENT_CALLS: usize = 30;
+
+/// Recent calls older than this don't count for health: a quiet backend isn't a slow one
+const RECENT_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// p95 latency above this is "slow"
+const SLOW_P95: Duration = Duration::from_secs(2);
+
+/// Share of failed recent calls at which the backend counts as failing
+const FAILING_RATE: f64 = 0.5;
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry { actions: BTreeMap::new(), recent: VecDeque::new() });
+
+struct Registry {
+    actions: BTreeMap<String, ActionSeries>,
+    recent: VecDeque<Call>,
+}
+
+/// How an Edge Function call ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ok,
+    /// 401: the printer token expired or was revoked
+    Unauthorized,
+    /// Network error, timeout, non-2xx or unreadable response
+    Error,
+}
+
+struct Call {
+    at: Instant,
+    elapsed: Duration,
+    outcome: Outcome,
+}
+
+#[derive(Default)]
+struct ActionSeries {
+    count: u64,
+    errors: u64,
+    unauthorized: u64,
+    sum_secs: f64,
+    max_secs: f64,
+    /// Calls at or under each `BUCKETS` bound
+    buckets: [u64; BUCKETS.len()],
+}
+
+/// Counters of one action (`get_metrics` → `edge`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActionSummary {
+    pub count: u64,
+    /// Failed calls, 401s included
+    pub errors: u64,
+    pub unauthorized: u64,
+    pub error_rate: f64,
+    pub avg_ms: u64,
+    pub max_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeStatus {
+    /// No calls in the last 10 minutes
+    Unknown,
+    Ok,
+    /// Answering, but p95 latency over 2s
+    Slow,
+    /// Half or more of the recent calls failed
+    Failing,
+    /// The last call was refused with 401: pairing has to be renewed
+    Unauthorized,
+}
+
+/// Backend health from the recent Edge Function calls, so the dashboard can tell "backend
+/// slow" from "printers slow"
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EdgeHealth {
+    pub status: EdgeStatus,
+    pub recent_calls: usize,
+    pub p95_ms: u64,
+    pub error_rate: f64,
+    /// Seconds since the last successful call (None: none since start)
+    pub last_success_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EdgeMetrics {
+    pub actions: BTreeMap<String, ActionSummary>,
+    pub health: EdgeHealth,
+}
+
+fn with_registry<T>(f: impl FnOnce(&mut Registry) -> T) -> T {
+    f(&mut REGISTRY.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Record one Edge Function call
+pub fn record(action: &str, elapsed: Duration, outcome: Outcome) {
+    record_at(action, elapsed, outcome, Instant::now());
+}
+
+fn record_at(action: &str, elapsed: Duration, outcome: Outcome, at: Instant) {
+    with_registry(|r| {
+        let series = r.actions.entry(action.to_string()).or_default();
+        let secs = elapsed.as_secs_f64();
+        series.count += 1;
+        series.sum_secs += secs;
+        series.max_secs = series.max_secs.max(secs);
+        match outcome {
+            Outcome::Ok => {}
+            Outcome::Unauthorized => {
+                series.errors += 1;
+                series.unauthorized += 1;
+            }
+            Outcome::Error => series.errors += 1,
+        }
+        for (bucket, bound) in series.buckets.iter_mut().zip(BUCKETS) {
+            if secs <= bound {
+                *bucket += 1;
+            }
+        }
+
+        if r.recent.len() == RECENT_CALLS {
+            r.recent.pop_front();
+        }
+        r.recent.push_back(Call { at, elapsed, outcome });
+    });
+}
+
+fn health_of(recent: &VecDeque<Call>, now: Instant) -> EdgeHealth {
+    let last_success_secs = recent
+        .iter()
+        .rev()
+        .find(|c| c.outcome == Outcome::Ok)
+        .map(|c| now.saturating_duration_since(c.at).as_secs());
+    let calls: Vec<&Call> = recent.iter().filter(|c| now.saturating_duration_since(c.at) <= RECENT_WINDOW).collect();
+    if calls.is_empty() {
+        return EdgeHealth { status: EdgeStatus::Unknown, recent_calls: 0, p95_ms: 0, error_rate: 0.0, last_success_secs };
+    }
+
+    let mut latencies: Vec<Duration> = calls.iter().map(|c| c.elapsed).collect();
+    latencies.sort_unstable();
+    let p95 = latencies[(latencies.len() * 95 / 100).min(latencies.len() - 1)];
+    let failed = calls.iter().filter(|c| c.outcome != Outcome::Ok).count();
+    let error_rate = failed as f64 / calls.len() as f64;
+
+    let status = if calls.last().is_some_and(|c| c.outcome == Outcome::Unauthorized) {
+        EdgeStatus::Unauthorized
+    } else if error_rate >= FAILING_RATE {
+        EdgeStatus::Failing
+    } else if p95 > SLOW_P95 {
+        EdgeStatus::Slow
+    } else {
+        EdgeStatus::Ok
+    };
+    EdgeHealth {
+        status,
+        recent_calls: calls.len(),
+        p95_ms: p95.as_millis() as u64,
+        error_rate: (error_rate * 1000.0).round() / 1000.0,
+        last_success_secs,
+    }
+}
+
+/// Backend health right now (`get_connection_state`)
+pub fn health() -> EdgeHealth {
+    with_registry(|r| health_of(&r.recent, Instant::now()))
+}
+
+/// Per-action counters and backend health (`get_metrics`, /api/metrics/json)
+pub fn snapshot() -> EdgeMetrics {
+    with_registry(|r| EdgeMetrics {
+        actions: r
+            .actions
+            .iter()
+            .map(|(action, s)| {
+                let summary = ActionSummary {
+                    count: s.count,
+                    errors: s.errors,
+                    unauthorized: s.unauthorized,
+                    error_rate: if s.count == 0 { 0.0 } else { s.errors as f64 / s.count as f64 },
+                    avg_ms: if s.count == 0 { 0 } else { (s.sum_secs * 1000.0 / s.count as f64).round() as u64 },
+                    max_ms: (s.max_secs * 1000.0).round() as u64,
+                };
+                (action.clone(), summary)
+            })
+            .collect(),
+        health: health_of(&r.recent, Instant::now()),
+    })
+}
+
+/// Prometheus text for /api/metrics: a latency histogram and error / 401 counters per action
+pub fn export_prometheus() -> String {
+    with_registry(|r| {
+        if r.actions.is_empty() {
+            return String::new();
+        }
+        let mut out = String::from(
+            "\n# HELP daemon_edge_call_duration_seconds Supabase Edge Function call latency\n\
+             # TYPE daemon_edge_call_duration_seconds histogram\n",
+        );
+        for (action, s) in &r.actions {
+            for (bound, count) in BUCKETS.iter().zip(s.buckets) {
+                out.push_str(&format!(
+                    "daemon_edge_call_duration_seconds_bucket{{action=\"{action}\",le=\"{bound}\"}} {count}\n"
+                ));
+            }
+            out.push_str(&format!(
+                "daemon_edge_call_duration_seconds_bucket{{action=\"{action}\",le=\"+Inf\"}} {}\n\
+                 daemon_edge_call_duration_seconds_sum{{action=\"{action}\"}} {:.3}\n\
+                 daemon_edge_call_duration_seconds_count{{action=\"{action}\"}} {}\n",
+                s.count, s.sum_secs, s.count
+            ));
+        }
+        let counters: [(&str, &str, fn(&ActionSeries) -> u64); 2] = [
+            ("daemon_edge_call_errors_total", "Failed Edge Function calls (401s included)", |s| s.errors),
+            ("daemon_edge_call_unauthorized_total", "Edge Function calls refused with 401", |s| s.unauthorized),
+        ];
+        for (name, help, value) in counters {
+            out.push_str(&format!("\n# HELP {name} {help}\n# TYPE {name} counter\n"));
+            for (action, s) in &r.actions {
+                out.push_str(&format!("{name}{{action=\"{action}\"}} {}\n", value(s)));
+            }
+        }
+        let health = health_of(&r.recent, Instant::now());
+        let healthy = matches!(health.status, EdgeStatus::Ok | EdgeStatus::Unknown) as u8;
+        out.push_str(&format!(
+            "\n# HELP daemon_edge_healthy Recent Edge Function calls are fast and succeed (0: slow, failing or 401)\n\
+             # TYPE daemon_edge_healthy gauge\n\
+             daemon_edge_healthy {healthy}\n"
+        ));
+        out
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calls(list: &[(u64, Outcome)], now: Instant) -> VecDeque<Call> {
+        list.iter()
+            .map(|(ms, outcome)| Call { at: now, elapsed: Duration::from_millis(*ms), outcome: *outcome })
+            .collect()
+    }
+
+    #[test]
+    fn test_edge_health() {
+        let now = Instant::now();
+        assert_eq!(health_of(&VecDeque::new(), now).status, EdgeStatus::Unknown);
+
+        let fast = calls(&[(120, Outcome::Ok); 10], now);
+        let health = health_of(&fast, now);
+        assert_eq!((health.status, health.p95_ms, health.last_success_secs), (EdgeStatus::Ok, 120, Some(0)));
+
+        let mut slow = calls(&[(300, Outcome::Ok); 18], now);
+        slow.extend(calls(&[(4000, Outcome::Ok); 2], now));
+        assert_eq!(health_of(&slow, now).status, EdgeStatus::Slow);
+
+        let failing = calls(&[(100, Outcome::Ok), (5000, Outcome::Error), (5000, Outcome::Error)], now);
+        let health = health_of(&failing, now);
+        assert_eq!((health.status, health.error_rate), (EdgeStatus::Failing, 0.667));
+
+        let revoked = calls(&[(100, Outcome::Ok), (100, Outcome::Ok), (80, Outcome::Unauthorized)], now);
+        assert_eq!(health_of(&revoked, now).status, EdgeStatus::Unauthorized);
+
+        // Old calls don't count
+        let later = now + RECENT_WINDOW + Duration::from_secs(1);
+        let health = health_of(&failing, later);
+        assert_eq!(health.status, EdgeStatus::Unknown);
+        assert_eq!(health.last_success_secs, Some(RECENT_WINDOW.as_secs() + 1));
+
+        record_at("test-action", Duration::from_millis(300), Outcome::Ok, now);
+        record_at("test-action", Duration::from_millis(700), Outcome::Unauthorized, now);
+        let summary = snapshot().actions["test-action"].clone();
+        assert_eq!((summary.count, summary.errors, summary.unauthorized, summary.avg_ms), (2, 1, 1, 500));
+        let prometheus = export_prometheus();
+        assert!(prometheus.contains("daemon_edge_call_duration_seconds_bucket{action=\"test-action\",le=\"0.5\"} 1\n"));
+        assert!(prometheus.contains("daemon_edge_call_unauthorized_total{action=\"test-action\"} 1\n"));
+    }
+}
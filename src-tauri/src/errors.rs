@@ -1,3 +1,5 @@
+use crate::troubleshoot::{classify_error, FailureClass};
+use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -18,6 +20,9 @@ pub enum DaemonError {
     #[error("Bluetooth error: {0}")]
     Bluetooth(String),
 
+    #[error("Serial port error: {0}")]
+    Serial(String),
+
     #[error("Network error: {0}")]
     Network(String),
 
@@ -33,6 +38,17 @@ pub enum DaemonError {
     #[error("Print job failed: {0}")]
     PrintJob(String),
 
+    /// The write went through but the printer reported it couldn't print it
+    #[error("Printer rejected the job: {0}")]
+    WriteRejected(String),
+
+    /// Stopped at a safe point (shutdown or pause) before anything reached the printer
+    #[error("Print job interrupted: {0}")]
+    Interrupted(String),
+
+    #[error("Barcode error: {0}")]
+    Barcode(#[from] eatsome_escpos::barcode::BarcodeError),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -44,3 +60,84 @@ pub enum DaemonError {
 }
 
 pub type Result<T> = std::result::Result<T, DaemonError>;
+
+/// How the job processor treats a failed print
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryClass {
+    /// Likely to pass on its own (timeouts, a busy port): retried with backoff
+    Retryable,
+    /// Waits on someone at the printer (switched off, cover open, paper out): retried on
+    /// a slow interval without using up the job's retries
+    Blocked,
+    /// Fails the same way every time (printer removed, job that can't be encoded): no retry
+    Permanent,
+}
+
+impl RetryClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetryClass::Retryable => "retryable",
+            RetryClass::Blocked => "blocked",
+            RetryClass::Permanent => "permanent",
+        }
+    }
+}
+
+impl DaemonError {
+    /// Classify a failed print for the retry policy and the job log
+    pub fn retry_class(&self) -> RetryClass {
+        match self {
+            DaemonError::Config(_) | DaemonError::PrinterNotFound(_) | DaemonError::Barcode(_) | DaemonError::Json(_) => {
+                RetryClass::Permanent
+            }
+            DaemonError::PrinterOffline(_) => RetryClass::Blocked,
+            // Open breaker, quarantine: the printer is known to be down
+            DaemonError::PrintJob(msg)
+                if msg.starts_with("Circuit breaker OPEN") || msg.ends_with("is quarantined") || msg.starts_with("No printer available") =>
+            {
+                RetryClass::Blocked
+            }
+            // The printer's own status: only an overheated head recovers by itself
+            DaemonError::WriteRejected(reason) => {
+                let reason = reason.to_lowercase();
+                if ["cover open", "paper", "cutter", "unrecoverable"].iter().any(|cause| reason.contains(cause)) {
+                    RetryClass::Blocked
+                } else {
+                    RetryClass::Retryable
+                }
+            }
+            DaemonError::Usb(_) | DaemonError::Bluetooth(_) | DaemonError::Serial(_) | DaemonError::Network(_) | DaemonError::Io(_) => {
+                match classify_error(self) {
+                    FailureClass::ConnectionRefused
+                    | FailureClass::Unreachable
+                    | FailureClass::UsbPermission
+                    | FailureClass::UsbNotFound
+                    | FailureClass::BluetoothUnavailable => RetryClass::Blocked,
+                    _ => RetryClass::Retryable,
+                }
+            }
+            _ => RetryClass::Retryable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_class() {
+        let class = |e: DaemonError| e.retry_class();
+        assert_eq!(class(DaemonError::Network("Connection timed out to 192.168.1.50:9100".into())), RetryClass::Retryable);
+        assert_eq!(class(DaemonError::Network("192.168.1.50:9100: Connection refused (os error 111)".into())), RetryClass::Blocked);
+        assert_eq!(class(DaemonError::WriteRejected("cover open".into())), RetryClass::Blocked);
+        assert_eq!(class(DaemonError::WriteRejected("printing stopped at paper end".into())), RetryClass::Blocked);
+        assert_eq!(class(DaemonError::WriteRejected("auto-recoverable error (print head overheated?)".into())), RetryClass::Retryable);
+        assert_eq!(class(DaemonError::Usb(rusb::Error::NoDevice)), RetryClass::Blocked);
+        assert_eq!(class(DaemonError::Usb(rusb::Error::Timeout)), RetryClass::Retryable);
+        assert_eq!(class(DaemonError::PrinterNotFound("p1".into())), RetryClass::Permanent);
+        assert_eq!(class(DaemonError::PrintJob("Printer p1 is quarantined".into())), RetryClass::Blocked);
+        assert_eq!(class(DaemonError::PrintJob("Total job timeout exceeded (120s)".into())), RetryClass::Retryable);
+    }
+}
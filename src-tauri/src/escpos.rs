@@ -1,430 +1,938 @@
-use image::DynamicImage;
+use crate::i18n::Locale;
+use crate::order_links::LinkPurpose;
+use crate::pickup_slip::PickupSlip;
+use crate::queue::JobKind;
+use crate::raster::{self, RasterDensity, RasterOptions};
+use crate::receipt_fields::ReceiptBlocks;
+use crate::venue_time;
 use serde::{Deserialize, Serialize};
-
-/// ESC/POS Commands (byte sequences)
-const ESC: u8 = 0x1b;
-const GS: u8 = 0x1d;
-const LF: u8 = 0x0a;
-const CR: u8 = 0x0d;
-
-/// DLE EOT Real-time status request: 0x10 0x04 n
-/// These commands are processed immediately by the printer (not buffered).
-const DLE: u8 = 0x10;
-const EOT: u8 = 0x04;
-
-/// DLE EOT status request types
-#[derive(Debug, Clone, Copy)]
-#[repr(u8)]
-pub enum StatusRequest {
-    /// Basic printer status (online/offline)
-    Printer = 1,
-    /// Offline cause (cover open, feed button pressed, error)
-    OfflineCause = 2,
-    /// Error cause (auto-cutter error, unrecoverable)
-    ErrorCause = 3,
-    /// Paper sensor status (near-end, paper-end)
-    PaperSensor = 4,
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::warn;
+
+// The protocol itself (commands, builder, preview parser) lives in the `eatsome-escpos`
+// crate; this module holds the daemon's receipts
+pub use eatsome_escpos::builder::*;
+pub use eatsome_escpos::parser::*;
+
+/// How much paper (and how many bytes) a kitchen ticket takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptLayout {
+    Normal,
+    /// Font B, tight line spacing, short separators, no blank lines between items: about
+    /// half the bytes, for Bluetooth printers (`PrinterConfig.compact`)
+    Compact,
 }
 
-/// Build a DLE EOT real-time status request command
-pub fn build_status_request(request: StatusRequest) -> Vec<u8> {
-    vec![DLE, EOT, request as u8]
-}
+/// Rendered item thumbnails (raster commands) by `PrintItem.image_url`, see `item_images.rs`
+pub type Thumbnails = HashMap<String, Arc<Vec<u8>>>;
 
-/// Build all 4 DLE EOT status requests concatenated into a single write
-pub fn build_full_status_request() -> Vec<u8> {
-    vec![
-        DLE, EOT, StatusRequest::Printer as u8,
-        DLE, EOT, StatusRequest::OfflineCause as u8,
-        DLE, EOT, StatusRequest::ErrorCause as u8,
-        DLE, EOT, StatusRequest::PaperSensor as u8,
-    ]
-}
+/// Most copies of one ticket sent to the printer at once
+pub const MAX_COPIES: u8 = 4;
 
-/// Paper width configuration
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub enum PaperWidth {
-    Width58mm = 32, // 32 characters per line
-    Width80mm = 48, // 48 characters per line
-}
+/// Format kitchen receipt.
+///
+/// With `copies` > 1 the ticket is repeated in the same buffer, each copy labelled
+/// "COPY n/N" and separated by a partial cut; the feed and full cut come once, after the
+/// last copy. The printer gets one send instead of one per copy.
+///
+/// Items whose `image_url` has an entry in `thumbnails` get the image printed above
+/// their line; the others print text only.
+///
+/// `ReceiptLayout::Compact` prints the same content in Font B with tighter lines, a
+/// double-height (not double-size) station, short separators and no blank lines.
+#[allow(clippy::too_many_arguments)]
+pub fn format_kitchen_receipt(
+    station: &str,
+    order_number: &str,
+    order_type: Option<&str>,
+    table_number: Option<&str>,
+    customer_name: Option<&str>,
+    priority: u8,
+    items: &[PrintItem],
+    timestamp: i64,
+    copies: u8,
+    reference: Option<&str>,
+    thumbnails: &Thumbnails,
+    paper_width: PaperWidth,
+    layout: ReceiptLayout,
+    blocks: &ReceiptBlocks,
+) -> Vec<u8> {
+    let copies = copies.clamp(1, MAX_COPIES);
+    let compact = layout == ReceiptLayout::Compact;
+    let mut builder = ESCPOSBuilder::new(paper_width);
+    builder.initialize();
+    if compact {
+        // Font B is 17 dots high: 20-dot lines instead of the default ~30
+        builder.font(Font::B).line_spacing(20);
+    }
+    let separator = |builder: &mut ESCPOSBuilder, c: char| {
+        if compact {
+            builder.short_line(c);
+        } else {
+            builder.draw_line(c);
+        }
+    };
 
-/// Text alignment
-#[derive(Debug, Clone, Copy)]
-pub enum Alignment {
-    Left = 0,
-    Center = 1,
-    Right = 2,
-}
+    // Timestamp
+    let time_str = venue_time::at_millis(timestamp)
+        .map(|dt| dt.format("%H:%M").to_string())
+        .unwrap_or_else(|| "??:??".to_string());
 
-/// Text size
-#[derive(Debug, Clone, Copy)]
-pub enum TextSize {
-    Normal = 0x00,
-    DoubleWidth = 0x10,
-    DoubleHeight = 0x20,
-    DoubleBoth = 0x30,
-}
+    for copy in 1..=copies {
+        if let Some(logo) = &blocks.logo {
+            builder.align(Alignment::Center).raw(logo).new_line();
+        }
+        builder
+            .align(Alignment::Center)
+            .size(if compact { TextSize::DoubleHeight } else { TextSize::DoubleBoth })
+            .bold(true)
+            .text(&station.to_uppercase())
+            .new_line()
+            .bold(false)
+            .size(TextSize::Normal);
 
-/// Font selection
-#[derive(Debug, Clone, Copy)]
-pub enum Font {
-    A = 0, // Standard (12x24)
-    B = 1, // Compressed (9x17)
-}
+        if copies > 1 {
+            builder.text(&format!("COPY {}/{}", copy, copies)).new_line();
+        }
+        text_block(&mut builder, &blocks.header);
 
-/// Character code page for international characters
-#[derive(Debug, Clone, Copy)]
-pub enum CodePage {
-    PC437USA = 0,
-    Katakana = 1,
-    PC850Multilingual = 2,
-    PC860Portuguese = 3,
-    PC863CanadianFrench = 4,
-    PC865Nordic = 5,
-    WPC1252Latin1 = 16,
-    PC866Cyrillic = 17,
-    PC852Latin2 = 18,
-    PC858Euro = 19,
-}
+        if !compact {
+            builder.draw_line('=');
+        }
 
-/// Barcode type
-#[derive(Debug, Clone, Copy)]
-pub enum BarcodeType {
-    UPCA = 65,
-    UPCE = 66,
-    EAN13 = 67,
-    EAN8 = 68,
-    CODE39 = 69,
-    ITF = 70,
-    CODABAR = 71,
-    CODE93 = 72,
-    CODE128 = 73,
-}
+        // Order information
+        builder
+            .align(Alignment::Left)
+            .size(TextSize::DoubleWidth)
+            .bold(true)
+            .text(&format!("ORDER {}", order_number))
+            .new_line()
+            .size(TextSize::Normal)
+            .bold(false);
 
-/// ESC/POS Command Builder
-pub struct ESCPOSBuilder {
-    buffer: Vec<u8>,
-    paper_width: PaperWidth,
-}
+        if let Some(order_type) = order_type {
+            builder.text(&format!("Type: {}", order_type.to_uppercase())).new_line();
+        }
 
-impl ESCPOSBuilder {
-    pub fn new(paper_width: PaperWidth) -> Self {
-        Self {
-            buffer: Vec::new(),
-            paper_width,
+        if let Some(table) = table_number {
+            builder.text(&format!("Table: {}", table)).new_line();
         }
-    }
 
-    /// Get the built command buffer
-    pub fn build(self) -> Vec<u8> {
-        self.buffer
-    }
+        if let Some(customer) = customer_name {
+            builder.text(&format!("Customer: {}", customer)).new_line();
+        }
 
-    /// Initialize printer
-    pub fn initialize(&mut self) -> &mut Self {
-        self.buffer.extend_from_slice(&[ESC, 0x40]);
-        self
-    }
+        // Priority indicator
+        if priority == 1 {
+            builder.inverse(true).bold(true).text(" URGENT ").inverse(false).bold(false).new_line();
+        }
 
-    /// Add text
-    pub fn text(&mut self, text: &str) -> &mut Self {
-        self.buffer.extend_from_slice(text.as_bytes());
-        self
-    }
+        separator(&mut builder, '-');
+
+        // Items
+        for item in items {
+            if let Some(thumbnail) = item.image_url.as_deref().and_then(|url| thumbnails.get(url)) {
+                builder.raw(thumbnail).new_line();
+            }
+
+            builder
+                .bold(true)
+                .size(TextSize::DoubleHeight)
+                .text(&format!("{}x {}", item.quantity, item.name))
+                .new_line()
+                .size(TextSize::Normal)
+                .bold(false);
+
+            // Modifiers
+            for modifier in &item.modifiers {
+                builder.text(&format!("  + {}", modifier)).new_line();
+            }
+
+            // Notes
+            if let Some(notes) = &item.notes {
+                builder.underline(true).text(&format!("  NOTE: {}", notes)).underline(false).new_line();
+            }
 
-    /// Add line feed
-    pub fn feed(&mut self, lines: u8) -> &mut Self {
-        for _ in 0..lines {
-            self.buffer.push(LF);
+            if !compact {
+                builder.feed(1);
+            }
         }
-        self
-    }
 
-    /// Add carriage return + line feed
-    pub fn new_line(&mut self) -> &mut Self {
-        self.buffer.extend_from_slice(&[CR, LF]);
-        self
-    }
+        separator(&mut builder, '-');
+        builder.align(Alignment::Center).text(&format!("Printed: {}", time_str));
 
-    /// Set text alignment
-    pub fn align(&mut self, alignment: Alignment) -> &mut Self {
-        self.buffer.extend_from_slice(&[ESC, 0x61, alignment as u8]);
-        self
-    }
+        // Correlation code support can search for
+        match reference.filter(|r| !r.is_empty()) {
+            Some(reference) if compact => {
+                builder.text(&format!("  ref {}", reference)).new_line();
+            }
+            Some(reference) => {
+                builder.new_line().text(&format!("ref {}", reference)).new_line();
+            }
+            None => {
+                builder.new_line();
+            }
+        }
+        text_block(&mut builder, &blocks.footer);
 
-    /// Set text size
-    pub fn size(&mut self, size: TextSize) -> &mut Self {
-        self.buffer.extend_from_slice(&[GS, 0x21, size as u8]);
-        self
+        // Tear-off between copies; the last copy gets the full cut below
+        if copy < copies {
+            builder.cut(true);
+        }
     }
 
-    /// Enable/disable bold
-    pub fn bold(&mut self, enabled: bool) -> &mut Self {
-        self.buffer.extend_from_slice(&[ESC, 0x45, if enabled { 1 } else { 0 }]);
-        self
-    }
+    builder.feed(2).cut(false);
+    builder.build()
+}
 
-    /// Enable/disable underline
-    pub fn underline(&mut self, enabled: bool) -> &mut Self {
-        self.buffer.extend_from_slice(&[ESC, 0x2d, if enabled { 1 } else { 0 }]);
-        self
+/// Venue header or footer lines (`receipt_fields.rs`), centered
+fn text_block(builder: &mut ESCPOSBuilder, lines: &[String]) {
+    if lines.is_empty() {
+        return;
     }
-
-    /// Enable/disable inverse (white text on black)
-    pub fn inverse(&mut self, enabled: bool) -> &mut Self {
-        self.buffer.extend_from_slice(&[GS, 0x42, if enabled { 1 } else { 0 }]);
-        self
+    builder.align(Alignment::Center);
+    for line in lines {
+        builder.text(line).new_line();
     }
+}
+
+/// Print item for receipts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrintItem {
+    pub quantity: u32,
+    pub name: String,
+    pub modifiers: Vec<String>,
+    pub notes: Option<String>,
+    /// Item photo for a thumbnail on the receipt (`AppConfig.item_images`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    /// Prices and tax for customer receipts; kitchen tickets ignore it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<LinePricing>,
+}
+
+/// Price, tax and discount of one receipt line. Amounts in cents, tax included.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinePricing {
+    /// Price per piece, or per kg for weighted items
+    pub unit_price_cents: Option<i64>,
+    /// Weighed quantity; the item is priced per kg and `quantity` is not used for the total
+    pub weight_grams: Option<u32>,
+    /// Amount charged for the line as computed upstream (wins over our own arithmetic)
+    pub line_total_cents: Option<i64>,
+    /// VAT rate in percent (9.0, 21.0)
+    pub tax_rate: Option<f64>,
+    /// Discount on the line, positive
+    pub discount_cents: Option<i64>,
+}
 
-    /// Draw horizontal line
-    pub fn draw_line(&mut self, char: char) -> &mut Self {
-        let line: String = char.to_string().repeat(self.paper_width as usize);
-        self.text(&line).new_line()
+impl PrintItem {
+    /// Amount charged for the line: upstream `line_total_cents`, otherwise unit price ×
+    /// quantity (or × weight) minus discount. None without prices.
+    pub fn line_total(&self) -> Option<i64> {
+        let pricing = self.pricing.as_ref()?;
+        if let Some(total) = pricing.line_total_cents {
+            return Some(total);
+        }
+        let unit = pricing.unit_price_cents?;
+        let gross = match pricing.weight_grams {
+            Some(grams) => (unit * grams as i64 + 500) / 1000,
+            None => unit * self.quantity as i64,
+        };
+        Some(gross - pricing.discount_cents.unwrap_or(0))
     }
+}
 
-    /// Print barcode
-    pub fn barcode(&mut self, data: &str, barcode_type: BarcodeType) -> &mut Self {
-        // Set barcode height
-        self.buffer.extend_from_slice(&[GS, 0x68, 80]); // 80 dots
+/// Totals of one VAT rate over a receipt
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaxLine {
+    /// Percent
+    pub rate: f64,
+    /// Line totals at this rate, tax included
+    pub gross_cents: i64,
+    pub tax_cents: i64,
+}
 
-        // Set barcode width
-        self.buffer.extend_from_slice(&[GS, 0x77, 2]); // module width 2
+/// Per-rate VAT summary, lowest rate first. Lines without a price or rate are left out.
+pub fn tax_summary(items: &[PrintItem]) -> Vec<TaxLine> {
+    // Keyed by basis points so 9 and 9.0 land in the same group
+    let mut by_rate: std::collections::BTreeMap<i64, i64> = std::collections::BTreeMap::new();
+    for item in items {
+        let (Some(total), Some(rate)) = (item.line_total(), item.pricing.as_ref().and_then(|p| p.tax_rate)) else {
+            continue;
+        };
+        *by_rate.entry((rate * 100.0).round() as i64).or_default() += total;
+    }
+    by_rate
+        .into_iter()
+        .map(|(basis_points, gross_cents)| TaxLine {
+            rate: basis_points as f64 / 100.0,
+            gross_cents,
+            // Prices include VAT: tax = gross × rate / (100% + rate)
+            tax_cents: (gross_cents as f64 * basis_points as f64 / (10_000 + basis_points) as f64).round() as i64,
+        })
+        .collect()
+}
 
-        // Print barcode
-        self.buffer.extend_from_slice(&[GS, 0x6b, barcode_type as u8, data.len() as u8]);
-        self.buffer.extend_from_slice(data.as_bytes());
+/// Order-level amounts of a customer receipt, on top of its lines. Cents, tax included.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckTotals {
+    /// Discount on the whole order, positive; spread over the VAT rates by their share
+    pub discount_cents: Option<i64>,
+    /// Tip; not subject to VAT
+    pub tip_cents: Option<i64>,
+    /// How the order was paid ("PIN", "Contant"). None: not paid yet (guest check)
+    pub payment_method: Option<String>,
+    /// Amount handed over when it differs from the total (cash), to print the change
+    pub tendered_cents: Option<i64>,
+}
 
-        self
+/// `taxes` after an order discount, divided over the rates by their gross share (the
+/// last rate takes the rounding remainder)
+fn discounted_taxes(taxes: Vec<TaxLine>, discount_cents: i64, subtotal_cents: i64) -> Vec<TaxLine> {
+    if discount_cents == 0 || subtotal_cents <= 0 {
+        return taxes;
     }
+    let taxed: i64 = taxes.iter().map(|t| t.gross_cents).sum();
+    // Only the taxed part of the subtotal carries its share of the discount
+    let mut remaining = (discount_cents as i128 * taxed as i128 / subtotal_cents as i128) as i64;
+    let count = taxes.len();
+    taxes
+        .into_iter()
+        .enumerate()
+        .map(|(i, tax)| {
+            let share = if i + 1 == count {
+                remaining
+            } else {
+                (discount_cents as i128 * tax.gross_cents as i128 / subtotal_cents as i128) as i64
+            };
+            remaining -= share;
+            let gross_cents = tax.gross_cents - share;
+            let basis_points = (tax.rate * 100.0).round();
+            TaxLine {
+                rate: tax.rate,
+                gross_cents,
+                tax_cents: (gross_cents as f64 * basis_points / (10_000.0 + basis_points)).round() as i64,
+            }
+        })
+        .collect()
+}
 
-    /// Print QR code
-    pub fn qr_code(&mut self, data: &str, size: u8) -> &mut Self {
-        let data_bytes = data.as_bytes();
-        let pl = ((data_bytes.len() + 3) % 256) as u8;
-        let ph = ((data_bytes.len() + 3) / 256) as u8;
+/// Cents as "12,50" (nl) or "12.50" (en)
+fn money(cents: i64, locale: Locale) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let separator = match locale {
+        Locale::Nl => ',',
+        Locale::En => '.',
+    };
+    format!("{}{}{}{:02}", sign, cents.abs() / 100, separator, cents.abs() % 100)
+}
 
-        // QR code model
-        self.buffer.extend_from_slice(&[GS, 0x28, 0x6b, 0x04, 0x00, 0x31, 0x41, 0x32, 0x00]);
+/// Customer receipt texts per UI language (ASCII only, like the test print)
+struct CustomerReceiptLabels {
+    order: &'static str,
+    discount: &'static str,
+    subtotal: &'static str,
+    tip: &'static str,
+    total: &'static str,
+    paid: &'static str,
+    change: &'static str,
+    tax: &'static str,
+    tax_over: &'static str,
+    thanks: &'static str,
+    scan_status: &'static str,
+    scan_feedback: &'static str,
+}
 
-        // QR code size
-        self.buffer.extend_from_slice(&[GS, 0x28, 0x6b, 0x03, 0x00, 0x31, 0x43, size]);
+fn customer_receipt_labels(locale: Locale) -> CustomerReceiptLabels {
+    match locale {
+        Locale::Nl => CustomerReceiptLabels {
+            order: "Bestelling",
+            discount: "Korting",
+            subtotal: "Subtotaal",
+            tip: "Fooi",
+            total: "TOTAAL",
+            paid: "Betaald",
+            change: "Wisselgeld",
+            tax: "BTW",
+            tax_over: "over",
+            thanks: "Bedankt en tot ziens!",
+            scan_status: "Scan om je bestelling te volgen",
+            scan_feedback: "Hoe was het? Scan en laat het ons weten",
+        },
+        Locale::En => CustomerReceiptLabels {
+            order: "Order",
+            discount: "Discount",
+            subtotal: "Subtotal",
+            tip: "Tip",
+            total: "TOTAL",
+            paid: "Paid",
+            change: "Change",
+            tax: "VAT",
+            tax_over: "on",
+            thanks: "Thank you, see you soon!",
+            scan_status: "Scan to follow your order",
+            scan_feedback: "How was it? Scan to let us know",
+        },
+    }
+}
 
-        // QR code error correction level (L=48, M=49, Q=50, H=51)
-        self.buffer.extend_from_slice(&[GS, 0x28, 0x6b, 0x03, 0x00, 0x31, 0x45, 0x31]);
+/// Customer receipt: priced lines (weighed items with their weight and price per kg),
+/// line and order discounts, subtotal, tip, the total, how it was paid and a VAT summary
+/// per rate. Without a payment method it is a guest check. `link` adds a QR code to the
+/// order's status or feedback page (`order_links.rs`).
+#[allow(clippy::too_many_arguments)]
+pub fn format_customer_receipt(
+    template: &TestPrintTemplate,
+    order_number: &str,
+    items: &[PrintItem],
+    check: &CheckTotals,
+    timestamp: i64,
+    locale: Locale,
+    paper_width: PaperWidth,
+    blocks: &ReceiptBlocks,
+    link: Option<(LinkPurpose, &str)>,
+) -> Vec<u8> {
+    let labels = customer_receipt_labels(locale);
+    let mut builder = ESCPOSBuilder::new(paper_width);
+
+    builder.initialize();
+    template_logo(&mut builder, template, paper_width);
 
-        // Store data
-        self.buffer.extend_from_slice(&[GS, 0x28, 0x6b, pl, ph, 0x31, 0x50, 0x30]);
-        self.buffer.extend_from_slice(data_bytes);
+    let time_str = venue_time::at_millis(timestamp)
+        .map(|dt| dt.format("%d-%m-%Y %H:%M").to_string())
+        .unwrap_or_default();
 
-        // Print QR code
-        self.buffer.extend_from_slice(&[GS, 0x28, 0x6b, 0x03, 0x00, 0x31, 0x51, 0x30]);
+    builder
+        .align(Alignment::Center)
+        .size(TextSize::DoubleBoth)
+        .bold(true)
+        .text(template.venue_name.as_deref().unwrap_or("Eatsome"))
+        .new_line()
+        .size(TextSize::Normal)
+        .bold(false);
+    text_block(&mut builder, &blocks.header);
+    builder
+        .align(Alignment::Left)
+        .draw_line('=')
+        .justify_text(&format!("{} {}", labels.order, order_number), &time_str)
+        .draw_line('-');
+
+    for item in items {
+        let pricing = item.pricing.clone().unwrap_or_default();
+        let total = item.line_total().map(|t| money(t, locale)).unwrap_or_default();
+        match pricing.weight_grams {
+            Some(grams) => {
+                builder.justify_text(&item.name, &total);
+                if let Some(unit) = pricing.unit_price_cents {
+                    builder
+                        .text(&format!("  {}.{:03} kg x {}/kg", grams / 1000, grams % 1000, money(unit, locale)))
+                        .new_line();
+                }
+            }
+            None => {
+                builder.justify_text(&format!("{}x {}", item.quantity, item.name), &total);
+                if let (Some(unit), true) = (pricing.unit_price_cents, item.quantity > 1) {
+                    builder.text(&format!("  {} x {}", item.quantity, money(unit, locale))).new_line();
+                }
+            }
+        }
+        for modifier in &item.modifiers {
+            builder.text(&format!("  + {}", modifier)).new_line();
+        }
+        // Upstream totals already have it subtracted; shown so the customer sees it
+        if let Some(discount) = pricing.discount_cents.filter(|d| *d != 0) {
+            builder.justify_text(&format!("  {}", labels.discount), &money(-discount, locale));
+        }
+    }
 
-        self
+    let subtotal: i64 = items.iter().filter_map(PrintItem::line_total).sum();
+    let discount = check.discount_cents.unwrap_or(0);
+    let tip = check.tip_cents.unwrap_or(0);
+    let total = subtotal - discount + tip;
+    builder.draw_line('-');
+    if discount != 0 || tip != 0 {
+        builder.justify_text(labels.subtotal, &money(subtotal, locale));
+        if discount != 0 {
+            builder.justify_text(labels.discount, &money(-discount, locale));
+        }
+        if tip != 0 {
+            builder.justify_text(labels.tip, &money(tip, locale));
+        }
     }
+    builder
+        .bold(true)
+        .justify_text(labels.total, &format!("EUR {}", money(total, locale)))
+        .bold(false);
 
-    /// Cut paper
-    pub fn cut(&mut self, partial: bool) -> &mut Self {
-        self.feed(3); // Feed before cut
-        self.buffer.extend_from_slice(&[GS, 0x56, if partial { 1 } else { 0 }]);
-        self
+    if let Some(method) = &check.payment_method {
+        let tendered = check.tendered_cents.unwrap_or(total);
+        builder.justify_text(&format!("{} ({})", labels.paid, method), &money(tendered, locale));
+        if tendered > total {
+            builder.justify_text(labels.change, &money(tendered - total, locale));
+        }
     }
 
-    /// Open cash drawer (if connected)
-    pub fn open_drawer(&mut self) -> &mut Self {
-        self.buffer.extend_from_slice(&[ESC, 0x70, 0, 25, 250]);
-        self
+    let taxes = discounted_taxes(tax_summary(items), discount, subtotal);
+    if !taxes.is_empty() {
+        builder.draw_line('-');
+        for tax in &taxes {
+            builder.justify_text(
+                &format!("{} {}% {} {}", labels.tax, tax.rate, labels.tax_over, money(tax.gross_cents, locale)),
+                &money(tax.tax_cents, locale),
+            );
+        }
     }
 
-    /// Open cash drawer on specific pin with custom timing
-    ///
-    /// # Arguments
-    /// * `pin` - Connector pin (2 or 5)
-    /// * `on_time_ms` - Pulse on-time in milliseconds (rounded to 2ms units)
-    /// * `off_time_ms` - Pulse off-time in milliseconds (rounded to 2ms units)
-    pub fn open_drawer_pin(&mut self, pin: u8, on_time_ms: u8, off_time_ms: u8) -> &mut Self {
-        let pin_val = if pin == 5 { 1 } else { 0 };
-        let t1 = on_time_ms / 2;
-        let t2 = off_time_ms / 2;
-        self.buffer.extend_from_slice(&[ESC, 0x70, pin_val, t1, t2]);
-        self
+    builder.draw_line('=').align(Alignment::Center).text(labels.thanks).new_line();
+    if let Some(footer) = &template.footer {
+        builder.text(footer).new_line();
     }
+    text_block(&mut builder, &blocks.footer);
 
-    /// Select font (Font A = standard 12x24, Font B = compressed 9x17)
-    pub fn font(&mut self, font: Font) -> &mut Self {
-        self.buffer.extend_from_slice(&[ESC, 0x4d, font as u8]);
-        self
+    if let Some((purpose, url)) = link {
+        let caption = match purpose {
+            LinkPurpose::Status => labels.scan_status,
+            LinkPurpose::Feedback => labels.scan_feedback,
+        };
+        builder.feed(1).qr_code(url, 6).feed(1).text(caption).new_line();
     }
 
-    /// Set custom line spacing (n units, printer-dependent: typically n/360 or n/180 inch)
-    pub fn line_spacing(&mut self, n: u8) -> &mut Self {
-        self.buffer.extend_from_slice(&[ESC, 0x33, n]);
-        self
+    builder.feed(3).cut(false);
+    builder.build()
+}
+
+/// Test print content (`AppConfig.test_print`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TestPrintTemplate {
+    /// Printed as the header; falls back to "Eatsome"
+    pub venue_name: Option<String>,
+    /// Logo image file printed above the header
+    pub logo_path: Option<String>,
+    /// Logo downloaded from Supabase storage, used when there is no `logo_path`
+    pub logo_url: Option<String>,
+    /// Also print the logo at the top of kitchen tickets (not on Bluetooth printers)
+    pub logo_on_kitchen_tickets: bool,
+    /// GS v 0 density for the logo (double modes print faster, coarser)
+    pub logo_density: RasterDensity,
+    /// Paper width, timestamp and printer name
+    pub printer_info: bool,
+    /// Bold / underline / inverse / size samples
+    pub formatting: bool,
+    /// QR code sample
+    pub qr_code: bool,
+    /// QR code contents
+    pub qr_url: String,
+    /// Free text line at the bottom (e.g. "Questions? Call 020-1234567")
+    pub footer: Option<String>,
+}
+
+impl Default for TestPrintTemplate {
+    fn default() -> Self {
+        Self {
+            venue_name: None,
+            logo_path: None,
+            logo_url: None,
+            logo_on_kitchen_tickets: false,
+            logo_density: RasterDensity::Normal,
+            printer_info: true,
+            formatting: true,
+            qr_code: true,
+            qr_url: "https://eatsome.nl".to_string(),
+            footer: None,
+        }
     }
+}
 
-    /// Reset line spacing to default (1/6 inch)
-    pub fn default_line_spacing(&mut self) -> &mut Self {
-        self.buffer.extend_from_slice(&[ESC, 0x32]);
-        self
+/// Receipt texts per UI language (ASCII only: printers use their own code page)
+struct TestPrintLabels {
+    title: &'static str,
+    working: &'static str,
+    paper_width: &'static str,
+    printer: &'static str,
+    time: &'static str,
+    formatting: &'static str,
+    bold: &'static str,
+    underlined: &'static str,
+    inverse: &'static str,
+    double_width: &'static str,
+    double_height: &'static str,
+    qr_code: &'static str,
+    setup_title: &'static str,
+    setup_ready: &'static str,
+    station: &'static str,
+    all_stations: &'static str,
+    setup_next: &'static str,
+    ruler_title: &'static str,
+    ruler_hint: &'static str,
+}
+
+fn test_print_labels(locale: Locale) -> TestPrintLabels {
+    match locale {
+        Locale::Nl => TestPrintLabels {
+            title: "PROEFAFDRUK",
+            working: "De printer werkt!",
+            paper_width: "Papierbreedte",
+            printer: "Printer",
+            time: "Tijd",
+            formatting: "Opmaak:",
+            bold: "Vetgedrukt",
+            underlined: "Onderstreept",
+            inverse: "Omgekeerd",
+            double_width: "Dubbel breed",
+            double_height: "Dubbel hoog",
+            qr_code: "QR-code test",
+            setup_title: "KLAAR!",
+            setup_ready: "Bestellingen worden vanaf nu hier geprint.",
+            station: "Station",
+            all_stations: "alle stations",
+            setup_next: "Wijs stations toe in je dashboard.",
+            ruler_title: "PAPIERBREEDTE",
+            ruler_hint: "Staat de liniaal op 1 regel (tot 48)? Dan is het 80mm papier. Breekt hij af na 32, dan 58mm.",
+        },
+        Locale::En => TestPrintLabels {
+            title: "TEST PRINT",
+            working: "Printer is working correctly!",
+            paper_width: "Paper width",
+            printer: "Printer",
+            time: "Time",
+            formatting: "Text Formatting Tests:",
+            bold: "Bold Text",
+            underlined: "Underlined Text",
+            inverse: "Inverse Text",
+            double_width: "Double Width",
+            double_height: "Double Height",
+            qr_code: "QR Code Test",
+            setup_title: "ALL SET!",
+            setup_ready: "Orders will print here from now on.",
+            station: "Station",
+            all_stations: "all stations",
+            setup_next: "Assign stations in your dashboard.",
+            ruler_title: "PAPER WIDTH",
+            ruler_hint: "Ruler on 1 line (up to 48)? Then the paper is 80mm. Breaks after 32? Then it is 58mm.",
+        },
     }
+}
 
-    /// Set right-side character spacing (0-255, in half-dot units)
-    pub fn char_spacing(&mut self, n: u8) -> &mut Self {
-        self.buffer.extend_from_slice(&[ESC, 0x20, n]);
-        self
+impl TestPrintTemplate {
+    /// Logo file to print: the local `logo_path`, else the downloaded `logo_url` once it
+    /// is on disk
+    pub fn logo_file(&self) -> Option<PathBuf> {
+        if let Some(path) = self.logo_path.as_deref() {
+            return Some(PathBuf::from(path));
+        }
+        let path = raster::downloaded_logo(self.logo_url.as_deref()?);
+        path.exists().then_some(path)
     }
+}
 
-    /// Select character code page for international character support
-    ///
-    /// Use `CodePage::WPC1252Latin1` for Western European (€, ü, é, etc.)
-    /// Use `CodePage::PC858Euro` for Euro symbol on older printers
-    pub fn code_page(&mut self, page: CodePage) -> &mut Self {
-        self.buffer.extend_from_slice(&[ESC, 0x74, page as u8]);
-        self
+/// Venue logo from the template, converted once per paper width and cached by file hash.
+/// None without a logo; a missing or broken one is skipped so the receipt still prints.
+pub fn venue_logo(template: &TestPrintTemplate, paper_width: PaperWidth) -> Option<Arc<Vec<u8>>> {
+    let path = template.logo_file()?;
+    let options = RasterOptions {
+        density: template.logo_density,
+        ..RasterOptions::default()
+    };
+    match raster::logo(&path, paper_width.dots(), &options) {
+        Ok(commands) => Some(commands),
+        Err(e) => {
+            warn!("Skipping logo {}: {}", path.display(), e);
+            None
+        }
     }
+}
 
-    /// Set width and height multiplier independently (1-8 each)
-    ///
-    /// More flexible than `size()` which uses predefined combinations.
-    /// width=2, height=3 means double-width, triple-height.
-    pub fn size_wh(&mut self, width: u8, height: u8) -> &mut Self {
-        let w = (width.clamp(1, 8) - 1) << 4;
-        let h = height.clamp(1, 8) - 1;
-        self.buffer.extend_from_slice(&[GS, 0x21, w | h]);
-        self
+fn template_logo(builder: &mut ESCPOSBuilder, template: &TestPrintTemplate, paper_width: PaperWidth) {
+    if let Some(commands) = venue_logo(template, paper_width) {
+        builder.align(Alignment::Center).raw(&commands).new_line();
     }
+}
 
-    /// Print raster bit image (monochrome bitmap)
-    ///
-    /// Converts DynamicImage to 1-bit bitmap and sends via GS v 0.
-    /// Automatically resizes to fit paper width.
-    pub fn raster_image(&mut self, img: &DynamicImage, max_width: u32) -> &mut Self {
-        let gray = img.to_luma8();
-        let (orig_width, orig_height) = gray.dimensions();
+/// Format test print from the venue's template
+pub fn format_test_print(
+    paper_width: PaperWidth,
+    template: &TestPrintTemplate,
+    locale: Locale,
+    printer_name: Option<&str>,
+) -> Vec<u8> {
+    let labels = test_print_labels(locale);
+    let mut builder = ESCPOSBuilder::new(paper_width);
 
-        // Resize if wider than max
-        let (width, height) = if orig_width > max_width {
-            let scale = max_width as f32 / orig_width as f32;
-            (max_width, (orig_height as f32 * scale) as u32)
-        } else {
-            (orig_width, orig_height)
-        };
+    builder.initialize();
+    template_logo(&mut builder, template, paper_width);
 
-        let resized = if width != orig_width {
-            img.resize(width, height, image::imageops::FilterType::Lanczos3)
-                .to_luma8()
-        } else {
-            gray
-        };
+    builder
+        .align(Alignment::Center)
+        .size(TextSize::DoubleBoth)
+        .bold(true)
+        .text(template.venue_name.as_deref().unwrap_or("Eatsome"))
+        .new_line()
+        .size(TextSize::Normal)
+        .text(labels.title)
+        .new_line()
+        .bold(false)
+        .draw_line('=')
+        .align(Alignment::Left)
+        .text(labels.working)
+        .new_line();
 
-        // Width in bytes (8 pixels per byte)
-        let byte_width = ((width + 7) / 8) as u16;
-
-        // GS v 0 - Print raster bit image
-        // m=0 (normal size)
-        self.buffer.extend_from_slice(&[GS, 0x76, 0x30, 0x00]);
-        self.buffer.push(byte_width as u8);          // xL
-        self.buffer.push((byte_width >> 8) as u8);   // xH
-        self.buffer.push(height as u8);               // yL
-        self.buffer.push((height >> 8) as u8);        // yH
-
-        // Convert pixels to 1-bit bitmap (black < 128 threshold)
-        for y in 0..height {
-            for bx in 0..byte_width {
-                let mut byte_val = 0u8;
-                for bit in 0..8u32 {
-                    let x = bx as u32 * 8 + bit;
-                    if x < width {
-                        let pixel = resized.get_pixel(x, y)[0];
-                        if pixel < 128 {
-                            byte_val |= 1 << (7 - bit);
-                        }
-                    }
+    if template.printer_info {
+        builder
+            .feed(1)
+            .text(&format!(
+                "{}: {}",
+                labels.paper_width,
+                match paper_width {
+                    PaperWidth::Width58mm => "58mm",
+                    PaperWidth::Width80mm => "80mm",
                 }
-                self.buffer.push(byte_val);
-            }
+            ))
+            .new_line();
+        if let Some(name) = printer_name {
+            builder.text(&format!("{}: {}", labels.printer, name)).new_line();
         }
+        builder
+            .text(&format!("{}: {}", labels.time, venue_time::now().format("%Y-%m-%d %H:%M:%S")))
+            .new_line();
+    }
 
-        self
+    if template.formatting {
+        builder
+            .draw_line('-')
+            .align(Alignment::Center)
+            .text(labels.formatting)
+            .new_line()
+            .feed(1)
+            .bold(true)
+            .text(labels.bold)
+            .new_line()
+            .bold(false)
+            .underline(true)
+            .text(labels.underlined)
+            .new_line()
+            .underline(false)
+            .inverse(true)
+            .text(labels.inverse)
+            .new_line()
+            .inverse(false)
+            .size(TextSize::DoubleWidth)
+            .text(labels.double_width)
+            .new_line()
+            .size(TextSize::DoubleHeight)
+            .text(labels.double_height)
+            .new_line()
+            .size(TextSize::Normal);
     }
 
-    /// Write raw ESC/POS bytes (for commands not yet in the builder)
-    pub fn raw(&mut self, data: &[u8]) -> &mut Self {
-        self.buffer.extend_from_slice(data);
-        self
+    builder.align(Alignment::Center).draw_line('=');
+
+    if template.qr_code {
+        builder
+            .feed(1)
+            .qr_code(&template.qr_url, 5)
+            .feed(1)
+            .text(labels.qr_code)
+            .new_line();
     }
 
-    /// Add centered text (auto-calculated padding)
-    pub fn center_text(&mut self, text: &str) -> &mut Self {
-        let padding = (self.paper_width as usize - text.len()) / 2;
-        let spaces = " ".repeat(padding.max(0));
-        self.text(&format!("{}{}", spaces, text)).new_line()
+    if let Some(footer) = &template.footer {
+        builder.feed(1).text(footer).new_line();
     }
 
-    /// Add left-right justified text
-    pub fn justify_text(&mut self, left: &str, right: &str) -> &mut Self {
-        let spaces = self.paper_width as usize - left.len() - right.len();
-        let spacing = " ".repeat(spaces.max(1));
-        self.text(&format!("{}{}{}", left, spacing, right)).new_line()
+    builder.feed(2).cut(false);
+
+    builder.build()
+}
+
+/// Self-measuring pattern for printers that can't report their paper width: a 48-column
+/// ruler (tens marked with their digit) wraps after column 32 on 58mm paper
+pub fn format_width_ruler(locale: Locale) -> Vec<u8> {
+    let labels = test_print_labels(locale);
+    let ruler: String = (1..=PaperWidth::Width80mm as u32)
+        .map(|col| {
+            let digit = if col % 10 == 0 { col / 10 } else { col % 10 };
+            char::from_digit(digit % 10, 10).unwrap_or('?')
+        })
+        .collect();
+
+    // Narrowest width so the hint wraps on either paper
+    let mut builder = ESCPOSBuilder::new(PaperWidth::Width58mm);
+    builder
+        .initialize()
+        .align(Alignment::Center)
+        .bold(true)
+        .text(labels.ruler_title)
+        .new_line()
+        .bold(false)
+        .align(Alignment::Left)
+        .feed(1)
+        .text(&ruler)
+        .new_line()
+        .feed(1)
+        .text(labels.ruler_hint)
+        .new_line()
+        .feed(2)
+        .cut(false);
+    builder.build()
+}
+
+/// Celebratory receipt printed on each printer at the end of the pairing wizard
+pub fn format_setup_complete(
+    paper_width: PaperWidth,
+    template: &TestPrintTemplate,
+    locale: Locale,
+    printer_name: &str,
+    station: Option<&str>,
+) -> Vec<u8> {
+    let labels = test_print_labels(locale);
+    let mut builder = ESCPOSBuilder::new(paper_width);
+
+    builder.initialize();
+    template_logo(&mut builder, template, paper_width);
+
+    builder
+        .align(Alignment::Center)
+        .text("*  *  *  *  *  *  *  *")
+        .new_line()
+        .feed(1)
+        .size(TextSize::DoubleBoth)
+        .bold(true)
+        .text(labels.setup_title)
+        .new_line()
+        .size(TextSize::Normal)
+        .text(template.venue_name.as_deref().unwrap_or("Eatsome"))
+        .new_line()
+        .bold(false)
+        .feed(1)
+        .text("*  *  *  *  *  *  *  *")
+        .new_line()
+        .draw_line('=')
+        .text(labels.setup_ready)
+        .new_line()
+        .feed(1)
+        .bold(true)
+        .text(printer_name)
+        .new_line()
+        .bold(false)
+        .text(&format!("{}: {}", labels.station, station.unwrap_or(labels.all_stations)))
+        .new_line()
+        .text(&venue_time::now().format("%Y-%m-%d %H:%M").to_string())
+        .new_line()
+        .draw_line('-')
+        .text(labels.setup_next)
+        .new_line();
+
+    if let Some(footer) = &template.footer {
+        builder.feed(1).text(footer).new_line();
     }
 
-    /// Add table row (multiple columns with auto-spacing)
-    pub fn table_row(&mut self, columns: &[&str], widths: Option<&[usize]>) -> &mut Self {
-        let default_widths;
-        let widths = if let Some(w) = widths {
-            w
-        } else {
-            let col_width = self.paper_width as usize / columns.len();
-            default_widths = vec![col_width; columns.len()];
-            &default_widths[..]
-        };
+    builder.feed(3).cut(false);
 
-        let mut row = String::new();
-        for (i, col) in columns.iter().enumerate() {
-            let width = widths.get(i).copied().unwrap_or(0);
-            if col.len() > width {
-                row.push_str(&col[..width]);
-            } else {
-                row.push_str(col);
-                row.push_str(&" ".repeat(width - col.len()));
-            }
-        }
+    builder.build()
+}
 
-        let truncated = if row.len() > self.paper_width as usize {
-            &row[..self.paper_width as usize]
-        } else {
-            &row
-        };
+/// "Order ready" slip for the pickup counter (`pickup_slip.rs`): the order number as
+/// large as the paper allows, the customer's name and a QR code to the order status page
+pub fn format_pickup_slip(slip: &PickupSlip, qr_url: Option<&str>, locale: Locale, paper_width: PaperWidth) -> Vec<u8> {
+    let (ready, scan) = match locale {
+        Locale::Nl => ("KLAAR OM OP TE HALEN", "Scan om je bestelling te volgen"),
+        Locale::En => ("READY FOR PICKUP", "Scan to follow your order"),
+    };
+    // Up to 4x wide, as long as the number fits on one line
+    let chars = slip.order_number.chars().count().max(1);
+    let scale = (paper_width.columns(Font::A) / chars).clamp(1, 4) as u8;
+
+    let mut builder = ESCPOSBuilder::new(paper_width);
+    builder
+        .initialize()
+        .align(Alignment::Center)
+        .bold(true)
+        .text(ready)
+        .new_line()
+        .draw_line('=')
+        .feed(1)
+        .size_wh(scale, 4)
+        .text(&slip.order_number)
+        .new_line()
+        .size(TextSize::Normal)
+        .bold(false)
+        .feed(1);
 
-        self.text(truncated).new_line()
+    if let Some(name) = slip.customer_name.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        builder.size(TextSize::DoubleBoth).text(name).new_line().size(TextSize::Normal);
     }
+
+    if let Some(url) = qr_url {
+        builder.feed(1).qr_code(url, 6).feed(1).text(scan).new_line();
+    }
+
+    builder.draw_line('=').feed(3).cut(false);
+    builder.build()
 }
 
-/// Format kitchen receipt
-pub fn format_kitchen_receipt(
+/// Follow-up ticket for an order whose ticket already printed (`JobKind::Void` / `Amend`).
+///
+/// Thermal printers can't strike text through, so removed items print small behind a
+/// "-" under a "do not make" heading, and additions large, inverted, behind a "+". A void
+/// strikes `items` (the original ticket's, when the request had none); an amend strikes
+/// `removed_items` and adds `items`. One copy, whatever the job's `copies`.
+#[allow(clippy::too_many_arguments)]
+pub fn format_change_ticket(
+    kind: JobKind,
     station: &str,
     order_number: &str,
-    order_type: Option<&str>,
     table_number: Option<&str>,
     customer_name: Option<&str>,
-    priority: u8,
     items: &[PrintItem],
+    removed_items: &[PrintItem],
     timestamp: i64,
+    reference: Option<&str>,
     paper_width: PaperWidth,
+    layout: ReceiptLayout,
+    blocks: &ReceiptBlocks,
 ) -> Vec<u8> {
+    let compact = layout == ReceiptLayout::Compact;
+    let (banner, struck) = match kind {
+        JobKind::Void => (" VOID ", items),
+        _ => (" CHANGED ", removed_items),
+    };
     let mut builder = ESCPOSBuilder::new(paper_width);
+    builder.initialize();
+    if compact {
+        builder.font(Font::B).line_spacing(20);
+    }
+    if let Some(logo) = &blocks.logo {
+        builder.align(Alignment::Center).raw(logo).new_line();
+    }
+    let separator = |builder: &mut ESCPOSBuilder| {
+        if compact {
+            builder.short_line('-');
+        } else {
+            builder.draw_line('-');
+        }
+    };
 
     builder
-        .initialize()
         .align(Alignment::Center)
-        .size(TextSize::DoubleBoth)
+        .size(if compact { TextSize::DoubleHeight } else { TextSize::DoubleBoth })
         .bold(true)
+        .inverse(true)
+        .text(banner)
+        .inverse(false)
+        .new_line()
         .text(&station.to_uppercase())
         .new_line()
-        .bold(false)
         .size(TextSize::Normal)
-        .draw_line('=');
+        .bold(false);
+    text_block(&mut builder, &blocks.header);
 
-    // Order information
     builder
         .align(Alignment::Left)
         .size(TextSize::DoubleWidth)
@@ -433,396 +941,434 @@ pub fn format_kitchen_receipt(
         .new_line()
         .size(TextSize::Normal)
         .bold(false);
-
-    if let Some(order_type) = order_type {
-        builder.text(&format!("Type: {}", order_type.to_uppercase())).new_line();
-    }
-
     if let Some(table) = table_number {
         builder.text(&format!("Table: {}", table)).new_line();
     }
-
     if let Some(customer) = customer_name {
         builder.text(&format!("Customer: {}", customer)).new_line();
     }
 
-    // Priority indicator
-    if priority == 1 {
-        builder.inverse(true).bold(true).text(" URGENT ").inverse(false).bold(false).new_line();
-    }
-
-    builder.draw_line('-');
-
-    // Items
-    for item in items {
+    if !struck.is_empty() {
+        separator(&mut builder);
         builder
             .bold(true)
-            .size(TextSize::DoubleHeight)
-            .text(&format!("{}x {}", item.quantity, item.name))
-            .new_line()
-            .size(TextSize::Normal)
-            .bold(false);
-
-        // Modifiers
-        for modifier in &item.modifiers {
-            builder.text(&format!("  + {}", modifier)).new_line();
+            .text(if kind == JobKind::Void { "CANCELLED - DO NOT MAKE" } else { "REMOVED - DO NOT MAKE" })
+            .bold(false)
+            .new_line();
+        for item in struck {
+            builder.text(&format!("- {}x {}", item.quantity, item.name)).new_line();
+            for modifier in &item.modifiers {
+                builder.text(&format!("    {}", modifier)).new_line();
+            }
         }
+    }
 
-        // Notes
-        if let Some(notes) = &item.notes {
-            builder.underline(true).text(&format!("  NOTE: {}", notes)).underline(false).new_line();
+    if kind == JobKind::Amend && !items.is_empty() {
+        separator(&mut builder);
+        builder.bold(true).text("ADDED").new_line();
+        for item in items {
+            builder
+                .size(TextSize::DoubleHeight)
+                .inverse(true)
+                .text(&format!("+ {}x {}", item.quantity, item.name))
+                .inverse(false)
+                .new_line()
+                .size(TextSize::Normal);
+            for modifier in &item.modifiers {
+                builder.text(&format!("  + {}", modifier)).new_line();
+            }
+            if let Some(notes) = &item.notes {
+                builder.underline(true).text(&format!("  NOTE: {}", notes)).underline(false).new_line();
+            }
         }
-
-        builder.feed(1);
+        builder.bold(false);
     }
 
-    builder.draw_line('-');
-
-    // Timestamp
-    let time_str = chrono::DateTime::from_timestamp(timestamp / 1000, 0)
+    separator(&mut builder);
+    let time_str = venue_time::at_millis(timestamp)
         .map(|dt| dt.format("%H:%M").to_string())
         .unwrap_or_else(|| "??:??".to_string());
+    builder.align(Alignment::Center).text(&format!("Printed: {}", time_str)).new_line();
+    if let Some(reference) = reference.filter(|r| !r.is_empty()) {
+        builder.text(&format!("ref {}", reference)).new_line();
+    }
+    text_block(&mut builder, &blocks.footer);
 
-    builder
-        .align(Alignment::Center)
-        .text(&format!("Printed: {}", time_str))
-        .new_line()
-        .feed(2)
-        .cut(false);
-
+    builder.feed(2).cut(false);
     builder.build()
 }
 
-/// Print item for receipts
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PrintItem {
-    pub quantity: u32,
-    pub name: String,
-    pub modifiers: Vec<String>,
-    pub notes: Option<String>,
-}
-
-// ============================================================================
-// ESC/POS Binary Parser (for print preview)
-// ============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt_text(commands: &[u8]) -> String {
+        parse_escpos(commands, PaperWidth::Width80mm)
+            .elements
+            .iter()
+            .filter_map(|e| match e {
+                ReceiptElement::Text { content, .. } => Some(content.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 
-/// Text alignment for parsed elements
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum TextAlignment {
-    Left,
-    Center,
-    Right,
-}
+    #[test]
+    fn test_test_print_follows_template() {
+        let template = TestPrintTemplate {
+            venue_name: Some("Cafe de Klos".to_string()),
+            formatting: false,
+            qr_code: false,
+            footer: Some("Bel 020-1234567".to_string()),
+            ..TestPrintTemplate::default()
+        };
 
-/// Text style state for parsed elements
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TextStyle {
-    pub bold: bool,
-    pub underline: bool,
-    pub double_width: bool,
-    pub double_height: bool,
-    pub inverted: bool,
-}
+        let text = receipt_text(&format_test_print(PaperWidth::Width80mm, &template, Locale::Nl, Some("Bar")));
+        assert!(text.contains("Cafe de Klos"));
+        assert!(text.contains("PROEFAFDRUK"));
+        assert!(text.contains("Printer: Bar"));
+        assert!(text.contains("Bel 020-1234567"));
+        assert!(!text.contains("Vetgedrukt"));
+        assert!(!text.contains("QR-code test"));
+
+        let text = receipt_text(&format_setup_complete(PaperWidth::Width80mm, &template, Locale::En, "Bar", None));
+        assert!(text.contains("ALL SET!"));
+        assert!(text.contains("Station: all stations"));
+    }
 
-impl Default for TextStyle {
-    fn default() -> Self {
-        Self {
-            bold: false,
-            underline: false,
-            double_width: false,
-            double_height: false,
-            inverted: false,
-        }
+    #[test]
+    fn test_logo_on_kitchen_tickets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logo.png");
+        image::RgbImage::from_pixel(64, 32, image::Rgb([0, 0, 0])).save(&path).unwrap();
+        let template = TestPrintTemplate {
+            logo_path: Some(path.display().to_string()),
+            logo_url: Some("https://example.supabase.co/storage/v1/object/public/logos/r1.png".to_string()),
+            ..TestPrintTemplate::default()
+        };
+        // A local file wins over the download
+        assert_eq!(template.logo_file(), Some(path.clone()));
+        let logo = venue_logo(&template, PaperWidth::Width58mm).unwrap();
+        // Converted once per paper width
+        assert!(Arc::ptr_eq(&logo, &venue_logo(&template, PaperWidth::Width58mm).unwrap()));
+
+        let items: Vec<PrintItem> = serde_json::from_str(r#"[{"quantity":1,"name":"Soup","modifiers":[],"notes":null}]"#).unwrap();
+        let ticket = |blocks: &ReceiptBlocks| {
+            format_kitchen_receipt(
+                "kitchen",
+                "42",
+                None,
+                None,
+                None,
+                3,
+                &items,
+                0,
+                2,
+                None,
+                &Thumbnails::new(),
+                PaperWidth::Width58mm,
+                ReceiptLayout::Normal,
+                blocks,
+            )
+        };
+        let bands = |commands: &[u8]| commands.windows(3).filter(|w| w == &[GS, b'v', b'0']).count();
+        assert_eq!(bands(&ticket(&ReceiptBlocks::default())), 0);
+        let logo_bands = bands(&logo);
+        assert!(logo_bands > 0);
+        // On top of every copy
+        let blocks = ReceiptBlocks { logo: Some(logo), ..ReceiptBlocks::default() };
+        assert_eq!(bands(&ticket(&blocks)), 2 * logo_bands);
+        // Void and amend tickets have one copy
+        let void = format_change_ticket(
+            JobKind::Void,
+            "kitchen",
+            "42",
+            None,
+            None,
+            &items,
+            &[],
+            0,
+            None,
+            PaperWidth::Width58mm,
+            ReceiptLayout::Normal,
+            &blocks,
+        );
+        assert_eq!(bands(&void), logo_bands);
+        // The whole raster image, above the VOID banner
+        let find = |haystack: &[u8], needle: &[u8]| haystack.windows(needle.len()).position(|w| w == needle);
+        let logo_at = find(&void, blocks.logo.as_ref().unwrap()).unwrap();
+        assert!(logo_at < find(&void, b" VOID ").unwrap());
+
+        // Not downloaded yet: no logo
+        let remote = TestPrintTemplate { logo_path: None, ..template };
+        assert_eq!(remote.logo_file(), None);
     }
-}
 
-/// A single element in a parsed receipt
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
-pub enum ReceiptElement {
-    Text {
-        content: String,
-        style: TextStyle,
-        alignment: TextAlignment,
-    },
-    Feed {
-        lines: u8,
-    },
-    Cut {
-        partial: bool,
-    },
-}
+    #[test]
+    fn test_copies_share_one_buffer_and_one_full_cut() {
+        let items = vec![PrintItem {
+            quantity: 2,
+            name: "Burger".to_string(),
+            modifiers: vec![],
+            notes: None,
+            image_url: None,
+            pricing: None,
+        }];
+        let receipt = |copies| {
+            format_kitchen_receipt(
+                "kitchen",
+                "42",
+                None,
+                None,
+                None,
+                3,
+                &items,
+                0,
+                copies,
+                Some("7f3a9c21"),
+                &Thumbnails::new(),
+                PaperWidth::Width80mm,
+                ReceiptLayout::Normal,
+                &ReceiptBlocks::default(),
+            )
+        };
+        let cuts = |commands: &[u8]| -> Vec<bool> {
+            parse_escpos(commands, PaperWidth::Width80mm)
+                .elements
+                .iter()
+                .filter_map(|e| match e {
+                    ReceiptElement::Cut { partial } => Some(*partial),
+                    _ => None,
+                })
+                .collect()
+        };
 
-/// Complete parsed receipt structure for frontend rendering
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ParsedReceipt {
-    pub elements: Vec<ReceiptElement>,
-    pub paper_width_mm: u16,
-    pub char_width: u8,
-}
+        let single = receipt(1);
+        assert!(!receipt_text(&single).contains("COPY"));
+        assert_eq!(cuts(&single), vec![false]);
 
-/// Parse ESC/POS binary buffer into structured receipt data
-///
-/// Interprets ESC/POS commands (ESC @, ESC E, ESC a, GS !, etc.)
-/// and converts them to a JSON-friendly structure that React can render
-/// with monospace fonts to simulate thermal printer output.
-pub fn parse_escpos(buffer: &[u8], paper_width: PaperWidth) -> ParsedReceipt {
-    let char_width = paper_width as u8;
-    let paper_width_mm = match paper_width {
-        PaperWidth::Width58mm => 58,
-        PaperWidth::Width80mm => 80,
-    };
+        let double = receipt(2);
+        let text = receipt_text(&double);
+        assert!(text.contains("COPY 1/2") && text.contains("COPY 2/2"));
+        assert_eq!(text.matches("2x Burger").count(), 2);
+        assert_eq!(text.matches("ref 7f3a9c21").count(), 2);
+        assert_eq!(cuts(&double), vec![true, false]);
 
-    let mut elements = Vec::new();
-    let mut style = TextStyle::default();
-    let mut alignment = TextAlignment::Left;
-    let mut text_buf = String::new();
-    let mut i = 0;
-
-    while i < buffer.len() {
-        match buffer[i] {
-            // ESC commands (0x1B)
-            0x1B if i + 1 < buffer.len() => {
-                // Flush text buffer before processing command
-                if !text_buf.is_empty() {
-                    elements.push(ReceiptElement::Text {
-                        content: text_buf.clone(),
-                        style: style.clone(),
-                        alignment: alignment.clone(),
-                    });
-                    text_buf.clear();
-                }
+        assert_eq!(cuts(&receipt(0)), vec![false]);
+        assert_eq!(cuts(&receipt(20)).len(), MAX_COPIES as usize);
+    }
 
-                match buffer[i + 1] {
-                    0x40 => {
-                        // ESC @ - Initialize (reset)
-                        style = TextStyle::default();
-                        alignment = TextAlignment::Left;
-                        i += 2;
-                    }
-                    0x45 if i + 2 < buffer.len() => {
-                        // ESC E n - Bold on/off
-                        style.bold = buffer[i + 2] != 0;
-                        i += 3;
-                    }
-                    0x61 if i + 2 < buffer.len() => {
-                        // ESC a n - Alignment
-                        alignment = match buffer[i + 2] {
-                            0 => TextAlignment::Left,
-                            1 => TextAlignment::Center,
-                            2 => TextAlignment::Right,
-                            _ => TextAlignment::Left,
-                        };
-                        i += 3;
-                    }
-                    0x2D if i + 2 < buffer.len() => {
-                        // ESC - n - Underline on/off
-                        style.underline = buffer[i + 2] != 0;
-                        i += 3;
-                    }
-                    0x4D if i + 2 < buffer.len() => {
-                        // ESC M n - Font select (skip)
-                        i += 3;
-                    }
-                    0x33 if i + 2 < buffer.len() => {
-                        // ESC 3 n - Line spacing (skip)
-                        i += 3;
-                    }
-                    0x32 => {
-                        // ESC 2 - Default line spacing (skip)
-                        i += 2;
-                    }
-                    0x20 if i + 2 < buffer.len() => {
-                        // ESC SP n - Character spacing (skip)
-                        i += 3;
-                    }
-                    0x74 if i + 2 < buffer.len() => {
-                        // ESC t n - Code page (skip)
-                        i += 3;
-                    }
-                    0x70 if i + 4 < buffer.len() => {
-                        // ESC p - Cash drawer (skip 5 bytes)
-                        i += 5;
-                    }
-                    _ => {
-                        // Unknown ESC command, skip 2 bytes
-                        i += 2;
-                    }
-                }
-            }
-            // GS commands (0x1D)
-            0x1D if i + 1 < buffer.len() => {
-                if !text_buf.is_empty() {
-                    elements.push(ReceiptElement::Text {
-                        content: text_buf.clone(),
-                        style: style.clone(),
-                        alignment: alignment.clone(),
-                    });
-                    text_buf.clear();
-                }
+    #[test]
+    fn test_compact_layout_uses_font_b_and_less_space() {
+        let items: Vec<PrintItem> = (0..6)
+            .map(|i| PrintItem {
+                quantity: 1,
+                name: format!("Dish {}", i),
+                modifiers: vec!["no onion".to_string()],
+                notes: None,
+                image_url: None,
+                pricing: None,
+            })
+            .collect();
+        let receipt = |layout| {
+            format_kitchen_receipt(
+                "kitchen",
+                "42",
+                Some("dine_in"),
+                Some("7"),
+                None,
+                0,
+                &items,
+                0,
+                1,
+                Some("7f3a9c21"),
+                &Thumbnails::new(),
+                PaperWidth::Width58mm,
+                layout,
+                &ReceiptBlocks::default(),
+            )
+        };
+        let normal = receipt(ReceiptLayout::Normal);
+        let compact = receipt(ReceiptLayout::Compact);
 
-                match buffer[i + 1] {
-                    0x21 if i + 2 < buffer.len() => {
-                        // GS ! n - Character size
-                        let n = buffer[i + 2];
-                        style.double_width = (n & 0x10) != 0;
-                        style.double_height = (n & 0x01) != 0 || (n & 0x20) != 0;
-                        i += 3;
-                    }
-                    0x42 if i + 2 < buffer.len() => {
-                        // GS B n - Inverse on/off
-                        style.inverted = buffer[i + 2] != 0;
-                        i += 3;
-                    }
-                    0x56 if i + 2 < buffer.len() => {
-                        // GS V n - Cut paper
-                        let partial = buffer[i + 2] != 0;
-                        elements.push(ReceiptElement::Cut { partial });
-                        i += 3;
-                    }
-                    0x28 if i + 2 < buffer.len() && buffer[i + 2] == 0x6B => {
-                        // GS ( k - QR code command (variable length, skip)
-                        if i + 4 < buffer.len() {
-                            let pl = buffer[i + 3] as usize;
-                            let ph = buffer[i + 4] as usize;
-                            let data_len = pl + (ph << 8);
-                            i += 5 + data_len.min(buffer.len() - i - 5);
-                        } else {
-                            i += 3;
-                        }
-                    }
-                    0x68 if i + 2 < buffer.len() => {
-                        // GS h n - Barcode height (skip)
-                        i += 3;
-                    }
-                    0x77 if i + 2 < buffer.len() => {
-                        // GS w n - Barcode width (skip)
-                        i += 3;
-                    }
-                    0x6B if i + 3 < buffer.len() => {
-                        // GS k - Barcode (variable length, skip)
-                        let data_len = buffer[i + 3] as usize;
-                        i += 4 + data_len.min(buffer.len() - i - 4);
-                    }
-                    0x76 if i + 7 < buffer.len() => {
-                        // GS v 0 - Raster image (skip entire image data)
-                        let xl = buffer[i + 4] as usize;
-                        let xh = buffer[i + 5] as usize;
-                        let yl = buffer[i + 6] as usize;
-                        let yh = buffer[i + 7] as usize;
-                        let byte_width = xl + (xh << 8);
-                        let height = yl + (yh << 8);
-                        i += 8 + (byte_width * height).min(buffer.len() - i - 8);
-                    }
-                    _ => {
-                        i += 2;
-                    }
-                }
-            }
-            // LF (Line Feed)
-            0x0A => {
-                if !text_buf.is_empty() {
-                    elements.push(ReceiptElement::Text {
-                        content: text_buf.clone(),
-                        style: style.clone(),
-                        alignment: alignment.clone(),
-                    });
-                    text_buf.clear();
-                }
-                elements.push(ReceiptElement::Feed { lines: 1 });
-                i += 1;
-            }
-            // CR (Carriage Return) - skip, usually paired with LF
-            0x0D => {
-                i += 1;
-            }
-            // Regular printable text
-            byte => {
-                if byte >= 0x20 {
-                    text_buf.push(byte as char);
-                }
-                i += 1;
-            }
-        }
-    }
+        assert!(compact.windows(3).any(|w| w == [0x1B, 0x4D, 0x01]));
+        assert!(!normal.windows(3).any(|w| w == [0x1B, 0x4D, 0x01]));
+        assert!(compact.len() < normal.len());
+        let lines = |commands: &[u8]| commands.iter().filter(|b| **b == b'\n').count();
+        assert!(lines(&compact) < lines(&normal));
 
-    // Flush remaining text
-    if !text_buf.is_empty() {
-        elements.push(ReceiptElement::Text {
-            content: text_buf,
-            style: style.clone(),
-            alignment: alignment.clone(),
-        });
+        let text = receipt_text(&compact);
+        assert!(text.contains("1x Dish 5") && text.contains("ref 7f3a9c21"));
     }
 
-    ParsedReceipt {
-        elements,
-        paper_width_mm,
-        char_width,
+    #[test]
+    fn test_customer_receipt_prices_weight_and_vat() {
+        // Items queued before pricing existed still load
+        let old: Vec<PrintItem> =
+            serde_json::from_str(r#"[{"quantity":1,"name":"Soup","modifiers":[],"notes":null}]"#).unwrap();
+        assert!(old[0].pricing.is_none() && old[0].line_total().is_none());
+
+        let priced = |quantity, name: &str, pricing| PrintItem {
+            quantity,
+            name: name.to_string(),
+            modifiers: vec![],
+            notes: None,
+            image_url: None,
+            pricing: Some(pricing),
+        };
+        let items = vec![
+            priced(2, "Burger", LinePricing { unit_price_cents: Some(1250), tax_rate: Some(9.0), ..LinePricing::default() }),
+            priced(1, "Cheese", LinePricing {
+                unit_price_cents: Some(2490),
+                weight_grams: Some(350),
+                tax_rate: Some(9.0),
+                ..LinePricing::default()
+            }),
+            priced(1, "Wine", LinePricing {
+                line_total_cents: Some(600),
+                discount_cents: Some(100),
+                tax_rate: Some(21.0),
+                ..LinePricing::default()
+            }),
+        ];
+
+        assert_eq!(
+            tax_summary(&items),
+            vec![
+                TaxLine { rate: 9.0, gross_cents: 3372, tax_cents: 278 },
+                TaxLine { rate: 21.0, gross_cents: 600, tax_cents: 104 },
+            ]
+        );
+
+        let template = TestPrintTemplate { venue_name: Some("De Gouden Lepel".to_string()), ..TestPrintTemplate::default() };
+        let blocks = ReceiptBlocks {
+            header: vec!["Afhalen om 18:30".to_string()],
+            footer: vec!["WiFi: pasta2024".to_string()],
+            logo: None,
+        };
+        let text = receipt_text(&format_customer_receipt(
+            &template,
+            "42",
+            &items,
+            &CheckTotals::default(),
+            0,
+            Locale::Nl,
+            PaperWidth::Width80mm,
+            &blocks,
+            None,
+        ));
+        assert!(text.contains("2 x 12,50"));
+        assert!(text.contains("0.350 kg x 24,90/kg"));
+        assert!(text.contains("Korting") && text.contains("-1,00"));
+        assert!(text.contains("EUR 39,72"));
+        assert!(text.contains("BTW 21% over 6,00"));
+        assert!(text.find("Afhalen om 18:30").unwrap() < text.find("Bestelling 42").unwrap());
+        assert!(text.contains("WiFi: pasta2024"));
+        assert!(!text.contains("Scan"));
+        assert!(!text.contains("Subtotaal") && !text.contains("Betaald"));
+
+        // Order discount and tip: VAT follows the discount, not the tip
+        let check = CheckTotals {
+            discount_cents: Some(372),
+            tip_cents: Some(200),
+            payment_method: Some("Contant".to_string()),
+            tendered_cents: Some(4000),
+        };
+        assert_eq!(
+            discounted_taxes(tax_summary(&items), 372, 3972),
+            vec![
+                TaxLine { rate: 9.0, gross_cents: 3057, tax_cents: 252 },
+                TaxLine { rate: 21.0, gross_cents: 543, tax_cents: 94 },
+            ]
+        );
+        let text = receipt_text(&format_customer_receipt(
+            &template,
+            "42",
+            &items,
+            &check,
+            0,
+            Locale::Nl,
+            PaperWidth::Width80mm,
+            &blocks,
+            None,
+        ));
+        assert!(text.contains("Subtotaal") && text.contains("39,72"));
+        assert!(text.contains("-3,72") && text.contains("Fooi"));
+        assert!(text.contains("EUR 38,00"));
+        assert!(text.contains("Betaald (Contant)") && text.contains("Wisselgeld"));
+        assert!(text.contains("BTW 21% over 5,43"));
+
+        let link = Some((LinkPurpose::Feedback, "https://eatsome.nl/f?token=abc"));
+        let text = receipt_text(&format_customer_receipt(
+            &template,
+            "42",
+            &items,
+            &CheckTotals::default(),
+            0,
+            Locale::En,
+            PaperWidth::Width80mm,
+            &blocks,
+            link,
+        ));
+        assert!(text.find("WiFi: pasta2024").unwrap() < text.find("How was it? Scan to let us know").unwrap());
     }
-}
 
-/// Format test print
-pub fn format_test_print(paper_width: PaperWidth) -> Vec<u8> {
-    let mut builder = ESCPOSBuilder::new(paper_width);
+    #[test]
+    fn test_pickup_slip_fits_order_number() {
+        let slip = PickupSlip { order_id: "o1".to_string(), order_number: "42".to_string(), customer_name: Some("Sam".to_string()) };
+        let commands = format_pickup_slip(&slip, Some("https://eatsome.nl/order/o1"), Locale::En, PaperWidth::Width80mm);
+        assert!(commands.windows(3).any(|w| w == [GS, 0x21, 0x33]));
+        let text = receipt_text(&commands);
+        assert!(text.contains("READY FOR PICKUP") && text.contains("Sam"));
+        assert!(text.contains("Scan to follow your order"));
+
+        // A long number on narrow paper is scaled down to stay on one line
+        let long = PickupSlip { order_number: "R001-0042-ABCDE".to_string(), customer_name: None, ..slip };
+        let commands = format_pickup_slip(&long, None, Locale::Nl, PaperWidth::Width58mm);
+        assert!(commands.windows(3).any(|w| w == [GS, 0x21, 0x13]));
+        assert!(!receipt_text(&commands).contains("Scan"));
+    }
 
-    builder
-        .initialize()
-        .align(Alignment::Center)
-        .size(TextSize::DoubleBoth)
-        .bold(true)
-        .text("TEST PRINT")
-        .new_line()
-        .size(TextSize::Normal)
-        .bold(false)
-        .draw_line('=')
-        .align(Alignment::Left)
-        .text("Printer is working correctly!")
-        .new_line()
-        .feed(1)
-        .text(&format!(
-            "Paper width: {}",
-            match paper_width {
-                PaperWidth::Width58mm => "58mm",
-                PaperWidth::Width80mm => "80mm",
-            }
-        ))
-        .new_line()
-        .text(&format!("Timestamp: {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S")))
-        .new_line()
-        .draw_line('-')
-        .align(Alignment::Center)
-        .text("Text Formatting Tests:")
-        .new_line()
-        .feed(1)
-        .bold(true)
-        .text("Bold Text")
-        .new_line()
-        .bold(false)
-        .underline(true)
-        .text("Underlined Text")
-        .new_line()
-        .underline(false)
-        .inverse(true)
-        .text("Inverse Text")
-        .new_line()
-        .inverse(false)
-        .size(TextSize::DoubleWidth)
-        .text("Double Width")
-        .new_line()
-        .size(TextSize::DoubleHeight)
-        .text("Double Height")
-        .new_line()
-        .size(TextSize::Normal)
-        .draw_line('=')
-        .feed(1)
-        .qr_code("https://eatsome.nl", 5)
-        .feed(1)
-        .text("QR Code Test")
-        .new_line()
-        .feed(2)
-        .cut(false);
+    #[test]
+    fn test_change_tickets_strike_and_add() {
+        let item = |quantity, name: &str| PrintItem {
+            quantity,
+            name: name.to_string(),
+            modifiers: vec![],
+            notes: None,
+            image_url: None,
+            pricing: None,
+        };
+        let blocks = ReceiptBlocks::default();
+        let ticket = |kind, items: &[PrintItem], removed: &[PrintItem]| {
+            receipt_text(&format_change_ticket(
+                kind,
+                "kitchen",
+                "42",
+                Some("7"),
+                None,
+                items,
+                removed,
+                0,
+                Some("a1b2c3d4"),
+                PaperWidth::Width80mm,
+                ReceiptLayout::Normal,
+                &blocks,
+            ))
+        };
 
-    builder.build()
+        let text = ticket(JobKind::Void, &[item(2, "Burger")], &[]);
+        assert!(text.contains("VOID") && text.contains("ORDER 42") && text.contains("Table: 7"));
+        assert!(text.contains("CANCELLED - DO NOT MAKE") && text.contains("- 2x Burger"));
+        assert!(!text.contains("ADDED"));
+        assert!(text.contains("ref a1b2c3d4"));
+
+        let text = ticket(JobKind::Amend, &[item(1, "Fries")], &[item(2, "Burger")]);
+        assert!(text.contains("CHANGED"));
+        assert!(text.find("- 2x Burger").unwrap() < text.find("+ 1x Fries").unwrap());
+        // Only additions: no "do not make" section
+        let text = ticket(JobKind::Amend, &[item(1, "Fries")], &[]);
+        assert!(!text.contains("DO NOT MAKE") && text.contains("+ 1x Fries"));
+    }
 }
@@ -0,0 +1,283 @@
+use crate::clock::{self, SharedClock};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Failover hysteresis configuration
+#[derive(Debug, Clone)]
+pub struct FailoverConfig {
+    /// Minimum time a failed-over printer's jobs stay on the backup (default: 2 minutes)
+    pub min_on_backup: Duration,
+    /// Consecutive healthy status checks of the primary before its jobs go back to it
+    /// (default: 3, 90s with the 30s status poller)
+    pub failback_after: u32,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            min_on_backup: Duration::from_secs(2 * 60),
+            failback_after: 3,
+        }
+    }
+}
+
+/// Per-printer failover snapshot (for the dashboard)
+#[derive(Debug, Clone, Serialize)]
+pub struct FailoverStatus {
+    pub primary_id: String,
+    pub backup_id: String,
+    pub on_backup_secs: u64,
+    pub healthy_checks: u32,
+    pub failback_after: u32,
+    /// Seconds until the minimum time on the backup is over (0 = only waiting for checks)
+    pub min_remaining_secs: u64,
+}
+
+#[derive(Debug)]
+struct Active {
+    backup: String,
+    since: Instant,
+    healthy_checks: u32,
+}
+
+/// Keeps a failed-over printer's jobs on its backup until the primary is healthy again.
+///
+/// Without it every job tries the primary first: on a borderline network jobs alternate
+/// between primary and backup, and each one waits out the primary's timeout before it
+/// fails over. Once a job printed on a backup, the next jobs go to that backup first
+/// (the primary is still tried last). Jobs go back to the primary only after
+/// `min_on_backup` and `failback_after` healthy status checks in a row.
+///
+/// A backup that is failed over itself is skipped, so A → B while B → A (a loop in the
+/// failover map, see `find_loops`) can't bounce tickets between two broken printers.
+pub struct Failover {
+    config: FailoverConfig,
+    active: Mutex<HashMap<String, Active>>,
+    clock: SharedClock,
+}
+
+impl Failover {
+    pub fn new(config: FailoverConfig) -> Self {
+        Self {
+            config,
+            active: Mutex::new(HashMap::new()),
+            clock: clock::system(),
+        }
+    }
+
+    /// Use a different time source (tests advance a `TestClock` instead of sleeping)
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Printers to try, in order, for a job routed to `primary` with these backups:
+    /// normally the primary and then the backups; while failed over the current backup
+    /// first and the primary last. Backups that are failed over themselves or paused
+    /// are left out.
+    pub async fn plan(&self, primary: &str, backups: &[String], paused: &[String]) -> Vec<String> {
+        let mut active = self.active.lock().await;
+        // Backup no longer configured (failover map refreshed): back to normal routing
+        if active.get(primary).is_some_and(|a| !backups.contains(&a.backup)) {
+            active.remove(primary);
+        }
+
+        let usable = backups
+            .iter()
+            .filter(|b| b.as_str() != primary && !active.contains_key(b.as_str()) && !paused.contains(*b))
+            .cloned();
+        match active.get(primary) {
+            Some(current) => {
+                let mut plan: Vec<String> = std::iter::once(current.backup.clone())
+                    .filter(|b| !paused.contains(b))
+                    .collect();
+                plan.extend(usable.filter(|b| *b != current.backup));
+                plan.push(primary.to_string());
+                plan
+            }
+            None => std::iter::once(primary.to_string()).chain(usable).collect(),
+        }
+    }
+
+    /// A job for `primary` printed on `used`. Returns true when this started a failover.
+    /// Printing on the primary while failed over counts as a healthy check.
+    pub async fn record_printed(&self, primary: &str, used: &str) -> bool {
+        if used == primary {
+            self.record_check(primary, true).await;
+            return false;
+        }
+        let now = self.clock.now();
+        let mut active = self.active.lock().await;
+        match active.get_mut(primary) {
+            // Moved on to another backup: the time on backup keeps counting
+            Some(current) => {
+                current.backup = used.to_string();
+                false
+            }
+            None => {
+                info!("Printer {} failed over to {}", primary, used);
+                active.insert(
+                    primary.to_string(),
+                    Active { backup: used.to_string(), since: now, healthy_checks: 0 },
+                );
+                true
+            }
+        }
+    }
+
+    /// Status check of a printer. A failed-over primary gets its jobs back after
+    /// `failback_after` healthy checks in a row, and not before `min_on_backup`.
+    /// Returns true when it failed back.
+    pub async fn record_check(&self, printer_id: &str, healthy: bool) -> bool {
+        let now = self.clock.now();
+        let mut active = self.active.lock().await;
+        let Some(current) = active.get_mut(printer_id) else {
+            return false;
+        };
+        if !healthy {
+            current.healthy_checks = 0;
+            return false;
+        }
+        current.healthy_checks += 1;
+        if current.healthy_checks < self.config.failback_after
+            || now.duration_since(current.since) < self.config.min_on_backup
+        {
+            return false;
+        }
+
+        let current = active.remove(printer_id).expect("checked above");
+        info!(
+            "Printer {} healthy again after {}s on {}, jobs fail back",
+            printer_id,
+            now.duration_since(current.since).as_secs(),
+            current.backup
+        );
+        true
+    }
+
+    /// Snapshot of all failed-over printers
+    pub async fn snapshot(&self) -> Vec<FailoverStatus> {
+        let now = self.clock.now();
+        self.active
+            .lock()
+            .await
+            .iter()
+            .map(|(id, a)| FailoverStatus {
+                primary_id: id.clone(),
+                backup_id: a.backup.clone(),
+                on_backup_secs: now.duration_since(a.since).as_secs(),
+                healthy_checks: a.healthy_checks,
+                failback_after: self.config.failback_after,
+                min_remaining_secs: self
+                    .config
+                    .min_on_backup
+                    .saturating_sub(now.duration_since(a.since))
+                    .as_secs(),
+            })
+            .collect()
+    }
+}
+
+impl Default for Failover {
+    fn default() -> Self {
+        Self::new(FailoverConfig::default())
+    }
+}
+
+/// Loops in a failover map (primary → backups): chains of backups that lead back to
+/// the printer they started from, e.g. `["a", "b"]` for a → b → a. Each loop once,
+/// starting at its smallest printer ID.
+pub fn find_loops(map: &HashMap<String, Vec<String>>) -> Vec<Vec<String>> {
+    fn walk(
+        map: &HashMap<String, Vec<String>>,
+        start: &str,
+        path: &mut Vec<String>,
+        loops: &mut BTreeSet<Vec<String>>,
+    ) {
+        let last = path.last().expect("path starts at `start`").clone();
+        for next in map.get(&last).into_iter().flatten() {
+            if next == start && path.len() > 1 {
+                loops.insert(path.clone());
+            } else if next.as_str() > start && !path.contains(next) {
+                path.push(next.clone());
+                walk(map, start, path, loops);
+                path.pop();
+            }
+        }
+    }
+
+    let mut loops = BTreeSet::new();
+    for start in map.keys() {
+        walk(map, start, &mut vec![start.clone()], &mut loops);
+    }
+    loops.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_hysteresis_and_loop_guard() {
+        let clock = TestClock::new();
+        let failover = Failover::default().with_clock(clock.clone());
+        let backups = ids(&["b", "c"]);
+
+        assert_eq!(failover.plan("a", &backups, &[]).await, ids(&["a", "b", "c"]));
+        assert!(failover.record_printed("a", "b").await);
+        // Jobs stay on the backup, the primary is the last resort
+        assert_eq!(failover.plan("a", &backups, &[]).await, ids(&["b", "c", "a"]));
+
+        // Healthy checks alone don't bring it back within the minimum time
+        for _ in 0..3 {
+            assert!(!failover.record_check("a", true).await);
+        }
+        clock.advance(Duration::from_secs(2 * 60));
+        // A failed check resets the streak
+        assert!(!failover.record_check("a", false).await);
+        assert!(!failover.record_check("a", true).await);
+        assert!(!failover.record_check("a", true).await);
+        assert_eq!(failover.snapshot().await[0].healthy_checks, 2);
+        assert!(failover.record_check("a", true).await);
+        assert_eq!(failover.plan("a", &backups, &[]).await, ids(&["a", "b", "c"]));
+
+        // a → b while b → a: b's jobs don't go to the failed-over a
+        assert!(failover.record_printed("a", "b").await);
+        assert_eq!(failover.plan("b", &ids(&["a"]), &[]).await, ids(&["b"]));
+        // Backup removed from the map: normal routing again
+        assert_eq!(failover.plan("a", &ids(&["c"]), &[]).await, ids(&["a", "c"]));
+        assert!(failover.snapshot().await.is_empty());
+
+        let map = HashMap::from([
+            ("a".to_string(), ids(&["b"])),
+            ("b".to_string(), ids(&["a", "c"])),
+            ("c".to_string(), ids(&["d"])),
+            ("d".to_string(), ids(&["b"])),
+            ("e".to_string(), ids(&["a"])),
+        ]);
+        assert_eq!(find_loops(&map), vec![ids(&["a", "b"]), ids(&["b", "c", "d"])]);
+    }
+
+    #[tokio::test]
+    async fn test_paused_backup_is_skipped() {
+        let failover = Failover::default();
+        let backups = ids(&["b", "c"]);
+        assert_eq!(failover.plan("a", &backups, &ids(&["b"])).await, ids(&["a", "c"]));
+        // The paused primary itself is still tried: its jobs are held before they get here
+        assert_eq!(failover.plan("a", &backups, &ids(&["a"])).await, ids(&["a", "b", "c"]));
+
+        // Failed over to b, then b is paused: c first, the primary last
+        assert!(failover.record_printed("a", "b").await);
+        assert_eq!(failover.plan("a", &backups, &ids(&["b"])).await, ids(&["c", "a"]));
+        assert_eq!(failover.plan("a", &backups, &[]).await, ids(&["b", "c", "a"]));
+    }
+}
@@ -0,0 +1,430 @@
+use crate::supabase_client::SupabaseClient;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+pub const ANON_KEY: &str = "fake-anon-key";
+pub const AUTH_TOKEN: &str = "fake-printer-token";
+
+/// Everything the daemon told the fake backend, for assertions
+#[derive(Debug, Default)]
+pub struct Recorded {
+    /// Rows returned by `poll-jobs` while their status is pending
+    pub jobs: Vec<Value>,
+    /// Latest status per job ID (`update-job-status`)
+    pub job_status: HashMap<String, String>,
+    /// `insert-job-log` payloads
+    pub job_logs: Vec<Value>,
+    /// Printers by ID (`upsert-printers`)
+    pub printers: HashMap<String, Value>,
+    /// Latest status per printer ID (`update-printer-status`)
+    pub printer_status: HashMap<String, String>,
+    /// `printer_ids` sent with each poll (heartbeat piggyback)
+    pub heartbeats: Vec<Vec<String>>,
+    /// `queue` health sent with each poll (back-pressure signal)
+    pub queue_reports: Vec<Value>,
+    /// `resources` sample sent with each poll (daemon CPU / memory)
+    pub resource_reports: Vec<Value>,
+    /// `report-self-test` payloads
+    pub self_tests: Vec<Value>,
+    /// Returned when a poll asks for `include_failover_config`
+    pub failover_config: Option<Value>,
+    /// Action names in call order
+    pub calls: Vec<String>,
+}
+
+/// In-process stand-in for the `printer-daemon-api` Edge Function.
+///
+/// Listens on a random localhost port and implements the actions the daemon uses
+/// (`poll-jobs`, `update-job-status`, `upsert-printers`, ...) against in-memory state,
+/// so poller → queue → processor → status reporting runs in `cargo test` without
+/// network access or secrets.
+pub struct FakeSupabase {
+    pub url: String,
+    state: Arc<Mutex<Recorded>>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl FakeSupabase {
+    pub async fn start() -> Self {
+        let state = Arc::new(Mutex::new(Recorded::default()));
+        let app = Router::new()
+            .route("/functions/v1/printer-daemon-api", post(handle))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        Self { url, state, server }
+    }
+
+    /// Client authenticated with the fake's token
+    pub fn client(&self) -> SupabaseClient {
+        SupabaseClient::new(self.url.clone(), ANON_KEY.to_string(), Some(AUTH_TOKEN.to_string()))
+    }
+
+    /// Make a job available to the next poll (upstream `print_jobs` row shape)
+    pub async fn push_job(&self, job: Value) {
+        self.state.lock().await.jobs.push(job);
+    }
+
+    pub async fn set_failover_config(&self, config: Value) {
+        self.state.lock().await.failover_config = Some(config);
+    }
+
+    pub async fn job_status(&self, job_id: &str) -> Option<String> {
+        self.state.lock().await.job_status.get(job_id).cloned()
+    }
+
+    /// Inspect the recorded state
+    pub async fn recorded<T>(&self, f: impl FnOnce(&Recorded) -> T) -> T {
+        f(&*self.state.lock().await)
+    }
+
+    /// Poll `check` every 100ms until it returns true or `timeout_secs` pass
+    pub async fn wait_for(&self, timeout_secs: u64, check: impl Fn(&Recorded) -> bool) -> bool {
+        let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(timeout_secs);
+        while tokio::time::Instant::now() < deadline {
+            if check(&*self.state.lock().await) {
+                return true;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        false
+    }
+}
+
+impl Drop for FakeSupabase {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn handle(
+    State(state): State<Arc<Mutex<Recorded>>>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> (StatusCode, Json<Value>) {
+    // Same contract as the real function: gateway key + per-restaurant token
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or_default().to_string();
+    if header("apikey") != ANON_KEY || header("X-Printer-Token") != AUTH_TOKEN {
+        return (StatusCode::UNAUTHORIZED, Json(json!({ "error": "Invalid token" })));
+    }
+
+    let action = body["action"].as_str().unwrap_or_default().to_string();
+    let payload = &body["payload"];
+    let mut state = state.lock().await;
+    state.calls.push(action.clone());
+
+    let response = match action.as_str() {
+        "poll-jobs" => {
+            let printer_ids = payload["printer_ids"]
+                .as_array()
+                .map(|ids| ids.iter().filter_map(|id| id.as_str().map(String::from)).collect())
+                .unwrap_or_default();
+            state.heartbeats.push(printer_ids);
+            if !payload["queue"].is_null() {
+                state.queue_reports.push(payload["queue"].clone());
+            }
+            if !payload["resources"].is_null() {
+                state.resource_reports.push(payload["resources"].clone());
+            }
+
+            let jobs: Vec<Value> = state
+                .jobs
+                .iter()
+                .filter(|job| {
+                    let id = job["id"].as_str().unwrap_or_default();
+                    state.job_status.get(id).map(|s| s == "pending").unwrap_or(true)
+                })
+                .cloned()
+                .collect();
+
+            let mut response = json!({ "jobs": jobs });
+            if payload["include_failover_config"] == json!(true) {
+                if let Some(config) = &state.failover_config {
+                    response["failover_config"] = config.clone();
+                }
+            }
+            response
+        }
+        "update-job-status" => {
+            let job_id = payload["job_id"].as_str().unwrap_or_default().to_string();
+            let status = payload["status"].as_str().unwrap_or_default().to_string();
+            state.job_status.insert(job_id, status);
+            json!({ "success": true })
+        }
+        "insert-job-log" => {
+            state.job_logs.push(payload.clone());
+            json!({ "success": true })
+        }
+        "upsert-printers" => {
+            for printer in payload["printers"].as_array().cloned().unwrap_or_default() {
+                let id = printer["id"].as_str().unwrap_or_default().to_string();
+                state.printers.insert(id, printer);
+            }
+            json!({ "success": true })
+        }
+        "update-printer-status" => {
+            let printer_id = payload["printer_id"].as_str().unwrap_or_default().to_string();
+            let status = payload["status"].as_str().unwrap_or_default().to_string();
+            state.printer_status.insert(printer_id, status);
+            json!({ "success": true })
+        }
+        "report-self-test" => {
+            state.self_tests.push(payload.clone());
+            json!({ "success": true })
+        }
+        "upload-support-bundle" => json!({ "bundle_id": "bundle-1" }),
+        _ => return (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("Unknown action: {}", action) }))),
+    };
+
+    (StatusCode::OK, Json(response))
+}
+
+/// TCP listener standing in for a network ESC/POS printer; collects everything written to it
+pub struct FakeNetworkPrinter {
+    pub address: String,
+    received: Arc<Mutex<Vec<u8>>>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl FakeNetworkPrinter {
+    pub async fn start() -> Self {
+        use tokio::io::AsyncReadExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let sink = received.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let sink = sink.clone();
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    while let Ok(n) = stream.read(&mut buf).await {
+                        if n == 0 {
+                            break;
+                        }
+                        sink.lock().await.extend_from_slice(&buf[..n]);
+                    }
+                });
+            }
+        });
+
+        Self { address, received, server }
+    }
+
+    /// Received bytes, lossily decoded (ESC/POS text is plain ASCII between commands)
+    pub async fn printed_text(&self) -> String {
+        String::from_utf8_lossy(&self.received.lock().await).to_string()
+    }
+}
+
+impl Drop for FakeNetworkPrinter {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AppConfig, ConnectionType, PrinterCapabilities, PrinterConfig};
+    use crate::job_poller::JobPoller;
+    use crate::printer::PrinterManager;
+    use crate::queue::QueueManager;
+    use crate::telemetry::TelemetryCollector;
+    use crate::transform::TransformRules;
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn test_client_contract() {
+        let fake = FakeSupabase::start().await;
+        let client = fake.client();
+
+        fake.push_job(json!({ "id": "job-1", "order_number": "42", "station": "bar", "items": [] })).await;
+        fake.set_failover_config(json!({ "p1": ["p2"] })).await;
+
+        let resources = crate::resources::ResourceUsage {
+            cpu_percent: Some(3.5),
+            rss_bytes: Some(48 * 1024 * 1024),
+            open_fds: Some(37),
+            tokio_tasks: 24,
+            sampled_at: 1_700_000_000,
+        };
+        let poll = client
+            .poll_pending_jobs_with_failover(&["p1".to_string()], true, None, Some(&resources), None, None)
+            .await
+            .unwrap();
+        assert_eq!(poll.jobs.len(), 1);
+        assert_eq!(poll.failover_config.unwrap()["p1"], vec!["p2".to_string()]);
+
+        client.update_job_status("job-1", "completed", None, Some(120), None).await.unwrap();
+        assert_eq!(fake.job_status("job-1").await.as_deref(), Some("completed"));
+        assert!(client.poll_pending_jobs(&[]).await.unwrap().is_empty());
+
+        let self_test = crate::self_test::SelfTestResult {
+            printer_id: "p1".to_string(),
+            printer_name: "Kitchen".to_string(),
+            passed: false,
+            hw_status: None,
+            printed: false,
+            error: Some("offline".to_string()),
+            tested_at: 1_700_000_000,
+        };
+        client.report_self_test(&[self_test]).await.unwrap();
+
+        let unauthorized = SupabaseClient::new(fake.url.clone(), ANON_KEY.to_string(), Some("wrong".to_string()));
+        assert!(unauthorized.poll_pending_jobs(&[]).await.is_err());
+
+        fake.recorded(|r| {
+            assert_eq!(r.heartbeats[0], vec!["p1".to_string()]);
+            assert_eq!(r.resource_reports, vec![json!(resources)]);
+            assert_eq!(r.self_tests[0]["passed"], json!(false));
+            assert_eq!(r.self_tests[0]["results"][0]["error"], json!("offline"));
+            assert_eq!(r.calls.iter().filter(|c| *c == "poll-jobs").count(), 2);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_resent_completed_job_is_acked_not_reprinted() {
+        let fake = FakeSupabase::start().await;
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+
+        // Printed a week ago, local row already cleaned up, completion never reported
+        let job = json!({ "id": "job-1", "order_number": "42", "station": "bar", "items": [] });
+        queue
+            .enqueue(TransformRules::default().to_print_job(&job, "rest_123").unwrap())
+            .await
+            .unwrap();
+        queue.mark_completed("job-1", 100, None).await.unwrap();
+        clock.advance(std::time::Duration::from_secs(8 * 24 * 3600));
+        queue.cleanup_old_jobs().await.unwrap();
+
+        fake.push_job(job).await;
+        let poller = JobPoller::start(
+            "rest_123".to_string(),
+            Arc::new(fake.client()),
+            queue.clone(),
+            Vec::new(),
+            Arc::new(Mutex::new(HashMap::new())),
+            TransformRules::default(),
+        );
+
+        let acked = fake
+            .wait_for(10, |r| r.job_status.get("job-1").map(|s| s == "completed").unwrap_or(false))
+            .await;
+        poller.abort();
+
+        assert!(acked, "already printed job was not acknowledged");
+        assert!(queue.get_pending_jobs(10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_polled_job_is_printed_and_reported() {
+        let printer_manager = match PrinterManager::new() {
+            Ok(pm) => pm,
+            Err(_) => return, // No USB context in this environment
+        };
+        let fake = FakeSupabase::start().await;
+        let printer = FakeNetworkPrinter::start().await;
+
+        let printer_config = PrinterConfig {
+            id: "printer-1".to_string(),
+            name: "Bar".to_string(),
+            connection_type: ConnectionType::Network,
+            address: printer.address.clone(),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: Some("bar".to_string()),
+            is_primary: true,
+            capabilities: PrinterCapabilities {
+                cutter: true,
+                drawer: false,
+                qrcode: true,
+                max_width: 48,
+            },
+            paper_width_checked: true,
+            recovery: Default::default(),
+            compact: None,
+            transports: vec![],
+            serial: None,
+            paused: false,
+        };
+        printer_manager.add_printer(printer_config.clone()).await;
+
+        let config = Arc::new(Mutex::new(AppConfig {
+            restaurant_id: Some("rest_123".to_string()),
+            auth_token: Some(AUTH_TOKEN.to_string()),
+            supabase_url: fake.url.clone(),
+            supabase_anon_key: ANON_KEY.to_string(),
+            printers: vec![printer_config],
+            ..AppConfig::default()
+        }));
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        let (address_monitor, _address_rx) = crate::readdress::AddressMonitor::new();
+        let (breakers, _status_rx) = crate::CircuitBreakerRegistry::new(address_monitor);
+        let failover_map = Arc::new(Mutex::new(HashMap::new()));
+        let cancel = crate::job_cancel::JobCancellation::new();
+
+        fake.push_job(json!({
+            "id": "job-1",
+            "order_id": "order-1",
+            "order_number": "R001-0042",
+            "station": "bar",
+            "printer_id": "printer-1",
+            "timestamp": 1700000000000i64,
+            "items": [{ "quantity": 2, "name": "Espresso", "modifiers": [] }]
+        }))
+        .await;
+
+        let poller = JobPoller::start(
+            "rest_123".to_string(),
+            Arc::new(fake.client()),
+            queue.clone(),
+            vec!["printer-1".to_string()],
+            failover_map.clone(),
+            TransformRules::default(),
+        );
+        crate::start_job_processor(
+            queue.clone(),
+            printer_manager,
+            Arc::new(TelemetryCollector::new()),
+            Arc::new(breakers),
+            config,
+            cancel.clone(),
+            failover_map,
+        )
+        .await;
+
+        let completed = fake
+            .wait_for(15, |r| r.job_status.get("job-1").map(|s| s == "completed").unwrap_or(false))
+            .await;
+        poller.abort();
+        cancel.shutdown();
+
+        assert!(completed, "job was not reported as completed");
+        assert!(printer.printed_text().await.contains("Espresso"));
+        fake.recorded(|r| {
+            assert_eq!(r.job_logs[0]["status"], "completed");
+            assert_eq!(r.heartbeats[0], vec!["printer-1".to_string()]);
+            assert_eq!(r.queue_reports[0]["state"], "normal");
+        })
+        .await;
+    }
+}
@@ -0,0 +1,118 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use tracing::{debug, warn};
+
+/// Env var that enables fault injection in release builds (always available in debug builds)
+pub const ENABLE_ENV: &str = "EATSOME_FAULT_INJECTION";
+
+/// Faults to inject into the print, status and Edge Function paths (QA / resilience testing).
+///
+/// Percentages are applied deterministically: at 25%, exactly every 4th call fails, so
+/// integration tests get reproducible breaker/retry/failover behaviour.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaultConfig {
+    /// Share of network print writes that fail as a dropped connection (0-100)
+    pub network_write_drop_percent: u8,
+    /// Every DLE EOT status poll over the network times out
+    pub status_poll_timeout: bool,
+    /// Share of Edge Function calls that fail with HTTP 500 (0-100)
+    pub edge_error_percent: u8,
+    /// Only inject printer faults for these addresses ("ip:port"); empty = all printers
+    pub addresses: Vec<String>,
+}
+
+impl FaultConfig {
+    fn applies_to(&self, address: &str) -> bool {
+        self.addresses.is_empty() || self.addresses.iter().any(|a| a == address)
+    }
+}
+
+struct FaultInjector {
+    config: RwLock<FaultConfig>,
+    network_writes: AtomicU64,
+    edge_calls: AtomicU64,
+}
+
+static FAULTS: Lazy<FaultInjector> = Lazy::new(|| FaultInjector {
+    config: RwLock::new(FaultConfig::default()),
+    network_writes: AtomicU64::new(0),
+    edge_calls: AtomicU64::new(0),
+});
+
+/// Whether fault injection may be switched on in this build/process
+pub fn is_available() -> bool {
+    cfg!(debug_assertions) || std::env::var(ENABLE_ENV).map(|v| v == "1").unwrap_or(false)
+}
+
+/// Replace the active fault configuration and restart the deterministic counters
+pub fn configure(config: FaultConfig) -> Result<FaultConfig, String> {
+    if !is_available() {
+        return Err(format!("Fault injection is disabled (set {}=1)", ENABLE_ENV));
+    }
+    if config.network_write_drop_percent > 100 || config.edge_error_percent > 100 {
+        return Err("Percentages must be between 0 and 100".to_string());
+    }
+
+    if config != FaultConfig::default() {
+        warn!("Fault injection active: {:?}", config);
+    }
+    FAULTS.network_writes.store(0, Ordering::SeqCst);
+    FAULTS.edge_calls.store(0, Ordering::SeqCst);
+    *FAULTS.config.write().unwrap_or_else(|e| e.into_inner()) = config.clone();
+    Ok(config)
+}
+
+/// Current fault configuration
+pub fn current() -> FaultConfig {
+    FAULTS.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Call number `n` (1-based) fails when it crosses the next multiple of 100 / percent
+fn hits(counter: &AtomicU64, percent: u8) -> bool {
+    if percent == 0 {
+        return false;
+    }
+    let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+    let percent = percent as u64;
+    (n * percent) / 100 > ((n - 1) * percent) / 100
+}
+
+/// Should this network print write to `address` be dropped?
+pub fn drop_network_write(address: &str) -> bool {
+    let config = current();
+    let drop = config.applies_to(address) && hits(&FAULTS.network_writes, config.network_write_drop_percent);
+    if drop {
+        debug!("Injected fault: dropping network write to {}", address);
+    }
+    drop
+}
+
+/// Should this status poll of `address` time out?
+pub fn status_poll_timeout(address: &str) -> bool {
+    let config = current();
+    config.status_poll_timeout && config.applies_to(address)
+}
+
+/// Should this Edge Function call fail with HTTP 500?
+pub fn edge_error() -> bool {
+    hits(&FAULTS.edge_calls, current().edge_error_percent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentages_are_deterministic() {
+        let counter = AtomicU64::new(0);
+        let pattern: Vec<bool> = (0..8).map(|_| hits(&counter, 25)).collect();
+        assert_eq!(pattern, vec![false, false, false, true, false, false, false, true]);
+
+        let counter = AtomicU64::new(0);
+        assert!((0..10).all(|_| hits(&counter, 100)));
+        assert!((0..10).all(|_| !hits(&counter, 0)));
+    }
+}
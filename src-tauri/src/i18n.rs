@@ -0,0 +1,495 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// UI language for user-facing messages (errors, event payloads, troubleshooting advice)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// Dutch: the setup wizard and most restaurants
+    #[default]
+    Nl,
+    En,
+}
+
+/// Stable identifiers for user-facing messages. The frontend can translate by key;
+/// the rendered `message` is a fallback in the configured locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKey {
+    InvalidPairingCode,
+    RestaurantIdEmpty,
+    InvalidRestaurantId,
+    RestaurantCodeNotFound,
+    RestaurantLookupFailed,
+    InvalidReceiptText,
+    InvalidTimezone,
+    InvalidRoutingRule,
+    InvalidWebhookConfig,
+    DuplicatePrinterAddress,
+    InvalidPrinterName,
+    AuthTokenStoreFailed,
+    NoAuthToken,
+    PrinterNotFound,
+    UsbPrinterNotFound,
+    UsbNoAutomaticFix,
+    UsbChecking,
+    UsbAlreadyGranted,
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    UsbOpeningSettings,
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    UsbWaitingForUser,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    UsbInstallingRule,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    UsbRuleInstalled,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    UsbRuleFailed,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    UsbDeviceIdsUnknown,
+    UsbVerifying,
+    UsbGranted,
+    UsbStillDenied,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    PkexecNotFound,
+    QueueSnapshotNotPaired,
+    QueueSnapshotUnreadable,
+    InternalError,
+    ActionNone,
+    ActionUnreachable,
+    ActionConnectionRefused,
+    ActionTimeout,
+    ActionPaperOut,
+    ActionCoverOpen,
+    ActionCutterError,
+    ActionPrinterError,
+    ActionUsbPermission,
+    ActionUsbNotFound,
+    ActionBluetoothUnavailable,
+    ActionPrintFailed,
+    RecoverOpenCover,
+    RecoverClearJam,
+    RecoverCutterKnob,
+    RecoverCloseCover,
+    RecoverPowerCycle,
+    RecoverRetry,
+    NotifySelfTestFailedTitle,
+    NotifySelfTestFailedBody,
+    NotifyPaperOutTitle,
+    NotifyPaperOutBody,
+    NotifyCoverOpenTitle,
+    NotifyCoverOpenBody,
+    NotifyCutterJammed,
+    NotifyMechanicalError,
+    NotifyMechanicalFaultTitle,
+    NotifyMechanicalFaultRecurringTitle,
+    NotifyMechanicalFaultBody,
+    NotifyMechanicalFaultRecurringBody,
+    NotifyTokenExpiringTitle,
+    NotifyTokenExpiringBody,
+    NotifyTokenExpiredBody,
+    NotifyBreakerOpenTitle,
+    NotifyBreakerOpenBody,
+    NotifyUpdateAvailableTitle,
+    NotifyUpdateAvailableBody,
+}
+
+impl MessageKey {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageKey::InvalidPairingCode => "invalid_pairing_code",
+            MessageKey::RestaurantIdEmpty => "restaurant_id_empty",
+            MessageKey::InvalidRestaurantId => "invalid_restaurant_id",
+            MessageKey::RestaurantCodeNotFound => "restaurant_code_not_found",
+            MessageKey::RestaurantLookupFailed => "restaurant_lookup_failed",
+            MessageKey::InvalidReceiptText => "invalid_receipt_text",
+            MessageKey::InvalidTimezone => "invalid_timezone",
+            MessageKey::InvalidRoutingRule => "invalid_routing_rule",
+            MessageKey::InvalidWebhookConfig => "invalid_webhook_config",
+            MessageKey::DuplicatePrinterAddress => "duplicate_printer_address",
+            MessageKey::InvalidPrinterName => "invalid_printer_name",
+            MessageKey::AuthTokenStoreFailed => "auth_token_store_failed",
+            MessageKey::NoAuthToken => "no_auth_token",
+            MessageKey::PrinterNotFound => "printer_not_found",
+            MessageKey::UsbPrinterNotFound => "usb_printer_not_found",
+            MessageKey::UsbNoAutomaticFix => "usb_no_automatic_fix",
+            MessageKey::UsbChecking => "usb_checking",
+            MessageKey::UsbAlreadyGranted => "usb_already_granted",
+            MessageKey::UsbOpeningSettings => "usb_opening_settings",
+            MessageKey::UsbWaitingForUser => "usb_waiting_for_user",
+            MessageKey::UsbInstallingRule => "usb_installing_rule",
+            MessageKey::UsbRuleInstalled => "usb_rule_installed",
+            MessageKey::UsbRuleFailed => "usb_rule_failed",
+            MessageKey::UsbDeviceIdsUnknown => "usb_device_ids_unknown",
+            MessageKey::UsbVerifying => "usb_verifying",
+            MessageKey::UsbGranted => "usb_granted",
+            MessageKey::UsbStillDenied => "usb_still_denied",
+            MessageKey::PkexecNotFound => "pkexec_not_found",
+            MessageKey::QueueSnapshotNotPaired => "queue_snapshot_not_paired",
+            MessageKey::QueueSnapshotUnreadable => "queue_snapshot_unreadable",
+            MessageKey::InternalError => "internal_error",
+            MessageKey::ActionNone => "action_none",
+            MessageKey::ActionUnreachable => "action_unreachable",
+            MessageKey::ActionConnectionRefused => "action_connection_refused",
+            MessageKey::ActionTimeout => "action_timeout",
+            MessageKey::ActionPaperOut => "action_paper_out",
+            MessageKey::ActionCoverOpen => "action_cover_open",
+            MessageKey::ActionCutterError => "action_cutter_error",
+            MessageKey::ActionPrinterError => "action_printer_error",
+            MessageKey::ActionUsbPermission => "action_usb_permission",
+            MessageKey::ActionUsbNotFound => "action_usb_not_found",
+            MessageKey::ActionBluetoothUnavailable => "action_bluetooth_unavailable",
+            MessageKey::ActionPrintFailed => "action_print_failed",
+            MessageKey::RecoverOpenCover => "recover_open_cover",
+            MessageKey::RecoverClearJam => "recover_clear_jam",
+            MessageKey::RecoverCutterKnob => "recover_cutter_knob",
+            MessageKey::RecoverCloseCover => "recover_close_cover",
+            MessageKey::RecoverPowerCycle => "recover_power_cycle",
+            MessageKey::RecoverRetry => "recover_retry",
+            MessageKey::NotifySelfTestFailedTitle => "notify_self_test_failed_title",
+            MessageKey::NotifySelfTestFailedBody => "notify_self_test_failed_body",
+            MessageKey::NotifyPaperOutTitle => "notify_paper_out_title",
+            MessageKey::NotifyPaperOutBody => "notify_paper_out_body",
+            MessageKey::NotifyCoverOpenTitle => "notify_cover_open_title",
+            MessageKey::NotifyCoverOpenBody => "notify_cover_open_body",
+            MessageKey::NotifyCutterJammed => "notify_cutter_jammed",
+            MessageKey::NotifyMechanicalError => "notify_mechanical_error",
+            MessageKey::NotifyMechanicalFaultTitle => "notify_mechanical_fault_title",
+            MessageKey::NotifyMechanicalFaultRecurringTitle => "notify_mechanical_fault_recurring_title",
+            MessageKey::NotifyMechanicalFaultBody => "notify_mechanical_fault_body",
+            MessageKey::NotifyMechanicalFaultRecurringBody => "notify_mechanical_fault_recurring_body",
+            MessageKey::NotifyTokenExpiringTitle => "notify_token_expiring_title",
+            MessageKey::NotifyTokenExpiringBody => "notify_token_expiring_body",
+            MessageKey::NotifyTokenExpiredBody => "notify_token_expired_body",
+            MessageKey::NotifyBreakerOpenTitle => "notify_breaker_open_title",
+            MessageKey::NotifyBreakerOpenBody => "notify_breaker_open_body",
+            MessageKey::NotifyUpdateAvailableTitle => "notify_update_available_title",
+            MessageKey::NotifyUpdateAvailableBody => "notify_update_available_body",
+        }
+    }
+
+    /// Message template; `{name}` placeholders are filled from params
+    fn template(&self, locale: Locale) -> &'static str {
+        match (self, locale) {
+            (MessageKey::InvalidPairingCode, Locale::Nl) => "Ongeldige code. Vul 9 cijfers in.",
+            (MessageKey::InvalidPairingCode, Locale::En) => "Invalid code. Enter 9 digits.",
+            (MessageKey::RestaurantIdEmpty, Locale::Nl) => "Restaurant-ID mag niet leeg zijn.",
+            (MessageKey::RestaurantIdEmpty, Locale::En) => "Restaurant ID cannot be empty.",
+            (MessageKey::InvalidRestaurantId, Locale::Nl) => {
+                "Ongeldige restaurant-ID: '{id}'. Gebruik je restaurantcode (bijv. W434N) of UUID."
+            }
+            (MessageKey::InvalidRestaurantId, Locale::En) => {
+                "Invalid restaurant identifier: '{id}'. Use your restaurant code (e.g., W434N) or UUID."
+            }
+            (MessageKey::RestaurantCodeNotFound, Locale::Nl) => {
+                "Restaurantcode '{code}' niet gevonden. Controleer de code en probeer opnieuw."
+            }
+            (MessageKey::RestaurantCodeNotFound, Locale::En) => {
+                "Restaurant code '{code}' not found. Check your code and try again."
+            }
+            (MessageKey::RestaurantLookupFailed, Locale::Nl) => "Kon restaurantcode '{code}' niet opzoeken: {error}",
+            (MessageKey::RestaurantLookupFailed, Locale::En) => "Could not look up restaurant code '{code}': {error}",
+            (MessageKey::InvalidReceiptText, Locale::Nl) => "Bon-{block} kan niet worden opgeslagen: {error}",
+            (MessageKey::InvalidReceiptText, Locale::En) => "Receipt {block} can't be saved: {error}",
+            (MessageKey::InvalidTimezone, Locale::Nl) => "Tijdzone kan niet worden opgeslagen: {error}",
+            (MessageKey::InvalidTimezone, Locale::En) => "Time zone can't be saved: {error}",
+            (MessageKey::InvalidRoutingRule, Locale::Nl) => "Routering kan niet worden opgeslagen: {error}",
+            (MessageKey::InvalidRoutingRule, Locale::En) => "Routing can't be saved: {error}",
+            (MessageKey::InvalidWebhookConfig, Locale::Nl) => "Webhooks kunnen niet worden opgeslagen: {error}",
+            (MessageKey::InvalidWebhookConfig, Locale::En) => "Webhooks can't be saved: {error}",
+            (MessageKey::DuplicatePrinterAddress, Locale::Nl) => {
+                "Printers {printers} gebruiken hetzelfde adres ({address}). Voeg ze samen of geef er een een ander adres."
+            }
+            (MessageKey::DuplicatePrinterAddress, Locale::En) => {
+                "Printers {printers} share one address ({address}). Merge them or give one another address."
+            }
+            (MessageKey::InvalidPrinterName, Locale::Nl) => "Printernaam kan niet worden opgeslagen: {error}",
+            (MessageKey::InvalidPrinterName, Locale::En) => "Printer name can't be saved: {error}",
+            (MessageKey::AuthTokenStoreFailed, Locale::Nl) => "Opslaan van de koppeling mislukt: {error}",
+            (MessageKey::AuthTokenStoreFailed, Locale::En) => "Failed to store auth token: {error}",
+            (MessageKey::NoAuthToken, Locale::Nl) => {
+                "Deze computer is nog niet gekoppeld. Koppel hem via de pagina POS-apparaten."
+            }
+            (MessageKey::NoAuthToken, Locale::En) => {
+                "No auth_token configured. Generate one from POS Devices page."
+            }
+            (MessageKey::PrinterNotFound, Locale::Nl) => "Printer niet gevonden: {printer_id}",
+            (MessageKey::PrinterNotFound, Locale::En) => "Printer not found: {printer_id}",
+            (MessageKey::UsbPrinterNotFound, Locale::Nl) => "USB-printer niet gevonden: {address}",
+            (MessageKey::UsbPrinterNotFound, Locale::En) => "USB printer not found: {address}",
+            (MessageKey::UsbNoAutomaticFix, Locale::Nl) => {
+                "Geen automatische oplossing voor USB-toegang beschikbaar op dit platform."
+            }
+            (MessageKey::UsbNoAutomaticFix, Locale::En) => {
+                "No automatic USB permission fix available on this platform."
+            }
+            (MessageKey::UsbChecking, Locale::Nl) => "USB-toegang controleren",
+            (MessageKey::UsbChecking, Locale::En) => "Checking USB access",
+            (MessageKey::UsbAlreadyGranted, Locale::Nl) => "USB-toegang is al in orde",
+            (MessageKey::UsbAlreadyGranted, Locale::En) => "USB access already granted",
+            (MessageKey::UsbOpeningSettings, Locale::Nl) => "Systeeminstellingen openen - sta het USB-accessoire toe",
+            (MessageKey::UsbOpeningSettings, Locale::En) => "Opening System Settings - approve the USB accessory",
+            (MessageKey::UsbWaitingForUser, Locale::Nl) => "Wachten op toestemming in Systeeminstellingen",
+            (MessageKey::UsbWaitingForUser, Locale::En) => "Waiting for approval in System Settings",
+            (MessageKey::UsbInstallingRule, Locale::Nl) => "Wachten op toestemming van de beheerder",
+            (MessageKey::UsbInstallingRule, Locale::En) => "Waiting for administrator approval",
+            (MessageKey::UsbRuleInstalled, Locale::Nl) => "udev-regel geïnstalleerd",
+            (MessageKey::UsbRuleInstalled, Locale::En) => "udev rule installed",
+            (MessageKey::UsbRuleFailed, Locale::Nl) => "Installeren van de udev-regel mislukt of geannuleerd: {error}",
+            (MessageKey::UsbRuleFailed, Locale::En) => "Installing the udev rule failed or was cancelled: {error}",
+            (MessageKey::UsbDeviceIdsUnknown, Locale::Nl) => {
+                "Vendor- en product-ID van de USB-printer op {address} konden niet worden gelezen"
+            }
+            (MessageKey::UsbDeviceIdsUnknown, Locale::En) => {
+                "Could not read the vendor and product ID of the USB printer at {address}"
+            }
+            (MessageKey::UsbVerifying, Locale::Nl) => "USB-toegang controleren",
+            (MessageKey::UsbVerifying, Locale::En) => "Verifying USB access",
+            (MessageKey::UsbGranted, Locale::Nl) => "USB-toegang verleend",
+            (MessageKey::UsbGranted, Locale::En) => "USB access granted",
+            (MessageKey::UsbStillDenied, Locale::Nl) => "USB-toegang nog steeds geweigerd - koppel de printer los en sluit hem opnieuw aan",
+            (MessageKey::UsbStillDenied, Locale::En) => "USB access still denied - unplug and reconnect the printer",
+            (MessageKey::PkexecNotFound, Locale::Nl) => "pkexec niet gevonden. Handmatig uitvoeren: {command}",
+            (MessageKey::PkexecNotFound, Locale::En) => "pkexec not found. Run manually: {command}",
+            (MessageKey::QueueSnapshotNotPaired, Locale::Nl) => {
+                "Koppel deze computer eerst aan het restaurant; de wachtrij-export is versleuteld per restaurant."
+            }
+            (MessageKey::QueueSnapshotNotPaired, Locale::En) => {
+                "Pair this computer with the restaurant first; queue snapshots are encrypted per restaurant."
+            }
+            (MessageKey::QueueSnapshotUnreadable, Locale::Nl) => {
+                "Kan de wachtrij-export niet openen. Is hij gemaakt voor hetzelfde restaurant? ({error})"
+            }
+            (MessageKey::QueueSnapshotUnreadable, Locale::En) => {
+                "Could not open the queue snapshot. Was it exported for the same restaurant? ({error})"
+            }
+            (MessageKey::InternalError, Locale::Nl) => "Er ging iets mis: {error}",
+            (MessageKey::InternalError, Locale::En) => "Something went wrong: {error}",
+            (MessageKey::ActionNone, Locale::Nl) => "De printer werkt. Geen actie nodig.",
+            (MessageKey::ActionNone, Locale::En) => "Printer is working. No action needed.",
+            (MessageKey::ActionUnreachable, Locale::Nl) => {
+                "Controleer of de printer aan staat en de netwerkkabel is aangesloten. Staat hij aan, print dan de netwerkconfiguratie (houd FEED ingedrukt tijdens het aanzetten) en vergelijk het IP-adres met het hier ingestelde adres."
+            }
+            (MessageKey::ActionUnreachable, Locale::En) => {
+                "Check that the printer is switched on and its network cable is plugged in. If it is on, print its network configuration page (hold FEED while powering on) and compare the IP address with the one configured here."
+            }
+            (MessageKey::ActionConnectionRefused, Locale::Nl) => {
+                "De printer reageert maar weigert printopdrachten. Zet hem uit en weer aan; helpt dat niet, controleer dan of raw printing (poort 9100) aan staat in de webinstellingen van de printer."
+            }
+            (MessageKey::ActionConnectionRefused, Locale::En) => {
+                "The printer answers but refuses print jobs. Turn it off and on again; if that doesn't help, check that raw printing (port 9100) is enabled in the printer's web settings."
+            }
+            (MessageKey::ActionTimeout, Locale::Nl) => {
+                "De printer reageert te traag. Controleer de netwerkkabel of het wifi-signaal en herstart de printer. Blijft het gebeuren, sluit de printer dan bekabeld aan."
+            }
+            (MessageKey::ActionTimeout, Locale::En) => {
+                "The printer responds too slowly. Check the network cable/Wi-Fi signal and restart the printer. If it persists, move the printer onto a wired connection."
+            }
+            (MessageKey::ActionPaperOut, Locale::Nl) => "Plaats een nieuwe papierrol en sluit de klep.",
+            (MessageKey::ActionPaperOut, Locale::En) => "Load a new paper roll and close the cover.",
+            (MessageKey::ActionCoverOpen, Locale::Nl) => "Druk de klep van de printer stevig dicht tot hij klikt.",
+            (MessageKey::ActionCoverOpen, Locale::En) => "Close the printer cover firmly until it clicks.",
+            (MessageKey::ActionCutterError, Locale::Nl) => {
+                "Open de klep, verwijder vastgelopen papier bij het mes, sluit de klep en zet de printer uit en weer aan."
+            }
+            (MessageKey::ActionCutterError, Locale::En) => {
+                "Open the cover, remove any jammed paper near the cutter, close the cover and turn the printer off and on."
+            }
+            (MessageKey::ActionPrinterError, Locale::Nl) => {
+                "De printer meldt een fout. Zet hem uit, wacht 10 seconden en zet hem weer aan."
+            }
+            (MessageKey::ActionPrinterError, Locale::En) => {
+                "The printer reports an error. Turn it off, wait 10 seconds and turn it on again."
+            }
+            (MessageKey::ActionUsbPermission, Locale::Nl) => {
+                "De printer is aangesloten maar deze computer blokkeert de toegang. Sluit andere printersoftware en geef USB-toegang (zie USB-toegang instellen)."
+            }
+            (MessageKey::ActionUsbPermission, Locale::En) => {
+                "The printer is connected but this computer blocks access. Close other printer software and grant USB access (see USB permission setup)."
+            }
+            (MessageKey::ActionUsbNotFound, Locale::Nl) => {
+                "De USB-printer wordt niet gevonden. Controleer de USB-kabel aan beide kanten, probeer een andere USB-poort en controleer of de printer aan staat."
+            }
+            (MessageKey::ActionUsbNotFound, Locale::En) => {
+                "The USB printer is not detected. Check the USB cable on both ends, try another USB port and make sure the printer is switched on."
+            }
+            (MessageKey::ActionBluetoothUnavailable, Locale::Nl) => {
+                "De Bluetooth-printer wordt niet gevonden. Controleer of Bluetooth aan staat, de printer aan staat en binnen een paar meter is, en niet met een ander apparaat verbonden is."
+            }
+            (MessageKey::ActionBluetoothUnavailable, Locale::En) => {
+                "The Bluetooth printer is not found. Make sure Bluetooth is on, the printer is switched on and within a few meters, and not connected to another device."
+            }
+            (MessageKey::ActionPrintFailed, Locale::Nl) => {
+                "Alle controles zijn geslaagd maar de testprint mislukte. Herstart de printer en probeer opnieuw; neem contact op met support als het blijft mislukken."
+            }
+            (MessageKey::ActionPrintFailed, Locale::En) => {
+                "All checks passed but the test print failed. Restart the printer and try again; contact support if it keeps failing."
+            }
+            (MessageKey::RecoverOpenCover, Locale::Nl) => "Open de klep van de printer.",
+            (MessageKey::RecoverOpenCover, Locale::En) => "Open the printer cover.",
+            (MessageKey::RecoverClearJam, Locale::Nl) => {
+                "Haal vastgelopen of gescheurd papier weg, ook snippers bij het mes en onder de rol."
+            }
+            (MessageKey::RecoverClearJam, Locale::En) => {
+                "Remove jammed or torn paper, including scraps near the cutter and under the roll."
+            }
+            (MessageKey::RecoverCutterKnob, Locale::Nl) => {
+                "Gaat de klep niet open of staat het mes vast? Draai het knopje achter het frontje tot het mes terug is."
+            }
+            (MessageKey::RecoverCutterKnob, Locale::En) => {
+                "Cover won't open or the blade is stuck? Turn the knob behind the front panel until the blade retracts."
+            }
+            (MessageKey::RecoverCloseCover, Locale::Nl) => "Leg de rol terug en druk de klep dicht tot hij klikt.",
+            (MessageKey::RecoverCloseCover, Locale::En) => "Put the roll back and close the cover until it clicks.",
+            (MessageKey::RecoverPowerCycle, Locale::Nl) => "Zet de printer uit, wacht 10 seconden en zet hem weer aan.",
+            (MessageKey::RecoverPowerCycle, Locale::En) => "Turn the printer off, wait 10 seconds and turn it on again.",
+            (MessageKey::RecoverRetry, Locale::Nl) => "Klik op Opnieuw proberen. Wachtende bonnen worden daarna geprint.",
+            (MessageKey::RecoverRetry, Locale::En) => "Click Try again. Waiting tickets print once the printer is clear.",
+            (MessageKey::NotifySelfTestFailedTitle, Locale::Nl) => "{printer}: zelftest mislukt",
+            (MessageKey::NotifySelfTestFailedTitle, Locale::En) => "{printer}: failed its self-test",
+            (MessageKey::NotifySelfTestFailedBody, Locale::Nl) => {
+                "{error}. Controleer de printer voordat de service begint."
+            }
+            (MessageKey::NotifySelfTestFailedBody, Locale::En) => "{error}. Check the printer before service starts.",
+            (MessageKey::NotifyPaperOutTitle, Locale::Nl) => "{printer}: papier op",
+            (MessageKey::NotifyPaperOutTitle, Locale::En) => "{printer}: out of paper",
+            (MessageKey::NotifyPaperOutBody, Locale::Nl) => {
+                "Plaats een nieuwe papierrol. Bonnen wachten in de wachtrij tot het papier terug is."
+            }
+            (MessageKey::NotifyPaperOutBody, Locale::En) => {
+                "Load a new paper roll. Tickets wait in the queue until it is back."
+            }
+            (MessageKey::NotifyCoverOpenTitle, Locale::Nl) => "{printer}: klep open",
+            (MessageKey::NotifyCoverOpenTitle, Locale::En) => "{printer}: cover open",
+            (MessageKey::NotifyCoverOpenBody, Locale::Nl) => {
+                "Sluit de klep van de printer. Bonnen wachten in de wachtrij tot hij dicht is."
+            }
+            (MessageKey::NotifyCoverOpenBody, Locale::En) => {
+                "Close the printer cover. Tickets wait in the queue until it is closed."
+            }
+            (MessageKey::NotifyCutterJammed, Locale::Nl) => "mes vastgelopen",
+            (MessageKey::NotifyCutterJammed, Locale::En) => "cutter jammed",
+            (MessageKey::NotifyMechanicalError, Locale::Nl) => "papierstoring of mechanische fout",
+            (MessageKey::NotifyMechanicalError, Locale::En) => "paper jam or mechanical error",
+            (MessageKey::NotifyMechanicalFaultTitle, Locale::Nl) => "{printer}: {fault}",
+            (MessageKey::NotifyMechanicalFaultTitle, Locale::En) => "{printer}: {fault}",
+            (MessageKey::NotifyMechanicalFaultRecurringTitle, Locale::Nl) => {
+                "{printer}: opnieuw {fault} ({count}x deze week)"
+            }
+            (MessageKey::NotifyMechanicalFaultRecurringTitle, Locale::En) => {
+                "{printer}: {fault} again ({count}x this week)"
+            }
+            (MessageKey::NotifyMechanicalFaultBody, Locale::Nl) => {
+                "Volg de stappen in de app om het op te lossen. Bonnen wachten in de wachtrij tot het verholpen is."
+            }
+            (MessageKey::NotifyMechanicalFaultBody, Locale::En) => {
+                "Follow the steps in the app to clear it. Tickets wait in the queue until it is fixed."
+            }
+            (MessageKey::NotifyMechanicalFaultRecurringBody, Locale::Nl) => {
+                "Volg de stappen in de app om het op te lossen. De printer loopt steeds vast: laat hem nakijken."
+            }
+            (MessageKey::NotifyMechanicalFaultRecurringBody, Locale::En) => {
+                "Follow the steps in the app to clear it. The printer keeps jamming: have it serviced."
+            }
+            (MessageKey::NotifyTokenExpiringTitle, Locale::Nl) => "Koppeling van de printer verloopt binnenkort",
+            (MessageKey::NotifyTokenExpiringTitle, Locale::En) => "Printer pairing expires soon",
+            (MessageKey::NotifyTokenExpiringBody, Locale::Nl) => {
+                "Verloopt over {hours} uur. Koppel dit apparaat opnieuw met een nieuwe code uit het dashboard."
+            }
+            (MessageKey::NotifyTokenExpiringBody, Locale::En) => {
+                "Expires in {hours} hours. Pair this device again with a new code from the dashboard."
+            }
+            (MessageKey::NotifyTokenExpiredBody, Locale::Nl) => {
+                "Er wordt niet meer geprint. Koppel dit apparaat opnieuw met een nieuwe code uit het dashboard."
+            }
+            (MessageKey::NotifyTokenExpiredBody, Locale::En) => {
+                "Printing has stopped. Pair this device again with a new code from the dashboard."
+            }
+            (MessageKey::NotifyBreakerOpenTitle, Locale::Nl) => "{printer}: printen gepauzeerd",
+            (MessageKey::NotifyBreakerOpenTitle, Locale::En) => "{printer}: printing paused",
+            (MessageKey::NotifyBreakerOpenBody, Locale::Nl) => {
+                "Meerdere prints mislukten achter elkaar. Bonnen gaan naar een reserveprinter of wachten tot hij hersteld is."
+            }
+            (MessageKey::NotifyBreakerOpenBody, Locale::En) => {
+                "Several prints failed in a row. Jobs go to a backup printer or wait until it recovers."
+            }
+            (MessageKey::NotifyUpdateAvailableTitle, Locale::Nl) => "Update {version} beschikbaar",
+            (MessageKey::NotifyUpdateAvailableTitle, Locale::En) => "Update {version} available",
+            (MessageKey::NotifyUpdateAvailableBody, Locale::Nl) => {
+                "Installeer hem vanuit het dashboard als het rustig is in de keuken."
+            }
+            (MessageKey::NotifyUpdateAvailableBody, Locale::En) => {
+                "Install it from the dashboard when the kitchen is quiet."
+            }
+        }
+    }
+}
+
+/// A user-facing message: stable key + params for frontend localization, plus the
+/// message rendered in the configured locale.
+///
+/// Also used as the error type of user-facing Tauri commands, so the frontend
+/// receives `{ key, params, message }` instead of a bare string.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocalizedMessage {
+    pub key: &'static str,
+    pub params: BTreeMap<&'static str, String>,
+    pub message: String,
+}
+
+impl LocalizedMessage {
+    pub fn new(locale: Locale, key: MessageKey, params: &[(&'static str, String)]) -> Self {
+        let params: BTreeMap<&'static str, String> = params.iter().cloned().collect();
+        let mut message = key.template(locale).to_string();
+        for (name, value) in &params {
+            message = message.replace(&format!("{{{}}}", name), value);
+        }
+
+        Self {
+            key: key.as_str(),
+            params,
+            message,
+        }
+    }
+
+    /// Wrap an unexpected (non user-actionable) error
+    pub fn internal(locale: Locale, error: impl std::fmt::Display) -> Self {
+        Self::new(locale, MessageKey::InternalError, &[("error", error.to_string())])
+    }
+}
+
+impl std::fmt::Display for LocalizedMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_with_params() {
+        let msg = LocalizedMessage::new(
+            Locale::En,
+            MessageKey::RestaurantCodeNotFound,
+            &[("code", "W434N".to_string())],
+        );
+        assert_eq!(msg.key, "restaurant_code_not_found");
+        assert_eq!(msg.params["code"], "W434N");
+        assert_eq!(msg.message, "Restaurant code 'W434N' not found. Check your code and try again.");
+
+        let nl = LocalizedMessage::new(Locale::Nl, MessageKey::InvalidPairingCode, &[]);
+        assert_eq!(nl.message, "Ongeldige code. Vul 9 cijfers in.");
+
+        let title = LocalizedMessage::new(Locale::Nl, MessageKey::NotifyPaperOutTitle, &[("printer", "Keuken".to_string())]);
+        assert_eq!(title.message, "Keuken: papier op");
+    }
+
+    #[test]
+    fn test_serializes_key_params_and_message() {
+        let msg = LocalizedMessage::internal(Locale::En, "disk full");
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["key"], "internal_error");
+        assert_eq!(json["params"]["error"], "disk full");
+        assert_eq!(json["message"], "Something went wrong: disk full");
+    }
+}
@@ -0,0 +1,135 @@
+use crate::troubleshoot::FailureClass;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Incidents kept in the queue database
+pub const RETENTION_SECS: i64 = 90 * 24 * 3600;
+
+/// Printer-down incidents: a printer unreachable for longer than `after_minutes` gets a
+/// record (start, end, affected jobs, probable cause) for post-mortems of an evening's
+/// outages, kept locally and synced to Supabase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IncidentConfig {
+    pub after_minutes: u32,
+}
+
+impl Default for IncidentConfig {
+    fn default() -> Self {
+        Self { after_minutes: 5 }
+    }
+}
+
+/// One outage of one printer (`get_printer_incidents`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Incident {
+    pub id: i64,
+    pub printer_id: String,
+    /// Unix seconds of the first failed status check
+    pub started_at: i64,
+    /// None while the printer is still down
+    pub ended_at: Option<i64>,
+    /// Failure class of the first failed check (`unreachable`, `connection_refused`,
+    /// `cover_open`, ...)
+    pub cause: String,
+    /// Jobs for the printer that waited during the outage (counted up to now while open)
+    pub affected_jobs: u64,
+}
+
+/// Name of a failure class as stored in `Incident.cause`
+pub fn cause_name(class: &FailureClass) -> String {
+    serde_json::to_value(class)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// A printer that is down, with its incident once it lasted long enough
+#[derive(Debug, Clone)]
+struct Outage {
+    since: i64,
+    cause: String,
+    incident_id: Option<i64>,
+}
+
+/// Follows the status poller's checks and says when an incident opens or closes; the
+/// records themselves are the queue's
+#[derive(Debug, Default)]
+pub struct IncidentTracker {
+    outages: HashMap<String, Outage>,
+}
+
+impl IncidentTracker {
+    /// Pick up the incidents still open from before a restart
+    pub fn resume(open: &[Incident]) -> Self {
+        let outages = open
+            .iter()
+            .map(|incident| {
+                let outage = Outage {
+                    since: incident.started_at,
+                    cause: incident.cause.clone(),
+                    incident_id: Some(incident.id),
+                };
+                (incident.printer_id.clone(), outage)
+            })
+            .collect();
+        Self { outages }
+    }
+
+    /// A failed check. Returns the start and cause of an incident to open once the printer
+    /// has been down for `after_secs`.
+    pub fn down(&mut self, printer_id: &str, cause: &str, now: i64, after_secs: i64) -> Option<(i64, String)> {
+        let outage = self.outages.entry(printer_id.to_string()).or_insert_with(|| Outage {
+            since: now,
+            cause: cause.to_string(),
+            incident_id: None,
+        });
+        (outage.incident_id.is_none() && now - outage.since >= after_secs).then(|| (outage.since, outage.cause.clone()))
+    }
+
+    /// The incident opened for the printer's outage
+    pub fn opened(&mut self, printer_id: &str, incident_id: i64) {
+        if let Some(outage) = self.outages.get_mut(printer_id) {
+            outage.incident_id = Some(incident_id);
+        }
+    }
+
+    /// A healthy check. Returns the incident to close, if the outage had one.
+    pub fn up(&mut self, printer_id: &str) -> Option<i64> {
+        self.outages.remove(printer_id).and_then(|outage| outage.incident_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_incident_opens_after_threshold() {
+        let mut tracker = IncidentTracker::default();
+        // A blip shorter than the threshold leaves no record
+        assert_eq!(tracker.down("bar", "timeout", 1000, 300), None);
+        assert_eq!(tracker.up("bar"), None);
+
+        assert_eq!(tracker.down("bar", "unreachable", 2000, 300), None);
+        // The cause is the first failure's
+        assert_eq!(tracker.down("bar", "timeout", 2300, 300), Some((2000, "unreachable".to_string())));
+        tracker.opened("bar", 7);
+        assert_eq!(tracker.down("bar", "timeout", 2330, 300), None);
+        assert_eq!(tracker.up("bar"), Some(7));
+        assert_eq!(tracker.up("bar"), None);
+
+        let open = Incident {
+            id: 9,
+            printer_id: "kitchen".to_string(),
+            started_at: 500,
+            ended_at: None,
+            cause: "cover_open".to_string(),
+            affected_jobs: 3,
+        };
+        assert_eq!(cause_name(&FailureClass::ConnectionRefused), "connection_refused");
+        let mut resumed = IncidentTracker::resume(&[open]);
+        assert_eq!(resumed.down("kitchen", "timeout", 5000, 300), None);
+        assert_eq!(resumed.up("kitchen"), Some(9));
+    }
+}
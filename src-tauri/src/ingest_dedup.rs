@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// Deduplication of upstream jobs by job id, across the transports that deliver them
+/// (`QueueManager::ingest`). The ids seen are kept in the queue database, so a job that
+/// arrives again over another transport, or again after a restart, is dropped instead of
+/// queued twice.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestDedupConfig {
+    pub enabled: bool,
+    /// How long a job id is remembered
+    pub ttl_minutes: u32,
+}
+
+impl Default for IngestDedupConfig {
+    fn default() -> Self {
+        Self { enabled: true, ttl_minutes: 24 * 60 }
+    }
+}
+
+impl IngestDedupConfig {
+    pub fn ttl_secs(&self) -> i64 {
+        self.ttl_minutes.max(1) as i64 * 60
+    }
+}
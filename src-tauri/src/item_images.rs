@@ -0,0 +1,184 @@
+use crate::escpos::{PaperWidth, PrintItem, Thumbnails};
+use crate::raster::{self, RasterOptions};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// Largest image file downloaded for a thumbnail
+const MAX_DOWNLOAD_BYTES: usize = 2 * 1024 * 1024;
+
+/// Rendered thumbnails kept in memory (a menu's worth)
+const RENDERED_CACHE_CAPACITY: usize = 256;
+
+/// Per-item thumbnails on receipts (`AppConfig.item_images`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemImageConfig {
+    /// Print `PrintItem.image_url` above each item. Bluetooth printers never get them.
+    pub enabled: bool,
+    /// Tallest printed thumbnail, in dots (203 dpi: 96 dots ≈ 12mm)
+    pub max_height: u32,
+    /// Give up on an image that hasn't downloaded in this many seconds (the item prints
+    /// without it; the next ticket tries again)
+    pub download_timeout_secs: u64,
+}
+
+impl Default for ItemImageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_height: 96,
+            download_timeout_secs: 3,
+        }
+    }
+}
+
+/// Downloads item images once, keeps the files on disk and the rendered raster
+/// commands in memory
+pub struct ItemImages {
+    config: Mutex<ItemImageConfig>,
+    dir: PathBuf,
+    client: reqwest::Client,
+    rendered: Mutex<HashMap<String, Arc<Vec<u8>>>>,
+}
+
+impl ItemImages {
+    /// Image files are cached in `dir` (created on first download)
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            config: Mutex::new(ItemImageConfig::default()),
+            dir,
+            client: reqwest::Client::new(),
+            rendered: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Apply settings (config load / save). Rendered thumbnails depend on the max height.
+    pub async fn set_config(&self, config: ItemImageConfig) {
+        let mut current = self.config.lock().await;
+        if *current != config {
+            self.rendered.lock().await.clear();
+            *current = config;
+        }
+    }
+
+    /// Raster commands for every item image on a ticket, keyed by URL. Images that fail
+    /// to download or decode are left out so the ticket still prints.
+    pub async fn thumbnails(&self, items: &[PrintItem], paper_width: PaperWidth) -> Thumbnails {
+        let config = self.config.lock().await.clone();
+        let mut thumbnails = Thumbnails::new();
+        if !config.enabled {
+            return thumbnails;
+        }
+
+        for url in items.iter().filter_map(|item| item.image_url.as_deref()) {
+            if thumbnails.contains_key(url) {
+                continue;
+            }
+            match self.thumbnail(url, paper_width, &config).await {
+                Ok(commands) => {
+                    thumbnails.insert(url.to_string(), commands);
+                }
+                Err(e) => warn!("Skipping item image {}: {}", url, e),
+            }
+        }
+        thumbnails
+    }
+
+    async fn thumbnail(&self, url: &str, paper_width: PaperWidth, config: &ItemImageConfig) -> Result<Arc<Vec<u8>>, String> {
+        let key = format!("{}:{}", url, paper_width.dots());
+        if let Some(cached) = self.rendered.lock().await.get(&key) {
+            return Ok(cached.clone());
+        }
+
+        let bytes = self.image_bytes(url, config).await?;
+        let img = image::load_from_memory(&bytes).map_err(|e| format!("unreadable image: {}", e))?;
+        let rendered = Arc::new(raster::thumbnail(
+            &img,
+            paper_width.dots(),
+            config.max_height,
+            &RasterOptions::default(),
+        ));
+
+        let mut cache = self.rendered.lock().await;
+        if cache.len() >= RENDERED_CACHE_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(key, rendered.clone());
+        Ok(rendered)
+    }
+
+    /// Image file from the disk cache, downloaded on a miss
+    async fn image_bytes(&self, url: &str, config: &ItemImageConfig) -> Result<Vec<u8>, String> {
+        let path = cache_file(&self.dir, url)?;
+        if let Ok(bytes) = tokio::fs::read(&path).await {
+            return Ok(bytes);
+        }
+
+        let response = self
+            .client
+            .get(url)
+            .timeout(Duration::from_secs(config.download_timeout_secs.max(1)))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("download failed: {}", e))?;
+        if response.content_length().is_some_and(|len| len as usize > MAX_DOWNLOAD_BYTES) {
+            return Err("image too large".to_string());
+        }
+        let bytes = response.bytes().await.map_err(|e| format!("download failed: {}", e))?;
+        if bytes.len() > MAX_DOWNLOAD_BYTES {
+            return Err("image too large".to_string());
+        }
+
+        // A failed cache write only costs a download next time
+        if let Err(e) = tokio::fs::create_dir_all(&self.dir).await {
+            warn!("Cannot create item image cache {}: {}", self.dir.display(), e);
+        } else if let Err(e) = tokio::fs::write(&path, &bytes).await {
+            warn!("Cannot cache item image {}: {}", path.display(), e);
+        }
+        debug!("Downloaded item image {} ({} bytes)", url, bytes.len());
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Disk cache file for an image URL (http/https only)
+fn cache_file(dir: &Path, url: &str) -> Result<PathBuf, String> {
+    let parsed = url::Url::parse(url).map_err(|e| format!("invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("unsupported URL scheme {}", parsed.scheme()));
+    }
+    Ok(dir.join(hex::encode(Sha256::digest(url.as_bytes()))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cache_file_and_disabled_config() {
+        let dir = Path::new("/tmp/item-images");
+        let a = cache_file(dir, "https://cdn.eatsome.nl/items/burger.png").unwrap();
+        assert_eq!(a, cache_file(dir, "https://cdn.eatsome.nl/items/burger.png").unwrap());
+        assert_ne!(a, cache_file(dir, "https://cdn.eatsome.nl/items/fries.png").unwrap());
+        assert!(cache_file(dir, "file:///etc/passwd").is_err());
+        assert!(cache_file(dir, "not a url").is_err());
+
+        // Off by default: nothing is downloaded
+        let images = ItemImages::new(dir.to_path_buf());
+        let items = vec![PrintItem {
+            quantity: 1,
+            name: "Burger".to_string(),
+            modifiers: vec![],
+            notes: None,
+            image_url: Some("https://cdn.eatsome.nl/items/burger.png".to_string()),
+            pricing: None,
+        }];
+        assert!(images.thumbnails(&items, PaperWidth::Width80mm).await.is_empty());
+    }
+}
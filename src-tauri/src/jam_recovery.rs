@@ -0,0 +1,145 @@
+use crate::i18n::{Locale, LocalizedMessage, MessageKey};
+use crate::status::PrinterHwStatus;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Faults within this window count towards "keeps happening" (a week of service)
+pub const RECURRING_WINDOW_SECS: i64 = 7 * 24 * 3600;
+/// Faults within the window after which staff are told to get the printer serviced
+pub const RECURRING_THRESHOLD: u32 = 3;
+/// An automatic recovery attempt on the same printer at most this often (a jam that
+/// is still in the cutter won't clear by resetting again)
+pub const AUTO_ATTEMPT_COOLDOWN_SECS: i64 = 300;
+/// Time the printer gets after the recovery commands before its status is read again
+pub const SETTLE_MS: u64 = 1500;
+
+/// Mechanical fault reported by DLE EOT 3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MechanicalFault {
+    /// Auto-cutter blade stuck, usually paper jammed around it
+    Cutter,
+    /// Paper jam, head or motor error the printer won't clear on its own
+    Unrecoverable,
+}
+
+impl MechanicalFault {
+    /// The fault a status reports, cutter first (it has the more specific remedy)
+    pub fn from_status(status: &PrinterHwStatus) -> Option<Self> {
+        if status.cutter_error {
+            Some(MechanicalFault::Cutter)
+        } else if status.unrecoverable_error {
+            Some(MechanicalFault::Unrecoverable)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MechanicalFault::Cutter => "cutter",
+            MechanicalFault::Unrecoverable => "unrecoverable",
+        }
+    }
+}
+
+/// Steps staff walk through on the dashboard when the automatic attempt didn't clear
+/// the fault. The last step is always "retry" (`attempt_printer_recovery`).
+pub fn guide_steps(fault: MechanicalFault, locale: Locale) -> Vec<LocalizedMessage> {
+    let keys: &[MessageKey] = match fault {
+        MechanicalFault::Cutter => &[
+            MessageKey::RecoverOpenCover,
+            MessageKey::RecoverClearJam,
+            MessageKey::RecoverCutterKnob,
+            MessageKey::RecoverCloseCover,
+            MessageKey::RecoverRetry,
+        ],
+        MechanicalFault::Unrecoverable => &[
+            MessageKey::RecoverOpenCover,
+            MessageKey::RecoverClearJam,
+            MessageKey::RecoverCloseCover,
+            MessageKey::RecoverPowerCycle,
+            MessageKey::RecoverRetry,
+        ],
+    };
+    keys.iter().map(|key| LocalizedMessage::new(locale, *key, &[])).collect()
+}
+
+/// Payload of the `printer-recovery-guide` event
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryGuide {
+    pub printer_id: String,
+    pub printer_name: String,
+    pub fault: MechanicalFault,
+    pub steps: Vec<LocalizedMessage>,
+    /// Faults on this printer within `RECURRING_WINDOW_SECS`, this one included
+    pub occurrences: u32,
+    /// `occurrences` reached `RECURRING_THRESHOLD`: the printer needs service
+    pub recurring: bool,
+}
+
+/// Result of a recovery attempt (automatic or from the guided flow)
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryAttempt {
+    pub printer_id: String,
+    /// None when the printer reported no fault any more (nothing was sent)
+    pub fault: Option<MechanicalFault>,
+    pub recovered: bool,
+    /// Status string after the attempt, None if the printer didn't answer
+    pub status: Option<String>,
+    pub automatic: bool,
+}
+
+/// Mechanical faults per printer over a period (`get_mechanical_faults`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MechanicalFaultSummary {
+    pub printer_id: String,
+    pub faults: u32,
+    /// Cleared by a recovery attempt (the rest needed staff or a power cycle)
+    pub recovered: u32,
+    /// Unix seconds of the most recent fault
+    pub last_at: i64,
+}
+
+/// When each printer last got an automatic recovery attempt
+#[derive(Debug, Default)]
+pub struct AutoAttempts {
+    last: HashMap<String, i64>,
+}
+
+impl AutoAttempts {
+    /// Whether an automatic attempt may run now; if so it counts as made
+    pub fn try_start(&mut self, printer_id: &str, now: i64) -> bool {
+        match self.last.get(printer_id) {
+            Some(last) if now - last < AUTO_ATTEMPT_COOLDOWN_SECS => false,
+            _ => {
+                self.last.insert(printer_id.to_string(), now);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_from_status_and_attempt_cooldown() {
+        let healthy = PrinterHwStatus::healthy();
+        assert_eq!(MechanicalFault::from_status(&healthy), None);
+        let both = PrinterHwStatus { cutter_error: true, unrecoverable_error: true, ..healthy.clone() };
+        assert_eq!(MechanicalFault::from_status(&both), Some(MechanicalFault::Cutter));
+        let jam = PrinterHwStatus { unrecoverable_error: true, ..healthy };
+        assert_eq!(MechanicalFault::from_status(&jam), Some(MechanicalFault::Unrecoverable));
+
+        let steps = guide_steps(MechanicalFault::Unrecoverable, Locale::En);
+        assert_eq!(steps.last().unwrap().key, "recover_retry");
+
+        let mut attempts = AutoAttempts::default();
+        assert!(attempts.try_start("p1", 1_000));
+        assert!(!attempts.try_start("p1", 1_000 + AUTO_ATTEMPT_COOLDOWN_SECS - 1));
+        assert!(attempts.try_start("p2", 1_000));
+        assert!(attempts.try_start("p1", 1_000 + AUTO_ATTEMPT_COOLDOWN_SECS));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::queue::PrintJob;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// Parsed payloads kept in memory; the processor only looks at a handful of jobs at a time
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// Hit / miss counters since start
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub len: usize,
+}
+
+/// Least-recently-used cache of job payloads (items already decoded from JSON).
+///
+/// The job processor asks for the same pending and retrying jobs every 2s; only their
+/// status columns are read from SQLite each time, the payload comes from here. Entries
+/// are dropped when a job reaches a final status.
+pub struct JobCache {
+    capacity: usize,
+    jobs: HashMap<String, PrintJob>,
+    /// Least recently used first
+    order: VecDeque<String>,
+    hits: u64,
+    misses: u64,
+}
+
+impl JobCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            jobs: HashMap::new(),
+            order: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn get(&mut self, job_id: &str) -> Option<PrintJob> {
+        match self.jobs.get(job_id) {
+            Some(job) => {
+                self.hits += 1;
+                let job = job.clone();
+                self.touch(job_id);
+                Some(job)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn put(&mut self, job: PrintJob) {
+        let id = job.id.clone();
+        if self.jobs.insert(id.clone(), job).is_some() {
+            self.touch(&id);
+            return;
+        }
+        self.order.push_back(id);
+        while self.order.len() > self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.jobs.remove(&evicted);
+            }
+        }
+    }
+
+    pub fn invalidate(&mut self, job_id: &str) {
+        if self.jobs.remove(job_id).is_some() {
+            self.order.retain(|id| id != job_id);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.jobs.clear();
+        self.order.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            len: self.jobs.len(),
+        }
+    }
+
+    fn touch(&mut self, job_id: &str) {
+        if let Some(pos) = self.order.iter().position(|id| id == job_id) {
+            if let Some(id) = self.order.remove(pos) {
+                self.order.push_back(id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str) -> PrintJob {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "restaurant_id": "r1",
+            "order_id": null,
+            "order_number": "42",
+            "station": "kitchen",
+            "station_id": null,
+            "printer_id": "p1",
+            "items": [],
+            "table_number": null,
+            "customer_name": null,
+            "order_type": null,
+            "priority": 3,
+            "timestamp": 0,
+            "status": "pending",
+            "retry_count": 0,
+            "error_message": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = JobCache::new(2);
+        cache.put(job("a"));
+        cache.put(job("b"));
+        assert!(cache.get("a").is_some()); // b is now least recently used
+        cache.put(job("c"));
+
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("c").is_some());
+
+        cache.invalidate("a");
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 2, len: 1 });
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+/// Cancellation for print jobs: shutdown stops everything for good, a pause (maintenance)
+/// stops the jobs running now and lets later ones through after resume, and `cancel_job`
+/// stops one running job that was cancelled.
+///
+/// Jobs check their token only where stopping is safe: waiting for a print slot, before
+/// each printer is tried and while waiting for a printer's I/O slot. Once bytes go to a
+/// printer the write finishes (its own timeouts bound it), so no ticket is cut in half.
+#[derive(Clone)]
+pub struct JobCancellation {
+    shutdown: CancellationToken,
+    /// Child of `shutdown`, replaced on resume (a cancelled token stays cancelled)
+    pause: Arc<Mutex<CancellationToken>>,
+    /// Jobs running now, by id
+    running: Arc<Mutex<HashMap<String, Running>>>,
+}
+
+#[derive(Clone)]
+struct Running {
+    token: CancellationToken,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// A job in the processor. Its token is cancelled by shutdown, the next pause and
+/// `JobCancellation::cancel_job`; dropping it takes the job off the running list.
+pub struct RunningJob {
+    pub token: CancellationToken,
+    job_id: String,
+    cancelled: Arc<AtomicBool>,
+    running: Arc<Mutex<HashMap<String, Running>>>,
+}
+
+impl RunningJob {
+    /// Whether the job itself was cancelled (not paused or shut down)
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for RunningJob {
+    fn drop(&mut self) {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        // Only this run's entry: the job may have been picked up again since
+        if running.get(&self.job_id).is_some_and(|r| Arc::ptr_eq(&r.cancelled, &self.cancelled)) {
+            running.remove(&self.job_id);
+        }
+    }
+}
+
+impl Default for JobCancellation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobCancellation {
+    pub fn new() -> Self {
+        let shutdown = CancellationToken::new();
+        let pause = Arc::new(Mutex::new(shutdown.child_token()));
+        Self { shutdown, pause, running: Arc::default() }
+    }
+
+    /// Start a job: its token is cancelled by shutdown, the next pause and `cancel_job`
+    pub fn start_job(&self, job_id: &str) -> RunningJob {
+        let token = self.pause.lock().unwrap_or_else(|e| e.into_inner()).child_token();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(job_id.to_string(), Running { token: token.clone(), cancelled: cancelled.clone() });
+        RunningJob { token, job_id: job_id.to_string(), cancelled, running: self.running.clone() }
+    }
+
+    /// Stop a running job at its next cancel point. False if it isn't running.
+    pub fn cancel_job(&self, job_id: &str) -> bool {
+        let running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(job) = running.get(job_id) else {
+            return false;
+        };
+        info!("Interrupting print job {}: cancelled", job_id);
+        job.cancelled.store(true, Ordering::SeqCst);
+        job.token.cancel();
+        true
+    }
+
+    /// Interrupt every job for good
+    pub fn shutdown(&self) {
+        if !self.shutdown.is_cancelled() {
+            info!("Interrupting print jobs: shutdown");
+        }
+        self.shutdown.cancel();
+    }
+
+    /// Interrupt the jobs running now (maintenance started)
+    pub fn pause(&self) {
+        let pause = self.pause.lock().unwrap_or_else(|e| e.into_inner());
+        if !pause.is_cancelled() {
+            info!("Interrupting print jobs: paused");
+            pause.cancel();
+        }
+    }
+
+    /// Let new jobs run again after a pause (no-op after shutdown)
+    pub fn resume(&self) {
+        let mut pause = self.pause.lock().unwrap_or_else(|e| e.into_inner());
+        if pause.is_cancelled() && !self.shutdown.is_cancelled() {
+            *pause = self.shutdown.child_token();
+        }
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+
+    /// Resolves once shutdown was requested
+    pub async fn shutdown_requested(&self) {
+        self.shutdown.cancelled().await
+    }
+
+    /// Why a job's token was cancelled (for its log line and the queue)
+    pub fn reason(&self) -> &'static str {
+        if self.is_shutdown() {
+            "shutdown"
+        } else {
+            "paused"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_resume_and_shutdown() {
+        let cancel = JobCancellation::new();
+        let running = cancel.start_job("j1");
+        assert!(!running.token.is_cancelled());
+
+        cancel.pause();
+        assert!(running.token.is_cancelled());
+        assert!(!running.is_cancelled());
+        assert_eq!(cancel.reason(), "paused");
+        // Started while paused: cancelled from the start
+        assert!(cancel.start_job("j2").token.is_cancelled());
+
+        cancel.resume();
+        let after_resume = cancel.start_job("j3");
+        assert!(!after_resume.token.is_cancelled());
+
+        cancel.shutdown();
+        assert!(after_resume.token.is_cancelled());
+        assert_eq!(cancel.reason(), "shutdown");
+        cancel.resume();
+        assert!(cancel.start_job("j4").token.is_cancelled());
+    }
+
+    #[test]
+    fn test_cancel_one_job() {
+        let cancel = JobCancellation::new();
+        let first = cancel.start_job("j1");
+        let second = cancel.start_job("j2");
+
+        assert!(cancel.cancel_job("j1"));
+        assert!(first.token.is_cancelled() && first.is_cancelled());
+        assert!(!second.token.is_cancelled());
+
+        drop(first);
+        assert!(!cancel.cancel_job("j1"));
+        assert!(!cancel.cancel_job("unknown"));
+    }
+}
@@ -0,0 +1,255 @@
+use crate::config::KEYRING_SERVICE;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::Engine;
+use hkdf::Hkdf;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tracing::info;
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+/// Keychain entry holding the daemon's job decryption key (hex X25519 secret)
+const KEYRING_KEY_USER: &str = "job-key";
+
+/// Envelope format version
+const VERSION: u8 = 1;
+
+/// Scheme name given to clients with the public key
+pub const ALGORITHM: &str = "x25519-hkdf-sha256-aes256gcm";
+
+/// HKDF info and AES-GCM associated data: a key or ciphertext from another use of the
+/// same X25519 key can't be replayed as a print job
+const CONTEXT: &[u8] = b"eatsome-print-job-v1";
+
+/// Encryption of print requests on the local API (`AppConfig.api_encryption`). Off by
+/// default: encrypted requests are always accepted once the daemon has a key, plain ones
+/// only while this isn't required.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiEncryptionConfig {
+    /// Refuse print requests that aren't encrypted (customer names, notes and addresses
+    /// never cross the LAN in the clear)
+    pub required: bool,
+}
+
+/// The daemon's public key, as handed to POS clients through pairing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobKeyInfo {
+    pub algorithm: &'static str,
+    /// First 8 bytes of the SHA-256 of the public key (hex); sent back in `kid`
+    pub key_id: String,
+    /// Base64 X25519 public key
+    pub public_key: String,
+}
+
+/// Encrypted print request body: `{"encrypted": {...}}`.
+///
+/// The client makes an ephemeral X25519 key pair, derives an AES-256 key with
+/// HKDF-SHA256 from the shared secret (salt: ephemeral public key followed by the
+/// daemon's public key, info: `eatsome-print-job-v1`) and encrypts the plain request
+/// JSON with AES-256-GCM (associated data: `eatsome-print-job-v1`). Binary fields are
+/// standard base64.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub v: u8,
+    pub kid: String,
+    /// Ephemeral public key
+    pub epk: String,
+    /// 12-byte AES-GCM nonce
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Why an encrypted request wasn't opened
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EnvelopeError {
+    #[error("print requests must be encrypted with the daemon's key")]
+    Required,
+
+    #[error("malformed encrypted request: {0}")]
+    Malformed(String),
+
+    #[error("request was encrypted for key {0}, not this daemon's key (pair the POS again)")]
+    UnknownKey(String),
+
+    #[error("request could not be decrypted")]
+    Decrypt,
+
+    #[error("job encryption key unavailable: {0}")]
+    KeyUnavailable(String),
+}
+
+/// The daemon's X25519 key pair
+pub struct JobKey {
+    secret: StaticSecret,
+    public: PublicKey,
+}
+
+impl JobKey {
+    fn from_secret(secret: StaticSecret) -> Self {
+        let public = PublicKey::from(&secret);
+        Self { secret, public }
+    }
+
+    pub fn info(&self) -> JobKeyInfo {
+        JobKeyInfo {
+            algorithm: ALGORITHM,
+            key_id: key_id(&self.public),
+            public_key: base64::engine::general_purpose::STANDARD.encode(self.public.as_bytes()),
+        }
+    }
+
+    /// Decrypt an envelope addressed to this key
+    pub fn open(&self, envelope: &Envelope) -> Result<Vec<u8>, EnvelopeError> {
+        if envelope.v != VERSION {
+            return Err(EnvelopeError::Malformed(format!("unknown version {}", envelope.v)));
+        }
+        if envelope.kid != key_id(&self.public) {
+            return Err(EnvelopeError::UnknownKey(envelope.kid.clone()));
+        }
+        let epk: [u8; 32] = decode(&envelope.epk, "epk")?
+            .try_into()
+            .map_err(|_| EnvelopeError::Malformed("epk must be 32 bytes".into()))?;
+        let nonce = decode(&envelope.nonce, "nonce")?;
+        if nonce.len() != 12 {
+            return Err(EnvelopeError::Malformed("nonce must be 12 bytes".into()));
+        }
+        let ciphertext = decode(&envelope.ciphertext, "ciphertext")?;
+
+        let epk = PublicKey::from(epk);
+        let shared = self.secret.diffie_hellman(&epk);
+        let cipher = cipher(shared.as_bytes(), &epk, &self.public);
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: CONTEXT })
+            .map_err(|_| EnvelopeError::Decrypt)
+    }
+}
+
+fn key_id(public: &PublicKey) -> String {
+    hex::encode(&Sha256::digest(public.as_bytes())[..8])
+}
+
+fn decode(value: &str, field: &str) -> Result<Vec<u8>, EnvelopeError> {
+    base64::engine::general_purpose::STANDARD
+        .decode(value)
+        .map_err(|_| EnvelopeError::Malformed(format!("{} is not base64", field)))
+}
+
+fn cipher(shared: &[u8; 32], epk: &PublicKey, recipient: &PublicKey) -> Aes256Gcm {
+    let mut salt = epk.as_bytes().to_vec();
+    salt.extend_from_slice(recipient.as_bytes());
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(&salt), shared)
+        .expand(CONTEXT, key.as_mut_slice())
+        .expect("32 bytes is a valid HKDF-SHA256 length");
+    Aes256Gcm::new_from_slice(key.as_slice()).expect("32-byte key")
+}
+
+/// Encrypt a request for the daemon's public key, as a POS client does (tests and
+/// support tooling)
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn seal(recipient: &JobKeyInfo, plaintext: &[u8]) -> Result<Envelope, EnvelopeError> {
+    let public: [u8; 32] = decode(&recipient.public_key, "public key")?
+        .try_into()
+        .map_err(|_| EnvelopeError::Malformed("public key must be 32 bytes".into()))?;
+    let recipient_key = PublicKey::from(public);
+    let ephemeral = StaticSecret::random_from_rng(OsRng);
+    let epk = PublicKey::from(&ephemeral);
+    let shared = ephemeral.diffie_hellman(&recipient_key);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(shared.as_bytes(), &epk, &recipient_key)
+        .encrypt(&nonce, Payload { msg: plaintext, aad: CONTEXT })
+        .map_err(|_| EnvelopeError::Decrypt)?;
+    let b64 = base64::engine::general_purpose::STANDARD;
+    Ok(Envelope {
+        v: VERSION,
+        kid: recipient.key_id.clone(),
+        epk: b64.encode(epk.as_bytes()),
+        nonce: b64.encode(nonce),
+        ciphertext: b64.encode(ciphertext),
+    })
+}
+
+static KEY: OnceCell<JobKey> = OnceCell::new();
+
+/// The daemon's key, loaded from the OS keychain (created on first use)
+pub fn key() -> Result<&'static JobKey, EnvelopeError> {
+    KEY.get_or_try_init(|| {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_KEY_USER)
+            .map_err(|e| EnvelopeError::KeyUnavailable(e.to_string()))?;
+        match entry.get_password() {
+            Ok(hex_key) => {
+                let bytes: [u8; 32] = hex::decode(hex_key.trim())
+                    .ok()
+                    .and_then(|b| b.try_into().ok())
+                    .ok_or_else(|| EnvelopeError::KeyUnavailable("stored key is damaged".into()))?;
+                Ok(JobKey::from_secret(StaticSecret::from(bytes)))
+            }
+            Err(keyring::Error::NoEntry) => {
+                let secret = StaticSecret::random_from_rng(OsRng);
+                entry
+                    .set_password(&hex::encode(secret.to_bytes()))
+                    .map_err(|e| EnvelopeError::KeyUnavailable(e.to_string()))?;
+                let key = JobKey::from_secret(secret);
+                info!("Job encryption key {} created in the OS keychain", key.info().key_id);
+                Ok(key)
+            }
+            Err(e) => Err(EnvelopeError::KeyUnavailable(e.to_string())),
+        }
+    })
+}
+
+/// Plain request JSON from a print request body: encrypted bodies are opened, plain
+/// ones pass unless encryption is required
+pub fn open_body(config: &ApiEncryptionConfig, body: &[u8]) -> Result<Option<Vec<u8>>, EnvelopeError> {
+    #[derive(Deserialize)]
+    struct Encrypted {
+        encrypted: Envelope,
+    }
+
+    let looks_encrypted = serde_json::from_slice::<serde_json::Value>(body)
+        .is_ok_and(|value| value.get("encrypted").is_some());
+    if !looks_encrypted {
+        return if config.required { Err(EnvelopeError::Required) } else { Ok(None) };
+    }
+    let Encrypted { encrypted } =
+        serde_json::from_slice(body).map_err(|e| EnvelopeError::Malformed(e.to_string()))?;
+    key()?.open(&encrypted).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = JobKey::from_secret(StaticSecret::random_from_rng(OsRng));
+        let info = key.info();
+        assert_eq!(info.key_id.len(), 16);
+
+        let request = br#"{"customer_name":"Jan de Vries"}"#;
+        let envelope = seal(&info, request).unwrap();
+        assert_eq!(key.open(&envelope).unwrap(), request.to_vec());
+        // The ciphertext doesn't carry the plain text
+        assert!(!envelope.ciphertext.contains("Jan"));
+
+        let mut tampered = envelope.clone();
+        let mut bytes = decode(&tampered.ciphertext, "ciphertext").unwrap();
+        bytes[0] ^= 1;
+        tampered.ciphertext = base64::engine::general_purpose::STANDARD.encode(bytes);
+        assert_eq!(key.open(&tampered), Err(EnvelopeError::Decrypt));
+
+        // Sealed for another daemon
+        let other = JobKey::from_secret(StaticSecret::random_from_rng(OsRng));
+        assert!(matches!(other.open(&envelope), Err(EnvelopeError::UnknownKey(_))));
+
+        // Plain bodies pass unless encryption is required
+        let plain = br#"{"order_number":"42"}"#;
+        assert_eq!(open_body(&ApiEncryptionConfig::default(), plain), Ok(None));
+        assert_eq!(open_body(&ApiEncryptionConfig { required: true }, plain), Err(EnvelopeError::Required));
+    }
+}
@@ -1,8 +1,16 @@
-use crate::errors::{DaemonError, Result};
-use crate::escpos::PrintItem;
-use crate::queue::{PrintJob, QueueManager};
+use crate::failover;
+use crate::log_level;
+use crate::low_resource;
+use crate::maintenance;
+use crate::payload_version;
+use crate::queue::QueueManager;
+use crate::resources;
+use crate::restarts;
 use crate::status;
 use crate::supabase_client::SupabaseClient;
+use crate::task_metrics;
+use crate::telemetry;
+use crate::transform::TransformRules;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -30,12 +38,14 @@ impl JobPoller {
     /// `printer_ids`: IDs of configured printers, sent with each poll
     /// for heartbeat piggyback (last_seen + status='online').
     /// `failover_map`: shared cache updated with failover config from edge function.
+    /// `rules`: payload normalization applied to every polled row before enqueue.
     pub fn start(
         restaurant_id: String,
         client: Arc<SupabaseClient>,
-        queue_manager: Arc<Mutex<QueueManager>>,
+        queue_manager: QueueManager,
         printer_ids: Vec<String>,
         failover_map: Arc<Mutex<HashMap<String, Vec<String>>>>,
+        rules: TransformRules,
     ) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
             let mut backoff_index: usize = 0;
@@ -48,24 +58,67 @@ impl JobPoller {
             );
 
             loop {
-                let delay = BACKOFF_STEPS[backoff_index];
+                // Doubled in low-resource mode
+                let delay = low_resource::scale_secs(BACKOFF_STEPS[backoff_index]);
                 tokio::time::sleep(tokio::time::Duration::from_secs(delay)).await;
+                let _tick = task_metrics::tick("job_poller");
+
+                // Maintenance: jobs stay pending in Supabase until it ends
+                if maintenance::is_active() {
+                    continue;
+                }
 
                 // Include failover config request every 5 minutes
                 let include_failover =
                     last_failover_refresh.elapsed().as_secs() >= FAILOVER_REFRESH_INTERVAL;
 
+                // Report backlog so the backend can warn / hold fan-out while we catch up
+                let queue_health = match queue_manager.get_health().await {
+                    Ok(health) => Some(health),
+                    Err(e) => {
+                        warn!("Failed to read queue health for heartbeat: {}", e);
+                        None
+                    }
+                };
+
+                // Queue depth is needed for back-pressure; the rest follows the telemetry mode
+                let sharing = telemetry::mode();
+                let resources = resources::latest().filter(|_| sharing.shares_usage());
+                let restarts = restarts::summary().filter(|_| sharing.shares_errors());
+                let payload_counts = payload_version::counts().filter(|_| sharing.shares_usage());
+
                 match client
-                    .poll_pending_jobs_with_failover(&printer_ids, include_failover)
+                    .poll_pending_jobs_with_failover(
+                        &printer_ids,
+                        include_failover,
+                        queue_health.as_ref(),
+                        resources.as_ref(),
+                        restarts.as_ref(),
+                        payload_counts.as_ref(),
+                    )
                     .await
                 {
                     Ok(poll_result) => {
+                        if let Some(request) = &poll_result.log_level {
+                            log_level::apply(request, &queue_manager).await;
+                        }
+
                         // Update failover config if received
                         if let Some(config) = poll_result.failover_config {
                             let mut map = failover_map.lock().await;
+                            let changed = *map != config;
                             *map = config;
                             last_failover_refresh = std::time::Instant::now();
                             info!("Failover config refreshed ({} primary printers mapped)", map.len());
+                            // Warned once per change, not on every refresh
+                            let loops = if changed { failover::find_loops(&map) } else { Vec::new() };
+                            for cycle in loops {
+                                warn!(
+                                    "Failover loop: {} → {} (failed-over printers are skipped as backups)",
+                                    cycle.join(" → "),
+                                    cycle[0]
+                                );
+                            }
                         }
 
                         if !poll_result.jobs.is_empty() {
@@ -76,17 +129,31 @@ impl JobPoller {
                             );
                             backoff_index = 0;
 
-                            let queue = queue_manager.lock().await;
+                            let queue = &queue_manager;
+                            let mut already_printed = Vec::new();
                             for job_json in &poll_result.jobs {
-                                match Self::parse_job(job_json, &restaurant_id) {
+                                let job_json = payload_version::adapt(job_json, &rules);
+                                match rules.to_print_job(&job_json, &restaurant_id) {
+                                    // Printed before, but the completion never reached the backend
+                                    // (e.g. restart right after printing): ack instead of reprinting
+                                    Ok(job) if queue.was_completed(&job.id).await.unwrap_or(false) => {
+                                        already_printed.push(job.id);
+                                    }
                                     Ok(job) => {
-                                        if let Err(e) = queue.enqueue(job).await {
+                                        if let Err(e) = queue.ingest(job, "poll").await {
                                             debug!("Enqueue skipped (likely dedup): {}", e);
                                         }
                                     }
                                     Err(e) => warn!("Failed to parse polled job: {}", e),
                                 }
                             }
+
+                            for job_id in already_printed {
+                                info!("Job {} was already printed, acknowledging without reprint", job_id);
+                                if let Err(e) = client.update_job_status(&job_id, status::COMPLETED, None, None, None).await {
+                                    warn!("Failed to acknowledge already printed job {}: {}", job_id, e);
+                                }
+                            }
                         } else {
                             // No pending jobs — back off
                             if backoff_index < BACKOFF_STEPS.len() - 1 {
@@ -109,61 +176,4 @@ impl JobPoller {
             }
         })
     }
-
-    /// Parse a Supabase row JSON into a PrintJob
-    fn parse_job(record: &serde_json::Value, restaurant_id: &str) -> Result<PrintJob> {
-        let id = record
-            .get("id")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| DaemonError::Queue("Missing job id".to_string()))?
-            .to_string();
-
-        let order_id = record
-            .get("order_id")
-            .and_then(|v| v.as_str())
-            .map(String::from);
-
-        let order_number = record
-            .get("order_number")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| DaemonError::Queue("Missing order_number".to_string()))?
-            .to_string();
-
-        let station = record
-            .get("station")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| DaemonError::Queue("Missing station".to_string()))?
-            .to_string();
-
-        let items_json = record
-            .get("items")
-            .ok_or_else(|| DaemonError::Queue("Missing items".to_string()))?;
-
-        let items: Vec<PrintItem> = serde_json::from_value(items_json.clone())
-            .map_err(|e| DaemonError::Queue(format!("Failed to parse items: {}", e)))?;
-
-        let timestamp = record
-            .get("timestamp")
-            .and_then(|v| v.as_i64())
-            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
-
-        Ok(PrintJob {
-            id,
-            restaurant_id: restaurant_id.to_string(),
-            order_id,
-            order_number,
-            station,
-            station_id: record.get("station_id").and_then(|v| v.as_str()).map(String::from),
-            printer_id: record.get("printer_id").and_then(|v| v.as_str()).map(String::from),
-            items,
-            table_number: record.get("table_number").and_then(|v| v.as_str()).map(String::from),
-            customer_name: record.get("customer_name").and_then(|v| v.as_str()).map(String::from),
-            order_type: record.get("order_type").and_then(|v| v.as_str()).map(String::from),
-            priority: record.get("priority").and_then(|v| v.as_u64()).unwrap_or(3) as u8,
-            timestamp,
-            status: status::PENDING.to_string(),
-            retry_count: 0,
-            error_message: None,
-        })
-    }
 }
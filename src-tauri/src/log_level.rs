@@ -0,0 +1,225 @@
+use crate::queue::QueueManager;
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tracing::{info, warn};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Target of the daemon's own log lines
+const CRATE: &str = "eatsome_printer_daemon";
+
+/// Elevation length when the request names none
+pub const DEFAULT_MINUTES: u32 = 30;
+
+/// Longest elevation a request gets, whatever it asks for
+pub const MAX_MINUTES: u32 = 120;
+
+/// Swaps the log filter at runtime (set once the subscriber is built)
+static HANDLE: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+/// Process-wide, like the telemetry mode: the job poller applies requests, the poll
+/// heartbeat reports the elevation
+static STATE: Mutex<State> = Mutex::new(State { elevation: None, last_request_id: None, generation: 0 });
+
+struct State {
+    elevation: Option<Elevation>,
+    last_request_id: Option<String>,
+    /// Bumped on every change, so a stale revert timer leaves a newer elevation alone
+    generation: u64,
+}
+
+/// Elevated logging asked for by the backend (`log_level` in the poll response), when
+/// support needs debug logs from a venue for a while
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LogLevelRequest {
+    /// `debug` or `trace`
+    pub level: String,
+    /// How long; `DEFAULT_MINUTES` when absent, at most `MAX_MINUTES`
+    #[serde(default)]
+    pub minutes: Option<u32>,
+    /// Support's reference. The backend may repeat a request on every poll: one with the
+    /// id of the last applied request is ignored, even after it ran out.
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+/// An active elevation, sent with every poll heartbeat
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Elevation {
+    pub level: String,
+    pub until: DateTime<Utc>,
+    pub request_id: Option<String>,
+}
+
+/// Filter outside an elevation, as the daemon has always logged: debug, with the
+/// daemon's own lines at debug whatever `RUST_LOG` adds
+pub fn baseline() -> EnvFilter {
+    let filter = EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into());
+    match format!("{}=debug", CRATE).parse() {
+        Ok(directive) => filter.add_directive(directive),
+        Err(_) => filter,
+    }
+}
+
+/// The baseline with the daemon's own lines raised to `level` (dependencies stay as they are)
+fn elevated(level: &str) -> EnvFilter {
+    match format!("{}={}", CRATE, level).parse() {
+        Ok(directive) => baseline().add_directive(directive),
+        Err(_) => baseline(),
+    }
+}
+
+/// Keep the reload handle of the subscriber's filter
+pub fn install(handle: reload::Handle<EnvFilter, Registry>) {
+    let _ = HANDLE.set(handle);
+}
+
+/// The active elevation, if any
+pub fn current() -> Option<Elevation> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner()).elevation.clone()
+}
+
+/// Check a request: a known level and a bounded duration
+fn validate(request: &LogLevelRequest) -> Result<(String, Duration), String> {
+    let level = request.level.trim().to_lowercase();
+    if !matches!(level.as_str(), "debug" | "trace") {
+        return Err(format!("unsupported log level '{}'", request.level));
+    }
+    let minutes = request.minutes.unwrap_or(DEFAULT_MINUTES).clamp(1, MAX_MINUTES);
+    Ok((level, Duration::minutes(minutes as i64)))
+}
+
+/// Start an elevation unless the request was applied already, or one is running without
+/// the request naming itself. Returns the elevation and its generation for the revert timer.
+fn begin(state: &mut State, request: &LogLevelRequest, now: DateTime<Utc>) -> Result<Option<(Elevation, u64)>, String> {
+    let (level, duration) = validate(request)?;
+    let repeated = match &request.id {
+        Some(id) => state.last_request_id.as_ref() == Some(id),
+        None => state.elevation.is_some(),
+    };
+    if repeated {
+        return Ok(None);
+    }
+    let elevation = Elevation { level, until: now + duration, request_id: request.id.clone() };
+    state.elevation = Some(elevation.clone());
+    state.last_request_id = request.id.clone();
+    state.generation += 1;
+    Ok(Some((elevation, state.generation)))
+}
+
+/// End the elevation of `generation`; false when a newer one replaced it
+fn end(state: &mut State, generation: u64) -> bool {
+    if state.generation != generation || state.elevation.is_none() {
+        return false;
+    }
+    state.elevation = None;
+    state.generation += 1;
+    true
+}
+
+fn set_filter(handle: Option<&reload::Handle<EnvFilter, Registry>>, filter: EnvFilter) {
+    if let Some(handle) = handle {
+        if let Err(e) = handle.reload(filter) {
+            warn!("Failed to change log level: {}", e);
+        }
+    }
+}
+
+/// End the elevation of `generation` and put the baseline filter back; false when a
+/// newer elevation replaced it
+fn revert(state: &mut State, generation: u64, handle: Option<&reload::Handle<EnvFilter, Registry>>) -> bool {
+    if !end(state, generation) {
+        return false;
+    }
+    set_filter(handle, baseline());
+    true
+}
+
+/// Apply a backend request: raise the level now and put it back when the time is up.
+/// Both changes are marked in the log file and the audit log, so support can find the
+/// window.
+pub async fn apply(request: &LogLevelRequest, queue: &QueueManager) {
+    let now = Utc::now();
+    let started = begin(&mut STATE.lock().unwrap_or_else(|e| e.into_inner()), request, now);
+    let (elevation, generation) = match started {
+        Ok(Some(started)) => started,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Ignoring log level request: {}", e);
+            return;
+        }
+    };
+
+    set_filter(HANDLE.get(), elevated(&elevation.level));
+    info!("==== Log level raised to {} until {} (requested remotely) ====", elevation.level, elevation.until.to_rfc3339());
+    if let Err(e) = queue.record_audit("log_level_elevated", "app.log", serde_json::json!(elevation)).await {
+        warn!("Failed to record log level change: {}", e);
+    }
+
+    let queue = queue.clone();
+    let wait = (elevation.until - now).to_std().unwrap_or_default();
+    tokio::spawn(async move {
+        tokio::time::sleep(wait).await;
+        if !revert(&mut STATE.lock().unwrap_or_else(|e| e.into_inner()), generation, HANDLE.get()) {
+            return;
+        }
+        info!("==== Log level back to normal after {} logging ====", elevation.level);
+        if let Err(e) = queue.record_audit("log_level_reverted", "app.log", serde_json::json!(elevation)).await {
+            warn!("Failed to record log level change: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(level: &str, minutes: Option<u32>, id: Option<&str>) -> LogLevelRequest {
+        LogLevelRequest { level: level.to_string(), minutes, id: id.map(str::to_string) }
+    }
+
+    #[test]
+    fn test_elevation_is_bounded_and_applied_once() {
+        let mut state = State { elevation: None, last_request_id: None, generation: 0 };
+        let now = Utc::now();
+
+        assert!(begin(&mut state, &request("verbose", None, None), now).is_err());
+        let (elevation, first) = begin(&mut state, &request(" DEBUG", None, Some("t-1")), now).unwrap().unwrap();
+        assert_eq!((elevation.level.as_str(), elevation.until), ("debug", now + Duration::minutes(30)));
+
+        // Resent with every poll: applied once, also after it ran out
+        assert_eq!(begin(&mut state, &request("debug", None, Some("t-1")), now).unwrap(), None);
+        assert!(end(&mut state, first));
+        assert!(!end(&mut state, first));
+        assert_eq!(begin(&mut state, &request("debug", None, Some("t-1")), now).unwrap(), None);
+
+        // A new request replaces a running one; the old timer leaves it alone
+        let (_, second) = begin(&mut state, &request("debug", Some(10), Some("t-2")), now).unwrap().unwrap();
+        let (elevation, third) = begin(&mut state, &request("trace", Some(600), Some("t-3")), now).unwrap().unwrap();
+        assert_eq!(elevation.until, now + Duration::minutes(MAX_MINUTES as i64));
+        assert!(!end(&mut state, second));
+        assert!(end(&mut state, third));
+        assert_eq!(state.elevation, None);
+    }
+
+    #[test]
+    fn test_revert_restores_the_baseline_filter() {
+        let (_layer, handle) = reload::Layer::<EnvFilter, Registry>::new(baseline());
+        let current = || handle.with_current(|filter| filter.to_string()).unwrap();
+        let configured = current();
+        let mut state = State { elevation: None, last_request_id: None, generation: 0 };
+
+        let (elevation, generation) = begin(&mut state, &request("trace", Some(5), Some("t-1")), Utc::now()).unwrap().unwrap();
+        set_filter(Some(&handle), elevated(&elevation.level));
+        assert!(current().contains(&format!("{}=trace", CRATE)));
+
+        assert!(revert(&mut state, generation, Some(&handle)));
+        assert_eq!(current(), configured);
+        assert!(current().contains(&format!("{}=debug", CRATE)));
+        // A stale timer changes nothing
+        set_filter(Some(&handle), elevated("trace"));
+        assert!(!revert(&mut state, generation, Some(&handle)));
+        assert!(current().contains(&format!("{}=trace", CRATE)));
+    }
+}
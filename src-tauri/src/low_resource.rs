@@ -0,0 +1,202 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Process-wide flag, read by hot loops (job processor, poller, status poller) and discovery
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Jobs printed in parallel normally / in low-resource mode
+pub const NORMAL_CONCURRENCY: usize = 5;
+pub const LOW_CONCURRENCY: usize = 2;
+
+/// Machine-wide CPU use (%) that counts as high
+pub const HIGH_CPU_PERCENT: f32 = 85.0;
+/// CPU is sampled this often by the load monitor
+pub const CPU_SAMPLE_SECS: u64 = 15;
+/// Samples that must all average above `HIGH_CPU_PERCENT` before the mode is suggested (5 min)
+pub const SUSTAINED_SAMPLES: usize = 20;
+
+/// Whether low-resource mode is on (`AppConfig.low_resource_mode`)
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Jobs the processor may print at the same time
+pub fn concurrency() -> usize {
+    if is_enabled() {
+        LOW_CONCURRENCY
+    } else {
+        NORMAL_CONCURRENCY
+    }
+}
+
+/// Poll delays are doubled in low-resource mode
+pub fn scale_secs(secs: u64) -> u64 {
+    if is_enabled() {
+        secs * 2
+    } else {
+        secs
+    }
+}
+
+/// Wait for the next run of a periodic task. In low-resource mode every other tick is
+/// skipped, so the task runs half as often.
+pub async fn tick(interval: &mut tokio::time::Interval) {
+    interval.tick().await;
+    if is_enabled() {
+        interval.tick().await;
+    }
+}
+
+/// Payload of the `low-resource-suggested` event
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadSuggestion {
+    /// Average machine CPU use (%) over the sustained window
+    pub cpu_percent: f32,
+    pub window_secs: u64,
+}
+
+/// Cumulative CPU time of the whole machine (all cores), in platform ticks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuTimes {
+    pub busy: u64,
+    pub total: u64,
+}
+
+/// CPU use (%) between two readings, None if no time passed
+pub fn cpu_percent(prev: CpuTimes, now: CpuTimes) -> Option<f32> {
+    let total = now.total.checked_sub(prev.total).filter(|t| *t > 0)?;
+    let busy = now.busy.saturating_sub(prev.busy).min(total);
+    Some(busy as f32 * 100.0 / total as f32)
+}
+
+/// First line of /proc/stat: `cpu user nice system idle iowait irq softirq steal ...`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn parse_proc_stat(stat: &str) -> Option<CpuTimes> {
+    let fields: Vec<u64> = stat
+        .lines()
+        .next()?
+        .strip_prefix("cpu ")?
+        .split_whitespace()
+        .take(8)
+        .map(|f| f.parse().ok())
+        .collect::<Option<_>>()?;
+    if fields.len() < 5 {
+        return None;
+    }
+    let total: u64 = fields.iter().sum();
+    let idle = fields[3] + fields[4];
+    Some(CpuTimes { busy: total - idle, total })
+}
+
+/// Current machine CPU times (None where unsupported, e.g. macOS)
+#[cfg(target_os = "linux")]
+pub fn read_cpu_times() -> Option<CpuTimes> {
+    parse_proc_stat(&std::fs::read_to_string("/proc/stat").ok()?)
+}
+
+#[cfg(windows)]
+pub fn read_cpu_times() -> Option<CpuTimes> {
+    #[repr(C)]
+    #[derive(Default)]
+    struct FileTime {
+        low: u32,
+        high: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetSystemTimes(idle: *mut FileTime, kernel: *mut FileTime, user: *mut FileTime) -> i32;
+    }
+
+    let (mut idle, mut kernel, mut user) = (FileTime::default(), FileTime::default(), FileTime::default());
+    // SAFETY: GetSystemTimes only writes the three FILETIME structs we pass
+    if unsafe { GetSystemTimes(&mut idle, &mut kernel, &mut user) } == 0 {
+        return None;
+    }
+    let ticks = |t: &FileTime| ((t.high as u64) << 32) | t.low as u64;
+    // Kernel time includes idle time
+    let total = ticks(&kernel) + ticks(&user);
+    Some(CpuTimes { busy: total.saturating_sub(ticks(&idle)), total })
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn read_cpu_times() -> Option<CpuTimes> {
+    None
+}
+
+/// Rolling window of CPU samples; reports high load once per sustained episode
+pub struct SustainedLoad {
+    samples: VecDeque<f32>,
+    window: usize,
+    /// Already reported for the current episode (reset once the average drops)
+    reported: bool,
+}
+
+impl SustainedLoad {
+    pub fn new(window: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(window),
+            window: window.max(1),
+            reported: false,
+        }
+    }
+
+    /// Add a sample. Returns the window average the first time a full window averages
+    /// at or above `threshold`.
+    pub fn push(&mut self, percent: f32, threshold: f32) -> Option<f32> {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(percent);
+        if self.samples.len() < self.window {
+            return None;
+        }
+
+        let average = self.samples.iter().sum::<f32>() / self.samples.len() as f32;
+        if average < threshold {
+            self.reported = false;
+            return None;
+        }
+        if self.reported {
+            return None;
+        }
+        self.reported = true;
+        Some(average)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proc_stat_and_percent() {
+        let before = parse_proc_stat("cpu  100 0 100 700 100 0 0 0 0 0\ncpu0 1 2 3 4 5\n").unwrap();
+        assert_eq!(before, CpuTimes { busy: 200, total: 1000 });
+        let after = parse_proc_stat("cpu  250 0 150 750 150 0 0 0 0 0\n").unwrap();
+        assert_eq!(cpu_percent(before, after), Some(200.0 * 100.0 / 300.0));
+        assert_eq!(cpu_percent(after, after), None);
+        assert!(parse_proc_stat("intr 1 2 3").is_none());
+    }
+
+    #[test]
+    fn test_sustained_load_reports_once_per_episode() {
+        let mut load = SustainedLoad::new(3);
+        assert_eq!(load.push(95.0, 85.0), None);
+        assert_eq!(load.push(95.0, 85.0), None);
+        assert_eq!(load.push(95.0, 85.0), Some(95.0));
+        assert_eq!(load.push(95.0, 85.0), None);
+
+        // Load drops, then climbs again: a new episode
+        load.push(10.0, 85.0);
+        load.push(10.0, 85.0);
+        load.push(99.0, 85.0);
+        load.push(99.0, 85.0);
+        assert!(load.push(99.0, 85.0).is_some());
+    }
+}
@@ -9,10 +9,13 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 use tokio::sync::Mutex;
-use tracing::{info, error, warn, debug};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, error, warn, debug, Instrument};
+use sentry::SentryFutureExt;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod config;
+mod commands;
 #[allow(dead_code)] // ESC/POS protocol library: not all builder methods/enums used yet
 mod escpos;
 mod printer;
@@ -30,16 +33,84 @@ mod status;
 mod updater;
 mod sentry_init;
 mod supabase_client;
+mod anomaly;
+mod support;
+mod troubleshoot;
+mod usb_setup;
+mod i18n;
+mod webhooks;
+mod transform;
+mod readdress;
+mod fault_injection;
+mod clock;
+mod printer_admin;
+mod status_page;
+mod business_date;
+mod raster;
+mod rediscovery;
+mod quarantine;
+mod notifications;
+mod maintenance;
+mod paper_detect;
+mod recovery;
+mod job_cache;
+mod low_resource;
+mod resources;
+mod stations;
+mod self_test;
+mod item_images;
+mod jam_recovery;
+mod incidents;
+mod ingest_dedup;
+mod routing;
+mod routing_sim;
+mod restarts;
+mod payload_version;
+mod transport;
+mod request_signing;
+mod task_metrics;
+mod job_cancel;
+mod receipt_fields;
+mod receipt_templates;
+mod pickup_slip;
+mod address_conflicts;
+mod config_seal;
+mod ble;
+mod printer_names;
+mod order_links;
+mod printer_stats;
+mod failover;
+mod write_timeout;
+mod printer_import;
+mod job_encryption;
+mod edge_metrics;
+mod queue_migrations;
+mod simulate;
+mod serial;
+mod venue_time;
+mod units;
+mod log_level;
+#[cfg(test)]
+mod fake_supabase;
 
 use config::AppConfig;
 use printer::PrinterManager;
 use queue::QueueManager;
+use stations::StationMap;
 use job_poller::JobPoller;
 use auth::JWTManager;
 use telemetry::{TelemetryCollector, TelemetryReporter};
 use errors::DaemonError;
 use supabase_client::SupabaseClient;
-use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+use circuit_breaker::{BreakerTransition, CircuitBreaker, CircuitBreakerConfig, CircuitState};
+use anomaly::{AnomalyDetector, DegradationChange};
+use readdress::{AddressMonitor, AddressRequest};
+use quarantine::{ProbeOutcome, Quarantine, QuarantineReason};
+use notifications::{AlertAction, AlertKind, Notifier};
+use maintenance::{Maintenance, MaintenanceWindow};
+use job_cancel::JobCancellation;
+use recovery::RecoveryPrints;
+use i18n::{LocalizedMessage, Locale, MessageKey};
 
 /// Per-printer circuit breaker registry
 pub struct CircuitBreakerRegistry {
@@ -47,40 +118,160 @@ pub struct CircuitBreakerRegistry {
     config: CircuitBreakerConfig,
     /// Watch channel for status propagation (printer_id, status)
     status_tx: tokio::sync::watch::Sender<(String, String)>,
+    /// Early-warning detector: flags printers drifting from their baseline before the breaker opens
+    anomaly: AnomalyDetector,
+    /// Spots network printers that stopped answering at their address (IP changed)
+    readdress: AddressMonitor,
+    /// Printers sidelined after repeated breaker trips, re-probed until they recover
+    quarantine: Quarantine,
+    /// Keeps failed-over printers' jobs on their backup until they are healthy again
+    failover: failover::Failover,
+    /// Breaker state transitions, persisted as breaker history
+    history_tx: Option<tokio::sync::mpsc::UnboundedSender<BreakerTransition>>,
+    /// Print attempts, counted in the printers' lifetime stats
+    stats_tx: Option<tokio::sync::mpsc::UnboundedSender<printer_stats::Attempt>>,
 }
 
 impl CircuitBreakerRegistry {
-    fn new() -> (Self, tokio::sync::watch::Receiver<(String, String)>) {
+    fn new(readdress: AddressMonitor) -> (Self, tokio::sync::watch::Receiver<(String, String)>) {
         let (tx, rx) = tokio::sync::watch::channel(("".to_string(), "online".to_string()));
         let registry = Self {
             breakers: Mutex::new(std::collections::HashMap::new()),
             config: CircuitBreakerConfig::default(),
             status_tx: tx,
+            anomaly: AnomalyDetector::default(),
+            readdress,
+            quarantine: Quarantine::default(),
+            failover: failover::Failover::default(),
+            history_tx: None,
+            stats_tx: None,
         };
         (registry, rx)
     }
 
+    /// Report every breaker transition to `history_tx` (breakers created from now on)
+    fn with_history(mut self, history_tx: tokio::sync::mpsc::UnboundedSender<BreakerTransition>) -> Self {
+        self.history_tx = Some(history_tx);
+        self
+    }
+
+    /// Report every print attempt to `stats_tx`
+    fn with_stats(mut self, stats_tx: tokio::sync::mpsc::UnboundedSender<printer_stats::Attempt>) -> Self {
+        self.stats_tx = Some(stats_tx);
+        self
+    }
+
     /// Get or create a circuit breaker for a printer
     async fn get_breaker(&self, printer_id: &str) -> Arc<CircuitBreaker> {
         let mut breakers = self.breakers.lock().await;
         breakers
             .entry(printer_id.to_string())
             .or_insert_with(|| {
-                Arc::new(CircuitBreaker::new_with_status_tx(
+                let breaker = CircuitBreaker::new_with_status_tx(
                     printer_id.to_string(),
                     self.config.clone(),
                     self.status_tx.clone(),
-                ))
+                );
+                Arc::new(match self.history_tx {
+                    Some(ref tx) => breaker.with_history(tx.clone()),
+                    None => breaker,
+                })
             })
             .clone()
     }
+
+    /// Feed a print outcome into the anomaly detector.
+    /// On a degraded/recovered transition, propagates the status through the same
+    /// channel as breaker state changes (→ Supabase) and records a telemetry event.
+    async fn record_outcome(
+        &self,
+        printer_id: &str,
+        success: bool,
+        duration_ms: u64,
+        telemetry: &TelemetryCollector,
+    ) {
+        let change = match self.anomaly.record(printer_id, success, duration_ms).await {
+            Some(change) => change,
+            None => return,
+        };
+
+        let event = match change {
+            DegradationChange::Degrading {
+                printer_id,
+                reason,
+                failure_rate,
+                baseline_failure_rate,
+                avg_latency_ms,
+                baseline_latency_ms,
+            } => {
+                let _ = self.status_tx.send((printer_id.clone(), "degraded".to_string()));
+                telemetry::TelemetryEvent::PrinterDegrading {
+                    printer_id,
+                    reason,
+                    failure_rate,
+                    baseline_failure_rate,
+                    avg_latency_ms,
+                    baseline_latency_ms,
+                }
+            }
+            DegradationChange::Recovered { printer_id } => {
+                let _ = self.status_tx.send((printer_id.clone(), "online".to_string()));
+                telemetry::TelemetryEvent::PrinterDegradationCleared { printer_id }
+            }
+        };
+
+        telemetry.record_event(event).await;
+    }
+
+    /// Count a breaker opening; quarantines the printer once it keeps tripping
+    async fn record_trip(&self, printer_id: &str, telemetry: &TelemetryCollector) {
+        if self.quarantine.record_trip(printer_id).await {
+            self.announce_quarantined(printer_id, QuarantineReason::RepeatedTrips, telemetry).await;
+        }
+    }
+
+    /// Sideline a printer on operator request. Returns false if it already was.
+    async fn quarantine(&self, printer_id: &str, telemetry: &TelemetryCollector) -> bool {
+        if !self.quarantine.quarantine(printer_id, QuarantineReason::Manual).await {
+            return false;
+        }
+        self.announce_quarantined(printer_id, QuarantineReason::Manual, telemetry).await;
+        true
+    }
+
+    /// Propagate a quarantine like any other status change (dashboard, Supabase) + telemetry
+    async fn announce_quarantined(&self, printer_id: &str, reason: QuarantineReason, telemetry: &TelemetryCollector) {
+        let _ = self.status_tx.send((printer_id.to_string(), "quarantined".to_string()));
+        telemetry
+            .record_event(telemetry::TelemetryEvent::PrinterQuarantineChanged {
+                printer_id: printer_id.to_string(),
+                quarantined: true,
+                reason: reason.as_str().to_string(),
+            })
+            .await;
+    }
+
+    /// Return a printer to rotation with a clean breaker and baseline
+    async fn release(&self, printer_id: &str, reason: &str, telemetry: &TelemetryCollector) {
+        self.quarantine.release(printer_id).await;
+        self.get_breaker(printer_id).await.reset(reason).await;
+        self.anomaly.reset(printer_id).await;
+        let _ = self.status_tx.send((printer_id.to_string(), "online".to_string()));
+        telemetry
+            .record_event(telemetry::TelemetryEvent::PrinterQuarantineChanged {
+                printer_id: printer_id.to_string(),
+                quarantined: false,
+                reason: reason.to_string(),
+            })
+            .await;
+    }
 }
 
 /// Global application state
 pub struct AppState {
     config: Arc<Mutex<AppConfig>>,
-    printer_manager: Arc<Mutex<PrinterManager>>,
-    queue_manager: Arc<Mutex<QueueManager>>,
+    printer_manager: PrinterManager,
+    queue_manager: QueueManager,
     job_poller_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     telemetry: Arc<TelemetryCollector>,
     #[allow(dead_code)] // Used by API server (api.rs), not directly from main
@@ -89,11 +280,23 @@ pub struct AppState {
     start_time: Instant,
     /// Shutdown flag: when true, background tasks should drain and stop
     shutdown_requested: Arc<AtomicBool>,
+    /// Interrupts print jobs at safe points on shutdown and maintenance
+    job_cancel: JobCancellation,
     /// Cached failover map: primary_printer_id → [backup_printer_ids]
     /// Refreshed every 5 minutes from Supabase via poll-jobs response.
     failover_map: Arc<Mutex<std::collections::HashMap<String, Vec<String>>>>,
     /// App handle set during Tauri .setup() — shared with background tasks for event emission
     app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+    /// Local HTTP API, running while the daemon is paired
+    api_server: Arc<api::ApiServer>,
+    /// Desktop notifications + in-app alert list
+    notifier: Arc<Notifier>,
+    /// Planned maintenance window (printing paused)
+    maintenance: Arc<Maintenance>,
+    /// Reprints after a paper-out, and the ones waiting for confirmation
+    recovery: Arc<RecoveryPrints>,
+    /// Outcome of the config.json integrity check at startup
+    config_integrity: Arc<std::sync::Mutex<config_seal::ConfigIntegrity>>,
 }
 
 // ============================================================================
@@ -113,15 +316,28 @@ async fn get_config(state: State<'_, AppState>) -> Result<AppConfig, String> {
     Ok(config)
 }
 
+/// Download the venue logo from Supabase storage in the background (config save / load).
+/// Receipts print without it until it is on disk; a local `logo_path` needs no download.
+fn spawn_logo_download(template: &escpos::TestPrintTemplate) {
+    let Some(url) = template.logo_url.clone().filter(|_| template.logo_path.is_none()) else {
+        return;
+    };
+    tokio::spawn(async move {
+        if let Err(e) = raster::fetch_logo(&url).await {
+            warn!("{}", e);
+        }
+    });
+}
+
 /// Validate restaurant identifier (UUID or restaurant_code)
 ///
 /// Accepts either:
 /// - UUID format (e.g., "0faee837-c64f-4ac9-ae2c-62f4f07e0054")
 /// - Short restaurant code (e.g., "W434N") — will be resolved to UUID via Supabase
-fn validate_restaurant_id(id: &str) -> Result<(), String> {
+fn validate_restaurant_id(id: &str, locale: Locale) -> Result<(), LocalizedMessage> {
     let trimmed = id.trim();
     if trimmed.is_empty() {
-        return Err("Restaurant ID cannot be empty".to_string());
+        return Err(LocalizedMessage::new(locale, MessageKey::RestaurantIdEmpty, &[]));
     }
     // Accept both UUID and short restaurant codes (alphanumeric, 3-10 chars)
     if uuid::Uuid::parse_str(trimmed).is_ok() {
@@ -130,9 +346,10 @@ fn validate_restaurant_id(id: &str) -> Result<(), String> {
     if trimmed.len() >= 3 && trimmed.len() <= 10 && trimmed.chars().all(|c| c.is_alphanumeric()) {
         return Ok(());
     }
-    Err(format!(
-        "Invalid restaurant identifier: '{}'. Use your restaurant code (e.g., W434N) or UUID.",
-        trimmed
+    Err(LocalizedMessage::new(
+        locale,
+        MessageKey::InvalidRestaurantId,
+        &[("id", trimmed.to_string())],
     ))
 }
 
@@ -142,12 +359,55 @@ async fn save_config(
     config: AppConfig,
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), LocalizedMessage> {
     let mut config = config;
+    let locale = config.locale;
+
+    // Header / footer may only use variables the daemon can fill in
+    config.receipt_fields.validate().map_err(|(block, e)| {
+        LocalizedMessage::new(
+            locale,
+            MessageKey::InvalidReceiptText,
+            &[("block", block.to_string()), ("error", e.to_string())],
+        )
+    })?;
+    config.receipt_templates.validate().map_err(|(station, e)| {
+        LocalizedMessage::new(
+            locale,
+            MessageKey::InvalidReceiptText,
+            &[("block", format!("template ({})", station)), ("error", e.to_string())],
+        )
+    })?;
+    if let Some(timezone) = config.timezone.as_deref().filter(|tz| !tz.trim().is_empty()) {
+        venue_time::parse(timezone)
+            .map_err(|e| LocalizedMessage::new(locale, MessageKey::InvalidTimezone, &[("error", e)]))?;
+    }
+
+    // Three printers called "Kitchen" can't be told apart in the dashboard
+    printer_names::check_all(&mut config.printers).map_err(|e| {
+        LocalizedMessage::new(locale, MessageKey::InvalidPrinterName, &[("error", e.to_string())])
+    })?;
+
+    config.routing.validate(&config.printers).map_err(|e| {
+        LocalizedMessage::new(locale, MessageKey::InvalidRoutingRule, &[("error", e)])
+    })?;
+    config
+        .webhooks
+        .validate()
+        .map_err(|e| LocalizedMessage::new(locale, MessageKey::InvalidWebhookConfig, &[("error", e)]))?;
+
+    // Two printers on one device would print each other's tickets
+    if let Some(conflict) = address_conflicts::find(&config.printers).into_iter().next() {
+        return Err(LocalizedMessage::new(
+            locale,
+            MessageKey::DuplicatePrinterAddress,
+            &[("address", conflict.address), ("printers", conflict.printer_ids.join(", "))],
+        ));
+    }
 
     // Validate and resolve restaurant identifier
     if let Some(ref restaurant_id) = config.restaurant_id {
-        validate_restaurant_id(restaurant_id)?;
+        validate_restaurant_id(restaurant_id, locale)?;
 
         // If not a UUID, treat as restaurant_code and resolve to UUID via Supabase
         if uuid::Uuid::parse_str(restaurant_id.trim()).is_err() {
@@ -173,15 +433,17 @@ async fn save_config(
                     config.restaurant_id = Some(uuid);
                 }
                 Ok(None) => {
-                    return Err(format!(
-                        "Restaurant code '{}' not found. Check your code and try again.",
-                        code
+                    return Err(LocalizedMessage::new(
+                        locale,
+                        MessageKey::RestaurantCodeNotFound,
+                        &[("code", code)],
                     ));
                 }
                 Err(e) => {
-                    return Err(format!(
-                        "Could not look up restaurant code '{}': {}",
-                        code, e
+                    return Err(LocalizedMessage::new(
+                        locale,
+                        MessageKey::RestaurantLookupFailed,
+                        &[("code", code), ("error", e.to_string())],
                     ));
                 }
             }
@@ -190,7 +452,9 @@ async fn save_config(
 
     // Store auth_token in OS keychain (not in config.json)
     if let Some(ref token) = config.auth_token {
-        config::store_auth_token(token).map_err(|e| format!("Failed to store auth token: {}", e))?;
+        config::store_auth_token(token).map_err(|e| {
+            LocalizedMessage::new(locale, MessageKey::AuthTokenStoreFailed, &[("error", e)])
+        })?;
         info!("Auth token stored in OS keychain");
     }
 
@@ -200,20 +464,34 @@ async fn save_config(
     // Save to Tauri store (without auth_token — it's in keychain)
     let mut config_for_store = config.clone();
     config_for_store.auth_token = None;
-    let store = app.store("config.json").map_err(|e| e.to_string())?;
-    store.set("config", serde_json::to_value(&config_for_store).map_err(|e| e.to_string())?);
-    store.save().map_err(|e| e.to_string())?;
+    let store = app.store("config.json").map_err(|e| LocalizedMessage::internal(locale, e))?;
+    store.set("config", config_seal::seal(&config_for_store).map_err(|e| LocalizedMessage::internal(locale, e))?);
+    store.save().map_err(|e| LocalizedMessage::internal(locale, e))?;
 
     info!("Configuration saved (token in keychain, config in store)");
 
     // Sync printers to PrinterManager so test_print works immediately
     {
-        let pm = state.printer_manager.lock().await;
-        // Clear existing and re-add from config
-        for printer in &config.printers {
-            pm.add_printer(printer.clone()).await;
-        }
+        let pm = &state.printer_manager;
+        pm.sync_printers(&config.printers).await;
+        pm.set_item_images(config.item_images.clone()).await;
+        pm.set_receipt_fields(config.receipt_fields.clone()).await;
+        pm.set_receipt_templates(&config.receipt_templates).await;
+        pm.set_kitchen_logo(&config.test_print).await;
+        spawn_logo_download(&config.test_print);
+        pm.set_write_timeouts(config.write_timeouts);
+        pm.set_units(config.unit_format());
     }
+    state.queue_manager
+        .set_stations(StationMap::new(&config.printers, &config.station_aliases));
+    state.queue_manager.set_pull_stations(&config.pull_stations);
+    state.queue_manager.set_paused_printers(&config.paused_printers()).await;
+    state.queue_manager.set_ingest_dedup(&config.ingest_dedup);
+    telemetry::set_mode(config.telemetry_mode);
+    venue_time::set(config.timezone.as_deref());
+
+    // Pairing starts the local API, a factory reset (no restaurant) stops it
+    state.api_server.sync(config.restaurant_id.as_deref()).await;
 
     // Sync printers to Supabase via Edge Function
     if let Some(restaurant_id) = &config.restaurant_id {
@@ -267,16 +545,18 @@ async fn claim_pairing_code(
     code: String,
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LocalizedMessage> {
     info!("Claiming pairing code: {}...", &code[..std::cmp::min(2, code.len())]);
 
+    let mut config = state.config.lock().await;
+    let locale = config.locale;
+
     // Validate code format (9 digits)
     let trimmed = code.trim();
     if trimmed.len() != 9 || !trimmed.chars().all(|c| c.is_ascii_digit()) {
-        return Err("Ongeldige code. Vul 9 cijfers in.".to_string());
+        return Err(LocalizedMessage::new(locale, MessageKey::InvalidPairingCode, &[]));
     }
 
-    let mut config = state.config.lock().await;
     let webapp_url = config.webapp_url.clone();
     let supabase_url = config.supabase_url.clone();
     let anon_key = config.supabase_anon_key.clone();
@@ -291,19 +571,24 @@ async fn claim_pairing_code(
     // Persist client_id if it was just generated
     if config.client_id.is_none() {
         config.client_id = Some(client_id.clone());
-        let store = app.store("config.json").map_err(|e| e.to_string())?;
-        store.set("config", serde_json::to_value(&*config).map_err(|e| e.to_string())?);
-        store.save().map_err(|e| e.to_string())?;
+        let store = app.store("config.json").map_err(|e| LocalizedMessage::internal(locale, e))?;
+        store.set("config", config_seal::seal(&*config).map_err(|e| LocalizedMessage::internal(locale, e))?);
+        store.save().map_err(|e| LocalizedMessage::internal(locale, e))?;
     }
     drop(config);
 
-    // Build client info from system
-    let client_info = serde_json::json!({
+    // Build client info from system. The web app hands the job encryption key to the
+    // restaurant's POS clients, so they can encrypt print requests for this daemon.
+    let mut client_info = serde_json::json!({
         "clientId": client_id,
         "name": "Eatsome Printer Service",
         "platform": std::env::consts::OS,
         "version": env!("CARGO_PKG_VERSION"),
     });
+    match job_encryption::key() {
+        Ok(key) => client_info["jobEncryptionKey"] = serde_json::json!(key.info()),
+        Err(e) => warn!("Pairing without a job encryption key: {}", e),
+    }
 
     // Create a temporary SupabaseClient (no auth_token yet — we're pairing)
     let client = SupabaseClient::new(supabase_url, anon_key, None);
@@ -311,7 +596,12 @@ async fn claim_pairing_code(
     let result = client
         .claim_pairing_code(&webapp_url, trimmed, &client_info)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| LocalizedMessage::internal(locale, e))?;
+
+    let restaurant_id = commands::record_pairing(&state.config, &app, &result, config::store_auth_token)
+        .await
+        .map_err(|e| LocalizedMessage::internal(locale, e))?;
+    state.api_server.sync(restaurant_id.as_deref()).await;
 
     Ok(serde_json::json!({
         "token": result.token,
@@ -321,15 +611,51 @@ async fn claim_pairing_code(
     }))
 }
 
-/// Discover all printers (USB + Network + Bluetooth) with ESC/POS protocol probing
+/// Pairing progress, so the frontend can resume a wizard that was closed after the
+/// code was claimed
+#[tauri::command]
+async fn get_pairing_state(state: State<'_, AppState>) -> Result<config::PairingState, String> {
+    Ok(state.config.lock().await.pairing_state())
+}
+
+/// Mark the setup wizard finished (printers discovered and saved)
+#[tauri::command]
+async fn complete_setup(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    let mut config = state.config.lock().await;
+    let Some(pairing) = config.pairing.as_mut() else {
+        return Ok(());
+    };
+    pairing.setup_completed_at = Some(chrono::Utc::now().to_rfc3339());
+
+    let mut config_for_store = config.clone();
+    config_for_store.auth_token = None;
+    let store = app.store("config.json").map_err(|e| e.to_string())?;
+    store.set("config", config_seal::seal(&config_for_store).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Discover all printers (USB + Network + Bluetooth) with ESC/POS protocol probing.
+/// `deep` scans many more TCP ports (support use; always a fresh scan).
+/// Each result gets a `suggested_name` from its model and `location` (the station it
+/// is being added for), unique among configured printers and the other results.
 #[tauri::command]
 async fn discover_printers(
     force: Option<bool>,
+    deep: Option<bool>,
+    location: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<Vec<serde_json::Value>, String> {
-    info!("Printer discovery requested (force: {:?})", force);
-    let manager = state.printer_manager.lock().await;
-    let results = manager.discover_all(force.unwrap_or(false))
+    info!("Printer discovery requested (force: {:?}, deep: {:?})", force, deep);
+    let deep = deep.unwrap_or(false);
+    let profile = if deep {
+        discovery::PortScanProfile::deep()
+    } else {
+        state.config.lock().await.port_scan.clone()
+    };
+    // Scan without holding the manager lock so printing continues meanwhile
+    let discovery = state.printer_manager.discovery().with_profile(profile);
+    let results = discovery.run(force.unwrap_or(false) || deep)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -346,11 +672,17 @@ async fn discover_printers(
         discovery::probe_unknown_printers(&mut printers).await;
     }
 
-    // Convert back to JSON values
-    let json_results: Vec<serde_json::Value> = printers
-        .iter()
-        .filter_map(|p| serde_json::to_value(p).ok())
-        .collect();
+    // Convert back to JSON values, with a friendly name to prefill
+    let mut taken: Vec<String> = state.config.lock().await.printers.iter().map(|p| p.name.clone()).collect();
+    let mut json_results = Vec::with_capacity(printers.len());
+    for printer in &printers {
+        let Ok(mut value) = serde_json::to_value(printer) else { continue };
+        let taken_refs: Vec<&str> = taken.iter().map(String::as_str).collect();
+        let suggested = printer_names::suggest(&printer.name, &printer.vendor, location.as_deref(), &taken_refs);
+        value["suggested_name"] = serde_json::json!(suggested);
+        taken.push(suggested);
+        json_results.push(value);
+    }
 
     Ok(json_results)
 }
@@ -361,11 +693,34 @@ async fn test_print(
     printer_id: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    info!("Test print requested for printer: {}", printer_id);
-    let manager = state.printer_manager.lock().await;
-    manager.test_print(&printer_id)
-        .await
-        .map_err(|e| e.to_string())
+    commands::test_print(&state.config, &state.printer_manager, &printer_id).await
+}
+
+/// Print the "setup complete" receipt on every configured printer (end of the pairing wizard).
+/// Returns the IDs of the printers that printed it.
+#[tauri::command]
+async fn print_setup_complete(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let (printers, template, locale) = {
+        let config = state.config.lock().await;
+        (config.printers.clone(), config.test_print.clone(), config.locale)
+    };
+
+    let manager = &state.printer_manager;
+    let mut printed = Vec::new();
+    for printer in &printers {
+        let commands = escpos::format_setup_complete(
+            escpos::PaperWidth::from_chars(printer.capabilities.max_width),
+            &template,
+            locale,
+            &printer.name,
+            printer.station.as_deref(),
+        );
+        match manager.test_print(&printer.id, &commands).await {
+            Ok(()) => printed.push(printer.id.clone()),
+            Err(e) => warn!("Setup receipt failed on {}: {}", printer.id, e),
+        }
+    }
+    Ok(printed)
 }
 
 /// Test print on a discovered printer (not yet added to config)
@@ -373,11 +728,15 @@ async fn test_print(
 async fn test_discovered_printer(
     address: String,
     connection_type: String,
+    serial: Option<serial::SerialSettings>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
     info!("Test print requested for discovered printer: {} ({})", address, connection_type);
-    let manager = state.printer_manager.lock().await;
-    manager.test_print_direct(&address, &connection_type)
+    let commands = {
+        let config = state.config.lock().await;
+        escpos::format_test_print(escpos::PaperWidth::Width80mm, &config.test_print, config.locale, None)
+    };
+    state.printer_manager.test_print_direct(&address, &connection_type, serial, &commands)
         .await
         .map_err(|e| e.to_string())
 }
@@ -390,18 +749,19 @@ async fn test_discovered_printer(
 async fn start_polling(
     restaurant_id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), LocalizedMessage> {
     info!("Job polling requested for restaurant: {}", restaurant_id);
 
-    // Step 1: Validate UUID format
-    validate_restaurant_id(&restaurant_id)?;
-
     let config = state.config.lock().await;
+    let locale = config.locale;
+
+    // Step 1: Validate UUID format
+    validate_restaurant_id(&restaurant_id, locale)?;
 
     // Step 2: Check auth_token exists (in-memory config or OS keyring fallback)
     let auth_token = config.auth_token.clone().or_else(|| config::load_auth_token());
     if auth_token.is_none() {
-        return Err("No auth_token configured. Generate one from POS Devices page.".to_string());
+        return Err(LocalizedMessage::new(locale, MessageKey::NoAuthToken, &[]));
     }
 
     let supabase_client = Arc::new(SupabaseClient::new(
@@ -412,6 +772,7 @@ async fn start_polling(
 
     // Gather printer_ids for heartbeat piggyback
     let printer_ids: Vec<String> = config.printers.iter().map(|p| p.id.clone()).collect();
+    let transform_rules = config.transform.clone();
     drop(config);
 
     // Stop existing poller first (prevents duplicates from React strict mode)
@@ -431,6 +792,7 @@ async fn start_polling(
         queue,
         printer_ids,
         state.failover_map.clone(),
+        transform_rules,
     );
 
     let mut handle = state.job_poller_handle.lock().await;
@@ -464,8 +826,21 @@ async fn stop_polling(state: State<'_, AppState>) -> Result<(), String> {
 async fn get_queue_stats(
     state: State<'_, AppState>,
 ) -> Result<serde_json::Value, String> {
-    let queue = state.queue_manager.lock().await;
-    queue.get_stats().await.map_err(|e| e.to_string())
+    state.queue_manager.get_stats().await.map_err(|e| e.to_string())
+}
+
+/// Get per-station print counts for a business date (default: the current one)
+#[tauri::command]
+async fn get_day_summary(
+    business_date: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<queue::DaySummary, String> {
+    if let Some(date) = &business_date {
+        business_date::parse(date).ok_or_else(|| format!("Invalid business date: {}", date))?;
+    }
+    let queue = &state.queue_manager;
+    let date = business_date.unwrap_or_else(|| queue.business_date());
+    queue.get_day_summary(date).await.map_err(|e| e.to_string())
 }
 
 /// Get telemetry metrics
@@ -474,18 +849,24 @@ async fn get_metrics(state: State<'_, AppState>) -> Result<serde_json::Value, St
     Ok(state.telemetry.get_metrics_json().await)
 }
 
+/// Polling connection state and backend health
+#[derive(serde::Serialize)]
+struct ConnectionState {
+    /// "connected" if the job poller is running, "disconnected" otherwise
+    state: &'static str,
+    /// Health of the Supabase Edge Function calls, so a slow backend shows apart from
+    /// slow printers
+    edge: edge_metrics::EdgeHealth,
+}
+
 /// Get polling connection state
-///
-/// Returns "connected" if the job poller is running, "disconnected" otherwise.
 #[tauri::command]
-async fn get_connection_state(state: State<'_, AppState>) -> Result<String, String> {
-    let handle = state.job_poller_handle.lock().await;
-    if let Some(h) = handle.as_ref() {
-        if !h.is_finished() {
-            return Ok("connected".to_string());
-        }
-    }
-    Ok("disconnected".to_string())
+async fn get_connection_state(state: State<'_, AppState>) -> Result<ConnectionState, String> {
+    let running = state.job_poller_handle.lock().await.as_ref().is_some_and(|h| !h.is_finished());
+    Ok(ConnectionState {
+        state: if running { "connected" } else { "disconnected" },
+        edge: edge_metrics::health(),
+    })
 }
 
 /// Check if printer is online
@@ -494,8 +875,7 @@ async fn is_printer_online(
     printer_id: String,
     state: State<'_, AppState>,
 ) -> Result<bool, String> {
-    let manager = state.printer_manager.lock().await;
-    Ok(manager.is_online(&printer_id).await)
+    Ok(state.printer_manager.is_online(&printer_id).await)
 }
 
 /// Add printer to configuration
@@ -505,21 +885,133 @@ async fn add_printer(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    info!("Adding printer: {} ({})", printer.name, printer.id);
+    commands::add_printer(&state.config, &state.printer_manager, &state.queue_manager, &app, printer).await
+}
 
-    let manager = state.printer_manager.lock().await;
-    manager.add_printer(printer.clone()).await;
+/// Rename a printer. The name must be valid and not used by another printer; the new
+/// name is pushed to Supabase right away so the dashboard and POS show it too.
+#[tauri::command]
+async fn rename_printer(
+    printer_id: String,
+    name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    commands::rename_printer(&state.config, &state.printer_manager, &state.queue_manager, &app, &printer_id, &name).await
+}
 
-    // Update config
-    let mut config = state.config.lock().await;
-    config.printers.push(printer);
+/// Pause a printer without removing it: its jobs wait in the queue until it is resumed
+#[tauri::command]
+async fn pause_printer(
+    printer_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    commands::set_printer_paused(&state.config, &state.printer_manager, &state.queue_manager, &app, &printer_id, true).await
+}
 
-    // Save to Tauri store
-    let store = app.store("config.json").map_err(|e| e.to_string())?;
-    store.set("config", serde_json::to_value(&*config).map_err(|e| e.to_string())?);
-    store.save().map_err(|e| e.to_string())?;
+/// Resume a paused printer; the jobs it held print right away
+#[tauri::command]
+async fn resume_printer(
+    printer_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    commands::set_printer_paused(&state.config, &state.printer_manager, &state.queue_manager, &app, &printer_id, false).await
+}
 
-    Ok(())
+/// Parse a printer list exported from another print service (PrintNode, Epson ePOS
+/// settings, CSV) into printer entries for review. Nothing is saved.
+#[tauri::command]
+async fn preview_printer_import(
+    content: String,
+    state: State<'_, AppState>,
+) -> Result<printer_import::ImportPreview, String> {
+    let existing = state.config.lock().await.printers.clone();
+    printer_import::preview(&content, &existing)
+}
+
+/// Add the reviewed printers of an import in one config save, then test print on each
+/// when asked. A name, ID or address conflict refuses the whole import.
+#[tauri::command]
+async fn import_printers(
+    printers: Vec<config::PrinterConfig>,
+    test_print: bool,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<printer_import::ImportOutcome>, String> {
+    if printers.is_empty() {
+        return Err("No printers to import".to_string());
+    }
+
+    let (imported, test_prints) = {
+        let mut config = state.config.lock().await;
+        let mut all = config.printers.clone();
+        all.extend(printers);
+        printer_names::check_all(&mut all).map_err(|e| e.to_string())?;
+        let imported = all[config.printers.len()..].to_vec();
+        if let Some(printer) = imported.iter().find(|p| all.iter().filter(|o| o.id == p.id).count() > 1) {
+            return Err(format!("Printer ID {} is used twice", printer.id));
+        }
+        if let Some(conflict) = address_conflicts::find(&all).into_iter().next() {
+            return Err(format!(
+                "Address {} is used by printers {}; fix it before importing",
+                conflict.address,
+                conflict.printer_ids.join(", ")
+            ));
+        }
+
+        for printer in &imported {
+            state.printer_manager.add_printer(printer.clone()).await;
+        }
+        config.printers = all;
+        state.queue_manager
+            .set_stations(StationMap::new(&config.printers, &config.station_aliases));
+
+        let store = app.store("config.json").map_err(|e| e.to_string())?;
+        store.set("config", config_seal::seal(&*config).map_err(|e| e.to_string())?);
+        store.save().map_err(|e| e.to_string())?;
+
+        let test_prints: Vec<Vec<u8>> = imported
+            .iter()
+            .map(|p| {
+                let width = escpos::PaperWidth::from_chars(p.capabilities.max_width);
+                escpos::format_test_print(width, &config.test_print, config.locale, Some(&p.name))
+            })
+            .collect();
+        (imported, test_prints)
+    };
+    info!("Imported {} printers", imported.len());
+
+    let names: Vec<&str> = imported.iter().map(|p| p.name.as_str()).collect();
+    if let Err(e) = state.queue_manager
+        .record_audit("printers_imported", "config.json", serde_json::json!({ "printers": names }))
+        .await
+    {
+        warn!("Failed to record printer import: {}", e);
+    }
+
+    let mut outcomes = Vec::with_capacity(imported.len());
+    for (printer, commands) in imported.iter().zip(test_prints) {
+        let (test_printed, test_error) = if test_print {
+            match state.printer_manager.test_print(&printer.id, &commands).await {
+                Ok(()) => (Some(true), None),
+                Err(e) => {
+                    warn!("Test print on imported printer {} failed: {}", printer.id, e);
+                    (Some(false), Some(e.to_string()))
+                }
+            }
+        } else {
+            (None, None)
+        };
+        outcomes.push(printer_import::ImportOutcome {
+            printer_id: printer.id.clone(),
+            name: printer.name.clone(),
+            test_printed,
+            test_error,
+        });
+    }
+    Ok(outcomes)
 }
 
 /// Remove printer from configuration
@@ -529,821 +1021,3109 @@ async fn remove_printer(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    info!("Removing printer: {}", printer_id);
+    commands::remove_printer(
+        &state.config,
+        &state.printer_manager,
+        &state.circuit_breakers,
+        &state.queue_manager,
+        &app,
+        &printer_id,
+    )
+    .await
+}
 
-    let manager = state.printer_manager.lock().await;
-    manager.remove_printer(&printer_id).await;
+/// Outcome of the config.json integrity check at startup (`config_seal.rs`)
+#[tauri::command]
+async fn get_config_integrity(state: State<'_, AppState>) -> Result<config_seal::ConfigIntegrity, String> {
+    Ok(state.config_integrity.lock().unwrap_or_else(|e| e.into_inner()).clone())
+}
 
-    // Update config
-    let mut config = state.config.lock().await;
-    config.printers.retain(|p| p.id != printer_id);
+/// Settle a config.json that failed its check at startup: `keep` its contents (only
+/// when still readable) and sign them again, or start over on defaults. Either way the
+/// rejected file stays in its backup.
+#[tauri::command]
+async fn recover_config(
+    keep: bool,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let (error, readable) = {
+        let integrity = state.config_integrity.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(error) = integrity.error.clone() else {
+            return Ok(());
+        };
+        if keep && integrity.readable.is_none() {
+            return Err("The rejected config can't be read any more; start over instead".to_string());
+        }
+        (error, integrity.readable.clone())
+    };
+    let recovered = readable.filter(|_| keep).unwrap_or_default();
 
-    // Save to Tauri store
+    let mut config_for_store = recovered.clone();
+    if config_for_store.auth_token.as_deref().is_some_and(|t| config::store_auth_token(t).is_ok()) {
+        config_for_store.auth_token = None;
+    }
     let store = app.store("config.json").map_err(|e| e.to_string())?;
-    store.set("config", serde_json::to_value(&*config).map_err(|e| e.to_string())?);
+    store.set("config", config_seal::seal(&config_for_store).map_err(|e| e.to_string())?);
     store.save().map_err(|e| e.to_string())?;
 
+    if keep {
+        apply_stored_config(
+            state.config.clone(),
+            state.printer_manager.clone(),
+            state.queue_manager.clone(),
+            state.api_server.clone(),
+            recovered,
+        )
+        .await;
+    }
+    *state.config_integrity.lock().unwrap_or_else(|e| e.into_inner()) = config_seal::ConfigIntegrity::default();
+    info!("Rejected config {} ({})", if keep { "kept" } else { "replaced by defaults" }, error);
+
+    if let Err(e) = state.queue_manager
+        .record_audit("config_recovered", "config.json", serde_json::json!({ "kept": keep, "error": error }))
+        .await
+    {
+        warn!("Failed to record config recovery: {}", e);
+    }
     Ok(())
 }
 
-/// Get daemon uptime in seconds
+/// Configured printers sharing an address (`address_conflicts.rs`)
 #[tauri::command]
-async fn get_uptime(state: State<'_, AppState>) -> Result<u64, String> {
-    Ok(state.start_time.elapsed().as_secs())
+async fn get_address_conflicts(state: State<'_, AppState>) -> Result<Vec<address_conflicts::AddressConflict>, String> {
+    Ok(address_conflicts::find(&state.config.lock().await.printers))
 }
 
-/// Generate a print preview for a test receipt
-///
-/// Returns a parsed receipt structure that the frontend can render
-/// using monospace fonts to simulate thermal printer output.
+/// Merge two printers that share an address: `duplicate_id` is removed and its queued
+/// and failed jobs move to `keep_id`. Returns how many jobs moved.
 #[tauri::command]
-async fn preview_test_print() -> Result<escpos::ParsedReceipt, String> {
-    let commands = escpos::format_test_print(escpos::PaperWidth::Width80mm);
-    Ok(escpos::parse_escpos(&commands, escpos::PaperWidth::Width80mm))
-}
+async fn merge_printers(
+    keep_id: String,
+    duplicate_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let mut config = state.config.lock().await;
+    let removed = address_conflicts::merge(&mut config.printers, &keep_id, &duplicate_id).map_err(|e| e.to_string())?;
+    info!("Merging printer {} into {} ({})", duplicate_id, keep_id, removed.address);
 
-/// Generate a print preview for a kitchen receipt
-#[tauri::command]
-async fn preview_kitchen_receipt(
-    station: String,
-    order_number: String,
-    order_type: Option<String>,
-    table_number: Option<String>,
-    customer_name: Option<String>,
-    priority: u8,
-    items: Vec<escpos::PrintItem>,
-) -> Result<escpos::ParsedReceipt, String> {
-    let timestamp = chrono::Utc::now().timestamp_millis();
-    let commands = escpos::format_kitchen_receipt(
-        &station,
-        &order_number,
-        order_type.as_deref(),
-        table_number.as_deref(),
-        customer_name.as_deref(),
-        priority,
-        &items,
-        timestamp,
-        escpos::PaperWidth::Width80mm,
-    );
-    Ok(escpos::parse_escpos(&commands, escpos::PaperWidth::Width80mm))
+    let pm = &state.printer_manager;
+    pm.sync_printers(&config.printers).await;
+    state.circuit_breakers.quarantine.release(&duplicate_id).await;
+    state.queue_manager
+        .set_stations(StationMap::new(&config.printers, &config.station_aliases));
+
+    let store = app.store("config.json").map_err(|e| e.to_string())?;
+    let mut config_for_store = config.clone();
+    config_for_store.auth_token = None;
+    store.set("config", config_seal::seal(&config_for_store).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())?;
+    drop(config);
+
+    let moved = state.queue_manager
+        .reassign_printer_jobs(&duplicate_id, &keep_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Err(e) = state.queue_manager
+        .record_audit(
+            "printers_merged",
+            &keep_id,
+            serde_json::json!({
+                "duplicate_id": duplicate_id,
+                "address": removed.address,
+                "jobs_moved": moved,
+            }),
+        )
+        .await
+    {
+        warn!("Failed to record printer merge: {}", e);
+    }
+    Ok(moved)
 }
 
-/// Escalate a pending job's priority (lower = higher priority, min 1)
+/// Print a column ruler so staff can read the paper width off the printout
+/// (for printers that don't report their model)
 #[tauri::command]
-async fn escalate_job_priority(
-    job_id: String,
-    new_priority: u8,
+async fn print_width_ruler(
+    printer_id: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    info!("Escalating job {} priority to {}", job_id, new_priority);
-    let queue = state.queue_manager.lock().await;
-    queue.escalate_priority(&job_id, new_priority).await.map_err(|e| e.to_string())
+    let commands = escpos::format_width_ruler(state.config.lock().await.locale);
+    state.printer_manager.test_print(&printer_id, &commands)
+        .await
+        .map_err(|e| e.to_string())
 }
 
-/// Get circuit breaker status for a specific printer
+/// Set a printer's paper width (58 or 80 mm), as read off the ruler by staff
 #[tauri::command]
-async fn get_circuit_breaker_status(
+async fn set_paper_width(
     printer_id: String,
+    width_mm: u16,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    let breaker = state.circuit_breakers.get_breaker(&printer_id).await;
-    let status = breaker.get_status().await;
-    serde_json::to_value(status).map_err(|e| e.to_string())
+) -> Result<(), String> {
+    let width = match width_mm {
+        58 => escpos::PaperWidth::Width58mm,
+        80 => escpos::PaperWidth::Width80mm,
+        _ => return Err(format!("Unsupported paper width: {}mm", width_mm)),
+    };
+    info!("Paper width of printer {} set to {}mm", printer_id, width_mm);
+
+    let mut config = state.config.lock().await;
+    let printer = config
+        .printers
+        .iter_mut()
+        .find(|p| p.id == printer_id)
+        .ok_or_else(|| format!("Printer not found: {}", printer_id))?;
+    printer.capabilities.max_width = width as u16;
+    printer.paper_width_checked = true;
+    state.printer_manager.add_printer(printer.clone()).await;
+    persist_config(&config, &state.app_handle).await;
+    Ok(())
 }
 
-/// Reset circuit breaker for a specific printer (admin function)
+/// Set what happens to a printer's failed tickets once its paper is replaced
 #[tauri::command]
-async fn reset_circuit_breaker(
+async fn set_recovery_mode(
     printer_id: String,
+    mode: recovery::RecoveryMode,
+    max_jobs: Option<usize>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    info!("Resetting circuit breaker for printer: {}", printer_id);
-    let breaker = state.circuit_breakers.get_breaker(&printer_id).await;
-    breaker.reset().await;
+    let mut config = state.config.lock().await;
+    let printer = config
+        .printers
+        .iter_mut()
+        .find(|p| p.id == printer_id)
+        .ok_or_else(|| format!("Printer not found: {}", printer_id))?;
+    printer.recovery.mode = mode;
+    if let Some(max_jobs) = max_jobs {
+        printer.recovery.max_jobs = max_jobs.max(1);
+    }
+    info!("Recovery print for {} set to {:?}", printer_id, mode);
+    state.printer_manager.add_printer(printer.clone()).await;
+    persist_config(&config, &state.app_handle).await;
     Ok(())
 }
 
-/// Manually trigger queue cleanup (remove old completed/failed jobs)
+/// Switch a printer to the compact (Font B) ticket layout or back; `None` restores the
+/// default (compact on Bluetooth only)
 #[tauri::command]
-async fn cleanup_queue(state: State<'_, AppState>) -> Result<(), String> {
-    info!("Manual queue cleanup requested");
-    let queue = state.queue_manager.lock().await;
-    queue.cleanup_old_jobs().await.map_err(|e| e.to_string())
+async fn set_printer_compact(printer_id: String, compact: Option<bool>, state: State<'_, AppState>) -> Result<(), String> {
+    let mut config = state.config.lock().await;
+    let printer = config
+        .printers
+        .iter_mut()
+        .find(|p| p.id == printer_id)
+        .ok_or_else(|| format!("Printer not found: {}", printer_id))?;
+    printer.compact = compact;
+    info!("Ticket layout for {} set to {:?}", printer_id, printer.receipt_layout());
+    state.printer_manager.add_printer(printer.clone()).await;
+    persist_config(&config, &state.app_handle).await;
+    Ok(())
 }
 
-/// Clear all jobs from the queue (used during factory reset)
+/// Set the transports a printer is tried over, in order (`transport.rs`). An empty list
+/// restores raw only.
 #[tauri::command]
-async fn clear_queue(state: State<'_, AppState>) -> Result<(), String> {
-    info!("Full queue clear requested (factory reset)");
-    let queue = state.queue_manager.lock().await;
-    queue.clear_all_jobs().await.map_err(|e| e.to_string())
+async fn set_printer_transports(
+    printer_id: String,
+    transports: Vec<transport::Transport>,
+    state: State<'_, AppState>,
+) -> Result<Vec<transport::Transport>, String> {
+    let mut config = state.config.lock().await;
+    let printer = config
+        .printers
+        .iter_mut()
+        .find(|p| p.id == printer_id)
+        .ok_or_else(|| format!("Printer not found: {}", printer_id))?;
+    printer.transports = transports;
+    let ladder = printer.transport_ladder();
+    info!("Transports for {} set to {:?}", printer_id, ladder);
+    state.printer_manager.add_printer(printer.clone()).await;
+    persist_config(&config, &state.app_handle).await;
+    Ok(ladder)
 }
 
-/// Get event history from telemetry
+/// Transport each printer last printed over (printers that haven't printed yet are absent)
 #[tauri::command]
-async fn get_event_history(
-    limit: usize,
+async fn get_printer_transports(
     state: State<'_, AppState>,
-) -> Result<Vec<(u64, telemetry::TelemetryEvent)>, String> {
-    Ok(state.telemetry.get_event_history(limit).await)
+) -> Result<std::collections::HashMap<String, transport::Transport>, String> {
+    Ok(state.printer_manager.active_transports().await)
 }
 
-/// Read last N lines from log file for debugging
+/// Recovery prints waiting for staff confirmation
 #[tauri::command]
-async fn get_log_tail(lines: usize) -> Result<String, String> {
-    let log_path = dirs::home_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("Library")
-        .join("Logs")
-        .join("EatsomePrinterService")
-        .join("app.log");
+async fn get_recovery_offers(state: State<'_, AppState>) -> Result<Vec<recovery::RecoveryOffer>, String> {
+    Ok(state.recovery.offers().await)
+}
+
+/// Reprint the tickets that failed while the printer was out of paper.
+/// Returns how many were queued again.
+#[tauri::command]
+async fn confirm_recovery_print(printer_id: String, state: State<'_, AppState>) -> Result<usize, String> {
+    state.recovery.confirm(&printer_id).await.map_err(|e| e.to_string())
+}
+
+/// Skip the recovery print (staff reprinted by hand)
+#[tauri::command]
+async fn dismiss_recovery_print(printer_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.recovery.dismiss(&printer_id).await;
+    Ok(())
+}
+
+/// Retry clearing a cutter jam / mechanical fault (last step of the guided recovery)
+#[tauri::command]
+async fn attempt_printer_recovery(
+    printer_id: String,
+    state: State<'_, AppState>,
+) -> Result<jam_recovery::RecoveryAttempt, String> {
+    let printer = state
+        .printer_manager
+        .get_printer(&printer_id)
+        .await
+        .ok_or_else(|| format!("Printer not found: {}", printer_id))?;
+    let status = {
+        let pm = &state.printer_manager;
+        pm.poll_status(&printer).await.map_err(|e| e.to_string())?
+    };
+    // Cleared by the staff's power cycle, or by the printer itself: nothing to send
+    let Some(fault) = jam_recovery::MechanicalFault::from_status(&status) else {
+        return Ok(jam_recovery::RecoveryAttempt {
+            printer_id,
+            fault: None,
+            recovered: true,
+            status: Some(status.to_status_string().to_string()),
+            automatic: false,
+        });
+    };
+    Ok(attempt_mechanical_recovery(&printer, fault, false, &state.printer_manager).await)
+}
+
+/// Cutter / mechanical faults per printer over the last `days` (default 30)
+#[tauri::command]
+async fn get_mechanical_faults(
+    days: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<Vec<jam_recovery::MechanicalFaultSummary>, String> {
+    let since = chrono::Utc::now().timestamp() - days.unwrap_or(30) as i64 * 24 * 3600;
+    state.queue_manager.mechanical_faults(since).await.map_err(|e| e.to_string())
+}
+
+/// Get daemon uptime in seconds
+#[tauri::command]
+async fn get_uptime(state: State<'_, AppState>) -> Result<u64, String> {
+    Ok(state.start_time.elapsed().as_secs())
+}
+
+/// Generate a print preview for a test receipt
+///
+/// Returns a parsed receipt structure that the frontend can render
+/// using monospace fonts to simulate thermal printer output.
+#[tauri::command]
+async fn preview_test_print(state: State<'_, AppState>) -> Result<escpos::ParsedReceipt, String> {
+    if low_resource::is_enabled() {
+        return Err("Receipt preview is off in low-resource mode".to_string());
+    }
+    let commands = {
+        let config = state.config.lock().await;
+        escpos::format_test_print(escpos::PaperWidth::Width80mm, &config.test_print, config.locale, None)
+    };
+    Ok(escpos::parse_escpos(&commands, escpos::PaperWidth::Width80mm))
+}
+
+/// Generate a print preview for a kitchen receipt (`compact`: the Font B layout), with
+/// the configured header and footer
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn preview_kitchen_receipt(
+    station: String,
+    order_number: String,
+    order_type: Option<String>,
+    table_number: Option<String>,
+    customer_name: Option<String>,
+    priority: u8,
+    items: Vec<escpos::PrintItem>,
+    compact: Option<bool>,
+    pickup_time: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<escpos::ParsedReceipt, String> {
+    if low_resource::is_enabled() {
+        return Err("Receipt preview is off in low-resource mode".to_string());
+    }
+    let layout = if compact.unwrap_or(false) {
+        escpos::ReceiptLayout::Compact
+    } else {
+        escpos::ReceiptLayout::Normal
+    };
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let (blocks, items) = {
+        let config = state.config.lock().await;
+        let blocks = config.receipt_fields.render(&receipt_fields::OrderFields {
+            order_number: &order_number,
+            pickup_time,
+        });
+        (blocks, units::localize_items(&items, config.unit_format()))
+    };
+    let commands = escpos::format_kitchen_receipt(
+        &station,
+        &order_number,
+        order_type.as_deref(),
+        table_number.as_deref(),
+        customer_name.as_deref(),
+        priority,
+        &items,
+        timestamp,
+        1,
+        None,
+        &escpos::Thumbnails::new(),
+        escpos::PaperWidth::Width80mm,
+        layout,
+        &blocks,
+    );
+    Ok(escpos::parse_escpos(&commands, escpos::PaperWidth::Width80mm))
+}
+
+/// Venue time zone: configured, detected and in use
+#[tauri::command]
+async fn get_timezone() -> Result<venue_time::TimezoneInfo, String> {
+    Ok(venue_time::info())
+}
+
+/// Kitchen ticket templates by station (`*`: stations without their own)
+#[tauri::command]
+async fn get_receipt_templates(
+    state: State<'_, AppState>,
+) -> Result<std::collections::BTreeMap<String, String>, String> {
+    Ok(state.config.lock().await.receipt_templates.stations.clone())
+}
+
+/// Check a ticket template without saving it; the error names the line
+#[tauri::command]
+async fn validate_receipt_template(source: String) -> Result<(), String> {
+    receipt_templates::compile(&source).map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// Save a station's ticket template, used from its next ticket on. An empty template
+/// restores the built-in layout.
+#[tauri::command]
+async fn save_receipt_template(station: String, source: String, state: State<'_, AppState>) -> Result<(), String> {
+    let station = station.trim().to_string();
+    if station.is_empty() {
+        return Err("Station is required".to_string());
+    }
+    let removed = source.trim().is_empty();
+    if !removed {
+        receipt_templates::compile(&source).map_err(|e| e.to_string())?;
+    }
+
+    let mut config = state.config.lock().await;
+    // One template per station, whatever the capitalisation it was saved with
+    config.receipt_templates.stations.retain(|s, _| !s.eq_ignore_ascii_case(&station));
+    if !removed {
+        config.receipt_templates.stations.insert(station.clone(), source);
+    }
+    state.printer_manager.set_receipt_templates(&config.receipt_templates).await;
+    persist_config(&config, &state.app_handle).await;
+    drop(config);
+    info!("Receipt template for {} {}", station, if removed { "removed" } else { "saved" });
+
+    if let Err(e) = state.queue_manager
+        .record_audit("receipt_template_saved", &station, serde_json::json!({ "removed": removed }))
+        .await
+    {
+        warn!("Failed to record receipt template change: {}", e);
+    }
+    Ok(())
+}
+
+/// Preview a kitchen ticket through a template: `source` if given (unsaved edits),
+/// otherwise the station's saved one
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn preview_receipt_template(
+    source: Option<String>,
+    station: String,
+    order_number: String,
+    order_type: Option<String>,
+    table_number: Option<String>,
+    customer_name: Option<String>,
+    priority: u8,
+    items: Vec<escpos::PrintItem>,
+    pickup_time: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<escpos::ParsedReceipt, String> {
+    if low_resource::is_enabled() {
+        return Err("Receipt preview is off in low-resource mode".to_string());
+    }
+    let (template, blocks) = {
+        let config = state.config.lock().await;
+        let template = match source {
+            Some(source) => receipt_templates::compile(&source).map_err(|e| e.to_string())?,
+            None => receipt_templates::ReceiptTemplates::new(&config.receipt_templates)
+                .for_station(&station)
+                .cloned()
+                .ok_or_else(|| format!("No receipt template for {}", station))?,
+        };
+        let blocks = config.receipt_fields.render(&receipt_fields::OrderFields {
+            order_number: &order_number,
+            pickup_time,
+        });
+        (template, blocks)
+    };
+    let commands = template.render(
+        &receipt_templates::TicketData {
+            station: &station,
+            order_number: &order_number,
+            order_type: order_type.as_deref(),
+            table_number: table_number.as_deref(),
+            customer_name: customer_name.as_deref(),
+            priority,
+            items: &items,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            pickup_time,
+            reference: None,
+        },
+        1,
+        escpos::PaperWidth::Width80mm,
+        &blocks,
+    );
+    Ok(escpos::parse_escpos(&commands, escpos::PaperWidth::Width80mm))
+}
+
+/// Generate a print preview for a customer receipt or guest check (prices, discounts,
+/// tip, payment, VAT summary)
+#[tauri::command]
+async fn preview_customer_receipt(
+    order_number: String,
+    items: Vec<escpos::PrintItem>,
+    check: Option<escpos::CheckTotals>,
+    pickup_time: Option<i64>,
+    order_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<escpos::ParsedReceipt, String> {
+    if low_resource::is_enabled() {
+        return Err("Receipt preview is off in low-resource mode".to_string());
+    }
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let commands = {
+        let config = state.config.lock().await;
+        let blocks = config.receipt_fields.render(&receipt_fields::OrderFields {
+            order_number: &order_number,
+            pickup_time,
+        });
+        let order_id =
+            order_id.unwrap_or_else(|| format!("local:{}:{}", state.queue_manager.business_date(), order_number));
+        let link = order_links::receipt_link(&config, &order_id);
+        escpos::format_customer_receipt(
+            &config.test_print,
+            &order_number,
+            &units::localize_items(&items, config.unit_format()),
+            &check.unwrap_or_default(),
+            timestamp,
+            config.locale,
+            escpos::PaperWidth::Width80mm,
+            &blocks,
+            link.as_ref().map(|(purpose, url)| (*purpose, url.as_str())),
+        )
+    };
+    Ok(escpos::parse_escpos(&commands, escpos::PaperWidth::Width80mm))
+}
+
+/// Queue an "order ready" slip for the pickup counter (printed within a few seconds on
+/// the slip printer). `order_id` defaults to today's order number, like local API jobs.
+#[tauri::command]
+async fn print_pickup_slip(
+    order_number: String,
+    customer_name: Option<String>,
+    order_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let queue = &state.queue_manager;
+    let slip = pickup_slip::PickupSlip {
+        order_id: order_id.unwrap_or_else(|| format!("local:{}:{}", queue.business_date(), order_number)),
+        order_number,
+        customer_name,
+    };
+    queue.request_pickup_slip(&slip).await.map_err(|e| e.to_string())
+}
+
+/// Generate a print preview for a pickup slip
+#[tauri::command]
+async fn preview_pickup_slip(
+    order_number: String,
+    customer_name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<escpos::ParsedReceipt, String> {
+    if low_resource::is_enabled() {
+        return Err("Receipt preview is off in low-resource mode".to_string());
+    }
+    let slip = pickup_slip::PickupSlip {
+        order_id: format!("local:{}:{}", state.queue_manager.business_date(), order_number),
+        order_number,
+        customer_name,
+    };
+    let commands = {
+        let config = state.config.lock().await;
+        let url = order_links::pickup_slip_link(&config, &slip);
+        escpos::format_pickup_slip(&slip, url.as_deref(), config.locale, escpos::PaperWidth::Width80mm)
+    };
+    Ok(escpos::parse_escpos(&commands, escpos::PaperWidth::Width80mm))
+}
+
+/// Attach an operator note to a job (e.g. "printer was unplugged"). The note is stored
+/// locally and reported to Supabase with a fresh job log entry.
+#[tauri::command]
+async fn annotate_job(
+    job_id: String,
+    note: String,
+    state: State<'_, AppState>,
+) -> Result<queue::JobHistoryEntry, String> {
+    commands::annotate_job(&state.config, &state.queue_manager, &job_id, &note).await
+}
+
+/// Recent jobs with their status and operator notes, newest first
+#[tauri::command]
+async fn get_job_history(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<queue::JobHistoryEntry>, String> {
+    state.queue_manager.job_history(limit.unwrap_or(50)).await.map_err(|e| e.to_string())
+}
+
+/// An order's tickets across stations: status per job, which stations printed
+#[tauri::command]
+async fn list_order_jobs(
+    order_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<queue::OrderView>, String> {
+    state.queue_manager.order_jobs(&order_id).await.map_err(|e| e.to_string())
+}
+
+/// Front-of-house search: jobs by table number, customer name (prefix) or order number
+/// within a time window (default the last 6 hours), newest first
+#[tauri::command]
+async fn find_jobs(
+    search: queue::JobSearch,
+    state: State<'_, AppState>,
+) -> Result<Vec<queue::JobSearchResult>, String> {
+    state.queue_manager.find_jobs(&search).await.map_err(|e| e.to_string())
+}
+
+/// Daemon runs, newest first, with how each ended (clean, update, crash)
+#[tauri::command]
+async fn get_restart_history(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<restarts::DaemonRun>, String> {
+    state.queue_manager.daemon_runs(limit.unwrap_or(20)).await.map_err(|e| e.to_string())
+}
+
+/// Printer-down incidents, newest first (open ones count their affected jobs so far)
+#[tauri::command]
+async fn get_printer_incidents(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<incidents::Incident>, String> {
+    state.queue_manager.incidents(false, limit.unwrap_or(50)).await.map_err(|e| e.to_string())
+}
+
+/// Replay recent jobs against a hypothetical routing (station assignments, aliases,
+/// failover) and report where each would have printed. Printers that are down right
+/// now (open breaker, quarantined) count as down. The live config is not touched.
+#[tauri::command]
+async fn simulate_routing(
+    scenario: routing_sim::RoutingScenario,
+    sample: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<routing_sim::RoutingSimulation, String> {
+    let sample = sample.unwrap_or(routing_sim::DEFAULT_SAMPLE).clamp(1, routing_sim::MAX_SAMPLE);
+    let jobs = {
+        let queue = &state.queue_manager;
+        queue.job_history(sample).await.map_err(|e| e.to_string())?
+    };
+    let (printers, aliases, routing_config) = {
+        let cfg = state.config.lock().await;
+        (cfg.printers.clone(), cfg.station_aliases.clone(), cfg.routing.clone())
+    };
+    let failover = state.failover_map.lock().await.clone();
+
+    let mut down: std::collections::HashSet<String> = scenario.assume_down.iter().cloned().collect();
+    for printer in &printers {
+        let breaker_open = state.circuit_breakers.get_breaker(&printer.id).await.get_status().await.state == CircuitState::Open;
+        if breaker_open || state.circuit_breakers.quarantine.is_quarantined(&printer.id).await {
+            down.insert(printer.id.clone());
+        }
+    }
+
+    let routing = routing_sim::Routing::new(&scenario, &printers, &aliases, &routing_config, &failover);
+    let simulation = routing.simulate(&jobs, &down);
+    info!(
+        "Routing simulation over {} jobs: {} rerouted, {} via failover, {} failed",
+        jobs.len(), simulation.changed, simulation.failovers, simulation.failed
+    );
+    Ok(simulation)
+}
+
+/// Escalate a pending job's priority (lower = higher priority, min 1)
+#[tauri::command]
+async fn escalate_job_priority(
+    job_id: String,
+    new_priority: u8,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    info!("Escalating job {} priority to {}", job_id, new_priority);
+    state.queue_manager.escalate_priority(&job_id, new_priority).await.map_err(|e| e.to_string())
+}
+
+/// Cancel a job that hasn't printed yet. False for unknown and finished jobs.
+#[tauri::command]
+async fn cancel_job(job_id: String, state: State<'_, AppState>) -> Result<bool, String> {
+    commands::cancel_job(&state.config, &state.queue_manager, &state.job_cancel, &job_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get circuit breaker status for a specific printer
+#[tauri::command]
+async fn get_circuit_breaker_status(
+    printer_id: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let breaker = state.circuit_breakers.get_breaker(&printer_id).await;
+    let status = breaker.get_status().await;
+    serde_json::to_value(status).map_err(|e| e.to_string())
+}
+
+/// Breaker history for a printer
+#[derive(serde::Serialize)]
+struct BreakerHistory {
+    /// Newest first
+    transitions: Vec<queue::BreakerHistoryEntry>,
+    /// Minutes open or half-open per business day, last 14 days
+    degraded_days: Vec<queue::DegradedDay>,
+}
+
+/// Get a printer's persisted breaker transitions and minutes degraded per day
+#[tauri::command]
+async fn get_breaker_history(
+    printer_id: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<BreakerHistory, String> {
+    let queue = &state.queue_manager;
+    let since = business_date::parse(&queue.business_date())
+        .map(|today| (today - chrono::Duration::days(13)).format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+
+    Ok(BreakerHistory {
+        transitions: queue
+            .get_breaker_history(&printer_id, limit.unwrap_or(100))
+            .await
+            .map_err(|e| e.to_string())?,
+        degraded_days: queue
+            .degraded_minutes(&since, Some(&printer_id))
+            .await
+            .map_err(|e| e.to_string())?,
+    })
+}
+
+/// Reset circuit breaker for a specific printer (admin function)
+#[tauri::command]
+async fn reset_circuit_breaker(
+    printer_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    info!("Resetting circuit breaker for printer: {}", printer_id);
+    let breaker = state.circuit_breakers.get_breaker(&printer_id).await;
+    breaker.reset("manual_reset").await;
+    state.circuit_breakers.anomaly.reset(&printer_id).await;
+    Ok(())
+}
+
+/// Get quarantined printers with their re-probe progress
+#[tauri::command]
+async fn get_quarantine_status(
+    state: State<'_, AppState>,
+) -> Result<Vec<quarantine::QuarantineStatus>, String> {
+    Ok(state.circuit_breakers.quarantine.snapshot().await)
+}
+
+/// Sideline a printer without removing it: no jobs are routed to it until released
+/// or until it passes its re-probes
+#[tauri::command]
+async fn quarantine_printer(printer_id: String, state: State<'_, AppState>) -> Result<bool, String> {
+    info!("Quarantining printer: {}", printer_id);
+    Ok(state.circuit_breakers.quarantine(&printer_id, &state.telemetry).await)
+}
+
+/// Return a quarantined printer to rotation immediately
+#[tauri::command]
+async fn release_printer(printer_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    if !state.circuit_breakers.quarantine.is_quarantined(&printer_id).await {
+        return Ok(());
+    }
+    info!("Releasing printer from quarantine: {}", printer_id);
+    state.circuit_breakers.release(&printer_id, "released", &state.telemetry).await;
+    Ok(())
+}
+
+/// Pause job intake and printing for planned maintenance (router swap, printer service).
+/// Without `minutes` it lasts until `end_maintenance`.
+#[tauri::command]
+async fn start_maintenance(
+    minutes: Option<u32>,
+    reason: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<MaintenanceWindow, String> {
+    if minutes == Some(0) {
+        return Err("Maintenance duration must be at least one minute".to_string());
+    }
+    let window = state
+        .maintenance
+        .start(chrono::Utc::now(), minutes.map(|m| chrono::Duration::minutes(m as i64)), reason.clone())
+        .await;
+    // Jobs still waiting go back to the queue; the processor resumes once maintenance ends
+    state.job_cancel.pause();
+    announce_maintenance(&state.app_handle, &state.telemetry, Some(&window), reason).await;
+    Ok(window)
+}
+
+/// End maintenance now and resume printing
+#[tauri::command]
+async fn end_maintenance(state: State<'_, AppState>) -> Result<(), String> {
+    if let Some(ended) = state.maintenance.end().await {
+        announce_maintenance(&state.app_handle, &state.telemetry, None, ended.reason).await;
+    }
+    Ok(())
+}
+
+/// Lifetime counters per printer: jobs printed, bytes sent, failures, average job
+/// duration, last error
+#[tauri::command]
+async fn get_printer_stats(state: State<'_, AppState>) -> Result<Vec<printer_stats::PrinterStats>, String> {
+    state.queue_manager.printer_stats(false).await.map_err(|e| e.to_string())
+}
+
+/// Public key POS clients encrypt local print requests with (also sent on pairing)
+#[tauri::command]
+async fn get_job_encryption_key() -> Result<job_encryption::JobKeyInfo, String> {
+    job_encryption::key().map(|key| key.info()).map_err(|e| e.to_string())
+}
+
+/// Printers whose jobs currently go to their backup, and how close they are to failing back
+#[tauri::command]
+async fn get_failover_status(state: State<'_, AppState>) -> Result<Vec<failover::FailoverStatus>, String> {
+    Ok(state.circuit_breakers.failover.snapshot().await)
+}
+
+/// Bluetooth adapter state: adapter in use, last failure, BLE prints in flight, scans
+#[tauri::command]
+async fn get_ble_status() -> Result<ble::BleStatus, String> {
+    Ok(ble::coordinator().status())
+}
+
+/// Get the active maintenance window, if any
+#[tauri::command]
+async fn get_maintenance(state: State<'_, AppState>) -> Result<Option<MaintenanceWindow>, String> {
+    Ok(state.maintenance.current().await)
+}
+
+/// Switch low-resource mode (slow kitchen PCs) on or off. Takes effect right away.
+#[tauri::command]
+async fn set_low_resource_mode(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    let mut config = state.config.lock().await;
+    config.low_resource_mode = enabled;
+    low_resource::set_enabled(enabled);
+    persist_config(&config, &state.app_handle).await;
+    info!("Low-resource mode {}", if enabled { "on" } else { "off" });
+    Ok(())
+}
+
+/// Choose what the daemon reports about itself (Sentry, heartbeats, telemetry reports).
+/// Takes effect right away.
+#[tauri::command]
+async fn set_telemetry_mode(mode: telemetry::TelemetryMode, state: State<'_, AppState>) -> Result<(), String> {
+    let mut config = state.config.lock().await;
+    config.telemetry_mode = mode;
+    telemetry::set_mode(mode);
+    persist_config(&config, &state.app_handle).await;
+    info!("Telemetry mode: {}", mode.as_str());
+    Ok(())
+}
+
+/// Run the printer self-test now (same checks as the scheduled morning test)
+#[tauri::command]
+async fn run_self_test(state: State<'_, AppState>) -> Result<Vec<self_test::SelfTestResult>, String> {
+    Ok(run_printer_self_test(
+        &state.config,
+        &state.printer_manager,
+        &state.queue_manager,
+        &state.notifier,
+        &state.app_handle,
+        "manual",
+    ).await)
+}
+
+/// Get recent alerts (newest first), including ones that were not shown as notifications
+#[tauri::command]
+async fn get_notifications(
+    state: State<'_, AppState>,
+) -> Result<Vec<notifications::Alert>, String> {
+    Ok(state.notifier.center().history().await)
+}
+
+/// Clear the in-app alert list
+#[tauri::command]
+async fn clear_notifications(state: State<'_, AppState>) -> Result<(), String> {
+    state.notifier.center().clear().await;
+    Ok(())
+}
+
+/// Follow-up of the last shown notification, once. Called by the frontend when the
+/// window gains focus, so opening the app after a notification lands on the right screen.
+#[tauri::command]
+async fn take_notification_action(state: State<'_, AppState>) -> Result<Option<AlertAction>, String> {
+    Ok(state.notifier.center().take_pending_action().await)
+}
+
+/// Get per-printer health (failure rate / latency vs baseline) from the anomaly detector
+#[tauri::command]
+async fn get_printer_health(
+    state: State<'_, AppState>,
+) -> Result<Vec<anomaly::PrinterHealth>, String> {
+    Ok(state.circuit_breakers.anomaly.snapshot().await)
+}
+
+/// Get local queue depth and saturation state (also sent upstream with every poll)
+#[tauri::command]
+async fn get_queue_health(
+    state: State<'_, AppState>,
+) -> Result<queue::QueueHealth, String> {
+    state.queue_manager.get_health().await.map_err(|e| e.to_string())
+}
+
+/// Print the next waiting ticket of a pull-mode station (bump bar). None when nothing waits.
+#[tauri::command]
+async fn print_next(
+    station: String,
+    state: State<'_, AppState>,
+) -> Result<Option<queue::PulledTicket>, String> {
+    let pulled = state.queue_manager.print_next(&station).await.map_err(|e| e.to_string())?;
+    if let Some(ticket) = &pulled {
+        info!("Station {} pulled order {} ({} waiting)", station, ticket.order_number, ticket.waiting);
+    }
+    Ok(pulled)
+}
+
+/// Per-station pending tickets and estimated backlog, busiest first (also pushed as
+/// `station-load-updated` every 30 seconds)
+#[tauri::command]
+async fn get_station_load(
+    state: State<'_, AppState>,
+) -> Result<Vec<queue::StationLoad>, String> {
+    state.queue_manager.station_load().await.map_err(|e| e.to_string())
+}
+
+/// Open a network printer's own web pages (Epson/Star config UI) through the local API
+#[tauri::command]
+async fn open_printer_admin(
+    printer_id: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let config = state.config.lock().await;
+    if config.restaurant_id.is_none() {
+        return Err("Local API is not running (restaurant not configured)".to_string());
+    }
+    if printer_admin::printer_base_url(&config, &printer_id).is_none() {
+        return Err(format!("Printer {} is not a network printer", printer_id));
+    }
+    drop(config);
+
+    let token = printer_admin::issue_token(&printer_id);
+    let url = printer_admin::admin_url(api::API_ADDR, &printer_id, &token);
+    printer_admin::open_in_browser(&url).await.map_err(|e| e.to_string())?;
+    Ok(url)
+}
+
+/// Get recent audit log entries (automatic config changes such as printer re-addressing)
+#[tauri::command]
+async fn get_audit_log(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<serde_json::Value>, String> {
+    state.queue_manager.get_audit_log(limit.unwrap_or(100)).await.map_err(|e| e.to_string())
+}
+
+/// Run guided diagnostics for a printer and return the next action for staff
+#[tauri::command]
+async fn troubleshoot_printer(
+    printer_id: String,
+    run_test_print: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<troubleshoot::TroubleshootReport, LocalizedMessage> {
+    let (printer, locale, test_print) = {
+        let config = state.config.lock().await;
+        let printer = config
+            .printers
+            .iter()
+            .find(|p| p.id == printer_id)
+            .cloned()
+            .ok_or_else(|| {
+                LocalizedMessage::new(config.locale, MessageKey::PrinterNotFound, &[("printer_id", printer_id.clone())])
+            })?;
+        let test_print = run_test_print.unwrap_or(false).then(|| {
+            escpos::format_test_print(escpos::PaperWidth::Width80mm, &config.test_print, config.locale, Some(&printer.name))
+        });
+        (printer, config.locale, test_print)
+    };
+
+    Ok(troubleshoot::troubleshoot(&state.printer_manager, &printer, test_print.as_deref(), locale).await)
+}
+
+/// Check whether a USB printer can be opened, and which automated fix applies if not
+#[tauri::command]
+async fn check_usb_permissions(
+    address: String,
+    state: State<'_, AppState>,
+) -> Result<usb_setup::UsbPermissionCheck, String> {
+    Ok(usb_setup::check(&state.printer_manager, &address).await)
+}
+
+/// Fix USB permissions (Linux: udev rule via PolicyKit, macOS: open Privacy settings).
+/// Progress is reported through `usb-setup-progress` events.
+#[tauri::command]
+async fn fix_usb_permissions(
+    address: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<usb_setup::UsbPermissionCheck, LocalizedMessage> {
+    let locale = state.config.lock().await.locale;
+    usb_setup::fix(&app, &state.printer_manager, &address, locale).await
+}
+
+/// Manually trigger queue cleanup (remove old completed/failed jobs)
+#[tauri::command]
+async fn cleanup_queue(state: State<'_, AppState>) -> Result<(), String> {
+    info!("Manual queue cleanup requested");
+    state.queue_manager.cleanup_old_jobs().await.map_err(|e| e.to_string())
+}
+
+/// Local HTTP API state (running, address, paired restaurant, last error)
+#[tauri::command]
+async fn get_api_server_status(state: State<'_, AppState>) -> Result<api::ApiServerStatus, String> {
+    Ok(state.api_server.status())
+}
+
+/// Clear all jobs from the queue (used during factory reset)
+#[tauri::command]
+async fn clear_queue(state: State<'_, AppState>) -> Result<(), String> {
+    info!("Full queue clear requested (factory reset)");
+    state.queue_manager.clear_all_jobs().await.map_err(|e| e.to_string())
+}
+
+/// Export pending/printing jobs to an encrypted snapshot file (moving to a new kitchen PC).
+/// Defaults to a timestamped file on the desktop. Returns the path and job count.
+#[tauri::command]
+async fn export_queue(
+    path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, LocalizedMessage> {
+    let config = state.config.lock().await;
+    let locale = config.locale;
+    let restaurant_id = config.restaurant_id.clone()
+        .ok_or_else(|| LocalizedMessage::new(locale, MessageKey::QueueSnapshotNotPaired, &[]))?;
+    drop(config);
+
+    let path = path.map(std::path::PathBuf::from).unwrap_or_else(|| {
+        dirs::desktop_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+            .join(format!("eatsome-queue-{}.snapshot", chrono::Local::now().format("%Y%m%d-%H%M%S")))
+    });
+    let key = QueueManager::derive_key(&restaurant_id, "eatsome-queue-snapshot");
+
+    let queue = &state.queue_manager;
+    let jobs = queue.export_snapshot(path.clone(), key).await
+        .map_err(|e| LocalizedMessage::internal(locale, e))?;
+    let _ = queue.record_audit(
+        "queue_exported",
+        &path.display().to_string(),
+        serde_json::json!({ "jobs": jobs }),
+    ).await;
+
+    info!("Exported {} queued jobs to {}", jobs, path.display());
+    Ok(serde_json::json!({ "path": path, "jobs": jobs }))
+}
+
+/// Import a queue snapshot exported on another machine of the same restaurant.
+/// Duplicates are skipped; jobs for printers unknown here go to the station's (or primary) printer.
+#[tauri::command]
+async fn import_queue(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<queue::SnapshotImport, LocalizedMessage> {
+    let config = state.config.lock().await;
+    let locale = config.locale;
+    let restaurant_id = config.restaurant_id.clone()
+        .ok_or_else(|| LocalizedMessage::new(locale, MessageKey::QueueSnapshotNotPaired, &[]))?;
+    let printers = config.printers.clone();
+    drop(config);
+
+    let route = move |station: &str, printer_id: Option<&str>| {
+        if printers.iter().any(|p| Some(p.id.as_str()) == printer_id) {
+            return None;
+        }
+        printers
+            .iter()
+            .find(|p| p.station.as_deref() == Some(station))
+            .or_else(|| printers.iter().find(|p| p.is_primary))
+            .map(|p| p.id.clone())
+    };
+    let key = QueueManager::derive_key(&restaurant_id, "eatsome-queue-snapshot");
+
+    let queue = &state.queue_manager;
+    let summary = queue.import_snapshot(std::path::PathBuf::from(&path), key, route).await
+        .map_err(|e| LocalizedMessage::new(locale, MessageKey::QueueSnapshotUnreadable, &[("error", e.to_string())]))?;
+    let _ = queue.record_audit("queue_imported", &path, serde_json::json!(summary)).await;
+
+    info!(
+        "Imported queue snapshot {}: {} jobs, {} duplicates skipped, {} rerouted",
+        path, summary.imported, summary.skipped_duplicates, summary.rerouted
+    );
+    Ok(summary)
+}
+
+/// Set the fault injection profile (QA only: debug builds or EATSOME_FAULT_INJECTION=1).
+/// Not exposed in the UI; call via `invoke` from the devtools console or a test harness.
+#[tauri::command]
+async fn set_fault_injection(
+    config: fault_injection::FaultConfig,
+) -> Result<fault_injection::FaultConfig, String> {
+    fault_injection::configure(config)
+}
+
+/// Get the active fault injection profile
+#[tauri::command]
+async fn get_fault_injection() -> Result<fault_injection::FaultConfig, String> {
+    Ok(fault_injection::current())
+}
+
+/// Turn all injected faults off
+#[tauri::command]
+async fn clear_fault_injection() -> Result<(), String> {
+    fault_injection::configure(fault_injection::FaultConfig::default()).map(|_| ())
+}
+
+/// Get event history from telemetry
+#[tauri::command]
+async fn get_event_history(
+    limit: usize,
+    state: State<'_, AppState>,
+) -> Result<Vec<(u64, telemetry::TelemetryEvent)>, String> {
+    Ok(state.telemetry.get_event_history(limit).await)
+}
+
+/// Read last N lines from log file for debugging
+#[tauri::command]
+async fn get_log_tail(lines: usize) -> Result<String, String> {
+    let log_path = config::log_dir().join("app.log");
+
+    match std::fs::read_to_string(&log_path) {
+        Ok(content) => {
+            let all_lines: Vec<&str> = content.lines().collect();
+            let start_index = all_lines.len().saturating_sub(lines);
+            let tail_lines: Vec<&str> = all_lines[start_index..].to_vec();
+            Ok(tail_lines.join("\n"))
+        }
+        Err(e) => Err(format!("Failed to read log file: {}", e)),
+    }
+}
+
+/// Get log file path for user reference
+#[tauri::command]
+async fn get_log_path() -> Result<String, String> {
+    let log_path = config::log_dir().join("app.log");
+
+    Ok(log_path.display().to_string())
+}
+
+/// Collect logs, redacted config, queue stats, printer diagnostics, telemetry and
+/// version info into a single zip next to app.log. With `upload`, the bundle is also
+/// sent to the backend so support can pick it up directly.
+#[tauri::command]
+async fn generate_support_bundle(
+    upload: bool,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    info!("Generating support bundle (upload: {})", upload);
+    let log_dir = config::log_dir();
+
+    let config = state.config.lock().await.clone();
+    let mut entries = vec![
+        support::BundleEntry::json("version.json", &support::version_info(state.start_time.elapsed().as_secs())),
+        support::BundleEntry::json("config.json", &support::redact_config(&config)),
+        support::BundleEntry::text("app.log", support::read_log_tail(&log_dir.join("app.log"))),
+    ];
+
+    let queue_stats = {
+        let queue = &state.queue_manager;
+        queue.get_stats().await.unwrap_or_else(|e| serde_json::json!({ "error": e.to_string() }))
+    };
+    entries.push(support::BundleEntry::json("queue_stats.json", &queue_stats));
+    entries.push(support::BundleEntry::json(
+        "api_server.json",
+        &serde_json::to_value(state.api_server.status()).unwrap_or_default(),
+    ));
+
+    // Per-printer diagnostics: hardware status, breaker state, anomaly baseline, quarantine,
+    // lifetime stats
+    let health = state.circuit_breakers.anomaly.snapshot().await;
+    let quarantined = state.circuit_breakers.quarantine.snapshot().await;
+    let stats = state.queue_manager.printer_stats(false).await.unwrap_or_default();
+    let mut printers = Vec::new();
+    for printer in &config.printers {
+        let hw_status = {
+            let pm = &state.printer_manager;
+            pm.poll_status(printer).await
+        };
+        let breaker = state.circuit_breakers.get_breaker(&printer.id).await.get_status().await;
+        printers.push(serde_json::json!({
+            "printer": printer,
+            "hw_status": match hw_status {
+                Ok(s) => serde_json::json!({ "status": s.to_status_string(), "detail": s }),
+                Err(e) => serde_json::json!({ "error": e.to_string() }),
+            },
+            "circuit_breaker": breaker,
+            "health": health.iter().find(|h| h.printer_id == printer.id),
+            "quarantine": quarantined.iter().find(|q| q.printer_id == printer.id),
+            "stats": stats.iter().find(|s| s.printer_id == printer.id),
+        }));
+    }
+    entries.push(support::BundleEntry::json("printers.json", &serde_json::json!(printers)));
+    entries.push(support::BundleEntry::json(
+        "bluetooth.json",
+        &serde_json::to_value(ble::coordinator().status()).unwrap_or_default(),
+    ));
+
+    entries.push(support::BundleEntry::json("metrics.json", &state.telemetry.get_metrics_json().await));
+    let history = state.telemetry.get_event_history(1000).await;
+    entries.push(support::BundleEntry::json(
+        "telemetry_events.json",
+        &serde_json::to_value(&history).unwrap_or_default(),
+    ));
+
+    let path = support::bundle_path(&log_dir);
+    let size_bytes = support::write_bundle(&path, &entries).map_err(|e| e.to_string())?;
+
+    let mut uploaded_id = None;
+    if upload {
+        let client = create_supabase_client_from_config(&config)
+            .ok_or_else(|| "Bundle saved locally, but upload requires a paired daemon".to_string())?;
+        let contents = std::fs::read(&path).map_err(|e| e.to_string())?;
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        uploaded_id = client
+            .upload_support_bundle(&file_name, &contents)
+            .await
+            .map_err(|e| format!("Bundle saved to {}, but upload failed: {}", path.display(), e))?
+            .or(Some(file_name));
+    }
+
+    Ok(serde_json::json!({
+        "path": path.display().to_string(),
+        "size_bytes": size_bytes,
+        "uploaded": uploaded_id.is_some(),
+        "bundle_id": uploaded_id,
+    }))
+}
+
+// ============================================================================
+// System Tray
+// ============================================================================
+
+/// Status line of the tray menu
+struct TrayStatus(MenuItem<tauri::Wry>);
+
+/// Tell the dashboard and tray that maintenance started or ended
+async fn announce_maintenance(
+    app_handle: &Arc<Mutex<Option<tauri::AppHandle>>>,
+    telemetry: &TelemetryCollector,
+    window: Option<&MaintenanceWindow>,
+    reason: Option<String>,
+) {
+    telemetry.record_event(telemetry::TelemetryEvent::MaintenanceChanged {
+        active: window.is_some(),
+        reason,
+    }).await;
+
+    let Some(ref handle) = *app_handle.lock().await else {
+        return;
+    };
+    let _ = handle.emit("maintenance-changed", window);
+    if let Some(tray_status) = handle.try_state::<TrayStatus>() {
+        let text = match window.map(|w| w.ends_at) {
+            None => "Status: Idle".to_string(),
+            Some(None) => "Status: Maintenance".to_string(),
+            Some(Some(end)) => format!(
+                "Status: Maintenance until {}",
+                end.with_timezone(&venue_time::zone()).format("%H:%M")
+            ),
+        };
+        let _ = tray_status.0.set_text(text);
+    }
+}
+
+/// End maintenance windows whose time is up (checked every 15 seconds)
+fn start_maintenance_watch(
+    maintenance: Arc<Maintenance>,
+    app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+    telemetry: Arc<TelemetryCollector>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(15));
+        loop {
+            interval.tick().await;
+            if let Some(ended) = maintenance.expire(chrono::Utc::now()).await {
+                info!("Maintenance window ended, resuming printing");
+                announce_maintenance(&app_handle, &telemetry, None, ended.reason).await;
+            }
+        }
+    });
+}
+
+fn setup_system_tray(app: &tauri::AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    // Create menu items
+    let status = MenuItem::with_id(app, "status", "Status: Idle", false, None::<&str>)?;
+    let show = MenuItem::with_id(app, "show", "Show Dashboard", true, None::<&str>)?;
+    let hide = MenuItem::with_id(app, "hide", "Hide Window", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, Some("cmd+q"))?;
+
+    // Build menu
+    let menu = Menu::with_items(app, &[&status, &show, &hide, &quit])?;
+    app.manage(TrayStatus(status.clone()));
+
+    // Create tray icon
+    let mut tray_builder = TrayIconBuilder::new()
+        .tooltip("Eatsome Printer Service")
+        .menu(&menu);
+
+    if let Some(icon) = app.default_window_icon() {
+        tray_builder = tray_builder.icon(icon.clone());
+    } else {
+        warn!("No default window icon found for system tray");
+    }
+
+    let _tray = tray_builder
+        .on_menu_event(move |app, event| {
+            match event.id().as_ref() {
+                "quit" => {
+                    info!("Graceful shutdown initiated from tray menu");
+                    let state = app.state::<AppState>();
+                    state.shutdown_requested.store(true, Ordering::SeqCst);
+                    state.job_cancel.shutdown();
+
+                    let app_handle = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let state = app_handle.state::<AppState>();
+
+                        // Drain: wait for in-flight jobs to complete (max 10s)
+                        for i in 0..20 {
+                            match state.queue_manager.get_processing_count().await {
+                                Ok(0) => {
+                                    info!("All in-flight jobs drained after {}ms", i * 500);
+                                    break;
+                                }
+                                Ok(count) => {
+                                    debug!("Draining {} in-flight jobs... ({}ms elapsed)", count, i * 500);
+                                }
+                                Err(e) => {
+                                    warn!("Failed to check processing count: {}", e);
+                                    break;
+                                }
+                            }
+                            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                        }
+
+                        // Flush SQLite WAL to ensure queue data is persisted
+                        if let Err(e) = state.queue_manager.flush_db().await {
+                            error!("Failed to flush queue on shutdown: {}", e);
+                        }
+
+                        info!("Graceful shutdown complete, exiting");
+                        restarts::mark_stopping(restarts::StopReason::Clean, chrono::Utc::now().timestamp());
+                        app_handle.exit(0);
+                    });
+                }
+                "show" => {
+                    if let Some(window) = app.get_webview_window("main") {
+                        let _ = window.show();
+                        let _ = window.unminimize();
+                        let _ = window.set_focus();
+                    }
+                }
+                "hide" => {
+                    if let Some(window) = app.get_webview_window("main") {
+                        let _ = window.hide();
+                        info!("Window hidden to system tray");
+                    }
+                }
+                _ => {}
+            }
+        })
+        .on_tray_icon_event(|tray, event| {
+            // Left-click on tray icon → toggle window visibility
+            if let tauri::tray::TrayIconEvent::Click {
+                button: tauri::tray::MouseButton::Left,
+                ..
+            } = event
+            {
+                if let Some(window) = tray.app_handle().get_webview_window("main") {
+                    if window.is_visible().unwrap_or(false) {
+                        let _ = window.hide();
+                    } else {
+                        let _ = window.show();
+                        let _ = window.unminimize();
+                        let _ = window.set_focus();
+                    }
+                }
+            }
+        })
+        .build(app)?;
+
+    // Intercept window close → hide to tray instead of quitting
+    // This is critical for a daemon: closing the window must NOT stop the print service
+    if let Some(window) = app.get_webview_window("main") {
+        let win = window.clone();
+        window.on_window_event(move |event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                api.prevent_close();
+                let _ = win.hide();
+                info!("Window close intercepted - hidden to system tray");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Background Tasks
+// ============================================================================
+
+/// Create a SupabaseClient from the current config, if possible.
+/// Returns None if restaurant_id or auth_token is missing.
+/// Falls back to OS keyring if auth_token is not in memory.
+fn create_supabase_client_from_config(cfg: &AppConfig) -> Option<SupabaseClient> {
+    cfg.restaurant_id.as_ref()?;
+    let auth_token = cfg.auth_token.clone().or_else(|| config::load_auth_token());
+    if auth_token.is_none() {
+        debug!("No auth_token configured, skipping Supabase client creation");
+        return None;
+    }
+    Some(SupabaseClient::new(
+        cfg.supabase_url.clone(),
+        cfg.supabase_anon_key.clone(),
+        auth_token,
+    ))
+}
+
+/// Stations with a job in flight (ordered delivery). The slot is released when the
+/// print task finishes, whichever way it exits.
+struct StationSlot {
+    station: String,
+    busy: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+}
+
+impl StationSlot {
+    fn claim(busy: &Arc<std::sync::Mutex<std::collections::HashSet<String>>>, station: &str) -> Option<Self> {
+        let mut stations = busy.lock().unwrap_or_else(|e| e.into_inner());
+        stations.insert(station.to_string()).then(|| Self {
+            station: station.to_string(),
+            busy: busy.clone(),
+        })
+    }
+}
+
+impl Drop for StationSlot {
+    fn drop(&mut self) {
+        self.busy.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.station);
+    }
+}
+
+/// Start background job processor with parallel execution, circuit breaker, and failover
+async fn start_job_processor(
+    queue_manager: QueueManager,
+    printer_manager: PrinterManager,
+    telemetry: Arc<TelemetryCollector>,
+    circuit_breakers: Arc<CircuitBreakerRegistry>,
+    config: Arc<Mutex<AppConfig>>,
+    cancel: JobCancellation,
+    failover_map: Arc<Mutex<std::collections::HashMap<String, Vec<String>>>>,
+) {
+    info!(
+        "Starting background job processor (concurrency: {}, failover: enabled)",
+        low_resource::concurrency()
+    );
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(low_resource::NORMAL_CONCURRENCY));
+    let busy_stations = Arc::new(std::sync::Mutex::new(std::collections::HashSet::new()));
+
+    tokio::spawn(async move {
+        let mut poll_interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        // Permits held back from print tasks while low-resource mode is on
+        let mut held_permits: Option<tokio::sync::OwnedSemaphorePermit> = None;
+
+        loop {
+            tokio::select! {
+                _ = low_resource::tick(&mut poll_interval) => {}
+                _ = cancel.shutdown_requested() => {
+                    info!("Job processor stopping (shutdown requested)");
+                    break;
+                }
+            }
+            let _tick = task_metrics::tick("job_processor");
+
+            if !low_resource::is_enabled() {
+                held_permits = None;
+            } else if held_permits.is_none() {
+                // Taken as running jobs finish
+                let spare = (low_resource::NORMAL_CONCURRENCY - low_resource::LOW_CONCURRENCY) as u32;
+                held_permits = semaphore.clone().try_acquire_many_owned(spare).ok();
+            }
+
+            // Maintenance: queued jobs wait until it ends
+            if maintenance::is_active() {
+                cancel.pause();
+                continue;
+            }
+            cancel.resume();
+
+            // Get pending jobs from queue (ordered: head of each station only)
+            let ordered = config.lock().await.ordered_delivery;
+            let limit = low_resource::concurrency();
+            let pending = if ordered {
+                queue_manager.get_pending_jobs_ordered(limit).await
+            } else {
+                queue_manager.get_pending_jobs(limit).await
+            };
+            let pending_jobs = match pending {
+                Ok(jobs) => jobs,
+                Err(e) => {
+                    error!("Failed to get pending jobs: {}", e);
+                    continue;
+                }
+            };
+
+            if pending_jobs.is_empty() {
+                continue;
+            }
+
+            debug!("Processing {} pending jobs", pending_jobs.len());
+
+            for job in pending_jobs {
+                // The head job may not be marked printing yet when the next tick comes
+                let station_slot = if ordered {
+                    match StationSlot::claim(&busy_stations, &job.station) {
+                        Some(slot) => Some(slot),
+                        None => continue,
+                    }
+                } else {
+                    None
+                };
+
+                let queue_mgr = queue_manager.clone();
+                let printer_mgr = printer_manager.clone();
+                let telem = telemetry.clone();
+                let breakers = circuit_breakers.clone();
+                let permit = semaphore.clone();
+                let cfg = config.clone();
+                let failover = failover_map.clone();
+                let jobs_cancel = cancel.clone();
+                let running = cancel.start_job(&job.id);
+                let token = running.token.clone();
+
+                // Every log line and Sentry event of this job carries its correlation ID
+                let span = tracing::info_span!("print_job", job_id = %job.id, correlation_id = %job.correlation_id);
+                let hub = Arc::new(sentry::Hub::new_from_top(sentry::Hub::current()));
+                hub.configure_scope(|scope| scope.set_tag("correlation_id", &job.correlation_id));
+
+                tokio::spawn(async move {
+                    let _station_slot = station_slot;
+
+                    // Acquire semaphore permit (limits concurrency to 5, or 2 in low-resource mode).
+                    // Interrupted while waiting, the job simply stays pending.
+                    let _permit = tokio::select! {
+                        biased;
+                        _ = token.cancelled() => return,
+                        permit = task_metrics::acquire(&permit, "print_slots") => match permit {
+                            Ok(p) => p,
+                            Err(_) => return,
+                        },
+                    };
+
+                    let job_id = job.id.clone();
+                    // Jobs that arrive without a printer go where their station routes
+                    let printer_id = match job.printer_id.clone() {
+                        Some(printer_id) => printer_id,
+                        None => {
+                            let config_guard = cfg.lock().await;
+                            match routing::Router::new(&config_guard.routing, &config_guard.printers).route(&job.station) {
+                                Some(route) => {
+                                    debug!("Job {} (station {}) routed to {} ({:?})", job_id, job.station, route.printer_id, route.reason);
+                                    route.printer_id
+                                }
+                                None => "unknown".to_string(),
+                            }
+                        }
+                    };
+                    // A paused printer's jobs wait; routed ones are only known to be its here
+                    if queue_mgr.is_printer_paused(&printer_id) {
+                        debug!("Printer {} is paused, job {} waits", printer_id, job_id);
+                        if let Err(e) = queue_mgr.hold_job(&job_id, &printer_id).await {
+                            error!("Failed to hold job {}: {}", job_id, e);
+                        }
+                        return;
+                    }
+                    let start = std::time::Instant::now();
+
+                    // Create Supabase client for status reporting (best-effort)
+                    let supabase = {
+                        let config_guard = cfg.lock().await;
+                        create_supabase_client_from_config(&config_guard)
+                    };
+
+                    // Mark as processing (local + Supabase)
+                    match queue_mgr.mark_printing(&job_id).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            info!("Print job {} was cancelled or is already printing, skipped", job_id);
+                            return;
+                        }
+                        Err(e) => {
+                            error!("Failed to mark job {} as printing: {}", job_id, e);
+                            return;
+                        }
+                    }
+                    if let Some(ref client) = supabase {
+                        let _ = client.update_job_status(&job_id, status::PRINTING, None, None, Some(&job.correlation_id)).await;
+                    }
+
+                    // Execute print with circuit breaker + failover (120s total timeout)
+                    let result = tokio::time::timeout(
+                        std::time::Duration::from_secs(120),
+                        try_print_with_failover(
+                            &printer_id,
+                            &job,
+                            &printer_mgr,
+                            &breakers,
+                            &failover,
+                            &telem,
+                            &token,
+                        ),
+                    ).await;
+
+                    // Flatten timeout result
+                    let result = match result {
+                        Ok(inner) => inner,
+                        Err(_) => {
+                            error!("Print job {} timed out after 120s", job_id);
+                            Err(DaemonError::PrintJob("Total job timeout exceeded (120s)".to_string()))
+                        }
+                    };
+
+                    let duration_ms = start.elapsed().as_millis() as u64;
+
+                    // Cancelled while running and not printed: stays cancelled, whatever stopped it
+                    if running.is_cancelled() && result.is_err() {
+                        if let Err(e) = queue_mgr.cancel_job(&job_id).await {
+                            error!("Failed to cancel job {}: {}", job_id, e);
+                        }
+                        if let Some(ref client) = supabase {
+                            let _ = client.update_job_status(&job_id, status::CANCELLED, None, None, Some(&job.correlation_id)).await;
+                        }
+                        info!("Print job {} stopped before printing: cancelled", job_id);
+                        return;
+                    }
+
+                    match result {
+                        Ok((used_printer, delivery)) => {
+                            // Mark completed locally
+                            let _ = queue_mgr.mark_completed(&job_id, duration_ms, Some(delivery)).await;
+                            let notes = note_texts(&queue_mgr, &job_id).await;
+
+                            // Report to Supabase (best-effort, fire-and-forget)
+                            if let Some(ref client) = supabase {
+                                let _ = client.update_job_delivered(&job_id, duration_ms, &delivery, &job.correlation_id).await;
+                                let _ = client.insert_job_log(
+                                    &job.restaurant_id,
+                                    job.order_id.as_deref(),
+                                    Some(&used_printer),
+                                    job.station_id.as_deref(),
+                                    status::COMPLETED,
+                                    None,
+                                    None,
+                                    Some(duration_ms),
+                                    job.retry_count as i32,
+                                    &notes,
+                                ).await;
+                            }
+
+                            // Last ticket of the order: report it done, its pickup slip prints next
+                            if let Some(order_id) = &job.order_id {
+                                let auto_slip = cfg.lock().await.pickup_slip.auto;
+                                let order = pickup_slip::PickupSlip {
+                                    order_id: order_id.clone(),
+                                    order_number: job.order_number.clone(),
+                                    customer_name: job.customer_name.clone(),
+                                };
+                                commands::complete_order(&queue_mgr, supabase.as_ref(), &order, auto_slip).await;
+                            }
+
+                            telem.record_event(telemetry::TelemetryEvent::PrintJobCompleted {
+                                job_id: job_id.clone(),
+                                order_number: job.order_number.clone(),
+                                station: job.station.clone(),
+                                printer_id: used_printer.clone(),
+                                duration_ms,
+                                retry_count: job.retry_count,
+                                correlation_id: Some(job.correlation_id.clone()),
+                            }).await;
+                            if running.is_cancelled() {
+                                warn!("Print job {} was cancelled while its ticket was going out; it printed", job_id);
+                            }
+                            if used_printer != printer_id {
+                                warn!("Print job {} {} via failover to {} ({}ms)", job_id, delivery.phase(), used_printer, duration_ms);
+                            } else {
+                                info!("Print job {} {} in {}ms", job_id, delivery.phase(), duration_ms);
+                            }
+                        }
+                        // Stopped before any bytes went out: not a failed attempt
+                        Err(DaemonError::Interrupted(at)) => {
+                            info!("Print job {} interrupted ({}) {}, back in the queue", job_id, jobs_cancel.reason(), at);
+                            if let Err(e) = queue_mgr.release_job(&job_id).await {
+                                error!("Failed to put interrupted job {} back in the queue: {}", job_id, e);
+                            } else if let Some(ref client) = supabase {
+                                let _ = client.update_job_status(&job_id, status::PENDING, None, None, Some(&job.correlation_id)).await;
+                            }
+                        }
+                        Err(e) => {
+                            let class = e.retry_class();
+                            // Waiting on someone at the printer: try again later without using up a retry
+                            let blocked = class == errors::RetryClass::Blocked
+                                && match queue_mgr.block_job(&job_id, &e.to_string()).await {
+                                    Ok(blocked) => blocked,
+                                    Err(block_err) => {
+                                        error!("Failed to put blocked job {} back in the queue: {}", job_id, block_err);
+                                        false
+                                    }
+                                };
+                            if blocked {
+                                if let Some(ref client) = supabase {
+                                    let _ = client.update_job_status(&job_id, status::PENDING, None, None, Some(&job.correlation_id)).await;
+                                }
+                                warn!(
+                                    "Print job {} blocked on printer {}, trying again in {}s: {}",
+                                    job_id, printer_id, queue::BLOCKED_RETRY_SECS, e
+                                );
+                            } else {
+                                let _ = queue_mgr.mark_failed(&job_id, &e.to_string()).await;
+
+                                // Auto-retry: if under max retries, reset to pending
+                                if class == errors::RetryClass::Retryable && job.retry_count < 3 {
+                                    match queue_mgr.retry_job(&job_id).await {
+                                        Ok(_) => {
+                                            // Report retry to Supabase
+                                            if let Some(ref client) = supabase {
+                                                let _ = client.update_job_status(&job_id, status::PENDING, None, None, Some(&job.correlation_id)).await;
+                                            }
+                                            warn!(
+                                                "Print job {} failed (attempt {}/3, {}), re-queued for retry: {}",
+                                                job_id, job.retry_count + 1, class.as_str(), e
+                                            );
+                                        }
+                                        Err(retry_err) => {
+                                            error!("Failed to re-queue job {} for retry: {}", job_id, retry_err);
+                                        }
+                                    }
+                                } else {
+                                    let notes = note_texts(&queue_mgr, &job_id).await;
+                                    // Permanently failed — report to Supabase
+                                    if let Some(ref client) = supabase {
+                                        let _ = client.update_job_status(&job_id, status::FAILED, Some(&e.to_string()), None, Some(&job.correlation_id)).await;
+                                        let _ = client.insert_job_log(
+                                            &job.restaurant_id,
+                                            job.order_id.as_deref(),
+                                            Some(&printer_id),
+                                            job.station_id.as_deref(),
+                                            status::FAILED,
+                                            Some(&e.to_string()),
+                                            Some(class.as_str()),
+                                            None,
+                                            job.retry_count as i32,
+                                            &notes,
+                                        ).await;
+                                    }
 
-    match std::fs::read_to_string(&log_path) {
-        Ok(content) => {
-            let all_lines: Vec<&str> = content.lines().collect();
-            let start_index = all_lines.len().saturating_sub(lines);
-            let tail_lines: Vec<&str> = all_lines[start_index..].to_vec();
-            Ok(tail_lines.join("\n"))
+                                    telem.record_event(telemetry::TelemetryEvent::PrintJobFailed {
+                                        job_id: job_id.clone(),
+                                        order_number: job.order_number.clone(),
+                                        station: job.station.clone(),
+                                        printer_id: Some(printer_id.clone()),
+                                        error: e.to_string(),
+                                        retry_count: job.retry_count,
+                                        correlation_id: Some(job.correlation_id.clone()),
+                                    }).await;
+                                    error!(
+                                        "Print job {} permanently failed ({}) after {} retries: {}",
+                                        job_id, class.as_str(), job.retry_count, e
+                                    );
+                                    sentry_init::capture_print_job_failure(&job_id, &e.to_string(), &printer_id);
+                                }
+                            }
+                        }
+                    }
+                }.instrument(span).bind_hub(hub));
+            }
         }
-        Err(e) => Err(format!("Failed to read log file: {}", e)),
-    }
+    });
 }
 
-/// Get log file path for user reference
-#[tauri::command]
-async fn get_log_path() -> Result<String, String> {
-    let log_path = dirs::home_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("Library")
-        .join("Logs")
-        .join("EatsomePrinterService")
-        .join("app.log");
+/// Background task: print queued pickup slips (`pickup_slip.rs`) on the slip printer.
+/// Slips wait during maintenance like tickets do.
+fn start_pickup_slip_printer(queue_manager: QueueManager, printer_manager: PrinterManager, config: Arc<Mutex<AppConfig>>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(pickup_slip::CHECK_INTERVAL);
 
-    Ok(log_path.display().to_string())
+        loop {
+            interval.tick().await;
+            if maintenance::is_active() {
+                continue;
+            }
+            let slips = match queue_manager.pending_pickup_slips(pickup_slip::BATCH).await {
+                Ok(slips) if !slips.is_empty() => slips,
+                Ok(_) => continue,
+                Err(e) => {
+                    error!("Failed to read pickup slips: {}", e);
+                    continue;
+                }
+            };
+            let _tick = task_metrics::tick("pickup_slips");
+
+            let (settings, printers, locale, urls) = {
+                let config = config.lock().await;
+                let urls: Vec<_> = slips.iter().map(|slip| order_links::pickup_slip_link(&config, slip)).collect();
+                (config.pickup_slip.clone(), config.printers.clone(), config.locale, urls)
+            };
+            for (slip, url) in slips.into_iter().zip(urls) {
+                let result = match settings.printer(&printers) {
+                    Some(printer) => {
+                        let commands = escpos::format_pickup_slip(
+                            &slip,
+                            url.as_deref(),
+                            locale,
+                            escpos::PaperWidth::from_chars(printer.capabilities.max_width),
+                        );
+                        printer_manager
+                            .print_commands(&printer.id, &format!("Pickup {}", slip.order_number), &commands)
+                            .await
+                            .map_err(|e| e.to_string())
+                    }
+                    None => Err("no slip printer and no primary printer configured".to_string()),
+                };
+                match &result {
+                    Ok(()) => info!("Pickup slip printed for order {}", slip.order_number),
+                    Err(e) => warn!("Pickup slip for order {} failed: {}", slip.order_number, e),
+                }
+                if let Err(e) = queue_manager.finish_pickup_slip(&slip.order_id, result.err().as_deref()).await {
+                    error!("Failed to record pickup slip for order {}: {}", slip.order_number, e);
+                }
+            }
+        }
+    });
 }
 
-// ============================================================================
-// System Tray
-// ============================================================================
+/// Try printing on the specified printer with circuit breaker protection.
+/// On failure, attempts backup printers from the failover map, in the order
+/// `failover::Failover::plan` gives (a failed-over printer's backup first).
+/// Returns the printer_id that successfully printed and how far delivery was confirmed.
+async fn try_print_with_failover(
+    printer_id: &str,
+    job: &queue::PrintJob,
+    printer_manager: &PrinterManager,
+    circuit_breakers: &Arc<CircuitBreakerRegistry>,
+    failover_map: &Arc<Mutex<std::collections::HashMap<String, Vec<String>>>>,
+    telemetry: &Arc<TelemetryCollector>,
+    cancel: &CancellationToken,
+) -> errors::Result<(String, status::Delivery)> {
+    let backups = {
+        let map = failover_map.lock().await;
+        map.get(printer_id).cloned().unwrap_or_default()
+    };
+    let mut paused = Vec::new();
+    for backup in &backups {
+        if printer_manager.get_printer(backup).await.is_some_and(|p| p.paused) {
+            debug!("Skipping paused backup printer {} for job {}", backup, job.id);
+            paused.push(backup.clone());
+        }
+    }
+    let plan = circuit_breakers.failover.plan(printer_id, &backups, &paused).await;
+    if plan.first().is_some_and(|first| first != printer_id) {
+        debug!("Printer {} is failed over, job {} goes to {} first", printer_id, job.id, plan[0]);
+    }
 
-fn setup_system_tray(app: &tauri::AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    // Create menu items
-    let status = MenuItem::with_id(app, "status", "Status: Idle", false, None::<&str>)?;
-    let show = MenuItem::with_id(app, "show", "Show Dashboard", true, None::<&str>)?;
-    let hide = MenuItem::with_id(app, "hide", "Hide Window", true, None::<&str>)?;
-    let quit = MenuItem::with_id(app, "quit", "Quit", true, Some("cmd+q"))?;
+    let mut last_err = None;
+    for candidate in &plan {
+        let is_backup = candidate != printer_id;
+        if is_backup {
+            if circuit_breakers.quarantine.is_quarantined(candidate).await {
+                debug!("Skipping quarantined backup printer {} for job {}", candidate, job.id);
+                continue;
+            }
+            info!("Trying backup printer {} for job {}", candidate, job.id);
+        }
+        match try_print_single(candidate, job, printer_manager, circuit_breakers, telemetry, cancel).await {
+            Ok((used_id, delivery)) => {
+                if circuit_breakers.failover.record_printed(printer_id, &used_id).await {
+                    warn!("Printer {} failed over to {}, its jobs stay there until it is healthy again", printer_id, used_id);
+                }
+                if is_backup {
+                    warn!(
+                        "Job {} printed via failover: {} → {}",
+                        job.id, printer_id, used_id
+                    );
+                    telemetry.record_event(telemetry::TelemetryEvent::FailoverAttempted {
+                        job_id: job.id.clone(),
+                        primary_printer_id: printer_id.to_string(),
+                        backup_printer_id: used_id.clone(),
+                        success: true,
+                    }).await;
+                }
+                return Ok((used_id, delivery));
+            }
+            Err(e @ DaemonError::Interrupted(_)) => return Err(e),
+            Err(e) => {
+                if is_backup {
+                    warn!("Backup printer {} also failed for job {}: {}", candidate, job.id, e);
+                    telemetry.record_event(telemetry::TelemetryEvent::FailoverAttempted {
+                        job_id: job.id.clone(),
+                        primary_printer_id: printer_id.to_string(),
+                        backup_printer_id: candidate.clone(),
+                        success: false,
+                    }).await;
+                } else if backups.is_empty() {
+                    warn!(
+                        "Printer {} failed for job {} with no backups configured: {}",
+                        printer_id, job.id, e
+                    );
+                }
+                last_err = Some(e);
+            }
+        }
+    }
 
-    // Build menu
-    let menu = Menu::with_items(app, &[&status, &show, &hide, &quit])?;
+    // All printers failed
+    if !backups.is_empty() {
+        error!(
+            "All printers failed for job {} (primary: {}, backups: {:?})",
+            job.id, printer_id, backups
+        );
+    }
+    Err(last_err.unwrap_or_else(|| DaemonError::PrintJob(format!("No printer available for job {}", job.id))))
+}
 
-    // Create tray icon
-    let mut tray_builder = TrayIconBuilder::new()
-        .tooltip("Eatsome Printer Service")
-        .menu(&menu);
+/// Try printing on a single printer with circuit breaker protection.
+async fn try_print_single(
+    printer_id: &str,
+    job: &queue::PrintJob,
+    printer_manager: &PrinterManager,
+    circuit_breakers: &Arc<CircuitBreakerRegistry>,
+    telemetry: &Arc<TelemetryCollector>,
+    cancel: &CancellationToken,
+) -> errors::Result<(String, status::Delivery)> {
+    // Cancel point: nothing has gone to this printer yet
+    if cancel.is_cancelled() {
+        return Err(DaemonError::Interrupted(format!("before printer {}", printer_id)));
+    }
 
-    if let Some(icon) = app.default_window_icon() {
-        tray_builder = tray_builder.icon(icon.clone());
-    } else {
-        warn!("No default window icon found for system tray");
+    // Quarantined printers get no jobs; the prober decides when they come back
+    if circuit_breakers.quarantine.is_quarantined(printer_id).await {
+        return Err(DaemonError::PrintJob(format!("Printer {} is quarantined", printer_id)));
     }
 
-    let _tray = tray_builder
-        .on_menu_event(move |app, event| {
-            match event.id().as_ref() {
-                "quit" => {
-                    info!("Graceful shutdown initiated from tray menu");
-                    let state = app.state::<AppState>();
-                    state.shutdown_requested.store(true, Ordering::SeqCst);
+    let breaker = circuit_breakers.get_breaker(printer_id).await;
+    let pm = printer_manager.clone();
+    let pid = printer_id.to_string();
+    let job_clone = job.clone();
+    let start = Instant::now();
 
-                    let app_handle = app.clone();
-                    tauri::async_runtime::spawn(async move {
-                        let state = app_handle.state::<AppState>();
+    let result = breaker.execute(|| {
+        let pm = pm.clone();
+        let pid = pid.clone();
+        let job_clone = job_clone.clone();
+        async move { pm.print_to_printer(&pid, &job_clone, cancel).await }
+    }).await;
 
-                        // Drain: wait for in-flight jobs to complete (max 10s)
-                        for i in 0..20 {
-                            let queue = state.queue_manager.lock().await;
-                            match queue.get_processing_count().await {
-                                Ok(0) => {
-                                    info!("All in-flight jobs drained after {}ms", i * 500);
-                                    break;
-                                }
-                                Ok(count) => {
-                                    debug!("Draining {} in-flight jobs... ({}ms elapsed)", count, i * 500);
-                                }
-                                Err(e) => {
-                                    warn!("Failed to check processing count: {}", e);
-                                    break;
-                                }
-                            }
-                            drop(queue);
-                            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                        }
+    // Rejections by an already-open breaker, and interruptions, say nothing new about the printer
+    let rejected_by_breaker = matches!(&result, Err(DaemonError::PrintJob(msg)) if msg.starts_with("Circuit breaker OPEN"));
+    if !rejected_by_breaker && !matches!(result, Err(DaemonError::Interrupted(_))) {
+        circuit_breakers
+            .record_outcome(printer_id, result.is_ok(), start.elapsed().as_millis() as u64, telemetry)
+            .await;
+        circuit_breakers.readdress.record(printer_id, result.as_ref().err()).await;
+        if let Some(ref tx) = circuit_breakers.stats_tx {
+            let _ = tx.send(match &result {
+                Ok(delivery) => printer_stats::Attempt::printed(printer_id, delivery.bytes, start.elapsed().as_millis() as u64),
+                Err(e) => printer_stats::Attempt::failed(printer_id, &e.to_string()),
+            });
+        }
 
-                        // Flush SQLite WAL to ensure queue data is persisted
-                        let queue = state.queue_manager.lock().await;
-                        if let Err(e) = queue.flush_db().await {
-                            error!("Failed to flush queue on shutdown: {}", e);
-                        }
-                        drop(queue);
+        // A failure that leaves the breaker open just tripped it
+        if result.is_err() && breaker.get_status().await.state == CircuitState::Open {
+            circuit_breakers.record_trip(printer_id, telemetry).await;
+        }
+    }
 
-                        info!("Graceful shutdown complete, exiting");
-                        app_handle.exit(0);
-                    });
+    match result {
+        Ok(delivery) => Ok((printer_id.to_string(), delivery)),
+        Err(e) => {
+            warn!("Printer {} failed for job {}: {}", printer_id, job.id, e);
+            Err(e)
+        }
+    }
+}
+
+/// Register printers in Supabase on startup (upsert once, retry until success).
+///
+/// Heartbeat updates are now piggybacked on poll-jobs calls (Wave B),
+/// so this function only needs to run once to register printer records.
+/// Retries every 60s until successful, then stops.
+async fn start_printer_registration(
+    config: Arc<Mutex<AppConfig>>,
+    telemetry: Arc<TelemetryCollector>,
+) {
+    info!("Starting printer registration (one-time upsert with retry)");
+
+    tokio::spawn(async move {
+        loop {
+            let cfg = config.lock().await;
+            let restaurant_id = match &cfg.restaurant_id {
+                Some(id) => id.clone(),
+                None => {
+                    drop(cfg);
+                    // No restaurant configured yet — wait and retry
+                    tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+                    continue;
                 }
-                "show" => {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.unminimize();
-                        let _ = window.set_focus();
-                    }
+            };
+            let supabase_url = cfg.supabase_url.clone();
+            let anon_key = cfg.supabase_anon_key.clone();
+            let auth_token = cfg.auth_token.clone();
+            let printer_configs = cfg.printers.clone();
+            drop(cfg);
+
+            if printer_configs.is_empty() {
+                debug!("No printers configured, skipping registration");
+                tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+                continue;
+            }
+
+            if auth_token.is_none() {
+                warn!("No auth_token configured, skipping registration");
+                tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+                continue;
+            }
+
+            let client = SupabaseClient::new(supabase_url, anon_key, auth_token);
+
+            let now = chrono::Utc::now().to_rfc3339();
+            let printers_to_upsert: Vec<supabase_client::PrinterUpsert> = printer_configs
+                .iter()
+                .map(|p| printer_upsert(p, &restaurant_id, "online", &now))
+                .collect();
+
+            let printer_count = printers_to_upsert.len();
+            match client.upsert_printers(printers_to_upsert).await {
+                Ok(_) => {
+                    info!("Registered {} printers in Supabase (one-time)", printer_count);
+                    telemetry.update_printer_counts(printer_count, 0).await;
+                    // Success — stop retrying. Heartbeats are now handled by poll-jobs piggyback.
+                    break;
                 }
-                "hide" => {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.hide();
-                        info!("Window hidden to system tray");
-                    }
+                Err(e) => {
+                    warn!("Failed to register printers: {}. Retrying in 60s...", e);
+                    tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
                 }
-                _ => {}
             }
-        })
-        .on_tray_icon_event(|tray, event| {
-            // Left-click on tray icon → toggle window visibility
-            if let tauri::tray::TrayIconEvent::Click {
-                button: tauri::tray::MouseButton::Left,
-                ..
-            } = event
-            {
-                if let Some(window) = tray.app_handle().get_webview_window("main") {
-                    if window.is_visible().unwrap_or(false) {
-                        let _ = window.hide();
-                    } else {
-                        let _ = window.show();
-                        let _ = window.unminimize();
-                        let _ = window.set_focus();
-                    }
+        }
+    });
+}
+
+/// Supabase `printers` row for a configured printer
+fn printer_upsert(
+    p: &config::PrinterConfig,
+    restaurant_id: &str,
+    status: &str,
+    last_seen: &str,
+) -> supabase_client::PrinterUpsert {
+    let conn_type = match p.connection_type {
+        config::ConnectionType::USB => "usb",
+        config::ConnectionType::Network => "network",
+        config::ConnectionType::Bluetooth => "bluetooth",
+        config::ConnectionType::Serial => "serial",
+    };
+    supabase_client::PrinterUpsert {
+        id: p.id.clone(),
+        restaurant_id: restaurant_id.to_string(),
+        name: p.name.clone(),
+        connection_type: conn_type.to_string(),
+        address: p.address.clone(),
+        protocol: p.protocol.clone(),
+        capabilities: serde_json::to_value(&p.capabilities).unwrap_or_default(),
+        status: status.to_string(),
+        last_seen: last_seen.to_string(),
+    }
+}
+
+/// Background task: off-peak printer rediscovery (see `rediscovery.rs` for the guardrails).
+///
+/// Scans the network, learns MACs of printers still at their address, hands printers
+/// that are no longer there to the address recovery task, and re-registers all
+/// printers in Supabase with their current status.
+fn start_background_rediscovery(
+    config: Arc<Mutex<AppConfig>>,
+    printer_manager: PrinterManager,
+    queue_manager: QueueManager,
+    circuit_breakers: Arc<CircuitBreakerRegistry>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(rediscovery::CHECK_INTERVAL);
+        let mut last_run: Option<std::time::Instant> = None;
+
+        loop {
+            interval.tick().await;
+            let _tick = task_metrics::tick("rediscovery");
+
+            let (settings, profile) = {
+                let cfg = config.lock().await;
+                (cfg.rediscovery.clone(), cfg.port_scan.clone())
+            };
+            if !settings.enabled {
+                continue;
+            }
+
+            let (active_jobs, last_print) = {
+                let queue = &queue_manager;
+                let active = queue.get_health().await.map(|h| h.pending + h.printing).unwrap_or(u64::MAX);
+                let last = queue.recent_completed(1).await.ok().and_then(|r| r.first().map(|p| p.completed_at));
+                (active, last)
+            };
+            let now = venue_time::now();
+            let conditions = rediscovery::Conditions {
+                hour: chrono::Timelike::hour(&now) as u8,
+                since_last_run_secs: last_run.map(|t| t.elapsed().as_secs()),
+                active_jobs,
+                since_last_print_secs: last_print.map(|t| (now.timestamp() - t).max(0) as u64),
+            };
+            if let Err(skip) = rediscovery::should_run(&settings, &conditions) {
+                debug!("Background rediscovery skipped: {:?}", skip);
+                continue;
+            }
+
+            info!("Starting background printer rediscovery");
+            last_run = Some(std::time::Instant::now());
+
+            let discovery = printer_manager.discovery().with_profile(profile);
+            let discovered: Vec<discovery::DiscoveredPrinter> = match discovery.run(true).await {
+                Ok(values) => values.into_iter().filter_map(|v| serde_json::from_value(v).ok()).collect(),
+                Err(e) => {
+                    warn!("Background rediscovery failed: {}", e);
+                    continue;
                 }
+            };
+
+            let cfg = config.lock().await.clone();
+            let result = rediscovery::reconcile(&cfg.printers, &discovered);
+            info!(
+                "Background rediscovery: {} printers found, {} configured printers seen, {} drifted",
+                discovered.len(),
+                result.seen.len(),
+                result.drifted.len()
+            );
+            for id in &result.seen {
+                circuit_breakers.readdress.request(AddressRequest::Learn(id.clone()));
+            }
+            for id in &result.drifted {
+                circuit_breakers.readdress.request(AddressRequest::Rediscover(id.clone()));
             }
-        })
-        .build(app)?;
 
-    // Intercept window close → hide to tray instead of quitting
-    // This is critical for a daemon: closing the window must NOT stop the print service
-    if let Some(window) = app.get_webview_window("main") {
-        let win = window.clone();
-        window.on_window_event(move |event| {
-            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                api.prevent_close();
-                let _ = win.hide();
-                info!("Window close intercepted - hidden to system tray");
+            let (Some(client), Some(restaurant_id)) = (create_supabase_client_from_config(&cfg), cfg.restaurant_id.as_ref()) else {
+                continue;
+            };
+            let last_seen = chrono::Utc::now().to_rfc3339();
+            let upserts = cfg
+                .printers
+                .iter()
+                .map(|p| {
+                    let status = if result.drifted.contains(&p.id) { "offline" } else { "online" };
+                    printer_upsert(p, restaurant_id, status, &last_seen)
+                })
+                .collect();
+            if let Err(e) = client.upsert_printers(upserts).await {
+                warn!("Failed to reconcile printers with Supabase: {}", e);
             }
-        });
-    }
+        }
+    });
+}
 
-    Ok(())
+/// Background task: morning printer self-test (see `self_test.rs`), once a day at the
+/// configured time. Skipped during maintenance.
+fn start_self_test_scheduler(
+    config: Arc<Mutex<AppConfig>>,
+    printer_manager: PrinterManager,
+    queue_manager: QueueManager,
+    notifier: Arc<Notifier>,
+    app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(self_test::CHECK_INTERVAL);
+        let mut last_run: Option<chrono::NaiveDate> = None;
+
+        loop {
+            interval.tick().await;
+
+            let settings = config.lock().await.self_test.clone();
+            let now = venue_time::now().naive_local();
+            if !self_test::is_due(&settings, now, last_run) {
+                continue;
+            }
+            last_run = Some(now.date());
+
+            if maintenance::is_active() {
+                info!("Morning self-test skipped: maintenance mode");
+                continue;
+            }
+            info!("Starting morning printer self-test");
+            run_printer_self_test(&config, &printer_manager, &queue_manager, &notifier, &app_handle, "scheduled").await;
+        }
+    });
 }
 
-// ============================================================================
-// Background Tasks
-// ============================================================================
+/// Self-test the selected printers: report the results to Supabase, record them in the
+/// audit log, alert on failures and emit `self-test-completed`.
+async fn run_printer_self_test(
+    config: &Arc<Mutex<AppConfig>>,
+    printer_manager: &PrinterManager,
+    queue_manager: &QueueManager,
+    notifier: &Notifier,
+    app_handle: &Arc<Mutex<Option<tauri::AppHandle>>>,
+    trigger: &str,
+) -> Vec<self_test::SelfTestResult> {
+    let cfg = config.lock().await.clone();
+    let now = chrono::Utc::now().timestamp();
+
+    let mut results = Vec::new();
+    {
+        let manager = printer_manager;
+        for printer in cfg.printers.iter().filter(|p| cfg.self_test.includes(&p.id)) {
+            let page = cfg.self_test.print_page.then(|| {
+                escpos::format_test_print(
+                    escpos::PaperWidth::from_chars(printer.capabilities.max_width),
+                    &cfg.test_print,
+                    cfg.locale,
+                    Some(&printer.name),
+                )
+            });
+            results.push(self_test::test_printer(manager, printer, page.as_deref(), now).await);
+        }
+    }
 
-/// Create a SupabaseClient from the current config, if possible.
-/// Returns None if restaurant_id or auth_token is missing.
-/// Falls back to OS keyring if auth_token is not in memory.
-fn create_supabase_client_from_config(cfg: &AppConfig) -> Option<SupabaseClient> {
-    cfg.restaurant_id.as_ref()?;
-    let auth_token = cfg.auth_token.clone().or_else(|| config::load_auth_token());
-    if auth_token.is_none() {
-        debug!("No auth_token configured, skipping Supabase client creation");
-        return None;
+    let failed: Vec<&self_test::SelfTestResult> = results.iter().filter(|r| !r.passed).collect();
+    info!("Printer self-test ({}): {}/{} passed", trigger, results.len() - failed.len(), results.len());
+    for result in &failed {
+        let error = result.error.as_deref().unwrap_or("unknown error");
+        warn!("Printer {} failed its self-test: {}", result.printer_id, error);
+        notifier.notify(
+            AlertKind::SelfTestFailed,
+            Some(&result.printer_id),
+            LocalizedMessage::new(cfg.locale, MessageKey::NotifySelfTestFailedTitle, &[("printer", result.printer_name.clone())]).message,
+            LocalizedMessage::new(cfg.locale, MessageKey::NotifySelfTestFailedBody, &[("error", error.to_string())]).message,
+        ).await;
     }
-    Some(SupabaseClient::new(
-        cfg.supabase_url.clone(),
-        cfg.supabase_anon_key.clone(),
-        auth_token,
-    ))
+
+    if let Err(e) = queue_manager
+        .record_audit("self_test", trigger, serde_json::json!(results))
+        .await
+    {
+        warn!("Failed to record self-test: {}", e);
+    }
+
+    if let Some(client) = create_supabase_client_from_config(&cfg) {
+        if let Err(e) = client.report_self_test(&results).await {
+            warn!("Failed to report self-test to Supabase: {}", e);
+        }
+    }
+
+    if let Some(ref handle) = *app_handle.lock().await {
+        let _ = handle.emit("self-test-completed", &results);
+    }
+    results
 }
 
-/// Start background job processor with parallel execution, circuit breaker, and failover
-async fn start_job_processor(
-    queue_manager: Arc<Mutex<QueueManager>>,
-    printer_manager: Arc<Mutex<PrinterManager>>,
-    telemetry: Arc<TelemetryCollector>,
-    circuit_breakers: Arc<CircuitBreakerRegistry>,
+/// Background task: Poll printer hardware status via DLE EOT every 30 seconds.
+///
+/// For each configured printer, sends DLE EOT commands to read paper/cover/error state.
+/// On status change: updates Supabase + emits Tauri event for the frontend.
+/// Requires 2 consecutive poll failures before marking offline (prevents flapping).
+async fn start_status_poller(
     config: Arc<Mutex<AppConfig>>,
-    shutdown: Arc<AtomicBool>,
-    failover_map: Arc<Mutex<std::collections::HashMap<String, Vec<String>>>>,
+    printer_manager: PrinterManager,
+    app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+    circuit_breakers: Arc<CircuitBreakerRegistry>,
+    telemetry: Arc<TelemetryCollector>,
+    notifier: Arc<Notifier>,
+    recovery: Arc<RecoveryPrints>,
+    queue_manager: QueueManager,
 ) {
-    info!("Starting background job processor (concurrency: 5, failover: enabled)");
-    let semaphore = Arc::new(tokio::sync::Semaphore::new(5));
+    info!("Starting DLE EOT hardware status poller (30s interval)");
 
     tokio::spawn(async move {
-        let mut poll_interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
+        // Track last known status per printer for change detection
+        let mut last_status: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+        // Track consecutive poll failures per printer (2 required before offline)
+        let mut poll_failures: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
+        // Last hardware fault per printer (paper out, cover open, cutter), for alerts the
+        // status string doesn't show
+        let mut last_fault: std::collections::HashMap<String, troubleshoot::FailureClass> = std::collections::HashMap::new();
+        // When each printer ran out of paper (Unix seconds), until it is back online
+        let mut paper_out_since: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
+        // Last automatic jam / cutter recovery attempt per printer
+        let mut auto_attempts = jam_recovery::AutoAttempts::default();
+        // Printers down right now, and their incidents (open ones survive a restart)
+        let mut incident_tracker = match queue_manager.incidents(true, 1000).await {
+            Ok(open) => incidents::IncidentTracker::resume(&open),
+            Err(e) => {
+                warn!("Failed to read open incidents: {}", e);
+                incidents::IncidentTracker::default()
+            }
+        };
 
         loop {
-            poll_interval.tick().await;
-
-            // Check shutdown flag
-            if shutdown.load(Ordering::Relaxed) {
-                info!("Job processor stopping (shutdown requested)");
-                break;
-            }
+            low_resource::tick(&mut interval).await;
+            let _tick = task_metrics::tick("status_poller");
 
-            // Get pending jobs from queue
-            let queue = queue_manager.lock().await;
-            let pending_jobs = match queue.get_pending_jobs(5).await {
-                Ok(jobs) => jobs,
-                Err(e) => {
-                    error!("Failed to get pending jobs: {}", e);
-                    continue;
-                }
-            };
-            drop(queue);
+            let cfg = config.lock().await;
+            let auth_token = cfg.auth_token.clone();
+            let supabase_url = cfg.supabase_url.clone();
+            let anon_key = cfg.supabase_anon_key.clone();
+            let printer_configs = cfg.printers.clone();
+            let locale = cfg.locale;
+            let restaurant_id = cfg.restaurant_id.clone();
+            let incident_after = cfg.incidents.after_minutes as i64 * 60;
+            drop(cfg);
 
-            if pending_jobs.is_empty() {
+            if printer_configs.is_empty() || auth_token.is_none() {
                 continue;
             }
 
-            debug!("Processing {} pending jobs", pending_jobs.len());
+            let client = SupabaseClient::new(supabase_url, anon_key, auth_token);
 
-            for job in pending_jobs {
-                let queue_mgr = queue_manager.clone();
-                let printer_mgr = printer_manager.clone();
-                let telem = telemetry.clone();
-                let breakers = circuit_breakers.clone();
-                let permit = semaphore.clone();
-                let cfg = config.clone();
-                let failover = failover_map.clone();
+            for printer in &printer_configs {
+                // Quarantined printers are probed on their own, slower schedule
+                if circuit_breakers.quarantine.is_quarantined(&printer.id).await {
+                    continue;
+                }
 
-                tokio::spawn(async move {
-                    // Acquire semaphore permit (limits concurrency to 5)
-                    let _permit = match permit.acquire().await {
-                        Ok(p) => p,
-                        Err(_) => return,
-                    };
+                // Briefly lock PrinterManager for each poll, then release
+                let poll_result = {
+                    let pm = &printer_manager;
+                    pm.poll_status(printer).await
+                };
 
-                    let job_id = job.id.clone();
-                    let printer_id = job.printer_id.clone().unwrap_or_else(|| "unknown".to_string());
-                    let start = std::time::Instant::now();
+                // Failed-over printers get their jobs back after enough healthy checks
+                let healthy = matches!(&poll_result, Ok(s) if s.to_status_string() == "online");
+                circuit_breakers.failover.record_check(&printer.id, healthy).await;
 
-                    // Create Supabase client for status reporting (best-effort)
-                    let supabase = {
-                        let config_guard = cfg.lock().await;
-                        create_supabase_client_from_config(&config_guard)
-                    };
+                match poll_result {
+                    Ok(hw_status) => {
+                        // Reset failure counter on successful poll
+                        poll_failures.remove(&printer.id);
 
-                    // Mark as processing (local + Supabase)
-                    {
-                        let queue = queue_mgr.lock().await;
-                        if let Err(e) = queue.mark_printing(&job_id).await {
-                            error!("Failed to mark job {} as printing: {}", job_id, e);
-                            return;
-                        }
-                    }
-                    if let Some(ref client) = supabase {
-                        let _ = client.update_job_status(&job_id, status::PRINTING, None, None).await;
-                    }
+                        let new_status = hw_status.to_status_string().to_string();
+                        let prev_status = last_status.get(&printer.id);
+                        let status_changed = prev_status.map_or(true, |prev| prev != &new_status);
+                        let fault = troubleshoot::classify_hw_status(&hw_status);
+                        let fault_changed = last_fault.get(&printer.id) != fault.as_ref();
+                        let down_cause = (new_status == "offline")
+                            .then(|| fault.as_ref().map_or_else(|| "offline".to_string(), incidents::cause_name));
+                        track_incident(&mut incident_tracker, &queue_manager, &printer.id, down_cause, incident_after).await;
+
+                        if status_changed || fault_changed {
+                            let old_str = prev_status.unwrap_or(&"unknown".to_string()).clone();
+                            if status_changed {
+                                info!(
+                                    "Printer {} status changed: {} → {}",
+                                    printer.id, old_str, new_status
+                                );
 
-                    // Execute print with circuit breaker + failover (120s total timeout)
-                    let result = tokio::time::timeout(
-                        std::time::Duration::from_secs(120),
-                        try_print_with_failover(
-                            &printer_id,
-                            &job,
-                            &printer_mgr,
-                            &breakers,
-                            &failover,
-                            &telem,
-                        ),
-                    ).await;
+                                // Emit telemetry for status transition
+                                telemetry.record_event(telemetry::TelemetryEvent::PrinterStatusChanged {
+                                    printer_id: printer.id.clone(),
+                                    old_status: old_str,
+                                    new_status: new_status.clone(),
+                                }).await;
+                            }
+                            if fault_changed {
+                                info!("Printer {} hardware fault: {:?}", printer.id, fault);
+                            }
 
-                    // Flatten timeout result
-                    let result = match result {
-                        Ok(inner) => inner,
-                        Err(_) => {
-                            error!("Print job {} timed out after 120s", job_id);
-                            Err(DaemonError::PrintJob("Total job timeout exceeded (120s)".to_string()))
-                        }
-                    };
+                            // Reset circuit breaker on recovery so jobs flow immediately
+                            if status_changed && new_status == "online" {
+                                let breaker = circuit_breakers.get_breaker(&printer.id).await;
+                                breaker.reset("printer_online").await;
+                                info!("Printer {} recovered — circuit breaker reset", printer.id);
 
-                    let duration_ms = start.elapsed().as_millis() as u64;
+                                if let Some(since) = paper_out_since.remove(&printer.id) {
+                                    recovery.paper_restored(printer, since).await;
+                                }
+                            }
 
-                    match result {
-                        Ok(used_printer) => {
-                            // Mark completed locally
-                            let queue = queue_mgr.lock().await;
-                            let _ = queue.mark_completed(&job_id, duration_ms).await;
-                            drop(queue);
+                            if status_changed && new_status == "paper_out" {
+                                paper_out_since.entry(printer.id.clone()).or_insert_with(|| chrono::Utc::now().timestamp());
+                                notifier.notify(
+                                    AlertKind::PaperOut,
+                                    Some(&printer.id),
+                                    LocalizedMessage::new(locale, MessageKey::NotifyPaperOutTitle, &[("printer", printer.name.clone())]).message,
+                                    LocalizedMessage::new(locale, MessageKey::NotifyPaperOutBody, &[]).message,
+                                ).await;
+                            }
 
-                            // Report to Supabase (best-effort, fire-and-forget)
-                            if let Some(ref client) = supabase {
-                                let _ = client.update_job_status(&job_id, status::COMPLETED, None, Some(duration_ms)).await;
-                                let _ = client.insert_job_log(
-                                    &job.restaurant_id,
-                                    job.order_id.as_deref(),
-                                    Some(&used_printer),
-                                    job.station_id.as_deref(),
-                                    status::COMPLETED,
-                                    None,
-                                    Some(duration_ms),
-                                    job.retry_count as i32,
+                            // Reported as "offline" in the status string
+                            if fault_changed && fault == Some(troubleshoot::FailureClass::CoverOpen) {
+                                notifier.notify(
+                                    AlertKind::CoverOpen,
+                                    Some(&printer.id),
+                                    LocalizedMessage::new(locale, MessageKey::NotifyCoverOpenTitle, &[("printer", printer.name.clone())]).message,
+                                    LocalizedMessage::new(locale, MessageKey::NotifyCoverOpenBody, &[]).message,
                                 ).await;
                             }
 
-                            telem.record_event(telemetry::TelemetryEvent::PrintJobCompleted {
-                                job_id: job_id.clone(),
-                                order_number: job.order_number.clone(),
-                                station: job.station.clone(),
-                                printer_id: used_printer.clone(),
-                                duration_ms,
-                                retry_count: job.retry_count,
-                            }).await;
-                            if used_printer != printer_id {
-                                warn!("Print job {} completed via failover to {} ({}ms)", job_id, used_printer, duration_ms);
-                            } else {
-                                info!("Print job {} completed in {}ms", job_id, duration_ms);
+                            if let Some(fault) = jam_recovery::MechanicalFault::from_status(&hw_status).filter(|_| status_changed) {
+                                handle_mechanical_fault(
+                                    printer,
+                                    fault,
+                                    locale,
+                                    &mut auto_attempts,
+                                    &printer_manager,
+                                    &queue_manager,
+                                    &app_handle,
+                                    &notifier,
+                                ).await;
+                            }
+
+                            // Update Supabase with detailed status (outside PM lock)
+                            if let Err(e) = client.update_printer_status_detailed(
+                                &printer.id,
+                                &new_status,
+                                &hw_status,
+                            ).await {
+                                warn!("Failed to update printer {} status in Supabase: {}", printer.id, e);
+                            }
+
+                            // Emit Tauri event for frontend
+                            if let Some(ref handle) = *app_handle.lock().await {
+                                let _ = handle.emit("printer-hw-status", serde_json::json!({
+                                    "printer_id": printer.id,
+                                    "status": new_status,
+                                    "hw_status": hw_status,
+                                    "fault": fault,
+                                }));
                             }
+
+                            last_status.insert(printer.id.clone(), new_status);
+                            match fault {
+                                Some(fault) => last_fault.insert(printer.id.clone(), fault),
+                                None => last_fault.remove(&printer.id),
+                            };
                         }
-                        Err(e) => {
-                            let queue = queue_mgr.lock().await;
-                            let _ = queue.mark_failed(&job_id, &e.to_string()).await;
-
-                            // Auto-retry: if under max retries, reset to pending
-                            if job.retry_count < 3 {
-                                match queue.retry_job(&job_id).await {
-                                    Ok(_) => {
-                                        drop(queue);
-                                        // Report retry to Supabase
-                                        if let Some(ref client) = supabase {
-                                            let _ = client.update_job_status(&job_id, status::PENDING, None, None).await;
-                                        }
-                                        warn!(
-                                            "Print job {} failed (attempt {}/3), re-queued for retry: {}",
-                                            job_id, job.retry_count + 1, e
-                                        );
-                                    }
-                                    Err(retry_err) => {
-                                        error!("Failed to re-queue job {} for retry: {}", job_id, retry_err);
-                                    }
-                                }
-                            } else {
-                                drop(queue);
-                                // Permanently failed — report to Supabase
-                                if let Some(ref client) = supabase {
-                                    let _ = client.update_job_status(&job_id, status::FAILED, Some(&e.to_string()), None).await;
-                                    let _ = client.insert_job_log(
-                                        &job.restaurant_id,
-                                        job.order_id.as_deref(),
-                                        Some(&printer_id),
-                                        job.station_id.as_deref(),
-                                        status::FAILED,
-                                        Some(&e.to_string()),
-                                        None,
-                                        job.retry_count as i32,
-                                    ).await;
-                                }
+                    }
+                    Err(e) => {
+                        let cause = incidents::cause_name(&troubleshoot::classify_error(&e));
+                        track_incident(&mut incident_tracker, &queue_manager, &printer.id, Some(cause), incident_after).await;
 
-                                telem.record_event(telemetry::TelemetryEvent::PrintJobFailed {
-                                    job_id: job_id.clone(),
-                                    order_number: job.order_number.clone(),
-                                    station: job.station.clone(),
-                                    printer_id: Some(printer_id.clone()),
-                                    error: e.to_string(),
-                                    retry_count: job.retry_count,
+                        let count = poll_failures.entry(printer.id.clone()).or_insert(0);
+                        *count += 1;
+
+                        if *count >= 2 {
+                            // 2 consecutive failures → consider offline
+                            let prev_status = last_status.get(&printer.id);
+                            if prev_status.map_or(true, |s| s != "offline") {
+                                let old_str = prev_status.cloned().unwrap_or_else(|| "unknown".to_string());
+                                warn!(
+                                    "Printer {} unreachable after {} consecutive poll failures: {}",
+                                    printer.id, count, e
+                                );
+                                telemetry.record_event(telemetry::TelemetryEvent::PrinterStatusChanged {
+                                    printer_id: printer.id.clone(),
+                                    old_status: old_str,
+                                    new_status: "offline".to_string(),
                                 }).await;
-                                error!("Print job {} permanently failed after {} retries: {}", job_id, job.retry_count, e);
-                                sentry_init::capture_print_job_failure(&job_id, &e.to_string(), &printer_id);
+                                if let Err(e) = client.update_printer_status(&printer.id, "offline").await {
+                                    warn!("Failed to mark printer {} offline in Supabase: {}", printer.id, e);
+                                }
+                                if let Some(ref handle) = *app_handle.lock().await {
+                                    let _ = handle.emit("printer-hw-status", serde_json::json!({
+                                        "printer_id": printer.id,
+                                        "status": "offline",
+                                    }));
+                                }
+                                last_status.insert(printer.id.clone(), "offline".to_string());
                             }
+                            // A fault still there once the printer answers again is alerted anew
+                            last_fault.remove(&printer.id);
+                        } else {
+                            debug!(
+                                "Printer {} poll failed ({}/2 before offline): {}",
+                                printer.id, count, e
+                            );
                         }
                     }
-                });
+                }
+            }
+
+            if let Some(restaurant_id) = &restaurant_id {
+                sync_incidents(&queue_manager, &client, restaurant_id).await;
             }
         }
     });
 }
 
-/// Try printing on the specified printer with circuit breaker protection.
-/// On failure, attempts backup printers from the failover map.
-/// Returns the printer_id that successfully printed.
-async fn try_print_with_failover(
+/// Feed a status check into the incident tracker (`incidents.rs`): `down_cause` is None
+/// when the printer answered healthy. Opens an incident once the printer has been down
+/// for `after_secs`, closes it when it answers again.
+async fn track_incident(
+    tracker: &mut incidents::IncidentTracker,
+    queue_manager: &QueueManager,
     printer_id: &str,
-    job: &queue::PrintJob,
-    printer_manager: &Arc<Mutex<PrinterManager>>,
-    circuit_breakers: &Arc<CircuitBreakerRegistry>,
-    failover_map: &Arc<Mutex<std::collections::HashMap<String, Vec<String>>>>,
-    telemetry: &Arc<TelemetryCollector>,
-) -> errors::Result<String> {
-    // 1. Try primary printer
-    let primary_result = try_print_single(printer_id, job, printer_manager, circuit_breakers).await;
-    if primary_result.is_ok() {
-        return primary_result;
+    down_cause: Option<String>,
+    after_secs: i64,
+) {
+    let now = chrono::Utc::now().timestamp();
+    match down_cause {
+        Some(cause) => {
+            let Some((started_at, cause)) = tracker.down(printer_id, &cause, now, after_secs) else {
+                return;
+            };
+            // Not recorded: tried again on the next check
+            match queue_manager.open_incident(printer_id, started_at, &cause).await {
+                Ok(id) => {
+                    warn!("Printer {} down for {}s ({}), incident {} opened", printer_id, now - started_at, cause, id);
+                    tracker.opened(printer_id, id);
+                }
+                Err(e) => warn!("Failed to open incident for printer {}: {}", printer_id, e),
+            }
+        }
+        None => {
+            if let Some(id) = tracker.up(printer_id) {
+                match queue_manager.close_incident(id, now).await {
+                    Ok(()) => info!("Printer {} is back, incident {} closed", printer_id, id),
+                    Err(e) => warn!("Failed to close incident {} of printer {}: {}", id, printer_id, e),
+                }
+            }
+        }
     }
-    let primary_err = primary_result.unwrap_err();
+}
 
-    // 2. Look up backup printers
-    let backups = {
-        let map = failover_map.lock().await;
-        map.get(printer_id).cloned().unwrap_or_default()
+/// Send incidents opened or closed since the last sync to Supabase; on failure they go
+/// with the next status poll
+async fn sync_incidents(queue_manager: &QueueManager, client: &SupabaseClient, restaurant_id: &str) {
+    let unsynced = match queue_manager.unsynced_incidents().await {
+        Ok(unsynced) if !unsynced.is_empty() => unsynced,
+        Ok(_) => return,
+        Err(e) => {
+            warn!("Failed to read unsynced incidents: {}", e);
+            return;
+        }
     };
-
-    if backups.is_empty() {
-        warn!(
-            "Printer {} failed for job {} with no backups configured: {}",
-            printer_id, job.id, primary_err
-        );
-        return Err(primary_err);
+    let (incidents, revisions): (Vec<_>, Vec<_>) = unsynced
+        .into_iter()
+        .map(|(incident, revision)| {
+            let id = incident.id;
+            (incident, (id, revision))
+        })
+        .unzip();
+    match client.report_printer_incidents(restaurant_id, &incidents).await {
+        Ok(()) => {
+            if let Err(e) = queue_manager.mark_incidents_synced(&revisions).await {
+                warn!("Failed to mark incidents synced: {}", e);
+            }
+        }
+        Err(e) => debug!("Incident sync failed: {}", e),
     }
+}
 
-    // 3. Try each backup in order
+/// Send the recovery commands and report whether the fault cleared
+async fn attempt_mechanical_recovery(
+    printer: &config::PrinterConfig,
+    fault: jam_recovery::MechanicalFault,
+    automatic: bool,
+    printer_manager: &PrinterManager,
+) -> jam_recovery::RecoveryAttempt {
+    let status = match printer_manager.attempt_error_recovery(printer).await {
+        Ok(status) => Some(status),
+        Err(e) => {
+            warn!("Error recovery on printer {} failed: {}", printer.id, e);
+            None
+        }
+    };
+    let recovered = status
+        .as_ref()
+        .is_some_and(|s| jam_recovery::MechanicalFault::from_status(s).is_none());
     info!(
-        "Primary printer {} failed, attempting {} backup(s) for job {}",
-        printer_id,
-        backups.len(),
-        job.id
+        "Error recovery on printer {} ({}, {}): {}",
+        printer.id,
+        fault.as_str(),
+        if automatic { "automatic" } else { "requested" },
+        if recovered { "cleared" } else { "still faulted" }
     );
+    jam_recovery::RecoveryAttempt {
+        printer_id: printer.id.clone(),
+        fault: Some(fault),
+        recovered,
+        status: status.map(|s| s.to_status_string().to_string()),
+        automatic,
+    }
+}
 
-    let mut last_err = primary_err;
-    for backup_id in &backups {
-        info!("Trying backup printer {} for job {}", backup_id, job.id);
-        match try_print_single(backup_id, job, printer_manager, circuit_breakers).await {
-            Ok(used_id) => {
-                warn!(
-                    "Job {} printed via failover: {} → {}",
-                    job.id, printer_id, used_id
-                );
-                telemetry.record_event(telemetry::TelemetryEvent::FailoverAttempted {
-                    job_id: job.id.clone(),
-                    primary_printer_id: printer_id.to_string(),
-                    backup_printer_id: used_id.clone(),
-                    success: true,
-                }).await;
-                return Ok(used_id);
-            }
-            Err(e) => {
-                warn!("Backup printer {} also failed for job {}: {}", backup_id, job.id, e);
-                telemetry.record_event(telemetry::TelemetryEvent::FailoverAttempted {
-                    job_id: job.id.clone(),
-                    primary_printer_id: printer_id.to_string(),
-                    backup_printer_id: backup_id.clone(),
-                    success: false,
-                }).await;
-                last_err = e;
-            }
+/// A printer just reported a cutter / mechanical fault: try to clear it once (per
+/// cooldown), record it, and if it is still there walk staff through clearing it
+#[allow(clippy::too_many_arguments)]
+async fn handle_mechanical_fault(
+    printer: &config::PrinterConfig,
+    fault: jam_recovery::MechanicalFault,
+    locale: Locale,
+    auto_attempts: &mut jam_recovery::AutoAttempts,
+    printer_manager: &PrinterManager,
+    queue_manager: &QueueManager,
+    app_handle: &Arc<Mutex<Option<tauri::AppHandle>>>,
+    notifier: &Notifier,
+) {
+    let attempt = if auto_attempts.try_start(&printer.id, chrono::Utc::now().timestamp()) {
+        Some(attempt_mechanical_recovery(printer, fault, true, printer_manager).await)
+    } else {
+        None
+    };
+    let recovered = attempt.as_ref().is_some_and(|a| a.recovered);
+
+    let occurrences = queue_manager
+        .record_mechanical_fault(&printer.id, fault, recovered)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Failed to record mechanical fault on {}: {}", printer.id, e);
+            1
+        });
+
+    if let Some(ref handle) = *app_handle.lock().await {
+        if let Some(attempt) = &attempt {
+            let _ = handle.emit("printer-recovery-attempted", attempt);
         }
     }
+    if recovered {
+        return;
+    }
 
-    // 4. All printers failed
-    error!(
-        "All printers failed for job {} (primary: {}, backups: {:?})",
-        job.id, printer_id, backups
-    );
-    Err(last_err)
+    let guide = jam_recovery::RecoveryGuide {
+        printer_id: printer.id.clone(),
+        printer_name: printer.name.clone(),
+        fault,
+        steps: jam_recovery::guide_steps(fault, locale),
+        occurrences,
+        recurring: occurrences >= jam_recovery::RECURRING_THRESHOLD,
+    };
+    if let Some(ref handle) = *app_handle.lock().await {
+        let _ = handle.emit("printer-recovery-guide", &guide);
+    }
+
+    let what = match fault {
+        jam_recovery::MechanicalFault::Cutter => MessageKey::NotifyCutterJammed,
+        jam_recovery::MechanicalFault::Unrecoverable => MessageKey::NotifyMechanicalError,
+    };
+    let params = [
+        ("printer", printer.name.clone()),
+        ("fault", LocalizedMessage::new(locale, what, &[]).message),
+        ("count", occurrences.to_string()),
+    ];
+    let (title, body) = if guide.recurring {
+        (MessageKey::NotifyMechanicalFaultRecurringTitle, MessageKey::NotifyMechanicalFaultRecurringBody)
+    } else {
+        (MessageKey::NotifyMechanicalFaultTitle, MessageKey::NotifyMechanicalFaultBody)
+    };
+    notifier
+        .notify(
+            AlertKind::MechanicalFault,
+            Some(&printer.id),
+            LocalizedMessage::new(locale, title, &params).message,
+            LocalizedMessage::new(locale, body, &[]).message,
+        )
+        .await;
 }
 
-/// Try printing on a single printer with circuit breaker protection.
-async fn try_print_single(
-    printer_id: &str,
-    job: &queue::PrintJob,
-    printer_manager: &Arc<Mutex<PrinterManager>>,
-    circuit_breakers: &Arc<CircuitBreakerRegistry>,
-) -> errors::Result<String> {
-    let breaker = circuit_breakers.get_breaker(printer_id).await;
-    let pm = printer_manager.clone();
-    let pid = printer_id.to_string();
-    let job_clone = job.clone();
+/// Warn once per token when the pairing token is about to expire (checked hourly).
+/// An expired token means polls fail and no tickets print until the daemon is re-paired.
+fn start_token_expiry_watch(config: Arc<Mutex<AppConfig>>, notifier: Arc<Notifier>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
+        let mut warned_for: Option<i64> = None;
 
-    let result = breaker.execute(|| {
-        let pm = pm.clone();
-        let pid = pid.clone();
-        let job_clone = job_clone.clone();
-        async move {
-            let manager = pm.lock().await;
-            manager.print_to_printer(&pid, &job_clone).await
+        loop {
+            interval.tick().await;
+
+            let (token, locale) = {
+                let config = config.lock().await;
+                (config.auth_token.clone(), config.locale)
+            };
+            let Some(expires_at) = token.as_deref().and_then(notifications::token_expiry) else {
+                continue;
+            };
+            let remaining = expires_at - chrono::Utc::now().timestamp();
+            if remaining > notifications::TOKEN_WARNING_SECS || warned_for == Some(expires_at) {
+                continue;
+            }
+
+            warned_for = Some(expires_at);
+            let body = if remaining <= 0 {
+                LocalizedMessage::new(locale, MessageKey::NotifyTokenExpiredBody, &[])
+            } else {
+                LocalizedMessage::new(locale, MessageKey::NotifyTokenExpiringBody, &[("hours", (remaining / 3600).to_string())])
+            };
+            let title = LocalizedMessage::new(locale, MessageKey::NotifyTokenExpiringTitle, &[]);
+            notifier.notify(AlertKind::TokenExpiring, None, title.message, body.message).await;
         }
-    }).await;
+    });
+}
 
-    match result {
-        Ok(_) => Ok(printer_id.to_string()),
-        Err(e) => {
-            warn!("Printer {} failed for job {}: {}", printer_id, job.id, e);
-            Err(e)
+/// Sample the daemon's own CPU, memory, descriptors and tasks every minute into telemetry.
+/// The latest sample also rides along with every poll heartbeat (`resources::latest`).
+fn start_resource_monitor(telemetry: Arc<TelemetryCollector>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(resources::SAMPLE_SECS));
+        let mut monitor = resources::ResourceMonitor::default();
+        loop {
+            interval.tick().await;
+            let _tick = task_metrics::tick("resource_monitor");
+            let now = chrono::Utc::now().timestamp();
+            let usage = monitor.sample(now);
+            telemetry.record_event(telemetry::TelemetryEvent::ResourceUsage(usage)).await;
+            restarts::touch(now);
         }
-    }
+    });
 }
 
-/// Register printers in Supabase on startup (upsert once, retry until success).
-///
-/// Heartbeat updates are now piggybacked on poll-jobs calls (Wave B),
-/// so this function only needs to run once to register printer records.
-/// Retries every 60s until successful, then stops.
-async fn start_printer_registration(
-    config: Arc<Mutex<AppConfig>>,
-    telemetry: Arc<TelemetryCollector>,
-) {
-    info!("Starting printer registration (one-time upsert with retry)");
+/// Sample machine CPU use and suggest low-resource mode on the dashboard
+/// (`low-resource-suggested`) when it stays high for 5 minutes. Nothing is switched
+/// automatically; staff turn it on with `set_low_resource_mode`.
+fn start_load_monitor(app_handle: Arc<Mutex<Option<tauri::AppHandle>>>) {
+    let Some(mut prev) = low_resource::read_cpu_times() else {
+        info!("CPU load monitor unavailable on this platform");
+        return;
+    };
 
     tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(tokio::time::Duration::from_secs(low_resource::CPU_SAMPLE_SECS));
+        let mut load = low_resource::SustainedLoad::new(low_resource::SUSTAINED_SAMPLES);
+
         loop {
-            let cfg = config.lock().await;
-            let restaurant_id = match &cfg.restaurant_id {
-                Some(id) => id.clone(),
-                None => {
-                    drop(cfg);
-                    // No restaurant configured yet — wait and retry
-                    tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
-                    continue;
-                }
+            interval.tick().await;
+            let Some(now) = low_resource::read_cpu_times() else {
+                continue;
             };
-            let supabase_url = cfg.supabase_url.clone();
-            let anon_key = cfg.supabase_anon_key.clone();
-            let auth_token = cfg.auth_token.clone();
-            let printer_configs = cfg.printers.clone();
-            drop(cfg);
-
-            if printer_configs.is_empty() {
-                debug!("No printers configured, skipping registration");
-                tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+            let percent = low_resource::cpu_percent(prev, now);
+            prev = now;
+            let Some(average) = percent.and_then(|p| load.push(p, low_resource::HIGH_CPU_PERCENT)) else {
+                continue;
+            };
+            if low_resource::is_enabled() {
                 continue;
             }
 
-            if auth_token.is_none() {
-                warn!("No auth_token configured, skipping registration");
-                tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
-                continue;
+            warn!("CPU use averaged {:.0}% over 5 minutes, suggesting low-resource mode", average);
+            let suggestion = low_resource::LoadSuggestion {
+                cpu_percent: average,
+                window_secs: low_resource::CPU_SAMPLE_SECS * low_resource::SUSTAINED_SAMPLES as u64,
+            };
+            if let Some(ref handle) = *app_handle.lock().await {
+                let _ = handle.emit("low-resource-suggested", &suggestion);
             }
+        }
+    });
+}
 
-            let client = SupabaseClient::new(supabase_url, anon_key, auth_token);
+/// Check the paper width of printers not checked yet, once they answer a status poll:
+/// ask for the model (GS I), correct the configured width when the model is known and
+/// disagrees, and tell the dashboard (`paper-width-detected`). Unknown models are left
+/// to staff, who print the width ruler and pick the width.
+fn start_paper_width_check(
+    config: Arc<Mutex<AppConfig>>,
+    printer_manager: PrinterManager,
+    queue_manager: QueueManager,
+    app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            let _tick = task_metrics::tick("paper_width_check");
 
-            let now = chrono::Utc::now().to_rfc3339();
-            let printers_to_upsert: Vec<supabase_client::PrinterUpsert> = printer_configs
+            let unchecked: Vec<config::PrinterConfig> = config
+                .lock()
+                .await
+                .printers
                 .iter()
-                .map(|p| {
-                    let conn_type = match p.connection_type {
-                        config::ConnectionType::USB => "usb",
-                        config::ConnectionType::Network => "network",
-                        config::ConnectionType::Bluetooth => "bluetooth",
-                    };
-                    supabase_client::PrinterUpsert {
-                        id: p.id.clone(),
-                        restaurant_id: restaurant_id.clone(),
-                        name: p.name.clone(),
-                        connection_type: conn_type.to_string(),
-                        address: p.address.clone(),
-                        protocol: p.protocol.clone(),
-                        capabilities: serde_json::to_value(&p.capabilities).unwrap_or_default(),
-                        status: "online".to_string(),
-                        last_seen: now.clone(),
-                    }
-                })
+                .filter(|p| !p.paper_width_checked)
+                .cloned()
                 .collect();
 
-            let printer_count = printers_to_upsert.len();
-            match client.upsert_printers(printers_to_upsert).await {
-                Ok(_) => {
-                    info!("Registered {} printers in Supabase (one-time)", printer_count);
-                    telemetry.update_printer_counts(printer_count, 0).await;
-                    // Success — stop retrying. Heartbeats are now handled by poll-jobs piggyback.
-                    break;
+            for printer in unchecked {
+                let model = {
+                    let pm = &printer_manager;
+                    if pm.poll_status(&printer).await.is_err() {
+                        // Not connected yet, try again next round
+                        continue;
+                    }
+                    pm.query_model(&printer).await.unwrap_or_else(|e| {
+                        debug!("Printer {} did not report its model: {}", printer.id, e);
+                        None
+                    })
+                };
+                let detection = paper_detect::detect(&printer, model);
+                info!(
+                    "Paper width check for {}: model {:?}, detected {:?}mm, configured {}mm",
+                    printer.id, detection.model, detection.width_mm, detection.configured_mm
+                );
+
+                {
+                    let mut cfg = config.lock().await;
+                    if let Some(p) = cfg.printers.iter_mut().find(|p| p.id == printer.id) {
+                        p.paper_width_checked = true;
+                        if let Some(mm) = detection.width_mm.filter(|_| detection.corrected) {
+                            p.capabilities.max_width = if mm == 58 {
+                                escpos::PaperWidth::Width58mm as u16
+                            } else {
+                                escpos::PaperWidth::Width80mm as u16
+                            };
+                        }
+                        printer_manager.add_printer(p.clone()).await;
+                    }
+                    persist_config(&cfg, &app_handle).await;
                 }
-                Err(e) => {
-                    warn!("Failed to register printers: {}. Retrying in 60s...", e);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+
+                if detection.corrected {
+                    let queue = &queue_manager;
+                    if let Err(e) = queue
+                        .record_audit(
+                            "paper_width_corrected",
+                            &printer.id,
+                            serde_json::json!({
+                                "model": detection.model,
+                                "old_width_mm": detection.configured_mm,
+                                "new_width_mm": detection.width_mm,
+                            }),
+                        )
+                        .await
+                    {
+                        warn!("Failed to record paper width change for {}: {}", printer.id, e);
+                    }
+                }
+
+                if let Some(ref handle) = *app_handle.lock().await {
+                    let _ = handle.emit("paper-width-detected", &detection);
                 }
             }
         }
     });
 }
 
-/// Background task: Poll printer hardware status via DLE EOT every 30 seconds.
-///
-/// For each configured printer, sends DLE EOT commands to read paper/cover/error state.
-/// On status change: updates Supabase + emits Tauri event for the frontend.
-/// Requires 2 consecutive poll failures before marking offline (prevents flapping).
-async fn start_status_poller(
+/// Re-probe quarantined printers (DLE EOT status poll) whenever their probe is due.
+/// A printer returns to rotation after several consecutive "online" probes.
+fn start_quarantine_prober(
     config: Arc<Mutex<AppConfig>>,
-    printer_manager: Arc<Mutex<PrinterManager>>,
-    app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+    printer_manager: PrinterManager,
     circuit_breakers: Arc<CircuitBreakerRegistry>,
     telemetry: Arc<TelemetryCollector>,
 ) {
-    info!("Starting DLE EOT hardware status poller (30s interval)");
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            let _tick = task_metrics::tick("quarantine_prober");
+
+            for printer_id in circuit_breakers.quarantine.due_for_probe().await {
+                let printer = config.lock().await.printers.iter().find(|p| p.id == printer_id).cloned();
+                let Some(printer) = printer else {
+                    // Removed while quarantined
+                    circuit_breakers.quarantine.release(&printer_id).await;
+                    continue;
+                };
+
+                let online = {
+                    let pm = &printer_manager;
+                    matches!(pm.poll_status(&printer).await, Ok(s) if s.to_status_string() == "online")
+                };
+                match circuit_breakers.quarantine.record_probe(&printer_id, online).await {
+                    ProbeOutcome::Released => {
+                        circuit_breakers.release(&printer_id, "probes_passed", &telemetry).await;
+                    }
+                    ProbeOutcome::Passing { ok, needed } => {
+                        info!("Quarantined printer {} probe OK ({}/{})", printer_id, ok, needed);
+                    }
+                    ProbeOutcome::Failed => {
+                        debug!("Quarantined printer {} still failing its probe", printer_id);
+                    }
+                    ProbeOutcome::NotQuarantined => {}
+                }
+            }
+        }
+    });
+}
 
+/// Failed jobs younger than this are retried once a moved printer is found again
+const REQUEUE_WINDOW_SECS: u64 = 3600;
+
+/// Handle address requests from the circuit breaker registry.
+///
+/// Learn: store the MAC of a working network printer (ARP cache) so it can be found later.
+/// Rediscover: sweep the subnet for the printer by MAC (or model), then update + persist
+/// its address, write an audit entry, reset its breaker and requeue its recent failed jobs.
+fn start_address_recovery(
+    mut requests: tokio::sync::mpsc::UnboundedReceiver<AddressRequest>,
+    config: Arc<Mutex<AppConfig>>,
+    printer_manager: PrinterManager,
+    queue_manager: QueueManager,
+    circuit_breakers: Arc<CircuitBreakerRegistry>,
+    telemetry: Arc<TelemetryCollector>,
+    app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+) {
     tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
-        // Track last known status per printer for change detection
-        let mut last_status: std::collections::HashMap<String, String> = std::collections::HashMap::new();
-        // Track consecutive poll failures per printer (2 required before offline)
-        let mut poll_failures: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
+        while let Some(request) = requests.recv().await {
+            match request {
+                AddressRequest::Learn(printer_id) => {
+                    let printer = {
+                        let cfg = config.lock().await;
+                        cfg.printers.iter().find(|p| p.id == printer_id).cloned()
+                    };
+                    let Some(printer) = printer else { continue };
+                    if printer.mac_address.is_some()
+                        || !matches!(printer.connection_type, config::ConnectionType::Network)
+                    {
+                        continue;
+                    }
+
+                    if let Some(mac) = readdress::lookup_mac(&printer.address).await {
+                        info!("Learned MAC {} for printer {}", mac, printer_id);
+                        let mut cfg = config.lock().await;
+                        if let Some(p) = cfg.printers.iter_mut().find(|p| p.id == printer_id) {
+                            p.mac_address = Some(mac);
+                            printer_manager.add_printer(p.clone()).await;
+                        }
+                        persist_config(&cfg, &app_handle).await;
+                    }
+                }
+                AddressRequest::Rediscover(printer_id) => {
+                    let (printer, taken) = {
+                        let cfg = config.lock().await;
+                        let printer = cfg.printers.iter().find(|p| p.id == printer_id).cloned();
+                        let taken: Vec<String> = cfg
+                            .printers
+                            .iter()
+                            .filter(|p| p.id != printer_id)
+                            .map(|p| p.address.clone())
+                            .collect();
+                        (printer, taken)
+                    };
+                    let Some(printer) = printer else { continue };
+
+                    let Some((new_address, method)) = readdress::rediscover(&printer, &taken).await else {
+                        warn!("Printer {} not found on the network under a new address", printer_id);
+                        continue;
+                    };
+                    let old_address = printer.address.clone();
+                    let mac = match &printer.mac_address {
+                        Some(mac) => Some(mac.clone()),
+                        None => readdress::lookup_mac(&new_address).await,
+                    };
+
+                    {
+                        let mut cfg = config.lock().await;
+                        if let Some(p) = cfg.printers.iter_mut().find(|p| p.id == printer_id) {
+                            p.address = new_address.clone();
+                            p.mac_address = mac.clone();
+                            printer_manager.add_printer(p.clone()).await;
+                        }
+                        persist_config(&cfg, &app_handle).await;
+                    }
 
-        loop {
-            interval.tick().await;
+                    circuit_breakers.get_breaker(&printer_id).await.reset("address_changed").await;
+                    circuit_breakers.anomaly.reset(&printer_id).await;
 
-            let cfg = config.lock().await;
-            let auth_token = cfg.auth_token.clone();
-            let supabase_url = cfg.supabase_url.clone();
-            let anon_key = cfg.supabase_anon_key.clone();
-            let printer_configs = cfg.printers.clone();
-            drop(cfg);
+                    telemetry.record_event(telemetry::TelemetryEvent::PrinterAddressChanged {
+                        printer_id: printer_id.clone(),
+                        old_address: old_address.clone(),
+                        new_address: new_address.clone(),
+                        method: method.as_str().to_string(),
+                    }).await;
 
-            if printer_configs.is_empty() || auth_token.is_none() {
-                continue;
-            }
+                    let queue = &queue_manager;
+                    if let Err(e) = queue
+                        .record_audit(
+                            "printer_address_changed",
+                            &printer_id,
+                            serde_json::json!({
+                                "old_address": old_address,
+                                "new_address": new_address,
+                                "mac_address": mac,
+                                "method": method.as_str(),
+                            }),
+                        )
+                        .await
+                    {
+                        warn!("Failed to record address change for {}: {}", printer_id, e);
+                    }
+                    let requeued = match queue.requeue_failed_for_printer(&printer_id, REQUEUE_WINDOW_SECS).await {
+                        Ok(ids) => ids,
+                        Err(e) => {
+                            error!("Failed to requeue jobs for printer {}: {}", printer_id, e);
+                            Vec::new()
+                        }
+                    };
 
-            let client = SupabaseClient::new(supabase_url, anon_key, auth_token);
+                    if !requeued.is_empty() {
+                        info!("Requeued {} failed jobs for printer {}", requeued.len(), printer_id);
+                        let client = create_supabase_client_from_config(&*config.lock().await);
+                        if let Some(client) = client {
+                            for job_id in &requeued {
+                                let _ = client.update_job_status(job_id, status::PENDING, None, None, None).await;
+                            }
+                        }
+                    }
 
-            for printer in &printer_configs {
-                // Briefly lock PrinterManager for each poll, then release
-                let poll_result = {
-                    let pm = printer_manager.lock().await;
-                    pm.poll_status(printer).await
-                };
+                    if let Some(ref handle) = *app_handle.lock().await {
+                        let _ = handle.emit("printer-address-changed", serde_json::json!({
+                            "printer_id": printer_id,
+                            "old_address": old_address,
+                            "new_address": new_address,
+                            "method": method.as_str(),
+                            "requeued_jobs": requeued.len(),
+                        }));
+                    }
+                }
+            }
+        }
+    });
+}
 
-                match poll_result {
-                    Ok(hw_status) => {
-                        // Reset failure counter on successful poll
-                        poll_failures.remove(&printer.id);
+/// Operator notes on a job, for the job log sent to Supabase
+async fn note_texts(queue: &QueueManager, job_id: &str) -> Vec<String> {
+    match queue.job_notes(job_id).await {
+        Ok(notes) => notes.into_iter().map(|n| n.note).collect(),
+        Err(e) => {
+            warn!("Failed to read notes for job {}: {}", job_id, e);
+            Vec::new()
+        }
+    }
+}
 
-                        let new_status = hw_status.to_status_string().to_string();
-                        let prev_status = last_status.get(&printer.id);
+/// Make a loaded config the running one: auth token from the keychain, printers,
+/// stations, receipt settings and the local API
+async fn apply_stored_config(
+    config_arc: Arc<Mutex<AppConfig>>,
+    pm_arc: PrinterManager,
+    queue_arc: QueueManager,
+    api_server: Arc<api::ApiServer>,
+    loaded: AppConfig,
+) {
+    let mut config = config_arc.lock().await;
+    // Load auth_token from keyring if not in config
+    if loaded.auth_token.is_none() {
+        if let Some(token) = config::load_auth_token() {
+            let mut loaded_with_token = loaded.clone();
+            loaded_with_token.auth_token = Some(token);
+            *config = loaded_with_token;
+        } else {
+            *config = loaded.clone();
+        }
+    } else {
+        *config = loaded.clone();
+    }
+    drop(config);
 
-                        if prev_status.map_or(true, |prev| prev != &new_status) {
-                            let old_str = prev_status.unwrap_or(&"unknown".to_string()).clone();
-                            info!(
-                                "Printer {} status changed: {} → {}",
-                                printer.id, old_str, new_status
-                            );
+    telemetry::set_mode(loaded.telemetry_mode);
+    venue_time::set(loaded.timezone.as_deref());
+    if loaded.telemetry_mode != telemetry::TelemetryMode::Full {
+        info!("Telemetry mode: {}", loaded.telemetry_mode.as_str());
+    }
 
-                            // Emit telemetry for status transition
-                            telemetry.record_event(telemetry::TelemetryEvent::PrinterStatusChanged {
-                                printer_id: printer.id.clone(),
-                                old_status: old_str,
-                                new_status: new_status.clone(),
-                            }).await;
+    pm_arc.sync_printers(&loaded.printers).await;
+    pm_arc.set_item_images(loaded.item_images.clone()).await;
+    pm_arc.set_receipt_fields(loaded.receipt_fields.clone()).await;
+    pm_arc.set_receipt_templates(&loaded.receipt_templates).await;
+    pm_arc.set_kitchen_logo(&loaded.test_print).await;
+    spawn_logo_download(&loaded.test_print);
+    pm_arc.set_write_timeouts(loaded.write_timeouts);
+    pm_arc.set_units(loaded.unit_format());
+
+    queue_arc.set_stations(StationMap::new(&loaded.printers, &loaded.station_aliases));
+    queue_arc.set_pull_stations(&loaded.pull_stations);
+    queue_arc.set_paused_printers(&loaded.paused_printers()).await;
+    queue_arc.set_ingest_dedup(&loaded.ingest_dedup);
+    for conflict in address_conflicts::find(&loaded.printers) {
+        warn!(
+            "Printers {} share address {}: merge them or change one in Settings",
+            conflict.printer_ids.join(", "),
+            conflict.address
+        );
+    }
 
-                            // Reset circuit breaker on recovery so jobs flow immediately
-                            if new_status == "online" {
-                                let breaker = circuit_breakers.get_breaker(&printer.id).await;
-                                breaker.reset().await;
-                                info!("Printer {} recovered — circuit breaker reset", printer.id);
-                            }
+    api_server.sync(loaded.restaurant_id.as_deref()).await;
 
-                            // Update Supabase with detailed status (outside PM lock)
-                            if let Err(e) = client.update_printer_status_detailed(
-                                &printer.id,
-                                &new_status,
-                                &hw_status,
-                            ).await {
-                                warn!("Failed to update printer {} status in Supabase: {}", printer.id, e);
-                            }
+    info!("Stored config applied: {} printers registered", loaded.printers.len());
+}
 
-                            // Emit Tauri event for frontend
-                            if let Some(ref handle) = *app_handle.lock().await {
-                                let _ = handle.emit("printer-hw-status", serde_json::json!({
-                                    "printer_id": printer.id,
-                                    "status": new_status,
-                                    "hw_status": hw_status,
-                                }));
-                            }
+/// Write the config to the Tauri store from a background task (no-op before setup ran)
+async fn persist_config(config: &AppConfig, app_handle: &Arc<Mutex<Option<tauri::AppHandle>>>) {
+    let Some(handle) = app_handle.lock().await.clone() else {
+        warn!("App not ready, address change kept in memory only");
+        return;
+    };
 
-                            last_status.insert(printer.id.clone(), new_status);
-                        }
-                    }
-                    Err(e) => {
-                        let count = poll_failures.entry(printer.id.clone()).or_insert(0);
-                        *count += 1;
+    // auth_token lives in the keychain
+    let mut config_for_store = config.clone();
+    config_for_store.auth_token = None;
+    let result = handle.store("config.json").map_err(|e| e.to_string()).and_then(|store| {
+        store.set("config", config_seal::seal(&config_for_store).map_err(|e| e.to_string())?);
+        store.save().map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        warn!("Failed to persist config: {}", e);
+    }
+}
 
-                        if *count >= 2 {
-                            // 2 consecutive failures → consider offline
-                            let prev_status = last_status.get(&printer.id);
-                            if prev_status.map_or(true, |s| s != "offline") {
-                                let old_str = prev_status.cloned().unwrap_or_else(|| "unknown".to_string());
-                                warn!(
-                                    "Printer {} unreachable after {} consecutive poll failures: {}",
-                                    printer.id, count, e
-                                );
-                                telemetry.record_event(telemetry::TelemetryEvent::PrinterStatusChanged {
-                                    printer_id: printer.id.clone(),
-                                    old_status: old_str,
-                                    new_status: "offline".to_string(),
-                                }).await;
-                                if let Err(e) = client.update_printer_status(&printer.id, "offline").await {
-                                    warn!("Failed to mark printer {} offline in Supabase: {}", printer.id, e);
-                                }
-                                if let Some(ref handle) = *app_handle.lock().await {
-                                    let _ = handle.emit("printer-hw-status", serde_json::json!({
-                                        "printer_id": printer.id,
-                                        "status": "offline",
-                                    }));
-                                }
-                                last_status.insert(printer.id.clone(), "offline".to_string());
-                            }
-                        } else {
-                            debug!(
-                                "Printer {} poll failed ({}/2 before offline): {}",
-                                printer.id, count, e
-                            );
-                        }
-                    }
-                }
+/// Watch the machine's local IP (every 30s) and drop printer caches when it changes:
+/// after a Wi-Fi switch or new DHCP lease, cached "online" results and pooled
+/// connections belong to the old network.
+fn start_network_watch(printer_manager: PrinterManager) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
+        let mut last_ip = local_ip_address::local_ip().ok();
+
+        loop {
+            low_resource::tick(&mut interval).await;
+            let _tick = task_metrics::tick("network_watch");
+            let ip = local_ip_address::local_ip().ok();
+            if ip != last_ip {
+                info!("Local network changed ({:?} -> {:?})", last_ip, ip);
+                last_ip = ip;
+                printer_manager.invalidate_all().await;
             }
         }
     });
@@ -1351,7 +4131,7 @@ async fn start_status_poller(
 
 /// Start periodic queue metrics snapshot (every 30s) with Tauri event push
 async fn start_queue_metrics(
-    queue_manager: Arc<Mutex<QueueManager>>,
+    queue_manager: QueueManager,
     telemetry: Arc<TelemetryCollector>,
     app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
 ) {
@@ -1359,18 +4139,35 @@ async fn start_queue_metrics(
 
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
+        let mut last_saturation = queue::Saturation::Normal;
 
         loop {
-            interval.tick().await;
+            low_resource::tick(&mut interval).await;
+            let _tick = task_metrics::tick("queue_metrics");
+
+            let queue = &queue_manager;
+
+            if let Ok(health) = queue.get_health().await {
+                if health.state != last_saturation {
+                    last_saturation = health.state;
+                    telemetry.record_event(telemetry::TelemetryEvent::QueueSaturationChanged {
+                        state: health.state.as_str().to_string(),
+                        pending: health.pending,
+                        oldest_pending_secs: health.oldest_pending_secs,
+                    }).await;
+
+                    if let Some(ref handle) = *app_handle.lock().await {
+                        let _ = handle.emit("queue-saturation-changed", &health);
+                    }
+                }
+            }
 
-            let queue = queue_manager.lock().await;
             if let Ok(stats) = queue.get_stats().await {
                 let pending = stats.get("pending").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
                 let processing = stats.get("printing").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
                 let completed = stats.get("completed").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
                 let failed = stats.get("failed").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
 
-                drop(queue);
 
                 telemetry.record_event(telemetry::TelemetryEvent::QueueSnapshot {
                     pending,
@@ -1384,12 +4181,18 @@ async fn start_queue_metrics(
                     let _ = handle.emit("queue-stats-updated", &stats);
                 }
             }
+
+            if let Ok(load) = queue.station_load().await {
+                if let Some(ref handle) = *app_handle.lock().await {
+                    let _ = handle.emit("station-load-updated", &load);
+                }
+            }
         }
     });
 }
 
 /// Start periodic cleanup task
-async fn start_cleanup_task(queue_manager: Arc<Mutex<QueueManager>>) {
+async fn start_cleanup_task(queue_manager: QueueManager) {
     info!("Starting periodic cleanup task (daily)");
 
     tokio::spawn(async move {
@@ -1397,9 +4200,10 @@ async fn start_cleanup_task(queue_manager: Arc<Mutex<QueueManager>>) {
 
         loop {
             interval.tick().await;
+            let _tick = task_metrics::tick("cleanup");
 
             info!("Running daily queue cleanup");
-            let queue = queue_manager.lock().await;
+            let queue = &queue_manager;
             if let Err(e) = queue.cleanup_old_jobs().await {
                 error!("Cleanup task failed: {}", e);
             }
@@ -1407,32 +4211,165 @@ async fn start_cleanup_task(queue_manager: Arc<Mutex<QueueManager>>) {
     });
 }
 
+/// Report jobs whose station matched no printer station or alias, so a typo upstream
+/// ("Keukn") or a new station shows up instead of silently printing on the primary.
+fn start_station_warnings(
+    mut unmatched_rx: tokio::sync::mpsc::UnboundedReceiver<stations::UnmatchedStation>,
+    telemetry: Arc<TelemetryCollector>,
+    app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+) {
+    tokio::spawn(async move {
+        while let Some(unmatched) = unmatched_rx.recv().await {
+            telemetry.record_event(telemetry::TelemetryEvent::StationUnmatched {
+                job_id: unmatched.job_id.clone(),
+                received: unmatched.received.clone(),
+                station: unmatched.station.clone(),
+            }).await;
+
+            if let Some(ref handle) = *app_handle.lock().await {
+                let _ = handle.emit("station-unmatched", &unmatched);
+            }
+        }
+    });
+}
+
+/// Count print attempts in the printers' lifetime stats (`printer_stats.rs`) and sync
+/// changed counters to the printers' Supabase rows every `SYNC_INTERVAL`. Counters are
+/// usage data: not synced unless telemetry mode is full.
+fn start_printer_stats(
+    mut stats_rx: tokio::sync::mpsc::UnboundedReceiver<printer_stats::Attempt>,
+    queue_manager: QueueManager,
+    config: Arc<Mutex<AppConfig>>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(printer_stats::SYNC_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                attempt = stats_rx.recv() => {
+                    let Some(attempt) = attempt else {
+                        break;
+                    };
+                    if let Err(e) = queue_manager.record_printer_attempt(&attempt).await {
+                        warn!("Failed to count print attempt for {}: {}", attempt.printer_id, e);
+                    }
+                }
+                _ = interval.tick() => {
+                    if !telemetry::mode().shares_usage() {
+                        continue;
+                    }
+                    let (client, restaurant_id) = {
+                        let config = config.lock().await;
+                        match (create_supabase_client_from_config(&config), config.restaurant_id.clone()) {
+                            (Some(client), Some(restaurant_id)) => (client, restaurant_id),
+                            _ => continue,
+                        }
+                    };
+                    let stats = match queue_manager.printer_stats(true).await {
+                        Ok(stats) if !stats.is_empty() => stats,
+                        Ok(_) => continue,
+                        Err(e) => {
+                            warn!("Failed to read printer stats: {}", e);
+                            continue;
+                        }
+                    };
+                    match client.report_printer_stats(&restaurant_id, &stats).await {
+                        Ok(()) => {
+                            if let Err(e) = queue_manager.mark_printer_stats_synced(&stats).await {
+                                warn!("Failed to mark printer stats synced: {}", e);
+                            }
+                        }
+                        // Kept unsynced: sent with the next sync
+                        Err(e) => debug!("Printer stats sync failed: {}", e),
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Persist breaker transitions and, once per business day, report how many minutes each
+/// printer's breaker spent open or half-open on the day that just ended.
+fn start_breaker_history(
+    mut history_rx: tokio::sync::mpsc::UnboundedReceiver<BreakerTransition>,
+    queue_manager: QueueManager,
+    telemetry: Arc<TelemetryCollector>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
+        let mut current_day = queue_manager.business_date();
+
+        loop {
+            tokio::select! {
+                transition = history_rx.recv() => {
+                    let Some(transition) = transition else {
+                        break;
+                    };
+                    if let Err(e) = queue_manager.record_breaker_transition(&transition).await {
+                        warn!("Failed to persist breaker transition for {}: {}", transition.printer_id, e);
+                    }
+                    telemetry.record_event(telemetry::TelemetryEvent::CircuitBreakerStateChanged {
+                        printer_id: transition.printer_id,
+                        old_state: transition.from.as_str().to_string(),
+                        new_state: transition.to.as_str().to_string(),
+                    }).await;
+                }
+                _ = interval.tick() => {
+                    let queue = &queue_manager;
+                    let today = queue.business_date();
+                    if today == current_day {
+                        continue;
+                    }
+                    let finished = std::mem::replace(&mut current_day, today);
+                    let days = match queue.degraded_minutes(&finished, None).await {
+                        Ok(days) => days,
+                        Err(e) => {
+                            warn!("Failed to roll up degraded minutes for {}: {}", finished, e);
+                            continue;
+                        }
+                    };
+
+                    for day in days.into_iter().filter(|d| d.business_date == finished) {
+                        telemetry.record_event(telemetry::TelemetryEvent::PrinterDegradedMinutes {
+                            printer_id: day.printer_id,
+                            business_date: day.business_date,
+                            minutes: day.minutes,
+                        }).await;
+                    }
+                }
+            }
+        }
+    });
+}
+
 // ============================================================================
 // Main Entry Point
 // ============================================================================
 
 #[tokio::main]
 async fn main() {
+    // Dev tool: render a job file as text and exit, before any daemon setup (`simulate.rs`)
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("simulate") {
+        std::process::exit(simulate::run(&args[2..]));
+    }
+
     // Initialize Sentry crash reporting FIRST (guard must outlive tracing)
     let _sentry_guard = sentry_init::init();
 
     // Initialize logging with file output for debugging
     // Logs go to: ~/Library/Logs/EatsomePrinterService/app.log (macOS)
-    let log_dir = dirs::home_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("Library")
-        .join("Logs")
-        .join("EatsomePrinterService");
+    let log_dir = config::log_dir();
 
     std::fs::create_dir_all(&log_dir).ok();
 
     let file_appender = tracing_appender::rolling::never(&log_dir, "app.log");
     let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
 
-    // Build tracing subscriber with file logging + Sentry integration
-    let env_filter = tracing_subscriber::EnvFilter::from_default_env()
-        .add_directive("eatsome_printer_daemon=debug".parse().unwrap())
-        .add_directive(tracing::Level::DEBUG.into());
+    // Build tracing subscriber with file logging + Sentry integration. The filter can be
+    // raised for a while from the backend (`log_level.rs`).
+    let (env_filter, log_filter) = tracing_subscriber::reload::Layer::new(log_level::baseline());
 
     let fmt_layer = tracing_subscriber::fmt::layer()
         .with_target(true)
@@ -1456,6 +4393,7 @@ async fn main() {
         .with(fmt_layer)
         .with(sentry_layer)
         .init();
+    log_level::install(log_filter);
 
     info!("========================================");
     info!("Eatsome Printer Service Starting...");
@@ -1494,8 +4432,11 @@ async fn main() {
     let encryption_key = config.restaurant_id.as_ref()
         .map(|id| QueueManager::derive_key(id, "eatsome-print-queue"));
 
+    let (unmatched_tx, unmatched_rx) = tokio::sync::mpsc::unbounded_channel();
     let queue_manager = match QueueManager::new(config.database_path(), encryption_key).await {
-        Ok(qm) => qm,
+        Ok(qm) => qm
+            .with_rollover_hour(config.business_day_rollover_hour)
+            .with_unmatched_stations(unmatched_tx),
         Err(e) => {
             error!("Failed to initialize queue manager: {}", e);
             error!("Cannot proceed without queue storage - exiting");
@@ -1504,6 +4445,18 @@ async fn main() {
     };
     info!("Database initialized at: {:?}", config.database_path());
 
+    // Restart history: close the previous run (crashed if it left its marker) and open this one
+    if let Some(dir) = config.database_path().parent() {
+        if let Err(e) = restarts::record_start(&queue_manager, dir, chrono::Utc::now().timestamp()).await {
+            warn!("Failed to record daemon start: {}", e);
+        }
+    }
+
+    low_resource::set_enabled(config.low_resource_mode);
+    if config.low_resource_mode {
+        info!("Low-resource mode on (concurrency {}, slower polling)", low_resource::LOW_CONCURRENCY);
+    }
+
     // Initialize telemetry
     let telemetry = Arc::new(TelemetryCollector::new());
 
@@ -1514,7 +4467,11 @@ async fn main() {
     let jwt_manager = Arc::new(JWTManager::new(jwt_secret));
 
     // Initialize circuit breaker registry with status propagation channel
-    let (cb_registry, mut status_rx) = CircuitBreakerRegistry::new();
+    let (address_monitor, address_rx) = AddressMonitor::new();
+    let (cb_registry, mut status_rx) = CircuitBreakerRegistry::new(address_monitor);
+    let (history_tx, history_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (stats_tx, stats_rx) = tokio::sync::mpsc::unbounded_channel();
+    let cb_registry = cb_registry.with_history(history_tx).with_stats(stats_tx);
     let circuit_breakers = Arc::new(cb_registry);
 
     // Initialize shutdown flag
@@ -1523,18 +4480,44 @@ async fn main() {
     // Create application state
     let failover_map = Arc::new(Mutex::new(std::collections::HashMap::new()));
     let shared_app_handle: Arc<Mutex<Option<tauri::AppHandle>>> = Arc::new(Mutex::new(None));
+    let shared_config = Arc::new(Mutex::new(config.clone()));
+    let start_time = Instant::now();
+    let notifier = Arc::new(Notifier::new(shared_config.clone(), shared_app_handle.clone()));
+    let job_cancel = JobCancellation::new();
+    let api_server = Arc::new(api::ApiServer::new(api::API_ADDR, api::ApiState {
+        config: shared_config.clone(),
+        queue_manager: queue_manager.clone(),
+        telemetry: telemetry.clone(),
+        jwt_manager: jwt_manager.clone(),
+        restaurant_id: String::new(),
+        supabase_connected: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        start_time,
+        nonces: Arc::new(std::sync::Mutex::new(request_signing::NonceCache::default())),
+        job_cancel: job_cancel.clone(),
+    }));
+    let recovery = Arc::new(RecoveryPrints::new(
+        queue_manager.clone(),
+        shared_config.clone(),
+        shared_app_handle.clone(),
+    ));
     let state = AppState {
-        config: Arc::new(Mutex::new(config.clone())),
-        printer_manager: Arc::new(Mutex::new(printer_manager)),
-        queue_manager: Arc::new(Mutex::new(queue_manager)),
+        config: shared_config,
+        printer_manager: printer_manager,
+        queue_manager,
         job_poller_handle: Arc::new(Mutex::new(None)),
         telemetry: telemetry.clone(),
         jwt_manager: jwt_manager.clone(),
         circuit_breakers: circuit_breakers.clone(),
-        start_time: Instant::now(),
+        start_time,
         shutdown_requested: shutdown_requested.clone(),
+        job_cancel,
         failover_map: failover_map.clone(),
         app_handle: shared_app_handle.clone(),
+        api_server,
+        notifier: notifier.clone(),
+        maintenance: Arc::new(Maintenance::default()),
+        recovery,
+        config_integrity: Arc::new(std::sync::Mutex::new(config_seal::ConfigIntegrity::default())),
     };
 
     // Start background tasks
@@ -1543,19 +4526,31 @@ async fn main() {
     let telemetry_clone = telemetry.clone();
     let breakers_clone = circuit_breakers.clone();
     let config_clone = state.config.clone();
-    let shutdown_clone = shutdown_requested.clone();
+    let cancel_clone = state.job_cancel.clone();
 
     let failover_clone = failover_map.clone();
     tokio::spawn(async move {
-        start_job_processor(queue_clone, printer_clone, telemetry_clone, breakers_clone, config_clone, shutdown_clone, failover_clone).await;
+        start_job_processor(queue_clone, printer_clone, telemetry_clone, breakers_clone, config_clone, cancel_clone, failover_clone).await;
     });
 
     // Start cleanup task
     start_cleanup_task(state.queue_manager.clone()).await;
 
+    // Persist breaker transitions + daily degraded-minutes rollup
+    start_breaker_history(history_rx, state.queue_manager.clone(), telemetry.clone());
+
+    // Lifetime per-printer counters, synced to the printers' Supabase rows
+    start_printer_stats(stats_rx, state.queue_manager.clone(), state.config.clone());
+
+    // Telemetry + `station-unmatched` event for jobs with an unknown station
+    start_station_warnings(unmatched_rx, telemetry.clone(), shared_app_handle.clone());
+
     // Start periodic queue metrics snapshot (app_handle set during Tauri .setup())
     start_queue_metrics(state.queue_manager.clone(), telemetry.clone(), shared_app_handle.clone()).await;
 
+    // Drop printer reachability caches when the machine moves networks
+    start_network_watch(state.printer_manager.clone());
+
     // Register printers in Supabase (one-time upsert, heartbeats piggybacked on polls)
     start_printer_registration(
         state.config.clone(),
@@ -1569,8 +4564,70 @@ async fn main() {
         shared_app_handle.clone(),
         circuit_breakers.clone(),
         telemetry.clone(),
+        state.notifier.clone(),
+        state.recovery.clone(),
+        state.queue_manager.clone(),
     ).await;
 
+    // Auto-resume when a maintenance window ends
+    start_maintenance_watch(state.maintenance.clone(), shared_app_handle.clone(), telemetry.clone());
+
+    // Daemon CPU / memory self-monitoring (telemetry + heartbeats)
+    start_resource_monitor(telemetry.clone());
+
+    // Suggest low-resource mode when the machine stays busy
+    start_load_monitor(shared_app_handle.clone());
+
+    // Warn before the pairing token expires
+    start_token_expiry_watch(state.config.clone(), state.notifier.clone());
+
+    // Paper width check on first connect
+    start_paper_width_check(
+        state.config.clone(),
+        state.printer_manager.clone(),
+        state.queue_manager.clone(),
+        shared_app_handle.clone(),
+    );
+
+    // Re-probe quarantined printers on a slow schedule until they earn their way back
+    start_quarantine_prober(
+        state.config.clone(),
+        state.printer_manager.clone(),
+        circuit_breakers.clone(),
+        telemetry.clone(),
+    );
+
+    // Start printer address recovery (learns MACs, follows printers that changed IP)
+    start_address_recovery(
+        address_rx,
+        state.config.clone(),
+        state.printer_manager.clone(),
+        state.queue_manager.clone(),
+        circuit_breakers.clone(),
+        telemetry.clone(),
+        shared_app_handle.clone(),
+    );
+
+    // Off-peak background rediscovery (disabled unless configured)
+    start_background_rediscovery(
+        state.config.clone(),
+        state.printer_manager.clone(),
+        state.queue_manager.clone(),
+        circuit_breakers.clone(),
+    );
+
+    // Morning printer self-test (disabled unless configured)
+    start_self_test_scheduler(
+        state.config.clone(),
+        state.printer_manager.clone(),
+        state.queue_manager.clone(),
+        notifier.clone(),
+        shared_app_handle.clone(),
+    );
+
+    // "Order ready" slips queued by the POS or by completed orders
+    start_pickup_slip_printer(state.queue_manager.clone(), state.printer_manager.clone(), state.config.clone());
+
     // Start TCP connection pool health checker (60s interval, 5min max idle)
     {
         let pm_for_pool = state.printer_manager.clone();
@@ -1579,9 +4636,7 @@ async fn main() {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
             loop {
                 interval.tick().await;
-                let pm = pm_for_pool.lock().await;
-                let (stale_removed, active) = pm.cleanup_stale_connections(300).await; // 5 minutes max idle
-                drop(pm);
+                let (stale_removed, active) = pm_for_pool.cleanup_stale_connections(300).await; // 5 minutes max idle
                 telem_for_pool.record_event(telemetry::TelemetryEvent::ConnectionPoolStats {
                     active_connections: active,
                     stale_removed,
@@ -1597,6 +4652,8 @@ async fn main() {
     // Start status propagation task: circuit breaker → Supabase → POS
     {
         let config_for_status = state.config.clone(); // Arc<Mutex<AppConfig>>, not default copy
+        let handle_for_status = shared_app_handle.clone();
+        let notifier = notifier.clone();
         tokio::spawn(async move {
             loop {
                 if status_rx.changed().await.is_err() {
@@ -1608,6 +4665,13 @@ async fn main() {
                 }
                 info!("Circuit breaker status change: printer {} → {}", printer_id, status);
 
+                if let Some(ref handle) = *handle_for_status.lock().await {
+                    let _ = handle.emit("printer-status-changed", serde_json::json!({
+                        "printer_id": printer_id,
+                        "status": status,
+                    }));
+                }
+
                 let cfg = config_for_status.lock().await;
                 let client = create_supabase_client_from_config(&cfg);
                 drop(cfg);
@@ -1617,28 +4681,45 @@ async fn main() {
                         warn!("Failed to propagate printer status to Supabase: {}", e);
                     }
                 }
+
+                if status == "error" {
+                    let (name, locale) = {
+                        let cfg = config_for_status.lock().await;
+                        let name = cfg.printers.iter()
+                            .find(|p| p.id == printer_id)
+                            .map(|p| p.name.clone())
+                            .unwrap_or_else(|| printer_id.clone());
+                        (name, cfg.locale)
+                    };
+                    notifier.notify(
+                        AlertKind::BreakerOpen,
+                        Some(&printer_id),
+                        LocalizedMessage::new(locale, MessageKey::NotifyBreakerOpenTitle, &[("printer", name)]).message,
+                        LocalizedMessage::new(locale, MessageKey::NotifyBreakerOpenBody, &[]).message,
+                    ).await;
+                }
             }
         });
     }
 
-    // Start HTTP API server (fallback)
-    if let Some(restaurant_id) = &config.restaurant_id {
-        let api_state = api::ApiState {
+    // HTTP API server (fallback): runs while paired, started/stopped by ApiServer::sync
+    // once the stored config is loaded and whenever the config is saved.
+    // Note: heartbeat is piggybacked on poll-jobs calls (no separate heartbeat task)
+    state.api_server.sync(config.restaurant_id.as_deref()).await;
+
+    // Start delivery platform webhook receiver (LAN, opt-in)
+    if config.webhooks.enabled {
+        let webhook_state = webhooks::WebhookState {
             queue_manager: state.queue_manager.clone(),
-            telemetry: telemetry.clone(),
-            jwt_manager: jwt_manager.clone(),
-            restaurant_id: restaurant_id.clone(),
-            supabase_connected: Arc::new(std::sync::atomic::AtomicBool::new(false)),
-            start_time: state.start_time,
+            config: state.config.clone(),
         };
+        let addr = config.webhooks.bind_address.clone();
 
         tokio::spawn(async move {
-            if let Err(e) = api::start_api_server("127.0.0.1:8043", api_state).await {
-                error!("Failed to start HTTP API server: {}", e);
+            if let Err(e) = webhooks::start_webhook_server(&addr, webhook_state).await {
+                error!("Failed to start webhook receiver on {}: {}", addr, e);
             }
         });
-
-        // Note: heartbeat is piggybacked on poll-jobs calls (no separate heartbeat task)
     }
 
     info!("Background services initialized");
@@ -1660,6 +4741,7 @@ async fn main() {
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             Some(vec![]),
         ))
+        .plugin(tauri_plugin_notification::init())
         .manage(state)
         .setup(|app| {
             // Set app_handle so background tasks can emit Tauri events
@@ -1676,61 +4758,46 @@ async fn main() {
             // Load config from store and apply to managed state
             let store = app.store("config.json")?;
             if let Some(stored_config) = store.get("config") {
-                match serde_json::from_value::<AppConfig>(stored_config.clone()) {
-                    Ok(loaded_config) => {
+                match config_seal::open(&stored_config) {
+                    config_seal::Loaded::Trusted(loaded_config) => {
                         info!("Config loaded from store (restaurant: {:?}, {} printers)",
                             loaded_config.restaurant_id, loaded_config.printers.len());
 
                         let state = app.state::<AppState>();
                         let config_arc = state.config.clone();
                         let pm_arc = state.printer_manager.clone();
+                        let queue_arc = state.queue_manager.clone();
+                        let api_server = state.api_server.clone();
                         let loaded = loaded_config.clone();
 
-                        // Keyring migration: move auth_token from config.json → OS keychain
+                        // Keyring migration: move auth_token from config.json → OS keychain.
+                        // Also seals a config.json written before sealing existed.
+                        let mut migrated = loaded_config.clone();
                         if let Some(ref token) = loaded_config.auth_token {
                             match config::store_auth_token(token) {
                                 Ok(_) => {
                                     info!("Migrated auth_token to OS keychain");
                                     // Clear token from config.json store
-                                    let mut migrated = loaded_config.clone();
                                     migrated.auth_token = None;
-                                    if let Ok(val) = serde_json::to_value(&migrated) {
-                                        store.set("config", val);
-                                        let _ = store.save();
-                                    }
                                 }
                                 Err(e) => {
                                     warn!("Keyring migration failed (keeping in config): {}", e);
                                 }
                             }
                         }
-
-                        // Apply stored config to the managed state (spawn, not block_on:
-                        // setup runs inside the tokio runtime, so block_on would panic)
-                        tauri::async_runtime::spawn(async move {
-                            let mut config = config_arc.lock().await;
-                            // Load auth_token from keyring if not in config
-                            if loaded.auth_token.is_none() {
-                                if let Some(token) = config::load_auth_token() {
-                                    let mut loaded_with_token = loaded.clone();
-                                    loaded_with_token.auth_token = Some(token);
-                                    *config = loaded_with_token;
-                                } else {
-                                    *config = loaded.clone();
+                        if loaded_config.auth_token.is_some() || stored_config.get("sealed").is_none() {
+                            match config_seal::seal(&migrated) {
+                                Ok(val) => {
+                                    store.set("config", val);
+                                    let _ = store.save();
                                 }
-                            } else {
-                                *config = loaded.clone();
+                                Err(e) => warn!("Failed to seal stored config: {}", e),
                             }
-                            drop(config);
-
-                            let pm = pm_arc.lock().await;
-                            for printer in &loaded.printers {
-                                pm.add_printer(printer.clone()).await;
-                            }
-                            drop(pm);
+                        }
 
-                            info!("Stored config applied: {} printers registered", loaded.printers.len());
-                        });
+                        // Apply stored config to the managed state (spawn, not block_on:
+                        // setup runs inside the tokio runtime, so block_on would panic)
+                        tauri::async_runtime::spawn(apply_stored_config(config_arc, pm_arc, queue_arc, api_server, loaded));
 
                         // Set Sentry context from stored config
                         if let Some(ref restaurant_id) = loaded_config.restaurant_id {
@@ -1738,8 +4805,28 @@ async fn main() {
                             sentry_init::set_user_context(restaurant_id);
                         }
                     }
-                    Err(e) => {
-                        warn!("Failed to parse stored config: {} - using defaults", e);
+                    config_seal::Loaded::Rejected { error, readable } => {
+                        // Not silently replaced: the file is kept aside and staff decide
+                        // (recover_config) while the daemon runs unpaired on defaults
+                        let backup = AppConfig::default().database_path().with_file_name(format!(
+                            "config.rejected-{}.json",
+                            chrono::Local::now().format("%Y%m%d-%H%M%S")
+                        ));
+                        let backup_path = serde_json::to_vec_pretty(&stored_config)
+                            .map_err(std::io::Error::from)
+                            .and_then(|bytes| std::fs::write(&backup, bytes))
+                            .map(|_| backup.display().to_string())
+                            .map_err(|e| warn!("Failed to back up rejected config: {}", e))
+                            .ok();
+                        error!("Stored config not loaded: {} - running on defaults until recovered (backup: {:?})", error, backup_path);
+
+                        let state = app.state::<AppState>();
+                        *state.config_integrity.lock().unwrap_or_else(|e| e.into_inner()) = config_seal::ConfigIntegrity {
+                            error: Some(error.to_string()),
+                            recoverable: readable.is_some(),
+                            backup_path,
+                            readable,
+                        };
                     }
                 }
             } else {
@@ -1751,7 +4838,14 @@ async fn main() {
 
             // Start update checker (notify-only, user decides when to install)
             let handle = app.handle().clone();
-            let checker = Arc::new(updater::UpdateChecker::new(handle));
+            let state = app.state::<AppState>();
+            let checker = Arc::new(updater::UpdateChecker::new(
+                handle,
+                state.notifier.clone(),
+                state.queue_manager.clone(),
+                state.config.clone(),
+            ));
+            app.manage(checker.clone());
             tauri::async_runtime::spawn(async move {
                 checker.start().await;
             });
@@ -1763,8 +4857,11 @@ async fn main() {
             get_config,
             save_config,
             claim_pairing_code,
+            get_pairing_state,
+            complete_setup,
             discover_printers,
             test_print,
+            print_setup_complete,
             test_discovered_printer,
             start_polling,
             stop_polling,
@@ -1774,17 +4871,87 @@ async fn main() {
             is_printer_online,
             add_printer,
             remove_printer,
+            print_width_ruler,
+            set_paper_width,
+            set_recovery_mode,
+            set_printer_compact,
+            set_printer_transports,
+            get_printer_transports,
+            get_recovery_offers,
+            confirm_recovery_print,
+            dismiss_recovery_print,
+            attempt_printer_recovery,
+            get_mechanical_faults,
             get_uptime,
             escalate_job_priority,
+            cancel_job,
+            annotate_job,
+            get_job_history,
+            get_address_conflicts,
+            get_config_integrity,
+            recover_config,
+            merge_printers,
+            rename_printer,
+            pause_printer,
+            resume_printer,
+            preview_printer_import,
+            import_printers,
+            list_order_jobs,
+            find_jobs,
+            simulate_routing,
+            get_restart_history,
+            get_printer_incidents,
             preview_test_print,
             preview_kitchen_receipt,
+            get_timezone,
+            get_receipt_templates,
+            validate_receipt_template,
+            save_receipt_template,
+            preview_receipt_template,
+            preview_customer_receipt,
+            print_pickup_slip,
+            preview_pickup_slip,
             cleanup_queue,
             clear_queue,
+            get_api_server_status,
+            export_queue,
+            import_queue,
             get_circuit_breaker_status,
+            get_breaker_history,
             reset_circuit_breaker,
+            get_printer_health,
+            get_quarantine_status,
+            quarantine_printer,
+            release_printer,
+            get_notifications,
+            start_maintenance,
+            end_maintenance,
+            get_maintenance,
+            set_low_resource_mode,
+            set_telemetry_mode,
+            get_ble_status,
+            get_printer_stats,
+            get_failover_status,
+            get_job_encryption_key,
+            run_self_test,
+            clear_notifications,
+            take_notification_action,
+            get_queue_health,
+            get_station_load,
+            print_next,
+            get_day_summary,
+            open_printer_admin,
+            get_audit_log,
+            troubleshoot_printer,
+            check_usb_permissions,
+            fix_usb_permissions,
             get_event_history,
             get_log_tail,
             get_log_path,
+            generate_support_bundle,
+            set_fault_injection,
+            get_fault_injection,
+            clear_fault_injection,
             updater::check_for_updates,
             updater::install_update,
         ])
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Process-wide flag, for places without access to app state (Sentry's before_send)
+/// and hot loops (job poller, job processor)
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether a maintenance window is active
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// A planned maintenance window: no jobs are fetched or printed, failure alerts and
+/// Sentry reports are suppressed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MaintenanceWindow {
+    pub started_at: DateTime<Utc>,
+    /// None = until ended by hand
+    pub ends_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+}
+
+/// Maintenance mode state. In memory: a restart ends maintenance.
+#[derive(Default)]
+pub struct Maintenance {
+    window: Mutex<Option<MaintenanceWindow>>,
+}
+
+impl Maintenance {
+    /// Start (or replace) a maintenance window
+    pub async fn start(
+        &self,
+        now: DateTime<Utc>,
+        duration: Option<Duration>,
+        reason: Option<String>,
+    ) -> MaintenanceWindow {
+        let window = MaintenanceWindow {
+            started_at: now,
+            ends_at: duration.map(|d| now + d),
+            reason,
+        };
+        info!(
+            "Maintenance mode on until {}",
+            window.ends_at.map(|t| t.to_rfc3339()).unwrap_or_else(|| "ended by hand".to_string())
+        );
+        *self.window.lock().await = Some(window.clone());
+        ACTIVE.store(true, Ordering::Relaxed);
+        window
+    }
+
+    /// End maintenance. Returns the window that was active, if any.
+    pub async fn end(&self) -> Option<MaintenanceWindow> {
+        let ended = self.window.lock().await.take();
+        ACTIVE.store(false, Ordering::Relaxed);
+        if ended.is_some() {
+            info!("Maintenance mode off, printing resumes");
+        }
+        ended
+    }
+
+    /// End the window if its time is up. Returns the expired window.
+    pub async fn expire(&self, now: DateTime<Utc>) -> Option<MaintenanceWindow> {
+        let expired = self
+            .window
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(|w| w.ends_at.is_some_and(|end| end <= now));
+        if expired {
+            self.end().await
+        } else {
+            None
+        }
+    }
+
+    pub async fn current(&self) -> Option<MaintenanceWindow> {
+        self.window.lock().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_window_expires_at_its_end() {
+        let maintenance = Maintenance::default();
+        let now = Utc::now();
+
+        maintenance.start(now, Some(Duration::minutes(30)), Some("router swap".to_string())).await;
+        assert_eq!(maintenance.expire(now + Duration::minutes(29)).await, None);
+        assert!(maintenance.current().await.is_some());
+
+        let expired = maintenance.expire(now + Duration::minutes(30)).await.unwrap();
+        assert_eq!(expired.reason.as_deref(), Some("router swap"));
+        assert_eq!(maintenance.current().await, None);
+
+        // Open-ended windows only end by hand
+        maintenance.start(now, None, None).await;
+        assert_eq!(maintenance.expire(now + Duration::days(7)).await, None);
+        assert!(maintenance.end().await.is_some());
+        assert!(maintenance.end().await.is_none());
+    }
+}
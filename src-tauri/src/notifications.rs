@@ -0,0 +1,329 @@
+use crate::config::AppConfig;
+use crate::maintenance;
+use crate::rediscovery::in_window;
+use crate::venue_time;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+/// Alerts kept for the in-app notification list
+const HISTORY_CAPACITY: usize = 50;
+
+/// Warn this long before the pairing token expires
+pub const TOKEN_WARNING_SECS: i64 = 3 * 24 * 3600;
+
+/// Problems the daemon raises as OS notifications
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    PaperOut,
+    /// Cover left open: the printer takes no tickets until it is closed
+    CoverOpen,
+    BreakerOpen,
+    TokenExpiring,
+    UpdateAvailable,
+    /// A printer failed the scheduled morning self-test
+    SelfTestFailed,
+    /// Cutter jam or mechanical error the automatic recovery didn't clear
+    MechanicalFault,
+}
+
+/// Screen the app opens when the user follows up on an alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertAction {
+    Diagnostics,
+    Settings,
+    Update,
+}
+
+impl AlertKind {
+    /// Printer failures (expected during maintenance)
+    pub fn is_failure(self) -> bool {
+        matches!(
+            self,
+            AlertKind::PaperOut
+                | AlertKind::CoverOpen
+                | AlertKind::BreakerOpen
+                | AlertKind::SelfTestFailed
+                | AlertKind::MechanicalFault
+        )
+    }
+
+    pub fn action(self) -> AlertAction {
+        match self {
+            AlertKind::PaperOut
+            | AlertKind::CoverOpen
+            | AlertKind::BreakerOpen
+            | AlertKind::SelfTestFailed
+            | AlertKind::MechanicalFault => AlertAction::Diagnostics,
+            AlertKind::TokenExpiring => AlertAction::Settings,
+            AlertKind::UpdateAvailable => AlertAction::Update,
+        }
+    }
+}
+
+/// Quiet hours (local time): alerts still land in the in-app list, but no OS notification
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// Inclusive
+    pub start_hour: u8,
+    /// Exclusive; may wrap past midnight
+    pub end_hour: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    pub enabled: bool,
+    /// Alert types that never raise an OS notification
+    pub disabled: Vec<AlertKind>,
+    pub quiet_hours: Option<QuietHours>,
+    /// The same alert for the same subject is shown at most once per this many minutes
+    pub repeat_minutes: u32,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            disabled: Vec::new(),
+            quiet_hours: None,
+            repeat_minutes: 30,
+        }
+    }
+}
+
+/// One raised alert
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub id: u64,
+    pub kind: AlertKind,
+    pub title: String,
+    pub body: String,
+    pub action: AlertAction,
+    /// Printer ID, version, ... (repeat suppression is per kind + subject)
+    pub subject: Option<String>,
+    /// Unix seconds
+    pub created_at: i64,
+    /// Whether it was shown as an OS notification
+    pub shown: bool,
+}
+
+/// Why an alert did not raise an OS notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suppressed {
+    Disabled,
+    KindDisabled,
+    QuietHours,
+    Repeat,
+    /// Failure alerts during planned maintenance
+    Maintenance,
+}
+
+/// Whether an OS notification may be shown for `kind` at local `hour`
+pub fn allowed(config: &NotificationConfig, kind: AlertKind, hour: u8) -> Result<(), Suppressed> {
+    if !config.enabled {
+        return Err(Suppressed::Disabled);
+    }
+    if config.disabled.contains(&kind) {
+        return Err(Suppressed::KindDisabled);
+    }
+    if config
+        .quiet_hours
+        .is_some_and(|q| in_window(hour, q.start_hour, q.end_hour))
+    {
+        return Err(Suppressed::QuietHours);
+    }
+    Ok(())
+}
+
+/// Recent alerts plus the follow-up action of the last OS notification.
+///
+/// Desktop notifications can't report clicks back, so the action of the last shown
+/// notification is held until the window regains focus; the frontend takes it then and
+/// navigates there.
+#[derive(Default)]
+pub struct NotificationCenter {
+    history: Mutex<VecDeque<Alert>>,
+    last_shown: Mutex<HashMap<(AlertKind, Option<String>), Instant>>,
+    pending_action: Mutex<Option<AlertAction>>,
+    next_id: Mutex<u64>,
+}
+
+impl NotificationCenter {
+    /// Record an alert and decide whether it becomes an OS notification
+    pub async fn raise(
+        &self,
+        config: &NotificationConfig,
+        kind: AlertKind,
+        subject: Option<&str>,
+        title: String,
+        body: String,
+    ) -> (Alert, Result<(), Suppressed>) {
+        let hour = chrono::Timelike::hour(&venue_time::now()) as u8;
+        let mut verdict = allowed(config, kind, hour);
+        if verdict.is_ok() && kind.is_failure() && maintenance::is_active() {
+            verdict = Err(Suppressed::Maintenance);
+        }
+
+        if verdict.is_ok() {
+            let key = (kind, subject.map(str::to_string));
+            let mut last_shown = self.last_shown.lock().await;
+            let repeat = Duration::from_secs(config.repeat_minutes as u64 * 60);
+            if last_shown.get(&key).is_some_and(|t| t.elapsed() < repeat) {
+                verdict = Err(Suppressed::Repeat);
+            } else {
+                last_shown.insert(key, Instant::now());
+                *self.pending_action.lock().await = Some(kind.action());
+            }
+        }
+
+        let id = {
+            let mut next_id = self.next_id.lock().await;
+            *next_id += 1;
+            *next_id
+        };
+        let alert = Alert {
+            id,
+            kind,
+            title,
+            body,
+            action: kind.action(),
+            subject: subject.map(str::to_string),
+            created_at: chrono::Utc::now().timestamp(),
+            shown: verdict.is_ok(),
+        };
+
+        let mut history = self.history.lock().await;
+        history.push_front(alert.clone());
+        history.truncate(HISTORY_CAPACITY);
+        (alert, verdict)
+    }
+
+    /// Newest first
+    pub async fn history(&self) -> Vec<Alert> {
+        self.history.lock().await.iter().cloned().collect()
+    }
+
+    pub async fn clear(&self) {
+        self.history.lock().await.clear();
+    }
+
+    /// Action of the last OS notification, once
+    pub async fn take_pending_action(&self) -> Option<AlertAction> {
+        self.pending_action.lock().await.take()
+    }
+}
+
+/// Raises alerts: records them, shows the OS notification when allowed and tells the
+/// dashboard (`notification-raised`)
+pub struct Notifier {
+    center: NotificationCenter,
+    config: Arc<Mutex<AppConfig>>,
+    /// Set during Tauri .setup(); alerts before that are only listed
+    app_handle: Arc<Mutex<Option<AppHandle>>>,
+}
+
+impl Notifier {
+    pub fn new(config: Arc<Mutex<AppConfig>>, app_handle: Arc<Mutex<Option<AppHandle>>>) -> Self {
+        Self {
+            center: NotificationCenter::default(),
+            config,
+            app_handle,
+        }
+    }
+
+    pub fn center(&self) -> &NotificationCenter {
+        &self.center
+    }
+
+    pub async fn notify(&self, kind: AlertKind, subject: Option<&str>, title: String, body: String) {
+        let config = self.config.lock().await.notifications.clone();
+        let (alert, verdict) = self.center.raise(&config, kind, subject, title, body).await;
+
+        let handle = self.app_handle.lock().await.clone();
+        let Some(handle) = handle else {
+            return;
+        };
+        match verdict {
+            Ok(()) => {
+                info!("Notification: {}", alert.title);
+                if let Err(e) = handle
+                    .notification()
+                    .builder()
+                    .title(&alert.title)
+                    .body(&alert.body)
+                    .show()
+                {
+                    warn!("Failed to show notification: {}", e);
+                }
+            }
+            Err(reason) => debug!("Notification suppressed ({:?}): {}", reason, alert.title),
+        }
+        let _ = handle.emit("notification-raised", &alert);
+    }
+}
+
+/// Expiry (Unix seconds) of a JWT, read without verifying it. The pairing token is
+/// issued by Supabase; the daemon only needs to know when to warn.
+pub fn token_expiry(token: &str) -> Option<i64> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    claims.get("exp")?.as_i64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_respects_toggles_and_quiet_hours() {
+        let config = NotificationConfig {
+            disabled: vec![AlertKind::UpdateAvailable],
+            quiet_hours: Some(QuietHours { start_hour: 23, end_hour: 7 }),
+            ..Default::default()
+        };
+
+        assert_eq!(allowed(&config, AlertKind::PaperOut, 12), Ok(()));
+        assert_eq!(allowed(&config, AlertKind::UpdateAvailable, 12), Err(Suppressed::KindDisabled));
+        assert_eq!(allowed(&config, AlertKind::PaperOut, 2), Err(Suppressed::QuietHours));
+        assert_eq!(allowed(&config, AlertKind::PaperOut, 7), Ok(()));
+
+        let off = NotificationConfig { enabled: false, ..Default::default() };
+        assert_eq!(allowed(&off, AlertKind::BreakerOpen, 12), Err(Suppressed::Disabled));
+    }
+
+    #[tokio::test]
+    async fn test_repeats_are_listed_but_not_shown_twice() {
+        let center = NotificationCenter::default();
+        let config = NotificationConfig::default();
+        let raise = |subject: &'static str| {
+            center.raise(&config, AlertKind::PaperOut, Some(subject), "Paper out".into(), String::new())
+        };
+
+        assert_eq!(raise("bar").await.1, Ok(()));
+        assert_eq!(raise("bar").await.1, Err(Suppressed::Repeat));
+        assert_eq!(raise("kitchen").await.1, Ok(()));
+
+        assert_eq!(center.history().await.len(), 3);
+        assert_eq!(center.take_pending_action().await, Some(AlertAction::Diagnostics));
+        assert_eq!(center.take_pending_action().await, None);
+    }
+
+    #[test]
+    fn test_token_expiry() {
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(r#"{"sub":"r1","exp":1800000000}"#);
+        assert_eq!(token_expiry(&format!("eyJhbGciOiJIUzI1NiJ9.{}.sig", payload)), Some(1_800_000_000));
+        assert_eq!(token_expiry("not-a-jwt"), None);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::auth::{JWTManager, OrderLinkClaims};
+use crate::business_date;
+use crate::config::AppConfig;
+use crate::pickup_slip::PickupSlip;
+use crate::venue_time;
+use chrono::{DateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Shortest signing secret accepted: printed tokens can be taken home and brute-forced
+/// offline, so a short secret would let anyone mint links
+pub const MIN_SECRET_LEN: usize = 32;
+
+/// Signed deep links to an order's status / feedback page, printed as QR codes on
+/// customer receipts and pickup slips (`AppConfig.order_links`). The link carries a
+/// token signed with a secret shared with the web app instead of the bare order ID, so
+/// links can't be guessed or enumerated, and they stop working when the business day ends.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrderLinkConfig {
+    /// HS256 secret shared with the web app, which checks the token. No links without it.
+    pub secret: Option<String>,
+    /// Order status page; `{token}` is filled in (appended as `token=` when missing)
+    pub status_url: Option<String>,
+    /// Feedback page, like `status_url`. Customer receipts link here when set, to the
+    /// status page otherwise.
+    pub feedback_url: Option<String>,
+}
+
+/// Page a link opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkPurpose {
+    Status,
+    Feedback,
+}
+
+impl LinkPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkPurpose::Status => "status",
+            LinkPurpose::Feedback => "feedback",
+        }
+    }
+}
+
+impl OrderLinkConfig {
+    fn template(&self, purpose: LinkPurpose) -> Option<&str> {
+        let template = match purpose {
+            LinkPurpose::Status => &self.status_url,
+            LinkPurpose::Feedback => &self.feedback_url,
+        };
+        template.as_deref().map(str::trim).filter(|t| !t.is_empty())
+    }
+
+    fn signer(&self) -> Option<JWTManager> {
+        match self.secret.as_deref().map(str::trim) {
+            Some(secret) if secret.len() >= MIN_SECRET_LEN => Some(JWTManager::new(secret.to_string())),
+            Some(_) => {
+                warn!("Order link secret shorter than {} characters, no links printed", MIN_SECRET_LEN);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Signed link to an order's page, valid until the business day of `now` ends.
+    /// None when the page or the secret isn't configured.
+    pub fn link<Tz: TimeZone>(
+        &self,
+        purpose: LinkPurpose,
+        restaurant_id: &str,
+        order_id: &str,
+        now: &DateTime<Tz>,
+        rollover_hour: u8,
+    ) -> Option<String> {
+        let template = self.template(purpose)?;
+        let signer = self.signer()?;
+        let iat = now.timestamp();
+        let exp = business_date::day_end(now, rollover_hour)
+            .map(|end| end.timestamp())
+            .unwrap_or(iat + 24 * 60 * 60);
+        let claims = OrderLinkClaims {
+            restaurant_id: restaurant_id.to_string(),
+            order_id: order_id.to_string(),
+            purpose: purpose.as_str().to_string(),
+            iat: iat.max(0) as u64,
+            exp: exp.max(0) as u64,
+        };
+        let token = signer
+            .generate_order_link(&claims)
+            .map_err(|e| warn!("Order link for {} not signed: {}", order_id, e))
+            .ok()?;
+
+        Some(if template.contains("{token}") {
+            template.replace("{token}", &token)
+        } else {
+            let separator = if template.contains('?') { '&' } else { '?' };
+            format!("{}{}token={}", template, separator, token)
+        })
+    }
+}
+
+/// Link for an order of this restaurant, minted now
+fn link_now(config: &AppConfig, purpose: LinkPurpose, order_id: &str) -> Option<String> {
+    let restaurant_id = config.restaurant_id.as_deref()?;
+    config
+        .order_links
+        .link(purpose, restaurant_id, order_id, &venue_time::now(), config.business_day_rollover_hour)
+}
+
+/// QR code on a customer receipt: the feedback page, or the status page
+pub fn receipt_link(config: &AppConfig, order_id: &str) -> Option<(LinkPurpose, String)> {
+    [LinkPurpose::Feedback, LinkPurpose::Status]
+        .into_iter()
+        .find_map(|purpose| link_now(config, purpose, order_id).map(|url| (purpose, url)))
+}
+
+/// QR code on a pickup slip: the signed status link, or the plain
+/// `pickup_slip.status_url` when order links aren't set up
+pub fn pickup_slip_link(config: &AppConfig, slip: &PickupSlip) -> Option<String> {
+    link_now(config, LinkPurpose::Status, &slip.order_id).or_else(|| config.pickup_slip.status_url(slip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn test_signed_link_expires_with_business_day() {
+        let secret = "s".repeat(MIN_SECRET_LEN);
+        let mut config = OrderLinkConfig {
+            secret: Some(secret.clone()),
+            status_url: Some("https://eatsome.nl/o/{token}".to_string()),
+            feedback_url: Some("https://eatsome.nl/feedback?lang=nl".to_string()),
+        };
+        let amsterdam = FixedOffset::east_opt(3600).unwrap();
+        // Ahead of the real clock, so the token still validates
+        let now = amsterdam.with_ymd_and_hms(2096, 3, 15, 1, 30, 0).unwrap();
+
+        let url = config.link(LinkPurpose::Status, "r1", "o42", &now, 5).unwrap();
+        let token = url.strip_prefix("https://eatsome.nl/o/").unwrap();
+        let claims = JWTManager::new(secret).validate_order_link(token).unwrap();
+        assert_eq!((claims.restaurant_id.as_str(), claims.order_id.as_str()), ("r1", "o42"));
+        assert_eq!(claims.purpose, "status");
+        // Valid until the 05:00 rollover that ends the night's service
+        let rollover = amsterdam.with_ymd_and_hms(2096, 3, 15, 5, 0, 0).unwrap();
+        assert_eq!(claims.exp as i64, rollover.timestamp());
+
+        let url = config.link(LinkPurpose::Feedback, "r1", "o42", &now, 5).unwrap();
+        assert!(url.starts_with("https://eatsome.nl/feedback?lang=nl&token="));
+
+        // No links without a long enough secret
+        config.secret = Some("short".to_string());
+        assert_eq!(config.link(LinkPurpose::Status, "r1", "o42", &now, 5), None);
+        config.secret = None;
+        assert_eq!(config.link(LinkPurpose::Status, "r1", "o42", &now, 5), None);
+    }
+}
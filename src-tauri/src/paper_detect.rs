@@ -0,0 +1,118 @@
+use crate::config::PrinterConfig;
+use crate::escpos::PaperWidth;
+use serde::Serialize;
+
+/// Models known to take 58mm paper (upper-case, matched as a prefix of the reported name)
+const MODELS_58MM: &[&str] = &[
+    "TM-P20", "TM-P60", "TM-M10", "MC-PRINT2", "SM-L200", "SM-S230", "MPOP", "POS-58",
+    "XP-58", "ZJ-58", "MTP-2", "PT-210",
+];
+
+/// Models known to take 80mm paper
+const MODELS_80MM: &[&str] = &[
+    "TM-T88", "TM-T20", "TM-T82", "TM-T70", "TM-M30", "TM-M50", "TM-L90", "TSP1", "TSP6", "TSP7",
+    "TSP8", "MC-PRINT3", "POS-80", "XP-80", "ZJ-80", "RP-80",
+];
+
+/// How the paper width was determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionMethod {
+    /// Model name from GS I, matched against the known-model table
+    Model,
+    /// Printer didn't say, or the model is unknown: staff read the printed ruler
+    Unknown,
+}
+
+/// Result of the first-connect paper width check
+#[derive(Debug, Clone, Serialize)]
+pub struct WidthDetection {
+    pub printer_id: String,
+    pub model: Option<String>,
+    /// Detected width (mm), None if unknown
+    pub width_mm: Option<u16>,
+    /// Width configured before the check
+    pub configured_mm: u16,
+    /// Whether the config was changed to the detected width
+    pub corrected: bool,
+    pub method: DetectionMethod,
+}
+
+/// Model name from a GS I 67 answer: `_` header, name, NUL
+pub fn parse_model_response(bytes: &[u8]) -> Option<String> {
+    let body = bytes.strip_prefix(b"_")?;
+    let end = body.iter().position(|b| *b == 0).unwrap_or(body.len());
+    let name = String::from_utf8_lossy(&body[..end]).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Paper width for a model name, if the model is known
+pub fn width_for_model(model: &str) -> Option<PaperWidth> {
+    let model = model.to_uppercase().replace(' ', "");
+    if MODELS_58MM.iter().any(|m| model.starts_with(m)) {
+        Some(PaperWidth::Width58mm)
+    } else if MODELS_80MM.iter().any(|m| model.starts_with(m)) {
+        Some(PaperWidth::Width80mm)
+    } else {
+        None
+    }
+}
+
+/// Compare what the printer reported with its config. `corrected` is set when the
+/// model is known and its width differs from the configured one.
+pub fn detect(printer: &PrinterConfig, model: Option<String>) -> WidthDetection {
+    let configured = PaperWidth::from_chars(printer.capabilities.max_width);
+    let detected = model.as_deref().and_then(width_for_model);
+    WidthDetection {
+        printer_id: printer.id.clone(),
+        model,
+        width_mm: detected.map(PaperWidth::mm),
+        configured_mm: configured.mm(),
+        corrected: detected.is_some_and(|d| d.mm() != configured.mm()),
+        method: if detected.is_some() { DetectionMethod::Model } else { DetectionMethod::Unknown },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_response_and_table() {
+        assert_eq!(parse_model_response(b"_TM-T20II\0").as_deref(), Some("TM-T20II"));
+        assert_eq!(parse_model_response(b"_TM-m10\0garbage").as_deref(), Some("TM-m10"));
+        assert_eq!(parse_model_response(b"_\0"), None);
+        assert_eq!(parse_model_response(b"\x12\x12"), None);
+
+        assert!(matches!(width_for_model("TM-T20II"), Some(PaperWidth::Width80mm)));
+        assert!(matches!(width_for_model("TM-m10"), Some(PaperWidth::Width58mm)));
+        assert!(matches!(width_for_model("mC-Print2"), Some(PaperWidth::Width58mm)));
+        assert!(matches!(width_for_model("TSP143IIIU"), Some(PaperWidth::Width80mm)));
+        assert!(width_for_model("Generic Printer").is_none());
+    }
+
+    #[test]
+    fn test_detect_corrects_only_known_mismatches() {
+        let printer: PrinterConfig = serde_json::from_value(serde_json::json!({
+            "id": "bar",
+            "name": "Bar",
+            "connection_type": "usb",
+            "address": "usb_04b8_0e15",
+            "protocol": "escpos",
+            "station": null,
+            "is_primary": true,
+            "capabilities": { "cutter": true, "drawer": false, "qrcode": true, "max_width": 48 },
+        }))
+        .unwrap();
+
+        let mismatch = detect(&printer, Some("TM-m10".to_string()));
+        assert!(mismatch.corrected);
+        assert_eq!((mismatch.width_mm, mismatch.configured_mm), (Some(58), 80));
+
+        assert!(!detect(&printer, Some("TM-T20II".to_string())).corrected);
+
+        let unknown = detect(&printer, None);
+        assert!(!unknown.corrected);
+        assert_eq!(unknown.method, DetectionMethod::Unknown);
+    }
+}
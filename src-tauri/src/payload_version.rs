@@ -0,0 +1,203 @@
+use crate::transform::TransformRules;
+use serde::Serialize;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::RwLock;
+use tracing::{debug, warn};
+
+/// Newest job payload format this daemon knows. Rows without `payload_version` are
+/// version 1.
+pub const CURRENT_VERSION: u64 = 2;
+
+/// Different unknown field names counted before the rest are lumped together (a backend
+/// sending random keys must not grow the heartbeat without bound)
+const MAX_TRACKED_FIELDS: usize = 50;
+
+/// Top-level row columns the daemon doesn't map but knows about
+const KNOWN_COLUMNS: &[&str] = &[
+    "payload_version",
+    "restaurant_id",
+    "status",
+    "created_at",
+    "updated_at",
+    "retry_count",
+    "error_message",
+    "metadata",
+];
+
+/// Occurrences since start, sent with every heartbeat
+static COUNTS: RwLock<PayloadCounts> = RwLock::new(PayloadCounts {
+    versions: BTreeMap::new(),
+    unknown_versions: 0,
+    unknown_fields: BTreeMap::new(),
+    untracked_fields: 0,
+});
+
+/// How the job payloads the backend sent matched what this daemon knows
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PayloadCounts {
+    /// Jobs per payload version
+    pub versions: BTreeMap<u64, u64>,
+    /// Jobs with a version newer than `CURRENT_VERSION` (parsed best-effort)
+    pub unknown_versions: u64,
+    /// Occurrences per top-level field no mapping reads
+    pub unknown_fields: BTreeMap<String, u64>,
+    /// Occurrences of unknown fields beyond `MAX_TRACKED_FIELDS` names
+    pub untracked_fields: u64,
+}
+
+impl PayloadCounts {
+    fn is_empty(&self) -> bool {
+        self.versions.is_empty()
+    }
+}
+
+/// Counters so far, None until the first job arrived
+pub fn counts() -> Option<PayloadCounts> {
+    let counts = COUNTS.read().unwrap_or_else(|e| e.into_inner());
+    (!counts.is_empty()).then(|| counts.clone())
+}
+
+/// `payload_version` of a job record (number or numeric string); 1 when absent
+pub fn version_of(record: &Value) -> u64 {
+    match record.get("payload_version") {
+        Some(Value::Number(n)) => n.as_u64().unwrap_or(1),
+        Some(Value::String(s)) => s.trim().parse().unwrap_or(1),
+        _ => 1,
+    }
+}
+
+/// Bring a polled job record to the current payload format and count what didn't fit.
+/// Records newer than `CURRENT_VERSION` are passed on unchanged: the field mappings skip
+/// what they don't know, so most additions still print.
+pub fn adapt<'a>(record: &'a Value, rules: &TransformRules) -> Cow<'a, Value> {
+    let version = version_of(record);
+    let adapted = match version {
+        1 => Cow::Owned(adapt_v1(record)),
+        _ => Cow::Borrowed(record),
+    };
+
+    let unknown = unknown_fields(&adapted, rules);
+    record_counts(version, &unknown);
+    adapted
+}
+
+/// Version 1: `items` and `metadata` may arrive as JSON text (text columns), and the
+/// only time is the row's `created_at` (RFC 3339)
+fn adapt_v1(record: &Value) -> Value {
+    let mut record = record.clone();
+    let Some(map) = record.as_object_mut() else {
+        return record;
+    };
+    for key in ["items", "metadata"] {
+        if let Some(Value::String(text)) = map.get(key) {
+            if let Ok(parsed) = serde_json::from_str::<Value>(text) {
+                map.insert(key.to_string(), parsed);
+            }
+        }
+    }
+    if !map.contains_key("timestamp") {
+        let created = map
+            .get("created_at")
+            .and_then(Value::as_str)
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok());
+        if let Some(created) = created {
+            map.insert("timestamp".to_string(), Value::from(created.timestamp_millis()));
+        }
+    }
+    record
+}
+
+/// Top-level keys neither a field mapping nor `KNOWN_COLUMNS` covers
+pub fn unknown_fields(record: &Value, rules: &TransformRules) -> Vec<String> {
+    let Some(map) = record.as_object() else {
+        return Vec::new();
+    };
+    let f = &rules.fields;
+    let mapped: HashSet<&str> = [
+        &f.id, &f.order_id, &f.order_number, &f.station, &f.station_id, &f.printer_id, &f.items,
+        &f.table_number, &f.customer_name, &f.order_type, &f.priority, &f.timestamp, &f.copies,
+        &f.correlation_id, &f.order_stations,
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|path| path.split('/').find(|s| !s.is_empty()))
+    .chain(KNOWN_COLUMNS.iter().copied())
+    .collect();
+
+    map.keys().filter(|key| !mapped.contains(key.as_str())).cloned().collect()
+}
+
+fn record_counts(version: u64, unknown: &[String]) {
+    let mut counts = COUNTS.write().unwrap_or_else(|e| e.into_inner());
+
+    let seen = {
+        let seen = counts.versions.entry(version).or_insert(0);
+        *seen += 1;
+        *seen
+    };
+    if version > CURRENT_VERSION {
+        counts.unknown_versions += 1;
+        if seen == 1 {
+            warn!(
+                "Job payload version {} is newer than this daemon knows ({}): parsing best-effort, consider updating",
+                version, CURRENT_VERSION
+            );
+        }
+    }
+
+    for field in unknown {
+        let tracked = counts.unknown_fields.len();
+        match counts.unknown_fields.get_mut(field) {
+            Some(count) => *count += 1,
+            None if tracked < MAX_TRACKED_FIELDS => {
+                debug!("Job payload has unknown field {:?} (version {})", field, version);
+                counts.unknown_fields.insert(field.clone(), 1);
+            }
+            None => counts.untracked_fields += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_v1_adapted_and_drift_counted() {
+        let rules = TransformRules::default();
+        let v1 = json!({
+            "id": "job-1",
+            "order_number": "42",
+            "station": "bar",
+            "created_at": "2026-03-01T18:30:00+00:00",
+            "items": "[{\"name\": \"Beer\", \"quantity\": 2}]",
+        });
+        assert_eq!(version_of(&v1), 1);
+        let adapted = adapt(&v1, &rules);
+        let job = rules.to_print_job(&adapted, "rest_123").unwrap();
+        assert_eq!(job.items[0].name, "Beer");
+        assert_eq!(job.timestamp, 1_772_389_800_000);
+
+        let v3 = json!({
+            "payload_version": "3",
+            "id": "job-2",
+            "order_number": "43",
+            "station": "bar",
+            "items": [{ "name": "Wine" }],
+            "course_number": 2,
+        });
+        assert_eq!(version_of(&v3), 3);
+        assert_eq!(unknown_fields(&v3, &rules), vec!["course_number".to_string()]);
+        let adapted = adapt(&v3, &rules);
+        assert!(matches!(adapted, Cow::Borrowed(_)));
+        assert!(rules.to_print_job(&adapted, "rest_123").is_ok());
+
+        let counts = counts().unwrap();
+        assert!(counts.unknown_versions >= 1);
+        assert!(counts.unknown_fields["course_number"] >= 1);
+        assert!(counts.versions[&1] >= 1);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::config::PrinterConfig;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How often queued pickup slips are picked up for printing
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Slips printed per check
+pub const BATCH: usize = 5;
+
+/// "Order ready" slips for the pickup counter (`AppConfig.pickup_slip`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PickupSlipConfig {
+    /// Print a slip on its own once every station ticket of an order has printed.
+    /// Off: only when the POS or the dashboard asks for one.
+    pub auto: bool,
+    /// Printer for slips; the primary printer when unset or no longer configured
+    pub printer_id: Option<String>,
+    /// Order status page the QR code opens; `{order_id}` and `{order_number}` are
+    /// filled in. No QR code when unset.
+    pub status_url: Option<String>,
+}
+
+/// The order a slip is printed for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PickupSlip {
+    pub order_id: String,
+    pub order_number: String,
+    pub customer_name: Option<String>,
+}
+
+impl PickupSlipConfig {
+    /// QR code contents for an order's slip
+    pub fn status_url(&self, slip: &PickupSlip) -> Option<String> {
+        let template = self.status_url.as_deref().map(str::trim).filter(|t| !t.is_empty())?;
+        let encode = |value: &str| url::form_urlencoded::byte_serialize(value.as_bytes()).collect::<String>();
+        Some(
+            template
+                .replace("{order_id}", &encode(&slip.order_id))
+                .replace("{order_number}", &encode(&slip.order_number)),
+        )
+    }
+
+    /// Printer the slip goes to
+    pub fn printer<'a>(&self, printers: &'a [PrinterConfig]) -> Option<&'a PrinterConfig> {
+        self.printer_id
+            .as_deref()
+            .and_then(|id| printers.iter().find(|p| p.id == id))
+            .or_else(|| printers.iter().find(|p| p.is_primary))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConnectionType, PrinterCapabilities};
+
+    fn printer(id: &str, is_primary: bool) -> PrinterConfig {
+        PrinterConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            connection_type: ConnectionType::Network,
+            address: "192.168.1.50:9100".to_string(),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: None,
+            is_primary,
+            capabilities: PrinterCapabilities { cutter: true, drawer: false, qrcode: true, max_width: 48 },
+            paper_width_checked: true,
+            recovery: Default::default(),
+            compact: None,
+            transports: vec![],
+            serial: None,
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn test_status_url_and_printer_choice() {
+        let slip = PickupSlip {
+            order_id: "local:2026-10-17:42".to_string(),
+            order_number: "42".to_string(),
+            customer_name: Some("Sam".to_string()),
+        };
+        let mut config = PickupSlipConfig::default();
+        assert_eq!(config.status_url(&slip), None);
+        config.status_url = Some("https://eatsome.nl/order/{order_id}?n={order_number}".to_string());
+        assert_eq!(
+            config.status_url(&slip).as_deref(),
+            Some("https://eatsome.nl/order/local%3A2026-10-17%3A42?n=42")
+        );
+
+        let printers = vec![printer("kitchen", true), printer("counter", false)];
+        assert_eq!(config.printer(&printers).map(|p| p.id.as_str()), Some("kitchen"));
+        config.printer_id = Some("counter".to_string());
+        assert_eq!(config.printer(&printers).map(|p| p.id.as_str()), Some("counter"));
+        // Removed from the config: back to the primary
+        config.printer_id = Some("gone".to_string());
+        assert_eq!(config.printer(&printers).map(|p| p.id.as_str()), Some("kitchen"));
+    }
+}
@@ -1,15 +1,28 @@
-use crate::config::{ConnectionType, PrinterConfig};
+use crate::config::{AppConfig, ConnectionType, PrinterConfig};
 use crate::discovery::{self, DiscoveredPrinter};
 use crate::errors::{DaemonError, Result};
-use crate::escpos::{build_full_status_request, format_kitchen_receipt, format_test_print, PaperWidth};
-use crate::queue::PrintJob;
-use crate::status::PrinterHwStatus;
+use crate::escpos::{
+    build_error_recovery, build_full_status_request, build_model_request, build_write_ack_request,
+    format_change_ticket, format_kitchen_receipt, venue_logo, PaperWidth, TestPrintTemplate, Thumbnails,
+};
+use crate::item_images::{ItemImageConfig, ItemImages};
+use crate::paper_detect::parse_model_response;
+use crate::queue::{JobKind, PrintJob};
+use crate::receipt_fields::{OrderFields, ReceiptFieldsConfig};
+use crate::receipt_templates::{ReceiptTemplates, ReceiptTemplatesConfig, TicketData};
+use crate::serial::{self, SerialSettings};
+use crate::status::{Delivery, PrinterHwStatus, WriteAck};
+use crate::task_metrics;
+use crate::transport::{self, Transport, TransportMemory};
+use crate::units::{self, UnitFormat};
+use crate::write_timeout::{WriteTimeoutConfig, WriteTimeouts};
 use rusb::{Context, Device, DeviceDescriptor, UsbContext};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::net::TcpStream;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, OwnedMutexGuard, RwLock};
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
 /// A persistent TCP connection to a network printer.
@@ -34,214 +47,264 @@ const VENDOR_IDS: &[(u16, &str)] = &[
 /// Cache TTL for discovery results (seconds)
 const DISCOVERY_CACHE_TTL_SECS: u64 = 30;
 
-pub struct PrinterManager {
-    printers: Arc<Mutex<HashMap<String, PrinterConfig>>>,
+/// Cache TTL for per-printer online checks (seconds)
+const ONLINE_CACHE_TTL_SECS: u64 = 30;
+
+/// Connect timeout / BLE scan window for a single online check (seconds)
+const ONLINE_CHECK_TIMEOUT_SECS: u64 = 2;
+
+/// Unanswered write acknowledgments after which a printer's jobs are no longer verified
+const ACK_MAX_MISSES: u32 = 3;
+
+/// Discovery results shared between scans and online checks
+#[derive(Default)]
+struct DiscoveryCache {
+    /// Filled method by method while a scan runs, complete once `completed_at` is set
+    results: discovery::DiscoveryResults,
+    /// When the last full scan finished
+    completed_at: Option<Instant>,
+    /// A scan is running and `results` are still partial
+    scanning: bool,
+}
+
+/// Runs printer discovery, streaming each method's results into the shared cache
+pub struct PrinterDiscovery {
     usb_context: Context,
-    online_cache: Arc<Mutex<HashMap<String, (bool, Instant)>>>,
-    discovery_cache: Arc<Mutex<(Vec<serde_json::Value>, Option<Instant>)>>,
-    /// Persistent TCP connection pool: address → NetworkConnection
-    network_pool: Arc<Mutex<HashMap<String, NetworkConnection>>>,
+    cache: Arc<Mutex<DiscoveryCache>>,
+    profile: discovery::PortScanProfile,
 }
 
-impl PrinterManager {
-    pub fn new() -> Result<Self> {
-        info!("Initializing PrinterManager");
-        let usb_context = Context::new().map_err(|e| {
-            error!("Failed to initialize USB context: {}", e);
-            DaemonError::Usb(e)
-        })?;
-        Ok(Self {
-            printers: Arc::new(Mutex::new(HashMap::new())),
-            usb_context,
-            online_cache: Arc::new(Mutex::new(HashMap::new())),
-            discovery_cache: Arc::new(Mutex::new((Vec::new(), None))),
-            network_pool: Arc::new(Mutex::new(HashMap::new())),
-        })
+impl PrinterDiscovery {
+    /// TCP port scan profile for this run (configured or deep scan)
+    pub fn with_profile(mut self, profile: discovery::PortScanProfile) -> Self {
+        self.profile = profile;
+        self
     }
 
-    /// Discover all printers (USB + Network + Bluetooth) with caching
-    ///
-    /// Returns cached results if the last scan was within the TTL window (30s).
-    /// This prevents redundant full-network scans during the setup wizard flow
-    /// where discovery may be triggered multiple times.
-    #[tracing::instrument(skip(self))]
-    pub async fn discover_all(&self, force: bool) -> Result<Vec<serde_json::Value>> {
+    /// Full scan (USB + all network/Bluetooth methods), or the cached results if the
+    /// last scan finished within the TTL
+    pub async fn run(&self, force: bool) -> Result<Vec<serde_json::Value>> {
         // Check cache first (skip if force=true)
         if !force {
-            let cache = self.discovery_cache.lock().await;
-            if let Some(last_scan) = cache.1 {
+            let cache = self.cache.lock().await;
+            if let Some(last_scan) = cache.completed_at {
                 if last_scan.elapsed() < Duration::from_secs(DISCOVERY_CACHE_TTL_SECS) {
                     info!(
                         "Returning {} cached discovery results (age: {:.1}s)",
-                        cache.0.len(),
+                        cache.results.len(),
                         last_scan.elapsed().as_secs_f32()
                     );
-                    return Ok(cache.0.clone());
+                    return Ok(to_values(cache.results.printers()));
                 }
             }
         }
 
         debug!("Starting comprehensive printer discovery");
-        let mut discovered = Vec::new();
-
-        // Discover USB printers
-        let usb_printers = self.discover_usb()?;
+        let usb_printers = discover_usb(&self.usb_context)?;
         info!("Discovered {} USB printers", usb_printers.len());
-        discovered.extend(
-            usb_printers
-                .into_iter()
-                .filter_map(|p| serde_json::to_value(p).ok()),
-        );
+        let serial_ports = tokio::task::spawn_blocking(serial::discover).await.unwrap_or_default();
+        info!("Found {} serial ports", serial_ports.len());
+
+        // Start a fresh result set; online checks see it grow as methods report
+        {
+            let mut cache = self.cache.lock().await;
+            cache.results = discovery::DiscoveryResults::default();
+            cache.results.merge(discovery::DiscoverySource::Usb, usb_printers);
+            cache.results.merge(discovery::DiscoverySource::Serial, serial_ports);
+            cache.scanning = true;
+        }
 
-        // Discover network printers via comprehensive multi-method discovery
         let subnet = discovery::detect_local_subnet();
         info!("Auto-detected subnet for scanning: {}", subnet);
 
-        match discovery::discover_all_printers(&subnet).await {
-            Ok(network_printers) => {
-                info!("Discovered {} network/bluetooth printers via comprehensive scan", network_printers.len());
-                discovered.extend(
-                    network_printers
-                        .into_iter()
-                        .filter_map(|p| serde_json::to_value(p).ok()),
-                );
-            }
-            Err(e) => {
-                warn!("Comprehensive network discovery failed: {}", e);
-            }
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let profile = self.profile.clone();
+        let scan = tokio::spawn(async move {
+            discovery::discover_all_printers_streaming(&subnet, &profile, tx).await
+        });
+        while let Some((source, printers)) = rx.recv().await {
+            let mut cache = self.cache.lock().await;
+            cache.results.merge(source, printers);
+            debug!("{:?} results merged ({} printers so far)", source, cache.results.len());
         }
-
-        info!("Total printers discovered: {}", discovered.len());
-
-        // Update cache
-        {
-            let mut cache = self.discovery_cache.lock().await;
-            cache.0 = discovered.clone();
-            cache.1 = Some(std::time::Instant::now());
+        if let Err(e) = scan.await {
+            warn!("Comprehensive network discovery failed: {}", e);
         }
 
-        Ok(discovered)
+        let mut cache = self.cache.lock().await;
+        cache.scanning = false;
+        cache.completed_at = Some(Instant::now());
+        info!("Total printers discovered: {}", cache.results.len());
+        Ok(to_values(cache.results.printers()))
     }
+}
 
-    /// Discover USB printers
-    fn discover_usb(&self) -> Result<Vec<DiscoveredPrinter>> {
-        let mut discovered = Vec::new();
+fn to_values(printers: Vec<DiscoveredPrinter>) -> Vec<serde_json::Value> {
+    printers
+        .into_iter()
+        .filter_map(|p| serde_json::to_value(p).ok())
+        .collect()
+}
 
-        for device in self.usb_context.devices()?.iter() {
-            let device_desc = device.device_descriptor()?;
-
-            // Check if vendor ID matches known thermal printer manufacturers
-            if let Some((_, vendor_name)) = VENDOR_IDS
-                .iter()
-                .find(|(vid, _)| *vid == device_desc.vendor_id())
-            {
-                let id = format!("usb_{:04x}_{:04x}", device_desc.vendor_id(), device_desc.product_id());
-                let name = self.get_usb_product_name(&device, &device_desc)
-                    .unwrap_or_else(|_| format!("{} Printer", vendor_name));
-
-                let address = format!(
-                    "/dev/bus/usb/{:03}/{:03}",
-                    device.bus_number(),
-                    device.address()
-                );
+/// A registered printer
+#[derive(Clone)]
+struct PrinterHandle {
+    config: PrinterConfig,
+    /// Held for one exchange with the printer (ticket and its acknowledgment, status
+    /// query, recovery) so two don't interleave on the device. Kept across config updates.
+    io: Arc<Mutex<()>>,
+}
 
-                discovered.push(DiscoveredPrinter {
-                    id,
-                    name,
-                    connection_type: "usb".to_string(),
-                    address,
-                    vendor: vendor_name.to_string(),
-                    capabilities: Some(serde_json::json!({
-                        "cutter": true,
-                        "drawer": false,
-                        "qrcode": true,
-                        "maxWidth": 48
-                    })),
-                    protocol: "escpos".to_string(), // Known vendor IDs = ESC/POS
-                });
-            }
-        }
+/// Printers, their connections and cached state. Cheap to clone and shared by value:
+/// there is no outer lock, so discovery, status checks and prints to different printers
+/// run in parallel. Jobs for the same printer queue on its handle's `io` lock.
+#[derive(Clone)]
+pub struct PrinterManager {
+    /// Registry: read on every print and status check, written on config changes
+    printers: Arc<RwLock<HashMap<String, PrinterHandle>>>,
+    usb_context: Context,
+    online_cache: Arc<Mutex<HashMap<String, (bool, Instant)>>>,
+    discovery_cache: Arc<Mutex<DiscoveryCache>>,
+    /// Persistent TCP connection pool: printer_id → NetworkConnection. Keyed by printer,
+    /// not address, so two printers briefly sharing an IP (DHCP drift) never get each
+    /// other's socket.
+    network_pool: Arc<Mutex<HashMap<String, NetworkConnection>>>,
+    /// Consecutive unanswered write acknowledgments per printer
+    ack_misses: Arc<Mutex<HashMap<String, u32>>>,
+    /// Item thumbnails for receipts (downloaded and rendered once)
+    item_images: Arc<ItemImages>,
+    /// Header / footer text printed on every ticket
+    receipt_fields: Arc<RwLock<ReceiptFieldsConfig>>,
+    /// Compiled per-station ticket templates
+    receipt_templates: Arc<RwLock<ReceiptTemplates>>,
+    /// Logo settings while the logo goes on kitchen tickets
+    kitchen_logo: Arc<RwLock<Option<TestPrintTemplate>>>,
+    /// Last working transport per printer
+    transports: Arc<Mutex<TransportMemory>>,
+    /// Raw write timeouts sized to the payload and the printer's measured throughput
+    write_timeouts: Arc<std::sync::Mutex<WriteTimeouts>>,
+    /// Units and number format of quantities in modifiers and notes
+    units: Arc<std::sync::RwLock<UnitFormat>>,
+    /// IPP and ePOS-Print requests
+    http: reqwest::Client,
+}
 
-        Ok(discovered)
+impl PrinterManager {
+    pub fn new() -> Result<Self> {
+        info!("Initializing PrinterManager");
+        let usb_context = Context::new().map_err(|e| {
+            error!("Failed to initialize USB context: {}", e);
+            DaemonError::Usb(e)
+        })?;
+        Ok(Self {
+            printers: Arc::new(RwLock::new(HashMap::new())),
+            usb_context,
+            online_cache: Arc::new(Mutex::new(HashMap::new())),
+            discovery_cache: Arc::new(Mutex::new(DiscoveryCache::default())),
+            network_pool: Arc::new(Mutex::new(HashMap::new())),
+            ack_misses: Arc::new(Mutex::new(HashMap::new())),
+            // Next to the queue database
+            item_images: Arc::new(ItemImages::new(AppConfig::default().database_path().with_file_name("item-images"))),
+            receipt_fields: Arc::new(RwLock::new(ReceiptFieldsConfig::default())),
+            receipt_templates: Arc::new(RwLock::new(ReceiptTemplates::default())),
+            kitchen_logo: Arc::new(RwLock::new(None)),
+            transports: Arc::new(Mutex::new(TransportMemory::default())),
+            write_timeouts: Arc::new(std::sync::Mutex::new(WriteTimeouts::default())),
+            units: Arc::new(std::sync::RwLock::new(UnitFormat::default())),
+            http: reqwest::Client::new(),
+        })
     }
 
-    /// Get USB product name from device descriptor
-    fn get_usb_product_name<T: UsbContext>(
-        &self,
-        device: &Device<T>,
-        device_desc: &DeviceDescriptor,
-    ) -> Result<String> {
-        let handle = device.open()?;
-        let timeout = Duration::from_secs(1);
-        let languages = handle.read_languages(timeout)?;
-
-        if let Some(language) = languages.first() {
-            let product_string = handle
-                .read_product_string(*language, device_desc, timeout)?;
-            Ok(product_string)
-        } else {
-            Err(DaemonError::Other(anyhow::anyhow!("No language found")))
+    /// Handle for running discovery on its own (a full scan takes ~20s; printing must
+    /// not wait for it)
+    pub fn discovery(&self) -> PrinterDiscovery {
+        PrinterDiscovery {
+            usb_context: self.usb_context.clone(),
+            cache: self.discovery_cache.clone(),
+            profile: discovery::PortScanProfile::default(),
         }
     }
 
-    /// Test print on a specific printer
-    #[tracing::instrument(skip(self), fields(printer_id))]
-    pub async fn test_print(&self, printer_id: &str) -> Result<()> {
+    /// Discover all printers (USB + Network + Bluetooth) with caching
+    ///
+    /// Returns cached results if the last scan was within the TTL window (30s).
+    /// This prevents redundant full-network scans during the setup wizard flow
+    /// where discovery may be triggered multiple times.
+    #[tracing::instrument(skip(self))]
+    pub async fn discover_all(&self, force: bool) -> Result<Vec<serde_json::Value>> {
+        self.discovery().run(force).await
+    }
+
+    /// Test print on a specific printer.
+    ///
+    /// `commands` is the test or setup-complete receipt, rendered by the caller from the
+    /// venue's `TestPrintTemplate`.
+    #[tracing::instrument(skip(self, commands), fields(printer_id))]
+    pub async fn test_print(&self, printer_id: &str, commands: &[u8]) -> Result<()> {
         info!("Test print requested for printer: {}", printer_id);
 
-        let printers = self.printers.lock().await;
-        let printer = printers
-            .get(printer_id)
-            .ok_or_else(|| {
-                error!("Printer not found: {}", printer_id);
-                DaemonError::PrinterNotFound(printer_id.to_string())
-            })?;
+        let printer = self.get_printer(printer_id).await.ok_or_else(|| {
+            error!("Printer not found: {}", printer_id);
+            DaemonError::PrinterNotFound(printer_id.to_string())
+        })?;
 
-        let commands = format_test_print(PaperWidth::Width80mm);
-        debug!("Generated test print commands: {} bytes", commands.len());
+        debug!("Test print commands: {} bytes", commands.len());
 
-        let result = match printer.connection_type {
-            ConnectionType::USB => {
-                debug!("Printing via USB to: {}", printer.address);
-                self.print_usb(&printer.address, &commands).await
-            }
-            ConnectionType::Network => {
-                debug!("Printing via Network to: {}", printer.address);
-                self.print_network(&printer.address, &commands).await
-            }
-            ConnectionType::Bluetooth => {
-                debug!("Printing via Bluetooth to: {}", printer.address);
-                self.print_bluetooth(&printer.address, &commands).await
-            }
-        };
+        let _io = self.io(printer_id).await;
+        let result = self.send(&printer, "Test print", commands).await;
 
         match &result {
-            Ok(_) => info!("Test print completed successfully for printer: {}", printer_id),
+            Ok(transport) => info!(
+                "Test print completed successfully for printer: {} (over {})",
+                printer_id,
+                transport.as_str()
+            ),
             Err(e) => error!("Test print failed for printer {}: {}", printer_id, e),
         }
 
-        result
+        result.map(|_| ())
+    }
+
+    /// Print a ready-made receipt (pickup slip) on a configured printer
+    pub async fn print_commands(&self, printer_id: &str, job_name: &str, commands: &[u8]) -> Result<()> {
+        let printer = self
+            .get_printer(printer_id)
+            .await
+            .ok_or_else(|| DaemonError::PrinterNotFound(printer_id.to_string()))?;
+        let _io = self.io(printer_id).await;
+        self.send(&printer, job_name, commands).await.map(|_| ())
     }
 
     /// Test print directly to an address without requiring printer to be registered
-    pub async fn test_print_direct(&self, address: &str, connection_type: &str) -> Result<()> {
+    /// `serial` applies to serial ports only (None: default line settings).
+    pub async fn test_print_direct(
+        &self,
+        address: &str,
+        connection_type: &str,
+        serial: Option<SerialSettings>,
+        commands: &[u8],
+    ) -> Result<()> {
         info!("Direct test print requested for: {} ({})", address, connection_type);
+        debug!("Test print commands: {} bytes", commands.len());
 
-        let commands = format_test_print(PaperWidth::Width80mm);
-        debug!("Generated test print commands: {} bytes", commands.len());
-
+        let timeout = |connection: ConnectionType| self.write_timeout(None, &connection, commands.len());
         let result = match connection_type {
             "usb" => {
                 debug!("Printing via USB to: {}", address);
-                self.print_usb(address, &commands).await
+                self.print_usb(address, commands, timeout(ConnectionType::USB)).await
             }
             "network" => {
                 debug!("Printing via Network to: {}", address);
-                self.print_network(address, &commands).await
+                self.print_network(None, address, commands, timeout(ConnectionType::Network)).await
             }
             "bluetooth" => {
                 debug!("Printing via Bluetooth to: {}", address);
-                self.print_bluetooth(address, &commands).await
+                self.print_bluetooth(address, commands, timeout(ConnectionType::Bluetooth)).await
+            }
+            "serial" => {
+                let settings = serial.unwrap_or_default();
+                debug!("Printing via serial port {} at {} baud", address, settings.baud_rate);
+                self.print_serial(address, settings, commands, timeout(ConnectionType::Serial)).await
             }
             _ => {
                 error!("Unknown connection type: {}", connection_type);
@@ -260,39 +323,202 @@ impl PrinterManager {
     /// Print a job to a specific printer
     ///
     /// Generates ESC/POS kitchen receipt from the job's items and sends to the printer.
-    #[tracing::instrument(skip(self, job), fields(printer_id, job_id = %job.id, order = %job.order_number))]
-    pub async fn print_to_printer(&self, printer_id: &str, job: &PrintJob) -> Result<()> {
+    /// Returns when the write finished and, if the printer confirmed it, when it printed.
+    #[tracing::instrument(skip(self, job, cancel), fields(printer_id, job_id = %job.id, order = %job.order_number, correlation_id = %job.correlation_id))]
+    pub async fn print_to_printer(&self, printer_id: &str, job: &PrintJob, cancel: &CancellationToken) -> Result<Delivery> {
         info!("Printing job {} to printer {}", job.id, printer_id);
 
-        let printers = self.printers.lock().await;
-        let printer = printers
-            .get(printer_id)
+        let printer = self
+            .get_printer(printer_id)
+            .await
             .ok_or_else(|| DaemonError::PrinterNotFound(printer_id.to_string()))?;
 
-        let commands = format_kitchen_receipt(
-            &job.station,
-            &job.order_number,
-            job.order_type.as_deref(),
-            job.table_number.as_deref(),
-            job.customer_name.as_deref(),
-            job.priority,
-            &job.items,
-            job.timestamp,
-            PaperWidth::Width80mm,
-        );
+        // Quantities in modifiers and notes in the venue's units ("0,5 l", "16.9 fl oz")
+        let units = *self.units.read().unwrap_or_else(|e| e.into_inner());
+        let job = &PrintJob {
+            items: units::localize_items(&job.items, units),
+            removed_items: units::localize_items(&job.removed_items, units),
+            ..job.clone()
+        };
 
+        // Thumbnails are fetched before taking the printer's I/O slot (a first download can
+        // take seconds). BLE printers move ~1-2 KB/s, so images would hold up the ticket: skipped.
+        let thumbnails = if matches!(printer.connection_type, ConnectionType::Bluetooth) {
+            Thumbnails::new()
+        } else {
+            let paper_width = PaperWidth::from_chars(printer.capabilities.max_width);
+            self.item_images.thumbnails(&job.items, paper_width).await
+        };
+
+        let mut blocks = self.receipt_fields.read().await.render(&OrderFields {
+            order_number: &job.order_number,
+            pickup_time: job.pickup_time,
+        });
+        let paper_width = PaperWidth::from_chars(printer.capabilities.max_width);
+        // Like thumbnails, the logo would cost a Bluetooth printer seconds per ticket
+        if !matches!(printer.connection_type, ConnectionType::Bluetooth) {
+            if let Some(template) = self.kitchen_logo.read().await.as_ref() {
+                blocks.logo = venue_logo(template, paper_width);
+            }
+        }
+        let commands = match self.receipt_templates.read().await.for_station(&job.station) {
+            // Void and amend tickets have one layout, template or not
+            _ if job.kind != JobKind::Order => format_change_ticket(
+                job.kind,
+                &job.station,
+                &job.order_number,
+                job.table_number.as_deref(),
+                job.customer_name.as_deref(),
+                &job.items,
+                &job.removed_items,
+                job.timestamp,
+                Some(job.short_ref()),
+                paper_width,
+                printer.receipt_layout(),
+                &blocks,
+            ),
+            // Templates lay out the ticket themselves: no thumbnails or compact layout
+            Some(template) => template.render(
+                &TicketData {
+                    station: &job.station,
+                    order_number: &job.order_number,
+                    order_type: job.order_type.as_deref(),
+                    table_number: job.table_number.as_deref(),
+                    customer_name: job.customer_name.as_deref(),
+                    priority: job.priority,
+                    items: &job.items,
+                    timestamp: job.timestamp,
+                    pickup_time: job.pickup_time,
+                    reference: Some(job.short_ref()),
+                },
+                job.copies,
+                paper_width,
+                &blocks,
+            ),
+            None => format_kitchen_receipt(
+                &job.station,
+                &job.order_number,
+                job.order_type.as_deref(),
+                job.table_number.as_deref(),
+                job.customer_name.as_deref(),
+                job.priority,
+                &job.items,
+                job.timestamp,
+                job.copies,
+                Some(job.short_ref()),
+                &thumbnails,
+                paper_width,
+                printer.receipt_layout(),
+                &blocks,
+            ),
+        };
+
+        // The acknowledgment must answer for this ticket: no other exchange in between.
+        // Waiting for the slot is the last point where the job can still be interrupted.
+        let _io = tokio::select! {
+            io = self.io(printer_id) => io,
+            _ = cancel.cancelled() => {
+                return Err(DaemonError::Interrupted(format!("waiting for printer {}", printer_id)));
+            }
+        };
+        let transport = self.send(&printer, &format!("Order {}", job.order_number), &commands).await?;
+        let sent_at_ms = chrono::Utc::now().timestamp_millis();
+
+        let ack = match transport {
+            // The write only proves the bytes left; only count the job once the printer agrees
+            Transport::Raw => self.verify_write(&printer).await,
+            // ePOS-Print answers once the ticket is out
+            Transport::Epos => WriteAck::Confirmed,
+            // Accepted into the printer's IPP queue
+            Transport::Ipp => WriteAck::Unconfirmed,
+        };
+        match ack {
+            WriteAck::Rejected(reason) => {
+                warn!("Printer {} rejected job {}: {}", printer_id, job.id, reason);
+                Err(DaemonError::WriteRejected(reason))
+            }
+            ack => Ok(Delivery::new(sent_at_ms, commands.len(), &ack)),
+        }
+    }
+
+    /// Send bytes through the printer's transports (`PrinterConfig.transports`), the last
+    /// working one first, until one takes them. Returns the transport that did; when all
+    /// fail, the error of the last one tried. Moving the job to another printer is left to
+    /// failover.
+    async fn send(&self, printer: &PrinterConfig, job_name: &str, data: &[u8]) -> Result<Transport> {
+        let ladder = self.transports.lock().await.order(printer);
+        let mut last_error = None;
+        for (i, transport) in ladder.iter().copied().enumerate() {
+            let result = match transport {
+                Transport::Raw => self.send_raw(printer, data).await,
+                Transport::Ipp => transport::send_ipp(&self.http, &printer.address, job_name, data).await,
+                Transport::Epos => transport::send_epos(&self.http, &printer.address, data).await,
+            };
+            match result {
+                Ok(()) => {
+                    self.transports.lock().await.succeeded(&printer.id, transport);
+                    return Ok(transport);
+                }
+                Err(e) => {
+                    if let Some(next) = ladder.get(i + 1) {
+                        warn!(
+                            "Printer {} failed over {} ({}), trying {}",
+                            printer.id,
+                            transport.as_str(),
+                            e,
+                            next.as_str()
+                        );
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| DaemonError::PrintJob(format!("No transport for printer {}", printer.id))))
+    }
+
+    /// Write bytes over the printer's own connection (TCP 9100, USB, BLE) with a timeout
+    /// sized to them, and learn the printer's throughput from the write
+    async fn send_raw(&self, printer: &PrinterConfig, data: &[u8]) -> Result<()> {
+        let timeout = self.write_timeout(Some(&printer.id), &printer.connection_type, data.len());
+        let start = Instant::now();
         match printer.connection_type {
-            ConnectionType::USB => self.print_usb(&printer.address, &commands).await,
-            ConnectionType::Network => self.print_network(&printer.address, &commands).await,
-            ConnectionType::Bluetooth => self.print_bluetooth(&printer.address, &commands).await,
+            ConnectionType::USB => self.print_usb(&printer.address, data, timeout).await?,
+            ConnectionType::Network => self.print_network(Some(&printer.id), &printer.address, data, timeout).await?,
+            ConnectionType::Bluetooth => self.print_bluetooth(&printer.address, data, timeout).await?,
+            ConnectionType::Serial => {
+                self.print_serial(&printer.address, printer.serial.unwrap_or_default(), data, timeout).await?
+            }
         }
+        self.write_timeouts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .record(&printer.id, data.len(), start.elapsed());
+        Ok(())
+    }
+
+    fn write_timeout(&self, printer_id: Option<&str>, connection: &ConnectionType, bytes: usize) -> Duration {
+        self.write_timeouts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .timeout(printer_id, connection, bytes)
+    }
+
+    /// Take a registered printer's I/O slot (None for printers not in the registry)
+    async fn io(&self, printer_id: &str) -> Option<OwnedMutexGuard<()>> {
+        let io = self.printers.read().await.get(printer_id).map(|p| p.io.clone())?;
+        Some(task_metrics::lock_owned(io, "printer_io").await)
+    }
+
+    /// Last working transport per printer
+    pub async fn active_transports(&self) -> HashMap<String, Transport> {
+        self.transports.lock().await.snapshot()
     }
 
     /// Print via USB
     ///
     /// Handles macOS-specific USB permission errors with user-friendly messages.
     /// On macOS, USB access requires entitlements in the app bundle.
-    async fn print_usb(&self, address: &str, data: &[u8]) -> Result<()> {
+    async fn print_usb(&self, address: &str, data: &[u8], timeout: Duration) -> Result<()> {
         // Parse device path: /dev/bus/usb/001/002
         let parts: Vec<&str> = address.split('/').collect();
         if parts.len() < 6 {
@@ -334,7 +560,6 @@ impl PrinterManager {
                 })?;
 
                 // Write data to OUT endpoint (typically 0x01 or 0x02)
-                let timeout = Duration::from_secs(5);
                 if let Err(e) = handle.write_bulk(0x01, data, timeout) {
                     handle.release_interface(0).ok();
                     return Err(DaemonError::PrintJob(format!("USB write failed: {}", e)));
@@ -351,27 +576,30 @@ impl PrinterManager {
     /// Print via network (raw TCP port 9100) with persistent connection pool.
     ///
     /// Connection pool strategy:
-    /// 1. Check pool for existing connection to this address
+    /// 1. Check pool for the printer's connection (dropped if it points at another address)
     /// 2. If found: attempt write (reuse connection)
     /// 3. If write fails: remove from pool, create new connection, retry once
     /// 4. If not found: create new connection, add to pool after successful write
     ///
-    /// Timeouts: Connect 5s, Write 20s, Flush 5s
-    async fn print_network(&self, address: &str, data: &[u8]) -> Result<()> {
+    /// `printer_id` None (direct test print to an unregistered address): never pooled.
+    ///
+    /// Timeouts: Connect 5s, Write `timeout` (sized to the payload, see `write_timeout.rs`), Flush 5s
+    async fn print_network(&self, printer_id: Option<&str>, address: &str, data: &[u8], timeout: Duration) -> Result<()> {
         use tokio::io::AsyncWriteExt;
 
+        if crate::fault_injection::drop_network_write(address) {
+            return Err(DaemonError::Network(format!("Connection reset by peer writing to {} (injected)", address)));
+        }
+
         // Try to reuse a pooled connection
-        let mut pooled_stream = {
-            let mut pool = self.network_pool.lock().await;
-            pool.remove(address)
-        };
+        let mut pooled_stream = self.take_pooled(printer_id, address).await;
 
         if let Some(mut conn) = pooled_stream.take() {
             debug!("Reusing pooled connection to {} (age: {:?})", address, conn.connected_at.elapsed());
 
             // Attempt write on existing connection
             let write_result = tokio::time::timeout(
-                Duration::from_secs(20),
+                timeout,
                 conn.stream.write_all(data),
             ).await;
 
@@ -388,8 +616,7 @@ impl PrinterManager {
                             // Success — return connection to pool
                             conn.last_used = Instant::now();
                             conn.consecutive_failures = 0;
-                            let mut pool = self.network_pool.lock().await;
-                            pool.insert(address.to_string(), conn);
+                            self.return_pooled(printer_id, conn).await;
                             return Ok(());
                         }
                         _ => {
@@ -417,13 +644,14 @@ impl PrinterManager {
         // Set TCP keepalive on new connections
         Self::set_tcp_keepalive(&stream);
 
-        // Write with 20s timeout
         tokio::time::timeout(
-            Duration::from_secs(20),
+            timeout,
             stream.write_all(data),
         )
         .await
-        .map_err(|_| DaemonError::Network(format!("Write timed out to {} ({} bytes)", address, data.len())))?
+        .map_err(|_| {
+            DaemonError::Network(format!("Write timed out to {} ({} bytes in {:?})", address, data.len(), timeout))
+        })?
         .map_err(|e| DaemonError::Network(e.to_string()))?;
 
         // Flush with 5s timeout
@@ -444,13 +672,30 @@ impl PrinterManager {
             last_used: now,
             consecutive_failures: 0,
         };
-        let mut pool = self.network_pool.lock().await;
-        pool.insert(address.to_string(), conn);
-        debug!("Added new connection to pool for {} (pool size: {})", address, pool.len());
+        self.return_pooled(printer_id, conn).await;
 
         Ok(())
     }
 
+    /// Take a printer's pooled connection, if it still points at `address`
+    async fn take_pooled(&self, printer_id: Option<&str>, address: &str) -> Option<NetworkConnection> {
+        let printer_id = printer_id?;
+        let conn = self.network_pool.lock().await.remove(printer_id)?;
+        if conn.address != address {
+            debug!("Dropping pooled connection of {} to old address {}", printer_id, conn.address);
+            return None;
+        }
+        Some(conn)
+    }
+
+    /// Put a working connection (back) in the pool under its printer
+    async fn return_pooled(&self, printer_id: Option<&str>, conn: NetworkConnection) {
+        let Some(printer_id) = printer_id else { return };
+        let mut pool = self.network_pool.lock().await;
+        pool.insert(printer_id.to_string(), conn);
+        debug!("Pooled connection for {} (pool size: {})", printer_id, pool.len());
+    }
+
     /// Configure TCP keepalive on a tokio TcpStream to detect dead connections.
     /// Keepalive: idle 30s, interval 10s. Uses socket2 via raw fd/socket.
     #[cfg(unix)]
@@ -499,10 +744,10 @@ impl PrinterManager {
     pub async fn cleanup_stale_connections(&self, max_idle_secs: u64) -> (usize, usize) {
         let mut pool = self.network_pool.lock().await;
         let before = pool.len();
-        pool.retain(|addr, conn| {
+        pool.retain(|printer_id, conn| {
             let idle = conn.last_used.elapsed().as_secs() > max_idle_secs;
             if idle {
-                debug!("Removing stale connection to {} (idle {:?})", addr, conn.last_used.elapsed());
+                debug!("Removing stale connection of {} to {} (idle {:?})", printer_id, conn.address, conn.last_used.elapsed());
             }
             !idle
         });
@@ -514,17 +759,36 @@ impl PrinterManager {
         (removed, active)
     }
 
+    /// Print over an RS-232 port. The port is opened per write (it can't be shared with
+    /// other programs anyway) and written in chunks on the blocking pool.
+    async fn print_serial(&self, address: &str, settings: SerialSettings, data: &[u8], timeout: Duration) -> Result<()> {
+        info!("Serial print to {} ({} bytes at {} baud)", address, data.len(), settings.baud_rate);
+        let address = address.to_string();
+        let data = data.to_vec();
+        tokio::task::spawn_blocking(move || serial::write_blocking(&address, &settings, &data, timeout))
+            .await
+            .map_err(|e| DaemonError::Other(anyhow::anyhow!("Serial print task failed: {}", e)))?
+    }
+
+    /// Real-time query over a serial printer's port (see `serial::query_blocking`)
+    async fn query_serial(&self, printer: &PrinterConfig, request: Vec<u8>, len: Option<usize>) -> Result<Vec<u8>> {
+        let address = printer.address.clone();
+        let settings = printer.serial.unwrap_or_default();
+        tokio::task::spawn_blocking(move || serial::query_blocking(&address, &settings, &request, len))
+            .await
+            .map_err(|e| DaemonError::Other(anyhow::anyhow!("Serial query task failed: {}", e)))?
+    }
+
     /// Print via Bluetooth BLE
     ///
-    /// Discovers the BLE peripheral by address, connects, finds a writable
-    /// GATT characteristic, and sends data in 20-byte chunks (safe BLE MTU minimum).
+    /// Finds the BLE peripheral by address (`ble.rs`), connects, finds a writable
+    /// GATT characteristic, and sends data in chunks sized to what the link carries.
     ///
     /// Known printer service/characteristic UUIDs are tried first (Star Micronics,
     /// generic BLE printer). Falls back to first characteristic with WRITE_WITHOUT_RESPONSE
     /// or WRITE property.
-    async fn print_bluetooth(&self, address: &str, data: &[u8]) -> Result<()> {
-        use btleplug::api::{Central, CharPropFlags, Manager as _, Peripheral as _, ScanFilter, WriteType};
-        use btleplug::platform::Manager;
+    async fn print_bluetooth(&self, address: &str, data: &[u8], timeout: Duration) -> Result<()> {
+        use btleplug::api::{CharPropFlags, Peripheral as _, WriteType};
         use uuid::Uuid;
 
         // Known BLE printer GATT characteristic UUIDs
@@ -534,49 +798,12 @@ impl PrinterManager {
 
         info!("BLE print requested for address: {} ({} bytes)", address, data.len());
 
-        // 1. Get BLE manager and adapter
-        let manager = Manager::new()
-            .await
-            .map_err(|e| DaemonError::Bluetooth(format!("Failed to create BLE manager: {}", e)))?;
-
-        let adapters = manager.adapters()
-            .await
-            .map_err(|e| DaemonError::Bluetooth(format!("Failed to get BLE adapters: {}", e)))?;
+        // 1. Announce the print so discovery and online checks keep off the radio
+        let ble = crate::ble::coordinator();
+        let _lease = ble.print_lease();
 
-        let adapter = adapters
-            .first()
-            .ok_or_else(|| DaemonError::Bluetooth("No Bluetooth adapters found".to_string()))?;
-
-        // 2. Brief scan to ensure peripheral is discoverable (macOS CoreBluetooth needs this)
-        adapter
-            .start_scan(ScanFilter::default())
-            .await
-            .map_err(|e| DaemonError::Bluetooth(format!("Failed to start BLE scan: {}", e)))?;
-
-        tokio::time::sleep(Duration::from_secs(3)).await;
-
-        adapter.stop_scan().await.ok(); // best-effort stop
-
-        // 3. Find peripheral by address
-        let peripherals = adapter
-            .peripherals()
-            .await
-            .map_err(|e| DaemonError::Bluetooth(format!("Failed to list peripherals: {}", e)))?;
-
-        let peripheral = {
-            let mut found = None;
-            for p in &peripherals {
-                if let Ok(Some(props)) = p.properties().await {
-                    if props.address.to_string() == address {
-                        found = Some(p);
-                        break;
-                    }
-                }
-            }
-            found.ok_or_else(|| {
-                DaemonError::Bluetooth(format!("Peripheral not found: {}", address))
-            })?
-        };
+        // 2-3. Find the peripheral on the shared adapter (short scan only if it isn't known yet)
+        let peripheral = ble.locate(address).await?;
 
         // 4. Connect with timeout
         tokio::time::timeout(Duration::from_secs(10), peripheral.connect())
@@ -638,12 +865,23 @@ impl PrinterManager {
             write_char.uuid, write_char.service_uuid, write_type
         );
 
-        // 6. Write data in chunks with adaptive sizing
-        // Start with 100-byte chunks (5x throughput vs 20B), fallback to 20B on error
-        let mut chunk_size: usize = 100;
+        // 6. Write data in chunks with adaptive sizing. btleplug doesn't expose the
+        // negotiated MTU, so the size is found by trying: start with the size that worked
+        // for this printer last time (the largest for a new one), step down on a failed write
+        let mut chunk_size = ble.chunk_size(address);
         let mut offset = 0;
+        let deadline = Instant::now() + timeout;
 
         while offset < data.len() {
+            if Instant::now() >= deadline {
+                let _ = peripheral.disconnect().await;
+                return Err(DaemonError::Bluetooth(format!(
+                    "Write timed out after {:?} ({} of {} bytes sent)",
+                    timeout,
+                    offset,
+                    data.len()
+                )));
+            }
             let end = std::cmp::min(offset + chunk_size, data.len());
             let chunk = &data[offset..end];
 
@@ -657,10 +895,11 @@ impl PrinterManager {
                 Ok(Ok(_)) => {
                     offset = end;
                 }
-                Ok(Err(e)) if chunk_size > 20 => {
+                Ok(Err(e)) if chunk_size > crate::ble::MIN_CHUNK => {
                     // Adaptive fallback: retry this chunk with smaller size
-                    warn!("BLE write failed with {}B chunks, falling back to 20B: {}", chunk_size, e);
-                    chunk_size = 20;
+                    let smaller = crate::ble::smaller_chunk(chunk_size);
+                    warn!("BLE write failed with {}B chunks, trying {}B: {}", chunk_size, smaller, e);
+                    chunk_size = smaller;
                     continue; // Retry same offset with smaller chunk
                 }
                 Ok(Err(e)) => {
@@ -677,6 +916,7 @@ impl PrinterManager {
             tokio::time::sleep(Duration::from_millis(10)).await;
         }
 
+        ble.chunk_size_worked(address, chunk_size);
         let chunks_sent = (data.len() + chunk_size - 1) / chunk_size;
         info!("BLE print complete: {} bytes sent in ~{} chunks ({}B each)", data.len(), chunks_sent, chunk_size);
 
@@ -688,51 +928,127 @@ impl PrinterManager {
         Ok(())
     }
 
-    /// Add printer to managed list
+    /// Add printer to managed list (or update it; a changed address drops its cached state)
     pub async fn add_printer(&self, config: PrinterConfig) {
-        let mut printers = self.printers.lock().await;
-        printers.insert(config.id.clone(), config);
+        let previous = {
+            let mut printers = self.printers.write().await;
+            let io = printers.get(&config.id).map(|p| p.io.clone()).unwrap_or_default();
+            printers
+                .insert(config.id.clone(), PrinterHandle { config: config.clone(), io })
+                .map(|p| p.config)
+        };
+        match previous {
+            Some(old) if old.address == config.address => {}
+            Some(old) => self.invalidate_printer(&old.id).await,
+            None => self.invalidate_printer(&config.id).await,
+        }
     }
 
     /// Remove printer from managed list
     pub async fn remove_printer(&self, printer_id: &str) {
-        let mut printers = self.printers.lock().await;
-        printers.remove(printer_id);
+        self.printers.write().await.remove(printer_id);
+        self.invalidate_printer(printer_id).await;
+    }
+
+    /// Apply item thumbnail settings (config save / reload)
+    pub async fn set_item_images(&self, config: ItemImageConfig) {
+        self.item_images.set_config(config).await;
+    }
+
+    /// Apply write timeout bounds (config save / reload)
+    pub fn set_write_timeouts(&self, config: WriteTimeoutConfig) {
+        self.write_timeouts.lock().unwrap_or_else(|e| e.into_inner()).set_config(config);
+    }
+
+    /// Apply the unit system and locale of ticket quantities (config save / reload)
+    pub fn set_units(&self, format: UnitFormat) {
+        *self.units.write().unwrap_or_else(|e| e.into_inner()) = format;
+    }
+
+    /// Apply the logo settings of kitchen tickets (config save / reload)
+    pub async fn set_kitchen_logo(&self, template: &TestPrintTemplate) {
+        *self.kitchen_logo.write().await = template.logo_on_kitchen_tickets.then(|| template.clone());
+    }
+
+    /// Apply receipt header / footer settings (config save / reload)
+    pub async fn set_receipt_fields(&self, config: ReceiptFieldsConfig) {
+        *self.receipt_fields.write().await = config;
+    }
+
+    /// Compile and apply the per-station ticket templates (config save / reload)
+    pub async fn set_receipt_templates(&self, config: &ReceiptTemplatesConfig) {
+        *self.receipt_templates.write().await = ReceiptTemplates::new(config);
+    }
+
+    /// Replace the managed list with the configured printers (config save / reload).
+    /// Printers missing from `configs` are removed.
+    pub async fn sync_printers(&self, configs: &[PrinterConfig]) {
+        let stale: Vec<String> = {
+            let printers = self.printers.read().await;
+            printers
+                .keys()
+                .filter(|id| !configs.iter().any(|c| &c.id == *id))
+                .cloned()
+                .collect()
+        };
+        for id in stale {
+            self.remove_printer(&id).await;
+        }
+        for config in configs {
+            self.add_printer(config.clone()).await;
+        }
+    }
+
+    /// Forget cached online state for a printer, and its pooled connection
+    async fn invalidate_printer(&self, printer_id: &str) {
+        self.online_cache.lock().await.remove(printer_id);
+        // A different device may be answering now
+        self.ack_misses.lock().await.remove(printer_id);
+        self.transports.lock().await.forget(printer_id);
+        self.write_timeouts.lock().unwrap_or_else(|e| e.into_inner()).forget(printer_id);
+        self.network_pool.lock().await.remove(printer_id);
+        debug!("Invalidated cached state for printer {}", printer_id);
+    }
+
+    /// Drop all cached reachability: online checks, discovery results and pooled
+    /// connections. Used when the machine's network changes.
+    pub async fn invalidate_all(&self) {
+        self.online_cache.lock().await.clear();
+        self.discovery_cache.lock().await.completed_at = None;
+        self.network_pool.lock().await.clear();
+        info!("Printer caches invalidated");
     }
 
     /// Get printer by ID
-    #[allow(dead_code)] // Public API for future callers
     pub async fn get_printer(&self, printer_id: &str) -> Option<PrinterConfig> {
-        let printers = self.printers.lock().await;
-        printers.get(printer_id).cloned()
+        let printers = self.printers.read().await;
+        printers.get(printer_id).map(|p| p.config.clone())
     }
 
     /// Check if printer is online (with 30-second cache)
+    ///
+    /// Configured printers are checked directly on their own connection; a full
+    /// discovery scan is only used for printers without a known address.
     pub async fn is_online(&self, printer_id: &str) -> bool {
-        use std::time::Instant;
-
         // Check cache first (30-second TTL)
         {
             let cache = self.online_cache.lock().await;
             if let Some((is_online, cached_at)) = cache.get(printer_id) {
-                if cached_at.elapsed() < Duration::from_secs(30) {
+                if cached_at.elapsed() < Duration::from_secs(ONLINE_CACHE_TTL_SECS) {
                     debug!("Using cached online status for printer: {} = {}", printer_id, is_online);
                     return *is_online;
                 }
             }
         }
 
-        // Cache miss or expired, perform discovery
         debug!("Checking online status for printer: {}", printer_id);
-        let is_online = if let Ok(discovered) = self.discover_all(false).await {
-            discovered.iter().any(|p| {
-                p.get("id")
-                    .and_then(|id| id.as_str())
-                    .map_or(false, |id| id == printer_id)
-            })
-        } else {
-            error!("Failed to discover printers during online check");
-            false
+        let printer = self.get_printer(printer_id).await.filter(|p| !p.address.is_empty());
+        let is_online = match printer {
+            Some(printer) => {
+                let _io = self.io(printer_id).await;
+                self.check_reachable(&printer).await
+            }
+            None => self.is_discovered(printer_id).await,
         };
 
         // Update cache
@@ -745,6 +1061,66 @@ impl PrinterManager {
         is_online
     }
 
+    /// Direct reachability check on the printer's own connection
+    async fn check_reachable(&self, printer: &PrinterConfig) -> bool {
+        match printer.connection_type {
+            ConnectionType::Network => {
+                // DLE EOT answer (reuses a pooled connection), else a bare TCP connect
+                // for printers that don't answer status requests
+                self.poll_status_network(&printer.id, &printer.address).await.is_ok()
+                    || tokio::time::timeout(
+                        Duration::from_secs(ONLINE_CHECK_TIMEOUT_SECS),
+                        TcpStream::connect(&printer.address),
+                    )
+                    .await
+                    .is_ok_and(|connected| connected.is_ok())
+            }
+            ConnectionType::USB => {
+                // Device descriptor present on the bus
+                let usb_ctx = self.usb_context.clone();
+                let address = printer.address.clone();
+                tokio::task::spawn_blocking(move || find_usb_device(&usb_ctx, &address).is_ok())
+                    .await
+                    .unwrap_or(false)
+            }
+            ConnectionType::Bluetooth => ble_advertising(&printer.address).await.unwrap_or_else(|e| {
+                warn!("BLE online check failed for {}: {}", printer.address, e);
+                false
+            }),
+            // DLE EOT answer, else the port at least exists (printers with the status
+            // lines unwired never answer)
+            ConnectionType::Serial => {
+                self.read_status(printer).await.is_ok() || {
+                    let address = printer.address.clone();
+                    tokio::task::spawn_blocking(move || serial::port_present(&address)).await.unwrap_or(false)
+                }
+            }
+        }
+    }
+
+    /// Fallback for printers without a known address: look for them in discovery results
+    async fn is_discovered(&self, printer_id: &str) -> bool {
+        // A scan in progress may already have found it
+        {
+            let discovery = self.discovery_cache.lock().await;
+            if discovery.scanning && discovery.results.contains_id(printer_id) {
+                debug!("Printer {} found in partial discovery results", printer_id);
+                return true;
+            }
+        }
+
+        if let Ok(discovered) = self.discover_all(false).await {
+            discovered.iter().any(|p| {
+                p.get("id")
+                    .and_then(|id| id.as_str())
+                    .map_or(false, |id| id == printer_id)
+            })
+        } else {
+            error!("Failed to discover printers during online check");
+            false
+        }
+    }
+
     // =========================================================================
     // DLE EOT Real-time Status Polling
     // =========================================================================
@@ -753,8 +1129,14 @@ impl PrinterManager {
     /// Returns structured status for network and USB printers.
     /// BLE printers return a healthy default (DLE EOT not reliably supported over BLE).
     pub async fn poll_status(&self, printer: &PrinterConfig) -> Result<PrinterHwStatus> {
+        let _io = self.io(&printer.id).await;
+        self.read_status(printer).await
+    }
+
+    /// `poll_status` for a caller already holding the printer's I/O slot
+    async fn read_status(&self, printer: &PrinterConfig) -> Result<PrinterHwStatus> {
         match printer.connection_type {
-            ConnectionType::Network => self.poll_status_network(&printer.address).await,
+            ConnectionType::Network => self.poll_status_network(&printer.id, &printer.address).await,
             ConnectionType::USB => {
                 // USB I/O is synchronous (rusb) — run on blocking thread pool
                 // to avoid stalling the tokio async runtime
@@ -770,32 +1152,43 @@ impl PrinterManager {
                 debug!("Skipping DLE EOT status poll for BLE printer {}", printer.id);
                 Ok(PrinterHwStatus::healthy())
             }
+            ConnectionType::Serial => {
+                let response = self.query_serial(printer, build_full_status_request(), Some(4)).await?;
+                Ok(PrinterHwStatus::from_dle_eot(response[0], response[1], response[2], response[3]))
+            }
         }
     }
 
     /// Poll status via TCP: send all 4 DLE EOT requests, read 4-byte response.
+    async fn poll_status_network(&self, printer_id: &str, address: &str) -> Result<PrinterHwStatus> {
+        if crate::fault_injection::status_poll_timeout(address) {
+            return Err(DaemonError::Network(format!("Status poll connect timed out to {} (injected)", address)));
+        }
+
+        let response: [u8; 4] = self.query_network(printer_id, address, &build_full_status_request()).await?;
+        Ok(PrinterHwStatus::from_dle_eot(
+            response[0], response[1], response[2], response[3],
+        ))
+    }
+
+    /// Send real-time (DLE EOT) requests over TCP and read one byte per request.
     /// Reuses persistent connection pool when available; falls back to ephemeral connection.
-    async fn poll_status_network(&self, address: &str) -> Result<PrinterHwStatus> {
+    async fn query_network<const N: usize>(&self, printer_id: &str, address: &str, request: &[u8]) -> Result<[u8; N]> {
         use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-        let request = build_full_status_request();
-
         // Try to reuse a pooled connection first
-        let mut pooled_conn = {
-            let mut pool = self.network_pool.lock().await;
-            pool.remove(address)
-        };
+        let mut pooled_conn = self.take_pooled(Some(printer_id), address).await;
 
         if let Some(mut conn) = pooled_conn.take() {
             debug!("Status poll reusing pooled connection to {}", address);
 
             let poll_result = async {
-                tokio::time::timeout(Duration::from_secs(2), conn.stream.write_all(&request))
+                tokio::time::timeout(Duration::from_secs(2), conn.stream.write_all(request))
                     .await
                     .map_err(|_| DaemonError::Network(format!("Status poll write timed out to {}", address)))?
                     .map_err(|e| DaemonError::Network(e.to_string()))?;
 
-                let mut response = [0u8; 4];
+                let mut response = [0u8; N];
                 tokio::time::timeout(Duration::from_secs(2), conn.stream.read_exact(&mut response))
                     .await
                     .map_err(|_| DaemonError::Network(format!("Status poll read timed out from {}", address)))?
@@ -808,11 +1201,8 @@ impl PrinterManager {
                 Ok(response) => {
                     // Success — return connection to pool with updated timestamp
                     conn.last_used = Instant::now();
-                    let mut pool = self.network_pool.lock().await;
-                    pool.insert(address.to_string(), conn);
-                    return Ok(PrinterHwStatus::from_dle_eot(
-                        response[0], response[1], response[2], response[3],
-                    ));
+                    self.return_pooled(Some(printer_id), conn).await;
+                    return Ok(response);
                 }
                 Err(e) => {
                     // Stale connection — drop it, fall through to ephemeral
@@ -830,114 +1220,511 @@ impl PrinterManager {
         .map_err(|_| DaemonError::Network(format!("Status poll connect timed out to {}", address)))?
         .map_err(|e| DaemonError::Network(format!("Status poll connect failed to {}: {}", address, e)))?;
 
-        tokio::time::timeout(Duration::from_secs(2), stream.write_all(&request))
+        tokio::time::timeout(Duration::from_secs(2), stream.write_all(request))
             .await
             .map_err(|_| DaemonError::Network(format!("Status poll write timed out to {}", address)))?
             .map_err(|e| DaemonError::Network(e.to_string()))?;
 
-        let mut response = [0u8; 4];
+        let mut response = [0u8; N];
         tokio::time::timeout(Duration::from_secs(2), stream.read_exact(&mut response))
             .await
             .map_err(|_| DaemonError::Network(format!("Status poll read timed out from {}", address)))?
             .map_err(|e| DaemonError::Network(format!("Status poll read failed from {}: {}", address, e)))?;
 
-        Ok(PrinterHwStatus::from_dle_eot(
-            response[0], response[1], response[2], response[3],
-        ))
+        Ok(response)
+    }
+
+    /// Try to clear a mechanical fault without a power cycle: send DLE ENQ 2 + ESC @,
+    /// give the printer `SETTLE_MS` and read its status again.
+    pub async fn attempt_error_recovery(&self, printer: &PrinterConfig) -> Result<PrinterHwStatus> {
+        info!("Attempting error recovery on printer {}", printer.id);
+        let commands = build_error_recovery();
+        let _io = self.io(&printer.id).await;
+        self.send_raw(printer, &commands).await?;
+        tokio::time::sleep(Duration::from_millis(crate::jam_recovery::SETTLE_MS)).await;
+        self.read_status(printer).await
+    }
+
+    /// Ask the printer whether the job just written went through (DLE EOT 2 + 3).
+    ///
+    /// Printers that never answer are skipped after `ACK_MAX_MISSES` unanswered queries,
+    /// so ones without DLE EOT support don't pay the read timeout on every job.
+    pub async fn verify_write(&self, printer: &PrinterConfig) -> WriteAck {
+        if self.ack_misses.lock().await.get(&printer.id).is_some_and(|m| *m >= ACK_MAX_MISSES) {
+            return WriteAck::Unconfirmed;
+        }
+
+        let request = build_write_ack_request();
+        let response = match printer.connection_type {
+            ConnectionType::Network => self.query_network::<2>(&printer.id, &printer.address, &request).await,
+            ConnectionType::USB => {
+                let usb_ctx = self.usb_context.clone();
+                let address = printer.address.clone();
+                tokio::task::spawn_blocking(move || query_usb_blocking::<2>(&usb_ctx, &address, &request))
+                    .await
+                    .map_err(|e| DaemonError::Other(anyhow::anyhow!("USB ack task failed: {}", e)))
+                    .and_then(|r| r)
+            }
+            // DLE EOT not reliably supported over BLE
+            ConnectionType::Bluetooth => return WriteAck::Unconfirmed,
+            ConnectionType::Serial => self
+                .query_serial(printer, request.to_vec(), Some(2))
+                .await
+                .map(|bytes| [bytes[0], bytes[1]]),
+        };
+
+        let ack = match response {
+            Ok(bytes) => WriteAck::from_dle_eot(bytes[0], bytes[1]),
+            Err(e) => {
+                debug!("No write acknowledgment from printer {}: {}", printer.id, e);
+                WriteAck::Unconfirmed
+            }
+        };
+
+        let mut misses = self.ack_misses.lock().await;
+        if ack == WriteAck::Unconfirmed {
+            let count = misses.entry(printer.id.clone()).or_insert(0);
+            *count += 1;
+            if *count == ACK_MAX_MISSES {
+                info!(
+                    "Printer {} does not answer status queries, jobs will no longer be verified",
+                    printer.id
+                );
+            }
+        } else {
+            misses.remove(&printer.id);
+        }
+        ack
+    }
+
+    /// Ask the printer for its model name (GS I 67). `Ok(None)` when it answers with
+    /// something that isn't a model name; BLE printers are not asked.
+    pub async fn query_model(&self, printer: &PrinterConfig) -> Result<Option<String>> {
+        // The answer must not mix with a ticket's acknowledgment or a status poll
+        let _io = self.io(&printer.id).await;
+        let request = build_model_request();
+        let response = match printer.connection_type {
+            ConnectionType::Network => query_network_text(&printer.address, &request).await?,
+            ConnectionType::USB => {
+                let usb_ctx = self.usb_context.clone();
+                let address = printer.address.clone();
+                tokio::task::spawn_blocking(move || query_usb_blocking::<64>(&usb_ctx, &address, &request))
+                    .await
+                    .map_err(|e| DaemonError::Other(anyhow::anyhow!("USB model query task failed: {}", e)))??
+                    .to_vec()
+            }
+            ConnectionType::Bluetooth => return Ok(None),
+            ConnectionType::Serial => self.query_serial(printer, request.to_vec(), None).await?,
+        };
+        Ok(parse_model_response(&response))
+    }
+
+    /// Check that a USB printer is attached and can be opened by this process.
+    /// Accepts both address formats in use: `/dev/bus/usb/BBB/AAA` and `usb_VVVV_PPPP`.
+    /// Returns `rusb::Error::Access` (wrapped) when the device exists but permissions are missing.
+    pub async fn probe_usb(&self, address: &str) -> Result<()> {
+        let usb_ctx = self.usb_context.clone();
+        let address = address.to_string();
+        tokio::task::spawn_blocking(move || {
+            let device = find_usb_device(&usb_ctx, &address)?;
+            device.open().map_err(DaemonError::Usb)?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Other(anyhow::anyhow!("USB probe task failed: {}", e)))?
+    }
+
+    /// Vendor and product ID of an attached USB device (for udev rule generation)
+    pub async fn usb_device_ids(&self, address: &str) -> Result<(u16, u16)> {
+        let usb_ctx = self.usb_context.clone();
+        let address = address.to_string();
+        tokio::task::spawn_blocking(move || {
+            let device = find_usb_device(&usb_ctx, &address)?;
+            let desc = device.device_descriptor().map_err(DaemonError::Usb)?;
+            Ok((desc.vendor_id(), desc.product_id()))
+        })
+        .await
+        .map_err(|e| DaemonError::Other(anyhow::anyhow!("USB lookup task failed: {}", e)))?
     }
 
     /// Get a snapshot of all configured printers (for status polling)
     pub async fn get_all_printers(&self) -> Vec<PrinterConfig> {
-        let printers = self.printers.lock().await;
-        printers.values().cloned().collect()
+        let printers = self.printers.read().await;
+        printers.values().map(|p| p.config.clone()).collect()
+    }
+}
+
+/// Send a GS I request over an ephemeral connection and read the NUL-terminated answer
+async fn query_network_text(address: &str, request: &[u8]) -> Result<Vec<u8>> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut stream = tokio::time::timeout(Duration::from_secs(2), TcpStream::connect(address))
+        .await
+        .map_err(|_| DaemonError::Network(format!("Model query connect timed out to {}", address)))?
+        .map_err(|e| DaemonError::Network(format!("Model query connect failed to {}: {}", address, e)))?;
+
+    tokio::time::timeout(Duration::from_secs(2), stream.write_all(request))
+        .await
+        .map_err(|_| DaemonError::Network(format!("Model query write timed out to {}", address)))?
+        .map_err(|e| DaemonError::Network(e.to_string()))?;
+
+    let mut response = Vec::new();
+    let mut chunk = [0u8; 64];
+    while !response.contains(&0) && response.len() < 64 {
+        let n = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut chunk))
+            .await
+            .map_err(|_| DaemonError::Network(format!("Model query read timed out from {}", address)))?
+            .map_err(|e| DaemonError::Network(format!("Model query read failed from {}: {}", address, e)))?;
+        if n == 0 {
+            break;
+        }
+        response.extend_from_slice(&chunk[..n]);
+    }
+    Ok(response)
+}
+
+/// Locate a USB device by bus path (`/dev/bus/usb/001/002`) or vendor/product (`usb_04b8_0e15`).
+/// Discover USB printers from known thermal printer vendors
+fn discover_usb(usb_context: &Context) -> Result<Vec<DiscoveredPrinter>> {
+    let mut discovered = Vec::new();
+
+    for device in usb_context.devices()?.iter() {
+        let device_desc = device.device_descriptor()?;
+
+        // Check if vendor ID matches known thermal printer manufacturers
+        if let Some((_, vendor_name)) = VENDOR_IDS
+            .iter()
+            .find(|(vid, _)| *vid == device_desc.vendor_id())
+        {
+            let id = format!("usb_{:04x}_{:04x}", device_desc.vendor_id(), device_desc.product_id());
+            let name = get_usb_product_name(&device, &device_desc)
+                .unwrap_or_else(|_| format!("{} Printer", vendor_name));
+
+            let address = format!(
+                "/dev/bus/usb/{:03}/{:03}",
+                device.bus_number(),
+                device.address()
+            );
+
+            discovered.push(DiscoveredPrinter {
+                id,
+                name,
+                connection_type: "usb".to_string(),
+                address,
+                vendor: vendor_name.to_string(),
+                capabilities: Some(serde_json::json!({
+                    "cutter": true,
+                    "drawer": false,
+                    "qrcode": true,
+                    "maxWidth": 48
+                })),
+                protocol: "escpos".to_string(), // Known vendor IDs = ESC/POS
+            });
+        }
+    }
+
+    Ok(discovered)
+}
+
+/// Get USB product name from device descriptor
+fn get_usb_product_name<T: UsbContext>(
+    device: &Device<T>,
+    device_desc: &DeviceDescriptor,
+) -> Result<String> {
+    let handle = device.open()?;
+    let timeout = Duration::from_secs(1);
+    let languages = handle.read_languages(timeout)?;
+
+    if let Some(language) = languages.first() {
+        let product_string = handle
+            .read_product_string(*language, device_desc, timeout)?;
+        Ok(product_string)
+    } else {
+        Err(DaemonError::Other(anyhow::anyhow!("No language found")))
+    }
+}
+
+/// Whether a BLE peripheral with this address is advertising (short scan, no connect)
+async fn ble_advertising(address: &str) -> Result<bool> {
+    crate::ble::coordinator()
+        .is_advertising(address, Duration::from_secs(ONLINE_CHECK_TIMEOUT_SECS))
+        .await
+}
+
+fn find_usb_device(usb_context: &Context, address: &str) -> Result<Device<Context>> {
+    let devices = usb_context.devices().map_err(DaemonError::Usb)?;
+
+    if address.starts_with("usb_") {
+        let parts: Vec<&str> = address.split('_').collect();
+        if parts.len() >= 3 {
+            if let (Ok(vendor_id), Ok(product_id)) = (
+                u16::from_str_radix(parts[1], 16),
+                u16::from_str_radix(parts[2], 16),
+            ) {
+                for device in devices.iter() {
+                    if let Ok(desc) = device.device_descriptor() {
+                        if desc.vendor_id() == vendor_id && desc.product_id() == product_id {
+                            return Ok(device);
+                        }
+                    }
+                }
+            }
+        }
+    } else {
+        let parts: Vec<&str> = address.split('/').collect();
+        if parts.len() >= 6 {
+            if let (Ok(bus), Ok(addr)) = (parts[4].parse::<u8>(), parts[5].parse::<u8>()) {
+                for device in devices.iter() {
+                    if device.bus_number() == bus && device.address() == addr {
+                        return Ok(device);
+                    }
+                }
+            }
+        }
     }
+
+    Err(DaemonError::PrinterNotFound(format!("USB device not found: {}", address)))
 }
 
 /// Poll printer status via USB (standalone, runs on blocking thread pool).
 /// Extracted from PrinterManager so it can be called from spawn_blocking.
 fn poll_status_usb_blocking(usb_context: &Context, address: &str) -> Result<PrinterHwStatus> {
-    let request = build_full_status_request();
+    let response: [u8; 4] = query_usb_blocking(usb_context, address, &build_full_status_request())?;
+    Ok(PrinterHwStatus::from_dle_eot(
+        response[0],
+        response[1],
+        response[2],
+        response[3],
+    ))
+}
+
+/// Send real-time (DLE EOT) requests over USB and read one byte per request (blocking)
+fn query_usb_blocking<const N: usize>(usb_context: &Context, address: &str, request: &[u8]) -> Result<[u8; N]> {
+    let device = find_usb_device(usb_context, address)?;
+    let handle = device.open()
+        .map_err(DaemonError::Usb)?;
+
+    // Find bulk OUT and IN endpoints
+    let config = device.active_config_descriptor()
+        .map_err(DaemonError::Usb)?;
 
-    // Parse vendor:product from address (e.g., "usb_04b8_0e15")
-    let parts: Vec<&str> = address.split('_').collect();
-    if parts.len() < 3 {
-        return Err(DaemonError::PrinterNotFound(format!(
-            "Invalid USB address format for status poll: {}", address
-        )));
+    let mut out_ep = None;
+    let mut in_ep = None;
+
+    for interface in config.interfaces() {
+        for iface_desc in interface.descriptors() {
+            for ep in iface_desc.endpoint_descriptors() {
+                match ep.direction() {
+                    rusb::Direction::Out if out_ep.is_none() => {
+                        out_ep = Some(ep.address());
+                    }
+                    rusb::Direction::In if in_ep.is_none() => {
+                        in_ep = Some(ep.address());
+                    }
+                    _ => {}
+                }
+            }
+        }
     }
 
-    let vendor_id = u16::from_str_radix(parts[1], 16)
-        .map_err(|_| DaemonError::PrinterNotFound(format!("Invalid vendor ID: {}", parts[1])))?;
-    let product_id = u16::from_str_radix(parts[2], 16)
-        .map_err(|_| DaemonError::PrinterNotFound(format!("Invalid product ID: {}", parts[2])))?;
+    let out_ep = out_ep.ok_or_else(|| {
+        DaemonError::PrintJob("No USB OUT endpoint found for status poll".to_string())
+    })?;
+    let in_ep = in_ep.ok_or_else(|| {
+        DaemonError::PrintJob("No USB IN endpoint found for status poll".to_string())
+    })?;
 
-    let devices = usb_context.devices()
+    // Claim interface 0
+    let _ = handle.set_auto_detach_kernel_driver(true);
+    handle.claim_interface(0)
         .map_err(DaemonError::Usb)?;
 
-    for device in devices.iter() {
-        if let Ok(desc) = device.device_descriptor() {
-            if desc.vendor_id() == vendor_id && desc.product_id() == product_id {
-                let handle = device.open()
-                    .map_err(DaemonError::Usb)?;
-
-                // Find bulk OUT and IN endpoints
-                let config = device.active_config_descriptor()
-                    .map_err(DaemonError::Usb)?;
-
-                let mut out_ep = None;
-                let mut in_ep = None;
-
-                for interface in config.interfaces() {
-                    for iface_desc in interface.descriptors() {
-                        for ep in iface_desc.endpoint_descriptors() {
-                            match ep.direction() {
-                                rusb::Direction::Out if out_ep.is_none() => {
-                                    out_ep = Some(ep.address());
-                                }
-                                rusb::Direction::In if in_ep.is_none() => {
-                                    in_ep = Some(ep.address());
-                                }
-                                _ => {}
+    // Write DLE EOT requests
+    handle.write_bulk(out_ep, request, Duration::from_secs(2))
+        .map_err(DaemonError::Usb)?;
+
+    // Read response
+    let mut response = [0u8; N];
+    handle.read_bulk(in_ep, &mut response, Duration::from_secs(2))
+        .map_err(DaemonError::Usb)?;
+
+    handle.release_interface(0)
+        .map_err(DaemonError::Usb)?;
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PrinterCapabilities;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// (device, event) in the order the fake devices saw them
+    type Events = Arc<std::sync::Mutex<Vec<(&'static str, &'static str)>>>;
+
+    /// Network printer that takes 200ms to answer each write acknowledgment
+    async fn slow_acking_device(name: &'static str, events: Events) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let ack_request = build_write_ack_request();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let events = events.clone();
+                let ack_request = ack_request.clone();
+                tokio::spawn(async move {
+                    let mut received = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    while let Ok(n) = stream.read(&mut buf).await {
+                        if n == 0 {
+                            break;
+                        }
+                        if received.is_empty() && buf[..n] != ack_request[..] {
+                            events.lock().unwrap().push((name, "ticket"));
+                        }
+                        received.extend_from_slice(&buf[..n]);
+                        if received.ends_with(&ack_request) {
+                            events.lock().unwrap().push((name, "ack_asked"));
+                            tokio::time::sleep(Duration::from_millis(200)).await;
+                            events.lock().unwrap().push((name, "ack_sent"));
+                            // Online, no error: the ticket printed
+                            if stream.write_all(&[0x12, 0x12]).await.is_err() {
+                                break;
                             }
+                            received.clear();
                         }
                     }
-                }
+                });
+            }
+        });
+        address
+    }
 
-                let out_ep = out_ep.ok_or_else(|| {
-                    DaemonError::PrintJob("No USB OUT endpoint found for status poll".to_string())
-                })?;
-                let in_ep = in_ep.ok_or_else(|| {
-                    DaemonError::PrintJob("No USB IN endpoint found for status poll".to_string())
-                })?;
+    fn printer(id: &str, address: &str) -> PrinterConfig {
+        PrinterConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            connection_type: ConnectionType::Network,
+            address: address.to_string(),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: None,
+            is_primary: false,
+            capabilities: PrinterCapabilities { cutter: true, drawer: false, qrcode: true, max_width: 48 },
+            paper_width_checked: true,
+            recovery: Default::default(),
+            compact: None,
+            transports: vec![],
+            serial: None,
+            paused: false,
+        }
+    }
 
-                // Claim interface 0
-                let _ = handle.set_auto_detach_kernel_driver(true);
-                handle.claim_interface(0)
-                    .map_err(DaemonError::Usb)?;
+    fn job(id: &str) -> PrintJob {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "restaurant_id": "rest_123",
+            "order_id": id,
+            "order_number": "R001-0042",
+            "station": "bar",
+            "items": [{ "quantity": 1, "name": "Espresso", "modifiers": [] }],
+            "priority": 3,
+            "timestamp": 1700000000000i64,
+            "status": "pending",
+            "retry_count": 0
+        }))
+        .unwrap()
+    }
 
-                // Write DLE EOT requests
-                handle.write_bulk(out_ep, &request, Duration::from_secs(2))
-                    .map_err(DaemonError::Usb)?;
+    #[tokio::test]
+    async fn test_prints_wait_for_their_printer_only() {
+        let pm = match PrinterManager::new() {
+            Ok(pm) => pm,
+            Err(_) => return, // No USB context in this environment
+        };
+        let events = Events::default();
+        pm.add_printer(printer("p1", &slow_acking_device("p1", events.clone()).await)).await;
+        pm.add_printer(printer("p2", &slow_acking_device("p2", events.clone()).await)).await;
+
+        let cancel = CancellationToken::new();
+        let (first, second, other) = tokio::join!(
+            pm.print_to_printer("p1", &job("j1"), &cancel),
+            pm.print_to_printer("p1", &job("j2"), &cancel),
+            pm.print_to_printer("p2", &job("j3"), &cancel),
+        );
+        for delivery in [first, second, other] {
+            assert!(delivery.unwrap().printed_at_ms.is_some());
+        }
 
-                // Read response
-                let mut response = [0u8; 4];
-                handle.read_bulk(in_ep, &mut response, Duration::from_secs(2))
-                    .map_err(DaemonError::Usb)?;
+        let events = events.lock().unwrap().clone();
+        let on = |device: &str| events.iter().filter(|(d, _)| *d == device).map(|(_, e)| *e).collect::<Vec<_>>();
+        // The second ticket only goes out once the first one is acknowledged
+        assert_eq!(on("p1"), vec!["ticket", "ack_asked", "ack_sent", "ticket", "ack_asked", "ack_sent"]);
+        // p2 doesn't wait for p1: both printers were asked for an acknowledgment at once
+        let first_answer = events.iter().position(|(_, e)| *e == "ack_sent").unwrap();
+        assert_eq!(events[..first_answer].iter().filter(|(_, e)| *e == "ack_asked").count(), 2);
+    }
 
-                handle.release_interface(0)
-                    .map_err(DaemonError::Usb)?;
+    #[tokio::test]
+    async fn test_model_query_waits_for_the_printer() {
+        let pm = match PrinterManager::new() {
+            Ok(pm) => pm,
+            Err(_) => return, // No USB context in this environment
+        };
+        // Nothing listens there: the query fails as soon as it gets to connect
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let config = printer("p1", &closed.to_string());
+        pm.add_printer(config.clone()).await;
+
+        let ticket = pm.io("p1").await;
+        let waiting = tokio::time::timeout(Duration::from_millis(100), pm.query_model(&config)).await;
+        assert!(waiting.is_err(), "model query ran during another exchange");
+        drop(ticket);
+        assert!(pm.query_model(&config).await.is_err());
+    }
 
-                return Ok(PrinterHwStatus::from_dle_eot(
-                    response[0],
-                    response[1],
-                    response[2],
-                    response[3],
-                ));
-            }
-        }
+    #[tokio::test]
+    async fn test_online_check_connects_to_the_printer() {
+        let pm = match PrinterManager::new() {
+            Ok(pm) => pm,
+            Err(_) => return, // No USB context in this environment
+        };
+        // Accepts connections but never answers a status request
+        let open = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        pm.add_printer(printer("open", &open.local_addr().unwrap().to_string())).await;
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        pm.add_printer(printer("closed", &closed.to_string())).await;
+
+        assert!(pm.is_online("open").await);
+        assert!(!pm.is_online("closed").await);
+        // Answered from the cache until it expires
+        drop(open);
+        assert!(pm.is_online("open").await);
     }
 
-    Err(DaemonError::PrinterNotFound(format!(
-        "USB device not found for status poll: {}", address
-    )))
+    #[tokio::test]
+    async fn test_removed_printer_leaves_the_caches() {
+        let pm = match PrinterManager::new() {
+            Ok(pm) => pm,
+            Err(_) => return, // No USB context in this environment
+        };
+        // A scan just finished without it, so unknown printers don't start a new one
+        pm.discovery_cache.lock().await.completed_at = Some(Instant::now());
+        let open = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+
+        pm.add_printer(printer("p1", &open.local_addr().unwrap().to_string())).await;
+        assert!(pm.is_online("p1").await);
+        // Moved: the cached answer was for the old address
+        pm.add_printer(printer("p1", &closed.to_string())).await;
+        assert!(!pm.is_online("p1").await);
+
+        pm.add_printer(printer("p1", &open.local_addr().unwrap().to_string())).await;
+        assert!(pm.is_online("p1").await);
+        // Gone from the config
+        pm.sync_printers(&[]).await;
+        assert!(pm.get_printer("p1").await.is_none());
+        assert!(!pm.is_online("p1").await);
+        assert!(pm.network_pool.lock().await.is_empty());
+    }
 }
@@ -0,0 +1,354 @@
+use crate::config::{AppConfig, ConnectionType};
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Local API path the printer web UIs are mounted under
+/// (`/printer-admin/{printer_id}/{token}/...`)
+pub const ROUTE_PREFIX: &str = "/printer-admin";
+
+/// How long a link from `open_printer_admin` works. Staff reopen the page from the
+/// dashboard after that.
+const SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Printer pages run scripts and submit forms, but in an opaque origin: they can't read
+/// the local API's responses or storage, which share 127.0.0.1:8043 with the proxy
+const CONTENT_SECURITY_POLICY: &str = "sandbox allow-scripts allow-forms allow-popups allow-modals";
+
+/// Printer web servers are slow (embedded CPUs), but staff are waiting on the page
+const PROXY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Larger responses are firmware images or logs, not config pages
+const MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Request headers passed through to the printer (login forms and basic auth)
+const FORWARD_REQUEST_HEADERS: [header::HeaderName; 4] = [
+    header::CONTENT_TYPE,
+    header::COOKIE,
+    header::AUTHORIZATION,
+    header::ACCEPT_LANGUAGE,
+];
+
+/// Response headers passed back to the browser (`Location` is rewritten separately)
+const FORWARD_RESPONSE_HEADERS: [header::HeaderName; 4] = [
+    header::CONTENT_TYPE,
+    header::SET_COOKIE,
+    header::WWW_AUTHENTICATE,
+    header::CACHE_CONTROL,
+];
+
+/// Session tokens handed out by `open_printer_admin`: token → (printer_id, expiry)
+static SESSIONS: Lazy<Mutex<HashMap<String, (String, Instant)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Start a proxy session for one printer. The token goes in the URL path, so only the
+/// browser tab opened from the dashboard can reach the printer through the daemon
+/// (no cross-site form posts to a guessable URL).
+pub fn issue_token(printer_id: &str) -> String {
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let now = Instant::now();
+    let mut sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    sessions.retain(|_, (_, expires)| *expires > now);
+    sessions.insert(token.clone(), (printer_id.to_string(), now + SESSION_TTL));
+    token
+}
+
+/// Whether `token` is a live session for `printer_id`
+pub fn token_valid(printer_id: &str, token: &str) -> bool {
+    SESSIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(token)
+        .is_some_and(|(id, expires)| id == printer_id && *expires > Instant::now())
+}
+
+/// Path prefix of one proxy session; the printer's pages live below it
+pub fn session_prefix(printer_id: &str, token: &str) -> String {
+    format!("{}/{}/{}", ROUTE_PREFIX, printer_id, token)
+}
+
+/// URL of a printer's admin pages through the local API
+pub fn admin_url(api_addr: &str, printer_id: &str, token: &str) -> String {
+    format!("http://{}{}/", api_addr, session_prefix(printer_id, token))
+}
+
+/// Web UI base URL of a configured network printer ("192.168.1.50:9100" → "http://192.168.1.50")
+pub fn printer_base_url(config: &AppConfig, printer_id: &str) -> Option<String> {
+    let printer = config.printers.iter().find(|p| p.id == printer_id)?;
+    if !matches!(printer.connection_type, ConnectionType::Network) {
+        return None;
+    }
+    let host = printer
+        .address
+        .rsplit_once(':')
+        .map(|(host, _)| host)
+        .unwrap_or(&printer.address);
+    Some(format!("http://{}", host))
+}
+
+/// Point root-relative links in a printer page back at the proxy.
+///
+/// Epson and Star pages link with absolute paths (`/PRESENTATION/...`), which would
+/// otherwise hit the local API root. Protocol-relative `//host` links are left alone.
+pub fn rewrite_root_links(body: &str, prefix: &str) -> String {
+    const ATTRIBUTES: [&str; 8] = [
+        "href=\"/", "src=\"/", "action=\"/", "url(/",
+        "href='/", "src='/", "action='/", "url('/",
+    ];
+
+    let mut out = String::with_capacity(body.len() + 256);
+    let mut rest = body;
+    while let Some((pos, attr)) = ATTRIBUTES
+        .iter()
+        .filter_map(|a| rest.find(a).map(|pos| (pos, *a)))
+        .min_by_key(|(pos, _)| *pos)
+    {
+        let end = pos + attr.len();
+        out.push_str(&rest[..end - 1]);
+        if !rest[end..].starts_with('/') {
+            out.push_str(prefix);
+        }
+        out.push('/');
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Confine a cookie from the printer to its proxy session: `Path` becomes the session
+/// prefix and `Domain` is dropped, so it is never sent to the local API or another
+/// printer
+pub fn scope_cookie(cookie: &str, prefix: &str) -> String {
+    let mut parts: Vec<&str> = cookie
+        .split(';')
+        .map(str::trim)
+        .enumerate()
+        .filter(|(i, part)| {
+            let attribute = part.split('=').next().unwrap_or_default().trim().to_ascii_lowercase();
+            *i == 0 || (attribute != "path" && attribute != "domain")
+        })
+        .map(|(_, part)| part)
+        .collect();
+    let path = format!("Path={}/", prefix);
+    parts.push(&path);
+    parts.join("; ")
+}
+
+/// Rewrite a redirect from the printer so the browser stays on the proxy
+pub fn rewrite_location(location: &str, base_url: &str, prefix: &str) -> String {
+    let path = location.strip_prefix(base_url).unwrap_or(location);
+    if path.starts_with('/') && !path.starts_with("//") {
+        format!("{}{}", prefix, path)
+    } else if path.is_empty() {
+        format!("{}/", prefix)
+    } else {
+        path.to_string()
+    }
+}
+
+/// Forward one request to the printer's web server and relay the response
+#[allow(clippy::too_many_arguments)]
+pub async fn proxy(
+    base_url: &str,
+    printer_id: &str,
+    token: &str,
+    path: &str,
+    query: Option<&str>,
+    method: Method,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> Response {
+    let prefix = session_prefix(printer_id, token);
+    let mut url = format!("{}/{}", base_url, path.trim_start_matches('/'));
+    if let Some(query) = query {
+        url.push('?');
+        url.push_str(query);
+    }
+    debug!("Proxying {} {} for printer {}", method, url, printer_id);
+
+    let client = match reqwest::Client::builder()
+        .timeout(PROXY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let method = match reqwest::Method::from_bytes(method.as_str().as_bytes()) {
+        Ok(method) => method,
+        Err(_) => return StatusCode::METHOD_NOT_ALLOWED.into_response(),
+    };
+    let mut request = client.request(method, &url).body(body.to_vec());
+    for name in &FORWARD_REQUEST_HEADERS {
+        if let Some(value) = headers.get(name).and_then(|v| v.to_str().ok()) {
+            request = request.header(name.as_str(), value);
+        }
+    }
+
+    let upstream = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Printer {} web UI unreachable at {}: {}", printer_id, base_url, e);
+            return (
+                StatusCode::BAD_GATEWAY,
+                format!("Printer web page at {} is not reachable: {}", base_url, e),
+            )
+                .into_response();
+        }
+    };
+
+    let status = StatusCode::from_u16(upstream.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+    let mut response_headers = HeaderMap::new();
+    for name in &FORWARD_RESPONSE_HEADERS {
+        for value in upstream.headers().get_all(name.as_str()) {
+            let value = if *name == header::SET_COOKIE {
+                value.to_str().ok().and_then(|cookie| HeaderValue::from_str(&scope_cookie(cookie, &prefix)).ok())
+            } else {
+                HeaderValue::from_bytes(value.as_bytes()).ok()
+            };
+            if let Some(value) = value {
+                response_headers.append(name.clone(), value);
+            }
+        }
+    }
+    response_headers.insert(header::CONTENT_SECURITY_POLICY, HeaderValue::from_static(CONTENT_SECURITY_POLICY));
+    if let Some(location) = upstream.headers().get("location").and_then(|v| v.to_str().ok()) {
+        if let Ok(value) = HeaderValue::from_str(&rewrite_location(location, base_url, &prefix)) {
+            response_headers.insert(header::LOCATION, value);
+        }
+    }
+
+    let content_type = upstream
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_lowercase();
+    let bytes = match read_limited(upstream, MAX_RESPONSE_BYTES).await {
+        Ok(Some(bytes)) => bytes,
+        Ok(None) => return (StatusCode::BAD_GATEWAY, "Printer response too large").into_response(),
+        Err(e) => return (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+    };
+
+    let body = if content_type.starts_with("text/html") || content_type.starts_with("text/css") {
+        Body::from(rewrite_root_links(&String::from_utf8_lossy(&bytes), &prefix))
+    } else {
+        Body::from(bytes)
+    };
+
+    (status, response_headers, body).into_response()
+}
+
+/// Read a response body, giving up (None) as soon as it is known to pass `limit`:
+/// from Content-Length when the printer sends one, otherwise while reading
+async fn read_limited(mut response: reqwest::Response, limit: usize) -> reqwest::Result<Option<Vec<u8>>> {
+    if response.content_length().is_some_and(|len| len > limit as u64) {
+        return Ok(None);
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            return Ok(None);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(Some(body))
+}
+
+/// Open a URL in the system browser
+pub async fn open_in_browser(url: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        tokio::process::Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = tokio::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        tokio::process::Command::new("xdg-open")
+    };
+    command.arg(url).status().await.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_root_links() {
+        let html = r#"<a href="/PRESENTATION/HTML/TOP/INDEX.HTML">Top</a>
+<img src='/img/logo.gif'><form action="/cgi-bin/set"></form>
+<a href="//cdn.example.com/x.js"></a><a href="status.html"></a>
+<style>body { background: url(/img/bg.png) }</style>"#;
+
+        let out = rewrite_root_links(html, "/printer-admin/p1");
+        assert!(out.contains(r#"href="/printer-admin/p1/PRESENTATION/HTML/TOP/INDEX.HTML""#));
+        assert!(out.contains("src='/printer-admin/p1/img/logo.gif'"));
+        assert!(out.contains(r#"action="/printer-admin/p1/cgi-bin/set""#));
+        assert!(out.contains("url(/printer-admin/p1/img/bg.png)"));
+        assert!(out.contains(r#"href="//cdn.example.com/x.js""#));
+        assert!(out.contains(r#"href="status.html""#));
+    }
+
+    #[test]
+    fn test_session_tokens_and_cookie_scope() {
+        let token = issue_token("p1");
+        assert!(token_valid("p1", &token));
+        assert!(!token_valid("p2", &token));
+        assert!(!token_valid("p1", "guessed"));
+        assert_eq!(admin_url("127.0.0.1:8043", "p1", &token), format!("http://127.0.0.1:8043/printer-admin/p1/{}/", token));
+
+        let prefix = session_prefix("p1", "t");
+        assert_eq!(
+            scope_cookie("SESSION=abc; Path=/; Domain=127.0.0.1; HttpOnly", &prefix),
+            "SESSION=abc; HttpOnly; Path=/printer-admin/p1/t/"
+        );
+        assert_eq!(scope_cookie("lang=en", &prefix), "lang=en; Path=/printer-admin/p1/t/");
+    }
+
+    #[test]
+    fn test_rewrite_location() {
+        let base = "http://192.168.1.50";
+        let prefix = "/printer-admin/p1";
+        assert_eq!(rewrite_location("http://192.168.1.50/login", base, prefix), "/printer-admin/p1/login");
+        assert_eq!(rewrite_location("/index.html", base, prefix), "/printer-admin/p1/index.html");
+        assert_eq!(rewrite_location("http://192.168.1.50", base, prefix), "/printer-admin/p1/");
+        assert_eq!(rewrite_location("next.html", base, prefix), "next.html");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_response_stops_early() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Printer that answers every request with `reply`, then closes
+        async fn serve(reply: &'static str) -> String {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/", listener.local_addr().unwrap());
+            tokio::spawn(async move {
+                while let Ok((mut socket, _)) = listener.accept().await {
+                    let mut request = [0u8; 1024];
+                    let _ = socket.read(&mut request).await;
+                    let _ = socket.write_all(reply.as_bytes()).await;
+                }
+            });
+            url
+        }
+
+        let streamed = serve("HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n0123456789012345678901234567890123456789").await;
+        let response = reqwest::get(&streamed).await.unwrap();
+        assert_eq!(read_limited(response, 16).await.unwrap(), None);
+        let response = reqwest::get(&streamed).await.unwrap();
+        assert_eq!(read_limited(response, 64).await.unwrap().unwrap().len(), 40);
+
+        // Announced size alone is enough to refuse, before any body arrives
+        let announced = serve("HTTP/1.1 200 OK\r\nContent-Length: 100000000\r\n\r\n").await;
+        let response = reqwest::get(&announced).await.unwrap();
+        assert_eq!(read_limited(response, 16).await.unwrap(), None);
+    }
+}
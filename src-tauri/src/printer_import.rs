@@ -0,0 +1,462 @@
+use crate::address_conflicts;
+use crate::config::{ConnectionType, PrinterCapabilities, PrinterConfig};
+use crate::escpos::PaperWidth;
+use crate::printer_names;
+use crate::readdress::normalize_mac;
+use crate::rediscovery::host_of;
+use regex::Regex;
+use serde::Serialize;
+use std::net::Ipv4Addr;
+
+/// Largest export accepted (a venue's printer list is a few KB)
+pub const MAX_IMPORT_BYTES: usize = 1024 * 1024;
+
+/// Kind of export, detected from its content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    /// PrintNode `GET /printers` JSON (printers with a `computer` object)
+    PrintNode,
+    /// Epson ePOS / TM-Intelligent device settings XML (`<printer>` / `<device>` blocks)
+    EposXml,
+    /// Any JSON list of printer objects (or `{ "printers": [...] }` / `{ "devices": [...] }`)
+    Json,
+    /// CSV with a header row, `,` or `;` separated
+    Csv,
+}
+
+/// One device from an export, turned into a printer entry. `errors` must be fixed
+/// (in the preview) before it can be imported; `warnings` say what was filled in.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedPrinter {
+    /// Position in the export (1-based; CSV: line number)
+    pub row: usize,
+    pub printer: PrinterConfig,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportPreview {
+    pub source: ImportSource,
+    pub printers: Vec<ImportedPrinter>,
+}
+
+/// Result of importing one printer (`import_printers`)
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportOutcome {
+    pub printer_id: String,
+    pub name: String,
+    /// None when no test print was asked for
+    pub test_printed: Option<bool>,
+    pub test_error: Option<String>,
+}
+
+/// Fields of a device as found in an export, before validation
+#[derive(Debug, Default)]
+struct Entry {
+    row: usize,
+    name: Option<String>,
+    model: Option<String>,
+    address: Option<String>,
+    port: Option<u16>,
+    connection: Option<String>,
+    station: Option<String>,
+    paper_mm: Option<u16>,
+}
+
+const NAME_KEYS: &[&str] = &["name", "printer", "printer_name", "display_name", "description", "devid", "device_id"];
+const MODEL_KEYS: &[&str] = &["model", "model_name", "printer_model"];
+const ADDRESS_KEYS: &[&str] = &["address", "ip", "ip_address", "ipaddress", "host", "hostname", "path"];
+const PORT_KEYS: &[&str] = &["port", "tcp_port"];
+const CONNECTION_KEYS: &[&str] = &["connection", "connection_type", "type", "interface"];
+const STATION_KEYS: &[&str] = &["station", "location", "category", "area"];
+const PAPER_KEYS: &[&str] = &["paper", "paper_width", "paper_width_mm", "width"];
+
+fn key_matches(keys: &[&str], key: &str) -> bool {
+    let key = key.trim().to_lowercase().replace([' ', '-'], "_");
+    keys.contains(&key.as_str())
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// First number in a paper description ("80", "58mm", "80 x 297 mm")
+fn paper_mm(value: &str) -> Option<u16> {
+    let digits: String = value.chars().skip_while(|c| !c.is_ascii_digit()).take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+impl Entry {
+    fn set(&mut self, key: &str, value: &str) {
+        let Some(value) = non_empty(value) else { return };
+        if key_matches(NAME_KEYS, key) {
+            // A display name wins over a device ID; the first one found otherwise
+            if self.name.is_none() || key_matches(&["name", "printer_name", "display_name"], key) {
+                self.name = Some(value);
+            }
+        } else if key_matches(MODEL_KEYS, key) {
+            self.model = Some(value);
+        } else if key_matches(ADDRESS_KEYS, key) {
+            self.address.get_or_insert(value);
+        } else if key_matches(PORT_KEYS, key) {
+            self.port = value.parse().ok();
+        } else if key_matches(CONNECTION_KEYS, key) {
+            self.connection = Some(value);
+        } else if key_matches(STATION_KEYS, key) {
+            self.station = Some(value);
+        } else if key_matches(PAPER_KEYS, key) {
+            self.paper_mm = paper_mm(&value);
+        }
+    }
+}
+
+/// Parse an export and turn its devices into printer entries, checked against the
+/// configured printers (names and addresses must stay unique)
+pub fn preview(content: &str, existing: &[PrinterConfig]) -> Result<ImportPreview, String> {
+    if content.len() > MAX_IMPORT_BYTES {
+        return Err(format!("Export is larger than {} KB", MAX_IMPORT_BYTES / 1024));
+    }
+    let (source, entries) = parse(content)?;
+    if entries.is_empty() {
+        return Err("No printers found in the export".to_string());
+    }
+
+    let mut taken: Vec<PrinterConfig> = existing.to_vec();
+    let mut printers = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let imported = build(entry, &taken);
+        if imported.errors.is_empty() {
+            taken.push(imported.printer.clone());
+        }
+        printers.push(imported);
+    }
+    Ok(ImportPreview { source, printers })
+}
+
+fn parse(content: &str) -> Result<(ImportSource, Vec<Entry>), String> {
+    let trimmed = content.trim_start_matches('\u{feff}').trim();
+    match trimmed.chars().next() {
+        Some('<') => Ok((ImportSource::EposXml, parse_epos_xml(trimmed))),
+        Some('[') | Some('{') => parse_json(trimmed),
+        Some(_) => parse_csv(trimmed).map(|entries| (ImportSource::Csv, entries)),
+        None => Err("Export is empty".to_string()),
+    }
+}
+
+fn parse_json(content: &str) -> Result<(ImportSource, Vec<Entry>), String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let list = match &value {
+        serde_json::Value::Array(list) => list.clone(),
+        serde_json::Value::Object(object) => ["printers", "devices", "data"]
+            .iter()
+            .find_map(|key| object.get(*key).and_then(|v| v.as_array()).cloned())
+            .unwrap_or_else(|| vec![value.clone()]),
+        _ => return Err("Expected a list of printers".to_string()),
+    };
+
+    let print_node = list.iter().any(|p| p.get("computer").is_some_and(|c| c.is_object()));
+    let entries = list
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| {
+            let object = item.as_object()?;
+            let mut entry = Entry { row: i + 1, ..Default::default() };
+            for (key, value) in object {
+                match value {
+                    serde_json::Value::String(s) => entry.set(key, s),
+                    serde_json::Value::Number(n) => entry.set(key, &n.to_string()),
+                    _ => {}
+                }
+            }
+            if print_node {
+                // PrintNode prints through the computer's queue: its name is the queue
+                // name, the description what the venue calls the printer
+                if let Some(description) = item.get("description").and_then(|d| d.as_str()).and_then(non_empty) {
+                    entry.name = Some(description);
+                }
+                let papers = item.pointer("/capabilities/papers").and_then(|p| p.as_object());
+                entry.paper_mm = papers.and_then(|p| p.keys().find_map(|k| paper_mm(k)));
+            }
+            Some(entry)
+        })
+        .collect();
+    let source = if print_node { ImportSource::PrintNode } else { ImportSource::Json };
+    Ok((source, entries))
+}
+
+fn parse_epos_xml(content: &str) -> Vec<Entry> {
+    let block = Regex::new(r"(?is)<(printer|device)\b[^>]*>(.*?)</(?:printer|device)>").expect("valid regex");
+    let field = Regex::new(r"(?is)<([a-z_]+)\b[^>]*>\s*([^<]*?)\s*</[a-z_]+>").expect("valid regex");
+    block
+        .captures_iter(content)
+        .enumerate()
+        .map(|(i, captures)| {
+            let mut entry = Entry { row: i + 1, ..Default::default() };
+            for field in field.captures_iter(&captures[2]) {
+                entry.set(&field[1], &field[2]);
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Split one CSV line (quoted fields with `""` escapes)
+fn csv_fields(line: &str, separator: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn parse_csv(content: &str) -> Result<Vec<Entry>, String> {
+    let mut lines = content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let (_, header) = lines.next().ok_or("Export is empty")?;
+    // Spreadsheets with a comma decimal separator export with `;`
+    let separator = if header.matches(';').count() > header.matches(',').count() { ';' } else { ',' };
+    let columns = csv_fields(header, separator);
+    if !columns.iter().any(|c| key_matches(ADDRESS_KEYS, c) || key_matches(NAME_KEYS, c)) {
+        return Err("CSV needs a header row with at least a name or an address column".to_string());
+    }
+
+    Ok(lines
+        .map(|(i, line)| {
+            let mut entry = Entry { row: i + 1, ..Default::default() };
+            for (column, value) in columns.iter().zip(csv_fields(line, separator)) {
+                entry.set(column, &value);
+            }
+            entry
+        })
+        .collect())
+}
+
+fn parse_connection(entry: &Entry, address: &str) -> ConnectionType {
+    let declared = entry.connection.as_deref().unwrap_or("").to_lowercase();
+    if declared.contains("usb") {
+        ConnectionType::USB
+    } else if declared.contains("bluetooth") || declared == "ble" || declared == "bt" {
+        ConnectionType::Bluetooth
+    } else if declared.contains("serial") || declared.contains("rs232") || declared.contains("rs-232") || declared == "com" {
+        ConnectionType::Serial
+    } else if !declared.is_empty() {
+        ConnectionType::Network
+    } else if is_serial_port(address) {
+        ConnectionType::Serial
+    } else if address.starts_with("/dev/") {
+        ConnectionType::USB
+    } else if normalize_mac(address).is_some() {
+        ConnectionType::Bluetooth
+    } else {
+        ConnectionType::Network
+    }
+}
+
+/// Address as the printer manager expects it, or why it can't be used
+fn parse_address(connection: &ConnectionType, address: &str, port: Option<u16>) -> Result<String, String> {
+    match connection {
+        ConnectionType::Network => {
+            let (host, port) = match address.rsplit_once(':') {
+                Some((host, port)) => (host, port.parse::<u16>().map_err(|_| format!("Invalid port in '{}'", address))?),
+                None => (address, port.unwrap_or(9100)),
+            };
+            let hostname = host
+                .split('.')
+                .all(|label| !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+            if host.parse::<Ipv4Addr>().is_err() && (!hostname || host.chars().all(|c| c.is_ascii_digit() || c == '.')) {
+                return Err(format!("'{}' is not an IP address or host name", host));
+            }
+            Ok(format!("{}:{}", host, port))
+        }
+        ConnectionType::USB => {
+            let parts: Vec<&str> = address.split('/').collect();
+            let valid = parts.len() == 6 && address.starts_with("/dev/bus/usb/") && parts[4..].iter().all(|p| p.parse::<u8>().is_ok());
+            valid
+                .then(|| address.to_string())
+                .ok_or_else(|| format!("USB address '{}' is not a /dev/bus/usb/<bus>/<device> path", address))
+        }
+        ConnectionType::Bluetooth => normalize_mac(address)
+            .map(|mac| mac.to_uppercase())
+            .ok_or_else(|| format!("'{}' is not a Bluetooth address", address)),
+        ConnectionType::Serial => is_serial_port(address)
+            .then(|| address.to_string())
+            .ok_or_else(|| format!("'{}' is not a serial port (COM3, /dev/ttyUSB0)", address)),
+    }
+}
+
+/// `COM<n>` or a `/dev/tty*` / `/dev/cu.*` device
+fn is_serial_port(address: &str) -> bool {
+    let upper = address.to_uppercase();
+    upper.strip_prefix("COM").is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        || address.starts_with("/dev/tty")
+        || address.starts_with("/dev/cu.")
+}
+
+/// `name`, numbered when taken ("Kitchen 2")
+fn numbered(name: &str, taken: &[&str]) -> String {
+    let same = |a: &str, b: &str| a.to_lowercase() == b.to_lowercase();
+    if !taken.iter().any(|t| same(t, name)) {
+        return name.to_string();
+    }
+    let base: String = name.chars().take(printer_names::MAX_LEN - 3).collect();
+    (2..)
+        .map(|n| format!("{} {}", base.trim_end(), n))
+        .find(|candidate| !taken.iter().any(|t| same(t, candidate)))
+        .expect("some number is free")
+}
+
+/// Any IPv4 address in a text (queue names like "Kitchen 192.168.1.50")
+fn ip_in(text: &str) -> Option<String> {
+    text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .find(|part| part.parse::<Ipv4Addr>().is_ok())
+        .map(str::to_string)
+}
+
+fn build(entry: Entry, taken: &[PrinterConfig]) -> ImportedPrinter {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    let mut raw_address = entry.address.clone();
+    if raw_address.is_none() {
+        if let Some(ip) = entry.name.as_deref().and_then(ip_in) {
+            warnings.push(format!("Address {} taken from the printer's name", ip));
+            raw_address = Some(ip);
+        }
+    }
+    let raw_address = raw_address.unwrap_or_default();
+    let connection_type = parse_connection(&entry, &raw_address);
+    let address = if raw_address.is_empty() {
+        errors.push("No address in the export; enter it or pick the printer from discovery".to_string());
+        String::new()
+    } else {
+        parse_address(&connection_type, &raw_address, entry.port).unwrap_or_else(|e| {
+            errors.push(e);
+            raw_address.clone()
+        })
+    };
+
+    let station = entry.station.as_deref().map(printer_names::normalize).filter(|s| !s.is_empty());
+    let taken_names: Vec<&str> = taken.iter().map(|p| p.name.as_str()).collect();
+    let name = match entry.name.as_deref().map(printer_names::validate) {
+        Some(Ok(name)) => {
+            let unique = numbered(&name, &taken_names);
+            if unique != name {
+                warnings.push(format!("Name '{}' is already used, renamed to '{}'", name, unique));
+            }
+            unique
+        }
+        Some(Err(e)) => {
+            errors.push(e.to_string());
+            entry.name.clone().unwrap_or_default()
+        }
+        None => {
+            let model = entry.model.as_deref().unwrap_or("Printer");
+            let suggested = printer_names::suggest(model, "", station.as_deref(), &taken_names);
+            warnings.push(format!("No name in the export, named '{}'", suggested));
+            suggested
+        }
+    };
+
+    let width = match entry.paper_mm {
+        Some(mm) if mm <= 60 => PaperWidth::Width58mm,
+        Some(_) => PaperWidth::Width80mm,
+        None => {
+            warnings.push("No paper width in the export, assuming 80mm (checked on first connect)".to_string());
+            PaperWidth::Width80mm
+        }
+    };
+
+    let printer = PrinterConfig {
+        id: format!("import_{}", uuid::Uuid::new_v4().simple()),
+        name,
+        connection_type,
+        address,
+        mac_address: None,
+        protocol: "escpos".to_string(),
+        station,
+        is_primary: false,
+        capabilities: PrinterCapabilities { cutter: true, drawer: false, qrcode: true, max_width: width as u16 },
+        paper_width_checked: false,
+        recovery: Default::default(),
+        compact: None,
+        transports: vec![],
+        serial: None,
+        paused: false,
+    };
+
+    if errors.is_empty() {
+        let mut all = taken.to_vec();
+        all.push(printer.clone());
+        if let Some(conflict) = address_conflicts::find(&all).into_iter().find(|c| c.printer_ids.contains(&printer.id)) {
+            let other = conflict.printer_ids.iter().find(|id| **id != printer.id).cloned().unwrap_or_default();
+            errors.push(format!("Address {} is already used by printer {}", host_of(&conflict.address), other));
+        }
+    }
+
+    ImportedPrinter { row: entry.row, printer, errors, warnings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_csv_printnode_and_epos() {
+        let existing = preview("name,ip\nKitchen,192.168.1.50", &[]).unwrap().printers[0].printer.clone();
+
+        let csv = "Name;IP Address;Port;Location;Paper\n\
+                   \"Bar; front\";192.168.1.60;9101;bar;58mm\n\
+                   kitchen;192.168.1.61;;;80\n\
+                   Pass;192.168.1.50;;;\n\
+                   BLE;00-11-62-0A-0B-0C;;;58\n";
+        let preview = preview(csv, std::slice::from_ref(&existing)).unwrap();
+        assert_eq!(preview.source, ImportSource::Csv);
+        let [bar, kitchen, pass, ble] = &preview.printers[..] else { panic!("four rows") };
+        assert_eq!((bar.row, bar.printer.name.as_str()), (2, "Bar; front"));
+        assert_eq!(bar.printer.address, "192.168.1.60:9101");
+        assert_eq!(bar.printer.station.as_deref(), Some("bar"));
+        assert_eq!(bar.printer.capabilities.max_width, 32);
+        assert!(bar.errors.is_empty() && bar.warnings.is_empty());
+        // Name taken by a configured printer: renamed, not refused
+        assert_eq!(kitchen.printer.name, "kitchen 2");
+        assert_eq!(kitchen.printer.address, "192.168.1.61:9100");
+        assert_eq!(pass.errors, vec![format!("Address 192.168.1.50 is already used by printer {}", existing.id)]);
+        assert!(matches!(ble.printer.connection_type, ConnectionType::Bluetooth));
+        assert_eq!(ble.printer.address, "00:11:62:0A:0B:0C");
+        let serial = super::preview("name,ip\nLegacy,COM3", &[]).unwrap().printers[0].printer.clone();
+        assert!(matches!(serial.connection_type, ConnectionType::Serial));
+
+        let print_node = r#"[{"id": 7, "name": "EPSON TM-T20 Receipt", "description": "Kitchen 10.0.0.9",
+            "computer": {"id": 1, "name": "POS-1"}, "capabilities": {"papers": {"80 x 297 mm": [800, 2970]}}},
+            {"id": 8, "name": "Star TSP100", "description": "", "computer": {"id": 1}}]"#;
+        let preview = super::preview(print_node, &[]).unwrap();
+        assert_eq!(preview.source, ImportSource::PrintNode);
+        assert_eq!(preview.printers[0].printer.address, "10.0.0.9:9100");
+        assert_eq!(preview.printers[0].printer.name, "Kitchen 10.0.0.9");
+        assert_eq!(preview.printers[1].errors.len(), 1);
+
+        let epos = r#"<settings><printer><devid>local_printer</devid><ip>192.168.0.20</ip>
+            <model>TM-m30</model></printer><device><devid>bar_printer</devid><ipaddress>bad host!</ipaddress></device></settings>"#;
+        let preview = super::preview(epos, &[]).unwrap();
+        assert_eq!(preview.source, ImportSource::EposXml);
+        assert_eq!(preview.printers[0].printer.name, "local_printer");
+        assert_eq!(preview.printers[0].printer.address, "192.168.0.20:9100");
+        assert_eq!(preview.printers[1].errors, vec!["'bad host!' is not an IP address or host name".to_string()]);
+
+        assert!(super::preview("color,size\nred,1", &[]).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use crate::config::PrinterConfig;
+use thiserror::Error;
+
+/// Longest name the dashboard and printed test receipts show in full
+pub const MAX_LEN: usize = 40;
+
+/// Why a printer name was refused
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    #[error("printer name is empty")]
+    Empty,
+
+    #[error("printer name is longer than {} characters", MAX_LEN)]
+    TooLong,
+
+    #[error("printer name contains control characters")]
+    ControlCharacters,
+
+    #[error("printer name '{name}' is already used by printer {printer_id}")]
+    Duplicate { name: String, printer_id: String },
+}
+
+/// Name as stored: trimmed, inner whitespace collapsed to single spaces
+pub fn normalize(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Check one name; returns it normalized
+pub fn validate(name: &str) -> Result<String, NameError> {
+    if name.chars().any(|c| c.is_control() && !c.is_whitespace()) {
+        return Err(NameError::ControlCharacters);
+    }
+    let name = normalize(name);
+    if name.is_empty() {
+        return Err(NameError::Empty);
+    }
+    if name.chars().count() > MAX_LEN {
+        return Err(NameError::TooLong);
+    }
+    Ok(name)
+}
+
+/// Names compare case-insensitively: "Kitchen" and "kitchen " are the same printer name
+fn same(a: &str, b: &str) -> bool {
+    normalize(a).to_lowercase() == normalize(b).to_lowercase()
+}
+
+/// Validate every name in place (normalized) and refuse names used twice
+pub fn check_all(printers: &mut [PrinterConfig]) -> Result<(), NameError> {
+    for printer in printers.iter_mut() {
+        printer.name = validate(&printer.name)?;
+    }
+    check_unique(printers, None)
+}
+
+/// Refuse names used twice. With `only`, just that printer's name is checked against
+/// the others (adding or renaming one printer).
+pub fn check_unique(printers: &[PrinterConfig], only: Option<&str>) -> Result<(), NameError> {
+    for (i, printer) in printers.iter().enumerate() {
+        if only.is_some_and(|id| id != printer.id) {
+            continue;
+        }
+        let taken_by = printers
+            .iter()
+            .enumerate()
+            .find(|(j, other)| *j != i && same(&other.name, &printer.name));
+        if let Some((_, other)) = taken_by {
+            return Err(NameError::Duplicate { name: printer.name.clone(), printer_id: other.id.clone() });
+        }
+    }
+    Ok(())
+}
+
+/// Model part of a discovered printer's name, without host suffixes and "at <ip>"
+/// placeholders. Falls back to the vendor when discovery found no model.
+fn model(discovered_name: &str, vendor: &str) -> String {
+    let mut name = discovered_name.trim().trim_end_matches('.').trim_end_matches(".local").to_string();
+    if let Some(pos) = name.find(" at ") {
+        name.truncate(pos);
+    }
+    // Serial / MAC suffixes in hostnames: "EPSON-TM-T20-1A2B3C", "TM-P20_012345"
+    if let Some(pos) = name.rfind(['-', '_']) {
+        let suffix = &name[pos + 1..];
+        if suffix.len() >= 4 && suffix.chars().all(|c| c.is_ascii_hexdigit()) {
+            name.truncate(pos);
+        }
+    }
+    let name = normalize(&name.replace('_', " "));
+    let generic = name.is_empty() || name.eq_ignore_ascii_case("printer");
+    match (generic, vendor.trim()) {
+        (false, _) => name,
+        (true, vendor) if !vendor.is_empty() && !vendor.eq_ignore_ascii_case("unknown") => format!("{} printer", vendor),
+        (true, _) => "Printer".to_string(),
+    }
+}
+
+/// Friendly name for a discovered printer: location (station) and model, numbered when
+/// taken ("Kitchen TM-T20III", "Kitchen TM-T20III 2")
+pub fn suggest(discovered_name: &str, vendor: &str, location: Option<&str>, taken: &[&str]) -> String {
+    let model = model(discovered_name, vendor);
+    let base = match location.map(normalize).filter(|l| !l.is_empty()) {
+        Some(location) => {
+            let mut chars = location.chars();
+            let location: String = chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default();
+            format!("{} {}", location, model)
+        }
+        None => model,
+    };
+    let base: String = base.chars().take(MAX_LEN - 3).collect::<String>().trim_end().to_string();
+
+    (1..)
+        .map(|n| if n == 1 { base.clone() } else { format!("{} {}", base, n) })
+        .find(|candidate| !taken.iter().any(|t| same(t, candidate)))
+        .expect("some number is free")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConnectionType, PrinterCapabilities};
+
+    fn printer(id: &str, name: &str) -> PrinterConfig {
+        PrinterConfig {
+            id: id.to_string(),
+            name: name.to_string(),
+            connection_type: ConnectionType::Network,
+            address: format!("192.168.1.{}:9100", id.len()),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: None,
+            is_primary: false,
+            capabilities: PrinterCapabilities { cutter: true, drawer: false, qrcode: true, max_width: 48 },
+            paper_width_checked: true,
+            recovery: Default::default(),
+            compact: None,
+            transports: vec![],
+            serial: None,
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn test_validate_unique_and_suggest() {
+        assert_eq!(validate("  Kitchen   left "), Ok("Kitchen left".to_string()));
+        assert_eq!(validate("   "), Err(NameError::Empty));
+        assert_eq!(validate("Bar\u{7}"), Err(NameError::ControlCharacters));
+        assert_eq!(validate(&"x".repeat(MAX_LEN + 1)), Err(NameError::TooLong));
+
+        let mut printers = vec![printer("a", " Kitchen"), printer("bb", "Bar"), printer("ccc", "kitchen ")];
+        assert_eq!(
+            check_all(&mut printers),
+            Err(NameError::Duplicate { name: "Kitchen".to_string(), printer_id: "ccc".to_string() })
+        );
+        printers[2].name = "Kitchen 2".to_string();
+        assert_eq!(check_all(&mut printers), Ok(()));
+        assert_eq!(check_unique(&printers, Some("bb")), Ok(()));
+
+        assert_eq!(suggest("TM-T20III", "Epson", Some("kitchen"), &[]), "Kitchen TM-T20III");
+        assert_eq!(suggest("EPSON-TM-T20-1A2B3C.local.", "Epson", None, &[]), "EPSON-TM-T20");
+        assert_eq!(suggest("Printer at 192.168.1.50", "Star", Some("bar"), &[]), "Bar Star printer");
+        assert_eq!(suggest("Printer at 192.168.1.51", "Unknown", None, &["printer"]), "Printer 2");
+        assert_eq!(
+            suggest("TM-m30", "Epson", Some("Kitchen"), &["Kitchen TM-m30", "kitchen tm-m30 2"]),
+            "Kitchen TM-m30 3"
+        );
+    }
+}
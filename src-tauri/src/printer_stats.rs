@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How often changed counters are synced to the printers' Supabase rows
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Longest last-error text kept; printer errors can carry whole transport dumps
+pub const MAX_ERROR_LEN: usize = 300;
+
+/// One print attempt on one printer (failover attempts count for the printer they ran on)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    pub printer_id: String,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Printed { bytes: u64, duration_ms: u64 },
+    Failed { error: String },
+}
+
+/// Lifetime counters of a printer, kept in the queue database (not cleaned up with the
+/// jobs) and synced to the printer's Supabase row for fleet reliability reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrinterStats {
+    pub printer_id: String,
+    pub jobs_printed: u64,
+    pub jobs_failed: u64,
+    pub bytes_sent: u64,
+    /// Mean time from handing a job to the printer until it was accepted
+    pub avg_duration_ms: Option<u64>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<i64>,
+    pub last_printed_at: Option<i64>,
+    /// First attempt counted (Unix seconds)
+    pub since: i64,
+    /// Bumped on every change; rows with a revision above the synced one are sent again
+    #[serde(skip)]
+    pub revision: i64,
+}
+
+impl Attempt {
+    pub fn printed(printer_id: &str, bytes: u64, duration_ms: u64) -> Self {
+        Self { printer_id: printer_id.to_string(), outcome: Outcome::Printed { bytes, duration_ms } }
+    }
+
+    pub fn failed(printer_id: &str, error: &str) -> Self {
+        let error = error.chars().take(MAX_ERROR_LEN).collect();
+        Self { printer_id: printer_id.to_string(), outcome: Outcome::Failed { error } }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::clock::{self, SharedClock};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Quarantine configuration
+#[derive(Debug, Clone)]
+pub struct QuarantineConfig {
+    /// Breaker openings within `trip_window` that quarantine a printer (default: 3)
+    pub trip_threshold: usize,
+    /// Window for counting breaker openings (default: 1 hour)
+    pub trip_window: Duration,
+    /// Time between re-probes of a quarantined printer (default: 5 minutes)
+    pub probe_interval: Duration,
+    /// Consecutive successful probes before it returns to rotation (default: 3)
+    pub release_after: u32,
+}
+
+impl Default for QuarantineConfig {
+    fn default() -> Self {
+        Self {
+            trip_threshold: 3,
+            trip_window: Duration::from_secs(60 * 60),
+            probe_interval: Duration::from_secs(5 * 60),
+            release_after: 3,
+        }
+    }
+}
+
+/// Why a printer was quarantined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuarantineReason {
+    /// Circuit breaker kept tripping
+    RepeatedTrips,
+    /// Operator sidelined it from the dashboard
+    Manual,
+}
+
+impl QuarantineReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QuarantineReason::RepeatedTrips => "repeated_trips",
+            QuarantineReason::Manual => "manual",
+        }
+    }
+}
+
+/// Result of a re-probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeOutcome {
+    /// Enough consecutive successes: back in rotation
+    Released,
+    /// Probe passed, `ok` of `needed` so far
+    Passing { ok: u32, needed: u32 },
+    /// Probe failed, streak reset
+    Failed,
+    /// Not quarantined (released or removed while probing)
+    NotQuarantined,
+}
+
+/// Per-printer quarantine snapshot (for the dashboard)
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarantineStatus {
+    pub printer_id: String,
+    pub reason: QuarantineReason,
+    pub quarantined_secs: u64,
+    pub probes: u32,
+    pub consecutive_ok: u32,
+    pub release_after: u32,
+    /// Seconds until the next re-probe (0 = due)
+    pub next_probe_secs: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    reason: QuarantineReason,
+    since: Instant,
+    last_probe: Instant,
+    probes: u32,
+    consecutive_ok: u32,
+}
+
+/// Sidelines printers whose circuit breaker keeps tripping.
+///
+/// The breaker alone half-opens every few minutes and fails a live job each time a
+/// dead printer is retried. A quarantined printer gets no jobs at all (failover takes
+/// them) and is re-probed on a slow schedule instead, returning to rotation only after
+/// several probes in a row succeed. Nothing is deleted from the config.
+pub struct Quarantine {
+    config: QuarantineConfig,
+    trips: Mutex<HashMap<String, VecDeque<Instant>>>,
+    entries: Mutex<HashMap<String, Entry>>,
+    clock: SharedClock,
+}
+
+impl Quarantine {
+    pub fn new(config: QuarantineConfig) -> Self {
+        Self {
+            config,
+            trips: Mutex::new(HashMap::new()),
+            entries: Mutex::new(HashMap::new()),
+            clock: clock::system(),
+        }
+    }
+
+    /// Use a different time source (tests advance a `TestClock` instead of sleeping)
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Record a breaker opening. Returns true if this put the printer in quarantine.
+    pub async fn record_trip(&self, printer_id: &str) -> bool {
+        let now = self.clock.now();
+        let count = {
+            let mut trips = self.trips.lock().await;
+            let history = trips.entry(printer_id.to_string()).or_default();
+            history.push_back(now);
+            while history
+                .front()
+                .is_some_and(|t| now.duration_since(*t) > self.config.trip_window)
+            {
+                history.pop_front();
+            }
+            history.len()
+        };
+
+        if count < self.config.trip_threshold {
+            return false;
+        }
+        warn!(
+            "Printer {} tripped its circuit breaker {} times within {:?}",
+            printer_id, count, self.config.trip_window
+        );
+        self.quarantine(printer_id, QuarantineReason::RepeatedTrips).await
+    }
+
+    /// Put a printer in quarantine. Returns false if it already was.
+    pub async fn quarantine(&self, printer_id: &str, reason: QuarantineReason) -> bool {
+        let now = self.clock.now();
+        let mut entries = self.entries.lock().await;
+        if entries.contains_key(printer_id) {
+            return false;
+        }
+        info!("Printer {} quarantined ({:?})", printer_id, reason);
+        entries.insert(
+            printer_id.to_string(),
+            Entry { reason, since: now, last_probe: now, probes: 0, consecutive_ok: 0 },
+        );
+        true
+    }
+
+    /// Return a printer to rotation. Returns false if it wasn't quarantined.
+    pub async fn release(&self, printer_id: &str) -> bool {
+        self.trips.lock().await.remove(printer_id);
+        let released = self.entries.lock().await.remove(printer_id).is_some();
+        if released {
+            info!("Printer {} released from quarantine", printer_id);
+        }
+        released
+    }
+
+    pub async fn is_quarantined(&self, printer_id: &str) -> bool {
+        self.entries.lock().await.contains_key(printer_id)
+    }
+
+    /// Quarantined printers whose next re-probe is due
+    pub async fn due_for_probe(&self) -> Vec<String> {
+        let now = self.clock.now();
+        self.entries
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, e)| now.duration_since(e.last_probe) >= self.config.probe_interval)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Record a re-probe result, releasing the printer after enough consecutive successes
+    pub async fn record_probe(&self, printer_id: &str, success: bool) -> ProbeOutcome {
+        let now = self.clock.now();
+        let mut entries = self.entries.lock().await;
+        let Some(entry) = entries.get_mut(printer_id) else {
+            return ProbeOutcome::NotQuarantined;
+        };
+        entry.last_probe = now;
+        entry.probes += 1;
+
+        if !success {
+            entry.consecutive_ok = 0;
+            return ProbeOutcome::Failed;
+        }
+        entry.consecutive_ok += 1;
+        if entry.consecutive_ok < self.config.release_after {
+            return ProbeOutcome::Passing { ok: entry.consecutive_ok, needed: self.config.release_after };
+        }
+
+        entries.remove(printer_id);
+        drop(entries);
+        self.trips.lock().await.remove(printer_id);
+        info!("Printer {} passed {} probes in a row, back in rotation", printer_id, self.config.release_after);
+        ProbeOutcome::Released
+    }
+
+    /// Snapshot of all quarantined printers
+    pub async fn snapshot(&self) -> Vec<QuarantineStatus> {
+        let now = self.clock.now();
+        self.entries
+            .lock()
+            .await
+            .iter()
+            .map(|(id, e)| QuarantineStatus {
+                printer_id: id.clone(),
+                reason: e.reason,
+                quarantined_secs: now.duration_since(e.since).as_secs(),
+                probes: e.probes,
+                consecutive_ok: e.consecutive_ok,
+                release_after: self.config.release_after,
+                next_probe_secs: self
+                    .config
+                    .probe_interval
+                    .saturating_sub(now.duration_since(e.last_probe))
+                    .as_secs(),
+            })
+            .collect()
+    }
+}
+
+impl Default for Quarantine {
+    fn default() -> Self {
+        Self::new(QuarantineConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+
+    #[tokio::test]
+    async fn test_repeated_trips_quarantine_and_probes_release() {
+        let clock = TestClock::new();
+        let quarantine = Quarantine::default().with_clock(clock.clone());
+
+        // Trips spread beyond the window don't add up
+        assert!(!quarantine.record_trip("p1").await);
+        clock.advance(Duration::from_secs(2 * 3600));
+        assert!(!quarantine.record_trip("p1").await);
+        assert!(!quarantine.record_trip("p1").await);
+        assert!(quarantine.record_trip("p1").await);
+        assert!(quarantine.is_quarantined("p1").await);
+
+        // Probed on the slow schedule only
+        assert!(quarantine.due_for_probe().await.is_empty());
+        clock.advance(Duration::from_secs(5 * 60));
+        assert_eq!(quarantine.due_for_probe().await, vec!["p1".to_string()]);
+
+        // A failure resets the streak
+        assert_eq!(quarantine.record_probe("p1", true).await, ProbeOutcome::Passing { ok: 1, needed: 3 });
+        assert_eq!(quarantine.record_probe("p1", false).await, ProbeOutcome::Failed);
+        assert!(quarantine.due_for_probe().await.is_empty());
+        for ok in 1..3 {
+            assert_eq!(quarantine.record_probe("p1", true).await, ProbeOutcome::Passing { ok, needed: 3 });
+        }
+        assert_eq!(quarantine.record_probe("p1", true).await, ProbeOutcome::Released);
+        assert!(!quarantine.is_quarantined("p1").await);
+
+        // Trip history was cleared with the release
+        assert!(!quarantine.record_trip("p1").await);
+    }
+
+    #[tokio::test]
+    async fn test_manual_quarantine() {
+        let quarantine = Quarantine::default();
+        assert!(quarantine.quarantine("p1", QuarantineReason::Manual).await);
+        assert!(!quarantine.quarantine("p1", QuarantineReason::Manual).await);
+
+        let snapshot = quarantine.snapshot().await;
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].reason, QuarantineReason::Manual);
+
+        assert!(quarantine.release("p1").await);
+        assert!(!quarantine.release("p1").await);
+        assert_eq!(quarantine.record_probe("p1", true).await, ProbeOutcome::NotQuarantined);
+    }
+}
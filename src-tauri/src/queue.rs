@@ -1,17 +1,40 @@
+use crate::business_date;
+use crate::circuit_breaker::{BreakerTransition, CircuitState};
+use crate::clock::{self, SharedClock};
 use crate::errors::{DaemonError, Result};
 use crate::escpos::PrintItem;
+use crate::incidents::{self, Incident};
+use crate::ingest_dedup::IngestDedupConfig;
+use crate::jam_recovery::{self, MechanicalFault, MechanicalFaultSummary};
+use crate::job_cache::{self, CacheStats, JobCache};
+use crate::pickup_slip::PickupSlip;
+use crate::printer_stats::{Attempt, Outcome, PrinterStats};
+use crate::queue_migrations;
+use crate::recovery::RecoveryJob;
+use crate::restarts::{DaemonRun, StopReason};
+use crate::stations::{StationMap, UnmatchedStation};
 use crate::status;
+use crate::task_metrics;
 use backon::{ExponentialBuilder, Retryable};
+use rusqlite::OptionalExtension;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, MutexGuard};
 use tokio_rusqlite::Connection;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 use sha2::Sha256;
 use zeroize::Zeroizing;
 
+/// How long a job blocked on the printer (cover open, switched off) waits between tries
+pub const BLOCKED_RETRY_SECS: i64 = 30;
+
+/// A blocked job older than this fails instead of waiting on: a ticket that arrives
+/// half an hour late no longer helps the kitchen
+pub const BLOCKED_MAX_SECS: i64 = 30 * 60;
+
 /// Job priority levels (lower number = higher priority)
 #[allow(dead_code)] // Infrastructure: constants document the aging formula and are used by escalate_priority
 pub mod priority {
@@ -48,13 +71,445 @@ pub struct PrintJob {
     pub status: String,
     pub retry_count: u32,
     pub error_message: Option<String>,
+    /// Copies printed in one send (e.g. kitchen + runner), see `escpos::MAX_COPIES`
+    #[serde(default = "single_copy")]
+    pub copies: u8,
+    /// Traces one order across webapp, daemon logs, telemetry, Supabase, Sentry and the
+    /// printed ticket. Taken from upstream when sent, otherwise generated at enqueue.
+    #[serde(default)]
+    pub correlation_id: String,
+    /// When the customer collects the order (Unix ms), for `{pickup_time}` in the
+    /// receipt header and footer
+    #[serde(default)]
+    pub pickup_time: Option<i64>,
+    /// Regular ticket, or a follow-up to the ticket of `original_job_id`
+    #[serde(default)]
+    pub kind: JobKind,
+    /// The job whose printed ticket a void or amend follows up on
+    #[serde(default)]
+    pub original_job_id: Option<String>,
+    /// Amend: items taken off the original ticket (`items` holds the additions)
+    #[serde(default)]
+    pub removed_items: Vec<PrintItem>,
+    /// Every station the order has a ticket for, as upstream fans it out. Tickets of one
+    /// order arrive separately, so the order only counts as printed once each of these
+    /// stations has one. Empty when upstream doesn't say.
+    #[serde(default)]
+    pub order_stations: Vec<String>,
+}
+
+/// Jobs of the incident's printer that waited during it (`?1`: incident id, `?2`: its end
+/// or now)
+const AFFECTED_JOBS_SQL: &str = "SELECT COUNT(*) FROM print_jobs j, printer_incidents o
+     WHERE o.id = ?1 AND j.printer_id = o.printer_id AND j.created_at <= ?2
+       AND (j.completed_at IS NULL OR j.completed_at >= o.started_at)";
+
+/// Stations an order's tickets announce (`order_stations`) that no ticket of the order
+/// has arrived for yet (`?1`: order id)
+const UNSEEN_STATIONS_SQL: &str = "SELECT DISTINCT s.value FROM print_jobs j, json_each(j.order_stations) s
+     WHERE j.order_id = ?1
+       AND NOT EXISTS (SELECT 1 FROM print_jobs o WHERE o.order_id = ?1 AND o.station = s.value)";
+
+/// Incident columns; open incidents count their affected jobs up to now (`?1`)
+fn incident_columns() -> String {
+    format!(
+        "i.id, i.printer_id, i.started_at, i.ended_at, i.cause,
+         CASE WHEN i.ended_at IS NULL THEN ({}) ELSE i.affected_jobs END",
+        AFFECTED_JOBS_SQL.replace("?1", "i.id").replace("?2", "?1")
+    )
+}
+
+fn incident_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Incident> {
+    Ok(Incident {
+        id: row.get(0)?,
+        printer_id: row.get(1)?,
+        started_at: row.get(2)?,
+        ended_at: row.get(3)?,
+        cause: row.get(4)?,
+        affected_jobs: row.get::<_, i64>(5)? as u64,
+    })
+}
+
+fn single_copy() -> u8 {
+    1
+}
+
+/// What a job prints. Void and amend jobs go to the same station as their original and
+/// always print the built-in follow-up ticket, whatever the station's template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobKind {
+    #[default]
+    Order,
+    /// The order (this station's part of it) was cancelled after its ticket printed
+    Void,
+    /// The order changed after its ticket printed: removed items struck, additions highlighted
+    Amend,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::Order => "order",
+            JobKind::Void => "void",
+            JobKind::Amend => "amend",
+        }
+    }
+
+    /// `print_jobs.kind` or an upstream value; unknown ones print as a regular ticket
+    pub fn from_name(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "void" => JobKind::Void,
+            "amend" => JobKind::Amend,
+            _ => JobKind::Order,
+        }
+    }
+}
+
+impl PrintJob {
+    /// Short form of the correlation ID printed in the ticket footer
+    pub fn short_ref(&self) -> &str {
+        let end = self.correlation_id.char_indices().nth(8).map_or(self.correlation_id.len(), |(i, _)| i);
+        &self.correlation_id[..end]
+    }
+}
+
+/// Longest correlation ID accepted from upstream
+const MAX_CORRELATION_ID_LEN: usize = 64;
+
+/// Correlation ID for a new job: the upstream one when it is usable (short, no spaces or
+/// control characters), a fresh one otherwise
+pub fn correlation_id_for(upstream: &str) -> String {
+    let upstream = upstream.trim();
+    let usable = !upstream.is_empty()
+        && upstream.len() <= MAX_CORRELATION_ID_LEN
+        && upstream.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    if usable {
+        upstream.to_string()
+    } else {
+        uuid::Uuid::new_v4().simple().to_string()
+    }
+}
+
+/// Result of importing a queue snapshot from another machine
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SnapshotImport {
+    /// Jobs added to this queue as pending
+    pub imported: usize,
+    /// Jobs already in this queue (same job ID, or same order + station)
+    pub skipped_duplicates: usize,
+    /// Imported jobs whose printer doesn't exist here and were routed to a local one
+    pub rerouted: usize,
+}
+
+/// How backed up the local queue is. Reported with every poll so the backend can warn the
+/// restaurant and hold new print fan-out while a printer is down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Saturation {
+    Normal,
+    /// Backlog building up (slow or briefly offline printer)
+    Elevated,
+    /// Jobs are piling up faster than they print
+    Saturated,
+}
+
+impl Saturation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Saturation::Normal => "normal",
+            Saturation::Elevated => "elevated",
+            Saturation::Saturated => "saturated",
+        }
+    }
+}
+
+/// A pull-mode ticket released by `print_next`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PulledTicket {
+    pub job_id: String,
+    pub order_number: String,
+    /// Tickets still waiting at the station
+    pub waiting: u64,
+}
+
+/// Completed jobs per station the average print duration is taken over
+const STATION_LOAD_SAMPLE: i64 = 20;
+
+/// How far behind one station is, for the manager dashboard (`get_station_load`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StationLoad {
+    pub station: String,
+    pub pending: u64,
+    pub printing: u64,
+    /// Seconds the oldest pending job has been waiting (0 when none)
+    pub oldest_pending_secs: u64,
+    /// Average print duration of the station's last 20 tickets; all stations' average
+    /// while it has none. None without any history.
+    pub avg_print_ms: Option<u64>,
+    /// Time to print everything pending and printing at that average
+    pub backlog_secs: Option<u64>,
+}
+
+/// Pending jobs at which the queue counts as elevated / saturated
+const ELEVATED_PENDING: u64 = 50;
+const SATURATED_PENDING: u64 = 200;
+/// Age of the oldest pending job at which the queue counts as elevated / saturated
+const ELEVATED_WAIT_SECS: u64 = 120;
+const SATURATED_WAIT_SECS: u64 = 600;
+
+/// Queue depth snapshot for the poll heartbeat, `/api/health` and the dashboard
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueueHealth {
+    pub pending: u64,
+    pub printing: u64,
+    /// Seconds the oldest pending job has been waiting (0 when empty)
+    pub oldest_pending_secs: u64,
+    pub state: Saturation,
+}
+
+impl QueueHealth {
+    pub fn new(pending: u64, printing: u64, oldest_pending_secs: u64) -> Self {
+        let state = if pending >= SATURATED_PENDING || oldest_pending_secs >= SATURATED_WAIT_SECS {
+            Saturation::Saturated
+        } else if pending >= ELEVATED_PENDING || oldest_pending_secs >= ELEVATED_WAIT_SECS {
+            Saturation::Elevated
+        } else {
+            Saturation::Normal
+        };
+
+        Self {
+            pending,
+            printing,
+            oldest_pending_secs,
+            state,
+        }
+    }
+}
+
+/// Print counts for one business date
+#[derive(Debug, Clone, Serialize)]
+pub struct DaySummary {
+    pub business_date: String,
+    pub printed: u64,
+    pub failed: u64,
+    pub stations: Vec<StationSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StationSummary {
+    pub station: String,
+    pub printed: u64,
+    pub failed: u64,
+}
+
+/// Jobs of one station or printer in the queue (retention: 7 days)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobBreakdown {
+    /// Station name, or printer id ("unassigned" for jobs without one)
+    pub key: String,
+    pub total: u64,
+    pub pending: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub avg_retries: f64,
+}
+
+/// Jobs created in one hour of the last 24
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HourBucket {
+    /// Unix seconds, start of the hour
+    pub hour: i64,
+    pub created: u64,
+    pub completed: u64,
+    pub failed: u64,
+}
+
+/// Failed jobs per error class (the `troubleshoot::FailureClass` names, plus
+/// `breaker_open`, `quarantined` and `paper_out`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailureReason {
+    pub class: String,
+    pub count: u64,
+}
+
+/// Error class of `error_message` in SQL, in the spirit of `troubleshoot::classify_error`
+const ERROR_CLASS_SQL: &str = r#"
+    CASE
+        WHEN lower(error_message) LIKE '%circuit breaker open%' THEN 'breaker_open'
+        WHEN lower(error_message) LIKE '%quarantined%' THEN 'quarantined'
+        WHEN lower(error_message) LIKE '%paper%' THEN 'paper_out'
+        WHEN lower(error_message) LIKE '%usb permission%'
+          OR lower(error_message) LIKE '%usb interface busy%' THEN 'usb_permission'
+        WHEN lower(error_message) LIKE '%usb device not found%' THEN 'usb_not_found'
+        WHEN lower(error_message) LIKE '%bluetooth%' THEN 'bluetooth_unavailable'
+        WHEN lower(error_message) LIKE '%refused%' THEN 'connection_refused'
+        WHEN lower(error_message) LIKE '%timed out%'
+          OR lower(error_message) LIKE '%timeout%' THEN 'timeout'
+        WHEN lower(error_message) LIKE '%unreachable%'
+          OR lower(error_message) LIKE '%no route%' THEN 'unreachable'
+        ELSE 'print_failed'
+    END
+"#;
+
+/// A recently printed job, for the venue status page
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentPrint {
+    pub order_number: String,
+    pub station: String,
+    pub printer_id: Option<String>,
+    pub table_number: Option<String>,
+    pub completed_at: i64,
+}
+
+/// Operator note on a job ("printer was unplugged"), for later support reviews
+#[derive(Debug, Clone, Serialize)]
+pub struct JobNote {
+    pub note: String,
+    /// Unix seconds
+    pub created_at: i64,
+}
+
+/// A job in the dashboard's job history, with its notes
+#[derive(Debug, Clone, Serialize)]
+pub struct JobHistoryEntry {
+    pub id: String,
+    pub order_id: Option<String>,
+    pub order_number: String,
+    pub station: String,
+    pub printer_id: Option<String>,
+    pub status: String,
+    pub error_message: Option<String>,
+    pub retry_count: u32,
+    /// Unix seconds
+    pub created_at: i64,
+    pub completed_at: Option<i64>,
+    pub correlation_id: String,
+    /// Oldest first
+    pub notes: Vec<JobNote>,
+}
+
+/// Search window when `find_jobs` gets no `since`: about one service
+pub const JOB_SEARCH_DEFAULT_WINDOW_SECS: i64 = 6 * 3600;
+
+/// Most jobs one search returns
+pub const JOB_SEARCH_LIMIT: usize = 50;
+
+/// Front-of-house job search ("did table 12's order print?"). At least one of the three
+/// must be set; when several are, a job must match all of them. Table and order number
+/// match exactly, the customer name by prefix, all ignoring (ASCII) case.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct JobSearch {
+    pub table_number: Option<String>,
+    pub customer_name: Option<String>,
+    pub order_number: Option<String>,
+    /// Unix seconds; default `JOB_SEARCH_DEFAULT_WINDOW_SECS` ago
+    pub since: Option<i64>,
+    /// Unix seconds; default now
+    pub until: Option<i64>,
+}
+
+/// A job found by `find_jobs`
+#[derive(Debug, Clone, Serialize)]
+pub struct JobSearchResult {
+    #[serde(flatten)]
+    pub job: JobHistoryEntry,
+    pub table_number: Option<String>,
+    pub customer_name: Option<String>,
+}
+
+/// An order's tickets across stations (`list_order_jobs`)
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderView {
+    pub order_id: String,
+    pub order_number: String,
+    /// Oldest first
+    pub jobs: Vec<JobHistoryEntry>,
+    /// Stations whose tickets all printed
+    pub stations_printed: Vec<String>,
+    /// Stations with a ticket still pending, printing or failed, or announced in
+    /// `order_stations` but not arrived yet
+    pub stations_pending: Vec<String>,
+    /// Unix seconds the last ticket printed; None while any is open
+    pub completed_at: Option<i64>,
+}
+
+/// A persisted circuit breaker transition
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerHistoryEntry {
+    pub from_state: String,
+    pub to_state: String,
+    pub reason: String,
+    pub detail: Option<String>,
+    /// Time spent in `from_state`
+    pub duration_ms: u64,
+    pub business_date: String,
+    /// Unix seconds
+    pub created_at: i64,
+}
+
+/// Minutes a printer's breaker was not closed (open or half-open) on one business day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DegradedDay {
+    pub printer_id: String,
+    pub business_date: String,
+    pub minutes: u64,
+}
+
+/// How long breaker history is kept
+const BREAKER_HISTORY_RETENTION_SECS: i64 = 90 * 24 * 3600;
+
+/// How long IDs of printed jobs are remembered after the jobs themselves are cleaned up,
+/// so a job the backend re-sends (daemon restarted before reporting) is not printed twice
+const COMPLETED_ID_RETENTION_SECS: i64 = 30 * 24 * 3600;
+
+/// How long idempotency keys of local API requests are remembered (a POS sending the same
+/// key a day later is resubmitting, not retrying)
+const IDEMPOTENCY_RETENTION_SECS: i64 = 24 * 3600;
+
+/// Print attempts per pickup slip before it is given up
+const PICKUP_SLIP_ATTEMPTS: u32 = 3;
+
+/// Job created for an idempotency key of the local API
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotentJob {
+    pub job_id: String,
+    pub correlation_id: String,
+    /// SHA-256 of the request body the key came with
+    pub body_hash: String,
 }
 
+/// Snapshot file format version (stored as the snapshot's `user_version`)
+const SNAPSHOT_VERSION: i32 = 1;
+
+/// The print queue. Cheap to clone: clones share the connection, rate limiter, cache and
+/// station map, and every call takes the connection lock only for its own statement(s),
+/// so callers hold a `QueueManager` rather than a lock around one.
+#[derive(Clone)]
 pub struct QueueManager {
     conn: Arc<Mutex<Connection>>,
     config: QueueConfig,
     /// Rate limiter: tracks last enqueue time and count per time window
     rate_limiter: Arc<Mutex<RateLimiterState>>,
+    /// Time source for backoff, aging, dedup and cleanup windows (SQL gets it as a parameter)
+    clock: SharedClock,
+    /// Local hour at which the business day rolls over (stats and summaries are per business date)
+    rollover_hour: u8,
+    /// Parsed payloads of jobs the processor keeps seeing (std Mutex: used inside `conn.call`)
+    job_cache: Arc<std::sync::Mutex<JobCache>>,
+    /// Canonical station names, applied at enqueue (replaced when printers or aliases change)
+    stations: Arc<std::sync::RwLock<StationMap>>,
+    /// Pull-mode stations (normalized): their pending jobs wait for `print_next`
+    pull_stations: Arc<std::sync::RwLock<Vec<String>>>,
+    /// Printers paused by staff (`PrinterConfig.paused`): their jobs stay pending
+    paused_printers: Arc<std::sync::RwLock<Vec<String>>>,
+    /// Routed jobs held for a paused printer (job id → printer id), released when it resumes
+    held_jobs: Arc<std::sync::Mutex<HashMap<String, String>>>,
+    /// Upstream job ids remembered across transports (`AppConfig.ingest_dedup`)
+    ingest_dedup: Arc<std::sync::RwLock<IngestDedupConfig>>,
+    /// Jobs whose station matched no printer station or alias
+    unmatched_tx: Option<tokio::sync::mpsc::UnboundedSender<UnmatchedStation>>,
 }
 
 /// Simple token bucket rate limiter state
@@ -70,19 +525,17 @@ struct RateLimiterState {
 }
 
 impl RateLimiterState {
-    fn new() -> Self {
+    fn new(now: std::time::Instant) -> Self {
         Self {
             count: 0,
-            window_start: std::time::Instant::now(),
+            window_start: now,
             max_per_window: 100, // 100 jobs per minute
             window_duration: Duration::from_secs(60),
         }
     }
 
     /// Check if rate limit allows a new enqueue
-    fn check(&mut self) -> bool {
-        let now = std::time::Instant::now();
-
+    fn check(&mut self, now: std::time::Instant) -> bool {
         // Reset window if expired
         if now.duration_since(self.window_start) >= self.window_duration {
             self.count = 0;
@@ -118,6 +571,110 @@ impl Default for QueueConfig {
     }
 }
 
+/// Map a `print_jobs` row selected in the column order used by the dequeue queries
+fn job_from_row(row: &rusqlite::Row) -> rusqlite::Result<PrintJob> {
+    let id: String = row.get(0)?;
+    let items_json: String = row.get(6)?;
+    let items: Vec<PrintItem> = serde_json::from_str(&items_json)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+    Ok(PrintJob {
+        // Jobs queued before correlation IDs existed are traced by their job ID
+        correlation_id: row.get::<_, Option<String>>(16)?.unwrap_or_else(|| id.clone()),
+        id,
+        restaurant_id: row.get(1)?,
+        order_id: row.get(2)?,
+        order_number: row.get(3)?,
+        station: row.get(4)?,
+        station_id: None, // Local queue doesn't store station_id
+        printer_id: row.get(5)?,
+        items,
+        table_number: row.get(7)?,
+        customer_name: row.get(8)?,
+        order_type: row.get(9)?,
+        priority: row.get(10)?,
+        timestamp: row.get(11)?,
+        status: row.get(12)?,
+        retry_count: row.get(13)?,
+        error_message: row.get(14)?,
+        copies: row.get::<_, Option<u8>>(15)?.unwrap_or(1),
+        pickup_time: row.get(17)?,
+        kind: JobKind::from_name(&row.get::<_, String>(18)?),
+        original_job_id: row.get(19)?,
+        removed_items: match row.get::<_, Option<String>>(20)? {
+            Some(json) => {
+                serde_json::from_str(&json).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?
+            }
+            None => Vec::new(),
+        },
+        order_stations: match row.get::<_, Option<String>>(21)? {
+            Some(json) => {
+                serde_json::from_str(&json).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?
+            }
+            None => Vec::new(),
+        },
+    })
+}
+
+/// The columns of a job that change after enqueue, as selected by the dequeue queries
+struct JobHead {
+    id: String,
+    printer_id: Option<String>,
+    priority: u8,
+    status: String,
+    retry_count: u32,
+    error_message: Option<String>,
+}
+
+fn head_from_row(row: &rusqlite::Row) -> rusqlite::Result<JobHead> {
+    Ok(JobHead {
+        id: row.get(0)?,
+        printer_id: row.get(1)?,
+        priority: row.get(2)?,
+        status: row.get(3)?,
+        retry_count: row.get(4)?,
+        error_message: row.get(5)?,
+    })
+}
+
+/// Full jobs for dequeued heads: the payload comes from the cache, or from SQLite (and is
+/// cached) on a miss. The head columns always come from the current row.
+fn hydrate_jobs(
+    conn: &rusqlite::Connection,
+    cache: &std::sync::Mutex<JobCache>,
+    heads: Vec<JobHead>,
+) -> rusqlite::Result<Vec<PrintJob>> {
+    let mut jobs = Vec::with_capacity(heads.len());
+    for head in heads {
+        let cached = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&head.id);
+        let mut job = match cached {
+            Some(job) => job,
+            None => {
+                let job = conn.query_row(
+                    r#"
+                    SELECT id, restaurant_id, order_id, order_number, station, printer_id,
+                           items, table_number, customer_name, order_type, priority, timestamp,
+                           status, retry_count, error_message, copies, correlation_id, pickup_time,
+                           kind, original_job_id, removed_items, order_stations
+                    FROM print_jobs WHERE id = ?1
+                    "#,
+                    [&head.id],
+                    job_from_row,
+                )?;
+                cache.lock().unwrap_or_else(|e| e.into_inner()).put(job.clone());
+                job
+            }
+        };
+        job.printer_id = head.printer_id;
+        job.priority = head.priority;
+        job.status = head.status;
+        job.retry_count = head.retry_count;
+        job.error_message = head.error_message;
+        jobs.push(job);
+    }
+    Ok(jobs)
+}
+
 impl QueueManager {
     /// Derive encryption key from restaurant ID using PBKDF2-HMAC-SHA256
     ///
@@ -166,131 +723,134 @@ impl QueueManager {
                 .map_err(|e| DaemonError::Queue(format!("Failed to open database: {}", e)))?
         };
 
-        // Migration: make order_id nullable (v1.1.6+)
-        // SQLite doesn't support ALTER COLUMN, so drop and recreate if needed.
-        // Print queue data is ephemeral — safe to recreate.
-        conn.call(|conn| {
-            let mut stmt = conn.prepare("PRAGMA table_info(print_jobs)")?;
-            let mut order_id_notnull = false;
-            let rows = stmt.query_map([], |row| {
-                let name: String = row.get(1)?;
-                let notnull: bool = row.get(3)?;
-                Ok((name, notnull))
-            })?;
-            for row in rows {
-                let (name, notnull) = row?;
-                if name == "order_id" && notnull {
-                    order_id_notnull = true;
-                }
-            }
-            if order_id_notnull {
-                tracing::info!("Migrating print_jobs: making order_id nullable");
-                conn.execute("DROP TABLE print_jobs", [])?;
-            }
-            Ok(())
+        // Schema changes are versioned migrations (`queue_migrations.rs`), each applied once
+        // in its own transaction; queued jobs are carried over, never dropped
+        let applied = conn
+            .call(|conn| Ok(queue_migrations::run(conn)?))
+            .await
+            .map_err(|e| DaemonError::Queue(format!("Queue migration failed: {}", e)))?;
+        if !applied.is_empty() {
+            tracing::info!("Queue schema migrations applied: {:?}", applied);
+        }
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            config: QueueConfig::default(),
+            rate_limiter: Arc::new(Mutex::new(RateLimiterState::new(std::time::Instant::now()))),
+            clock: clock::system(),
+            rollover_hour: business_date::DEFAULT_ROLLOVER_HOUR,
+            job_cache: Arc::new(std::sync::Mutex::new(JobCache::new(job_cache::DEFAULT_CAPACITY))),
+            stations: Arc::new(std::sync::RwLock::new(StationMap::default())),
+            pull_stations: Arc::new(std::sync::RwLock::new(Vec::new())),
+            paused_printers: Arc::new(std::sync::RwLock::new(Vec::new())),
+            held_jobs: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            ingest_dedup: Arc::new(std::sync::RwLock::new(IngestDedupConfig::default())),
+            unmatched_tx: None,
         })
-        .await
-        .map_err(|e| DaemonError::Queue(format!("Migration check failed: {}", e)))?;
+    }
 
-        // Migration: unify status vocabulary ('processing' → 'printing')
-        // Only runs if print_jobs table already exists (skips on fresh/in-memory DBs)
-        conn.call(|conn| {
-            let table_exists: bool = conn.query_row(
-                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name='print_jobs'",
-                [],
-                |row| row.get(0),
-            )?;
-            if table_exists {
-                let changed = conn.execute(
-                    "UPDATE print_jobs SET status = ?1 WHERE status = 'processing'",
-                    [status::PRINTING],
+    /// The connection, waiting for calls in flight (time spent waiting is in the task metrics)
+    async fn db(&self) -> MutexGuard<'_, Connection> {
+        task_metrics::lock(&self.conn, "queue_db").await
+    }
+
+    /// Report jobs with an unknown station on this channel
+    pub fn with_unmatched_stations(mut self, tx: tokio::sync::mpsc::UnboundedSender<UnmatchedStation>) -> Self {
+        self.unmatched_tx = Some(tx);
+        self
+    }
+
+    /// Replace the station names jobs are normalized to (printers or `station_aliases` changed)
+    pub fn set_stations(&self, stations: StationMap) {
+        *self.stations.write().unwrap_or_else(|e| e.into_inner()) = stations;
+    }
+
+    /// Replace the pull-mode stations (`AppConfig.pull_stations`). Tickets of a station
+    /// taken out of pull mode print right away.
+    pub fn set_pull_stations(&self, stations: &[String]) {
+        let normalized = stations.iter().map(|s| crate::stations::normalize(s)).filter(|s| !s.is_empty()).collect();
+        *self.pull_stations.write().unwrap_or_else(|e| e.into_inner()) = normalized;
+    }
+
+    /// Replace the paused printers (`AppConfig::paused_printers`). Jobs of a resumed
+    /// printer print right away: routed ones held for it (`hold_job`) lose their hold.
+    pub async fn set_paused_printers(&self, printer_ids: &[String]) {
+        *self.paused_printers.write().unwrap_or_else(|e| e.into_inner()) = printer_ids.to_vec();
+
+        let released: Vec<String> = {
+            let mut held = self.held_jobs.lock().unwrap_or_else(|e| e.into_inner());
+            let released = held.iter().filter(|(_, p)| !printer_ids.contains(*p)).map(|(id, _)| id.clone()).collect();
+            held.retain(|_, p| printer_ids.contains(p));
+            released
+        };
+        if released.is_empty() {
+            return;
+        }
+        let ids = serde_json::to_string(&released).unwrap_or_default();
+        let conn = self.db().await;
+        let result = conn
+            .call(move |conn| {
+                conn.execute(
+                    "UPDATE print_jobs SET retry_after = NULL WHERE status = ?1 AND id IN (SELECT value FROM json_each(?2))",
+                    rusqlite::params![status::PENDING, ids],
                 )?;
-                if changed > 0 {
-                    tracing::info!("Migrated {changed} jobs from 'processing' to 'printing'");
-                }
-            }
-            Ok(())
-        })
-        .await
-        .map_err(|e| DaemonError::Queue(format!("Status migration failed: {}", e)))?;
+                Ok(())
+            })
+            .await;
+        if let Err(e) = result {
+            warn!("Failed to release jobs held for resumed printers: {}", e);
+        }
+    }
 
-        // Migration: add retry_after column (v1.2+)
-        // ALTER TABLE ADD COLUMN is safe in SQLite — no-ops if column already exists would error,
-        // so we check first.
-        conn.call(|conn| {
-            let table_exists: bool = conn.query_row(
-                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name='print_jobs'",
-                [],
-                |row| row.get(0),
-            )?;
-            if table_exists {
-                let has_column: bool = conn
-                    .prepare("PRAGMA table_info(print_jobs)")?
-                    .query_map([], |row| row.get::<_, String>(1))?
-                    .any(|name| name.as_deref() == Ok("retry_after"));
-                if !has_column {
-                    conn.execute("ALTER TABLE print_jobs ADD COLUMN retry_after INTEGER", [])?;
-                    tracing::info!("Migrated print_jobs: added retry_after column");
-                }
-            }
-            Ok(())
-        })
-        .await
-        .map_err(|e| DaemonError::Queue(format!("retry_after migration failed: {}", e)))?;
+    /// Whether staff paused the printer
+    pub fn is_printer_paused(&self, printer_id: &str) -> bool {
+        self.paused_printers.read().unwrap_or_else(|e| e.into_inner()).iter().any(|id| id == printer_id)
+    }
 
-        // Create tables
-        conn.call(|conn| {
-            conn.execute(
-                r#"
-                CREATE TABLE IF NOT EXISTS print_jobs (
-                    id TEXT PRIMARY KEY,
-                    restaurant_id TEXT NOT NULL,
-                    order_id TEXT,
-                    order_number TEXT NOT NULL,
-                    station TEXT NOT NULL,
-                    printer_id TEXT,
-                    items TEXT NOT NULL,
-                    table_number TEXT,
-                    customer_name TEXT,
-                    order_type TEXT,
-                    priority INTEGER DEFAULT 3,
-                    timestamp INTEGER NOT NULL,
-                    status TEXT NOT NULL,
-                    retry_count INTEGER DEFAULT 0,
-                    error_message TEXT,
-                    created_at INTEGER DEFAULT (strftime('%s', 'now')),
-                    processing_at INTEGER,
-                    completed_at INTEGER,
-                    retry_after INTEGER
-                )
-                "#,
-                [],
-            )?;
+    /// Paused printers as a JSON array, for `json_each` in the dequeue queries
+    fn paused_printers_json(&self) -> String {
+        serde_json::to_string(&*self.paused_printers.read().unwrap_or_else(|e| e.into_inner())).unwrap_or_default()
+    }
 
-            conn.execute(
-                "CREATE INDEX IF NOT EXISTS idx_status ON print_jobs(status)",
-                [],
-            )?;
+    /// Replace the ingest deduplication settings (`AppConfig.ingest_dedup`)
+    pub fn set_ingest_dedup(&self, config: &IngestDedupConfig) {
+        *self.ingest_dedup.write().unwrap_or_else(|e| e.into_inner()) = config.clone();
+    }
 
-            conn.execute(
-                "CREATE INDEX IF NOT EXISTS idx_restaurant ON print_jobs(restaurant_id)",
-                [],
-            )?;
+    /// A station name as `pull_stations` holds it: aliases resolved, normalized
+    fn pull_key(&self, station: &str) -> String {
+        let resolved = self.stations.read().unwrap_or_else(|e| e.into_inner()).resolve(station).station;
+        crate::stations::normalize(&resolved)
+    }
 
-            conn.execute(
-                "CREATE INDEX IF NOT EXISTS idx_priority ON print_jobs(priority, created_at)",
-                [],
-            )?;
+    /// Whether `station` (or the station it is an alias of) is in pull mode
+    pub fn is_pull_station(&self, station: &str) -> bool {
+        let station = self.pull_key(station);
+        self.pull_stations.read().unwrap_or_else(|e| e.into_inner()).contains(&station)
+    }
 
-            Ok(())
-        })
-        .await?;
+    /// Pull-mode stations as a JSON array, for `json_each` in the dequeue queries
+    fn pull_stations_json(&self) -> String {
+        serde_json::to_string(&*self.pull_stations.read().unwrap_or_else(|e| e.into_inner())).unwrap_or_default()
+    }
 
-        Ok(Self {
-            conn: Arc::new(Mutex::new(conn)),
-            config: QueueConfig::default(),
-            rate_limiter: Arc::new(Mutex::new(RateLimiterState::new())),
-        })
+    /// Set the business day rollover hour (`AppConfig.business_day_rollover_hour`)
+    pub fn with_rollover_hour(mut self, hour: u8) -> Self {
+        self.rollover_hour = hour.min(23);
+        self
+    }
+
+    /// Current business date (YYYY-MM-DD)
+    pub fn business_date(&self) -> String {
+        business_date::for_unix_secs(self.clock.unix_secs(), self.rollover_hour)
+    }
+
+    /// Use a different time source (tests advance a `TestClock` instead of sleeping)
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.rate_limiter = Arc::new(Mutex::new(RateLimiterState::new(clock.now())));
+        self.clock = clock;
+        self
     }
 
     /// Open an encrypted database, verifying the key works.
@@ -356,13 +916,69 @@ impl QueueManager {
         Ok(conn)
     }
 
+    /// Enqueue a job that came from upstream over `transport` ("poll"), unless a job with
+    /// its id came in within the dedup TTL, over any transport. Returns false for such a
+    /// duplicate, so transports delivering the same jobs never queue one twice.
+    pub async fn ingest(&self, job: PrintJob, transport: &str) -> Result<bool> {
+        let config = self.ingest_dedup.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if !config.enabled {
+            self.enqueue(job).await?;
+            return Ok(true);
+        }
+
+        let now = self.clock.unix_secs();
+        let expired = now - config.ttl_secs();
+        let (job_id, transport) = (job.id.clone(), transport.to_string());
+        let first = {
+            let conn = self.db().await;
+            conn.call(move |conn| {
+                conn.execute(
+                    "DELETE FROM ingested_job_ids WHERE id = ?1 AND seen_at <= ?2",
+                    rusqlite::params![job_id, expired],
+                )?;
+                let claimed = conn.execute(
+                    "INSERT OR IGNORE INTO ingested_job_ids (id, transport, seen_at) VALUES (?1, ?2, ?3)",
+                    rusqlite::params![job_id, transport, now],
+                )?;
+                if claimed > 0 {
+                    return Ok(None);
+                }
+                Ok(Some(conn.query_row(
+                    "SELECT transport FROM ingested_job_ids WHERE id = ?1",
+                    [&job_id],
+                    |row| row.get::<_, String>(0),
+                )?))
+            })
+            .await
+            .map_err(|e| DaemonError::Queue(format!("Failed to check ingested job: {}", e)))?
+        };
+        if let Some(first) = first {
+            debug!("Job {} already came in over {}, dropped", job.id, first);
+            return Ok(false);
+        }
+
+        let job_id = job.id.clone();
+        if let Err(e) = self.enqueue(job).await {
+            // Not queued: the next delivery may try again
+            let conn = self.db().await;
+            let _ = conn
+                .call(move |conn| {
+                    conn.execute("DELETE FROM ingested_job_ids WHERE id = ?1", [job_id])?;
+                    Ok(())
+                })
+                .await;
+            return Err(e);
+        }
+        Ok(true)
+    }
+
     /// Enqueue a new print job with deduplication
     #[tracing::instrument(skip(self, job), fields(job_id = %job.id, order = %job.order_number, station = %job.station))]
-    pub async fn enqueue(&self, job: PrintJob) -> Result<()> {
+    pub async fn enqueue(&self, mut job: PrintJob) -> Result<()> {
         // Rate limit check (100 jobs/minute)
         {
             let mut limiter = self.rate_limiter.lock().await;
-            if !limiter.check() {
+            if !limiter.check(self.clock.now()) {
                 warn!("Rate limit exceeded: >100 jobs/minute - rejecting job {}", job.id);
                 return Err(DaemonError::Queue(
                     "Rate limit exceeded: too many print jobs per minute".to_string()
@@ -370,18 +986,100 @@ impl QueueManager {
             }
         }
 
-        let conn = self.conn.lock().await;
+        let conn = self.db().await;
+
+        // A follow-up prints where its original did, for the same order
+        if let (Some(original_id), JobKind::Void | JobKind::Amend) = (job.original_job_id.clone(), job.kind) {
+            let original = conn
+                .call(move |conn| {
+                    Ok(conn.query_row(
+                        "SELECT station, printer_id, order_id, items FROM print_jobs WHERE id = ?1",
+                        [&original_id],
+                        |row| {
+                            Ok((
+                                row.get::<_, String>(0)?,
+                                row.get::<_, Option<String>>(1)?,
+                                row.get::<_, Option<String>>(2)?,
+                                row.get::<_, String>(3)?,
+                            ))
+                        },
+                    )
+                    .optional()?)
+                })
+                .await
+                .map_err(|e| DaemonError::Queue(format!("Failed to look up original job: {}", e)))?;
+            match original {
+                Some((station, printer_id, order_id, items)) => {
+                    job.station = station;
+                    job.printer_id = job.printer_id.take().or(printer_id);
+                    job.order_id = job.order_id.take().or(order_id);
+                    // A void without items strikes the whole original ticket
+                    if job.kind == JobKind::Void && job.items.is_empty() {
+                        job.items = serde_json::from_str(&items).unwrap_or_default();
+                    }
+                }
+                // Cleaned up (older than 7 days) or queued on another machine
+                None => warn!(
+                    "Original job {} of {} job {} not in the queue - printed as sent",
+                    job.original_job_id.as_deref().unwrap_or_default(),
+                    job.kind.as_str(),
+                    job.id
+                ),
+            }
+        }
+
+        let resolved = self.stations.read().unwrap_or_else(|e| e.into_inner()).resolve(&job.station);
+        if !resolved.matched {
+            warn!("Job {} has unknown station '{}' - queued as '{}'", job.id, job.station, resolved.station);
+            if let Some(ref tx) = self.unmatched_tx {
+                let _ = tx.send(UnmatchedStation {
+                    job_id: job.id.clone(),
+                    order_number: job.order_number.clone(),
+                    received: job.station.clone(),
+                    station: resolved.station.clone(),
+                });
+            }
+        } else if resolved.station != job.station {
+            debug!("Job {} station '{}' normalized to '{}'", job.id, job.station, resolved.station);
+        }
+        job.station = resolved.station;
+        if !job.order_stations.is_empty() {
+            let stations = self.stations.read().unwrap_or_else(|e| e.into_inner());
+            job.order_stations = job.order_stations.iter().map(|station| stations.resolve(station).station).collect();
+        }
 
         let items_json = serde_json::to_string(&job.items)
             .map_err(|e| DaemonError::Queue(format!("Failed to serialize items: {}", e)))?;
 
+        let removed_items_json = if job.removed_items.is_empty() {
+            None
+        } else {
+            Some(
+                serde_json::to_string(&job.removed_items)
+                    .map_err(|e| DaemonError::Queue(format!("Failed to serialize items: {}", e)))?,
+            )
+        };
+
+        let order_stations_json = if job.order_stations.is_empty() {
+            None
+        } else {
+            Some(
+                serde_json::to_string(&job.order_stations)
+                    .map_err(|e| DaemonError::Queue(format!("Failed to serialize order stations: {}", e)))?,
+            )
+        };
+
         // Check for duplicate job (same order_id + station within last 5 minutes)
-        // Skip deduplication for test prints (order_id is None)
+        // Skip deduplication for test prints (order_id is None) and for void / amend
+        // follow-ups, which share their original's order and station
         let job_id_clone = job.id.clone();
+        let correlation_id = correlation_id_for(&job.correlation_id);
         let order_id = job.order_id.clone();
         let station = job.station.clone();
+        let now = self.clock.unix_secs();
+        let business_date = business_date::for_unix_secs(now, self.rollover_hour);
 
-        if let Some(ref oid) = order_id {
+        if let (Some(oid), JobKind::Order) = (&order_id, job.kind) {
             let oid_clone = oid.clone();
             let station_clone = station.clone();
 
@@ -393,11 +1091,12 @@ impl QueueManager {
                         WHERE order_id = ?1
                           AND station = ?2
                           AND status IN (?3, ?4)
-                          AND created_at > strftime('%s', 'now', '-5 minutes')
+                          AND created_at > ?5 - 300
+                          AND kind = 'order'
                         "#,
                     )?;
 
-                    let count: i64 = stmt.query_row(rusqlite::params![oid_clone, station_clone, status::PENDING, status::PRINTING], |row| {
+                    let count: i64 = stmt.query_row(rusqlite::params![oid_clone, station_clone, status::PENDING, status::PRINTING, now], |row| {
                         row.get(0)
                     })?;
 
@@ -412,14 +1111,19 @@ impl QueueManager {
             }
         }
 
+        let log_ids = (job_id_clone.clone(), correlation_id.clone());
+
         // Insert job
         conn.call(move |conn| {
             conn.execute(
                 r#"
                 INSERT INTO print_jobs (
                     id, restaurant_id, order_id, order_number, station, printer_id,
-                    items, table_number, customer_name, order_type, priority, timestamp, status
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+                    items, table_number, customer_name, order_type, priority, timestamp, status,
+                    created_at, business_date, copies, correlation_id, pickup_time,
+                    kind, original_job_id, removed_items, order_stations
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18,
+                          ?19, ?20, ?21, ?22)
                 "#,
                 rusqlite::params![
                     job_id_clone,
@@ -435,12 +1139,28 @@ impl QueueManager {
                     job.priority,
                     job.timestamp,
                     job.status,
+                    now,
+                    business_date,
+                    job.copies,
+                    correlation_id,
+                    job.pickup_time,
+                    job.kind.as_str(),
+                    job.original_job_id,
+                    removed_items_json,
+                    order_stations_json,
                 ],
             )?;
+            // A ticket added to an order that was done reopens it (a void takes one away)
+            if let Some(order_id) = job.order_id.as_ref().filter(|_| job.kind != JobKind::Void) {
+                conn.execute("DELETE FROM order_completions WHERE order_id = ?1", [order_id])?;
+            }
             Ok(())
         })
         .await
-        .map_err(|e| DaemonError::Queue(format!("Failed to enqueue job: {}", e)))
+        .map_err(|e| DaemonError::Queue(format!("Failed to enqueue job: {}", e)))?;
+
+        info!(job_id = %log_ids.0, correlation_id = %log_ids.1, "Job enqueued");
+        Ok(())
     }
 
     /// Get next pending jobs ordered by effective priority with aging.
@@ -451,57 +1171,39 @@ impl QueueManager {
     ///
     /// Effective priority = MAX(1, priority - (wait_seconds / 300))
     pub async fn get_pending_jobs(&self, limit: usize) -> Result<Vec<PrintJob>> {
-        let conn = self.conn.lock().await;
+        let conn = self.db().await;
         let aging_threshold = priority::AGING_THRESHOLD_SECS;
+        let now = self.clock.unix_secs();
+        let cache = self.job_cache.clone();
+        let pull_stations = self.pull_stations_json();
+        let paused_printers = self.paused_printers_json();
 
         let jobs = conn
             .call(move |conn| {
+                // Pull-mode stations' tickets wait until released, paused printers' until resumed
                 let mut stmt = conn.prepare(
                     r#"
-                    SELECT id, restaurant_id, order_id, order_number, station, printer_id,
-                           items, table_number, customer_name, order_type, priority, timestamp,
-                           status, retry_count, error_message
+                    SELECT id, printer_id, priority, status, retry_count, error_message
                     FROM print_jobs
                     WHERE status = ?3
-                      AND (retry_after IS NULL OR retry_after <= strftime('%s', 'now'))
+                      AND (retry_after IS NULL OR retry_after <= ?4)
+                      AND (released_at IS NOT NULL OR lower(station) NOT IN (SELECT value FROM json_each(?5)))
+                      AND (printer_id IS NULL OR printer_id NOT IN (SELECT value FROM json_each(?6)))
                     ORDER BY
-                        MAX(1, priority - (strftime('%s', 'now') - created_at) / ?2) ASC,
+                        MAX(1, priority - (?4 - created_at) / ?2) ASC,
                         created_at ASC
                     LIMIT ?1
                     "#,
                 )?;
 
-                let rows = stmt.query_map(rusqlite::params![limit, aging_threshold, status::PENDING], |row| {
-                    let items_json: String = row.get(6)?;
-                    let items: Vec<PrintItem> = serde_json::from_str(&items_json)
-                        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-
-                    Ok(PrintJob {
-                        id: row.get(0)?,
-                        restaurant_id: row.get(1)?,
-                        order_id: row.get(2)?,
-                        order_number: row.get(3)?,
-                        station: row.get(4)?,
-                        station_id: None, // Local queue doesn't store station_id
-                        printer_id: row.get(5)?,
-                        items,
-                        table_number: row.get(7)?,
-                        customer_name: row.get(8)?,
-                        order_type: row.get(9)?,
-                        priority: row.get(10)?,
-                        timestamp: row.get(11)?,
-                        status: row.get(12)?,
-                        retry_count: row.get(13)?,
-                        error_message: row.get(14)?,
-                    })
-                })?;
-
-                let mut jobs = Vec::new();
-                for job_result in rows {
-                    jobs.push(job_result?);
-                }
+                let heads = stmt
+                    .query_map(
+                        rusqlite::params![limit, aging_threshold, status::PENDING, now, pull_stations, paused_printers],
+                        head_from_row,
+                    )?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
 
-                Ok(jobs)
+                Ok(hydrate_jobs(conn, &cache, heads)?)
             })
             .await
             .map_err(|e| DaemonError::Queue(format!("Failed to get pending jobs: {}", e)))?;
@@ -509,54 +1211,342 @@ impl QueueManager {
         Ok(jobs)
     }
 
-    /// Mark job as printing
-    #[tracing::instrument(skip(self), fields(job_id))]
-    pub async fn mark_printing(&self, job_id: &str) -> Result<()> {
-        let conn = self.conn.lock().await;
-        let job_id = job_id.to_string();
-
+    /// Get the next job per station for ordered delivery.
+    ///
+    /// Only the oldest unfinished ticket of each station (by order creation time) is
+    /// eligible, and only while nothing else for that station is printing. A head job
+    /// waiting out its retry backoff holds back the tickets behind it; once it fails
+    /// permanently the station moves on. Different stations are still returned together
+    /// (by effective priority) so they print in parallel.
+    pub async fn get_pending_jobs_ordered(&self, limit: usize) -> Result<Vec<PrintJob>> {
+        let conn = self.db().await;
+        let aging_threshold = priority::AGING_THRESHOLD_SECS;
+        let now = self.clock.unix_secs();
+        let cache = self.job_cache.clone();
+        let pull_stations = self.pull_stations_json();
+        let paused_printers = self.paused_printers_json();
+
+        let jobs = conn
+            .call(move |conn| {
+                // In pull mode the station picks the order: waiting tickets don't count
+                let mut stmt = conn.prepare(
+                    r#"
+                    SELECT id, printer_id, priority, status, retry_count, error_message
+                    FROM print_jobs AS head
+                    WHERE status = ?3
+                      AND (retry_after IS NULL OR retry_after <= ?4)
+                      AND (released_at IS NOT NULL OR lower(station) NOT IN (SELECT value FROM json_each(?6)))
+                      AND (printer_id IS NULL OR printer_id NOT IN (SELECT value FROM json_each(?7)))
+                      AND NOT EXISTS (
+                          SELECT 1 FROM print_jobs AS other
+                          WHERE other.station = head.station
+                            AND (
+                                other.status = ?5
+                                OR (other.status = ?3
+                                    AND (other.released_at IS NOT NULL
+                                         OR lower(other.station) NOT IN (SELECT value FROM json_each(?6)))
+                                    AND (
+                                        other.timestamp < head.timestamp
+                                        OR (other.timestamp = head.timestamp AND other.created_at < head.created_at)
+                                        OR (other.timestamp = head.timestamp AND other.created_at = head.created_at
+                                            AND other.rowid < head.rowid)
+                                    ))
+                            )
+                      )
+                    ORDER BY
+                        MAX(1, priority - (?4 - created_at) / ?2) ASC,
+                        created_at ASC
+                    LIMIT ?1
+                    "#,
+                )?;
+
+                let rows = stmt.query_map(
+                    rusqlite::params![limit, aging_threshold, status::PENDING, now, status::PRINTING, pull_stations, paused_printers],
+                    head_from_row,
+                )?;
+                let heads = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+
+                Ok(hydrate_jobs(conn, &cache, heads)?)
+            })
+            .await
+            .map_err(|e| DaemonError::Queue(format!("Failed to get ordered pending jobs: {}", e)))?;
+
+        Ok(jobs)
+    }
+
+    /// Release the next waiting ticket of a pull-mode station, in the processor's order
+    /// (effective priority, then age); it prints with the next processor tick. None when
+    /// nothing waits.
+    pub async fn print_next(&self, station: &str) -> Result<Option<PulledTicket>> {
+        if !self.is_pull_station(station) {
+            return Err(DaemonError::Queue(format!("Station '{}' is not in pull mode", station.trim())));
+        }
+        let station = self.pull_key(station);
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+
         conn.call(move |conn| {
-            conn.execute(
+            let tx = conn.transaction()?;
+            let next = tx
+                .query_row(
+                    r#"
+                    SELECT id, order_number FROM print_jobs
+                    WHERE status = ?1
+                      AND released_at IS NULL
+                      AND lower(station) = ?2
+                      AND (retry_after IS NULL OR retry_after <= ?3)
+                    ORDER BY
+                        MAX(1, priority - (?3 - created_at) / ?4) ASC,
+                        created_at ASC
+                    LIMIT 1
+                    "#,
+                    rusqlite::params![status::PENDING, station, now, priority::AGING_THRESHOLD_SECS],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+                )
+                .optional()?;
+            let Some((job_id, order_number)) = next else {
+                return Ok(None);
+            };
+            tx.execute("UPDATE print_jobs SET released_at = ?2 WHERE id = ?1", rusqlite::params![job_id, now])?;
+            let waiting: i64 = tx.query_row(
+                "SELECT COUNT(*) FROM print_jobs WHERE status = ?1 AND released_at IS NULL AND lower(station) = ?2",
+                rusqlite::params![status::PENDING, station],
+                |row| row.get(0),
+            )?;
+            tx.commit()?;
+            Ok(Some(PulledTicket { job_id, order_number, waiting: waiting as u64 }))
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to release next ticket: {}", e)))
+    }
+
+    /// Drop a job's cached payload once it reaches a final status
+    fn forget_payload(&self, job_id: &str) {
+        self.job_cache.lock().unwrap_or_else(|e| e.into_inner()).invalidate(job_id);
+    }
+
+    /// Hit / miss counts of the job payload cache
+    pub fn job_cache_stats(&self) -> CacheStats {
+        self.job_cache.lock().unwrap_or_else(|e| e.into_inner()).stats()
+    }
+
+    /// Mark job as printing. False if it was cancelled since it was picked up, or another
+    /// worker that picked it up too got there first: only one of them prints it.
+    #[tracing::instrument(skip(self), fields(job_id))]
+    pub async fn mark_printing(&self, job_id: &str) -> Result<bool> {
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+        let job_id = job_id.to_string();
+
+        conn.call(move |conn| {
+            let marked = conn.execute(
                 r#"
                 UPDATE print_jobs
                 SET status = ?2,
-                    processing_at = strftime('%s', 'now')
-                WHERE id = ?1
+                    processing_at = ?3
+                WHERE id = ?1 AND status = ?4
                 "#,
-                rusqlite::params![job_id, status::PRINTING],
+                rusqlite::params![job_id, status::PRINTING, now, status::PENDING],
             )?;
-            Ok(())
+            Ok(marked > 0)
         })
         .await
         .map_err(|e| DaemonError::Queue(format!("Failed to mark job as printing: {}", e)))
     }
 
-    /// Mark job as completed
-    #[tracing::instrument(skip(self), fields(job_id, duration_ms = print_duration_ms))]
-    pub async fn mark_completed(&self, job_id: &str, print_duration_ms: u64) -> Result<()> {
-        let conn = self.conn.lock().await;
+    /// Mark job as completed, with its delivery timestamps when known
+    #[tracing::instrument(skip(self, delivery), fields(job_id, duration_ms = print_duration_ms))]
+    pub async fn mark_completed(
+        &self,
+        job_id: &str,
+        print_duration_ms: u64,
+        delivery: Option<status::Delivery>,
+    ) -> Result<()> {
+        self.forget_payload(job_id);
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
         let job_id = job_id.to_string();
 
         conn.call(move |conn| {
-            conn.execute(
+            let tx = conn.transaction()?;
+            tx.execute(
                 r#"
                 UPDATE print_jobs
                 SET status = ?2,
-                    completed_at = strftime('%s', 'now')
+                    completed_at = ?3,
+                    sent_at_ms = ?4,
+                    printed_at_ms = ?5,
+                    print_duration_ms = ?6
                 WHERE id = ?1
                 "#,
-                rusqlite::params![job_id, status::COMPLETED],
+                rusqlite::params![
+                    job_id,
+                    status::COMPLETED,
+                    now,
+                    delivery.map(|d| d.sent_at_ms),
+                    delivery.and_then(|d| d.printed_at_ms),
+                    print_duration_ms as i64,
+                ],
+            )?;
+            tx.execute(
+                "INSERT OR REPLACE INTO completed_job_ids (id, completed_at) VALUES (?1, ?2)",
+                rusqlite::params![job_id, now],
             )?;
+            tx.commit()?;
             Ok(())
         })
         .await
         .map_err(|e| DaemonError::Queue(format!("Failed to mark job as completed: {}", e)))
     }
 
+    /// Most recently printed jobs, newest first
+    pub async fn recent_completed(&self, limit: usize) -> Result<Vec<RecentPrint>> {
+        let conn = self.db().await;
+
+        conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT order_number, station, printer_id, table_number, completed_at
+                FROM print_jobs
+                WHERE status = ?1 AND completed_at IS NOT NULL
+                ORDER BY completed_at DESC, rowid DESC
+                LIMIT ?2
+                "#,
+            )?;
+            let rows = stmt
+                .query_map(rusqlite::params![status::COMPLETED, limit as i64], |row| {
+                    Ok(RecentPrint {
+                        order_number: row.get(0)?,
+                        station: row.get(1)?,
+                        printer_id: row.get(2)?,
+                        table_number: row.get(3)?,
+                        completed_at: row.get(4)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to get recent prints: {}", e)))
+    }
+
+    /// Attach an operator note to a job. Returns the job with all its notes.
+    pub async fn add_job_note(&self, job_id: &str, note: &str) -> Result<JobHistoryEntry> {
+        let conn = self.db().await;
+        let job_id = job_id.to_string();
+        let note = note.trim().to_string();
+        let now = self.clock.unix_secs();
+
+        conn.call(move |conn| {
+            let tx = conn.transaction()?;
+            let Some(mut entry) = Self::history_entry(&tx, &job_id)? else {
+                return Ok(None);
+            };
+            tx.execute(
+                "INSERT INTO job_notes (job_id, note, created_at) VALUES (?1, ?2, ?3)",
+                rusqlite::params![job_id, note, now],
+            )?;
+            tx.commit()?;
+            entry.notes.push(JobNote { note, created_at: now });
+            Ok(Some(entry))
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to add job note: {}", e)))?
+        .ok_or_else(|| DaemonError::Queue("Job not found".to_string()))
+    }
+
+    /// Notes on a job, oldest first
+    pub async fn job_notes(&self, job_id: &str) -> Result<Vec<JobNote>> {
+        let conn = self.db().await;
+        let job_id = job_id.to_string();
+
+        conn.call(move |conn| Ok(Self::notes_for(conn, &job_id)?))
+            .await
+            .map_err(|e| DaemonError::Queue(format!("Failed to read job notes: {}", e)))
+    }
+
+    /// Most recent jobs (any status) with their notes, newest first
+    pub async fn job_history(&self, limit: usize) -> Result<Vec<JobHistoryEntry>> {
+        let conn = self.db().await;
+
+        conn.call(move |conn| {
+            let ids: Vec<String> = conn
+                .prepare("SELECT id FROM print_jobs ORDER BY created_at DESC, rowid DESC LIMIT ?1")?
+                .query_map([limit as i64], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            let mut entries = Vec::with_capacity(ids.len());
+            for id in ids {
+                entries.extend(Self::history_entry(conn, &id)?);
+            }
+            Ok(entries)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read job history: {}", e)))
+    }
+
+    fn history_entry(conn: &rusqlite::Connection, job_id: &str) -> rusqlite::Result<Option<JobHistoryEntry>> {
+        let entry = conn
+            .query_row(
+                r#"
+                SELECT id, order_id, order_number, station, printer_id, status, error_message,
+                       retry_count, created_at, completed_at, COALESCE(correlation_id, id)
+                FROM print_jobs WHERE id = ?1
+                "#,
+                [job_id],
+                |row| {
+                    Ok(JobHistoryEntry {
+                        id: row.get(0)?,
+                        order_id: row.get(1)?,
+                        order_number: row.get(2)?,
+                        station: row.get(3)?,
+                        printer_id: row.get(4)?,
+                        status: row.get(5)?,
+                        error_message: row.get(6)?,
+                        retry_count: row.get(7)?,
+                        created_at: row.get(8)?,
+                        completed_at: row.get(9)?,
+                        correlation_id: row.get(10)?,
+                        notes: Vec::new(),
+                    })
+                },
+            )
+            .optional()?;
+        let Some(mut entry) = entry else {
+            return Ok(None);
+        };
+        entry.notes = Self::notes_for(conn, job_id)?;
+        Ok(Some(entry))
+    }
+
+    fn notes_for(conn: &rusqlite::Connection, job_id: &str) -> rusqlite::Result<Vec<JobNote>> {
+        conn.prepare("SELECT note, created_at FROM job_notes WHERE job_id = ?1 ORDER BY id")?
+            .query_map([job_id], |row| Ok(JobNote { note: row.get(0)?, created_at: row.get(1)? }))?
+            .collect()
+    }
+
+    /// Whether a job with this ID was already printed here (within the retention window)
+    pub async fn was_completed(&self, job_id: &str) -> Result<bool> {
+        let conn = self.db().await;
+        let job_id = job_id.to_string();
+
+        conn.call(move |conn| {
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM completed_job_ids WHERE id = ?1",
+                [job_id],
+                |row| row.get(0),
+            )?;
+            Ok(count > 0)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to check completed job: {}", e)))
+    }
+
     /// Mark job as failed
     #[tracing::instrument(skip(self), fields(job_id))]
     pub async fn mark_failed(&self, job_id: &str, error_message: &str) -> Result<()> {
-        let conn = self.conn.lock().await;
+        self.forget_payload(job_id);
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
         let job_id = job_id.to_string();
         let error_message = error_message.to_string();
 
@@ -567,10 +1557,10 @@ impl QueueManager {
                 SET status = ?3,
                     error_message = ?2,
                     retry_count = retry_count + 1,
-                    completed_at = strftime('%s', 'now')
+                    completed_at = ?4
                 WHERE id = ?1
                 "#,
-                rusqlite::params![job_id, error_message, status::FAILED],
+                rusqlite::params![job_id, error_message, status::FAILED, now],
             )?;
             Ok(())
         })
@@ -583,7 +1573,8 @@ impl QueueManager {
     /// Backoff formula: delay = min(2^retry_count * 2s, 60s)
     /// retry 0 → 2s, retry 1 → 4s, retry 2 → 8s (max 3 retries)
     pub async fn retry_job(&self, job_id: &str) -> Result<()> {
-        let conn = self.conn.lock().await;
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
         let job_id = job_id.to_string();
 
         conn.call(move |conn| {
@@ -603,10 +1594,10 @@ impl QueueManager {
                 SET status = ?3,
                     retry_count = retry_count + 1,
                     processing_at = NULL,
-                    retry_after = strftime('%s', 'now') + ?2
+                    retry_after = ?4 + ?2
                 WHERE id = ?1 AND retry_count < 3
                 "#,
-                rusqlite::params![job_id, delay_secs, status::PENDING],
+                rusqlite::params![job_id, delay_secs, status::PENDING, now],
             )?;
             Ok(())
         })
@@ -614,174 +1605,1684 @@ impl QueueManager {
         .map_err(|e| DaemonError::Queue(format!("Failed to retry job: {}", e)))
     }
 
-    /// Escalate a pending job's priority (lower number = higher priority)
-    ///
-    /// Used when a job needs urgent attention (e.g., customer waiting).
-    /// Clamps to URGENT (1) minimum.
-    #[tracing::instrument(skip(self), fields(job_id, new_priority))]
-    pub async fn escalate_priority(&self, job_id: &str, new_priority: u8) -> Result<()> {
-        let clamped = new_priority.max(priority::URGENT);
-        info!("Escalating job {} priority to {}", job_id, clamped);
-
-        let conn = self.conn.lock().await;
+    /// Put a job blocked on its printer back to pending, tried again after
+    /// `BLOCKED_RETRY_SECS`. Not a retry: the retry count is left alone. Returns false,
+    /// leaving the job as it is, once the job is older than `BLOCKED_MAX_SECS`.
+    pub async fn block_job(&self, job_id: &str, error_message: &str) -> Result<bool> {
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
         let job_id = job_id.to_string();
+        let error_message = error_message.to_string();
 
         conn.call(move |conn| {
-            conn.execute(
+            let blocked = conn.execute(
                 r#"
                 UPDATE print_jobs
-                SET priority = ?2
-                WHERE id = ?1 AND status = ?3
+                SET status = ?3,
+                    error_message = ?2,
+                    processing_at = NULL,
+                    retry_after = ?4 + ?5
+                WHERE id = ?1 AND created_at > ?4 - ?6
                 "#,
-                rusqlite::params![job_id, clamped, status::PENDING],
+                rusqlite::params![job_id, error_message, status::PENDING, now, BLOCKED_RETRY_SECS, BLOCKED_MAX_SECS],
             )?;
-            Ok(())
+            Ok(blocked > 0)
         })
         .await
-        .map_err(|e| DaemonError::Queue(format!("Failed to escalate priority: {}", e)))
+        .map_err(|e| DaemonError::Queue(format!("Failed to block job: {}", e)))
     }
 
-    /// Get queue statistics with explicit total, pending, processing, completed, failed counts
-    ///
-    /// Returns a structured JSON object that the frontend can consume directly.
-    /// Uses COALESCE to ensure zero-counts are returned even when no jobs exist.
-    pub async fn get_stats(&self) -> Result<serde_json::Value> {
-        let conn = self.conn.lock().await;
-
-        let stats = conn
-            .call(|conn| {
-                let total: i64 = conn.query_row(
-                    "SELECT COUNT(*) FROM print_jobs",
-                    [],
-                    |row| row.get(0),
-                )?;
-
-                let pending: i64 = conn.query_row(
-                    "SELECT COUNT(*) FROM print_jobs WHERE status = ?1",
-                    [status::PENDING],
-                    |row| row.get(0),
-                )?;
-
-                let printing: i64 = conn.query_row(
-                    "SELECT COUNT(*) FROM print_jobs WHERE status = ?1",
-                    [status::PRINTING],
-                    |row| row.get(0),
-                )?;
-
-                let completed: i64 = conn.query_row(
-                    "SELECT COUNT(*) FROM print_jobs WHERE status = ?1",
-                    [status::COMPLETED],
-                    |row| row.get(0),
-                )?;
-
-                let failed: i64 = conn.query_row(
-                    "SELECT COUNT(*) FROM print_jobs WHERE status = ?1",
-                    [status::FAILED],
-                    |row| row.get(0),
-                )?;
-
-                Ok(serde_json::json!({
-                    "total": total,
-                    "pending": pending,
-                    "printing": printing,
-                    "completed": completed,
-                    "failed": failed
-                }))
-            })
-            .await
-            .map_err(|e| DaemonError::Queue(format!("Failed to get stats: {}", e)))?;
+    /// Hold a pending job routed to a paused printer for `BLOCKED_RETRY_SECS`, so the
+    /// processor doesn't pick it up on every tick. Not a retry and never a failure: the
+    /// job waits for as long as the printer stays paused, and no longer once it resumes.
+    pub async fn hold_job(&self, job_id: &str, printer_id: &str) -> Result<()> {
+        self.held_jobs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(job_id.to_string(), printer_id.to_string());
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+        let job_id = job_id.to_string();
 
-        Ok(stats)
+        conn.call(move |conn| {
+            conn.execute(
+                "UPDATE print_jobs SET retry_after = ?3 + ?4 WHERE id = ?1 AND status = ?2",
+                rusqlite::params![job_id, status::PENDING, now, BLOCKED_RETRY_SECS],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to hold job: {}", e)))
     }
 
-    /// Clean up old completed jobs (older than 7 days)
-    pub async fn cleanup_old_jobs(&self) -> Result<()> {
-        let conn = self.conn.lock().await;
+    /// Cancel a job that hasn't printed: pending, or printing and stopped before anything
+    /// was sent (the processor stops it, `JobCancellation::cancel_job`). A ticket already
+    /// going out finishes and is marked completed over this. False for unknown and finished
+    /// jobs.
+    pub async fn cancel_job(&self, job_id: &str) -> Result<bool> {
+        self.forget_payload(job_id);
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+        let job_id = job_id.to_string();
 
-        conn.call(|conn| {
-            conn.execute(
+        conn.call(move |conn| {
+            let cancelled = conn.execute(
                 r#"
-                DELETE FROM print_jobs
-                WHERE status IN (?1, ?2)
-                  AND completed_at < strftime('%s', 'now', '-7 days')
+                UPDATE print_jobs
+                SET status = ?2,
+                    processing_at = NULL,
+                    completed_at = ?3
+                WHERE id = ?1 AND status IN (?4, ?5)
                 "#,
-                rusqlite::params![status::COMPLETED, status::FAILED],
+                rusqlite::params![job_id, status::CANCELLED, now, status::PENDING, status::PRINTING],
             )?;
-            Ok(())
+            Ok(cancelled > 0)
         })
         .await
-        .map_err(|e| DaemonError::Queue(format!("Failed to cleanup old jobs: {}", e)))
+        .map_err(|e| DaemonError::Queue(format!("Failed to cancel job: {}", e)))
     }
 
-    /// Delete ALL jobs from the queue (used during factory reset)
-    pub async fn clear_all_jobs(&self) -> Result<()> {
-        let conn = self.conn.lock().await;
+    /// The order a job belongs to, as its pickup slip would show it; None for test prints
+    /// and unknown jobs
+    pub async fn order_of_job(&self, job_id: &str) -> Result<Option<PickupSlip>> {
+        let conn = self.db().await;
+        let job_id = job_id.to_string();
 
-        conn.call(|conn| {
-            conn.execute("DELETE FROM print_jobs", [])?;
-            Ok(())
+        conn.call(move |conn| {
+            conn.query_row(
+                "SELECT order_id, order_number, customer_name FROM print_jobs WHERE id = ?1 AND order_id IS NOT NULL",
+                [&job_id],
+                |row| {
+                    Ok(PickupSlip {
+                        order_id: row.get(0)?,
+                        order_number: row.get(1)?,
+                        customer_name: row.get(2)?,
+                    })
+                },
+            )
+            .optional()
         })
         .await
-        .map_err(|e| DaemonError::Queue(format!("Failed to clear all jobs: {}", e)))
+        .map_err(|e| DaemonError::Queue(format!("Failed to read job order: {}", e)))
     }
 
-    /// Process a job with exponential backoff retry
-    #[allow(dead_code)] // Infrastructure: will be called when job processor loop is implemented
-    pub async fn process_with_retry<F, Fut>(&self, job_id: &str, process_fn: F) -> Result<()>
-    where
-        F: Fn() -> Fut + Send + Sync,
-        Fut: std::future::Future<Output = Result<()>> + Send,
-    {
-        let retry_strategy = ExponentialBuilder::default()
-            .with_min_delay(Duration::from_millis(self.config.initial_retry_delay_ms))
-            .with_max_delay(Duration::from_millis(self.config.max_retry_delay_ms))
-            .with_max_times(self.config.max_retries as usize);
-
-        let result = (|| async { process_fn().await })
-            .retry(retry_strategy)
-            .await;
+    /// Put a job that was interrupted before printing (shutdown, pause) back to pending.
+    /// Not a retry: the retry count and backoff are left alone.
+    pub async fn release_job(&self, job_id: &str) -> Result<()> {
+        let conn = self.db().await;
+        let job_id = job_id.to_string();
 
-        match result {
-            Ok(_) => {
-                self.mark_completed(job_id, 0).await?;
-                Ok(())
-            }
-            Err(e) => {
-                self.mark_failed(job_id, &e.to_string()).await?;
-                Err(e)
-            }
-        }
+        conn.call(move |conn| {
+            conn.execute(
+                "UPDATE print_jobs SET status = ?2, processing_at = NULL WHERE id = ?1 AND status = ?3",
+                rusqlite::params![job_id, status::PENDING, status::PRINTING],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to release job: {}", e)))
     }
 
-    /// Flush SQLite WAL (Write-Ahead Log) to main database file
+    /// Put a printer's recently failed jobs back in the queue with a fresh retry budget.
     ///
-    /// Called during graceful shutdown to ensure all queued data is persisted.
-    /// Uses TRUNCATE mode which reclaims WAL file space.
-    pub async fn flush_db(&self) -> Result<()> {
-        info!("Flushing SQLite queue database to disk...");
-        let conn = self.conn.lock().await;
+    /// Used after the printer came back (e.g. under a new IP address). Also clears the
+    /// backoff on its pending retries so they go out right away. Returns the job IDs that
+    /// were moved from failed back to pending.
+    pub async fn requeue_failed_for_printer(&self, printer_id: &str, since_secs: u64) -> Result<Vec<String>> {
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+        let printer_id = printer_id.to_string();
 
-        conn.call(|conn| {
-            conn.execute_batch(
-                "PRAGMA wal_checkpoint(TRUNCATE); PRAGMA synchronous = FULL;",
+        conn.call(move |conn| {
+            let tx = conn.transaction()?;
+            let ids = {
+                let mut stmt = tx.prepare(
+                    r#"
+                    SELECT id FROM print_jobs
+                    WHERE printer_id = ?1 AND status = ?2
+                      AND completed_at >= ?4 - ?3
+                    "#,
+                )?;
+                let rows = stmt.query_map(rusqlite::params![printer_id, status::FAILED, since_secs, now], |row| {
+                    row.get::<_, String>(0)
+                })?;
+                rows.collect::<std::result::Result<Vec<_>, _>>()?
+            };
+
+            tx.execute(
+                r#"
+                UPDATE print_jobs
+                SET status = ?3,
+                    retry_count = 0,
+                    error_message = NULL,
+                    processing_at = NULL,
+                    completed_at = NULL,
+                    retry_after = NULL
+                WHERE printer_id = ?1 AND status = ?2
+                  AND completed_at >= ?5 - ?4
+                "#,
+                rusqlite::params![printer_id, status::FAILED, status::PENDING, since_secs, now],
             )?;
-            Ok(())
+            tx.execute(
+                "UPDATE print_jobs SET retry_after = NULL WHERE printer_id = ?1 AND status = ?2",
+                rusqlite::params![printer_id, status::PENDING],
+            )?;
+            tx.commit()?;
+            Ok(ids)
         })
         .await
-        .map_err(|e| DaemonError::Queue(format!("Failed to flush database: {}", e)))?;
-
-        info!("SQLite queue database flushed successfully");
-        Ok(())
+        .map_err(|e| DaemonError::Queue(format!("Failed to requeue jobs: {}", e)))
     }
 
-    /// Get count of in-progress jobs (for shutdown drain monitoring)
-    pub async fn get_processing_count(&self) -> Result<u64> {
-        let conn = self.conn.lock().await;
+    /// Jobs for a printer that failed permanently at or after `since` (Unix seconds),
+    /// oldest first
+    pub async fn failed_since(&self, printer_id: &str, since: i64) -> Result<Vec<RecoveryJob>> {
+        let conn = self.db().await;
+        let printer_id = printer_id.to_string();
 
-        conn.call(|conn| {
-            let count: i64 = conn.query_row(
-                "SELECT COUNT(*) FROM print_jobs WHERE status = ?1",
-                [status::PRINTING],
+        conn.call(move |conn| {
+            let jobs = conn
+                .prepare(
+                    r#"
+                    SELECT id, order_number FROM print_jobs
+                    WHERE printer_id = ?1 AND status = ?2 AND completed_at >= ?3
+                    ORDER BY created_at ASC, rowid ASC
+                    "#,
+                )?
+                .query_map(rusqlite::params![printer_id, status::FAILED, since], |row| {
+                    Ok(RecoveryJob { job_id: row.get(0)?, order_number: row.get(1)? })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(jobs)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read failed jobs: {}", e)))
+    }
+
+    /// Put the given failed jobs of a printer back to pending (recovery print) and lift the
+    /// retry backoff of its waiting jobs. Returns the IDs actually requeued (jobs reprinted
+    /// or removed meanwhile are skipped).
+    pub async fn requeue_jobs(&self, printer_id: &str, job_ids: Vec<String>) -> Result<Vec<String>> {
+        let conn = self.db().await;
+        let printer_id = printer_id.to_string();
+
+        conn.call(move |conn| {
+            let tx = conn.transaction()?;
+            let mut requeued = Vec::new();
+            for id in job_ids {
+                let changed = tx.execute(
+                    r#"
+                    UPDATE print_jobs
+                    SET status = ?3,
+                        retry_count = 0,
+                        error_message = NULL,
+                        processing_at = NULL,
+                        completed_at = NULL,
+                        retry_after = NULL
+                    WHERE id = ?1 AND printer_id = ?2 AND status = ?4
+                    "#,
+                    rusqlite::params![id, printer_id, status::PENDING, status::FAILED],
+                )?;
+                if changed > 0 {
+                    requeued.push(id);
+                }
+            }
+            tx.execute(
+                "UPDATE print_jobs SET retry_after = NULL WHERE printer_id = ?1 AND status = ?2",
+                rusqlite::params![printer_id, status::PENDING],
+            )?;
+            tx.commit()?;
+            Ok(requeued)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to requeue jobs: {}", e)))
+    }
+
+    /// Move a merged-away printer's queued and failed jobs to the printer it was merged
+    /// into (see `address_conflicts.rs`). Returns how many moved.
+    pub async fn reassign_printer_jobs(&self, from_printer_id: &str, to_printer_id: &str) -> Result<usize> {
+        let conn = self.db().await;
+        let from = from_printer_id.to_string();
+        let to = to_printer_id.to_string();
+
+        conn.call(move |conn| {
+            Ok(conn.execute(
+                "UPDATE print_jobs SET printer_id = ?2 WHERE printer_id = ?1 AND status IN (?3, ?4)",
+                rusqlite::params![from, to, status::PENDING, status::FAILED],
+            )?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to reassign jobs: {}", e)))
+    }
+
+    /// Write the pending/printing jobs to an encrypted snapshot file.
+    ///
+    /// The snapshot is a separate sqlcipher database (same PBKDF2-derived key scheme as the
+    /// queue itself), so it can be carried to a replacement machine and imported there.
+    /// An existing file at `path` is replaced. Returns the number of exported jobs.
+    pub async fn export_snapshot(&self, path: PathBuf, key: Zeroizing<String>) -> Result<usize> {
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .map_err(|e| DaemonError::Queue(format!("Failed to replace snapshot file: {}", e)))?;
+        }
+
+        let conn = self.db().await;
+        let path_str = path.to_string_lossy().to_string();
+
+        conn.call(move |conn| {
+            conn.execute("ATTACH DATABASE ?1 AS snapshot KEY ?2", rusqlite::params![path_str, key.as_str()])?;
+            let exported = (|| -> rusqlite::Result<usize> {
+                conn.pragma_update(Some(rusqlite::DatabaseName::Attached("snapshot")), "user_version", SNAPSHOT_VERSION)?;
+                conn.execute("CREATE TABLE snapshot.print_jobs AS SELECT * FROM main.print_jobs WHERE 0", [])?;
+                conn.execute(
+                    "INSERT INTO snapshot.print_jobs SELECT * FROM main.print_jobs WHERE status IN (?1, ?2)",
+                    rusqlite::params![status::PENDING, status::PRINTING],
+                )
+            })();
+            conn.execute("DETACH DATABASE snapshot", [])?;
+            Ok(exported?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to export queue snapshot: {}", e)))
+    }
+
+    /// Import jobs from a snapshot written by `export_snapshot`.
+    ///
+    /// Jobs come in as pending (a job that was mid-print on the old machine is printed
+    /// again rather than lost). Jobs already known here by ID, or by order + station, are
+    /// skipped. `route` maps a job's (station, printer_id) to a local printer when its
+    /// printer isn't configured on this machine.
+    pub async fn import_snapshot<F>(&self, path: PathBuf, key: Zeroizing<String>, route: F) -> Result<SnapshotImport>
+    where
+        F: Fn(&str, Option<&str>) -> Option<String> + Send + 'static,
+    {
+        if !path.exists() {
+            return Err(DaemonError::Queue(format!("Snapshot file not found: {}", path.display())));
+        }
+
+        let conn = self.db().await;
+        let path_str = path.to_string_lossy().to_string();
+
+        let result = conn
+            .call(move |conn| {
+                conn.execute("ATTACH DATABASE ?1 AS snapshot KEY ?2", rusqlite::params![path_str, key.as_str()])?;
+                // A wrong key (other restaurant) or a damaged file fails on the first read
+                let version = conn
+                    .pragma_query_value(Some(rusqlite::DatabaseName::Attached("snapshot")), "user_version", |row| row.get::<_, i32>(0))
+                    .and_then(|v| conn.query_row("SELECT count(*) FROM snapshot.sqlite_master", [], |_| Ok(v)));
+                let result = match version {
+                    Ok(SNAPSHOT_VERSION) => Self::copy_snapshot_jobs(conn, &route)
+                        .map_err(|e| DaemonError::Queue(format!("Failed to import queue snapshot: {}", e))),
+                    Ok(v) => Err(DaemonError::Queue(format!("Unsupported snapshot version {}", v))),
+                    Err(_) => Err(DaemonError::Queue("Snapshot could not be decrypted".to_string())),
+                };
+                conn.execute("DETACH DATABASE snapshot", [])?;
+                Ok(result)
+            })
+            .await
+            .map_err(|e| DaemonError::Queue(format!("Failed to import queue snapshot: {}", e)))?;
+
+        result
+    }
+
+    fn copy_snapshot_jobs<F>(conn: &mut rusqlite::Connection, route: &F) -> rusqlite::Result<SnapshotImport>
+    where
+        F: Fn(&str, Option<&str>) -> Option<String>,
+    {
+        let tx = conn.transaction()?;
+        let mut summary = SnapshotImport::default();
+        {
+            // Snapshots from older releases lack the columns added since: their jobs get
+            // the defaults the queue migrations give old jobs
+            let present: Vec<String> = tx
+                .prepare("PRAGMA snapshot.table_info(print_jobs)")?
+                .query_map([], |row| row.get(1))?
+                .collect::<rusqlite::Result<_>>()?;
+            let column = |name: &str, fallback: &str| {
+                if present.iter().any(|c| c == name) {
+                    name.to_string()
+                } else {
+                    format!("{} AS {}", fallback, name)
+                }
+            };
+            let mut select = tx.prepare(&format!(
+                r#"
+                SELECT id, restaurant_id, order_id, order_number, station, printer_id, items,
+                       table_number, customer_name, order_type, priority, timestamp, retry_count, created_at,
+                       {}, {}, {}, {}, {}, {}, {}
+                FROM snapshot.print_jobs
+                "#,
+                column("copies", "1"),
+                column("correlation_id", "NULL"),
+                column("pickup_time", "NULL"),
+                column("kind", "'order'"),
+                column("original_job_id", "NULL"),
+                column("removed_items", "NULL"),
+                column("order_stations", "NULL"),
+            ))?;
+            let mut rows = select.query([])?;
+
+            while let Some(row) = rows.next()? {
+                let id: String = row.get(0)?;
+                let order_id: Option<String> = row.get(2)?;
+                let station: String = row.get(4)?;
+                let printer_id: Option<String> = row.get(5)?;
+                let kind: String = row.get(17)?;
+
+                // Void and amend tickets share their original's order and station
+                let duplicate: bool = tx.query_row(
+                    r#"
+                    SELECT COUNT(*) > 0 FROM main.print_jobs
+                    WHERE id = ?1
+                       OR (?2 IS NOT NULL AND ?4 = 'order' AND order_id = ?2 AND station = ?3 AND kind = 'order')
+                    "#,
+                    rusqlite::params![id, order_id, station, kind],
+                    |r| r.get(0),
+                )?;
+                if duplicate {
+                    summary.skipped_duplicates += 1;
+                    continue;
+                }
+
+                let local_printer = route(&station, printer_id.as_deref());
+                if local_printer.is_some() && local_printer != printer_id {
+                    summary.rerouted += 1;
+                }
+
+                tx.execute(
+                    r#"
+                    INSERT INTO main.print_jobs (
+                        id, restaurant_id, order_id, order_number, station, printer_id, items,
+                        table_number, customer_name, order_type, priority, timestamp, status,
+                        retry_count, created_at, copies, correlation_id, pickup_time, kind,
+                        original_job_id, removed_items, order_stations
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)
+                    "#,
+                    rusqlite::params![
+                        id,
+                        row.get::<_, String>(1)?,
+                        order_id,
+                        row.get::<_, String>(3)?,
+                        station,
+                        local_printer.or(printer_id),
+                        row.get::<_, String>(6)?,
+                        row.get::<_, Option<String>>(7)?,
+                        row.get::<_, Option<String>>(8)?,
+                        row.get::<_, Option<String>>(9)?,
+                        row.get::<_, i64>(10)?,
+                        row.get::<_, i64>(11)?,
+                        status::PENDING,
+                        row.get::<_, i64>(12)?,
+                        row.get::<_, Option<i64>>(13)?,
+                        row.get::<_, rusqlite::types::Value>(14)?,
+                        row.get::<_, rusqlite::types::Value>(15)?,
+                        row.get::<_, rusqlite::types::Value>(16)?,
+                        kind,
+                        row.get::<_, Option<String>>(18)?,
+                        row.get::<_, Option<String>>(19)?,
+                        row.get::<_, Option<String>>(20)?,
+                    ],
+                )?;
+                summary.imported += 1;
+            }
+        }
+        tx.commit()?;
+
+        Ok(summary)
+    }
+
+    /// Append an entry to the audit log
+    pub async fn record_audit(&self, action: &str, subject: &str, details: serde_json::Value) -> Result<()> {
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+        let action = action.to_string();
+        let subject = subject.to_string();
+        let details = details.to_string();
+
+        conn.call(move |conn| {
+            conn.execute(
+                "INSERT INTO audit_log (action, subject, details, created_at) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![action, subject, details, now],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to write audit log: {}", e)))
+    }
+
+    /// Most recent audit log entries, newest first
+    pub async fn get_audit_log(&self, limit: usize) -> Result<Vec<serde_json::Value>> {
+        let conn = self.db().await;
+
+        conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT action, subject, details, created_at FROM audit_log ORDER BY id DESC LIMIT ?1",
+            )?;
+            let rows = stmt.query_map([limit as i64], |row| {
+                let details: String = row.get(2)?;
+                Ok(serde_json::json!({
+                    "action": row.get::<_, String>(0)?,
+                    "subject": row.get::<_, String>(1)?,
+                    "details": serde_json::from_str::<serde_json::Value>(&details).unwrap_or(serde_json::Value::Null),
+                    "created_at": row.get::<_, i64>(3)?,
+                }))
+            })?;
+            Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read audit log: {}", e)))
+    }
+
+    /// Persist a circuit breaker transition
+    pub async fn record_breaker_transition(&self, transition: &BreakerTransition) -> Result<()> {
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+        let business_date = business_date::for_unix_secs(now, self.rollover_hour);
+        let transition = transition.clone();
+
+        conn.call(move |conn| {
+            conn.execute(
+                r#"
+                INSERT INTO breaker_transitions
+                    (printer_id, from_state, to_state, reason, detail, duration_ms, business_date, created_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                "#,
+                rusqlite::params![
+                    transition.printer_id,
+                    transition.from.as_str(),
+                    transition.to.as_str(),
+                    transition.reason,
+                    transition.detail,
+                    transition.duration_ms as i64,
+                    business_date,
+                    now,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to record breaker transition: {}", e)))
+    }
+
+    /// A printer's breaker transitions, newest first
+    pub async fn get_breaker_history(&self, printer_id: &str, limit: usize) -> Result<Vec<BreakerHistoryEntry>> {
+        let conn = self.db().await;
+        let printer_id = printer_id.to_string();
+
+        conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT from_state, to_state, reason, detail, duration_ms, business_date, created_at
+                FROM breaker_transitions
+                WHERE printer_id = ?1
+                ORDER BY id DESC
+                LIMIT ?2
+                "#,
+            )?;
+            let rows = stmt.query_map(rusqlite::params![printer_id, limit as i64], |row| {
+                Ok(BreakerHistoryEntry {
+                    from_state: row.get(0)?,
+                    to_state: row.get(1)?,
+                    reason: row.get(2)?,
+                    detail: row.get(3)?,
+                    duration_ms: row.get::<_, i64>(4)? as u64,
+                    business_date: row.get(5)?,
+                    created_at: row.get(6)?,
+                })
+            })?;
+            Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read breaker history: {}", e)))
+    }
+
+    /// Minutes per printer per business day that breakers were open or half-open, from
+    /// `since` (YYYY-MM-DD) on. A degraded period counts towards the day it ended; one
+    /// still in progress is not counted yet.
+    pub async fn degraded_minutes(&self, since: &str, printer_id: Option<&str>) -> Result<Vec<DegradedDay>> {
+        let conn = self.db().await;
+        let since = since.to_string();
+        let printer_id = printer_id.map(str::to_string);
+
+        conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT printer_id, business_date, SUM(duration_ms)
+                FROM breaker_transitions
+                WHERE from_state != ?1
+                  AND business_date >= ?2
+                  AND (?3 IS NULL OR printer_id = ?3)
+                GROUP BY printer_id, business_date
+                ORDER BY business_date, printer_id
+                "#,
+            )?;
+            let rows = stmt.query_map(
+                rusqlite::params![CircuitState::Closed.as_str(), since, printer_id],
+                |row| {
+                    Ok(DegradedDay {
+                        printer_id: row.get(0)?,
+                        business_date: row.get(1)?,
+                        minutes: (row.get::<_, i64>(2)? as u64).div_ceil(60_000),
+                    })
+                },
+            )?;
+            Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read degraded minutes: {}", e)))
+    }
+
+    /// Persist a mechanical fault and whether the recovery attempt cleared it. Returns the
+    /// printer's faults within `jam_recovery::RECURRING_WINDOW_SECS`, this one included.
+    pub async fn record_mechanical_fault(&self, printer_id: &str, fault: MechanicalFault, recovered: bool) -> Result<u32> {
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+        let since = now - jam_recovery::RECURRING_WINDOW_SECS;
+        let printer_id = printer_id.to_string();
+
+        conn.call(move |conn| {
+            conn.execute(
+                "INSERT INTO mechanical_faults (printer_id, fault, recovered, created_at) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![printer_id, fault.as_str(), recovered, now],
+            )?;
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM mechanical_faults WHERE printer_id = ?1 AND created_at >= ?2",
+                rusqlite::params![printer_id, since],
+                |row| row.get(0),
+            )?;
+            Ok(count as u32)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to record mechanical fault: {}", e)))
+    }
+
+    /// Mechanical faults per printer since `since` (Unix seconds), most faults first
+    pub async fn mechanical_faults(&self, since: i64) -> Result<Vec<MechanicalFaultSummary>> {
+        let conn = self.db().await;
+
+        conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT printer_id, COUNT(*), COALESCE(SUM(recovered), 0), MAX(created_at)
+                FROM mechanical_faults
+                WHERE created_at >= ?1
+                GROUP BY printer_id
+                ORDER BY COUNT(*) DESC, printer_id
+                "#,
+            )?;
+            let rows = stmt.query_map([since], |row| {
+                Ok(MechanicalFaultSummary {
+                    printer_id: row.get(0)?,
+                    faults: row.get::<_, i64>(1)? as u32,
+                    recovered: row.get::<_, i64>(2)? as u32,
+                    last_at: row.get(3)?,
+                })
+            })?;
+            Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read mechanical faults: {}", e)))
+    }
+
+    /// Count a print attempt in the printer's lifetime stats
+    pub async fn record_printer_attempt(&self, attempt: &Attempt) -> Result<()> {
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+        let attempt = attempt.clone();
+
+        conn.call(move |conn| {
+            let (printed, failed, bytes, print_ms, error) = match attempt.outcome {
+                Outcome::Printed { bytes, duration_ms } => (1, 0, bytes as i64, duration_ms as i64, None),
+                Outcome::Failed { error } => (0, 1, 0, 0, Some(error)),
+            };
+            conn.execute(
+                r#"
+                INSERT INTO printer_stats
+                    (printer_id, jobs_printed, jobs_failed, bytes_sent, print_ms,
+                     last_error, last_error_at, last_printed_at, since, revision)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, CASE WHEN ?6 IS NULL THEN NULL ELSE ?7 END,
+                        CASE WHEN ?2 = 1 THEN ?7 END, ?7, 1)
+                ON CONFLICT(printer_id) DO UPDATE SET
+                    jobs_printed = jobs_printed + excluded.jobs_printed,
+                    jobs_failed = jobs_failed + excluded.jobs_failed,
+                    bytes_sent = bytes_sent + excluded.bytes_sent,
+                    print_ms = print_ms + excluded.print_ms,
+                    last_error = COALESCE(excluded.last_error, last_error),
+                    last_error_at = COALESCE(excluded.last_error_at, last_error_at),
+                    last_printed_at = COALESCE(excluded.last_printed_at, last_printed_at),
+                    revision = revision + 1
+                "#,
+                rusqlite::params![attempt.printer_id, printed, failed, bytes, print_ms, error, now],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to record printer stats: {}", e)))
+    }
+
+    /// Lifetime stats of every printer that printed or failed at least once; with
+    /// `unsynced`, only those changed since they were last synced to Supabase
+    pub async fn printer_stats(&self, unsynced: bool) -> Result<Vec<PrinterStats>> {
+        let conn = self.db().await;
+
+        conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT printer_id, jobs_printed, jobs_failed, bytes_sent, print_ms,
+                       last_error, last_error_at, last_printed_at, since, revision
+                FROM printer_stats
+                WHERE ?1 = 0 OR revision > synced_revision
+                ORDER BY printer_id
+                "#,
+            )?;
+            let rows = stmt.query_map([unsynced], |row| {
+                let jobs_printed = row.get::<_, i64>(1)? as u64;
+                let print_ms = row.get::<_, i64>(4)? as u64;
+                Ok(PrinterStats {
+                    printer_id: row.get(0)?,
+                    jobs_printed,
+                    jobs_failed: row.get::<_, i64>(2)? as u64,
+                    bytes_sent: row.get::<_, i64>(3)? as u64,
+                    avg_duration_ms: (jobs_printed > 0).then(|| print_ms / jobs_printed),
+                    last_error: row.get(5)?,
+                    last_error_at: row.get(6)?,
+                    last_printed_at: row.get(7)?,
+                    since: row.get(8)?,
+                    revision: row.get(9)?,
+                })
+            })?;
+            Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read printer stats: {}", e)))
+    }
+
+    /// Record that these stats reached Supabase. Changes made while they were on their
+    /// way have a higher revision and go with the next sync.
+    pub async fn mark_printer_stats_synced(&self, stats: &[PrinterStats]) -> Result<()> {
+        let conn = self.db().await;
+        let synced: Vec<(String, i64)> = stats.iter().map(|s| (s.printer_id.clone(), s.revision)).collect();
+
+        conn.call(move |conn| {
+            let tx = conn.transaction()?;
+            for (printer_id, revision) in synced {
+                tx.execute(
+                    "UPDATE printer_stats SET synced_revision = MAX(synced_revision, ?2) WHERE printer_id = ?1",
+                    rusqlite::params![printer_id, revision],
+                )?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to mark printer stats synced: {}", e)))
+    }
+
+    /// Record the start of a printer-down incident. Returns its id.
+    pub async fn open_incident(&self, printer_id: &str, started_at: i64, cause: &str) -> Result<i64> {
+        let conn = self.db().await;
+        let printer_id = printer_id.to_string();
+        let cause = cause.to_string();
+
+        conn.call(move |conn| {
+            conn.execute(
+                "INSERT INTO printer_incidents (printer_id, started_at, cause) VALUES (?1, ?2, ?3)",
+                rusqlite::params![printer_id, started_at, cause],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to open incident: {}", e)))
+    }
+
+    /// Close an incident, counting the printer's jobs that waited during it: queued before
+    /// it ended and not printed (or failed) before it started
+    pub async fn close_incident(&self, incident_id: i64, ended_at: i64) -> Result<()> {
+        let conn = self.db().await;
+
+        conn.call(move |conn| {
+            conn.execute(
+                &format!(
+                    "UPDATE printer_incidents SET ended_at = ?2, affected_jobs = ({}), revision = revision + 1
+                     WHERE id = ?1 AND ended_at IS NULL",
+                    AFFECTED_JOBS_SQL
+                ),
+                rusqlite::params![incident_id, ended_at],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to close incident: {}", e)))
+    }
+
+    /// Incidents, newest first: the open ones (`open_only`) or the latest `limit`
+    pub async fn incidents(&self, open_only: bool, limit: usize) -> Result<Vec<Incident>> {
+        self.read_incidents(
+            if open_only { "ended_at IS NULL" } else { "1" },
+            limit,
+        )
+        .await
+    }
+
+    /// Incidents opened or closed since their last sync, with their revision
+    pub async fn unsynced_incidents(&self) -> Result<Vec<(Incident, i64)>> {
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+
+        conn.call(move |conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {}, revision FROM printer_incidents i WHERE revision > synced_revision ORDER BY id",
+                incident_columns()
+            ))?;
+            let rows = stmt.query_map([now], |row| Ok((incident_from_row(row)?, row.get(6)?)))?;
+            Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read unsynced incidents: {}", e)))
+    }
+
+    /// Record that these incident revisions reached Supabase
+    pub async fn mark_incidents_synced(&self, synced: &[(i64, i64)]) -> Result<()> {
+        let conn = self.db().await;
+        let synced = synced.to_vec();
+
+        conn.call(move |conn| {
+            let tx = conn.transaction()?;
+            for (id, revision) in synced {
+                tx.execute(
+                    "UPDATE printer_incidents SET synced_revision = MAX(synced_revision, ?2) WHERE id = ?1",
+                    rusqlite::params![id, revision],
+                )?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to mark incidents synced: {}", e)))
+    }
+
+    async fn read_incidents(&self, filter: &'static str, limit: usize) -> Result<Vec<Incident>> {
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+
+        conn.call(move |conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM printer_incidents i WHERE {} ORDER BY started_at DESC, id DESC LIMIT ?2",
+                incident_columns(),
+                filter
+            ))?;
+            let rows = stmt.query_map(rusqlite::params![now, limit as i64], incident_from_row)?;
+            Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read incidents: {}", e)))
+    }
+
+    /// Claim an idempotency key for `job`. Returns the job that already holds it (within
+    /// `IDEMPOTENCY_RETENTION_SECS`) instead; lookup and insert are one call, so two
+    /// concurrent retries can't both claim it.
+    pub async fn claim_idempotency_key(&self, key: &str, job: &IdempotentJob) -> Result<Option<IdempotentJob>> {
+        let conn = self.db().await;
+        let key = key.to_string();
+        let job = job.clone();
+        let now = self.clock.unix_secs();
+        let cutoff = now - IDEMPOTENCY_RETENTION_SECS;
+
+        conn.call(move |conn| {
+            let existing = conn
+                .query_row(
+                    r#"
+                    SELECT job_id, correlation_id, body_hash FROM api_idempotency_keys
+                    WHERE key = ?1 AND created_at >= ?2
+                    "#,
+                    rusqlite::params![key, cutoff],
+                    |row| {
+                        Ok(IdempotentJob {
+                            job_id: row.get(0)?,
+                            correlation_id: row.get(1)?,
+                            body_hash: row.get(2)?,
+                        })
+                    },
+                )
+                .optional()?;
+            if existing.is_none() {
+                // An expired entry is replaced
+                conn.execute(
+                    r#"
+                    INSERT OR REPLACE INTO api_idempotency_keys (key, job_id, correlation_id, body_hash, created_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5)
+                    "#,
+                    rusqlite::params![key, job.job_id, job.correlation_id, job.body_hash, now],
+                )?;
+            }
+            Ok(existing)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to claim idempotency key: {}", e)))
+    }
+
+    /// Give up a claimed idempotency key whose job was never queued, so a retry can
+    pub async fn release_idempotency_key(&self, key: &str, job_id: &str) -> Result<()> {
+        let conn = self.db().await;
+        let key = key.to_string();
+        let job_id = job_id.to_string();
+
+        conn.call(move |conn| {
+            conn.execute(
+                "DELETE FROM api_idempotency_keys WHERE key = ?1 AND job_id = ?2",
+                rusqlite::params![key, job_id],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to release idempotency key: {}", e)))
+    }
+
+    /// Queue a pickup slip on request (POS, dashboard). Asking again for the same order
+    /// prints it again.
+    pub async fn request_pickup_slip(&self, slip: &PickupSlip) -> Result<()> {
+        let conn = self.db().await;
+        let slip = slip.clone();
+        let now = self.clock.unix_secs();
+
+        conn.call(move |conn| {
+            conn.execute(
+                r#"
+                INSERT INTO pickup_slips (order_id, order_number, customer_name, status, attempts, created_at)
+                VALUES (?1, ?2, ?3, ?4, 0, ?5)
+                ON CONFLICT(order_id) DO UPDATE SET
+                    order_number = excluded.order_number,
+                    customer_name = excluded.customer_name,
+                    status = excluded.status,
+                    attempts = 0,
+                    error_message = NULL,
+                    created_at = excluded.created_at
+                "#,
+                rusqlite::params![slip.order_id, slip.order_number, slip.customer_name, status::PENDING, now],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to queue pickup slip: {}", e)))
+    }
+
+    /// Record `order_id` as fully printed if none of its tickets is still open and every
+    /// station in its `order_stations` has one (call after `mark_completed` and
+    /// `cancel_job`). True if this call completed it: when two stations finish at once
+    /// only one of them sees the order complete, so it is reported once.
+    pub async fn complete_order_if_done(&self, order_id: &str) -> Result<bool> {
+        let conn = self.db().await;
+        let order_id = order_id.to_string();
+        let now = self.clock.unix_secs();
+
+        conn.call(move |conn| {
+            let (open, printed): (i64, i64) = conn.query_row(
+                "SELECT COALESCE(SUM(status NOT IN (?2, ?3)), 0), COALESCE(SUM(status = ?2), 0)
+                 FROM print_jobs WHERE order_id = ?1",
+                rusqlite::params![order_id, status::COMPLETED, status::CANCELLED],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            // An order whose every ticket was cancelled didn't print
+            if open > 0 || printed == 0 {
+                return Ok(false);
+            }
+            // A station whose ticket wasn't polled yet would otherwise be reported done
+            let unseen: i64 = conn.query_row(
+                &format!("SELECT COUNT(*) FROM ({})", UNSEEN_STATIONS_SQL),
+                [&order_id],
+                |row| row.get(0),
+            )?;
+            if unseen > 0 {
+                return Ok(false);
+            }
+            let completed = conn.execute(
+                "INSERT OR IGNORE INTO order_completions (order_id, completed_at) VALUES (?1, ?2)",
+                rusqlite::params![order_id, now],
+            )?;
+            Ok(completed == 1)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to check order completion: {}", e)))
+    }
+
+    /// Jobs matching a front-of-house search, newest first (at most `JOB_SEARCH_LIMIT`)
+    pub async fn find_jobs(&self, search: &JobSearch) -> Result<Vec<JobSearchResult>> {
+        let given = |value: &Option<String>| value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+        let table_number = given(&search.table_number);
+        let customer_name = given(&search.customer_name);
+        let order_number = given(&search.order_number);
+        if table_number.is_none() && customer_name.is_none() && order_number.is_none() {
+            return Err(DaemonError::Queue(
+                "Search needs a table number, customer name or order number".to_string(),
+            ));
+        }
+        let until = search.until.unwrap_or_else(|| self.clock.unix_secs());
+        let since = search.since.unwrap_or(until - JOB_SEARCH_DEFAULT_WINDOW_SECS);
+
+        let mut clauses = vec!["created_at >= ?1".to_string(), "created_at <= ?2".to_string()];
+        let mut params: Vec<rusqlite::types::Value> = vec![since.into(), until.into()];
+        let mut bind = |value: String| {
+            params.push(value.into());
+            format!("?{}", params.len())
+        };
+        if let Some(table_number) = table_number {
+            clauses.push(format!("table_number = {} COLLATE NOCASE", bind(table_number)));
+        }
+        if let Some(order_number) = order_number {
+            clauses.push(format!("order_number = {} COLLATE NOCASE", bind(order_number)));
+        }
+        if let Some(prefix) = customer_name {
+            // Range instead of LIKE, so the NOCASE index serves the prefix match
+            let upper = format!("{}\u{10FFFF}", prefix);
+            clauses.push(format!(
+                "customer_name >= {} COLLATE NOCASE AND customer_name < {} COLLATE NOCASE",
+                bind(prefix),
+                bind(upper)
+            ));
+        }
+        let sql = format!(
+            "SELECT id, table_number, customer_name FROM print_jobs WHERE {} ORDER BY created_at DESC, rowid DESC LIMIT {}",
+            clauses.join(" AND "),
+            JOB_SEARCH_LIMIT
+        );
+
+        let conn = self.db().await;
+        conn.call(move |conn| {
+            let rows: Vec<(String, Option<String>, Option<String>)> = conn
+                .prepare(&sql)?
+                .query_map(rusqlite::params_from_iter(params), |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<rusqlite::Result<_>>()?;
+            let mut results = Vec::with_capacity(rows.len());
+            for (id, table_number, customer_name) in rows {
+                if let Some(job) = Self::history_entry(conn, &id)? {
+                    results.push(JobSearchResult { job, table_number, customer_name });
+                }
+            }
+            Ok(results)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to search jobs: {}", e)))
+    }
+
+    /// An order's tickets and which stations are done; None for an unknown order
+    pub async fn order_jobs(&self, order_id: &str) -> Result<Option<OrderView>> {
+        let conn = self.db().await;
+        let order_id = order_id.to_string();
+
+        conn.call(move |conn| {
+            let ids: Vec<String> = conn
+                .prepare("SELECT id FROM print_jobs WHERE order_id = ?1 ORDER BY created_at, rowid")?
+                .query_map([&order_id], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            let mut jobs = Vec::with_capacity(ids.len());
+            for id in ids {
+                jobs.extend(Self::history_entry(conn, &id)?);
+            }
+            let Some(first) = jobs.first() else {
+                return Ok(None);
+            };
+            let order_number = first.order_number.clone();
+
+            // Per station, in the order tickets came in: printed once all its tickets are
+            // (cancelled tickets left out)
+            let mut stations: Vec<(String, bool)> = Vec::new();
+            for job in jobs.iter().filter(|job| job.status != status::CANCELLED) {
+                let printed = job.status == status::COMPLETED;
+                match stations.iter_mut().find(|(station, _)| *station == job.station) {
+                    Some((_, all_printed)) => *all_printed &= printed,
+                    None => stations.push((job.station.clone(), printed)),
+                }
+            }
+            let (printed, pending): (Vec<_>, Vec<_>) = stations.into_iter().partition(|(_, printed)| *printed);
+            let stations_printed = printed.into_iter().map(|(station, _)| station).collect();
+            let mut stations_pending: Vec<String> = pending.into_iter().map(|(station, _)| station).collect();
+            // Stations still to come are pending too
+            stations_pending.extend(
+                conn.prepare(UNSEEN_STATIONS_SQL)?
+                    .query_map([&order_id], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()?,
+            );
+            let completed_at = conn
+                .query_row(
+                    "SELECT completed_at FROM order_completions WHERE order_id = ?1",
+                    [&order_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            Ok(Some(OrderView {
+                order_id,
+                order_number,
+                jobs,
+                stations_printed,
+                stations_pending,
+                completed_at,
+            }))
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read order jobs: {}", e)))
+    }
+
+    /// Queue an order's automatic pickup slip. True if queued: an order gets one
+    /// automatic slip, and a slip the POS already asked for is not printed twice.
+    pub async fn queue_pickup_slip(&self, slip: &PickupSlip) -> Result<bool> {
+        let conn = self.db().await;
+        let slip = slip.clone();
+        let now = self.clock.unix_secs();
+
+        conn.call(move |conn| {
+            let queued = conn.execute(
+                r#"
+                INSERT OR IGNORE INTO pickup_slips (order_id, order_number, customer_name, status, attempts, created_at)
+                VALUES (?1, ?2, ?3, ?4, 0, ?5)
+                "#,
+                rusqlite::params![slip.order_id, slip.order_number, slip.customer_name, status::PENDING, now],
+            )?;
+            Ok(queued == 1)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to queue pickup slip: {}", e)))
+    }
+
+    /// Pickup slips waiting to print, oldest first
+    pub async fn pending_pickup_slips(&self, limit: usize) -> Result<Vec<PickupSlip>> {
+        let conn = self.db().await;
+
+        conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT order_id, order_number, customer_name FROM pickup_slips
+                WHERE status = ?1
+                ORDER BY created_at, rowid
+                LIMIT ?2
+                "#,
+            )?;
+            let rows = stmt.query_map(rusqlite::params![status::PENDING, limit as i64], |row| {
+                Ok(PickupSlip {
+                    order_id: row.get(0)?,
+                    order_number: row.get(1)?,
+                    customer_name: row.get(2)?,
+                })
+            })?;
+            Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read pickup slips: {}", e)))
+    }
+
+    /// Record a pickup slip print attempt: printed, or the error. A slip that failed
+    /// `PICKUP_SLIP_ATTEMPTS` times is given up.
+    pub async fn finish_pickup_slip(&self, order_id: &str, error: Option<&str>) -> Result<()> {
+        let conn = self.db().await;
+        let order_id = order_id.to_string();
+        let error = error.map(str::to_string);
+        let now = self.clock.unix_secs();
+
+        conn.call(move |conn| {
+            match error {
+                None => conn.execute(
+                    "UPDATE pickup_slips SET status = ?2, printed_at = ?3, error_message = NULL WHERE order_id = ?1",
+                    rusqlite::params![order_id, status::COMPLETED, now],
+                )?,
+                Some(error) => conn.execute(
+                    r#"
+                    UPDATE pickup_slips
+                    SET attempts = attempts + 1,
+                        error_message = ?2,
+                        status = CASE WHEN attempts + 1 >= ?3 THEN ?4 ELSE status END
+                    WHERE order_id = ?1
+                    "#,
+                    rusqlite::params![order_id, error, PICKUP_SLIP_ATTEMPTS, status::FAILED],
+                )?,
+            };
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to update pickup slip: {}", e)))
+    }
+
+    /// Record a daemon start; returns the run's id
+    pub async fn start_daemon_run(&self, version: &str, started_at: i64) -> Result<i64> {
+        let conn = self.db().await;
+        let version = version.to_string();
+
+        conn.call(move |conn| {
+            conn.execute(
+                "INSERT INTO daemon_runs (version, started_at) VALUES (?1, ?2)",
+                rusqlite::params![version, started_at],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to record daemon start: {}", e)))
+    }
+
+    /// Record how a run ended (a run already closed is left alone)
+    pub async fn finish_daemon_run(&self, run_id: i64, stopped_at: i64, reason: StopReason) -> Result<()> {
+        let conn = self.db().await;
+
+        conn.call(move |conn| {
+            conn.execute(
+                "UPDATE daemon_runs SET stopped_at = ?2, stop_reason = ?3 WHERE id = ?1 AND stop_reason IS NULL",
+                rusqlite::params![run_id, stopped_at, reason.as_str()],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to record daemon stop: {}", e)))
+    }
+
+    /// Most recent daemon runs, newest first
+    pub async fn daemon_runs(&self, limit: usize) -> Result<Vec<DaemonRun>> {
+        let conn = self.db().await;
+
+        conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT id, version, started_at, stopped_at, stop_reason
+                FROM daemon_runs
+                ORDER BY started_at DESC, id DESC
+                LIMIT ?1
+                "#,
+            )?;
+            let rows = stmt.query_map([limit as i64], |row| {
+                Ok(DaemonRun {
+                    id: row.get(0)?,
+                    version: row.get(1)?,
+                    started_at: row.get(2)?,
+                    stopped_at: row.get(3)?,
+                    stop_reason: row.get::<_, Option<String>>(4)?.as_deref().and_then(StopReason::parse),
+                })
+            })?;
+            Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to read restart history: {}", e)))
+    }
+
+    /// Starts and crashed runs since `since` (Unix seconds)
+    pub async fn restart_counts(&self, since: i64) -> Result<(u32, u32)> {
+        let conn = self.db().await;
+
+        conn.call(move |conn| {
+            Ok(conn.query_row(
+                r#"
+                SELECT COUNT(*), COALESCE(SUM(stop_reason = 'crash'), 0)
+                FROM daemon_runs WHERE started_at >= ?1
+                "#,
+                [since],
+                |row| Ok((row.get::<_, i64>(0)? as u32, row.get::<_, i64>(1)? as u32)),
+            )?)
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to count restarts: {}", e)))
+    }
+
+    /// Escalate a pending job's priority (lower number = higher priority)
+    ///
+    /// Used when a job needs urgent attention (e.g., customer waiting).
+    /// Clamps to URGENT (1) minimum.
+    #[tracing::instrument(skip(self), fields(job_id, new_priority))]
+    pub async fn escalate_priority(&self, job_id: &str, new_priority: u8) -> Result<()> {
+        let clamped = new_priority.max(priority::URGENT);
+        info!("Escalating job {} priority to {}", job_id, clamped);
+
+        let conn = self.db().await;
+        let job_id = job_id.to_string();
+
+        conn.call(move |conn| {
+            conn.execute(
+                r#"
+                UPDATE print_jobs
+                SET priority = ?2
+                WHERE id = ?1 AND status = ?3
+                "#,
+                rusqlite::params![job_id, clamped, status::PENDING],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to escalate priority: {}", e)))
+    }
+
+    /// Get queue statistics with explicit total, pending, processing, completed, failed counts
+    ///
+    /// Returns a structured JSON object that the frontend can consume directly.
+    /// Uses COALESCE to ensure zero-counts are returned even when no jobs exist.
+    pub async fn get_stats(&self) -> Result<serde_json::Value> {
+        let conn = self.db().await;
+        let today = self.business_date();
+        let now = self.clock.unix_secs();
+
+        let mut stats = conn
+            .call(move |conn| {
+                let total: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM print_jobs",
+                    [],
+                    |row| row.get(0),
+                )?;
+
+                let pending: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM print_jobs WHERE status = ?1",
+                    [status::PENDING],
+                    |row| row.get(0),
+                )?;
+
+                let printing: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM print_jobs WHERE status = ?1",
+                    [status::PRINTING],
+                    |row| row.get(0),
+                )?;
+
+                let completed: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM print_jobs WHERE status = ?1",
+                    [status::COMPLETED],
+                    |row| row.get(0),
+                )?;
+
+                let failed: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM print_jobs WHERE status = ?1",
+                    [status::FAILED],
+                    |row| row.get(0),
+                )?;
+
+                // Counters for the current business day (doesn't reset at midnight mid-service)
+                let (completed_today, failed_today): (i64, i64) = conn.query_row(
+                    r#"
+                    SELECT COALESCE(SUM(status = ?2), 0), COALESCE(SUM(status = ?3), 0)
+                    FROM print_jobs WHERE business_date = ?1
+                    "#,
+                    rusqlite::params![today, status::COMPLETED, status::FAILED],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?;
+
+                let avg_retries: f64 = conn.query_row(
+                    "SELECT COALESCE(AVG(retry_count), 0.0) FROM print_jobs",
+                    [],
+                    |row| row.get(0),
+                )?;
+
+                Ok(serde_json::json!({
+                    "total": total,
+                    "pending": pending,
+                    "printing": printing,
+                    "completed": completed,
+                    "failed": failed,
+                    "business_date": today,
+                    "completed_today": completed_today,
+                    "failed_today": failed_today,
+                    "avg_retries": avg_retries,
+                    "by_station": Self::breakdown(conn, "station")?,
+                    "by_printer": Self::breakdown(conn, "COALESCE(printer_id, 'unassigned')")?,
+                    "hourly": Self::hourly(conn, now)?,
+                    "failure_reasons": Self::failure_reasons(conn)?,
+                }))
+            })
+            .await
+            .map_err(|e| DaemonError::Queue(format!("Failed to get stats: {}", e)))?;
+
+        stats["job_cache"] = serde_json::json!(self.job_cache_stats());
+        Ok(stats)
+    }
+
+    /// Job counts grouped by a column expression (`station`, printer), most failures first
+    fn breakdown(conn: &rusqlite::Connection, key: &str) -> rusqlite::Result<Vec<JobBreakdown>> {
+        let sql = format!(
+            r#"
+            SELECT {key}, COUNT(*), COALESCE(SUM(status = ?1), 0), COALESCE(SUM(status = ?2), 0),
+                   COALESCE(SUM(status = ?3), 0), AVG(retry_count)
+            FROM print_jobs
+            GROUP BY 1
+            ORDER BY 5 DESC, 2 DESC, 1
+            "#
+        );
+        conn.prepare(&sql)?
+            .query_map(rusqlite::params![status::PENDING, status::COMPLETED, status::FAILED], |row| {
+                Ok(JobBreakdown {
+                    key: row.get(0)?,
+                    total: row.get::<_, i64>(1)? as u64,
+                    pending: row.get::<_, i64>(2)? as u64,
+                    completed: row.get::<_, i64>(3)? as u64,
+                    failed: row.get::<_, i64>(4)? as u64,
+                    avg_retries: row.get::<_, Option<f64>>(5)?.unwrap_or(0.0),
+                })
+            })?
+            .collect()
+    }
+
+    /// Jobs per hour over the last 24 hours, oldest first, empty hours included
+    fn hourly(conn: &rusqlite::Connection, now: i64) -> rusqlite::Result<Vec<HourBucket>> {
+        let current = now - now.rem_euclid(3600);
+        let first = current - 23 * 3600;
+        let mut buckets: Vec<HourBucket> = (0..24)
+            .map(|i| HourBucket { hour: first + i * 3600, created: 0, completed: 0, failed: 0 })
+            .collect();
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT (created_at - ?1) / 3600, COUNT(*), COALESCE(SUM(status = ?2), 0), COALESCE(SUM(status = ?3), 0)
+            FROM print_jobs
+            WHERE created_at >= ?1
+            GROUP BY 1
+            "#,
+        )?;
+        let rows = stmt.query_map(rusqlite::params![first, status::COMPLETED, status::FAILED], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?, row.get::<_, i64>(3)?))
+        })?;
+        for row in rows {
+            let (index, created, completed, failed) = row?;
+            if let Some(bucket) = buckets.get_mut(index as usize) {
+                bucket.created = created as u64;
+                bucket.completed = completed as u64;
+                bucket.failed = failed as u64;
+            }
+        }
+        Ok(buckets)
+    }
+
+    /// Failed jobs grouped by error class, most common first
+    fn failure_reasons(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<FailureReason>> {
+        let sql = format!(
+            "SELECT {ERROR_CLASS_SQL}, COUNT(*) FROM print_jobs WHERE status = ?1 GROUP BY 1 ORDER BY 2 DESC, 1"
+        );
+        conn.prepare(&sql)?
+            .query_map([status::FAILED], |row| {
+                Ok(FailureReason { class: row.get(0)?, count: row.get::<_, i64>(1)? as u64 })
+            })?
+            .collect()
+    }
+
+    /// Per-station print counts for one business date (retention: 7 days)
+    pub async fn get_day_summary(&self, business_date: String) -> Result<DaySummary> {
+        let conn = self.db().await;
+
+        conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT station, COALESCE(SUM(status = ?2), 0), COALESCE(SUM(status = ?3), 0)
+                FROM print_jobs
+                WHERE business_date = ?1
+                GROUP BY station
+                ORDER BY station
+                "#,
+            )?;
+            let stations = stmt
+                .query_map(
+                    rusqlite::params![business_date, status::COMPLETED, status::FAILED],
+                    |row| {
+                        Ok(StationSummary {
+                            station: row.get(0)?,
+                            printed: row.get(1)?,
+                            failed: row.get(2)?,
+                        })
+                    },
+                )?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            Ok(DaySummary {
+                printed: stations.iter().map(|s| s.printed).sum(),
+                failed: stations.iter().map(|s| s.failed).sum(),
+                business_date,
+                stations,
+            })
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to get day summary: {}", e)))
+    }
+
+    /// Queue depth and saturation state
+    pub async fn get_health(&self) -> Result<QueueHealth> {
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+
+        let (pending, printing, oldest) = conn
+            .call(move |conn| {
+                let row = conn.query_row(
+                    r#"
+                    SELECT
+                        COALESCE(SUM(status = ?1), 0),
+                        COALESCE(SUM(status = ?2), 0),
+                        MIN(CASE WHEN status = ?1 THEN created_at END)
+                    FROM print_jobs
+                    "#,
+                    rusqlite::params![status::PENDING, status::PRINTING],
+                    |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, Option<i64>>(2)?)),
+                )?;
+                Ok(row)
+            })
+            .await
+            .map_err(|e| DaemonError::Queue(format!("Failed to get queue health: {}", e)))?;
+
+        let oldest_pending_secs = oldest.map(|created| (now - created).max(0) as u64).unwrap_or(0);
+        Ok(QueueHealth::new(pending as u64, printing as u64, oldest_pending_secs))
+    }
+
+    /// Load per station: stations with jobs waiting or printing, or with tickets this
+    /// business day. Busiest (longest backlog) first.
+    pub async fn station_load(&self) -> Result<Vec<StationLoad>> {
+        let conn = self.db().await;
+        let now = self.clock.unix_secs();
+        let business_date = self.business_date();
+
+        let (counts, averages) = conn
+            .call(move |conn| {
+                let counts = conn
+                    .prepare(
+                        r#"
+                        SELECT station,
+                               COALESCE(SUM(status = ?1), 0),
+                               COALESCE(SUM(status = ?2), 0),
+                               MIN(CASE WHEN status = ?1 THEN created_at END)
+                        FROM print_jobs
+                        WHERE status IN (?1, ?2) OR business_date = ?3
+                        GROUP BY station
+                        "#,
+                    )?
+                    .query_map(rusqlite::params![status::PENDING, status::PRINTING, business_date], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, i64>(1)?,
+                            row.get::<_, i64>(2)?,
+                            row.get::<_, Option<i64>>(3)?,
+                        ))
+                    })?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                // Rolling: each station's most recent tickets only
+                let averages = conn
+                    .prepare(
+                        r#"
+                        SELECT station, SUM(print_duration_ms), COUNT(*) FROM (
+                            SELECT station, print_duration_ms,
+                                   ROW_NUMBER() OVER (PARTITION BY station ORDER BY completed_at DESC) AS n
+                            FROM print_jobs
+                            WHERE status = ?1 AND print_duration_ms IS NOT NULL
+                        )
+                        WHERE n <= ?2
+                        GROUP BY station
+                        "#,
+                    )?
+                    .query_map(rusqlite::params![status::COMPLETED, STATION_LOAD_SAMPLE], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+                    })?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                Ok((counts, averages))
+            })
+            .await
+            .map_err(|e| DaemonError::Queue(format!("Failed to get station load: {}", e)))?;
+
+        let (total_ms, total_count) =
+            averages.iter().fold((0i64, 0i64), |(ms, count), (_, sum, n)| (ms + sum, count + n));
+        let overall = (total_count > 0).then(|| (total_ms / total_count) as u64);
+
+        let mut load: Vec<StationLoad> = counts
+            .into_iter()
+            .map(|(station, pending, printing, oldest)| {
+                let avg_print_ms = averages
+                    .iter()
+                    .find(|(s, _, _)| *s == station)
+                    .map(|(_, sum, n)| (sum / n) as u64)
+                    .or(overall);
+                let (pending, printing) = (pending as u64, printing as u64);
+                StationLoad {
+                    backlog_secs: avg_print_ms.map(|ms| ((pending + printing) * ms).div_ceil(1000)),
+                    oldest_pending_secs: oldest.map(|created| (now - created).max(0) as u64).unwrap_or(0),
+                    station,
+                    pending,
+                    printing,
+                    avg_print_ms,
+                }
+            })
+            .collect();
+        load.sort_by(|a, b| {
+            (b.backlog_secs, b.pending).cmp(&(a.backlog_secs, a.pending)).then_with(|| a.station.cmp(&b.station))
+        });
+        Ok(load)
+    }
+
+    /// Clean up old completed jobs (older than 7 days)
+    pub async fn cleanup_old_jobs(&self) -> Result<()> {
+        let conn = self.db().await;
+
+        let cutoff = self.clock.unix_secs() - 7 * 24 * 3600;
+        let id_cutoff = self.clock.unix_secs() - COMPLETED_ID_RETENTION_SECS;
+        let breaker_cutoff = self.clock.unix_secs() - BREAKER_HISTORY_RETENTION_SECS;
+        let idempotency_cutoff = self.clock.unix_secs() - IDEMPOTENCY_RETENTION_SECS;
+        let incident_cutoff = self.clock.unix_secs() - incidents::RETENTION_SECS;
+        let ingest_cutoff =
+            self.clock.unix_secs() - self.ingest_dedup.read().unwrap_or_else(|e| e.into_inner()).ttl_secs();
+
+        conn.call(move |conn| {
+            conn.execute(
+                r#"
+                DELETE FROM print_jobs
+                WHERE status IN (?1, ?2, ?4)
+                  AND completed_at < ?3
+                "#,
+                rusqlite::params![status::COMPLETED, status::FAILED, cutoff, status::CANCELLED],
+            )?;
+            conn.execute(
+                "DELETE FROM completed_job_ids WHERE completed_at < ?1",
+                [id_cutoff],
+            )?;
+            conn.execute(
+                "DELETE FROM breaker_transitions WHERE created_at < ?1",
+                [breaker_cutoff],
+            )?;
+            // Kept as long as breaker history: both feed printer reliability reviews
+            conn.execute(
+                "DELETE FROM mechanical_faults WHERE created_at < ?1",
+                [breaker_cutoff],
+            )?;
+            conn.execute(
+                "DELETE FROM daemon_runs WHERE started_at < ?1",
+                [breaker_cutoff],
+            )?;
+            conn.execute(
+                "DELETE FROM printer_incidents WHERE ended_at < ?1",
+                [incident_cutoff],
+            )?;
+            conn.execute(
+                "DELETE FROM ingested_job_ids WHERE seen_at <= ?1",
+                [ingest_cutoff],
+            )?;
+            conn.execute(
+                "DELETE FROM job_notes WHERE job_id NOT IN (SELECT id FROM print_jobs)",
+                [],
+            )?;
+            conn.execute(
+                "DELETE FROM api_idempotency_keys WHERE created_at < ?1",
+                [idempotency_cutoff],
+            )?;
+            conn.execute(
+                "DELETE FROM pickup_slips WHERE created_at < ?1",
+                [cutoff],
+            )?;
+            conn.execute(
+                "DELETE FROM order_completions WHERE completed_at < ?1",
+                [cutoff],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to cleanup old jobs: {}", e)))
+    }
+
+    /// Delete ALL jobs from the queue (used during factory reset)
+    pub async fn clear_all_jobs(&self) -> Result<()> {
+        self.job_cache.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        let conn = self.db().await;
+
+        conn.call(|conn| {
+            conn.execute("DELETE FROM print_jobs", [])?;
+            conn.execute("DELETE FROM completed_job_ids", [])?;
+            conn.execute("DELETE FROM ingested_job_ids", [])?;
+            conn.execute("DELETE FROM job_notes", [])?;
+            conn.execute("DELETE FROM api_idempotency_keys", [])?;
+            conn.execute("DELETE FROM pickup_slips", [])?;
+            conn.execute("DELETE FROM order_completions", [])?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to clear all jobs: {}", e)))
+    }
+
+    /// Process a job with exponential backoff retry
+    #[allow(dead_code)] // Infrastructure: will be called when job processor loop is implemented
+    pub async fn process_with_retry<F, Fut>(&self, job_id: &str, process_fn: F) -> Result<()>
+    where
+        F: Fn() -> Fut + Send + Sync,
+        Fut: std::future::Future<Output = Result<()>> + Send,
+    {
+        let retry_strategy = ExponentialBuilder::default()
+            .with_min_delay(Duration::from_millis(self.config.initial_retry_delay_ms))
+            .with_max_delay(Duration::from_millis(self.config.max_retry_delay_ms))
+            .with_max_times(self.config.max_retries as usize);
+
+        let result = (|| async { process_fn().await })
+            .retry(retry_strategy)
+            .await;
+
+        match result {
+            Ok(_) => {
+                self.mark_completed(job_id, 0, None).await?;
+                Ok(())
+            }
+            Err(e) => {
+                self.mark_failed(job_id, &e.to_string()).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Flush SQLite WAL (Write-Ahead Log) to main database file
+    ///
+    /// Called during graceful shutdown to ensure all queued data is persisted.
+    /// Uses TRUNCATE mode which reclaims WAL file space.
+    pub async fn flush_db(&self) -> Result<()> {
+        info!("Flushing SQLite queue database to disk...");
+        let conn = self.db().await;
+
+        conn.call(|conn| {
+            conn.execute_batch(
+                "PRAGMA wal_checkpoint(TRUNCATE); PRAGMA synchronous = FULL;",
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| DaemonError::Queue(format!("Failed to flush database: {}", e)))?;
+
+        info!("SQLite queue database flushed successfully");
+        Ok(())
+    }
+
+    /// Get count of in-progress jobs (for shutdown drain monitoring)
+    pub async fn get_processing_count(&self) -> Result<u64> {
+        let conn = self.db().await;
+
+        conn.call(|conn| {
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM print_jobs WHERE status = ?1",
+                [status::PRINTING],
                 |row| row.get(0),
             )?;
             Ok(count as u64)
@@ -794,3 +3295,972 @@ impl QueueManager {
     // Currently commented out due to invalid self parameter type (Arc<Mutex<Self>>)
     // See main.rs for stubbed implementation
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str, order_id: &str, printer_id: &str) -> PrintJob {
+        PrintJob {
+            id: id.to_string(),
+            restaurant_id: "r1".to_string(),
+            order_id: Some(order_id.to_string()),
+            order_number: "42".to_string(),
+            station: "kitchen".to_string(),
+            station_id: None,
+            printer_id: Some(printer_id.to_string()),
+            items: vec![PrintItem {
+                quantity: 1,
+                name: "Burger".to_string(),
+                modifiers: vec![],
+                notes: None,
+                image_url: None,
+                pricing: None,
+            }],
+            table_number: None,
+            customer_name: None,
+            order_type: None,
+            priority: priority::NORMAL,
+            timestamp: 0,
+            status: status::PENDING.to_string(),
+            retry_count: 0,
+            error_message: None,
+            copies: 1,
+            correlation_id: String::new(),
+            pickup_time: None,
+            kind: JobKind::Order,
+            original_job_id: None,
+            removed_items: vec![],
+            order_stations: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dedup_window_and_backoff_follow_clock() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+        let pending_ids = |jobs: Vec<PrintJob>| jobs.into_iter().map(|j| j.id).collect::<Vec<_>>();
+
+        queue.enqueue(job("j1", "o1", "p1")).await.unwrap();
+        queue.enqueue(job("j1-resent", "o1", "p1")).await.unwrap();
+        assert_eq!(pending_ids(queue.get_pending_jobs(10).await.unwrap()), vec!["j1"]);
+
+        // Outside the 5 minute dedup window the same order is accepted again
+        clock.advance(Duration::from_secs(301));
+        queue.enqueue(job("j1-later", "o1", "p1")).await.unwrap();
+        assert_eq!(queue.get_pending_jobs(10).await.unwrap().len(), 2);
+
+        // Processor flow: mark_failed counts the attempt, so the first retry backs off 2^1 * 2s
+        queue.mark_printing("j1").await.unwrap();
+        queue.mark_failed("j1", "offline").await.unwrap();
+        queue.retry_job("j1").await.unwrap();
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(pending_ids(queue.get_pending_jobs(10).await.unwrap()), vec!["j1-later"]);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(queue.get_pending_jobs(10).await.unwrap().len(), 2);
+
+        // Blocked on the printer: waits without using up a retry, until it's too old
+        queue.mark_printing("j1").await.unwrap();
+        assert!(queue.block_job("j1", "cover open").await.unwrap());
+        clock.advance(Duration::from_secs(BLOCKED_RETRY_SECS as u64 - 1));
+        assert_eq!(pending_ids(queue.get_pending_jobs(10).await.unwrap()), vec!["j1-later"]);
+        clock.advance(Duration::from_secs(1));
+        let jobs = queue.get_pending_jobs(10).await.unwrap();
+        assert_eq!(jobs.iter().find(|j| j.id == "j1").unwrap().retry_count, 2);
+        clock.advance(Duration::from_secs(BLOCKED_MAX_SECS as u64));
+        assert!(!queue.block_job("j1", "cover open").await.unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_workers_never_take_a_job_twice() {
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        let producer = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                for i in 0..40 {
+                    queue.enqueue(job(&format!("j{}", i), &format!("o{}", i), "p1")).await.unwrap();
+                    tokio::task::yield_now().await;
+                }
+            })
+        };
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let queue = queue.clone();
+                tokio::spawn(async move {
+                    let mut taken = Vec::new();
+                    for _ in 0..200 {
+                        for job in queue.get_pending_jobs(5).await.unwrap() {
+                            if queue.mark_printing(&job.id).await.unwrap() {
+                                taken.push(job.id);
+                            }
+                        }
+                        tokio::task::yield_now().await;
+                    }
+                    taken
+                })
+            })
+            .collect();
+
+        producer.await.unwrap();
+        let mut taken = Vec::new();
+        for worker in workers {
+            taken.extend(worker.await.unwrap());
+        }
+        // Whatever arrived after the workers stopped
+        for job in queue.get_pending_jobs(50).await.unwrap() {
+            assert!(queue.mark_printing(&job.id).await.unwrap());
+            taken.push(job.id);
+        }
+        taken.sort();
+        let mut all: Vec<_> = (0..40).map(|i| format!("j{}", i)).collect();
+        all.sort();
+        // Each job exactly once
+        assert_eq!(taken, all);
+        assert!(!queue.mark_printing("j0").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_job_before_it_prints() {
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        queue.enqueue(job("j1", "o1", "p1")).await.unwrap();
+        queue.enqueue(job("j2", "o2", "p1")).await.unwrap();
+        queue.enqueue(job("j3", "o3", "p1")).await.unwrap();
+        let pending_ids = |jobs: Vec<PrintJob>| jobs.into_iter().map(|j| j.id).collect::<Vec<_>>();
+
+        // Picked up by the processor, then cancelled before it was marked printing
+        assert_eq!(queue.get_pending_jobs(10).await.unwrap().len(), 3);
+        assert!(queue.cancel_job("j1").await.unwrap());
+        assert!(!queue.mark_printing("j1").await.unwrap());
+        assert_eq!(pending_ids(queue.get_pending_jobs(10).await.unwrap()), vec!["j2", "j3"]);
+
+        assert!(queue.mark_printing("j2").await.unwrap());
+        assert!(queue.cancel_job("j2").await.unwrap());
+        queue.mark_completed("j3", 100, None).await.unwrap();
+        assert!(!queue.cancel_job("j3").await.unwrap());
+        assert!(!queue.cancel_job("j1").await.unwrap());
+        assert!(!queue.cancel_job("missing").await.unwrap());
+        let order = queue.order_jobs("o2").await.unwrap().unwrap();
+        assert_eq!(order.jobs[0].status, status::CANCELLED);
+        assert!(order.stations_pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_station_load_estimates_backlog() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+        let bar = |id: &str, order: &str| PrintJob { station: "bar".to_string(), ..job(id, order, "p2") };
+        queue.enqueue(bar("b1", "o1")).await.unwrap();
+        queue.enqueue(bar("b2", "o2")).await.unwrap();
+        queue.mark_completed("b1", 2000, None).await.unwrap();
+        queue.mark_completed("b2", 4000, None).await.unwrap();
+        queue.enqueue(job("k1", "o3", "p1")).await.unwrap();
+        queue.enqueue(job("k2", "o4", "p1")).await.unwrap();
+        clock.advance(Duration::from_secs(30));
+
+        // No kitchen history yet: all stations' average
+        let load = queue.station_load().await.unwrap();
+        assert_eq!(load.iter().map(|l| l.station.as_str()).collect::<Vec<_>>(), vec!["kitchen", "bar"]);
+        assert_eq!((load[0].pending, load[0].avg_print_ms, load[0].backlog_secs), (2, Some(3000), Some(6)));
+        assert_eq!(load[0].oldest_pending_secs, 30);
+        assert_eq!((load[1].pending, load[1].backlog_secs), (0, Some(0)));
+
+        queue.mark_printing("k1").await.unwrap();
+        queue.mark_completed("k1", 1000, None).await.unwrap();
+        let load = queue.station_load().await.unwrap();
+        assert_eq!((load[0].station.as_str(), load[0].avg_print_ms, load[0].backlog_secs), ("kitchen", Some(1000), Some(1)));
+    }
+
+    #[tokio::test]
+    async fn test_pull_station_waits_for_print_next() {
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        queue.set_pull_stations(&["Grill ".to_string()]);
+        let grill = |id: &str, order: &str, priority| PrintJob { station: "grill".to_string(), priority, ..job(id, order, "p1") };
+        queue.enqueue(grill("g1", "o1", priority::NORMAL)).await.unwrap();
+        queue.enqueue(grill("g2", "o2", priority::URGENT)).await.unwrap();
+        queue.enqueue(job("k1", "o3", "p2")).await.unwrap();
+        let pending_ids = |jobs: Vec<PrintJob>| jobs.into_iter().map(|j| j.id).collect::<Vec<_>>();
+        assert_eq!(pending_ids(queue.get_pending_jobs(10).await.unwrap()), vec!["k1"]);
+        assert_eq!(pending_ids(queue.get_pending_jobs_ordered(10).await.unwrap()), vec!["k1"]);
+
+        // Most urgent first, even though it came in later
+        let pulled = queue.print_next("GRILL").await.unwrap().unwrap();
+        assert_eq!((pulled.job_id.as_str(), pulled.waiting), ("g2", 1));
+        assert_eq!(pending_ids(queue.get_pending_jobs_ordered(10).await.unwrap()), vec!["g2", "k1"]);
+        assert!(queue.print_next("kitchen").await.is_err());
+
+        queue.print_next("grill").await.unwrap().unwrap();
+        assert_eq!(queue.print_next("grill").await.unwrap(), None);
+
+        // Out of pull mode, waiting tickets print as usual
+        queue.enqueue(grill("g3", "o4", priority::NORMAL)).await.unwrap();
+        queue.set_pull_stations(&[]);
+        assert_eq!(queue.get_pending_jobs(10).await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_paused_printer_holds_its_jobs() {
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        queue.enqueue(job("j1", "o1", "p1")).await.unwrap();
+        queue.enqueue(job("j2", "o2", "p2")).await.unwrap();
+        queue.set_paused_printers(&["p1".to_string()]).await;
+        assert!(queue.is_printer_paused("p1") && !queue.is_printer_paused("p2"));
+
+        let pending_ids = |jobs: Vec<PrintJob>| jobs.into_iter().map(|j| j.id).collect::<Vec<_>>();
+        assert_eq!(pending_ids(queue.get_pending_jobs(10).await.unwrap()), vec!["j2"]);
+        assert_eq!(pending_ids(queue.get_pending_jobs_ordered(10).await.unwrap()), vec!["j2"]);
+
+        // Routed to p1 by station: held, not failed
+        queue.enqueue(PrintJob { printer_id: None, ..job("j3", "o3", "p1") }).await.unwrap();
+        queue.hold_job("j3", "p1").await.unwrap();
+        assert_eq!(pending_ids(queue.get_pending_jobs(10).await.unwrap()), vec!["j2"]);
+
+        // Resumed, the held jobs print right away; nothing failed meanwhile
+        queue.set_paused_printers(&[]).await;
+        let pending = queue.get_pending_jobs(10).await.unwrap();
+        assert_eq!(pending.len(), 3);
+        assert!(pending.iter().all(|j| j.status == status::PENDING && j.retry_count == 0));
+    }
+
+    #[tokio::test]
+    async fn test_void_follows_its_original() {
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        queue.enqueue(PrintJob { station: "grill".to_string(), ..job("j1", "o1", "p1") }).await.unwrap();
+
+        // Same order and station as the pending original, but not a duplicate
+        let void = PrintJob {
+            station: "kitchen".to_string(),
+            order_id: None,
+            printer_id: None,
+            items: vec![],
+            kind: JobKind::Void,
+            original_job_id: Some("j1".to_string()),
+            ..job("j2", "o1", "p1")
+        };
+        queue.enqueue(void).await.unwrap();
+        let jobs = queue.get_pending_jobs(10).await.unwrap();
+        let void = jobs.iter().find(|j| j.id == "j2").unwrap();
+        assert_eq!(void.kind, JobKind::Void);
+        assert_eq!((void.station.as_str(), void.printer_id.as_deref()), ("grill", Some("p1")));
+        assert_eq!(void.order_id.as_deref(), Some("o1"));
+        assert_eq!(void.items.len(), 1);
+
+        let amend = PrintJob {
+            kind: JobKind::Amend,
+            original_job_id: Some("j1".to_string()),
+            removed_items: void.items.clone(),
+            ..job("j3", "o1", "p1")
+        };
+        queue.enqueue(amend).await.unwrap();
+        let jobs = queue.get_pending_jobs(10).await.unwrap();
+        assert_eq!(jobs.iter().find(|j| j.id == "j3").unwrap().removed_items[0].name, "Burger");
+    }
+
+    #[tokio::test]
+    async fn test_order_completes_once_all_stations_printed() {
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        let slip = PickupSlip { order_id: "o1".to_string(), order_number: "42".to_string(), customer_name: None };
+
+        queue.enqueue(job("j1", "o1", "p1")).await.unwrap();
+        queue.enqueue(PrintJob { station: "bar".to_string(), ..job("j2", "o1", "p2") }).await.unwrap();
+        queue.mark_completed("j1", 10, None).await.unwrap();
+        assert!(!queue.complete_order_if_done("o1").await.unwrap());
+        let order = queue.order_jobs("o1").await.unwrap().unwrap();
+        assert_eq!(order.jobs.len(), 2);
+        assert_eq!(order.stations_printed, vec!["kitchen"]);
+        assert_eq!(order.stations_pending, vec!["bar"]);
+        assert_eq!(order.completed_at, None);
+
+        queue.mark_completed("j2", 10, None).await.unwrap();
+        assert!(queue.complete_order_if_done("o1").await.unwrap());
+        // The other station finishing at the same moment doesn't complete it again
+        assert!(!queue.complete_order_if_done("o1").await.unwrap());
+        let order = queue.order_jobs("o1").await.unwrap().unwrap();
+        assert_eq!(order.stations_printed, vec!["kitchen", "bar"]);
+        assert!(order.completed_at.is_some());
+        assert!(queue.order_jobs("unknown").await.unwrap().is_none());
+
+        // A ticket added later reopens the order
+        queue.enqueue(PrintJob { station: "dessert".to_string(), ..job("j3", "o1", "p1") }).await.unwrap();
+        assert_eq!(queue.order_jobs("o1").await.unwrap().unwrap().completed_at, None);
+        queue.mark_completed("j3", 10, None).await.unwrap();
+        assert!(queue.complete_order_if_done("o1").await.unwrap());
+
+        assert!(queue.queue_pickup_slip(&slip).await.unwrap());
+        assert!(!queue.queue_pickup_slip(&slip).await.unwrap());
+        assert_eq!(queue.pending_pickup_slips(10).await.unwrap(), vec![slip.clone()]);
+
+        // Given up after PICKUP_SLIP_ATTEMPTS failures; a POS request prints it again
+        for _ in 0..PICKUP_SLIP_ATTEMPTS {
+            queue.finish_pickup_slip("o1", Some("offline")).await.unwrap();
+        }
+        assert!(queue.pending_pickup_slips(10).await.unwrap().is_empty());
+        queue.request_pickup_slip(&slip).await.unwrap();
+        assert_eq!(queue.pending_pickup_slips(10).await.unwrap().len(), 1);
+        queue.finish_pickup_slip("o1", None).await.unwrap();
+        assert!(queue.pending_pickup_slips(10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_order_waits_for_stations_not_polled_yet() {
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        let fanned_out = |id: &str, station: &str| PrintJob {
+            station: station.to_string(),
+            order_stations: vec!["kitchen".to_string(), "Bar".to_string()],
+            ..job(id, "o1", "p1")
+        };
+
+        // Kitchen prints before the bar ticket was polled
+        queue.enqueue(fanned_out("j1", "kitchen")).await.unwrap();
+        queue.mark_completed("j1", 10, None).await.unwrap();
+        assert!(!queue.complete_order_if_done("o1").await.unwrap());
+        let order = queue.order_jobs("o1").await.unwrap().unwrap();
+        assert_eq!(order.stations_printed, vec!["kitchen"]);
+        assert_eq!(order.stations_pending, vec!["bar"]);
+
+        queue.enqueue(fanned_out("j2", "bar")).await.unwrap();
+        assert!(!queue.complete_order_if_done("o1").await.unwrap());
+        queue.mark_completed("j2", 10, None).await.unwrap();
+        assert!(queue.complete_order_if_done("o1").await.unwrap());
+        assert!(queue.order_jobs("o1").await.unwrap().unwrap().stations_pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_order_completes_when_its_last_open_ticket_is_cancelled() {
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        queue.enqueue(job("j1", "o1", "p1")).await.unwrap();
+        queue.enqueue(PrintJob { station: "bar".to_string(), ..job("j2", "o1", "p2") }).await.unwrap();
+        queue.mark_completed("j1", 10, None).await.unwrap();
+        assert!(!queue.complete_order_if_done("o1").await.unwrap());
+
+        assert!(queue.cancel_job("j2").await.unwrap());
+        let order = queue.order_of_job("j2").await.unwrap().unwrap();
+        assert_eq!((order.order_id.as_str(), order.order_number.as_str()), ("o1", "42"));
+        assert!(queue.complete_order_if_done("o1").await.unwrap());
+
+        // Nothing printed at all: the order was called off, not printed
+        queue.enqueue(job("j3", "o2", "p1")).await.unwrap();
+        assert!(queue.cancel_job("j3").await.unwrap());
+        assert!(!queue.complete_order_if_done("o2").await.unwrap());
+
+        queue.enqueue(PrintJob { order_id: None, ..job("j4", "o3", "p1") }).await.unwrap();
+        assert_eq!(queue.order_of_job("j4").await.unwrap(), None);
+        assert_eq!(queue.order_of_job("missing").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_backlog_age() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+
+        assert_eq!(queue.get_health().await.unwrap().state, Saturation::Normal);
+
+        queue.enqueue(job("j1", "o1", "p1")).await.unwrap();
+        clock.advance(Duration::from_secs(SATURATED_WAIT_SECS));
+        let health = queue.get_health().await.unwrap();
+        assert_eq!(health.pending, 1);
+        assert_eq!(health.oldest_pending_secs, SATURATED_WAIT_SECS);
+        assert_eq!(health.state, Saturation::Saturated);
+
+        assert_eq!(QueueHealth::new(ELEVATED_PENDING, 0, 0).state, Saturation::Elevated);
+        assert_eq!(QueueHealth::new(SATURATED_PENDING, 0, 0).state, Saturation::Saturated);
+    }
+
+    #[tokio::test]
+    async fn test_stats_follow_business_date() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone())
+            .with_rollover_hour(5);
+
+        let today = queue.business_date();
+        queue.enqueue(job("j1", "o1", "p1")).await.unwrap();
+        queue.mark_completed("j1", 100, None).await.unwrap();
+
+        let stats = queue.get_stats().await.unwrap();
+        assert_eq!(stats["business_date"], today.as_str());
+        assert_eq!(stats["completed_today"], 1);
+
+        let summary = queue.get_day_summary(today.clone()).await.unwrap();
+        assert_eq!(summary.printed, 1);
+        assert_eq!(summary.stations[0].station, "kitchen");
+
+        // A full day later it's a new business day
+        clock.advance(Duration::from_secs(24 * 3600));
+        let stats = queue.get_stats().await.unwrap();
+        assert_ne!(stats["business_date"], today.as_str());
+        assert_eq!(stats["completed_today"], 0);
+        assert_eq!(stats["completed"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_stats_breakdowns() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+
+        queue.enqueue(job("j1", "o1", "p1")).await.unwrap();
+        queue.enqueue(PrintJob { station: "bar".to_string(), ..job("j2", "o2", "p2") }).await.unwrap();
+        queue.enqueue(PrintJob { station: "bar".to_string(), ..job("j3", "o3", "p2") }).await.unwrap();
+        queue.mark_completed("j1", 100, None).await.unwrap();
+        queue.mark_failed("j2", "Connection timed out").await.unwrap();
+        queue.mark_failed("j3", "Circuit breaker OPEN for printer p2").await.unwrap();
+
+        let stats = queue.get_stats().await.unwrap();
+        assert_eq!(stats["by_station"][0]["key"], "bar");
+        assert_eq!(stats["by_station"][0]["failed"], 2);
+        assert_eq!(stats["by_printer"][0]["key"], "p2");
+        assert_eq!(stats["avg_retries"], 2.0 / 3.0);
+
+        let hourly = stats["hourly"].as_array().unwrap();
+        assert_eq!(hourly.len(), 24);
+        assert_eq!(hourly[23]["created"], 3);
+        assert_eq!(hourly[23]["failed"], 2);
+
+        let reasons: Vec<&str> = stats["failure_reasons"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["class"].as_str().unwrap())
+            .collect();
+        assert_eq!(reasons, vec!["breaker_open", "timeout"]);
+    }
+
+    #[tokio::test]
+    async fn test_ordered_delivery_is_fifo_per_station() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+
+        // Enqueued out of order: bar-2 was created after bar-1 but arrives first
+        let mut bar2 = job("bar-2", "o2", "p1");
+        bar2.station = "bar".to_string();
+        bar2.timestamp = 2000;
+        let mut bar1 = job("bar-1", "o1", "p1");
+        bar1.station = "bar".to_string();
+        bar1.timestamp = 1000;
+        let mut kitchen = job("kitchen-1", "o3", "p2");
+        kitchen.timestamp = 3000;
+        for j in [bar2, bar1, kitchen] {
+            queue.enqueue(j).await.unwrap();
+        }
+
+        let ids = |jobs: Vec<PrintJob>| {
+            let mut ids: Vec<String> = jobs.into_iter().map(|j| j.id).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(queue.get_pending_jobs_ordered(10).await.unwrap()), vec!["bar-1", "kitchen-1"]);
+
+        // Station busy: nothing more for the bar
+        queue.mark_printing("bar-1").await.unwrap();
+        assert_eq!(ids(queue.get_pending_jobs_ordered(10).await.unwrap()), vec!["kitchen-1"]);
+
+        // Head in retry backoff still holds the station
+        queue.mark_failed("bar-1", "timeout").await.unwrap();
+        queue.retry_job("bar-1").await.unwrap();
+        assert_eq!(ids(queue.get_pending_jobs_ordered(10).await.unwrap()), vec!["kitchen-1"]);
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(ids(queue.get_pending_jobs_ordered(10).await.unwrap()), vec!["bar-1", "kitchen-1"]);
+
+        queue.mark_completed("bar-1", 100, None).await.unwrap();
+        assert_eq!(ids(queue.get_pending_jobs_ordered(10).await.unwrap()), vec!["bar-2", "kitchen-1"]);
+    }
+
+    #[tokio::test]
+    async fn test_completed_ids_outlive_job_cleanup() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+
+        queue.enqueue(job("j1", "o1", "p1")).await.unwrap();
+        queue.mark_completed("j1", 100, None).await.unwrap();
+        assert!(queue.was_completed("j1").await.unwrap());
+        assert!(!queue.was_completed("j2").await.unwrap());
+
+        clock.advance(Duration::from_secs(8 * 24 * 3600));
+        queue.cleanup_old_jobs().await.unwrap();
+        assert_eq!(queue.get_stats().await.unwrap()["completed"], 0);
+        assert!(queue.was_completed("j1").await.unwrap());
+
+        clock.advance(Duration::from_secs(COMPLETED_ID_RETENTION_SECS as u64));
+        queue.cleanup_old_jobs().await.unwrap();
+        assert!(!queue.was_completed("j1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_breaker_history_and_degraded_minutes() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+        let transition = |printer_id: &str, from: CircuitState, to: CircuitState, minutes: u64| BreakerTransition {
+            printer_id: printer_id.to_string(),
+            from,
+            to,
+            reason: "test".to_string(),
+            detail: None,
+            duration_ms: minutes * 60_000,
+        };
+        let today = queue.business_date();
+
+        for t in [
+            transition("p1", CircuitState::Closed, CircuitState::Open, 120),
+            transition("p1", CircuitState::Open, CircuitState::HalfOpen, 5),
+            transition("p1", CircuitState::HalfOpen, CircuitState::Open, 1),
+            transition("p1", CircuitState::Open, CircuitState::Closed, 4),
+            transition("p2", CircuitState::Open, CircuitState::HalfOpen, 5),
+        ] {
+            queue.record_breaker_transition(&t).await.unwrap();
+        }
+
+        let history = queue.get_breaker_history("p1", 10).await.unwrap();
+        assert_eq!(history.len(), 4);
+        assert_eq!((history[0].from_state.as_str(), history[0].to_state.as_str()), ("open", "closed"));
+
+        // Closed time doesn't count
+        assert_eq!(
+            queue.degraded_minutes(&today, None).await.unwrap(),
+            vec![
+                DegradedDay { printer_id: "p1".to_string(), business_date: today.clone(), minutes: 10 },
+                DegradedDay { printer_id: "p2".to_string(), business_date: today.clone(), minutes: 5 },
+            ]
+        );
+        assert_eq!(queue.degraded_minutes(&today, Some("p2")).await.unwrap().len(), 1);
+
+        clock.advance(Duration::from_secs(BREAKER_HISTORY_RETENTION_SECS as u64 + 1));
+        queue.cleanup_old_jobs().await.unwrap();
+        assert!(queue.get_breaker_history("p1", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mechanical_faults_counted_per_window() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+
+        assert_eq!(queue.record_mechanical_fault("p1", MechanicalFault::Cutter, true).await.unwrap(), 1);
+        assert_eq!(queue.record_mechanical_fault("p2", MechanicalFault::Unrecoverable, false).await.unwrap(), 1);
+        assert_eq!(queue.record_mechanical_fault("p1", MechanicalFault::Cutter, false).await.unwrap(), 2);
+
+        let summary = queue.mechanical_faults(0).await.unwrap();
+        assert_eq!((summary[0].printer_id.as_str(), summary[0].faults, summary[0].recovered), ("p1", 2, 1));
+        assert_eq!(summary[1].printer_id, "p2");
+
+        // Older faults drop out of the recurring window
+        clock.advance(Duration::from_secs(jam_recovery::RECURRING_WINDOW_SECS as u64 + 1));
+        assert_eq!(queue.record_mechanical_fault("p1", MechanicalFault::Cutter, true).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_printer_stats_accumulate_and_sync() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+        let start = queue.clock.unix_secs();
+
+        queue.record_printer_attempt(&Attempt::printed("p1", 1000, 400)).await.unwrap();
+        clock.advance(Duration::from_secs(60));
+        queue.record_printer_attempt(&Attempt::failed("p1", "connection refused")).await.unwrap();
+        queue.record_printer_attempt(&Attempt::printed("p1", 500, 200)).await.unwrap();
+        queue.record_printer_attempt(&Attempt::failed("p2", &"x".repeat(1000))).await.unwrap();
+
+        let stats = queue.printer_stats(false).await.unwrap();
+        let p1 = &stats[0];
+        assert_eq!((p1.jobs_printed, p1.jobs_failed, p1.bytes_sent), (2, 1, 1500));
+        assert_eq!(p1.avg_duration_ms, Some(300));
+        assert_eq!(p1.last_error.as_deref(), Some("connection refused"));
+        assert_eq!((p1.since, p1.last_printed_at), (start, Some(start + 60)));
+        let p2 = &stats[1];
+        assert_eq!((p2.jobs_printed, p2.avg_duration_ms, p2.last_printed_at), (0, None, None));
+        assert_eq!(p2.last_error.as_ref().map(|e| e.len()), Some(crate::printer_stats::MAX_ERROR_LEN));
+
+        // Synced rows are only sent again once they change
+        let unsynced = queue.printer_stats(true).await.unwrap();
+        assert_eq!(unsynced.len(), 2);
+        queue.record_printer_attempt(&Attempt::printed("p2", 10, 10)).await.unwrap();
+        queue.mark_printer_stats_synced(&unsynced).await.unwrap();
+        let unsynced = queue.printer_stats(true).await.unwrap();
+        assert_eq!(unsynced.iter().map(|s| s.printer_id.as_str()).collect::<Vec<_>>(), vec!["p2"]);
+
+        // Lifetime: survives job cleanup
+        clock.advance(Duration::from_secs(30 * 24 * 3600));
+        queue.cleanup_old_jobs().await.unwrap();
+        assert_eq!(queue.printer_stats(false).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_ingest_drops_jobs_seen_over_any_transport() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+
+        assert!(queue.ingest(job("j1", "o1", "p1"), "poll").await.unwrap());
+        assert!(!queue.ingest(job("j1", "o1", "p1"), "realtime").await.unwrap());
+        clock.advance(Duration::from_secs(3600));
+        queue.cleanup_old_jobs().await.unwrap();
+        assert!(!queue.ingest(job("j1", "o1", "p1"), "poll").await.unwrap());
+
+        // Forgotten after the TTL; a delivery that fails to queue isn't remembered
+        queue.set_ingest_dedup(&IngestDedupConfig { enabled: true, ttl_minutes: 60 });
+        clock.advance(Duration::from_secs(3600));
+        queue.cleanup_old_jobs().await.unwrap();
+        assert!(queue.ingest(job("j1", "o1", "p1"), "poll").await.is_err());
+        assert!(queue.ingest(job("j1", "o1", "p1"), "poll").await.is_err());
+
+        queue.set_ingest_dedup(&IngestDedupConfig { enabled: false, ..Default::default() });
+        assert!(queue.ingest(job("j2", "o2", "p1"), "poll").await.unwrap());
+        assert_eq!(queue.get_pending_jobs(10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_incidents_count_affected_jobs() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+        let start = queue.clock.unix_secs();
+
+        queue.enqueue(job("before", "o1", "bar")).await.unwrap();
+        let id = queue.open_incident("bar", start, "unreachable").await.unwrap();
+        clock.advance(Duration::from_secs(60));
+        queue.enqueue(job("during", "o2", "bar")).await.unwrap();
+        queue.enqueue(job("elsewhere", "o3", "kitchen")).await.unwrap();
+
+        // Open: counted up to now, and waiting to be synced
+        let open = queue.incidents(true, 10).await.unwrap();
+        assert_eq!((open[0].id, open[0].ended_at, open[0].affected_jobs), (id, None, 2));
+        let unsynced = queue.unsynced_incidents().await.unwrap();
+        assert_eq!(unsynced.len(), 1);
+        queue.mark_incidents_synced(&[(id, unsynced[0].1)]).await.unwrap();
+        assert!(queue.unsynced_incidents().await.unwrap().is_empty());
+
+        queue.close_incident(id, start + 120).await.unwrap();
+        clock.advance(Duration::from_secs(600));
+        queue.enqueue(job("after", "o4", "bar")).await.unwrap();
+        let all = queue.incidents(false, 10).await.unwrap();
+        assert_eq!((all[0].ended_at, all[0].affected_jobs), (Some(start + 120), 2));
+        assert!(queue.incidents(true, 10).await.unwrap().is_empty());
+        // Closing is synced again
+        assert_eq!(queue.unsynced_incidents().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_roundtrip_dedups_and_reroutes() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = || Zeroizing::new("snapshot-key".to_string());
+
+        let old = QueueManager::new(dir.path().join("old.db"), Some(key())).await.unwrap();
+        let j1 = PrintJob {
+            copies: 3,
+            correlation_id: "trace-j1".to_string(),
+            pickup_time: Some(1_700_000_000_000),
+            ..job("j1", "o1", "old-printer")
+        };
+        old.enqueue(j1).await.unwrap();
+        old.enqueue(job("j2", "o2", "old-printer")).await.unwrap();
+        old.enqueue(job("j3", "o3", "old-printer")).await.unwrap();
+        old.mark_completed("j3", 10, None).await.unwrap();
+
+        let snapshot = dir.path().join("queue.snapshot");
+        assert_eq!(old.export_snapshot(snapshot.clone(), key()).await.unwrap(), 2);
+
+        let new = QueueManager::new(dir.path().join("new.db"), Some(key())).await.unwrap();
+        // Already re-delivered to the new machine under a different job ID
+        new.enqueue(job("other", "o2", "new-printer")).await.unwrap();
+
+        let route = |_: &str, _: Option<&str>| Some("new-printer".to_string());
+        let summary = new.import_snapshot(snapshot.clone(), key(), route).await.unwrap();
+        assert_eq!(summary.imported, 1);
+        assert_eq!(summary.skipped_duplicates, 1);
+        assert_eq!(summary.rerouted, 1);
+
+        let pending = new.get_pending_jobs(10).await.unwrap();
+        let imported = pending.iter().find(|j| j.id == "j1").unwrap();
+        assert_eq!(imported.printer_id.as_deref(), Some("new-printer"));
+        assert_eq!(imported.copies, 3);
+        assert_eq!(imported.correlation_id, "trace-j1");
+        assert_eq!(imported.pickup_time, Some(1_700_000_000_000));
+
+        let wrong_key = Zeroizing::new("other-restaurant".to_string());
+        assert!(new.import_snapshot(snapshot, wrong_key, route).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_keeps_every_job_field() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = || Zeroizing::new("snapshot-key".to_string());
+
+        // Every stored field away from its default (`station_id` isn't stored)
+        let sent = PrintJob {
+            restaurant_id: "r9".to_string(),
+            order_number: "A-17".to_string(),
+            station: "bar".to_string(),
+            items: vec![PrintItem {
+                quantity: 2,
+                name: "Cola".to_string(),
+                modifiers: vec!["No ice".to_string()],
+                notes: Some("In a glass".to_string()),
+                image_url: Some("https://cdn.eatsome.nl/cola.png".to_string()),
+                pricing: Some(crate::escpos::LinePricing { unit_price_cents: Some(350), ..Default::default() }),
+            }],
+            table_number: Some("12".to_string()),
+            customer_name: Some("Sam".to_string()),
+            order_type: Some("dine_in".to_string()),
+            priority: priority::HIGH,
+            timestamp: 1_700_000_000,
+            copies: 2,
+            correlation_id: "trace-j1".to_string(),
+            pickup_time: Some(1_700_000_900_000),
+            // Follows up on a ticket queued on another machine: printed as sent
+            kind: JobKind::Amend,
+            original_job_id: Some("j0".to_string()),
+            removed_items: job("j0", "o1", "p1").items,
+            order_stations: vec!["bar".to_string(), "kitchen".to_string()],
+            ..job("j1", "o1", "p1")
+        };
+        let old = QueueManager::new(dir.path().join("old.db"), Some(key())).await.unwrap();
+        old.enqueue(sent.clone()).await.unwrap();
+
+        let snapshot = dir.path().join("queue.snapshot");
+        assert_eq!(old.export_snapshot(snapshot.clone(), key()).await.unwrap(), 1);
+        let new = QueueManager::new(dir.path().join("new.db"), Some(key())).await.unwrap();
+        let summary = new.import_snapshot(snapshot, key(), |_: &str, _: Option<&str>| None).await.unwrap();
+        assert_eq!(summary.imported, 1);
+
+        let imported = new.get_pending_jobs(10).await.unwrap().remove(0);
+        assert_eq!(serde_json::to_value(&imported).unwrap(), serde_json::to_value(&sent).unwrap());
+        assert_eq!(imported.pickup_time, Some(1_700_000_900_000));
+        assert_eq!((imported.kind, imported.original_job_id.as_deref()), (JobKind::Amend, Some("j0")));
+        assert_eq!(imported.removed_items[0].name, "Burger");
+    }
+
+    #[tokio::test]
+    async fn test_correlation_id_follows_the_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = || Zeroizing::new("snapshot-key".to_string());
+
+        let old = QueueManager::new(dir.path().join("old.db"), Some(key())).await.unwrap();
+        old.enqueue(PrintJob { correlation_id: "trace-j1".to_string(), ..job("j1", "o1", "p1") }).await.unwrap();
+        // Unusable upstream value: one is generated at enqueue
+        old.enqueue(PrintJob { correlation_id: "not a trace id".to_string(), ..job("j2", "o2", "p1") }).await.unwrap();
+
+        let traces = |jobs: Vec<PrintJob>| {
+            let mut traces: Vec<_> = jobs.into_iter().map(|j| (j.id, j.correlation_id)).collect();
+            traces.sort();
+            traces
+        };
+        let queued = traces(old.get_pending_jobs(10).await.unwrap());
+        assert_eq!(queued[0], ("j1".to_string(), "trace-j1".to_string()));
+        assert_eq!(queued[1].1.len(), 32);
+
+        let snapshot = dir.path().join("queue.snapshot");
+        old.export_snapshot(snapshot.clone(), key()).await.unwrap();
+        let new = QueueManager::new(dir.path().join("new.db"), Some(key())).await.unwrap();
+        new.import_snapshot(snapshot, key(), |_: &str, _: Option<&str>| None).await.unwrap();
+        assert_eq!(traces(new.get_pending_jobs(10).await.unwrap()), queued);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_keeps_void_and_amend_tickets() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = || Zeroizing::new("snapshot-key".to_string());
+
+        let old = QueueManager::new(dir.path().join("old.db"), Some(key())).await.unwrap();
+        old.enqueue(job("j1", "o1", "p1")).await.unwrap();
+        let void = PrintJob { kind: JobKind::Void, original_job_id: Some("j1".to_string()), ..job("j2", "o1", "p1") };
+        old.enqueue(void).await.unwrap();
+        let amend = PrintJob {
+            kind: JobKind::Amend,
+            original_job_id: Some("j1".to_string()),
+            removed_items: job("j1", "o1", "p1").items,
+            ..job("j3", "o1", "p1")
+        };
+        old.enqueue(amend).await.unwrap();
+
+        let snapshot = dir.path().join("queue.snapshot");
+        assert_eq!(old.export_snapshot(snapshot.clone(), key()).await.unwrap(), 3);
+
+        let new = QueueManager::new(dir.path().join("new.db"), Some(key())).await.unwrap();
+        let summary = new.import_snapshot(snapshot, key(), |_: &str, _: Option<&str>| None).await.unwrap();
+        assert_eq!((summary.imported, summary.skipped_duplicates), (3, 0));
+
+        let jobs = new.get_pending_jobs(10).await.unwrap();
+        let void = jobs.iter().find(|j| j.id == "j2").unwrap();
+        assert_eq!(void.kind, JobKind::Void);
+        assert_eq!(void.original_job_id.as_deref(), Some("j1"));
+        let amend = jobs.iter().find(|j| j.id == "j3").unwrap();
+        assert_eq!(amend.kind, JobKind::Amend);
+        assert_eq!(amend.removed_items[0].name, "Burger");
+        assert_eq!(jobs.iter().find(|j| j.id == "j1").unwrap().kind, JobKind::Order);
+    }
+
+    #[tokio::test]
+    async fn test_find_jobs_by_table_customer_and_order() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+        let seated = |id: &str, order: &str, table: &str, name: &str| PrintJob {
+            table_number: Some(table.to_string()),
+            customer_name: Some(name.to_string()),
+            order_number: order.to_string(),
+            ..job(id, order, "p1")
+        };
+        queue.enqueue(seated("j1", "o1", "12", "Sam Jansen")).await.unwrap();
+        queue.enqueue(PrintJob { station: "bar".to_string(), ..seated("j2", "o1", "12", "Sam Jansen") }).await.unwrap();
+        queue.enqueue(seated("j3", "o2", "7", "Samira")).await.unwrap();
+        queue.enqueue(seated("j4", "o3", "T12", "Alex")).await.unwrap();
+        queue.mark_completed("j1", 10, None).await.unwrap();
+
+        let by_table = queue
+            .find_jobs(&JobSearch { table_number: Some(" 12 ".to_string()), ..Default::default() })
+            .await
+            .unwrap();
+        let ids: Vec<&str> = by_table.iter().map(|r| r.job.id.as_str()).collect();
+        assert_eq!(ids, vec!["j2", "j1"]);
+        assert_eq!(by_table[1].job.status, status::COMPLETED);
+
+        let by_name = queue
+            .find_jobs(&JobSearch { customer_name: Some("sam".to_string()), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(by_name.len(), 3);
+        let both = JobSearch { customer_name: Some("SAM".to_string()), table_number: Some("7".to_string()), ..Default::default() };
+        assert_eq!(queue.find_jobs(&both).await.unwrap()[0].customer_name.as_deref(), Some("Samira"));
+        let by_order = JobSearch { order_number: Some("o3".to_string()), ..Default::default() };
+        assert_eq!(queue.find_jobs(&by_order).await.unwrap()[0].table_number.as_deref(), Some("T12"));
+
+        // Outside the window, and a search without criteria
+        clock.advance(std::time::Duration::from_secs(JOB_SEARCH_DEFAULT_WINDOW_SECS as u64 + 60));
+        assert!(queue.find_jobs(&by_order).await.unwrap().is_empty());
+        assert!(queue.find_jobs(&JobSearch::default()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_job_notes_show_in_history() {
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        queue.enqueue(job("j1", "o1", "p1")).await.unwrap();
+        queue.enqueue(job("j2", "o2", "p1")).await.unwrap();
+        queue.mark_failed("j1", "Connection refused").await.unwrap();
+
+        let entry = queue.add_job_note("j1", " printer was unplugged ").await.unwrap();
+        assert_eq!(entry.status, status::FAILED);
+        assert_eq!(entry.notes[0].note, "printer was unplugged");
+        queue.add_job_note("j1", "plugged back in, reprinted").await.unwrap();
+        assert!(queue.add_job_note("missing", "note").await.is_err());
+
+        let history = queue.job_history(10).await.unwrap();
+        assert_eq!(history.len(), 2);
+        let failed = history.iter().find(|e| e.id == "j1").unwrap();
+        assert_eq!(failed.notes.len(), 2);
+        assert_eq!(failed.error_message.as_deref(), Some("Connection refused"));
+        assert!(history.iter().find(|e| e.id == "j2").unwrap().notes.is_empty());
+
+        queue.clear_all_jobs().await.unwrap();
+        assert!(queue.job_notes("j1").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_recovery_requeues_jobs_failed_during_outage() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+        for (id, order) in [("j1", "o1"), ("j2", "o2"), ("j3", "o3")] {
+            queue.enqueue(job(id, order, "p1")).await.unwrap();
+        }
+
+        queue.mark_failed("j1", "Cover open").await.unwrap();
+        clock.advance(Duration::from_secs(600));
+        let outage_started = crate::clock::Clock::unix_secs(&*clock);
+        queue.mark_failed("j2", "Paper out").await.unwrap();
+        queue.mark_failed("j3", "Paper out").await.unwrap();
+
+        let failed = queue.failed_since("p1", outage_started).await.unwrap();
+        assert_eq!(failed.iter().map(|j| j.job_id.as_str()).collect::<Vec<_>>(), vec!["j2", "j3"]);
+        assert!(queue.failed_since("p2", outage_started).await.unwrap().is_empty());
+
+        let requeued = queue.requeue_jobs("p1", vec!["j2".to_string(), "j1-gone".to_string()]).await.unwrap();
+        assert_eq!(requeued, vec!["j2"]);
+        let pending: Vec<String> = queue.get_pending_jobs(10).await.unwrap().into_iter().map(|j| j.id).collect();
+        assert_eq!(pending, vec!["j2"]);
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_normalizes_station_and_reports_unmatched() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_unmatched_stations(tx);
+        let aliases = std::collections::BTreeMap::from([("KEUKEN".to_string(), "kitchen".to_string())]);
+        queue.set_stations(StationMap::new(&[], &aliases));
+
+        let mut j1 = job("j1", "o1", "p1");
+        j1.station = "Keuken ".to_string();
+        queue.enqueue(j1).await.unwrap();
+        let mut j2 = job("j2", "o2", "p1");
+        j2.station = "Bar".to_string();
+        queue.enqueue(j2).await.unwrap();
+
+        let pending = queue.get_pending_jobs(10).await.unwrap();
+        let station = |id: &str| pending.iter().find(|j| j.id == id).unwrap().station.clone();
+        assert_eq!(station("j1"), "kitchen");
+        assert_eq!(station("j2"), "bar");
+
+        let unmatched = rx.try_recv().unwrap();
+        assert_eq!((unmatched.job_id.as_str(), unmatched.received.as_str()), ("j2", "Bar"));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cached_payload_keeps_head_columns_current() {
+        let clock = crate::clock::TestClock::new();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None)
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+        queue.enqueue(job("j1", "o1", "p1")).await.unwrap();
+
+        assert_eq!(queue.get_pending_jobs(5).await.unwrap()[0].priority, priority::NORMAL);
+        queue.escalate_priority("j1", priority::URGENT).await.unwrap();
+        queue.retry_job("j1").await.unwrap();
+        clock.advance(Duration::from_secs(5));
+
+        let jobs = queue.get_pending_jobs_ordered(5).await.unwrap();
+        assert_eq!((jobs[0].priority, jobs[0].retry_count), (priority::URGENT, 1));
+        assert_eq!(jobs[0].items[0].name, "Burger");
+        assert_eq!(queue.job_cache_stats(), CacheStats { hits: 1, misses: 1, len: 1 });
+
+        queue.mark_completed("j1", 10, None).await.unwrap();
+        assert_eq!(queue.job_cache_stats().len, 0);
+    }
+}
@@ -0,0 +1,434 @@
+use crate::status;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use tracing::{info, warn};
+
+/// One schema change of the queue database. Applied once, in version order, in its own
+/// transaction together with its `schema_migrations` row: a migration that fails leaves
+/// the database as it was and the daemon retries it on the next start.
+///
+/// Never edit a migration that has shipped; add a new one. Migrations carry their own
+/// SQL (no shared column lists), so a database is the same whichever version it was
+/// created at. Changes must keep the rows: add columns, copy tables, transform data;
+/// never drop a table with jobs in it.
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    apply: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+/// All migrations, oldest first. 1-6 turn the schema the daemon built inline before
+/// migrations existed into version 6, whatever release created the database.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "create_tables", apply: create_tables },
+    Migration { version: 2, name: "print_jobs_add_columns", apply: print_jobs_add_columns },
+    Migration { version: 3, name: "print_jobs_nullable_order_id", apply: print_jobs_nullable_order_id },
+    Migration { version: 4, name: "status_processing_to_printing", apply: status_processing_to_printing },
+    Migration { version: 5, name: "print_jobs_indexes", apply: print_jobs_indexes },
+    Migration { version: 6, name: "backfill_completed_job_ids", apply: backfill_completed_job_ids },
+    Migration { version: 7, name: "print_jobs_add_change_columns", apply: print_jobs_add_change_columns },
+    Migration { version: 8, name: "print_jobs_add_print_duration", apply: print_jobs_add_print_duration },
+    Migration { version: 9, name: "print_jobs_add_released_at", apply: print_jobs_add_released_at },
+    Migration { version: 10, name: "create_printer_incidents", apply: create_printer_incidents },
+    Migration { version: 11, name: "create_ingested_job_ids", apply: create_ingested_job_ids },
+    Migration { version: 12, name: "print_jobs_add_order_stations", apply: print_jobs_add_order_stations },
+];
+
+/// Bring the database up to the newest schema. Returns the versions applied now.
+pub fn run(conn: &mut Connection) -> rusqlite::Result<Vec<u32>> {
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+        )
+        "#,
+        [],
+    )?;
+    let current = current_version(conn)?;
+    let latest = MIGRATIONS.last().map_or(0, |m| m.version);
+    if current > latest {
+        // Downgraded daemon: the newer schema is a superset, so keep going without touching it
+        warn!("Queue schema is at version {}, newer than this daemon knows ({})", current, latest);
+    }
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        (migration.apply)(&tx)?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, name) VALUES (?1, ?2)",
+            rusqlite::params![migration.version, migration.name],
+        )?;
+        tx.commit()?;
+        info!("Queue schema migrated to version {} ({})", migration.version, migration.name);
+        applied.push(migration.version);
+    }
+    Ok(applied)
+}
+
+/// Newest applied migration (0 for a database without any)
+pub fn current_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_migrations", [], |row| row.get(0))
+}
+
+fn columns(tx: &Transaction, table: &str) -> rusqlite::Result<Vec<(String, bool)>> {
+    tx.prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| Ok((row.get::<_, String>(1)?, row.get::<_, bool>(3)?)))?
+        .collect()
+}
+
+fn create_tables(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS print_jobs (
+            id TEXT PRIMARY KEY,
+            restaurant_id TEXT NOT NULL,
+            order_id TEXT,
+            order_number TEXT NOT NULL,
+            station TEXT NOT NULL,
+            printer_id TEXT,
+            items TEXT NOT NULL,
+            table_number TEXT,
+            customer_name TEXT,
+            order_type TEXT,
+            priority INTEGER DEFAULT 3,
+            timestamp INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            retry_count INTEGER DEFAULT 0,
+            error_message TEXT,
+            created_at INTEGER DEFAULT (strftime('%s', 'now')),
+            processing_at INTEGER,
+            completed_at INTEGER,
+            retry_after INTEGER,
+            business_date TEXT,
+            sent_at_ms INTEGER,
+            printed_at_ms INTEGER,
+            copies INTEGER DEFAULT 1,
+            correlation_id TEXT,
+            pickup_time INTEGER
+        );
+
+        -- Audit trail for changes the daemon makes on its own (e.g. printer re-addressing)
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            action TEXT NOT NULL,
+            subject TEXT NOT NULL,
+            details TEXT NOT NULL,
+            created_at INTEGER DEFAULT (strftime('%s', 'now'))
+        );
+
+        -- Circuit breaker transitions (breaker history, degraded minutes per day)
+        CREATE TABLE IF NOT EXISTS breaker_transitions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            printer_id TEXT NOT NULL,
+            from_state TEXT NOT NULL,
+            to_state TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            detail TEXT,
+            duration_ms INTEGER NOT NULL,
+            business_date TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_breaker_printer ON breaker_transitions(printer_id, created_at);
+
+        -- Cutter / mechanical faults per printer (recurring fault tracking)
+        CREATE TABLE IF NOT EXISTS mechanical_faults (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            printer_id TEXT NOT NULL,
+            fault TEXT NOT NULL,
+            recovered INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_mechanical_printer ON mechanical_faults(printer_id, created_at);
+
+        -- Daemon runs and how each ended (restart history)
+        CREATE TABLE IF NOT EXISTS daemon_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            version TEXT NOT NULL,
+            started_at INTEGER NOT NULL,
+            stopped_at INTEGER,
+            stop_reason TEXT
+        );
+
+        -- Operator notes on jobs; removed together with their job
+        CREATE TABLE IF NOT EXISTS job_notes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            job_id TEXT NOT NULL,
+            note TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_job_notes_job ON job_notes(job_id);
+
+        -- Pickup slips, one per order (see `pickup_slip.rs`)
+        CREATE TABLE IF NOT EXISTS pickup_slips (
+            order_id TEXT PRIMARY KEY,
+            order_number TEXT NOT NULL,
+            customer_name TEXT,
+            status TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            error_message TEXT,
+            created_at INTEGER NOT NULL,
+            printed_at INTEGER
+        );
+
+        -- Orders whose tickets have all printed, so completion is reported once
+        CREATE TABLE IF NOT EXISTS order_completions (
+            order_id TEXT PRIMARY KEY,
+            completed_at INTEGER NOT NULL
+        );
+
+        -- Local API idempotency keys (key is scoped to the signing client)
+        CREATE TABLE IF NOT EXISTS api_idempotency_keys (
+            key TEXT PRIMARY KEY,
+            job_id TEXT NOT NULL,
+            correlation_id TEXT NOT NULL,
+            body_hash TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+
+        -- IDs of printed jobs; outlives print_jobs cleanup (see COMPLETED_ID_RETENTION_SECS)
+        CREATE TABLE IF NOT EXISTS completed_job_ids (
+            id TEXT PRIMARY KEY,
+            completed_at INTEGER NOT NULL
+        );
+
+        -- Lifetime counters per printer (`printer_stats.rs`); never cleaned up
+        CREATE TABLE IF NOT EXISTS printer_stats (
+            printer_id TEXT PRIMARY KEY,
+            jobs_printed INTEGER NOT NULL DEFAULT 0,
+            jobs_failed INTEGER NOT NULL DEFAULT 0,
+            bytes_sent INTEGER NOT NULL DEFAULT 0,
+            print_ms INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT,
+            last_error_at INTEGER,
+            last_printed_at INTEGER,
+            since INTEGER NOT NULL,
+            revision INTEGER NOT NULL DEFAULT 0,
+            synced_revision INTEGER NOT NULL DEFAULT 0
+        );
+        "#,
+    )
+}
+
+/// Columns added to print_jobs over time by releases that migrated inline. Jobs queued
+/// before them: no retry delay or business day, one copy, correlation ID falls back to
+/// the job ID.
+fn print_jobs_add_columns(tx: &Transaction) -> rusqlite::Result<()> {
+    let existing = columns(tx, "print_jobs")?;
+    for (column, definition) in [
+        ("retry_after", "INTEGER"),
+        ("business_date", "TEXT"),
+        ("sent_at_ms", "INTEGER"),
+        ("printed_at_ms", "INTEGER"),
+        ("copies", "INTEGER DEFAULT 1"),
+        ("correlation_id", "TEXT"),
+        ("pickup_time", "INTEGER"),
+    ] {
+        if !existing.iter().any(|(name, _)| name == column) {
+            tx.execute(&format!("ALTER TABLE print_jobs ADD COLUMN {} {}", column, definition), [])?;
+            info!("Migrated print_jobs: added {} column", column);
+        }
+    }
+    Ok(())
+}
+
+/// Databases from before v1.1.6 have `order_id NOT NULL`. SQLite can't alter a column,
+/// so the table is rebuilt and every job copied over (this used to drop the table).
+fn print_jobs_nullable_order_id(tx: &Transaction) -> rusqlite::Result<()> {
+    let existing = columns(tx, "print_jobs")?;
+    if !existing.iter().any(|(name, not_null)| name == "order_id" && *not_null) {
+        return Ok(());
+    }
+    tx.execute_batch(
+        r#"
+        CREATE TABLE print_jobs_rebuild (
+            id TEXT PRIMARY KEY,
+            restaurant_id TEXT NOT NULL,
+            order_id TEXT,
+            order_number TEXT NOT NULL,
+            station TEXT NOT NULL,
+            printer_id TEXT,
+            items TEXT NOT NULL,
+            table_number TEXT,
+            customer_name TEXT,
+            order_type TEXT,
+            priority INTEGER DEFAULT 3,
+            timestamp INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            retry_count INTEGER DEFAULT 0,
+            error_message TEXT,
+            created_at INTEGER DEFAULT (strftime('%s', 'now')),
+            processing_at INTEGER,
+            completed_at INTEGER,
+            retry_after INTEGER,
+            business_date TEXT,
+            sent_at_ms INTEGER,
+            printed_at_ms INTEGER,
+            copies INTEGER DEFAULT 1,
+            correlation_id TEXT,
+            pickup_time INTEGER
+        );
+        "#,
+    )?;
+    let names: Vec<&str> = existing.iter().map(|(name, _)| name.as_str()).collect();
+    let list = names.join(", ");
+    let copied = tx.execute(&format!("INSERT INTO print_jobs_rebuild ({list}) SELECT {list} FROM print_jobs"), [])?;
+    tx.execute_batch("DROP TABLE print_jobs; ALTER TABLE print_jobs_rebuild RENAME TO print_jobs;")?;
+    info!("Migrated print_jobs: order_id nullable, {} jobs kept", copied);
+    Ok(())
+}
+
+fn status_processing_to_printing(tx: &Transaction) -> rusqlite::Result<()> {
+    let changed = tx.execute("UPDATE print_jobs SET status = ?1 WHERE status = 'processing'", [status::PRINTING])?;
+    if changed > 0 {
+        info!("Migrated {} jobs from 'processing' to 'printing'", changed);
+    }
+    Ok(())
+}
+
+fn print_jobs_indexes(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_status ON print_jobs(status);
+        CREATE INDEX IF NOT EXISTS idx_restaurant ON print_jobs(restaurant_id);
+        CREATE INDEX IF NOT EXISTS idx_priority ON print_jobs(priority, created_at);
+        CREATE INDEX IF NOT EXISTS idx_business_date ON print_jobs(business_date);
+
+        -- Front-of-house search (`find_jobs`): case-insensitive lookups within a time window
+        CREATE INDEX IF NOT EXISTS idx_table_number ON print_jobs(table_number COLLATE NOCASE, created_at);
+        CREATE INDEX IF NOT EXISTS idx_customer_name ON print_jobs(customer_name COLLATE NOCASE, created_at);
+        CREATE INDEX IF NOT EXISTS idx_order_number ON print_jobs(order_number COLLATE NOCASE, created_at);
+        "#,
+    )
+}
+
+fn backfill_completed_job_ids(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        r#"
+        INSERT OR IGNORE INTO completed_job_ids (id, completed_at)
+        SELECT id, completed_at FROM print_jobs
+        WHERE status = ?1 AND completed_at IS NOT NULL
+        "#,
+        [status::COMPLETED],
+    )?;
+    Ok(())
+}
+
+/// Void and amend tickets (`PrintJob.kind`). Jobs queued before them are regular tickets.
+fn print_jobs_add_change_columns(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        ALTER TABLE print_jobs ADD COLUMN kind TEXT NOT NULL DEFAULT 'order';
+        ALTER TABLE print_jobs ADD COLUMN original_job_id TEXT;
+        ALTER TABLE print_jobs ADD COLUMN removed_items TEXT;
+        CREATE INDEX IF NOT EXISTS idx_original_job ON print_jobs(original_job_id);
+        "#,
+    )
+}
+
+/// How long each job took to print, for the station backlog estimate
+fn print_jobs_add_print_duration(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        ALTER TABLE print_jobs ADD COLUMN print_duration_ms INTEGER;
+        CREATE INDEX IF NOT EXISTS idx_station_completed ON print_jobs(station, completed_at);
+        "#,
+    )
+}
+
+/// When a pull-mode station's ticket was released with `print_next`
+fn print_jobs_add_released_at(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute("ALTER TABLE print_jobs ADD COLUMN released_at INTEGER", [])?;
+    Ok(())
+}
+
+/// Printer-down incidents (`incidents.rs`), synced to Supabase by revision like the
+/// printer stats
+fn create_printer_incidents(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS printer_incidents (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            printer_id TEXT NOT NULL,
+            started_at INTEGER NOT NULL,
+            ended_at INTEGER,
+            cause TEXT NOT NULL,
+            affected_jobs INTEGER NOT NULL DEFAULT 0,
+            revision INTEGER NOT NULL DEFAULT 1,
+            synced_revision INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_incidents_started ON printer_incidents(started_at);
+        "#,
+    )
+}
+
+/// Upstream job ids seen by `QueueManager::ingest`, with the transport that brought each
+fn create_ingested_job_ids(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS ingested_job_ids (
+            id TEXT PRIMARY KEY,
+            transport TEXT NOT NULL,
+            seen_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_ingested_seen ON ingested_job_ids(seen_at);
+        "#,
+    )
+}
+
+/// Stations an order fans out to (`PrintJob.order_stations`, JSON array). Jobs queued
+/// before it don't say, so their orders complete on the tickets that arrived.
+fn print_jobs_add_order_stations(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute("ALTER TABLE print_jobs ADD COLUMN order_stations TEXT", [])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_database_keeps_its_jobs() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        // Schema of a v1.1 install: order_id NOT NULL, none of the later columns
+        conn.execute_batch(
+            r#"
+            CREATE TABLE print_jobs (
+                id TEXT PRIMARY KEY, restaurant_id TEXT NOT NULL, order_id TEXT NOT NULL,
+                order_number TEXT NOT NULL, station TEXT NOT NULL, printer_id TEXT,
+                items TEXT NOT NULL, table_number TEXT, customer_name TEXT, order_type TEXT,
+                priority INTEGER DEFAULT 3, timestamp INTEGER NOT NULL, status TEXT NOT NULL,
+                retry_count INTEGER DEFAULT 0, error_message TEXT,
+                created_at INTEGER DEFAULT (strftime('%s', 'now')), processing_at INTEGER, completed_at INTEGER
+            );
+            INSERT INTO print_jobs (id, restaurant_id, order_id, order_number, station, items, timestamp, status)
+            VALUES ('j1', 'r1', 'o1', '42', 'kitchen', '[]', 1, 'processing'),
+                   ('j2', 'r1', 'o2', '43', 'bar', '[]', 2, 'pending');
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(run(&mut conn).unwrap(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+        let jobs: Vec<(String, String, i64)> = conn
+            .prepare("SELECT id, status, copies FROM print_jobs ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(jobs, vec![("j1".into(), status::PRINTING.into(), 1), ("j2".into(), "pending".into(), 1)]);
+        conn.execute(
+            "INSERT INTO print_jobs (id, restaurant_id, order_number, station, items, timestamp, status)
+             VALUES ('j3', 'r1', '44', 'bar', '[]', 3, 'pending')",
+            [],
+        )
+        .unwrap();
+
+        // Nothing left to do on the next start; a fresh database ends up at the same version
+        assert_eq!(run(&mut conn).unwrap(), Vec::<u32>::new());
+        let mut fresh = Connection::open_in_memory().unwrap();
+        run(&mut fresh).unwrap();
+        assert_eq!(current_version(&fresh).unwrap(), current_version(&conn).unwrap());
+    }
+}
@@ -0,0 +1,310 @@
+use crate::config::{ConnectionType, PrinterConfig};
+use crate::discovery;
+use crate::errors::DaemonError;
+use crate::troubleshoot::{classify_error, FailureClass};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, info, warn};
+
+/// Consecutive connection failures before a rediscovery is attempted
+const FAILURE_THRESHOLD: u32 = 3;
+
+/// Minimum time between rediscovery attempts for the same printer
+const REDISCOVERY_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// Per-host connect timeout for the subnet sweep
+const SCAN_TIMEOUT_MS: u64 = 300;
+
+/// Work for the address recovery task
+#[derive(Debug, Clone, PartialEq)]
+pub enum AddressRequest {
+    /// Printer works: record its MAC (from the ARP cache) if we don't know it yet
+    Learn(String),
+    /// Printer keeps refusing/timing out: look for it under a new address
+    Rediscover(String),
+}
+
+#[derive(Debug, Default)]
+struct FailureState {
+    consecutive: u32,
+    last_attempt: Option<Instant>,
+}
+
+/// Watches print outcomes for connection failures that look like an IP change.
+///
+/// Only counts failures to connect at all (timeout / refused / unreachable); printer
+/// errors like paper out mean the address is fine.
+pub struct AddressMonitor {
+    failures: Mutex<HashMap<String, FailureState>>,
+    learned: Mutex<HashSet<String>>,
+    tx: mpsc::UnboundedSender<AddressRequest>,
+}
+
+impl AddressMonitor {
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<AddressRequest>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let monitor = Self {
+            failures: Mutex::new(HashMap::new()),
+            learned: Mutex::new(HashSet::new()),
+            tx,
+        };
+        (monitor, rx)
+    }
+
+    /// Queue address work directly (background rediscovery)
+    pub fn request(&self, request: AddressRequest) {
+        let _ = self.tx.send(request);
+    }
+
+    /// Record a print outcome for a printer
+    pub async fn record(&self, printer_id: &str, error: Option<&DaemonError>) {
+        let Some(error) = error else {
+            self.failures.lock().await.remove(printer_id);
+            if self.learned.lock().await.insert(printer_id.to_string()) {
+                let _ = self.tx.send(AddressRequest::Learn(printer_id.to_string()));
+            }
+            return;
+        };
+
+        if !is_connection_failure(error) {
+            return;
+        }
+
+        let mut failures = self.failures.lock().await;
+        let state = failures.entry(printer_id.to_string()).or_default();
+        state.consecutive += 1;
+
+        let cooled_down = state
+            .last_attempt
+            .map(|t| t.elapsed() >= REDISCOVERY_COOLDOWN)
+            .unwrap_or(true);
+
+        if state.consecutive >= FAILURE_THRESHOLD && cooled_down {
+            state.last_attempt = Some(Instant::now());
+            info!(
+                "Printer {} failed to connect {} times in a row, requesting rediscovery",
+                printer_id, state.consecutive
+            );
+            let _ = self.tx.send(AddressRequest::Rediscover(printer_id.to_string()));
+        }
+    }
+}
+
+fn is_connection_failure(error: &DaemonError) -> bool {
+    matches!(error, DaemonError::Network(_))
+        && matches!(
+            classify_error(error),
+            FailureClass::Timeout | FailureClass::ConnectionRefused | FailureClass::Unreachable
+        )
+}
+
+/// Normalize a MAC address to lowercase, zero-padded, colon separated
+/// (`0:11:62:a:b:c` / `00-11-62-0A-0B-0C` → `00:11:62:0a:0b:0c`).
+pub fn normalize_mac(raw: &str) -> Option<String> {
+    let parts: Vec<&str> = raw.split([':', '-']).collect();
+    if parts.len() != 6 {
+        return None;
+    }
+    let mut octets = Vec::with_capacity(6);
+    for part in parts {
+        let value = u8::from_str_radix(part, 16).ok()?;
+        octets.push(format!("{:02x}", value));
+    }
+    let mac = octets.join(":");
+    // Incomplete ARP entries show up as all zeros
+    (mac != "00:00:00:00:00:00").then_some(mac)
+}
+
+/// Parse ARP table output into (ip, mac) pairs.
+///
+/// Handles `/proc/net/arp` (Linux), `arp -an` (macOS) and `arp -a` (Windows): each line
+/// is scanned for an IPv4 address and a MAC address token.
+pub fn parse_arp_table(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut ip = None;
+            let mut mac = None;
+            for token in line.split_whitespace() {
+                let token = token.trim_matches(|c| c == '(' || c == ')');
+                if ip.is_none() && token.parse::<std::net::Ipv4Addr>().is_ok() {
+                    ip = Some(token.to_string());
+                } else if mac.is_none() {
+                    mac = normalize_mac(token);
+                }
+            }
+            Some((ip?, mac?))
+        })
+        .collect()
+}
+
+/// Read the OS ARP cache
+async fn read_arp_table() -> Vec<(String, String)> {
+    if cfg!(target_os = "linux") {
+        if let Ok(contents) = tokio::fs::read_to_string("/proc/net/arp").await {
+            return parse_arp_table(&contents);
+        }
+    }
+
+    let args: &[&str] = if cfg!(target_os = "windows") { &["-a"] } else { &["-an"] };
+    match tokio::process::Command::new("arp").args(args).output().await {
+        Ok(output) => parse_arp_table(&String::from_utf8_lossy(&output.stdout)),
+        Err(e) => {
+            warn!("Failed to read ARP table: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Host part of an "ip:port" address
+fn host_of(address: &str) -> &str {
+    address.rsplit_once(':').map(|(h, _)| h).unwrap_or(address)
+}
+
+/// Port part of an "ip:port" address (ESC/POS raw port if missing)
+fn port_of(address: &str) -> &str {
+    address.rsplit_once(':').map(|(_, p)| p).unwrap_or("9100")
+}
+
+/// MAC address of a network printer, if it is in the ARP cache
+pub async fn lookup_mac(address: &str) -> Option<String> {
+    let host = host_of(address);
+    read_arp_table()
+        .await
+        .into_iter()
+        .find(|(ip, _)| ip == host)
+        .map(|(_, mac)| mac)
+}
+
+/// How the new address was found
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchMethod {
+    Mac,
+    Model,
+}
+
+impl MatchMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchMethod::Mac => "mac",
+            MatchMethod::Model => "model",
+        }
+    }
+}
+
+/// Look for a moved network printer on the local subnet.
+///
+/// Matches on the learned MAC address first; without one, falls back to a unique
+/// printer with the same name/model that isn't already configured. Returns the new
+/// "ip:port" address.
+pub async fn rediscover(printer: &PrinterConfig, taken_addresses: &[String]) -> Option<(String, MatchMethod)> {
+    if !matches!(printer.connection_type, ConnectionType::Network) {
+        return None;
+    }
+
+    let old_host = host_of(&printer.address);
+    let port = port_of(&printer.address);
+    let subnet = discovery::detect_local_subnet();
+    info!("Rediscovering printer {} ({}) on {}", printer.id, printer.address, subnet);
+
+    // The sweep finds open print ports and, as a side effect, refreshes the ARP cache
+    // Include the printer's own port in case it isn't one of the standard ones
+    let profile = discovery::PortScanProfile {
+        timeout_ms: SCAN_TIMEOUT_MS,
+        ..discovery::PortScanProfile::default()
+    }
+    .with_port(port.parse().unwrap_or(9100));
+    let found = match discovery::scan_subnet_tcp(&subnet, &profile).await {
+        Ok(found) => found,
+        Err(e) => {
+            warn!("Rediscovery scan failed for {}: {}", printer.id, e);
+            return None;
+        }
+    };
+
+    if let Some(mac) = &printer.mac_address {
+        let new_host = read_arp_table()
+            .await
+            .into_iter()
+            .find(|(ip, m)| m == mac && ip != old_host)
+            .map(|(ip, _)| ip);
+        if let Some(host) = new_host {
+            return Some((format!("{}:{}", host, port), MatchMethod::Mac));
+        }
+        debug!("MAC {} of printer {} not seen on the network", mac, printer.id);
+    }
+
+    // Fallback: exactly one unconfigured printer of the same model
+    let candidates: Vec<_> = found
+        .iter()
+        .filter(|d| host_of(&d.address) != old_host)
+        .filter(|d| !taken_addresses.iter().any(|a| host_of(a) == host_of(&d.address)))
+        .filter(|d| same_model(d, printer))
+        .collect();
+
+    match candidates.as_slice() {
+        [only] => Some((format!("{}:{}", host_of(&only.address), port), MatchMethod::Model)),
+        [] => None,
+        many => {
+            warn!(
+                "Rediscovery for {} found {} printers matching '{}', not picking one",
+                printer.id,
+                many.len(),
+                printer.name
+            );
+            None
+        }
+    }
+}
+
+/// A discovered printer "looks like" a configured one if the IPP name matches, or the
+/// vendor reported by the printer appears in the configured name (e.g. "Epson TM-T88").
+fn same_model(found: &discovery::DiscoveredPrinter, printer: &PrinterConfig) -> bool {
+    let configured = printer.name.to_lowercase();
+    let vendor = found.vendor.to_lowercase();
+    found.name.to_lowercase() == configured
+        || (!vendor.is_empty() && vendor != "unknown" && configured.contains(&vendor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_arp_table_formats() {
+        let linux = "IP address       HW type     Flags       HW address            Mask     Device\n\
+                     192.168.1.50     0x1         0x2         00:11:62:0a:0b:0c     *        eth0\n\
+                     192.168.1.51     0x1         0x0         00:00:00:00:00:00     *        eth0\n";
+        assert_eq!(
+            parse_arp_table(linux),
+            vec![("192.168.1.50".to_string(), "00:11:62:0a:0b:0c".to_string())]
+        );
+
+        let macos = "? (192.168.1.50) at 0:11:62:a:b:c on en0 ifscope [ethernet]\n";
+        assert_eq!(parse_arp_table(macos)[0].1, "00:11:62:0a:0b:0c");
+
+        let windows = "  192.168.1.50          00-11-62-0a-0b-0c     dynamic\n";
+        assert_eq!(parse_arp_table(windows)[0].0, "192.168.1.50");
+    }
+
+    #[tokio::test]
+    async fn test_rediscovery_requested_after_repeated_connection_failures() {
+        let (monitor, mut rx) = AddressMonitor::new();
+        let timeout = DaemonError::Network("Connection timed out to 192.168.1.50:9100".to_string());
+        let paper_out = DaemonError::PrintJob("Paper out".to_string());
+
+        monitor.record("p1", Some(&paper_out)).await;
+        for _ in 0..FAILURE_THRESHOLD {
+            monitor.record("p1", Some(&timeout)).await;
+        }
+        assert_eq!(rx.try_recv().ok(), Some(AddressRequest::Rediscover("p1".to_string())));
+
+        // Cooldown: no second request right away
+        monitor.record("p1", Some(&timeout)).await;
+        assert!(rx.try_recv().is_err());
+
+        monitor.record("p2", None).await;
+        assert_eq!(rx.try_recv().ok(), Some(AddressRequest::Learn("p2".to_string())));
+    }
+}
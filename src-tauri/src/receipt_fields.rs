@@ -0,0 +1,173 @@
+use crate::venue_time;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Variables a header or footer may use, as `{name}`
+pub const VARIABLES: &[&str] = &["order_number", "pickup_time", "wifi_code", "vat_number"];
+
+/// Venue text printed above and below kitchen tickets and customer receipts
+/// (`AppConfig.receipt_fields`). Checked with `validate` when the config is saved.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReceiptFieldsConfig {
+    /// Under the station / venue name, e.g. "Afhalen om {pickup_time}"
+    pub header: Option<String>,
+    /// Above the cut, e.g. "WiFi: {wifi_code}\nBTW {vat_number}"
+    pub footer: Option<String>,
+    pub wifi_code: Option<String>,
+    pub vat_number: Option<String>,
+}
+
+/// Why a header or footer was refused
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FieldError {
+    #[error("unknown variable {{{0}}} (available: {{order_number}}, {{pickup_time}}, {{wifi_code}}, {{vat_number}})")]
+    UnknownVariable(String),
+
+    #[error("'{{' without a closing '}}' (write '{{{{' for a literal brace)")]
+    Unclosed,
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    Var(&'a str),
+}
+
+/// Split one line into text and variables. `{{` is a literal brace; a stray `}` is text.
+fn segments(line: &str) -> Result<Vec<Segment<'_>>, FieldError> {
+    let mut out = Vec::new();
+    let mut rest = line;
+    while let Some(pos) = rest.find('{') {
+        if pos > 0 {
+            out.push(Segment::Text(&rest[..pos]));
+        }
+        let tail = &rest[pos..];
+        if tail.starts_with("{{") {
+            out.push(Segment::Text("{"));
+            rest = &tail[2..];
+            continue;
+        }
+        let end = tail.find('}').ok_or(FieldError::Unclosed)?;
+        let name = tail[1..end].trim();
+        if !VARIABLES.contains(&name) {
+            return Err(FieldError::UnknownVariable(name.to_string()));
+        }
+        out.push(Segment::Var(name));
+        rest = &tail[end + 1..];
+    }
+    if !rest.is_empty() {
+        out.push(Segment::Text(rest));
+    }
+    Ok(out)
+}
+
+/// Check a header or footer against the available variables
+pub fn validate(text: &str) -> Result<(), FieldError> {
+    text.lines().try_for_each(|line| segments(line).map(|_| ()))
+}
+
+/// The order a ticket is for
+#[derive(Debug, Clone, Copy)]
+pub struct OrderFields<'a> {
+    pub order_number: &'a str,
+    /// Unix ms; printed as venue time HH:MM
+    pub pickup_time: Option<i64>,
+}
+
+/// Header and footer lines of one ticket, variables filled in
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReceiptBlocks {
+    pub header: Vec<String>,
+    pub footer: Vec<String>,
+    /// Venue logo raster commands over each copy of a kitchen ticket, station templates
+    /// and void / amend tickets included (`TestPrintTemplate.logo_on_kitchen_tickets`)
+    pub logo: Option<Arc<Vec<u8>>>,
+}
+
+impl ReceiptFieldsConfig {
+    /// Check header and footer; the error says which of the two is wrong
+    pub fn validate(&self) -> Result<(), (&'static str, FieldError)> {
+        for (block, text) in [("header", &self.header), ("footer", &self.footer)] {
+            if let Some(text) = text {
+                validate(text).map_err(|e| (block, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&self, name: &str, order: &OrderFields) -> Option<String> {
+        let set = |v: &Option<String>| v.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+        match name {
+            "order_number" => Some(order.order_number.to_string()),
+            "pickup_time" => order
+                .pickup_time
+                .and_then(venue_time::at_millis)
+                .map(|dt| dt.format("%H:%M").to_string()),
+            "wifi_code" => set(&self.wifi_code),
+            "vat_number" => set(&self.vat_number),
+            _ => None,
+        }
+    }
+
+    /// Fill in `text` line by line. A line using a variable with no value (no pickup
+    /// time on this order, no WiFi code set) is left out rather than printed half empty.
+    fn render_text(&self, text: &str, order: &OrderFields) -> Vec<String> {
+        text.lines()
+            .filter_map(|line| {
+                // Saved texts were validated; an invalid one (edited by hand) prints nothing
+                let segments = segments(line).ok()?;
+                let mut rendered = String::new();
+                for segment in segments {
+                    match segment {
+                        Segment::Text(text) => rendered.push_str(text),
+                        Segment::Var(name) => rendered.push_str(&self.value(name, order)?),
+                    }
+                }
+                Some(rendered.trim_end().to_string())
+            })
+            .collect()
+    }
+
+    /// Header and footer for one order
+    pub fn render(&self, order: &OrderFields) -> ReceiptBlocks {
+        let render = |text: &Option<String>| text.as_deref().map(|t| self.render_text(t, order)).unwrap_or_default();
+        ReceiptBlocks {
+            header: render(&self.header),
+            footer: render(&self.footer),
+            logo: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_and_render() {
+        assert_eq!(validate("Order {order_number} - {{not a variable}"), Ok(()));
+        assert_eq!(validate("Table {table}"), Err(FieldError::UnknownVariable("table".to_string())));
+        assert_eq!(validate("WiFi: {wifi_code"), Err(FieldError::Unclosed));
+
+        let config = ReceiptFieldsConfig {
+            header: Some("Bestelling {order_number}\nAfhalen om {pickup_time}".to_string()),
+            footer: Some("WiFi: { wifi_code }\nBTW {vat_number}\n{{ Tot ziens }".to_string()),
+            wifi_code: Some("pasta2024".to_string()),
+            vat_number: Some("  ".to_string()),
+        };
+        assert!(config.validate().is_ok());
+        let typo = ReceiptFieldsConfig { footer: Some("BTW {vat}".to_string()), ..config.clone() };
+        assert_eq!(typo.validate(), Err(("footer", FieldError::UnknownVariable("vat".to_string()))));
+
+        let blocks = config.render(&OrderFields { order_number: "42", pickup_time: None });
+        // No pickup time and a blank VAT number: those lines are left out
+        assert_eq!(blocks.header, vec!["Bestelling 42"]);
+        assert_eq!(blocks.footer, vec!["WiFi: pasta2024", "{ Tot ziens }"]);
+
+        let blocks = config.render(&OrderFields { order_number: "42", pickup_time: Some(0) });
+        assert_eq!(blocks.header.len(), 2);
+        let none = OrderFields { order_number: "1", pickup_time: None };
+        assert_eq!(ReceiptFieldsConfig::default().render(&none), ReceiptBlocks::default());
+    }
+}
@@ -0,0 +1,656 @@
+use crate::escpos::{Alignment, ESCPOSBuilder, Font, PaperWidth, PrintItem, TextSize, MAX_COPIES};
+use crate::receipt_fields::ReceiptBlocks;
+use crate::venue_time;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+
+/// Longest template source accepted
+pub const MAX_TEMPLATE_BYTES: usize = 16 * 1024;
+
+/// Station key of the template for stations without one of their own
+pub const ANY_STATION: &str = "*";
+
+/// Order variables, usable anywhere as `{{name}}` or `{{upper name}}`
+pub const ORDER_VARIABLES: &[&str] = &[
+    "station",
+    "order_number",
+    "order_type",
+    "table_number",
+    "customer_name",
+    "time",
+    "date",
+    "pickup_time",
+    "ref",
+    "copy",
+    "urgent",
+];
+
+/// Variables of one item, inside `{{#each items}}`
+pub const ITEM_VARIABLES: &[&str] = &["quantity", "name", "notes", "modifiers"];
+
+/// Per-station kitchen ticket layouts (`AppConfig.receipt_templates`). A station with a
+/// template prints it instead of the built-in layout; checked with `compile` when saved.
+///
+/// Templates are text with Handlebars-like tags:
+///
+/// ```text
+/// {{align center}}{{size double}}{{upper station}}
+/// {{size normal}}{{line =}}
+/// {{bold on}}ORDER {{order_number}}{{bold off}}
+/// {{#if table_number}}Table {{table_number}}{{/if}}
+/// {{#each items}}
+/// {{quantity}}x {{name}}
+/// {{#each modifiers}}  + {{modifier}}
+/// {{/each}}
+/// {{/each}}
+/// ```
+///
+/// A line holding only block, format or comment tags prints nothing, not even an empty
+/// line. Copies, the final feed and the cut are added around the template.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReceiptTemplatesConfig {
+    /// Template source by station name (matched case-insensitively); `*` for the rest
+    pub stations: BTreeMap<String, String>,
+}
+
+/// Why a template was refused (line numbers from 1)
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("template is larger than {} KB", MAX_TEMPLATE_BYTES / 1024)]
+    TooLarge,
+
+    #[error("line {line}: '{{{{' without a closing '}}}}' on the same line")]
+    Unclosed { line: usize },
+
+    #[error("line {line}: unknown tag '{tag}'")]
+    UnknownTag { line: usize, tag: String },
+
+    #[error("line {line}: '{name}' is only available inside {{{{#each {list}}}}}")]
+    OutOfScope { line: usize, name: String, list: &'static str },
+
+    #[error("line {line}: '{tag}' expects {expected}")]
+    BadArgument { line: usize, tag: String, expected: &'static str },
+
+    #[error("line {line}: '{tag}' doesn't close an open block")]
+    Unbalanced { line: usize, tag: String },
+
+    #[error("'{{{{#{block}}}}}' opened on line {line} is never closed")]
+    NotClosed { line: usize, block: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Order,
+    Item,
+    Modifier,
+}
+
+#[derive(Debug, Clone)]
+enum Command {
+    Align(Alignment),
+    Size(TextSize),
+    Bold(bool),
+    Underline(bool),
+    Inverse(bool),
+    Font(Font),
+    Line(char),
+    Feed(u8),
+    Header,
+    Footer,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    NewLine,
+    Var { name: String, upper: bool },
+    Command(Command),
+    If { name: String, negate: bool, then: Vec<Node>, otherwise: Vec<Node> },
+    EachItem(Vec<Node>),
+    EachModifier(Vec<Node>),
+}
+
+/// A compiled template
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    Tag(&'a str),
+}
+
+/// Split one line into text and `{{tags}}`
+fn segments(line: &str, number: usize) -> Result<Vec<Segment<'_>>, TemplateError> {
+    let mut out = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            out.push(Segment::Text(&rest[..start]));
+        }
+        let end = rest[start..].find("}}").ok_or(TemplateError::Unclosed { line: number })? + start;
+        out.push(Segment::Tag(rest[start + 2..end].trim()));
+        rest = &rest[end + 2..];
+    }
+    if !rest.is_empty() {
+        out.push(Segment::Text(rest));
+    }
+    Ok(out)
+}
+
+fn switch(tag: &str, arg: Option<&str>, line: usize) -> Result<bool, TemplateError> {
+    match arg {
+        Some("on") => Ok(true),
+        Some("off") => Ok(false),
+        _ => Err(TemplateError::BadArgument { line, tag: tag.to_string(), expected: "'on' or 'off'" }),
+    }
+}
+
+fn command(tag: &str, line: usize) -> Result<Option<Command>, TemplateError> {
+    let mut words = tag.split_whitespace();
+    let (Some(name), arg) = (words.next(), words.next()) else {
+        return Ok(None);
+    };
+    let bad = |expected| Err(TemplateError::BadArgument { line, tag: tag.to_string(), expected });
+    let command = match name {
+        "align" => match arg {
+            Some("left") => Command::Align(Alignment::Left),
+            Some("center") => Command::Align(Alignment::Center),
+            Some("right") => Command::Align(Alignment::Right),
+            _ => return bad("'left', 'center' or 'right'"),
+        },
+        "size" => match arg {
+            Some("normal") => Command::Size(TextSize::Normal),
+            Some("wide") => Command::Size(TextSize::DoubleWidth),
+            Some("tall") => Command::Size(TextSize::DoubleHeight),
+            Some("double") => Command::Size(TextSize::DoubleBoth),
+            _ => return bad("'normal', 'wide', 'tall' or 'double'"),
+        },
+        "bold" => Command::Bold(switch(tag, arg, line)?),
+        "underline" => Command::Underline(switch(tag, arg, line)?),
+        "inverse" => Command::Inverse(switch(tag, arg, line)?),
+        "font" => match arg {
+            Some("a") => Command::Font(Font::A),
+            Some("b") => Command::Font(Font::B),
+            _ => return bad("'a' or 'b'"),
+        },
+        "line" => match arg.map(|a| (a.chars().count(), a.chars().next())) {
+            None => Command::Line('-'),
+            Some((1, Some(c))) if c.is_ascii_graphic() => Command::Line(c),
+            _ => return bad("one character, e.g. '=' (default '-')"),
+        },
+        "feed" => match arg.map(str::parse::<u8>) {
+            None => Command::Feed(1),
+            Some(Ok(n)) if (1..=9).contains(&n) => Command::Feed(n),
+            _ => return bad("a number of lines from 1 to 9"),
+        },
+        "header" if arg.is_none() => Command::Header,
+        "footer" if arg.is_none() => Command::Footer,
+        _ => return Ok(None),
+    };
+    if words.next().is_some() {
+        return bad("a single argument");
+    }
+    Ok(Some(command))
+}
+
+/// Scope a variable or `#if` condition belongs to (None: unknown)
+fn scope_of(name: &str) -> Option<Scope> {
+    if ORDER_VARIABLES.contains(&name) {
+        Some(Scope::Order)
+    } else if ITEM_VARIABLES.contains(&name) {
+        Some(Scope::Item)
+    } else if name == "modifier" {
+        Some(Scope::Modifier)
+    } else {
+        None
+    }
+}
+
+/// An open block while parsing
+struct Frame {
+    kind: &'static str,
+    line: usize,
+    /// Nodes of an `#if` before its `{{else}}`
+    then: Option<Vec<Node>>,
+    condition: Option<(String, bool)>,
+    nodes: Vec<Node>,
+}
+
+/// Parse and check a template
+pub fn compile(source: &str) -> Result<Template, TemplateError> {
+    if source.len() > MAX_TEMPLATE_BYTES {
+        return Err(TemplateError::TooLarge);
+    }
+    let mut stack = vec![Frame { kind: "template", line: 0, then: None, condition: None, nodes: Vec::new() }];
+    let lines: Vec<&str> = source.split('\n').collect();
+
+    for (index, raw) in lines.iter().enumerate() {
+        let line = index + 1;
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        let segments = segments(raw, line)?;
+        // Lines of only block / format / comment tags leave no trace on paper
+        let standalone = segments.iter().any(|s| matches!(s, Segment::Tag(_)))
+            && segments.iter().all(|s| match s {
+                Segment::Text(text) => text.trim().is_empty(),
+                Segment::Tag(tag) => {
+                    let first = tag.split_whitespace().next().unwrap_or("");
+                    !(scope_of(first).is_some() || first == "upper")
+                }
+            });
+
+        for segment in segments {
+            let tag = match segment {
+                Segment::Text(text) => {
+                    if !standalone {
+                        stack.last_mut().expect("root frame").nodes.push(Node::Text(text.to_string()));
+                    }
+                    continue;
+                }
+                Segment::Tag(tag) => tag,
+            };
+            if tag.starts_with('!') {
+                continue;
+            }
+            let scopes: Vec<&str> = stack.iter().map(|f| f.kind).collect();
+            let in_scope = |name: &str| -> Result<(), TemplateError> {
+                let (needed, list) = match scope_of(name) {
+                    None => return Err(TemplateError::UnknownTag { line, tag: tag.to_string() }),
+                    Some(Scope::Order) => return Ok(()),
+                    Some(Scope::Item) => ("each items", "items"),
+                    Some(Scope::Modifier) => ("each modifiers", "modifiers"),
+                };
+                if scopes.contains(&needed) {
+                    Ok(())
+                } else {
+                    Err(TemplateError::OutOfScope { line, name: name.to_string(), list })
+                }
+            };
+
+            let mut words = tag.split_whitespace();
+            match (words.next().unwrap_or(""), words.next(), words.next()) {
+                (open @ ("#if" | "#unless"), Some(name), None) => {
+                    in_scope(name)?;
+                    let kind = if open == "#if" { "if" } else { "unless" };
+                    let condition = Some((name.to_string(), open == "#unless"));
+                    stack.push(Frame { kind, line, then: None, condition, nodes: Vec::new() });
+                }
+                ("#each", Some("items"), None) if !scopes.contains(&"each items") => {
+                    stack.push(Frame { kind: "each items", line, then: None, condition: None, nodes: Vec::new() });
+                }
+                ("#each", Some("modifiers"), None) if scopes.contains(&"each items") => {
+                    stack.push(Frame { kind: "each modifiers", line, then: None, condition: None, nodes: Vec::new() });
+                }
+                ("#each", Some("modifiers"), None) => {
+                    return Err(TemplateError::OutOfScope { line, name: "modifiers".to_string(), list: "items" });
+                }
+                ("else", None, None) => {
+                    let frame = stack.last_mut().expect("root frame");
+                    if !matches!(frame.kind, "if" | "unless") || frame.then.is_some() {
+                        return Err(TemplateError::Unbalanced { line, tag: tag.to_string() });
+                    }
+                    frame.then = Some(std::mem::take(&mut frame.nodes));
+                }
+                (close, None, None) if close.starts_with('/') => {
+                    let frame = stack.pop().expect("root frame");
+                    let expected = match frame.kind {
+                        "if" => "/if",
+                        "unless" => "/unless",
+                        "each items" | "each modifiers" => "/each",
+                        _ => "",
+                    };
+                    if close != expected {
+                        return Err(TemplateError::Unbalanced { line, tag: tag.to_string() });
+                    }
+                    let node = match frame.kind {
+                        "each items" => Node::EachItem(frame.nodes),
+                        "each modifiers" => Node::EachModifier(frame.nodes),
+                        _ => {
+                            let (name, negate) = frame.condition.expect("if frames have a condition");
+                            let (then, otherwise) = match frame.then {
+                                Some(then) => (then, frame.nodes),
+                                None => (frame.nodes, Vec::new()),
+                            };
+                            Node::If { name, negate, then, otherwise }
+                        }
+                    };
+                    stack.last_mut().expect("root frame").nodes.push(node);
+                }
+                ("upper", Some(name), None) => {
+                    in_scope(name)?;
+                    stack.last_mut().expect("root frame").nodes.push(Node::Var { name: name.to_string(), upper: true });
+                }
+                (name, None, None) if scope_of(name).is_some() => {
+                    in_scope(name)?;
+                    stack.last_mut().expect("root frame").nodes.push(Node::Var { name: name.to_string(), upper: false });
+                }
+                _ => match command(tag, line)? {
+                    Some(command) => stack.last_mut().expect("root frame").nodes.push(Node::Command(command)),
+                    None => return Err(TemplateError::UnknownTag { line, tag: tag.to_string() }),
+                },
+            }
+        }
+        if !standalone && index + 1 < lines.len() {
+            stack.last_mut().expect("root frame").nodes.push(Node::NewLine);
+        }
+    }
+
+    let root = stack.remove(0);
+    if let Some(open) = stack.first() {
+        return Err(TemplateError::NotClosed { line: open.line, block: open.kind.to_string() });
+    }
+    Ok(Template { nodes: root.nodes })
+}
+
+/// The order a templated ticket is for
+#[derive(Debug, Clone, Copy)]
+pub struct TicketData<'a> {
+    pub station: &'a str,
+    pub order_number: &'a str,
+    pub order_type: Option<&'a str>,
+    pub table_number: Option<&'a str>,
+    pub customer_name: Option<&'a str>,
+    pub priority: u8,
+    pub items: &'a [PrintItem],
+    /// Unix ms
+    pub timestamp: i64,
+    /// Unix ms
+    pub pickup_time: Option<i64>,
+    pub reference: Option<&'a str>,
+}
+
+struct Context<'a> {
+    ticket: &'a TicketData<'a>,
+    blocks: &'a ReceiptBlocks,
+    copy: Option<(u8, u8)>,
+    item: Option<&'a PrintItem>,
+    modifier: Option<&'a str>,
+}
+
+impl Context<'_> {
+    /// Value of a variable; empty counts as false in `#if`
+    fn value(&self, name: &str) -> String {
+        let ticket = self.ticket;
+        let local = |ms: i64, format: &str| {
+            venue_time::at_millis(ms).map(|dt| dt.format(format).to_string()).unwrap_or_default()
+        };
+        let item = |f: fn(&PrintItem) -> String| self.item.map(f).unwrap_or_default();
+        match name {
+            "station" => ticket.station.to_string(),
+            "order_number" => ticket.order_number.to_string(),
+            "order_type" => ticket.order_type.unwrap_or_default().to_string(),
+            "table_number" => ticket.table_number.unwrap_or_default().to_string(),
+            "customer_name" => ticket.customer_name.unwrap_or_default().to_string(),
+            "time" => local(ticket.timestamp, "%H:%M"),
+            "date" => local(ticket.timestamp, "%d-%m-%Y"),
+            "pickup_time" => ticket.pickup_time.map(|ms| local(ms, "%H:%M")).unwrap_or_default(),
+            "ref" => ticket.reference.unwrap_or_default().to_string(),
+            "copy" => self.copy.map(|(n, of)| format!("{}/{}", n, of)).unwrap_or_default(),
+            // Same rule as the built-in layout's URGENT banner
+            "urgent" => if ticket.priority == 1 { "URGENT".to_string() } else { String::new() },
+            "quantity" => item(|i| i.quantity.to_string()),
+            "name" => item(|i| i.name.clone()),
+            "notes" => item(|i| i.notes.clone().unwrap_or_default()),
+            "modifiers" => item(|i| i.modifiers.join(", ")),
+            "modifier" => self.modifier.unwrap_or_default().to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn render(&self, nodes: &[Node], builder: &mut ESCPOSBuilder) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => {
+                    builder.text(text);
+                }
+                Node::NewLine => {
+                    builder.new_line();
+                }
+                Node::Var { name, upper } => {
+                    let value = self.value(name);
+                    builder.text(&if *upper { value.to_uppercase() } else { value });
+                }
+                Node::Command(command) => match command {
+                    Command::Align(alignment) => {
+                        builder.align(*alignment);
+                    }
+                    Command::Size(size) => {
+                        builder.size(*size);
+                    }
+                    Command::Bold(on) => {
+                        builder.bold(*on);
+                    }
+                    Command::Underline(on) => {
+                        builder.underline(*on);
+                    }
+                    Command::Inverse(on) => {
+                        builder.inverse(*on);
+                    }
+                    Command::Font(font) => {
+                        builder.font(*font);
+                    }
+                    Command::Line(c) => {
+                        builder.draw_line(*c);
+                    }
+                    Command::Feed(lines) => {
+                        builder.feed(*lines);
+                    }
+                    Command::Header | Command::Footer => {
+                        let lines = if matches!(command, Command::Header) { &self.blocks.header } else { &self.blocks.footer };
+                        for line in lines {
+                            builder.text(line).new_line();
+                        }
+                    }
+                },
+                Node::If { name, negate, then, otherwise } => {
+                    let set = !self.value(name).is_empty();
+                    self.render(if set != *negate { then } else { otherwise }, builder);
+                }
+                Node::EachItem(body) => {
+                    for item in self.ticket.items {
+                        Context { item: Some(item), ..*self }.render(body, builder);
+                    }
+                }
+                Node::EachModifier(body) => {
+                    for modifier in self.item.map(|i| i.modifiers.as_slice()).unwrap_or_default() {
+                        Context { modifier: Some(modifier), ..*self }.render(body, builder);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Template {
+    /// ESC/POS commands for a ticket: the template once per copy (partial cut between
+    /// copies), then the feed and full cut
+    pub fn render(&self, ticket: &TicketData, copies: u8, paper_width: PaperWidth, blocks: &ReceiptBlocks) -> Vec<u8> {
+        let copies = copies.clamp(1, MAX_COPIES);
+        let mut builder = ESCPOSBuilder::new(paper_width);
+        for copy in 1..=copies {
+            builder.initialize();
+            // Over each copy, like the built-in layout; the template starts left-aligned
+            if let Some(logo) = &blocks.logo {
+                builder.align(Alignment::Center).raw(logo).new_line().align(Alignment::Left);
+            }
+            let context = Context {
+                ticket,
+                blocks,
+                copy: (copies > 1).then_some((copy, copies)),
+                item: None,
+                modifier: None,
+            };
+            context.render(&self.nodes, &mut builder);
+            if copy < copies {
+                builder.new_line().cut(true);
+            }
+        }
+        builder.new_line().feed(2).cut(false);
+        builder.build()
+    }
+}
+
+impl ReceiptTemplatesConfig {
+    /// Check every template; the error names the station
+    pub fn validate(&self) -> Result<(), (String, TemplateError)> {
+        for (station, source) in &self.stations {
+            compile(source).map_err(|e| (station.clone(), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Compiled templates by lowercased station, for printing
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptTemplates {
+    by_station: HashMap<String, Template>,
+}
+
+impl ReceiptTemplates {
+    /// Compile the configured templates. Saved ones were validated; one edited by hand
+    /// into something invalid is skipped (the station prints the built-in layout).
+    pub fn new(config: &ReceiptTemplatesConfig) -> Self {
+        let by_station = config
+            .stations
+            .iter()
+            .filter_map(|(station, source)| match compile(source) {
+                Ok(template) => Some((station.trim().to_lowercase(), template)),
+                Err(e) => {
+                    tracing::warn!("Ignoring receipt template for {}: {}", station, e);
+                    None
+                }
+            })
+            .collect();
+        Self { by_station }
+    }
+
+    /// Template for a station: its own, else the `*` template, else none (built-in layout)
+    pub fn for_station(&self, station: &str) -> Option<&Template> {
+        self.by_station
+            .get(&station.trim().to_lowercase())
+            .or_else(|| self.by_station.get(ANY_STATION))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escpos::{parse_escpos, ReceiptElement};
+
+    fn item(quantity: u32, name: &str, modifiers: &[&str], notes: Option<&str>) -> PrintItem {
+        PrintItem {
+            quantity,
+            name: name.to_string(),
+            modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+            notes: notes.map(str::to_string),
+            image_url: None,
+            pricing: None,
+        }
+    }
+
+    fn lines(commands: &[u8]) -> Vec<String> {
+        let mut lines = vec![String::new()];
+        for element in parse_escpos(commands, PaperWidth::Width80mm).elements {
+            match element {
+                ReceiptElement::Text { content, .. } => lines.last_mut().unwrap().push_str(&content),
+                ReceiptElement::Feed { .. } => lines.push(String::new()),
+                ReceiptElement::Cut { .. } => {}
+            }
+        }
+        lines.into_iter().filter(|l| !l.is_empty()).collect()
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        assert_eq!(compile("Order {{order_number").unwrap_err(), TemplateError::Unclosed { line: 1 });
+        assert_eq!(
+            compile("{{align center}}\n{{tabel_number}}").unwrap_err(),
+            TemplateError::UnknownTag { line: 2, tag: "tabel_number".to_string() }
+        );
+        assert_eq!(
+            compile("{{name}}").unwrap_err(),
+            TemplateError::OutOfScope { line: 1, name: "name".to_string(), list: "items" }
+        );
+        assert!(matches!(compile("{{size huge}}"), Err(TemplateError::BadArgument { line: 1, .. })));
+        assert!(matches!(compile("{{#each items}}\n{{/if}}"), Err(TemplateError::Unbalanced { line: 2, .. })));
+        assert_eq!(
+            compile("{{#each items}}\n{{#if notes}}\n{{/if}}").unwrap_err(),
+            TemplateError::NotClosed { line: 1, block: "each items".to_string() }
+        );
+        assert!(compile(&"x".repeat(MAX_TEMPLATE_BYTES + 1)).is_err());
+    }
+
+    #[test]
+    fn test_render_template() {
+        let source = "{{! kitchen layout }}\n\
+                      {{align center}}{{size double}}{{upper station}}\n\
+                      {{size normal}}{{#if copy}}Copy {{copy}}\n{{/if}}\
+                      {{header}}\n\
+                      {{line =}}\n\
+                      {{align left}}ORDER {{order_number}}{{#if table_number}} / table {{table_number}}{{/if}}\n\
+                      {{#unless customer_name}}\nWalk-in\n{{else}}\nFor {{customer_name}}\n{{/unless}}\n\
+                      {{#each items}}\n\
+                      {{quantity}}x {{name}}\n\
+                      {{#each modifiers}}\n  + {{modifier}}\n{{/each}}\n\
+                      {{#if notes}}\n  NOTE: {{notes}}\n{{/if}}\n\
+                      {{/each}}\n\
+                      {{line}}\n\
+                      ref {{ref}}";
+        let template = compile(source).unwrap();
+        let items = vec![item(2, "Burger", &["no onion", "extra cheese"], None), item(1, "Soup", &[], Some("hot"))];
+        let ticket = TicketData {
+            station: "Kitchen",
+            order_number: "42",
+            order_type: None,
+            table_number: Some("7"),
+            customer_name: None,
+            priority: 3,
+            items: &items,
+            timestamp: 0,
+            pickup_time: None,
+            reference: Some("ab12"),
+        };
+        let blocks = ReceiptBlocks { header: vec!["Afhalen om 18:30".to_string()], footer: vec![], logo: None };
+
+        let printed = lines(&template.render(&ticket, 1, PaperWidth::Width80mm, &blocks));
+        assert_eq!(
+            printed,
+            vec![
+                "KITCHEN".to_string(),
+                "Afhalen om 18:30".to_string(),
+                "=".repeat(48),
+                "ORDER 42 / table 7".to_string(),
+                "Walk-in".to_string(),
+                "2x Burger".to_string(),
+                "  + no onion".to_string(),
+                "  + extra cheese".to_string(),
+                "1x Soup".to_string(),
+                "  NOTE: hot".to_string(),
+                "-".repeat(48),
+                "ref ab12".to_string(),
+            ]
+        );
+
+        // Copies repeat the template with their label
+        let printed = lines(&template.render(&ticket, 2, PaperWidth::Width80mm, &blocks));
+        assert_eq!(printed.iter().filter(|l| l.as_str() == "KITCHEN").count(), 2);
+        assert!(printed.contains(&"Copy 2/2".to_string()));
+
+        // The venue logo goes over every copy
+        let logo = std::sync::Arc::new(b"\x1dv0LOGO".to_vec());
+        let with_logo = ReceiptBlocks { logo: Some(logo.clone()), ..blocks.clone() };
+        let commands = template.render(&ticket, 2, PaperWidth::Width80mm, &with_logo);
+        assert_eq!(commands.windows(logo.len()).filter(|w| *w == logo.as_slice()).count(), 2);
+
+        let config = ReceiptTemplatesConfig {
+            stations: BTreeMap::from([("Kitchen".to_string(), source.to_string()), ("Bar".to_string(), "{{name}}".to_string())]),
+        };
+        assert_eq!(config.validate().unwrap_err().0, "Bar");
+        let templates = ReceiptTemplates::new(&config);
+        assert!(templates.for_station(" kitchen ").is_some());
+        assert!(templates.for_station("bar").is_none());
+    }
+}
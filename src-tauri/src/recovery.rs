@@ -0,0 +1,232 @@
+use crate::config::{AppConfig, PrinterConfig};
+use crate::errors::{DaemonError, Result};
+use crate::queue::QueueManager;
+use crate::status;
+use crate::supabase_client::SupabaseClient;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Jobs that failed this long before paper-out was noticed count as part of the outage
+/// (the status poll runs every 30s, jobs fail on the printer first)
+pub const OUTAGE_GRACE_SECS: i64 = 120;
+
+/// What happens to tickets that failed while a printer was out of paper
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryMode {
+    /// Staff reprint by hand
+    Off,
+    /// Reprint as soon as the paper is back
+    Auto,
+    /// Ask on the dashboard first
+    #[default]
+    Confirm,
+}
+
+/// Per-printer recovery print settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecoveryConfig {
+    pub mode: RecoveryMode,
+    /// Most tickets reprinted after one outage (the most recent ones)
+    pub max_jobs: usize,
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self {
+            mode: RecoveryMode::default(),
+            max_jobs: 10,
+        }
+    }
+}
+
+/// A failed job that is a candidate for the recovery print
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryJob {
+    pub job_id: String,
+    pub order_number: String,
+}
+
+/// Tickets to reprint after a paper-out outage, waiting for staff in `Confirm` mode
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryOffer {
+    pub printer_id: String,
+    pub printer_name: String,
+    /// Unix seconds
+    pub outage_started: i64,
+    /// Oldest first
+    pub jobs: Vec<RecoveryJob>,
+    /// Failed during the outage but over `max_jobs`; left for staff
+    pub skipped: usize,
+}
+
+/// Keep the `max_jobs` most recent of the failed jobs (given oldest first)
+pub fn cap(mut failed: Vec<RecoveryJob>, max_jobs: usize) -> (Vec<RecoveryJob>, usize) {
+    let skipped = failed.len().saturating_sub(max_jobs);
+    (failed.split_off(skipped), skipped)
+}
+
+/// Reprints tickets that failed while a printer was out of paper, once the paper is back.
+///
+/// In `Confirm` mode the reprint waits as an offer on the dashboard
+/// (`recovery-print-offered`), one per printer; a newer outage replaces it.
+pub struct RecoveryPrints {
+    offers: Mutex<HashMap<String, RecoveryOffer>>,
+    queue_manager: QueueManager,
+    config: Arc<Mutex<AppConfig>>,
+    /// Set during Tauri .setup()
+    app_handle: Arc<Mutex<Option<AppHandle>>>,
+}
+
+impl RecoveryPrints {
+    pub fn new(
+        queue_manager: QueueManager,
+        config: Arc<Mutex<AppConfig>>,
+        app_handle: Arc<Mutex<Option<AppHandle>>>,
+    ) -> Self {
+        Self {
+            offers: Mutex::new(HashMap::new()),
+            queue_manager,
+            config,
+            app_handle,
+        }
+    }
+
+    /// The printer is back online after running out of paper at `outage_started`
+    pub async fn paper_restored(&self, printer: &PrinterConfig, outage_started: i64) {
+        if printer.recovery.mode == RecoveryMode::Off {
+            return;
+        }
+
+        let failed = {
+            let queue = &self.queue_manager;
+            queue.failed_since(&printer.id, outage_started - OUTAGE_GRACE_SECS).await
+        };
+        let failed = match failed {
+            Ok(failed) if !failed.is_empty() => failed,
+            Ok(_) => return,
+            Err(e) => {
+                warn!("Failed to look up jobs for recovery print on {}: {}", printer.id, e);
+                return;
+            }
+        };
+
+        let (jobs, skipped) = cap(failed, printer.recovery.max_jobs);
+        let offer = RecoveryOffer {
+            printer_id: printer.id.clone(),
+            printer_name: printer.name.clone(),
+            outage_started,
+            jobs,
+            skipped,
+        };
+
+        match printer.recovery.mode {
+            RecoveryMode::Auto => {
+                if let Err(e) = self.print(offer).await {
+                    warn!("Recovery print on {} failed: {}", printer.id, e);
+                }
+            }
+            RecoveryMode::Confirm => {
+                info!(
+                    "Paper back on {}: {} failed tickets waiting for confirmation",
+                    printer.id,
+                    offer.jobs.len()
+                );
+                self.offers.lock().await.insert(printer.id.clone(), offer.clone());
+                self.emit("recovery-print-offered", &offer).await;
+            }
+            RecoveryMode::Off => {}
+        }
+    }
+
+    /// Reprint the offered tickets. Returns how many were queued again.
+    pub async fn confirm(&self, printer_id: &str) -> Result<usize> {
+        let offer = self
+            .offers
+            .lock()
+            .await
+            .remove(printer_id)
+            .ok_or_else(|| DaemonError::Queue(format!("No recovery print waiting for {}", printer_id)))?;
+        self.print(offer).await
+    }
+
+    pub async fn dismiss(&self, printer_id: &str) -> bool {
+        self.offers.lock().await.remove(printer_id).is_some()
+    }
+
+    pub async fn offers(&self) -> Vec<RecoveryOffer> {
+        self.offers.lock().await.values().cloned().collect()
+    }
+
+    /// Requeue the offer's jobs, report them pending to Supabase and audit the reprint
+    async fn print(&self, offer: RecoveryOffer) -> Result<usize> {
+        let job_ids = offer.jobs.iter().map(|j| j.job_id.clone()).collect();
+        let queue = &self.queue_manager;
+        let requeued = queue.requeue_jobs(&offer.printer_id, job_ids).await?;
+        if let Err(e) = queue
+            .record_audit(
+                "recovery_print",
+                &offer.printer_id,
+                serde_json::json!({
+                    "outage_started": offer.outage_started,
+                    "jobs": requeued,
+                    "skipped": offer.skipped,
+                }),
+            )
+            .await
+        {
+            warn!("Failed to record recovery print for {}: {}", offer.printer_id, e);
+        }
+        info!("Recovery print on {}: {} tickets queued again", offer.printer_id, requeued.len());
+
+        let client = {
+            let cfg = self.config.lock().await;
+            cfg.auth_token
+                .clone()
+                .or_else(crate::config::load_auth_token)
+                .map(|token| SupabaseClient::new(cfg.supabase_url.clone(), cfg.supabase_anon_key.clone(), Some(token)))
+        };
+        if let Some(client) = client {
+            for job_id in &requeued {
+                let _ = client.update_job_status(job_id, status::PENDING, None, None, None).await;
+            }
+        }
+
+        self.emit(
+            "recovery-print-started",
+            &serde_json::json!({ "printer_id": offer.printer_id, "requeued": requeued.len() }),
+        )
+        .await;
+        Ok(requeued.len())
+    }
+
+    async fn emit<S: Serialize + Clone>(&self, event: &str, payload: &S) {
+        if let Some(ref handle) = *self.app_handle.lock().await {
+            let _ = handle.emit(event, payload.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_keeps_most_recent() {
+        let jobs: Vec<RecoveryJob> = (1..=5)
+            .map(|n| RecoveryJob { job_id: format!("j{}", n), order_number: n.to_string() })
+            .collect();
+
+        let (kept, skipped) = cap(jobs.clone(), 3);
+        assert_eq!(skipped, 2);
+        assert_eq!(kept.iter().map(|j| j.job_id.as_str()).collect::<Vec<_>>(), vec!["j3", "j4", "j5"]);
+
+        let (kept, skipped) = cap(jobs, 10);
+        assert_eq!((kept.len(), skipped), (5, 0));
+    }
+}
@@ -0,0 +1,206 @@
+use crate::config::{ConnectionType, PrinterConfig};
+use crate::discovery::DiscoveredPrinter;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How often the scheduler checks whether a rediscovery is due
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Off-peak background rediscovery: refresh printer metadata, catch IP drift and
+/// re-register printers in Supabase while the venue is closed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RediscoveryConfig {
+    pub enabled: bool,
+    /// Minimum hours between runs
+    pub interval_hours: u32,
+    /// Local hour the off-peak window opens (inclusive)
+    pub window_start_hour: u8,
+    /// Local hour the off-peak window closes (exclusive); may wrap past midnight
+    pub window_end_hour: u8,
+    /// Skip if anything printed within this many minutes (a late service still running)
+    pub quiet_minutes: u32,
+}
+
+impl Default for RediscoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: 24,
+            window_start_hour: 3,
+            window_end_hour: 6,
+            quiet_minutes: 30,
+        }
+    }
+}
+
+/// Why a scheduled rediscovery did not run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Skip {
+    Disabled,
+    OutsideWindow,
+    RanRecently,
+    JobsQueued,
+    RecentlyPrinted,
+}
+
+/// What the scheduler knows when it checks
+#[derive(Debug, Clone, Copy)]
+pub struct Conditions {
+    /// Local hour (0-23)
+    pub hour: u8,
+    /// Seconds since the last rediscovery, None if it never ran
+    pub since_last_run_secs: Option<u64>,
+    /// Pending + printing jobs
+    pub active_jobs: u64,
+    /// Seconds since the last completed print, None if nothing printed yet
+    pub since_last_print_secs: Option<u64>,
+}
+
+/// Whether `hour` falls in [start, end), wrapping past midnight when start > end
+pub fn in_window(hour: u8, start: u8, end: u8) -> bool {
+    if start <= end {
+        (start..end).contains(&hour)
+    } else {
+        hour >= start || hour < end
+    }
+}
+
+/// Guardrails: only in the off-peak window, at most once per interval, and never
+/// while jobs are queued or shortly after the last print.
+pub fn should_run(config: &RediscoveryConfig, now: &Conditions) -> Result<(), Skip> {
+    if !config.enabled {
+        return Err(Skip::Disabled);
+    }
+    if !in_window(now.hour, config.window_start_hour, config.window_end_hour) {
+        return Err(Skip::OutsideWindow);
+    }
+    if now
+        .since_last_run_secs
+        .is_some_and(|secs| secs < config.interval_hours as u64 * 3600)
+    {
+        return Err(Skip::RanRecently);
+    }
+    if now.active_jobs > 0 {
+        return Err(Skip::JobsQueued);
+    }
+    if now
+        .since_last_print_secs
+        .is_some_and(|secs| secs < config.quiet_minutes as u64 * 60)
+    {
+        return Err(Skip::RecentlyPrinted);
+    }
+    Ok(())
+}
+
+/// Configured network printers split by whether discovery still sees them at their address
+#[derive(Debug, Default, PartialEq)]
+pub struct Reconciliation {
+    /// Answering at the configured address
+    pub seen: Vec<String>,
+    /// Nothing answers at the configured address any more (likely a new DHCP lease)
+    pub drifted: Vec<String>,
+}
+
+pub fn host_of(address: &str) -> &str {
+    address.rsplit_once(':').map(|(h, _)| h).unwrap_or(address)
+}
+
+/// Compare configured network printers with a discovery run
+pub fn reconcile(configured: &[PrinterConfig], discovered: &[DiscoveredPrinter]) -> Reconciliation {
+    let mut result = Reconciliation::default();
+    for printer in configured
+        .iter()
+        .filter(|p| matches!(p.connection_type, ConnectionType::Network))
+    {
+        let host = host_of(&printer.address);
+        let found = discovered
+            .iter()
+            .any(|d| d.connection_type == "network" && host_of(&d.address) == host);
+        if found {
+            result.seen.push(printer.id.clone());
+        } else {
+            result.drifted.push(printer.id.clone());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PrinterCapabilities;
+
+    fn conditions(hour: u8) -> Conditions {
+        Conditions {
+            hour,
+            since_last_run_secs: None,
+            active_jobs: 0,
+            since_last_print_secs: Some(3 * 3600),
+        }
+    }
+
+    #[test]
+    fn test_guardrails() {
+        let config = RediscoveryConfig {
+            enabled: true,
+            window_start_hour: 23,
+            window_end_hour: 5,
+            ..Default::default()
+        };
+
+        assert_eq!(should_run(&config, &conditions(2)), Ok(()));
+        assert_eq!(should_run(&config, &conditions(23)), Ok(()));
+        assert_eq!(should_run(&config, &conditions(12)), Err(Skip::OutsideWindow));
+        assert_eq!(should_run(&config, &conditions(5)), Err(Skip::OutsideWindow));
+
+        let ran = Conditions { since_last_run_secs: Some(3600), ..conditions(2) };
+        assert_eq!(should_run(&config, &ran), Err(Skip::RanRecently));
+        let busy = Conditions { active_jobs: 1, ..conditions(2) };
+        assert_eq!(should_run(&config, &busy), Err(Skip::JobsQueued));
+        let late_service = Conditions { since_last_print_secs: Some(300), ..conditions(2) };
+        assert_eq!(should_run(&config, &late_service), Err(Skip::RecentlyPrinted));
+
+        assert_eq!(should_run(&RediscoveryConfig::default(), &conditions(4)), Err(Skip::Disabled));
+    }
+
+    #[test]
+    fn test_reconcile_flags_drifted_network_printers() {
+        let printer = |id: &str, connection_type: ConnectionType, address: &str| PrinterConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            connection_type,
+            address: address.to_string(),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: None,
+            is_primary: false,
+            capabilities: PrinterCapabilities { cutter: true, drawer: false, qrcode: true, max_width: 48 },
+            paper_width_checked: false,
+            recovery: Default::default(),
+            compact: None,
+            transports: vec![],
+            serial: None,
+            paused: false,
+        };
+        let configured = vec![
+            printer("kitchen", ConnectionType::Network, "192.168.1.50:9100"),
+            printer("bar", ConnectionType::Network, "192.168.1.51:9100"),
+            printer("usb", ConnectionType::USB, "/dev/bus/usb/001/004"),
+        ];
+        let discovered = vec![DiscoveredPrinter {
+            id: "tcp_192_168_1_50".to_string(),
+            name: "Printer at 192.168.1.50".to_string(),
+            connection_type: "network".to_string(),
+            address: "192.168.1.50:631".to_string(),
+            vendor: "Epson".to_string(),
+            capabilities: None,
+            protocol: "unknown".to_string(),
+        }];
+
+        assert_eq!(
+            reconcile(&configured, &discovered),
+            Reconciliation { seen: vec!["kitchen".to_string()], drifted: vec!["bar".to_string()] }
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use axum::http::HeaderMap;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Client id the signing secret belongs to
+pub const CLIENT_HEADER: &str = "x-eatsome-client";
+/// Unix seconds when the client signed the request
+pub const TIMESTAMP_HEADER: &str = "x-eatsome-timestamp";
+/// Random value, unique per request of a client
+pub const NONCE_HEADER: &str = "x-eatsome-nonce";
+/// Hex HMAC-SHA256 of `"{timestamp}.{nonce}."` followed by the raw body
+pub const SIGNATURE_HEADER: &str = "x-eatsome-signature";
+/// Same key on a retry: the first request's job is returned instead of printing again
+pub const IDEMPOTENCY_HEADER: &str = "idempotency-key";
+
+/// Longest nonce / idempotency key accepted (they are kept in memory and in the queue)
+const MAX_TOKEN_LEN: usize = 128;
+
+/// Request signing for POST /api/print (on top of the JWT). Off until clients are added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiSigningConfig {
+    /// Reject unsigned print requests. Off: unsigned requests still pass on the JWT alone
+    /// (so POS clients can be moved over one by one); signed ones are always verified.
+    pub required: bool,
+    pub clients: Vec<ApiClient>,
+    /// How far a request's timestamp may be from the daemon clock, either way
+    pub max_skew_secs: u64,
+}
+
+impl Default for ApiSigningConfig {
+    fn default() -> Self {
+        Self {
+            required: false,
+            clients: Vec::new(),
+            max_skew_secs: 300,
+        }
+    }
+}
+
+/// A POS client with its own signing secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiClient {
+    /// Sent in `x-eatsome-client`
+    pub id: String,
+    pub name: String,
+    pub secret: String,
+}
+
+/// Why a print request's signature was refused
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SigningError {
+    #[error("request is not signed")]
+    Unsigned,
+
+    #[error("missing or malformed {0} header")]
+    BadHeader(&'static str),
+
+    #[error("unknown client {0:?}")]
+    UnknownClient(String),
+
+    #[error("client {0:?} has no signing secret")]
+    NoSecret(String),
+
+    #[error("timestamp is {0}s away from the daemon clock")]
+    Stale(i64),
+
+    #[error("signature does not match")]
+    BadSignature,
+
+    #[error("nonce was already used")]
+    Replayed,
+}
+
+/// A request whose signature checked out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedRequest {
+    pub client_id: String,
+    pub nonce: String,
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &'static str) -> Option<&'a str> {
+    headers.get(name).and_then(|h| h.to_str().ok()).map(str::trim)
+}
+
+/// A nonce or idempotency key: printable ASCII, bounded length
+fn token(headers: &HeaderMap, name: &'static str) -> Result<Option<String>, SigningError> {
+    match header(headers, name) {
+        None => Ok(None),
+        Some(value) if !value.is_empty() && value.len() <= MAX_TOKEN_LEN && value.bytes().all(|b| b.is_ascii_graphic()) => {
+            Ok(Some(value.to_string()))
+        }
+        Some(_) => Err(SigningError::BadHeader(name)),
+    }
+}
+
+/// HMAC-SHA256 of a request, hex
+pub fn sign(secret: &str, timestamp: i64, nonce: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(format!("{}.{}.", timestamp, nonce).as_bytes());
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Check a print request's signature and timestamp. `Ok(None)` for an unsigned request
+/// when signing isn't required. The nonce is not checked here (see `NonceCache`), so an
+/// idempotent retry can be answered before it counts as a replay.
+pub fn verify(
+    config: &ApiSigningConfig,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: i64,
+) -> Result<Option<SignedRequest>, SigningError> {
+    let Some(signature) = header(headers, SIGNATURE_HEADER) else {
+        return if config.required { Err(SigningError::Unsigned) } else { Ok(None) };
+    };
+
+    let client_id = header(headers, CLIENT_HEADER).ok_or(SigningError::BadHeader(CLIENT_HEADER))?;
+    let client = config
+        .clients
+        .iter()
+        .find(|c| c.id == client_id)
+        .ok_or_else(|| SigningError::UnknownClient(client_id.to_string()))?;
+    // Anyone can compute the HMAC under an empty key
+    if client.secret.trim().is_empty() {
+        return Err(SigningError::NoSecret(client.id.clone()));
+    }
+    let timestamp: i64 = header(headers, TIMESTAMP_HEADER)
+        .and_then(|t| t.parse().ok())
+        .ok_or(SigningError::BadHeader(TIMESTAMP_HEADER))?;
+    let nonce = token(headers, NONCE_HEADER)?.ok_or(SigningError::BadHeader(NONCE_HEADER))?;
+
+    // The timestamp is the client's: saturate rather than overflow on extreme values
+    let skew = now.saturating_sub(timestamp);
+    if skew.unsigned_abs() > config.max_skew_secs {
+        return Err(SigningError::Stale(skew));
+    }
+
+    let signature = hex::decode(signature).map_err(|_| SigningError::BadHeader(SIGNATURE_HEADER))?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(client.secret.as_bytes()).map_err(|_| SigningError::BadSignature)?;
+    mac.update(format!("{}.{}.", timestamp, nonce).as_bytes());
+    mac.update(body);
+    mac.verify_slice(&signature).map_err(|_| SigningError::BadSignature)?;
+
+    Ok(Some(SignedRequest {
+        client_id: client.id.clone(),
+        nonce,
+    }))
+}
+
+/// Idempotency key of a request, scoped to the signing client (`"-"` for unsigned
+/// requests) so two POS clients can't collide
+pub fn idempotency_key(headers: &HeaderMap, signed: Option<&SignedRequest>) -> Result<Option<String>, SigningError> {
+    let client = signed.map(|s| s.client_id.as_str()).unwrap_or("-");
+    Ok(token(headers, IDEMPOTENCY_HEADER)?.map(|key| format!("{}:{}", client, key)))
+}
+
+/// SHA-256 of a request body, hex: a reused idempotency key must come with the same body
+pub fn body_hash(body: &[u8]) -> String {
+    hex::encode(Sha256::digest(body))
+}
+
+/// Nonces seen within the timestamp window. Older ones can go: their requests are
+/// refused as stale anyway.
+#[derive(Debug, Default)]
+pub struct NonceCache {
+    /// (client, nonce) → Unix seconds after which it may be forgotten
+    seen: HashMap<(String, String), i64>,
+}
+
+impl NonceCache {
+    /// Record a request's nonce; false if it was used before
+    pub fn check(&mut self, request: &SignedRequest, now: i64, max_skew_secs: u64) -> bool {
+        self.seen.retain(|_, expires| *expires >= now);
+        let key = (request.client_id.clone(), request.nonce.clone());
+        if self.seen.contains_key(&key) {
+            return false;
+        }
+        // A timestamp up to max_skew ahead stays valid until 2 × max_skew from now
+        self.seen.insert(key, now + 2 * max_skew_secs as i64);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn config(required: bool) -> ApiSigningConfig {
+        ApiSigningConfig {
+            required,
+            clients: vec![ApiClient {
+                id: "pos-1".to_string(),
+                name: "Counter".to_string(),
+                secret: "s3cret".to_string(),
+            }],
+            ..ApiSigningConfig::default()
+        }
+    }
+
+    fn signed_headers(secret: &str, timestamp: i64, nonce: &str, body: &[u8]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CLIENT_HEADER, HeaderValue::from_static("pos-1"));
+        headers.insert(TIMESTAMP_HEADER, HeaderValue::from_str(&timestamp.to_string()).unwrap());
+        headers.insert(NONCE_HEADER, HeaderValue::from_str(nonce).unwrap());
+        headers.insert(SIGNATURE_HEADER, HeaderValue::from_str(&sign(secret, timestamp, nonce, body)).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_signature_timestamp_and_nonce() {
+        let body = br#"{"order_number":"42"}"#;
+        let now = 1_700_000_000;
+
+        assert_eq!(verify(&config(false), &HeaderMap::new(), body, now), Ok(None));
+        assert_eq!(verify(&config(true), &HeaderMap::new(), body, now), Err(SigningError::Unsigned));
+
+        let headers = signed_headers("s3cret", now - 10, "n-1", body);
+        let signed = verify(&config(true), &headers, body, now).unwrap().unwrap();
+        assert_eq!(signed.client_id, "pos-1");
+        assert_eq!(
+            verify(&config(true), &headers, br#"{"order_number":"43"}"#, now),
+            Err(SigningError::BadSignature)
+        );
+        assert_eq!(
+            verify(&config(true), &signed_headers("wrong", now, "n-2", body), body, now),
+            Err(SigningError::BadSignature)
+        );
+        assert_eq!(verify(&config(true), &headers, body, now + 400), Err(SigningError::Stale(410)));
+        let extreme = signed_headers("s3cret", i64::MIN, "n-3", body);
+        assert_eq!(verify(&config(true), &extreme, body, now), Err(SigningError::Stale(i64::MAX)));
+
+        let mut no_secret = config(true);
+        no_secret.clients[0].secret = String::new();
+        assert_eq!(
+            verify(&no_secret, &signed_headers("", now, "n-4", body), body, now),
+            Err(SigningError::NoSecret("pos-1".to_string()))
+        );
+
+        let mut nonces = NonceCache::default();
+        assert!(nonces.check(&signed, now, 300));
+        assert!(!nonces.check(&signed, now + 5, 300));
+        // Forgotten once its timestamp can no longer pass
+        assert!(nonces.check(&signed, now + 601, 300));
+    }
+
+    #[test]
+    fn test_idempotency_key_scoped_per_client() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(idempotency_key(&headers, None), Ok(None));
+        headers.insert(IDEMPOTENCY_HEADER, HeaderValue::from_static("order-42-try"));
+        assert_eq!(idempotency_key(&headers, None).unwrap().as_deref(), Some("-:order-42-try"));
+        let signed = SignedRequest { client_id: "pos-1".to_string(), nonce: "n".to_string() };
+        assert_eq!(idempotency_key(&headers, Some(&signed)).unwrap().as_deref(), Some("pos-1:order-42-try"));
+
+        headers.insert(IDEMPOTENCY_HEADER, HeaderValue::from_static("has space"));
+        assert_eq!(idempotency_key(&headers, None), Err(SigningError::BadHeader(IDEMPOTENCY_HEADER)));
+    }
+}
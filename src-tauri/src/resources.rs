@@ -0,0 +1,240 @@
+use crate::low_resource::{self, CpuTimes};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// How often the daemon samples its own resource use
+pub const SAMPLE_SECS: u64 = 60;
+
+/// Latest sample, for the job poller (heartbeats) and status reporting
+static LATEST: RwLock<Option<ResourceUsage>> = RwLock::new(None);
+
+/// The daemon's own resource use at one point in time. Fields the platform can't
+/// report are None (macOS: CPU and RSS).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// Share of the whole machine (all cores) used by the daemon since the last sample
+    pub cpu_percent: Option<f32>,
+    /// Resident memory
+    pub rss_bytes: Option<u64>,
+    /// Open file descriptors (Windows: handles)
+    pub open_fds: Option<u64>,
+    /// Alive tokio tasks (background loops + in-flight jobs)
+    pub tokio_tasks: usize,
+    /// Unix seconds
+    pub sampled_at: i64,
+}
+
+/// Latest sample taken by `ResourceMonitor`
+pub fn latest() -> Option<ResourceUsage> {
+    LATEST.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Samples the daemon's CPU, memory, descriptors and tasks. CPU use needs two samples,
+/// so the first one reports None.
+#[derive(Default)]
+pub struct ResourceMonitor {
+    /// Process CPU time and machine CPU times at the previous sample (same units)
+    prev: Option<(u64, CpuTimes)>,
+}
+
+impl ResourceMonitor {
+    pub fn sample(&mut self, now_secs: i64) -> ResourceUsage {
+        let cpu = read_process_cpu().zip(low_resource::read_cpu_times());
+        let cpu_percent = match (self.prev, cpu) {
+            (Some((prev_proc, prev_machine)), Some((proc, machine))) => {
+                process_percent(prev_proc, proc, prev_machine, machine)
+            }
+            _ => None,
+        };
+        self.prev = cpu;
+
+        let usage = ResourceUsage {
+            cpu_percent,
+            rss_bytes: read_rss_bytes(),
+            open_fds: count_open_fds(),
+            tokio_tasks: tokio::runtime::Handle::try_current()
+                .map(|h| h.metrics().num_alive_tasks())
+                .unwrap_or(0),
+            sampled_at: now_secs,
+        };
+        *LATEST.write().unwrap_or_else(|e| e.into_inner()) = Some(usage.clone());
+        usage
+    }
+}
+
+/// Process CPU use (%) of the whole machine between two samples
+pub fn process_percent(prev_proc: u64, proc: u64, prev: CpuTimes, now: CpuTimes) -> Option<f32> {
+    let total = now.total.checked_sub(prev.total).filter(|t| *t > 0)?;
+    let used = proc.saturating_sub(prev_proc).min(total);
+    Some(used as f32 * 100.0 / total as f32)
+}
+
+/// utime + stime (clock ticks, same unit as /proc/stat) from /proc/self/stat
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn parse_self_stat(stat: &str) -> Option<u64> {
+    // The command name is in parentheses and may contain spaces
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    // Fields 14 and 15 of the line; the first after the name is field 3
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// `VmRSS:` from /proc/self/status, in bytes
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn parse_vm_rss(status: &str) -> Option<u64> {
+    let kb: u64 = status
+        .lines()
+        .find_map(|l| l.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(target_os = "linux")]
+fn read_process_cpu() -> Option<u64> {
+    parse_self_stat(&std::fs::read_to_string("/proc/self/stat").ok()?)
+}
+
+#[cfg(target_os = "linux")]
+fn read_rss_bytes() -> Option<u64> {
+    parse_vm_rss(&std::fs::read_to_string("/proc/self/status").ok()?)
+}
+
+#[cfg(unix)]
+fn count_open_fds() -> Option<u64> {
+    let dir = if cfg!(target_os = "linux") { "/proc/self/fd" } else { "/dev/fd" };
+    // Reading the directory opens one descriptor itself
+    let entries = std::fs::read_dir(dir).ok()?.count() as u64;
+    Some(entries.saturating_sub(1))
+}
+
+#[cfg(windows)]
+mod win {
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct FileTime {
+        pub low: u32,
+        pub high: u32,
+    }
+
+    impl FileTime {
+        /// 100ns units, same as `GetSystemTimes`
+        pub fn ticks(&self) -> u64 {
+            ((self.high as u64) << 32) | self.low as u64
+        }
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct ProcessMemoryCounters {
+        pub cb: u32,
+        pub page_fault_count: u32,
+        pub peak_working_set_size: usize,
+        pub working_set_size: usize,
+        pub quota_peak_paged_pool_usage: usize,
+        pub quota_paged_pool_usage: usize,
+        pub quota_peak_non_paged_pool_usage: usize,
+        pub quota_non_paged_pool_usage: usize,
+        pub pagefile_usage: usize,
+        pub peak_pagefile_usage: usize,
+    }
+
+    pub type Handle = *mut std::ffi::c_void;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        pub fn GetCurrentProcess() -> Handle;
+        pub fn GetProcessTimes(
+            process: Handle,
+            creation: *mut FileTime,
+            exit: *mut FileTime,
+            kernel: *mut FileTime,
+            user: *mut FileTime,
+        ) -> i32;
+        pub fn K32GetProcessMemoryInfo(process: Handle, counters: *mut ProcessMemoryCounters, cb: u32) -> i32;
+        pub fn GetProcessHandleCount(process: Handle, count: *mut u32) -> i32;
+    }
+}
+
+#[cfg(windows)]
+fn read_process_cpu() -> Option<u64> {
+    let mut times: [win::FileTime; 4] = Default::default();
+    let [creation, exit, kernel, user] = &mut times;
+    // SAFETY: pseudo handle of this process; the call only writes the structs we pass
+    let ok = unsafe { win::GetProcessTimes(win::GetCurrentProcess(), creation, exit, kernel, user) };
+    (ok != 0).then(|| kernel.ticks() + user.ticks())
+}
+
+#[cfg(windows)]
+fn read_rss_bytes() -> Option<u64> {
+    let mut counters = win::ProcessMemoryCounters {
+        cb: std::mem::size_of::<win::ProcessMemoryCounters>() as u32,
+        ..Default::default()
+    };
+    // SAFETY: `cb` is the size of the struct being written
+    let ok = unsafe { win::K32GetProcessMemoryInfo(win::GetCurrentProcess(), &mut counters, counters.cb) };
+    (ok != 0).then_some(counters.working_set_size as u64)
+}
+
+#[cfg(windows)]
+fn count_open_fds() -> Option<u64> {
+    let mut count = 0u32;
+    // SAFETY: only writes `count`
+    let ok = unsafe { win::GetProcessHandleCount(win::GetCurrentProcess(), &mut count) };
+    (ok != 0).then_some(count as u64)
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn read_process_cpu() -> Option<u64> {
+    None
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn read_rss_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg(not(any(unix, windows)))]
+fn count_open_fds() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_files() {
+        let stat = "4242 (tokio worker) S 1 4242 4242 0 -1 4194560 1500 0 0 0 120 30 0 0 20 0 12 0";
+        assert_eq!(parse_self_stat(stat), Some(150));
+        assert_eq!(parse_self_stat("garbage"), None);
+
+        let status = "Name:\tdaemon\nVmPeak:\t  900000 kB\nVmRSS:\t   52340 kB\nThreads:\t12\n";
+        assert_eq!(parse_vm_rss(status), Some(52340 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tdaemon\n"), None);
+
+        let prev = CpuTimes { busy: 0, total: 1000 };
+        let now = CpuTimes { busy: 0, total: 1400 };
+        assert_eq!(process_percent(100, 140, prev, now), Some(10.0));
+        assert_eq!(process_percent(100, 140, prev, prev), None);
+    }
+
+    #[tokio::test]
+    async fn test_sample_reports_tasks_and_cpu_after_second_sample() {
+        let mut monitor = ResourceMonitor::default();
+        let first = monitor.sample(100);
+        assert_eq!(first.cpu_percent, None);
+        assert!(first.tokio_tasks < 1000);
+
+        let second = monitor.sample(160);
+        assert_eq!(latest(), Some(second.clone()));
+        if cfg!(target_os = "linux") {
+            assert!(second.rss_bytes.unwrap() > 0);
+            assert!(second.open_fds.unwrap() > 0);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use crate::errors::Result;
+use crate::queue::QueueManager;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tracing::{info, warn};
+
+/// Restarts and crashes within this window are reported with heartbeats
+pub const SUMMARY_WINDOW_SECS: i64 = 7 * 24 * 3600;
+
+/// Marker file of the running daemon, next to the queue database
+const MARKER_FILE: &str = "daemon.running";
+
+/// Marker of this run, set by `record_start`
+static MARKER: RwLock<Option<(PathBuf, RunMarker)>> = RwLock::new(None);
+
+/// Summary for heartbeats, set by `record_start`
+static SUMMARY: RwLock<Option<RestartSummary>> = RwLock::new(None);
+
+/// Why a daemon run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// Quit from the tray menu
+    Clean,
+    /// Restarted to install an update
+    Update,
+    /// The process went away without stopping (crash, kill, power loss): found by the
+    /// marker file it left behind
+    Crash,
+}
+
+impl StopReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StopReason::Clean => "clean",
+            StopReason::Update => "update",
+            StopReason::Crash => "crash",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "clean" => Some(StopReason::Clean),
+            "update" => Some(StopReason::Update),
+            "crash" => Some(StopReason::Crash),
+            _ => None,
+        }
+    }
+}
+
+/// One daemon run in the restart history (`get_restart_history`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DaemonRun {
+    pub id: i64,
+    pub version: String,
+    /// Unix seconds
+    pub started_at: i64,
+    /// Crashes: when the daemon was last seen alive (within a minute of the crash)
+    pub stopped_at: Option<i64>,
+    /// None while running
+    pub stop_reason: Option<StopReason>,
+}
+
+/// Restart counts sent with every heartbeat
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RestartSummary {
+    pub started_at: i64,
+    pub version: String,
+    /// How the previous run ended (None on the first run)
+    pub previous_stop: Option<StopReason>,
+    /// Within `SUMMARY_WINDOW_SECS`, this start included
+    pub restarts: u32,
+    pub crashes: u32,
+}
+
+/// Contents of the marker file. It exists while the daemon runs; a stop reason is written
+/// into it on the way out, so a marker without one means the run crashed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RunMarker {
+    run_id: i64,
+    /// Refreshed every minute (`touch`)
+    alive_at: i64,
+    #[serde(default)]
+    stop: Option<StopReason>,
+}
+
+/// Close the previous run (crashed if its marker has no stop reason), record this one
+/// and leave a marker for it
+pub async fn record_start(queue: &QueueManager, dir: &Path, now: i64) -> Result<RestartSummary> {
+    let path = dir.join(MARKER_FILE);
+    let previous = read_marker(&path);
+
+    let previous_stop = match previous {
+        Some(marker) => {
+            let reason = marker.stop.unwrap_or(StopReason::Crash);
+            if reason == StopReason::Crash {
+                warn!(
+                    "Previous run did not shut down cleanly (last seen {})",
+                    chrono::DateTime::from_timestamp(marker.alive_at, 0)
+                        .map(|t| t.to_rfc3339())
+                        .unwrap_or_default()
+                );
+            }
+            queue.finish_daemon_run(marker.run_id, marker.alive_at, reason).await?;
+            Some(reason)
+        }
+        None => None,
+    };
+
+    let version = env!("CARGO_PKG_VERSION").to_string();
+    let run_id = queue.start_daemon_run(&version, now).await?;
+    let marker = RunMarker { run_id, alive_at: now, stop: None };
+    write_marker(&path, &marker);
+    *MARKER.write().unwrap_or_else(|e| e.into_inner()) = Some((path, marker));
+
+    let (restarts, crashes) = queue.restart_counts(now - SUMMARY_WINDOW_SECS).await?;
+    let summary = RestartSummary { started_at: now, version, previous_stop, restarts, crashes };
+    info!(
+        "Run {} started (previous stop: {}, {} restarts / {} crashes this week)",
+        run_id,
+        previous_stop.map(StopReason::as_str).unwrap_or("none"),
+        restarts,
+        crashes
+    );
+    *SUMMARY.write().unwrap_or_else(|e| e.into_inner()) = Some(summary.clone());
+    Ok(summary)
+}
+
+/// Latest summary, for heartbeats
+pub fn summary() -> Option<RestartSummary> {
+    SUMMARY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Refresh the marker's "last seen alive", the crash time of a run that never stops
+pub fn touch(now: i64) {
+    update_marker(|marker| marker.alive_at = now);
+}
+
+/// Record why the daemon is about to exit. Call right before exiting; a run that exits
+/// without this is reported as a crash on the next start.
+pub fn mark_stopping(reason: StopReason, now: i64) {
+    info!("Daemon stopping ({})", reason.as_str());
+    update_marker(|marker| {
+        marker.alive_at = now;
+        marker.stop = Some(reason);
+    });
+}
+
+fn update_marker(change: impl FnOnce(&mut RunMarker)) {
+    let mut guard = MARKER.write().unwrap_or_else(|e| e.into_inner());
+    if let Some((path, marker)) = guard.as_mut() {
+        change(marker);
+        write_marker(path, marker);
+    }
+}
+
+fn read_marker(path: &Path) -> Option<RunMarker> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(marker) => Some(marker),
+        Err(e) => {
+            warn!("Ignoring unreadable run marker {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn write_marker(path: &Path, marker: &RunMarker) {
+    let result = serde_json::to_vec(marker)
+        .map_err(std::io::Error::from)
+        .and_then(|bytes| std::fs::write(path, bytes));
+    if let Err(e) = result {
+        warn!("Failed to write run marker {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_crash_detected_from_leftover_marker() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+
+        let first = record_start(&queue, dir.path(), 1_000).await.unwrap();
+        assert_eq!((first.previous_stop, first.restarts, first.crashes), (None, 1, 0));
+
+        // Exit without mark_stopping: the marker is left behind
+        touch(1_060);
+        let second = record_start(&queue, dir.path(), 2_000).await.unwrap();
+        assert_eq!((second.previous_stop, second.restarts, second.crashes), (Some(StopReason::Crash), 2, 1));
+
+        mark_stopping(StopReason::Update, 2_500);
+        let third = record_start(&queue, dir.path(), 3_000).await.unwrap();
+        assert_eq!(third.previous_stop, Some(StopReason::Update));
+
+        let runs = queue.daemon_runs(10).await.unwrap();
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0].stop_reason, None);
+        assert_eq!((runs[1].stopped_at, runs[1].stop_reason), (Some(2_500), Some(StopReason::Update)));
+        assert_eq!((runs[2].stopped_at, runs[2].stop_reason), (Some(1_060), Some(StopReason::Crash)));
+    }
+}
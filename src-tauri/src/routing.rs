@@ -0,0 +1,237 @@
+use crate::config::PrinterConfig;
+use crate::stations::normalize;
+use serde::{Deserialize, Serialize};
+
+/// Where jobs without a `printer_id` print (`AppConfig.routing`).
+///
+/// A station goes to, in order: an exact `overrides` entry, a printer configured for the
+/// station, the first matching wildcard in `rules`, `default_printer_id`, the primary
+/// printer. Entries naming a printer that no longer exists are passed over, and so are
+/// paused printers while anything else fits.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoutingConfig {
+    /// Station → printer id, ahead of the printers' own stations
+    pub overrides: Vec<RoutingRule>,
+    /// Station patterns (`*` any characters, `?` one): `bar-*`, `*dessert*`
+    pub rules: Vec<RoutingRule>,
+    /// Printer for stations nothing else matches; the primary printer when unset
+    pub default_printer_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutingRule {
+    pub station: String,
+    pub printer_id: String,
+}
+
+/// Why a station went to its printer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "pattern", rename_all = "snake_case")]
+pub enum RouteReason {
+    Override,
+    PrinterStation,
+    Rule(String),
+    Default,
+    Primary,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Route {
+    pub printer_id: String,
+    pub reason: RouteReason,
+}
+
+impl RoutingConfig {
+    /// Check the entries before saving: a pattern and an existing printer each
+    pub fn validate(&self, printers: &[PrinterConfig]) -> Result<(), String> {
+        let known = |id: &str| printers.iter().any(|p| p.id == id);
+        for rule in self.overrides.iter().chain(&self.rules) {
+            if normalize(&rule.station).is_empty() {
+                return Err(format!("Rule for printer {} has no station", rule.printer_id));
+            }
+            if !known(&rule.printer_id) {
+                return Err(format!("Station {} routes to unknown printer {}", rule.station.trim(), rule.printer_id));
+            }
+        }
+        if let Some(id) = self.default_printer_id.as_deref().filter(|id| !known(id)) {
+            return Err(format!("Default printer {} doesn't exist", id));
+        }
+        Ok(())
+    }
+}
+
+/// Routing over the current printers
+pub struct Router<'a> {
+    config: &'a RoutingConfig,
+    printers: &'a [PrinterConfig],
+}
+
+impl<'a> Router<'a> {
+    pub fn new(config: &'a RoutingConfig, printers: &'a [PrinterConfig]) -> Self {
+        Self { config, printers }
+    }
+
+    /// Printer for a station; None only without any printer to fall back to. Paused
+    /// printers are passed over; with only paused ones left the job still goes to one of
+    /// them, to wait there until it resumes.
+    pub fn route(&self, station: &str) -> Option<Route> {
+        self.station_route(station, true)
+            .or_else(|| self.fallback_route(true))
+            .or_else(|| self.route_station(station))
+            .or_else(|| self.fallback())
+    }
+
+    /// Printer chosen for the station itself (overrides, printer stations, wildcard rules),
+    /// without the default and primary fallbacks
+    pub fn route_station(&self, station: &str) -> Option<Route> {
+        self.station_route(station, false)
+    }
+
+    /// The default printer, else the primary one
+    pub fn fallback(&self) -> Option<Route> {
+        self.fallback_route(false)
+    }
+
+    fn usable(&self, printer: &PrinterConfig, skip_paused: bool) -> bool {
+        !(skip_paused && printer.paused)
+    }
+
+    fn station_route(&self, station: &str, skip_paused: bool) -> Option<Route> {
+        let station = normalize(station);
+        let exists = |id: &str| self.printers.iter().any(|p| p.id == id && self.usable(p, skip_paused));
+        let route = |printer_id: &str, reason| Some(Route { printer_id: printer_id.to_string(), reason });
+
+        if let Some(rule) = self
+            .config
+            .overrides
+            .iter()
+            .find(|r| normalize(&r.station) == station && exists(&r.printer_id))
+        {
+            return route(&rule.printer_id, RouteReason::Override);
+        }
+        if let Some(printer) = self
+            .printers
+            .iter()
+            .find(|p| self.usable(p, skip_paused) && p.station.as_deref().is_some_and(|s| normalize(s) == station))
+        {
+            return route(&printer.id, RouteReason::PrinterStation);
+        }
+        self.config
+            .rules
+            .iter()
+            .find(|r| exists(&r.printer_id) && glob_match(&normalize(&r.station), &station))
+            .and_then(|r| route(&r.printer_id, RouteReason::Rule(r.station.trim().to_string())))
+    }
+
+    fn fallback_route(&self, skip_paused: bool) -> Option<Route> {
+        if let Some(id) = self.config.default_printer_id.as_deref() {
+            if self.printers.iter().any(|p| p.id == id && self.usable(p, skip_paused)) {
+                return Some(Route { printer_id: id.to_string(), reason: RouteReason::Default });
+            }
+        }
+        self.printers
+            .iter()
+            .find(|p| p.is_primary && self.usable(p, skip_paused))
+            .map(|p| Route { printer_id: p.id.clone(), reason: RouteReason::Primary })
+    }
+}
+
+/// `*` matches any run of characters, `?` exactly one; everything else literally
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Last `*` seen and the text position it was tried at, for backtracking
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConnectionType, PrinterCapabilities};
+
+    fn printer(id: &str, station: Option<&str>, is_primary: bool) -> PrinterConfig {
+        PrinterConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            connection_type: ConnectionType::Network,
+            address: "192.168.1.50:9100".to_string(),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: station.map(str::to_string),
+            is_primary,
+            capabilities: PrinterCapabilities { cutter: true, drawer: false, qrcode: true, max_width: 48 },
+            paper_width_checked: true,
+            recovery: Default::default(),
+            compact: None,
+            transports: vec![],
+            serial: None,
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn test_routing_order() {
+        let printers = vec![printer("kitchen", Some("Kitchen"), true), printer("bar", Some("bar"), false), printer("pass", None, false)];
+        let rule = |station: &str, printer_id: &str| RoutingRule { station: station.to_string(), printer_id: printer_id.to_string() };
+        let mut config = RoutingConfig {
+            overrides: vec![rule("bar", "pass"), rule("grill", "gone")],
+            rules: vec![rule("bar-*", "bar"), rule("*dessert*", "pass"), rule("grill", "kitchen")],
+            default_printer_id: None,
+        };
+        let router = Router::new(&config, &printers);
+        let routed = |station: &str| router.route(station).map(|r| (r.printer_id, r.reason));
+
+        assert_eq!(routed("BAR"), Some(("pass".to_string(), RouteReason::Override)));
+        assert_eq!(routed(" kitchen"), Some(("kitchen".to_string(), RouteReason::PrinterStation)));
+        assert_eq!(routed("bar-terrace"), Some(("bar".to_string(), RouteReason::Rule("bar-*".to_string()))));
+        assert_eq!(routed("cold dessert bar").unwrap().0, "pass");
+        // Override to a removed printer: the next match applies
+        assert_eq!(routed("grill").unwrap().1, RouteReason::Rule("grill".to_string()));
+        assert_eq!(routed("sushi"), Some(("kitchen".to_string(), RouteReason::Primary)));
+        assert!(config.validate(&printers).is_err());
+
+        config.overrides.pop();
+        config.default_printer_id = Some("pass".to_string());
+        assert_eq!(config.validate(&printers), Ok(()));
+        assert_eq!(Router::new(&config, &printers).route("sushi").unwrap().reason, RouteReason::Default);
+        assert_eq!(Router::new(&config, &[]).route("bar"), None);
+
+        // Paused printers are passed over while another fits, else the job waits for one
+        let mut printers = printers;
+        printers[1].paused = true;
+        let router = Router::new(&config, &printers);
+        assert_eq!(router.route("bar-terrace").unwrap(), Route { printer_id: "pass".to_string(), reason: RouteReason::Default });
+        printers[2].paused = true;
+        printers[0].paused = true;
+        let router = Router::new(&config, &printers);
+        assert_eq!(router.route("bar-terrace").unwrap().printer_id, "bar");
+        assert_eq!(router.route("sushi").unwrap().printer_id, "pass");
+
+        assert!(glob_match("a*b?c", "axxbyc"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("a*b", "ab-c"));
+    }
+}
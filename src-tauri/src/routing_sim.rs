@@ -0,0 +1,270 @@
+use crate::config::PrinterConfig;
+use crate::queue::JobHistoryEntry;
+use crate::routing::{Router, RoutingConfig};
+use crate::stations::StationMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Recent jobs replayed when the caller doesn't say how many
+pub const DEFAULT_SAMPLE: usize = 50;
+/// Most jobs one simulation replays
+pub const MAX_SAMPLE: usize = 500;
+
+/// A routing change to try out (`simulate_routing`). Anything left out keeps its live
+/// value; nothing here is ever written to the config.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RoutingScenario {
+    /// Printer id → station. `null` or `""` takes the printer off its station; printers
+    /// not listed keep theirs.
+    pub printer_stations: HashMap<String, Option<String>>,
+    /// Replaces `AppConfig.station_aliases`
+    pub station_aliases: Option<BTreeMap<String, String>>,
+    /// Replaces `AppConfig.routing`
+    pub routing: Option<RoutingConfig>,
+    /// Replaces the failover map (primary → backups, in order)
+    pub failover: Option<HashMap<String, Vec<String>>>,
+    /// Printers to treat as down, on top of the ones down right now
+    pub assume_down: Vec<String>,
+}
+
+/// How a replayed job would have come out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulatedOutcome {
+    /// Printed on the printer it was routed to
+    Primary,
+    /// Routed printer down, printed on a backup
+    Failover,
+    /// Routed printer and all its backups down
+    Failed,
+    /// No printer for the station, no recorded printer and no primary printer
+    Unrouted,
+}
+
+/// One replayed job
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedJob {
+    pub job_id: String,
+    pub order_number: String,
+    pub station: String,
+    /// False if the scenario's stations and aliases don't know the station
+    pub station_matched: bool,
+    /// Printer the job was routed to live
+    pub live_printer_id: Option<String>,
+    /// Printer the scenario routes it to (before failover)
+    pub routed_printer_id: Option<String>,
+    /// Printer it would have printed on
+    pub printer_id: Option<String>,
+    pub outcome: SimulatedOutcome,
+    /// `printer_id` differs from `live_printer_id`
+    pub changed: bool,
+}
+
+/// Result of `simulate_routing`
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutingSimulation {
+    pub jobs: Vec<SimulatedJob>,
+    /// Jobs per printer they would have printed on
+    pub per_printer: BTreeMap<String, u32>,
+    pub changed: u32,
+    pub failovers: u32,
+    pub failed: u32,
+    pub unrouted: u32,
+    /// Printers treated as down (live and assumed)
+    pub down: Vec<String>,
+}
+
+/// The routing a scenario describes, built from the live config
+pub struct Routing {
+    printers: Vec<PrinterConfig>,
+    stations: StationMap,
+    routing: RoutingConfig,
+    failover: HashMap<String, Vec<String>>,
+}
+
+impl Routing {
+    pub fn new(
+        scenario: &RoutingScenario,
+        printers: &[PrinterConfig],
+        aliases: &BTreeMap<String, String>,
+        routing: &RoutingConfig,
+        failover: &HashMap<String, Vec<String>>,
+    ) -> Self {
+        let printers: Vec<PrinterConfig> = printers
+            .iter()
+            .cloned()
+            .map(|mut printer| {
+                if let Some(station) = scenario.printer_stations.get(&printer.id) {
+                    printer.station = station.clone().filter(|s| !s.trim().is_empty());
+                }
+                printer
+            })
+            .collect();
+        let aliases = scenario.station_aliases.as_ref().unwrap_or(aliases);
+        Self {
+            stations: StationMap::new(&printers, aliases),
+            routing: scenario.routing.clone().unwrap_or_else(|| routing.clone()),
+            printers,
+            failover: scenario.failover.clone().unwrap_or_else(|| failover.clone()),
+        }
+    }
+
+    /// Route a job with the routing engine (`routing.rs`): what the station routes to,
+    /// else the printer upstream assigned (if it still exists), else the default or
+    /// primary printer
+    fn route(&self, station: &str, live_printer_id: Option<&str>) -> (Option<String>, bool) {
+        let resolved = self.stations.resolve(station);
+        let router = Router::new(&self.routing, &self.printers);
+        let printer = router
+            .route_station(&resolved.station)
+            .map(|route| route.printer_id)
+            .or_else(|| live_printer_id.filter(|id| self.printers.iter().any(|p| p.id == *id)).map(str::to_string))
+            .or_else(|| router.fallback().map(|route| route.printer_id));
+        (printer, resolved.matched)
+    }
+
+    /// Replay `jobs` against this routing with `down` printers unavailable
+    pub fn simulate(&self, jobs: &[JobHistoryEntry], down: &HashSet<String>) -> RoutingSimulation {
+        let mut simulation = RoutingSimulation {
+            jobs: Vec::with_capacity(jobs.len()),
+            per_printer: BTreeMap::new(),
+            changed: 0,
+            failovers: 0,
+            failed: 0,
+            unrouted: 0,
+            down: down.iter().cloned().collect(),
+        };
+        simulation.down.sort();
+
+        for job in jobs {
+            let (routed, station_matched) = self.route(&job.station, job.printer_id.as_deref());
+            let (printer_id, outcome) = match &routed {
+                None => (None, SimulatedOutcome::Unrouted),
+                Some(id) if !down.contains(id) => (Some(id.clone()), SimulatedOutcome::Primary),
+                Some(id) => {
+                    let backup = self
+                        .failover
+                        .get(id)
+                        .and_then(|backups| backups.iter().find(|b| !down.contains(*b)));
+                    match backup {
+                        Some(backup) => (Some(backup.clone()), SimulatedOutcome::Failover),
+                        None => (None, SimulatedOutcome::Failed),
+                    }
+                }
+            };
+
+            match outcome {
+                SimulatedOutcome::Failover => simulation.failovers += 1,
+                SimulatedOutcome::Failed => simulation.failed += 1,
+                SimulatedOutcome::Unrouted => simulation.unrouted += 1,
+                SimulatedOutcome::Primary => {}
+            }
+            if let Some(id) = &printer_id {
+                *simulation.per_printer.entry(id.clone()).or_insert(0) += 1;
+            }
+            let changed = printer_id != job.printer_id;
+            if changed {
+                simulation.changed += 1;
+            }
+
+            simulation.jobs.push(SimulatedJob {
+                job_id: job.id.clone(),
+                order_number: job.order_number.clone(),
+                station: job.station.clone(),
+                station_matched,
+                live_printer_id: job.printer_id.clone(),
+                routed_printer_id: routed,
+                printer_id,
+                outcome,
+                changed,
+            });
+        }
+        simulation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConnectionType, PrinterCapabilities};
+
+    fn printer(id: &str, station: Option<&str>, is_primary: bool) -> PrinterConfig {
+        PrinterConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            connection_type: ConnectionType::Network,
+            address: "192.168.1.50:9100".to_string(),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: station.map(str::to_string),
+            is_primary,
+            capabilities: PrinterCapabilities { cutter: true, drawer: false, qrcode: true, max_width: 48 },
+            paper_width_checked: true,
+            recovery: Default::default(),
+            compact: None,
+            transports: vec![],
+            serial: None,
+            paused: false,
+        }
+    }
+
+    fn job(id: &str, station: &str, printer_id: &str) -> JobHistoryEntry {
+        JobHistoryEntry {
+            id: id.to_string(),
+            order_id: None,
+            order_number: id.to_string(),
+            station: station.to_string(),
+            printer_id: Some(printer_id.to_string()),
+            status: "completed".to_string(),
+            error_message: None,
+            retry_count: 0,
+            created_at: 0,
+            completed_at: None,
+            correlation_id: id.to_string(),
+            notes: vec![],
+        }
+    }
+
+    #[test]
+    fn test_scenario_reroutes_and_fails_over() {
+        let printers = vec![
+            printer("kitchen-1", Some("kitchen"), true),
+            printer("bar-1", Some("bar"), false),
+            printer("spare", None, false),
+        ];
+        let live_failover = HashMap::from([("kitchen-1".to_string(), vec!["spare".to_string()])]);
+        let jobs = vec![job("1", "Kitchen", "kitchen-1"), job("2", "bar", "bar-1"), job("3", "Dessert", "kitchen-1")];
+
+        // Live routing replays as it printed
+        let live = Routing::new(&RoutingScenario::default(), &printers, &BTreeMap::new(), &RoutingConfig::default(), &live_failover);
+        let result = live.simulate(&jobs, &HashSet::new());
+        assert_eq!(result.changed, 0);
+        assert!(!result.jobs[2].station_matched);
+
+        // Bar moves to the spare printer, the kitchen printer is down
+        let scenario = RoutingScenario {
+            printer_stations: HashMap::from([
+                ("bar-1".to_string(), None),
+                ("spare".to_string(), Some("bar".to_string())),
+            ]),
+            assume_down: vec!["kitchen-1".to_string()],
+            ..Default::default()
+        };
+        let routing = Routing::new(&scenario, &printers, &BTreeMap::new(), &RoutingConfig::default(), &live_failover);
+        let down: HashSet<String> = scenario.assume_down.iter().cloned().collect();
+        let result = routing.simulate(&jobs, &down);
+        assert_eq!(result.jobs[0].outcome, SimulatedOutcome::Failover);
+        assert_eq!(result.jobs[0].printer_id.as_deref(), Some("spare"));
+        assert_eq!(result.jobs[1].printer_id.as_deref(), Some("spare"));
+        assert_eq!(result.per_printer.get("spare"), Some(&3));
+        assert_eq!(result.changed, 3);
+
+        // Without backups the kitchen's tickets fail
+        let no_failover = RoutingScenario { failover: Some(HashMap::new()), ..scenario };
+        let routing = Routing::new(&no_failover, &printers, &BTreeMap::new(), &RoutingConfig::default(), &live_failover);
+        let result = routing.simulate(&jobs, &down);
+        assert_eq!(result.failed, 2);
+        assert_eq!(result.jobs[0].printer_id, None);
+    }
+}
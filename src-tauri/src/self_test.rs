@@ -0,0 +1,164 @@
+use crate::config::PrinterConfig;
+use crate::printer::PrinterManager;
+use crate::status::PrinterHwStatus;
+use chrono::{NaiveDate, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How often the scheduler checks whether the morning self-test is due
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A test missed at its time (PC off, daemon restarting) still runs this many hours later
+pub const LATE_START_HOURS: u32 = 2;
+
+/// Scheduled self-test for unattended venues (ghost kitchens): every morning each printer
+/// reports its hardware status and prints a test page, so a jammed or empty printer is
+/// found before staff arrive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelfTestConfig {
+    pub enabled: bool,
+    /// Local time of the test
+    pub hour: u8,
+    pub minute: u8,
+    /// Printers to test; empty tests all of them
+    pub printer_ids: Vec<String>,
+    /// Print a test page; off only checks the hardware status (saves paper)
+    pub print_page: bool,
+}
+
+impl Default for SelfTestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hour: 7,
+            minute: 0,
+            printer_ids: Vec::new(),
+            print_page: true,
+        }
+    }
+}
+
+impl SelfTestConfig {
+    pub fn includes(&self, printer_id: &str) -> bool {
+        self.printer_ids.is_empty() || self.printer_ids.iter().any(|id| id == printer_id)
+    }
+}
+
+/// Whether the scheduled test should run at local time `now`: enabled, not yet run
+/// today, and no more than `LATE_START_HOURS` past its time.
+pub fn is_due(config: &SelfTestConfig, now: NaiveDateTime, last_run: Option<NaiveDate>) -> bool {
+    if !config.enabled || last_run == Some(now.date()) {
+        return false;
+    }
+    let scheduled = config.hour.min(23) as u32 * 60 + config.minute.min(59) as u32;
+    let minute_of_day = now.hour() * 60 + now.minute();
+    (scheduled..scheduled + LATE_START_HOURS * 60).contains(&minute_of_day)
+}
+
+/// Outcome of one printer's self-test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestResult {
+    pub printer_id: String,
+    pub printer_name: String,
+    pub passed: bool,
+    /// DLE EOT status; None if the printer didn't answer
+    pub hw_status: Option<PrinterHwStatus>,
+    /// The test page was sent
+    pub printed: bool,
+    /// Why it failed
+    pub error: Option<String>,
+    /// Unix seconds
+    pub tested_at: i64,
+}
+
+/// Why a printer with this status can't print tickets, None if it's ready
+pub fn not_ready_reason(status: &PrinterHwStatus) -> Option<&'static str> {
+    if !status.online {
+        Some("offline")
+    } else if status.cover_open {
+        Some("cover open")
+    } else if !status.paper_present {
+        Some("out of paper")
+    } else if status.cutter_error {
+        Some("cutter error")
+    } else if status.unrecoverable_error {
+        Some("paper jam or mechanical error")
+    } else if status.error {
+        Some("printer error")
+    } else {
+        None
+    }
+}
+
+/// Check one printer: read its status, then print `page` if it is ready. Paper near
+/// its end still passes (the status is reported with the result).
+pub async fn test_printer(
+    manager: &PrinterManager,
+    printer: &PrinterConfig,
+    page: Option<&[u8]>,
+    now_secs: i64,
+) -> SelfTestResult {
+    let mut result = SelfTestResult {
+        printer_id: printer.id.clone(),
+        printer_name: printer.name.clone(),
+        passed: false,
+        hw_status: None,
+        printed: false,
+        error: None,
+        tested_at: now_secs,
+    };
+
+    match manager.poll_status(printer).await {
+        Ok(status) => {
+            result.error = not_ready_reason(&status).map(str::to_string);
+            result.hw_status = Some(status);
+        }
+        Err(e) => result.error = Some(format!("no status: {}", e)),
+    }
+    if result.error.is_some() {
+        return result;
+    }
+
+    if let Some(page) = page {
+        if let Err(e) = manager.test_print(&printer.id, page).await {
+            result.error = Some(format!("test page failed: {}", e));
+            return result;
+        }
+        result.printed = true;
+    }
+    result.passed = true;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_due_once_per_day_in_window() {
+        let config = SelfTestConfig { enabled: true, ..Default::default() };
+        assert!(!is_due(&config, at(6, 59), None));
+        assert!(is_due(&config, at(7, 0), None));
+        assert!(is_due(&config, at(8, 30), None));
+        assert!(!is_due(&config, at(9, 0), None));
+
+        let yesterday = NaiveDate::from_ymd_opt(2026, 3, 1);
+        assert!(is_due(&config, at(7, 5), yesterday));
+        assert!(!is_due(&config, at(7, 5), Some(at(7, 0).date())));
+        assert!(!is_due(&SelfTestConfig::default(), at(7, 0), None));
+    }
+
+    #[test]
+    fn test_not_ready_reasons() {
+        let healthy = PrinterHwStatus::healthy();
+        assert_eq!(not_ready_reason(&healthy), None);
+        assert_eq!(not_ready_reason(&PrinterHwStatus { paper_near_end: true, ..healthy.clone() }), None);
+        assert_eq!(not_ready_reason(&PrinterHwStatus { cover_open: true, ..healthy.clone() }), Some("cover open"));
+        assert_eq!(not_ready_reason(&PrinterHwStatus { paper_present: false, ..healthy }), Some("out of paper"));
+    }
+}
@@ -3,6 +3,7 @@ use regex::Regex;
 use sentry::{ClientInitGuard, ClientOptions};
 use std::env;
 use std::sync::Arc;
+use crate::telemetry::{mode, TelemetryMode};
 
 // Pre-compiled regex patterns for PII stripping (compiled once, used many times)
 static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| {
@@ -60,6 +61,15 @@ pub fn init() -> Option<ClientInitGuard> {
             attach_stacktrace: true,
             send_default_pii: false, // GDPR compliance - no PII
             before_send: Some(Arc::new(before_send_filter)),
+            // Telemetry mode is read per event, so a change applies without a restart
+            before_breadcrumb: Some(Arc::new(|breadcrumb| mode().shares_usage().then_some(breadcrumb))),
+            traces_sampler: Some(Arc::new(move |_: &sentry::TransactionContext| {
+                if mode().shares_usage() {
+                    traces_sample_rate
+                } else {
+                    0.0
+                }
+            })),
             ..Default::default()
         },
     ));
@@ -74,12 +84,33 @@ pub fn init() -> Option<ClientInitGuard> {
 
 /// Filter function to strip PII before sending errors to Sentry
 ///
+/// Honors the telemetry mode: nothing is sent when it is off, only errors (without
+/// breadcrumbs, host name or user) in errors-only.
+///
 /// **Privacy Rules:**
 /// - NEVER send customer names, addresses, emails, phone numbers
 /// - NEVER send order contents (menu items, quantities, prices)
 /// - ONLY send error messages, stack traces, operational metadata
 /// - Strip restaurant-specific data (replace with generic placeholders)
 fn before_send_filter(mut event: sentry::protocol::Event<'static>) -> Option<sentry::protocol::Event<'static>> {
+    // Planned maintenance: failures are expected, don't report them
+    if crate::maintenance::is_active() {
+        return None;
+    }
+
+    match mode() {
+        TelemetryMode::Full => {}
+        TelemetryMode::ErrorsOnly => {
+            if !matches!(event.level, sentry::Level::Error | sentry::Level::Fatal) {
+                return None;
+            }
+            event.breadcrumbs.values.clear();
+            event.server_name = None;
+            event.user = None;
+        }
+        TelemetryMode::Off => return None,
+    }
+
     // Strip PII from error messages
     if let Some(message) = event.message.as_mut() {
         *message = strip_pii_from_message(message);
@@ -135,6 +166,15 @@ fn strip_pii_from_message(message: &str) -> String {
     cleaned
 }
 
+/// Strip credentials and email addresses from free text (log lines, support bundles).
+///
+/// Lighter than `strip_pii_from_message`: keeps UUIDs and numbers, which support
+/// needs to correlate jobs and printers.
+pub fn redact_secrets(message: &str) -> String {
+    let cleaned = JWT_REGEX.replace_all(message, "[JWT_REDACTED]");
+    EMAIL_REGEX.replace_all(&cleaned, "[EMAIL_REDACTED]").to_string()
+}
+
 /// Add restaurant context to current Sentry scope
 ///
 /// **Safe to call** - restaurant_id is anonymized (hashed) before sending
@@ -0,0 +1,247 @@
+use crate::discovery::DiscoveredPrinter;
+use crate::errors::{DaemonError, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Bytes per write call, so a stalled line is noticed between chunks
+const CHUNK: usize = 256;
+
+/// How long a status or model query waits for the answer
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Baud rates thermal printers offer on their DIP switches
+pub const BAUD_RATES: &[u32] = &[2400, 4800, 9600, 19200, 38400, 57600, 115200];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Parity {
+    #[default]
+    None,
+    Odd,
+    Even,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlowControl {
+    None,
+    /// XON / XOFF
+    Software,
+    /// RTS / CTS
+    #[default]
+    Hardware,
+}
+
+/// Line settings of an RS-232 printer (`PrinterConfig.serial`); they must match the
+/// printer's DIP switches. Default 38400 baud, 8N1, RTS/CTS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SerialSettings {
+    pub baud_rate: u32,
+    /// 7 or 8
+    pub data_bits: u8,
+    pub parity: Parity,
+    /// 1 or 2
+    pub stop_bits: u8,
+    pub flow_control: FlowControl,
+}
+
+impl Default for SerialSettings {
+    fn default() -> Self {
+        Self { baud_rate: 38400, data_bits: 8, parity: Parity::None, stop_bits: 1, flow_control: FlowControl::Hardware }
+    }
+}
+
+impl SerialSettings {
+    /// Check the settings before a port is opened with them
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if !BAUD_RATES.contains(&self.baud_rate) {
+            return Err(format!("Unsupported baud rate {}", self.baud_rate));
+        }
+        if !matches!(self.data_bits, 7 | 8) {
+            return Err(format!("Data bits must be 7 or 8, not {}", self.data_bits));
+        }
+        if !matches!(self.stop_bits, 1 | 2) {
+            return Err(format!("Stop bits must be 1 or 2, not {}", self.stop_bits));
+        }
+        Ok(())
+    }
+
+    /// Bytes per second the line carries (start, data, parity and stop bits per byte)
+    pub fn bytes_per_sec(&self) -> f64 {
+        let bits = 1 + self.data_bits as u32 + (self.parity != Parity::None) as u32 + self.stop_bits as u32;
+        self.baud_rate as f64 / bits as f64
+    }
+
+    fn open(&self, port: &str, timeout: Duration) -> Result<Box<dyn serialport::SerialPort>> {
+        self.validate().map_err(DaemonError::Serial)?;
+        serialport::new(port, self.baud_rate)
+            .data_bits(if self.data_bits == 7 { serialport::DataBits::Seven } else { serialport::DataBits::Eight })
+            .parity(match self.parity {
+                Parity::None => serialport::Parity::None,
+                Parity::Odd => serialport::Parity::Odd,
+                Parity::Even => serialport::Parity::Even,
+            })
+            .stop_bits(if self.stop_bits == 2 { serialport::StopBits::Two } else { serialport::StopBits::One })
+            .flow_control(match self.flow_control {
+                FlowControl::None => serialport::FlowControl::None,
+                FlowControl::Software => serialport::FlowControl::Software,
+                FlowControl::Hardware => serialport::FlowControl::Hardware,
+            })
+            .timeout(timeout)
+            .open()
+            .map_err(|e| port_error(port, e))
+    }
+}
+
+fn port_error(port: &str, e: serialport::Error) -> DaemonError {
+    match e.kind() {
+        serialport::ErrorKind::NoDevice => DaemonError::Serial(format!("Serial port {} not found", port)),
+        _ => DaemonError::Serial(format!("{}: {}", port, e)),
+    }
+}
+
+/// Write `data` to the printer on `port`. Blocking: run on the blocking pool.
+pub fn write_blocking(port: &str, settings: &SerialSettings, data: &[u8], timeout: Duration) -> Result<()> {
+    let started = Instant::now();
+    let mut serial = settings.open(port, timeout)?;
+    for chunk in data.chunks(CHUNK) {
+        // A printer holding CTS low (paper out, cover open) stalls the write
+        if started.elapsed() > timeout {
+            return Err(DaemonError::Serial(format!("Write to {} timed out", port)));
+        }
+        serial.write_all(chunk).map_err(|e| match e.kind() {
+            std::io::ErrorKind::TimedOut => DaemonError::Serial(format!("Write to {} timed out", port)),
+            _ => DaemonError::Serial(format!("Write to {} failed: {}", port, e)),
+        })?;
+    }
+    serial.flush().map_err(|e| DaemonError::Serial(format!("Write to {} failed: {}", port, e)))?;
+    debug!("Wrote {} bytes to serial port {} in {:?}", data.len(), port, started.elapsed());
+    Ok(())
+}
+
+/// Send a real-time request and read an answer of `len` bytes, or up to a NUL when `len`
+/// is None (GS I text answers). Blocking.
+pub fn query_blocking(port: &str, settings: &SerialSettings, request: &[u8], len: Option<usize>) -> Result<Vec<u8>> {
+    let mut serial = settings.open(port, QUERY_TIMEOUT)?;
+    // Leftovers of an earlier answer would be read as this one
+    serial
+        .clear(serialport::ClearBuffer::Input)
+        .map_err(|e| port_error(port, e))?;
+    serial.write_all(request).map_err(|e| DaemonError::Serial(format!("Query to {} failed: {}", port, e)))?;
+
+    let deadline = Instant::now() + QUERY_TIMEOUT;
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while len.is_none_or(|len| response.len() < len) && Instant::now() < deadline {
+        match serial.read(&mut byte) {
+            Ok(1) if len.is_none() && byte[0] == 0 => break,
+            Ok(1) => response.push(byte[0]),
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => break,
+            Err(e) => return Err(DaemonError::Serial(format!("Query to {} failed: {}", port, e))),
+        }
+    }
+    match len {
+        Some(len) if response.len() < len => {
+            Err(DaemonError::Serial(format!("No answer from {} (status query timed out)", port)))
+        }
+        _ => Ok(response),
+    }
+}
+
+/// Whether `port` is among the system's serial ports
+pub fn port_present(port: &str) -> bool {
+    serialport::available_ports().is_ok_and(|ports| ports.iter().any(|p| p.port_name == port))
+}
+
+/// Ports worth offering as printers: Bluetooth and debug ports are left out, and on macOS
+/// only the call-out (`cu.`) side of each port, since opening `tty.` waits for carrier
+fn offered(port: &serialport::SerialPortInfo) -> bool {
+    let name = port.port_name.to_lowercase();
+    !matches!(port.port_type, serialport::SerialPortType::BluetoothPort)
+        && !name.contains("bluetooth")
+        && !name.contains("debug-console")
+        && !name.starts_with("/dev/tty.")
+}
+
+/// Serial ports as discovery results. Nothing is sent: an unknown device on a port
+/// could take any byte as a command, so the protocol stays "unknown".
+pub fn discover() -> Vec<DiscoveredPrinter> {
+    let ports = match serialport::available_ports() {
+        Ok(ports) => ports,
+        Err(e) => {
+            debug!("Serial port enumeration failed: {}", e);
+            return Vec::new();
+        }
+    };
+    ports
+        .into_iter()
+        .filter(offered)
+        .map(|port| {
+            let (name, vendor) = match &port.port_type {
+                // USB-to-serial adapters describe the adapter, not the printer
+                serialport::SerialPortType::UsbPort(usb) => (
+                    format!("Serial printer on {}", usb.product.as_deref().unwrap_or(&port.port_name)),
+                    usb.manufacturer.clone().unwrap_or_else(|| "Unknown".to_string()),
+                ),
+                _ => (format!("Serial printer on {}", port.port_name), "Unknown".to_string()),
+            };
+            DiscoveredPrinter {
+                id: format!("serial_{}", port_id(&port.port_name)),
+                name,
+                connection_type: "serial".to_string(),
+                address: port.port_name,
+                vendor,
+                capabilities: None,
+                protocol: "unknown".to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Port name as an id fragment: `/dev/ttyUSB0` → `ttyusb0`, `COM3` → `com3`
+fn port_id(port: &str) -> String {
+    port.rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(port)
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialport::{SerialPortInfo, SerialPortType};
+
+    #[test]
+    fn test_serial_settings_and_ports() {
+        let settings = SerialSettings::default();
+        assert_eq!(settings.validate(), Ok(()));
+        assert_eq!(settings.bytes_per_sec(), 3840.0);
+        let even = SerialSettings { baud_rate: 9600, parity: Parity::Even, stop_bits: 2, ..settings };
+        assert_eq!(even.bytes_per_sec(), 800.0);
+        assert!(SerialSettings { baud_rate: 12345, ..settings }.validate().is_err());
+        assert!(SerialSettings { data_bits: 5, ..settings }.validate().is_err());
+
+        // Older configs without settings get the defaults
+        let parsed: SerialSettings = serde_json::from_str(r#"{"baud_rate": 9600, "flow_control": "software"}"#).unwrap();
+        assert_eq!((parsed.baud_rate, parsed.data_bits, parsed.flow_control), (9600, 8, FlowControl::Software));
+
+        let port = |name: &str, port_type| SerialPortInfo { port_name: name.to_string(), port_type };
+        assert!(offered(&port("/dev/ttyUSB0", SerialPortType::Unknown)));
+        assert!(offered(&port("/dev/cu.usbserial-1410", SerialPortType::Unknown)));
+        assert!(offered(&port("COM3", SerialPortType::PciPort)));
+        assert!(!offered(&port("/dev/tty.usbserial-1410", SerialPortType::Unknown)));
+        assert!(!offered(&port("/dev/cu.Bluetooth-Incoming-Port", SerialPortType::Unknown)));
+        assert!(!offered(&port("COM7", SerialPortType::BluetoothPort)));
+
+        assert_eq!(port_id("/dev/ttyUSB0"), "ttyusb0");
+        assert_eq!(port_id("COM3"), "com3");
+        assert_eq!(port_id("/dev/cu.usbserial-1410"), "cu_usbserial_1410");
+    }
+}
@@ -0,0 +1,206 @@
+use crate::escpos::{
+    format_change_ticket, format_customer_receipt, format_kitchen_receipt, parse_escpos, CheckTotals, PaperWidth,
+    PrintItem, ReceiptLayout, TestPrintTemplate, Thumbnails,
+};
+use crate::i18n::Locale;
+use crate::queue::JobKind;
+use crate::receipt_fields::ReceiptBlocks;
+use crate::receipt_templates::{self, Template, TicketData};
+use serde::Deserialize;
+
+const USAGE: &str = "usage: eatsome-printer-daemon simulate <job.json> [--template <file>] [--compact]";
+
+/// A job file for `simulate`: the body of POST /api/print (`restaurant_id` isn't needed),
+/// plus an optional `check` for the customer receipt
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SimulatedJob {
+    pub station: String,
+    pub order_number: String,
+    pub order_type: Option<String>,
+    pub table_number: Option<String>,
+    pub customer_name: Option<String>,
+    pub priority: Option<u8>,
+    pub copies: Option<u8>,
+    pub items: Vec<PrintItem>,
+    /// Unix ms
+    pub pickup_time: Option<i64>,
+    /// Unix ms the ticket is dated (default: now); set it for output that doesn't change
+    /// between runs
+    pub timestamp: Option<i64>,
+    /// Also render the customer receipt with these order totals
+    pub check: Option<CheckTotals>,
+    /// Customer receipt labels
+    pub locale: Locale,
+    /// "void" or "amend" renders the follow-up ticket instead
+    pub kind: JobKind,
+    /// Amend: items taken off the order
+    pub removed_items: Vec<PrintItem>,
+}
+
+/// The job's tickets as text, on 58 and 80 mm paper: the kitchen ticket (through
+/// `template` if given, or the void / amend ticket) and, with a `check`, the customer receipt
+pub fn render(job: &SimulatedJob, template: Option<&Template>, layout: ReceiptLayout) -> String {
+    let timestamp = job.timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let blocks = ReceiptBlocks::default();
+    let mut out = String::new();
+    for paper_width in [PaperWidth::Width58mm, PaperWidth::Width80mm] {
+        let commands = match template {
+            _ if job.kind != JobKind::Order => format_change_ticket(
+                job.kind,
+                &job.station,
+                &job.order_number,
+                job.table_number.as_deref(),
+                job.customer_name.as_deref(),
+                &job.items,
+                &job.removed_items,
+                timestamp,
+                None,
+                paper_width,
+                layout,
+                &blocks,
+            ),
+            Some(template) => template.render(
+                &TicketData {
+                    station: &job.station,
+                    order_number: &job.order_number,
+                    order_type: job.order_type.as_deref(),
+                    table_number: job.table_number.as_deref(),
+                    customer_name: job.customer_name.as_deref(),
+                    priority: job.priority.unwrap_or(3),
+                    items: &job.items,
+                    timestamp,
+                    pickup_time: job.pickup_time,
+                    reference: None,
+                },
+                job.copies.unwrap_or(1),
+                paper_width,
+                &blocks,
+            ),
+            None => format_kitchen_receipt(
+                &job.station,
+                &job.order_number,
+                job.order_type.as_deref(),
+                job.table_number.as_deref(),
+                job.customer_name.as_deref(),
+                job.priority.unwrap_or(3),
+                &job.items,
+                timestamp,
+                job.copies.unwrap_or(1),
+                None,
+                &Thumbnails::new(),
+                paper_width,
+                layout,
+                &blocks,
+            ),
+        };
+        out.push_str(&format!("Kitchen ticket, {} mm\n", paper_width.mm()));
+        out.push_str(&parse_escpos(&commands, paper_width).to_ascii());
+        out.push('\n');
+
+        if let Some(check) = &job.check {
+            let commands = format_customer_receipt(
+                &TestPrintTemplate::default(),
+                &job.order_number,
+                &job.items,
+                check,
+                timestamp,
+                job.locale,
+                paper_width,
+                &blocks,
+                None,
+            );
+            out.push_str(&format!("Customer receipt, {} mm\n", paper_width.mm()));
+            out.push_str(&parse_escpos(&commands, paper_width).to_ascii());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// `eatsome-printer-daemon simulate ...`: print a job file's tickets to stdout without
+/// printers or a running daemon, so layout changes can be reviewed as text. Returns the
+/// exit code.
+pub fn run(args: &[String]) -> i32 {
+    let mut job_path = None;
+    let mut template_path = None;
+    let mut layout = ReceiptLayout::Normal;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--template" => match args.next() {
+                Some(path) => template_path = Some(path),
+                None => {
+                    eprintln!("{}", USAGE);
+                    return 2;
+                }
+            },
+            "--compact" => layout = ReceiptLayout::Compact,
+            path if job_path.is_none() && !path.starts_with("--") => job_path = Some(path),
+            _ => {
+                eprintln!("{}", USAGE);
+                return 2;
+            }
+        }
+    }
+    let Some(job_path) = job_path else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+
+    let job: SimulatedJob = match std::fs::read_to_string(job_path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+    {
+        Ok(job) => job,
+        Err(e) => {
+            eprintln!("{}: {}", job_path, e);
+            return 1;
+        }
+    };
+    let template = match template_path {
+        Some(path) => match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|source| receipt_templates::compile(&source).map_err(|e| e.to_string()))
+        {
+            Ok(template) => Some(template),
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                return 1;
+            }
+        },
+        None => None,
+    };
+
+    print!("{}", render(&job, template.as_ref(), layout));
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_job_file() {
+        let job: SimulatedJob = serde_json::from_str(
+            r#"{
+                "restaurant_id": "ignored",
+                "station": "bar",
+                "order_number": "17",
+                "items": [{"quantity": 2, "name": "Cola", "modifiers": ["no ice"], "notes": null}],
+                "timestamp": 0
+            }"#,
+        )
+        .unwrap();
+        let out = render(&job, None, ReceiptLayout::Normal);
+        assert!(out.contains("Kitchen ticket, 58 mm\n+--------------------------------+\n"));
+        assert!(out.contains(&format!("Kitchen ticket, 80 mm\n+{}+\n", "-".repeat(48))));
+        assert!(out.contains("Cola"));
+        assert!(!out.contains("Customer receipt"));
+
+        let template = receipt_templates::compile("{{#each items}}\n{{quantity}}x {{name}}\n{{/each}}").unwrap();
+        let out = render(&SimulatedJob { check: Some(CheckTotals::default()), ..job }, Some(&template), ReceiptLayout::Normal);
+        assert!(out.contains(&format!("|{:<32}|", "2x Cola")));
+        assert!(out.contains("Customer receipt, 80 mm"));
+    }
+}
@@ -0,0 +1,121 @@
+use crate::config::PrinterConfig;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Payload of the `station-unmatched` event
+#[derive(Debug, Clone, Serialize)]
+pub struct UnmatchedStation {
+    pub job_id: String,
+    pub order_number: String,
+    /// Station as sent upstream
+    pub received: String,
+    /// What the job was queued under (trimmed, lowercase)
+    pub station: String,
+}
+
+/// Result of resolving an upstream station name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StationMatch {
+    pub station: String,
+    /// False if the name is neither a configured station nor an alias
+    pub matched: bool,
+}
+
+/// Resolves the station names upstream sends ("Kitchen", "kitchen ", "KEUKEN") to the
+/// stations printers are configured for. Names are compared trimmed, lowercase, with
+/// inner whitespace collapsed.
+#[derive(Debug, Clone, Default)]
+pub struct StationMap {
+    /// Normalized name → canonical station (aliases and the canonical names themselves)
+    names: HashMap<String, String>,
+}
+
+impl StationMap {
+    /// Canonical stations are the printers' stations plus alias targets
+    /// (`AppConfig.station_aliases`: variant → station)
+    pub fn new(printers: &[PrinterConfig], aliases: &BTreeMap<String, String>) -> Self {
+        let mut names = HashMap::new();
+        let canonical = printers
+            .iter()
+            .filter_map(|p| p.station.as_deref())
+            .chain(aliases.values().map(String::as_str));
+        for station in canonical {
+            let station = station.trim();
+            if !station.is_empty() {
+                names.insert(normalize(station), station.to_string());
+            }
+        }
+        for (variant, station) in aliases {
+            let station = station.trim();
+            if !station.is_empty() {
+                names.insert(normalize(variant), station.to_string());
+            }
+        }
+        Self { names }
+    }
+
+    /// Canonical station for `raw`. Unknown names are still normalized, so "Bar " and
+    /// "bar" queue (and deduplicate) as the same station. With no stations configured at
+    /// all, every name counts as matched.
+    pub fn resolve(&self, raw: &str) -> StationMatch {
+        let key = normalize(raw);
+        match self.names.get(&key) {
+            Some(station) => StationMatch { station: station.clone(), matched: true },
+            None => StationMatch { station: key, matched: self.names.is_empty() },
+        }
+    }
+}
+
+/// Trimmed, lowercase, single spaces
+pub fn normalize(station: &str) -> String {
+    station.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConnectionType, PrinterCapabilities};
+
+    fn printer(station: &str) -> PrinterConfig {
+        PrinterConfig {
+            id: format!("printer-{}", station),
+            name: station.to_string(),
+            connection_type: ConnectionType::Network,
+            address: "192.168.1.50:9100".to_string(),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: Some(station.to_string()),
+            is_primary: false,
+            capabilities: PrinterCapabilities { cutter: true, drawer: false, qrcode: true, max_width: 48 },
+            paper_width_checked: true,
+            recovery: Default::default(),
+            compact: None,
+            transports: vec![],
+            serial: None,
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn test_resolves_case_whitespace_and_aliases() {
+        let aliases = BTreeMap::from([
+            ("Keuken".to_string(), "kitchen".to_string()),
+            ("drinks".to_string(), "bar".to_string()),
+        ]);
+        let map = StationMap::new(&[printer("kitchen"), printer("Pastry")], &aliases);
+
+        let matched = |station: &str| StationMatch { station: station.to_string(), matched: true };
+        assert_eq!(map.resolve("Kitchen"), matched("kitchen"));
+        assert_eq!(map.resolve("  kitchen "), matched("kitchen"));
+        assert_eq!(map.resolve("KEUKEN"), matched("kitchen"));
+        assert_eq!(map.resolve("Drinks"), matched("bar"));
+        assert_eq!(map.resolve("bar"), matched("bar"));
+        assert_eq!(map.resolve("pastry"), matched("Pastry"));
+
+        assert_eq!(
+            map.resolve(" Cold  Kitchen"),
+            StationMatch { station: "cold kitchen".to_string(), matched: false }
+        );
+        assert!(StationMap::default().resolve("anything").matched);
+    }
+}
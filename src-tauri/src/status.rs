@@ -1,10 +1,18 @@
 /// Print job status constants — single source of truth.
 /// Used in both local SQLite and remote Supabase.
-/// Must match Supabase CHECK constraint: ('pending', 'printing', 'completed', 'failed')
+/// Must match Supabase CHECK constraint: ('pending', 'printing', 'completed', 'failed', 'cancelled')
 pub const PENDING: &str = "pending";
 pub const PRINTING: &str = "printing";
 pub const COMPLETED: &str = "completed";
 pub const FAILED: &str = "failed";
+/// Taken out of the queue before it printed (`cancel_job`)
+pub const CANCELLED: &str = "cancelled";
+
+/// Delivery phases of a completed job, reported next to COMPLETED (not job statuses,
+/// so the CHECK constraint above is unchanged):
+/// SENT = transport write finished, PRINTED = printer confirmed it had no error.
+pub const SENT: &str = "sent";
+pub const PRINTED: &str = "printed";
 
 // =============================================================================
 // Hardware Status (DLE EOT response parsing)
@@ -17,7 +25,7 @@ use serde::{Deserialize, Serialize};
 /// ESC/POS DLE EOT response format (each response is 1 byte):
 ///   n=1 (Printer): bit 3 = offline
 ///   n=2 (Offline cause): bit 2 = cover open, bit 3 = feed button, bit 5 = error
-///   n=3 (Error cause): bit 2 = auto-cutter error, bit 5 = unrecoverable
+///   n=3 (Error cause): bit 2 = auto-cutter error, bit 3 = unrecoverable
 ///   n=4 (Paper sensor): bit 2+3 = paper near-end, bit 5+6 = paper end
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PrinterHwStatus {
@@ -27,6 +35,9 @@ pub struct PrinterHwStatus {
     pub paper_near_end: bool,
     pub error: bool,
     pub cutter_error: bool,
+    /// Mechanical fault the printer can't clear itself (jam, head or motor error)
+    #[serde(default)]
+    pub unrecoverable_error: bool,
 }
 
 impl PrinterHwStatus {
@@ -48,6 +59,8 @@ impl PrinterHwStatus {
             error: (offline_cause & 0x20) != 0,
             // n=3: bit 2 set = auto-cutter error
             cutter_error: (error_cause & 0x04) != 0,
+            // n=3: bit 3 set = unrecoverable error
+            unrecoverable_error: (error_cause & 0x08) != 0,
         }
     }
 
@@ -60,7 +73,7 @@ impl PrinterHwStatus {
             "paper_out"
         } else if self.paper_near_end {
             "paper_low"
-        } else if self.error || self.cutter_error {
+        } else if self.error || self.cutter_error || self.unrecoverable_error {
             "error"
         } else {
             "online"
@@ -76,6 +89,82 @@ impl PrinterHwStatus {
             paper_near_end: false,
             error: false,
             cutter_error: false,
+            unrecoverable_error: false,
+        }
+    }
+}
+
+/// The printer's answer to the status query sent right after a job's data.
+///
+/// A successful TCP/USB write only means the bytes left this machine; a printer with
+/// its cover open, out of paper or in an error state drops them. Before a job counts
+/// as printed, DLE EOT 2 (offline cause) and DLE EOT 3 (error cause) are queried.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteAck {
+    /// No error reported: the job was accepted
+    Confirmed,
+    /// The printer reported a condition that stops or loses the print (retry the job)
+    Rejected(String),
+    /// No usable answer (printer doesn't implement DLE EOT); treated as printed
+    Unconfirmed,
+}
+
+impl WriteAck {
+    /// Classify the DLE EOT 2 / DLE EOT 3 response bytes (Epson bit layout).
+    ///
+    /// Status bytes always have bits 1 and 4 set and bits 0 and 7 clear; anything
+    /// else is not a status answer and leaves the write unconfirmed.
+    pub fn from_dle_eot(offline_cause: u8, error_cause: u8) -> Self {
+        if offline_cause & 0x93 != 0x12 || error_cause & 0x93 != 0x12 {
+            return WriteAck::Unconfirmed;
+        }
+
+        // Most specific cause first
+        let reason = if error_cause & 0x04 != 0 {
+            "auto-cutter error"
+        } else if error_cause & 0x08 != 0 {
+            "unrecoverable error"
+        } else if error_cause & 0x20 != 0 {
+            "auto-recoverable error (print head overheated?)"
+        } else if offline_cause & 0x04 != 0 {
+            "cover open"
+        } else if offline_cause & 0x20 != 0 {
+            "printing stopped at paper end"
+        } else if offline_cause & 0x40 != 0 {
+            "error"
+        } else {
+            return WriteAck::Confirmed;
+        };
+        WriteAck::Rejected(reason.to_string())
+    }
+}
+
+/// How far a completed job is known to have got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delivery {
+    /// Unix ms when the transport write finished
+    pub sent_at_ms: i64,
+    /// Unix ms when the printer confirmed the job; None if it can't tell us (no DLE EOT)
+    pub printed_at_ms: Option<i64>,
+    /// Size of the job as sent to the printer
+    pub bytes: u64,
+}
+
+impl Delivery {
+    pub fn new(sent_at_ms: i64, bytes: usize, ack: &WriteAck) -> Self {
+        Self {
+            sent_at_ms,
+            bytes: bytes as u64,
+            printed_at_ms: (*ack == WriteAck::Confirmed).then(|| chrono::Utc::now().timestamp_millis()),
+        }
+    }
+
+    /// SENT ("on its way") or PRINTED ("paper in hand")
+    pub fn phase(&self) -> &'static str {
+        if self.printed_at_ms.is_some() {
+            PRINTED
+        } else {
+            SENT
         }
     }
 }
@@ -84,6 +173,35 @@ impl PrinterHwStatus {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_delivery_phase_follows_ack() {
+        let printed = Delivery::new(1_700_000_000_000, 512, &WriteAck::Confirmed);
+        assert_eq!(printed.phase(), PRINTED);
+        assert!(printed.printed_at_ms.unwrap() >= printed.sent_at_ms);
+
+        // A printer that can't answer only gets as far as "sent"
+        assert_eq!(Delivery::new(1_700_000_000_000, 512, &WriteAck::Unconfirmed).phase(), SENT);
+    }
+
+    #[test]
+    fn test_write_ack_classification() {
+        assert_eq!(WriteAck::from_dle_eot(0x12, 0x12), WriteAck::Confirmed);
+        // Feed button held is not an error
+        assert_eq!(WriteAck::from_dle_eot(0x1A, 0x12), WriteAck::Confirmed);
+
+        assert_eq!(WriteAck::from_dle_eot(0x16, 0x12), WriteAck::Rejected("cover open".to_string()));
+        assert_eq!(
+            WriteAck::from_dle_eot(0x32, 0x12),
+            WriteAck::Rejected("printing stopped at paper end".to_string())
+        );
+        // Error cause wins over the generic error flag
+        assert_eq!(WriteAck::from_dle_eot(0x52, 0x16), WriteAck::Rejected("auto-cutter error".to_string()));
+
+        // Not status bytes (no answer, echoed data)
+        assert_eq!(WriteAck::from_dle_eot(0x00, 0x00), WriteAck::Unconfirmed);
+        assert_eq!(WriteAck::from_dle_eot(0x12, 0xFF), WriteAck::Unconfirmed);
+    }
+
     #[test]
     fn test_healthy_printer() {
         // All zeros = printer online, no errors, paper present
@@ -137,6 +255,41 @@ mod tests {
         let status = PrinterHwStatus::from_dle_eot(0x00, 0x00, 0x04, 0x00);
         assert!(status.cutter_error);
         assert_eq!(status.to_status_string(), "error");
+
+        // Bit 3 of error_cause = unrecoverable (jam)
+        let status = PrinterHwStatus::from_dle_eot(0x00, 0x00, 0x08, 0x00);
+        assert!(status.unrecoverable_error && !status.cutter_error);
+        assert_eq!(status.to_status_string(), "error");
+    }
+
+    #[test]
+    fn test_status_bytes_to_fault_state() {
+        use crate::jam_recovery::MechanicalFault;
+        use crate::troubleshoot::{classify_hw_status, FailureClass};
+        let fault = |printer, offline_cause, error_cause, paper| {
+            let status = PrinterHwStatus::from_dle_eot(printer, offline_cause, error_cause, paper);
+            (status.to_status_string().to_string(), classify_hw_status(&status), MechanicalFault::from_status(&status))
+        };
+
+        // Real answers carry the fixed bits 0x12 in every byte
+        assert_eq!(fault(0x16, 0x12, 0x12, 0x12), ("online".to_string(), None, None));
+        // Cover open takes the printer offline: alerted as cover open, not mechanical
+        assert_eq!(
+            fault(0x1E, 0x36, 0x12, 0x12),
+            ("offline".to_string(), Some(FailureClass::CoverOpen), None)
+        );
+        // Cutter jam
+        assert_eq!(
+            fault(0x1E, 0x32, 0x16, 0x12),
+            ("offline".to_string(), Some(FailureClass::CutterError), Some(MechanicalFault::Cutter))
+        );
+        // Unrecoverable hardware fault (head, motor)
+        assert_eq!(
+            fault(0x1E, 0x32, 0x1A, 0x12),
+            ("offline".to_string(), Some(FailureClass::PrinterError), Some(MechanicalFault::Unrecoverable))
+        );
+        // Out of paper with the cover open: the paper is what to fix first
+        assert_eq!(fault(0x1E, 0x36, 0x12, 0x72).1, Some(FailureClass::PaperOut));
     }
 
     #[test]
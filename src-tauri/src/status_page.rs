@@ -0,0 +1,297 @@
+use crate::api::ApiState;
+use crate::queue::{QueueHealth, RecentPrint, Saturation};
+use crate::venue_time;
+use axum::{extract::State, response::Html, routing::get, Router};
+use serde::{Deserialize, Serialize};
+use tower_http::trace::TraceLayer;
+use tracing::{error, info};
+
+/// LAN address for the venue status page (tablets next to the pass can't reach 127.0.0.1)
+pub const DEFAULT_STATUS_PAGE_ADDR: &str = "0.0.0.0:8045";
+
+/// How many printed orders the page lists
+const RECENT_PRINTS: usize = 10;
+
+/// Page reload interval (seconds); plain meta refresh so any cheap tablet browser works
+const REFRESH_SECS: u32 = 10;
+
+/// Read-only print health page on the LAN (off by default; `/status` on the local API is always on)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusPageConfig {
+    pub enabled: bool,
+    pub bind_address: String,
+}
+
+impl Default for StatusPageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: DEFAULT_STATUS_PAGE_ADDR.to_string(),
+        }
+    }
+}
+
+/// One printer tile
+#[derive(Debug, Clone)]
+pub struct PrinterTile {
+    pub name: String,
+    pub station: Option<String>,
+    /// Last hardware status ("online", "paper_out", ...); None until first polled
+    pub status: Option<String>,
+}
+
+/// Everything the page shows
+#[derive(Debug, Clone)]
+pub struct StatusView {
+    pub printers: Vec<PrinterTile>,
+    pub queue: Option<QueueHealth>,
+    pub recent: Vec<RecentPrint>,
+}
+
+impl StatusView {
+    /// Conditions staff should act on
+    pub fn alerts(&self) -> Vec<String> {
+        let mut alerts: Vec<String> = self
+            .printers
+            .iter()
+            .filter_map(|p| match p.status.as_deref() {
+                None | Some("online") => None,
+                Some("paper_low") => Some(format!("{}: paper running low", p.name)),
+                Some("paper_out") => Some(format!("{}: out of paper", p.name)),
+                Some("offline") => Some(format!("{}: offline", p.name)),
+                Some(other) => Some(format!("{}: {}", p.name, other.replace('_', " "))),
+            })
+            .collect();
+
+        match self.queue.as_ref().map(|q| q.state) {
+            Some(Saturation::Saturated) => alerts.push("Print queue is backed up".to_string()),
+            Some(Saturation::Elevated) => alerts.push("Print queue is building up".to_string()),
+            _ => {}
+        }
+        alerts
+    }
+}
+
+/// Gather the page data from the API state
+pub async fn collect(state: &ApiState) -> StatusView {
+    let statuses = state.telemetry.printer_statuses().await;
+    let printers = state
+        .config
+        .lock()
+        .await
+        .printers
+        .iter()
+        .map(|p| PrinterTile {
+            name: p.name.clone(),
+            station: p.station.clone(),
+            status: statuses.get(&p.id).cloned(),
+        })
+        .collect();
+
+    let queue = &state.queue_manager;
+    let health = queue.get_health().await.ok();
+    let recent = queue.recent_completed(RECENT_PRINTS).await.unwrap_or_default();
+
+    StatusView {
+        printers,
+        queue: health,
+        recent,
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn status_class(status: Option<&str>) -> &'static str {
+    match status {
+        Some("online") => "up",
+        Some("paper_low") => "warn",
+        None => "unknown",
+        Some(_) => "down",
+    }
+}
+
+/// Render the big-screen HTML page
+pub fn render(view: &StatusView) -> String {
+    let printers: String = view
+        .printers
+        .iter()
+        .map(|p| {
+            format!(
+                r#"<div class="tile {}"><div class="name">{}</div><div class="sub">{} &middot; {}</div></div>"#,
+                status_class(p.status.as_deref()),
+                escape(&p.name),
+                escape(p.station.as_deref().unwrap_or("all stations")),
+                escape(&p.status.as_deref().unwrap_or("waiting for status").replace('_', " ")),
+            )
+        })
+        .collect();
+
+    let queue = match &view.queue {
+        Some(q) => format!(
+            r#"<div class="tile {}"><div class="name">{} waiting</div><div class="sub">{} printing &middot; oldest {}s</div></div>"#,
+            match q.state {
+                Saturation::Normal => "up",
+                Saturation::Elevated => "warn",
+                Saturation::Saturated => "down",
+            },
+            q.pending,
+            q.printing,
+            q.oldest_pending_secs,
+        ),
+        None => r#"<div class="tile unknown"><div class="name">Queue unavailable</div></div>"#.to_string(),
+    };
+
+    let recent: String = if view.recent.is_empty() {
+        r#"<tr><td colspan="3" class="sub">Nothing printed yet</td></tr>"#.to_string()
+    } else {
+        view.recent
+            .iter()
+            .map(|r| {
+                let time = venue_time::at_secs(r.completed_at)
+                    .map(|t| t.format("%H:%M").to_string())
+                    .unwrap_or_default();
+                let table = r.table_number.as_deref().map(|t| format!(" &middot; table {}", escape(t)));
+                format!(
+                    "<tr><td>{}</td><td>{}{}</td><td>{}</td></tr>",
+                    time,
+                    escape(&r.order_number),
+                    table.unwrap_or_default(),
+                    escape(&r.station),
+                )
+            })
+            .collect()
+    };
+
+    let alerts: String = view
+        .alerts()
+        .iter()
+        .map(|a| format!(r#"<div class="alert">{}</div>"#, escape(a)))
+        .collect();
+    let alerts = if alerts.is_empty() {
+        r#"<div class="ok">All printers OK</div>"#.to_string()
+    } else {
+        alerts
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta http-equiv="refresh" content="{refresh}">
+<title>Print status</title>
+<style>
+body {{ margin: 0; padding: 2vw; background: #111; color: #eee; font: 2.2vw/1.3 system-ui, sans-serif; }}
+h2 {{ margin: 1.5vw 0 0.8vw; font-size: 1.6vw; text-transform: uppercase; color: #888; }}
+.grid {{ display: grid; grid-template-columns: repeat(auto-fill, minmax(22vw, 1fr)); gap: 1vw; }}
+.tile {{ padding: 1.2vw; border-radius: 0.8vw; background: #333; }}
+.tile.up {{ background: #1b5e20; }} .tile.warn {{ background: #8d6e00; }} .tile.down {{ background: #b71c1c; }}
+.name {{ font-size: 2.6vw; font-weight: 700; }} .sub {{ color: #ccc; }}
+.alert {{ padding: 1vw; margin-bottom: 0.6vw; border-radius: 0.8vw; background: #b71c1c; font-weight: 700; }}
+.ok {{ padding: 1vw; border-radius: 0.8vw; background: #1b5e20; }}
+table {{ width: 100%; border-collapse: collapse; }} td {{ padding: 0.4vw 0; border-bottom: 1px solid #333; }}
+</style>
+</head>
+<body>
+<h2>Alerts</h2>
+{alerts}
+<h2>Printers</h2>
+<div class="grid">{printers}</div>
+<h2>Queue</h2>
+<div class="grid">{queue}</div>
+<h2>Last printed</h2>
+<table>{recent}</table>
+</body>
+</html>
+"#,
+        refresh = REFRESH_SECS,
+        alerts = alerts,
+        printers = printers,
+        queue = queue,
+        recent = recent,
+    )
+}
+
+/// GET /status - Big-screen print health page
+pub async fn handle_status(State(state): State<ApiState>) -> Html<String> {
+    Html(render(&collect(&state).await))
+}
+
+/// Router serving only the status page, for the LAN listener
+pub fn create_router(state: ApiState) -> Router {
+    Router::new()
+        .route("/status", get(handle_status))
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+/// Start the LAN status page listener; runs until `shutdown` resolves
+pub async fn start_status_server(
+    addr: &str,
+    state: ApiState,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let router = create_router(state);
+
+    info!("Starting venue status page on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown)
+        .await
+        .map_err(|e| {
+            error!("Status page server error: {}", e);
+            e.into()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_escapes_and_flags_problems() {
+        let view = StatusView {
+            printers: vec![
+                PrinterTile {
+                    name: "Bar <Epson>".to_string(),
+                    station: Some("bar".to_string()),
+                    status: Some("paper_out".to_string()),
+                },
+                PrinterTile {
+                    name: "Kitchen".to_string(),
+                    station: None,
+                    status: Some("online".to_string()),
+                },
+            ],
+            queue: Some(QueueHealth::new(250, 1, 30)),
+            recent: vec![RecentPrint {
+                order_number: "R001-0042".to_string(),
+                station: "bar".to_string(),
+                printer_id: Some("p1".to_string()),
+                table_number: Some("5".to_string()),
+                completed_at: 1_700_000_000,
+            }],
+        };
+
+        assert_eq!(
+            view.alerts(),
+            vec!["Bar <Epson>: out of paper".to_string(), "Print queue is backed up".to_string()]
+        );
+
+        let html = render(&view);
+        assert!(html.contains("Bar &lt;Epson&gt;"));
+        assert!(!html.contains("<Epson>"));
+        assert!(html.contains("R001-0042 &middot; table 5"));
+        assert!(html.contains(r#"<div class="tile down"><div class="name">250 waiting"#));
+    }
+}
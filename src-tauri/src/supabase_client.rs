@@ -1,3 +1,4 @@
+use crate::edge_metrics;
 use crate::errors::{DaemonError, Result};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -29,6 +30,8 @@ pub struct PollResult {
     pub jobs: Vec<serde_json::Value>,
     /// Map of primary_printer_id → [backup_printer_ids], refreshed periodically
     pub failover_config: Option<std::collections::HashMap<String, Vec<String>>>,
+    /// Elevated logging asked for by support (`log_level.rs`)
+    pub log_level: Option<crate::log_level::LogLevelRequest>,
 }
 
 impl SupabaseClient {
@@ -221,12 +224,35 @@ impl SupabaseClient {
     ///
     /// Sends: Authorization: Bearer {anon_key} (Supabase gateway)
     ///        X-Printer-Token: {auth_token} (our custom JWT)
+    ///
+    /// Latency and outcome are recorded per action (`edge_metrics.rs`).
     async fn edge_call(&self, action: &str, payload: serde_json::Value) -> Result<serde_json::Value> {
         let token = self.auth_token.as_ref()
             .ok_or_else(|| DaemonError::Config("No auth_token configured. Generate one from POS Devices page.".into()))?;
 
+        let started = std::time::Instant::now();
+        let result = self.post_edge(token, action, payload).await;
+        let outcome = match &result {
+            Ok(_) => edge_metrics::Outcome::Ok,
+            // The only config error once a token is present: the 401 below
+            Err(DaemonError::Config(_)) => edge_metrics::Outcome::Unauthorized,
+            Err(_) => edge_metrics::Outcome::Error,
+        };
+        edge_metrics::record(action, started.elapsed(), outcome);
+        result
+    }
+
+    async fn post_edge(&self, token: &str, action: &str, payload: serde_json::Value) -> Result<serde_json::Value> {
         let url = format!("{}/functions/v1/printer-daemon-api", self.base_url);
 
+        if crate::fault_injection::edge_error() {
+            warn!("Edge Function '{}' failed: 500 (injected)", action);
+            return Err(DaemonError::Network(format!(
+                "Edge Function '{}' failed: 500 Internal Server Error - injected fault",
+                action
+            )));
+        }
+
         let response = self
             .client
             .post(&url)
@@ -280,6 +306,39 @@ impl SupabaseClient {
         Ok(())
     }
 
+    /// Sync printers' lifetime stats (`printer_stats.rs`) to their printer rows
+    pub async fn report_printer_stats(
+        &self,
+        restaurant_id: &str,
+        stats: &[crate::printer_stats::PrinterStats],
+    ) -> Result<()> {
+        self.edge_call("printer-stats", json!({
+            "restaurant_id": restaurant_id,
+            "printers": stats,
+        }))
+        .await?;
+
+        debug!("Synced stats of {} printers", stats.len());
+        Ok(())
+    }
+
+    /// Sync printer-down incidents (`incidents.rs`); the backend keys them on restaurant,
+    /// printer and start, so a resent incident updates its row
+    pub async fn report_printer_incidents(
+        &self,
+        restaurant_id: &str,
+        incidents: &[crate::incidents::Incident],
+    ) -> Result<()> {
+        self.edge_call("printer-incidents", json!({
+            "restaurant_id": restaurant_id,
+            "incidents": incidents,
+        }))
+        .await?;
+
+        debug!("Synced {} printer incidents", incidents.len());
+        Ok(())
+    }
+
     /// Update print job status via Edge Function
     pub async fn update_job_status(
         &self,
@@ -287,6 +346,7 @@ impl SupabaseClient {
         status: &str,
         error_message: Option<&str>,
         print_duration_ms: Option<u64>,
+        correlation_id: Option<&str>,
     ) -> Result<()> {
         debug!("Updating job {} status to '{}'", job_id, status);
 
@@ -294,6 +354,9 @@ impl SupabaseClient {
             "job_id": job_id,
             "status": status,
         });
+        if let Some(correlation_id) = correlation_id {
+            payload["correlation_id"] = json!(correlation_id);
+        }
 
         if let Some(err) = error_message {
             payload["error_message"] = json!(err);
@@ -308,6 +371,49 @@ impl SupabaseClient {
         Ok(())
     }
 
+    /// Report a job as completed with its delivery phase: `sent` (written to the printer)
+    /// or `printed` (printer confirmed it), each with a timestamp
+    pub async fn update_job_delivered(
+        &self,
+        job_id: &str,
+        print_duration_ms: u64,
+        delivery: &crate::status::Delivery,
+        correlation_id: &str,
+    ) -> Result<()> {
+        let rfc3339 = |ms: i64| chrono::DateTime::from_timestamp_millis(ms).map(|t| t.to_rfc3339());
+        let payload = json!({
+            "job_id": job_id,
+            "status": crate::status::COMPLETED,
+            "print_duration_ms": print_duration_ms,
+            "delivery": delivery.phase(),
+            "sent_at": rfc3339(delivery.sent_at_ms),
+            "printed_at": delivery.printed_at_ms.and_then(rfc3339),
+            "correlation_id": correlation_id,
+        });
+
+        self.edge_call("update-job-status", payload).await?;
+
+        debug!("Job {} reported {}", job_id, delivery.phase());
+        Ok(())
+    }
+
+    /// Report that every station ticket of an order has printed, so the POS can move the
+    /// order on
+    pub async fn report_order_printed(&self, order: &crate::queue::OrderView) -> Result<()> {
+        debug!("Reporting order {} fully printed", order.order_id);
+
+        self.edge_call("order-printed", json!({
+            "order_id": order.order_id,
+            "stations": order.stations_printed,
+            "job_ids": order.jobs.iter().map(|j| j.id.as_str()).collect::<Vec<_>>(),
+            "completed_at": order.completed_at
+                .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+                .map(|t| t.to_rfc3339()),
+        })).await?;
+
+        Ok(())
+    }
+
     /// Insert a record into print_jobs_log via Edge Function
     pub async fn insert_job_log(
         &self,
@@ -317,8 +423,10 @@ impl SupabaseClient {
         station_id: Option<&str>,
         status: &str,
         error_message: Option<&str>,
+        error_class: Option<&str>,
         print_duration_ms: Option<u64>,
         retry_count: i32,
+        notes: &[String],
     ) -> Result<()> {
         debug!("Inserting job log: status={}", status);
 
@@ -339,9 +447,15 @@ impl SupabaseClient {
         if let Some(err) = error_message {
             payload["error_message"] = json!(err);
         }
+        if let Some(class) = error_class {
+            payload["error_class"] = json!(class);
+        }
         if let Some(ms) = print_duration_ms {
             payload["print_duration_ms"] = json!(ms as i64);
         }
+        if !notes.is_empty() {
+            payload["notes"] = json!(notes);
+        }
 
         self.edge_call("insert-job-log", payload).await?;
 
@@ -374,6 +488,8 @@ impl SupabaseClient {
 
         let error_details: Option<&str> = if hw_status.cutter_error {
             Some("cutter_error")
+        } else if hw_status.unrecoverable_error {
+            Some("unrecoverable_error")
         } else if hw_status.error {
             Some("general_error")
         } else {
@@ -398,27 +514,81 @@ impl SupabaseClient {
         Ok(())
     }
 
+    /// Report the results of a printer self-test run (scheduled morning check or manual)
+    pub async fn report_self_test(&self, results: &[crate::self_test::SelfTestResult]) -> Result<()> {
+        let passed = results.iter().all(|r| r.passed);
+        debug!("Reporting self-test of {} printers (passed: {})", results.len(), passed);
+
+        self.edge_call("report-self-test", json!({
+            "passed": passed,
+            "results": results,
+        })).await?;
+
+        Ok(())
+    }
+
+    /// Upload a support bundle (zip) via Edge Function so support can fetch it
+    /// without the venue having to email files around. Returns the server-side reference.
+    pub async fn upload_support_bundle(&self, file_name: &str, contents: &[u8]) -> Result<Option<String>> {
+        use base64::Engine;
+
+        info!("Uploading support bundle {} ({} bytes)", file_name, contents.len());
+
+        let response = self.edge_call("upload-support-bundle", json!({
+            "file_name": file_name,
+            "size_bytes": contents.len(),
+            "content_base64": base64::engine::general_purpose::STANDARD.encode(contents),
+        })).await?;
+
+        Ok(response["bundle_id"].as_str().map(|s| s.to_string()))
+    }
+
     /// Poll for pending print jobs via Edge Function (without failover config).
     /// If `printer_ids` is non-empty, piggybacks a heartbeat update.
     /// Prefer `poll_pending_jobs_with_failover()` for full functionality.
     #[allow(dead_code)]
     pub async fn poll_pending_jobs(&self, printer_ids: &[String]) -> Result<Vec<serde_json::Value>> {
-        let result = self.poll_pending_jobs_with_failover(printer_ids, false).await?;
+        let result = self.poll_pending_jobs_with_failover(printer_ids, false, None, None, None, None).await?;
         Ok(result.jobs)
     }
 
     /// Poll for pending jobs, optionally including failover config.
     /// When `include_failover` is true, the response includes a failover_config map
     /// of primary_printer_id → [backup_printer_ids].
+    /// `queue` piggybacks the local queue depth/saturation so the backend can apply back-pressure.
+    /// `resources` piggybacks the daemon's latest CPU / memory sample (see `resources.rs`).
+    /// `restarts` piggybacks how the last run ended and this week's restarts (see `restarts.rs`).
+    /// `payload_counts` piggybacks job payload versions and unknown fields seen (see `payload_version.rs`).
+    /// The telemetry mode always rides along, so the backend knows why the others may be missing,
+    /// and so does a running log level elevation.
     pub async fn poll_pending_jobs_with_failover(
         &self,
         printer_ids: &[String],
         include_failover: bool,
+        queue: Option<&crate::queue::QueueHealth>,
+        resources: Option<&crate::resources::ResourceUsage>,
+        restarts: Option<&crate::restarts::RestartSummary>,
+        payload_counts: Option<&crate::payload_version::PayloadCounts>,
     ) -> Result<PollResult> {
-        let mut payload = json!({});
+        let mut payload = json!({ "telemetry": crate::telemetry::mode() });
+        if let Some(elevation) = crate::log_level::current() {
+            payload["log_level"] = json!(elevation);
+        }
         if !printer_ids.is_empty() {
             payload["printer_ids"] = json!(printer_ids);
         }
+        if let Some(queue) = queue {
+            payload["queue"] = json!(queue);
+        }
+        if let Some(resources) = resources {
+            payload["resources"] = json!(resources);
+        }
+        if let Some(restarts) = restarts {
+            payload["restarts"] = json!(restarts);
+        }
+        if let Some(counts) = payload_counts {
+            payload["payload"] = json!(counts);
+        }
         if include_failover {
             payload["include_failover_config"] = json!(true);
         }
@@ -448,7 +618,15 @@ impl SupabaseClient {
             })
         });
 
-        Ok(PollResult { jobs, failover_config })
+        let log_level = result.get("log_level").and_then(|v| match serde_json::from_value(v.clone()) {
+            Ok(request) => Some(request),
+            Err(e) => {
+                warn!("Ignoring malformed log_level in poll response: {}", e);
+                None
+            }
+        });
+
+        Ok(PollResult { jobs, failover_config, log_level })
     }
 }
 
@@ -0,0 +1,188 @@
+use crate::config::AppConfig;
+use crate::errors::{DaemonError, Result};
+use crate::sentry_init;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Max bytes of app.log included in a bundle (tail of the file)
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Placeholder written instead of secret values
+const REDACTED: &str = "[REDACTED]";
+
+/// A single file inside the support bundle
+pub struct BundleEntry {
+    pub name: String,
+    pub contents: Vec<u8>,
+}
+
+impl BundleEntry {
+    pub fn json(name: &str, value: &serde_json::Value) -> Self {
+        Self {
+            name: name.to_string(),
+            contents: serde_json::to_vec_pretty(value).unwrap_or_default(),
+        }
+    }
+
+    pub fn text(name: &str, text: String) -> Self {
+        Self {
+            name: name.to_string(),
+            contents: text.into_bytes(),
+        }
+    }
+}
+
+/// Serialize the config with auth token and keys replaced by a placeholder.
+/// Printer addresses stay: support needs them to diagnose network issues.
+pub fn redact_config(config: &AppConfig) -> serde_json::Value {
+    let mut value = serde_json::to_value(config).unwrap_or_default();
+    for key in ["auth_token", "supabase_anon_key"] {
+        if let Some(field) = value.get_mut(key) {
+            if !field.is_null() {
+                *field = serde_json::Value::String(REDACTED.to_string());
+            }
+        }
+    }
+    if let Some(secret) = value.pointer_mut("/order_links/secret").filter(|s| !s.is_null()) {
+        *secret = serde_json::Value::String(REDACTED.to_string());
+    }
+    for pointer in ["/webhooks/platforms", "/api_signing/clients"] {
+        if let Some(entries) = value.pointer_mut(pointer).and_then(|p| p.as_array_mut()) {
+            for entry in entries {
+                entry["secret"] = serde_json::Value::String(REDACTED.to_string());
+            }
+        }
+    }
+    value
+}
+
+/// Read the tail of the log file (at most `MAX_LOG_BYTES`), with secrets redacted
+pub fn read_log_tail(log_path: &Path) -> String {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = match std::fs::File::open(log_path) {
+        Ok(f) => f,
+        Err(e) => return format!("Failed to open {}: {}", log_path.display(), e),
+    };
+
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    if len > MAX_LOG_BYTES {
+        let _ = file.seek(SeekFrom::Start(len - MAX_LOG_BYTES));
+    }
+
+    let mut buf = Vec::new();
+    if let Err(e) = file.read_to_end(&mut buf) {
+        return format!("Failed to read {}: {}", log_path.display(), e);
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    // Drop the (probably partial) first line when we started mid-file
+    let text = if len > MAX_LOG_BYTES {
+        text.split_once('\n').map(|(_, rest)| rest).unwrap_or(&text)
+    } else {
+        &text
+    };
+
+    text.lines()
+        .map(sentry_init::redact_secrets)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Version / platform information for the bundle
+pub fn version_info(uptime_secs: u64) -> serde_json::Value {
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "uptime_secs": uptime_secs,
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Default bundle location: next to app.log, timestamped
+pub fn bundle_path(dir: &Path) -> PathBuf {
+    let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+    dir.join(format!("support-bundle-{}.zip", stamp))
+}
+
+/// Write all entries into a zip archive at `path`
+pub fn write_bundle(path: &Path, entries: &[BundleEntry]) -> Result<u64> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let file = std::fs::File::create(path)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for entry in entries {
+        zip.start_file(entry.name.as_str(), options)
+            .map_err(|e| DaemonError::Other(anyhow::anyhow!("Failed to add {} to bundle: {}", entry.name, e)))?;
+        zip.write_all(&entry.contents)?;
+    }
+
+    zip.finish()
+        .map_err(|e| DaemonError::Other(anyhow::anyhow!("Failed to finalize bundle: {}", e)))?;
+
+    let size = std::fs::metadata(path)?.len();
+    info!("Support bundle written: {} ({} bytes, {} files)", path.display(), size, entries.len());
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_config_hides_secrets() {
+        let config = AppConfig {
+            auth_token: Some("secret-token".to_string()),
+            order_links: crate::order_links::OrderLinkConfig {
+                secret: Some("order-link-secret".to_string()),
+                ..Default::default()
+            },
+            api_signing: crate::request_signing::ApiSigningConfig {
+                clients: vec![crate::request_signing::ApiClient {
+                    id: "pos-1".to_string(),
+                    name: "Front counter".to_string(),
+                    secret: "pos-signing-secret".to_string(),
+                }],
+                ..Default::default()
+            },
+            ..AppConfig::default()
+        };
+
+        let redacted = redact_config(&config);
+        let text = redacted.to_string();
+
+        assert!(!text.contains("secret-token"));
+        assert!(!text.contains("order-link-secret"));
+        assert!(!text.contains("pos-signing-secret"));
+        assert_eq!(redacted["api_signing"]["clients"][0]["id"], "pos-1");
+        assert!(!text.contains(&config.supabase_anon_key));
+        assert_eq!(redacted["auth_token"], REDACTED);
+        assert_eq!(redacted["supabase_url"], config.supabase_url);
+    }
+
+    #[test]
+    fn test_write_bundle_creates_zip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = bundle_path(dir.path());
+
+        let entries = vec![
+            BundleEntry::text("app.log", "line 1\nline 2".to_string()),
+            BundleEntry::json("version.json", &version_info(42)),
+        ];
+
+        let size = write_bundle(&path, &entries).unwrap();
+        assert!(size > 0);
+
+        let archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert!(names.contains(&"app.log"));
+        assert!(names.contains(&"version.json"));
+    }
+}
@@ -0,0 +1,256 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{AcquireError, MutexGuard, OwnedMutexGuard, Semaphore, SemaphorePermit};
+use tracing::warn;
+
+/// Samples kept per series for the percentile (the last few minutes of a busy task)
+const RECENT_SAMPLES: usize = 256;
+
+/// Waits longer than this are logged: at that point printing visibly stalls
+const SLOW_WAIT: Duration = Duration::from_secs(1);
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    ticks: BTreeMap::new(),
+    locks: BTreeMap::new(),
+    semaphores: BTreeMap::new(),
+});
+
+struct Registry {
+    ticks: BTreeMap<&'static str, Series>,
+    locks: BTreeMap<&'static str, Series>,
+    semaphores: BTreeMap<&'static str, Series>,
+}
+
+/// Durations of one thing measured over and over
+#[derive(Default)]
+struct Series {
+    count: u64,
+    total_us: u64,
+    max_us: u64,
+    /// Microseconds, newest last
+    recent: VecDeque<u64>,
+}
+
+impl Series {
+    fn record(&mut self, elapsed: Duration) {
+        let us = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        self.count += 1;
+        self.total_us = self.total_us.saturating_add(us);
+        self.max_us = self.max_us.max(us);
+        if self.recent.len() == RECENT_SAMPLES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(us);
+    }
+
+    fn summary(&self) -> DurationSummary {
+        let mut recent: Vec<u64> = self.recent.iter().copied().collect();
+        recent.sort_unstable();
+        let p95 = recent
+            .get((recent.len() * 95 / 100).min(recent.len().saturating_sub(1)))
+            .copied()
+            .unwrap_or(0);
+        DurationSummary {
+            count: self.count,
+            avg_ms: ms(self.total_us.checked_div(self.count).unwrap_or(0)),
+            p95_ms: ms(p95),
+            max_ms: ms(self.max_us),
+            last_ms: ms(self.recent.back().copied().unwrap_or(0)),
+        }
+    }
+}
+
+fn ms(us: u64) -> f64 {
+    (us as f64 / 1000.0 * 100.0).round() / 100.0
+}
+
+/// Summary of one series (`get_metrics` → `tasks`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DurationSummary {
+    pub count: u64,
+    pub avg_ms: f64,
+    /// Over the last `RECENT_SAMPLES` samples
+    pub p95_ms: f64,
+    /// Since start
+    pub max_ms: f64,
+    pub last_ms: f64,
+}
+
+/// Tokio scheduler state
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuntimeSnapshot {
+    pub workers: usize,
+    pub alive_tasks: usize,
+    /// Tasks waiting in the shared queue for a free worker: above zero for long means
+    /// the workers are busy (or blocked)
+    pub global_queue_depth: usize,
+}
+
+/// Background task timings, sent with `get_metrics` and on /api/metrics
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskMetrics {
+    /// One loop iteration of each long-lived task
+    pub ticks: BTreeMap<String, DurationSummary>,
+    /// Time spent waiting for shared locks
+    pub lock_waits: BTreeMap<String, DurationSummary>,
+    /// Time spent waiting for semaphore permits
+    pub semaphore_waits: BTreeMap<String, DurationSummary>,
+    pub runtime: Option<RuntimeSnapshot>,
+}
+
+fn with_registry(f: impl FnOnce(&mut Registry)) {
+    f(&mut REGISTRY.lock().unwrap_or_else(|e| e.into_inner()));
+}
+
+/// Times one iteration of a background task until dropped. Take it right after the
+/// task's interval fired, so idle time isn't counted.
+#[must_use = "the tick is recorded when the guard is dropped"]
+pub struct Tick {
+    task: &'static str,
+    started: Instant,
+}
+
+impl Drop for Tick {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        with_registry(|r| r.ticks.entry(self.task).or_default().record(elapsed));
+    }
+}
+
+pub fn tick(task: &'static str) -> Tick {
+    Tick { task, started: Instant::now() }
+}
+
+/// Lock a shared mutex, recording how long it took
+pub async fn lock<'a, T>(mutex: &'a tokio::sync::Mutex<T>, name: &'static str) -> MutexGuard<'a, T> {
+    let started = Instant::now();
+    let guard = mutex.lock().await;
+    record_wait(name, started.elapsed(), |r| &mut r.locks);
+    guard
+}
+
+/// `lock` for a mutex shared through an `Arc`, when the guard must outlive the borrow
+pub async fn lock_owned<T>(mutex: Arc<tokio::sync::Mutex<T>>, name: &'static str) -> OwnedMutexGuard<T> {
+    let started = Instant::now();
+    let guard = mutex.lock_owned().await;
+    record_wait(name, started.elapsed(), |r| &mut r.locks);
+    guard
+}
+
+/// Acquire a semaphore permit, recording how long it took
+pub async fn acquire<'a>(semaphore: &'a Semaphore, name: &'static str) -> Result<SemaphorePermit<'a>, AcquireError> {
+    let started = Instant::now();
+    let permit = semaphore.acquire().await;
+    record_wait(name, started.elapsed(), |r| &mut r.semaphores);
+    permit
+}
+
+fn record_wait(name: &'static str, elapsed: Duration, series: fn(&mut Registry) -> &mut BTreeMap<&'static str, Series>) {
+    if elapsed >= SLOW_WAIT {
+        warn!("Waited {:?} for {}", elapsed, name);
+    }
+    with_registry(|r| series(r).entry(name).or_default().record(elapsed));
+}
+
+/// Current summaries and runtime state
+pub fn snapshot() -> TaskMetrics {
+    let registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    let summarize = |series: &BTreeMap<&'static str, Series>| {
+        series.iter().map(|(name, s)| (name.to_string(), s.summary())).collect()
+    };
+    TaskMetrics {
+        ticks: summarize(&registry.ticks),
+        lock_waits: summarize(&registry.locks),
+        semaphore_waits: summarize(&registry.semaphores),
+        runtime: tokio::runtime::Handle::try_current().ok().map(|h| {
+            let metrics = h.metrics();
+            RuntimeSnapshot {
+                workers: metrics.num_workers(),
+                alive_tasks: metrics.num_alive_tasks(),
+                global_queue_depth: metrics.global_queue_depth(),
+            }
+        }),
+    }
+}
+
+/// Prometheus text for the task metrics: summaries in seconds, labelled by name
+pub fn export_prometheus(metrics: &TaskMetrics) -> String {
+    let mut out = String::new();
+    let families = [
+        ("daemon_task_tick_seconds", "Duration of one background task iteration", "task", &metrics.ticks),
+        ("daemon_lock_wait_seconds", "Time spent waiting for a shared lock", "lock", &metrics.lock_waits),
+        ("daemon_semaphore_wait_seconds", "Time spent waiting for a semaphore permit", "semaphore", &metrics.semaphore_waits),
+    ];
+    for (name, help, label, series) in families {
+        if series.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n# HELP {name} {help}\n# TYPE {name} summary\n"));
+        for (key, s) in series {
+            out.push_str(&format!("{name}{{{label}=\"{key}\",quantile=\"0.95\"}} {}\n", s.p95_ms / 1000.0));
+            out.push_str(&format!("{name}_sum{{{label}=\"{key}\"}} {}\n", s.avg_ms * s.count as f64 / 1000.0));
+            out.push_str(&format!("{name}_count{{{label}=\"{key}\"}} {}\n", s.count));
+        }
+        out.push_str(&format!("\n# HELP {name}_max Longest since start\n# TYPE {name}_max gauge\n"));
+        for (key, s) in series {
+            out.push_str(&format!("{name}_max{{{label}=\"{key}\"}} {}\n", s.max_ms / 1000.0));
+        }
+    }
+    if let Some(runtime) = &metrics.runtime {
+        let gauges = [
+            ("tokio_workers", "Tokio worker threads", runtime.workers),
+            ("tokio_global_queue_depth", "Tasks waiting in the tokio global queue", runtime.global_queue_depth),
+        ];
+        for (name, help, value) in gauges {
+            out.push_str(&format!("\n# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}\n"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series_summary() {
+        let mut series = Series::default();
+        for ms in 1..=100u64 {
+            series.record(Duration::from_millis(ms));
+        }
+        let summary = series.summary();
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.avg_ms, 50.5);
+        assert_eq!(summary.p95_ms, 96.0);
+        assert_eq!(summary.max_ms, 100.0);
+        assert_eq!(summary.last_ms, 100.0);
+
+        for _ in 0..RECENT_SAMPLES {
+            series.record(Duration::from_millis(2));
+        }
+        let summary = series.summary();
+        assert_eq!(summary.p95_ms, 2.0);
+        assert_eq!(summary.max_ms, 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_waits_and_ticks_recorded() {
+        let mutex = tokio::sync::Mutex::new(0);
+        let semaphore = Semaphore::new(1);
+        {
+            let _tick = tick("test_task");
+            *lock(&mutex, "test_lock").await += 1;
+            let _permit = acquire(&semaphore, "test_slots").await.unwrap();
+        }
+        let metrics = snapshot();
+        assert!(metrics.ticks["test_task"].count >= 1);
+        assert!(metrics.lock_waits["test_lock"].count >= 1);
+        assert!(metrics.semaphore_waits["test_slots"].count >= 1);
+
+        let text = export_prometheus(&metrics);
+        assert!(text.contains("daemon_task_tick_seconds_count{task=\"test_task\"}"));
+        assert!(text.contains("# TYPE daemon_lock_wait_seconds summary"));
+    }
+}
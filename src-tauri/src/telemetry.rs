@@ -1,8 +1,68 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+
+/// Process-wide mode, read by Sentry's filters, the poll heartbeat and the reporter
+static MODE: AtomicU8 = AtomicU8::new(0);
+
+/// Histogram bucket bounds (seconds) for print durations
+const DURATION_BUCKETS: [f64; 8] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// How much the daemon reports about itself (`AppConfig.telemetry_mode`). Local metrics
+/// (dashboard, `/metrics`) are kept in every mode; only what leaves the machine changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TelemetryMode {
+    /// Sentry errors with breadcrumbs and traces, resource and payload stats in heartbeats
+    #[default]
+    Full,
+    /// Sentry errors only (no breadcrumbs, traces or host name), restart history in heartbeats
+    ErrorsOnly,
+    /// Nothing beyond what printing needs (printer ids and queue depth)
+    Off,
+}
+
+impl TelemetryMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::ErrorsOnly => "errors_only",
+            Self::Off => "off",
+        }
+    }
+
+    /// Usage data: breadcrumbs, traces, resource samples, payload counts, periodic reports
+    pub fn shares_usage(self) -> bool {
+        self == Self::Full
+    }
+
+    /// Error reports and restart history
+    pub fn shares_errors(self) -> bool {
+        self != Self::Off
+    }
+}
+
+/// Current telemetry mode
+pub fn mode() -> TelemetryMode {
+    match MODE.load(Ordering::Relaxed) {
+        0 => TelemetryMode::Full,
+        1 => TelemetryMode::ErrorsOnly,
+        _ => TelemetryMode::Off,
+    }
+}
+
+pub fn set_mode(mode: TelemetryMode) {
+    let value = match mode {
+        TelemetryMode::Full => 0,
+        TelemetryMode::ErrorsOnly => 1,
+        TelemetryMode::Off => 2,
+    };
+    MODE.store(value, Ordering::Relaxed);
+}
 
 /// Telemetry event types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +76,8 @@ pub enum TelemetryEvent {
         printer_id: String,
         duration_ms: u64,
         retry_count: u32,
+        #[serde(default)]
+        correlation_id: Option<String>,
     },
     /// Print job failed
     PrintJobFailed {
@@ -25,6 +87,8 @@ pub enum TelemetryEvent {
         printer_id: Option<String>,
         error: String,
         retry_count: u32,
+        #[serde(default)]
+        correlation_id: Option<String>,
     },
     /// Printer status changed
     PrinterStatusChanged {
@@ -56,6 +120,62 @@ pub enum TelemetryEvent {
         active_connections: usize,
         stale_removed: usize,
     },
+    /// Printer failure rate / latency drifted away from its baseline (early warning)
+    PrinterDegrading {
+        printer_id: String,
+        reason: String,
+        failure_rate: f64,
+        baseline_failure_rate: f64,
+        avg_latency_ms: u64,
+        baseline_latency_ms: u64,
+    },
+    /// Printer returned to its baseline after a degradation alert
+    PrinterDegradationCleared {
+        printer_id: String,
+    },
+    /// Printer was sidelined (quarantined) or returned to rotation
+    PrinterQuarantineChanged {
+        printer_id: String,
+        quarantined: bool,
+        /// "repeated_trips", "manual", "probes_passed" or "released"
+        reason: String,
+    },
+    /// Daily rollup: minutes a printer's breaker was open or half-open on a finished business day
+    PrinterDegradedMinutes {
+        printer_id: String,
+        business_date: String,
+        minutes: u64,
+    },
+    /// Network printer was found under a new address and its config was updated
+    PrinterAddressChanged {
+        printer_id: String,
+        old_address: String,
+        new_address: String,
+        /// How it was matched: "mac" or "model"
+        method: String,
+    },
+    /// Maintenance mode started or ended
+    MaintenanceChanged {
+        active: bool,
+        reason: Option<String>,
+    },
+    /// Queue saturation state changed (back-pressure signal)
+    QueueSaturationChanged {
+        /// "normal", "elevated" or "saturated"
+        state: String,
+        pending: u64,
+        oldest_pending_secs: u64,
+    },
+    /// A job arrived with a station that matches no printer station or alias
+    StationUnmatched {
+        job_id: String,
+        /// As sent upstream
+        received: String,
+        /// As queued (normalized)
+        station: String,
+    },
+    /// The daemon's own CPU / memory / descriptor / task use (sampled every minute)
+    ResourceUsage(crate::resources::ResourceUsage),
     /// Queue statistics snapshot
     QueueSnapshot {
         pending: usize,
@@ -84,6 +204,14 @@ pub struct TelemetryMetrics {
     pub printers_offline: usize,
     /// Circuit breakers open
     pub circuit_breakers_open: usize,
+    /// Breaker state per printer ("closed", "open", "half_open"), from the transitions seen
+    #[serde(default)]
+    pub circuit_breakers: BTreeMap<String, String>,
+    /// Pooled network printer connections, as of the last pool cleanup
+    #[serde(default)]
+    pub connection_pool_size: usize,
+    /// Latest resource sample of the daemon process
+    pub resources: Option<crate::resources::ResourceUsage>,
     /// Last update timestamp
     pub last_update_ts: u64,
 }
@@ -99,6 +227,9 @@ impl Default for TelemetryMetrics {
             printers_online: 0,
             printers_offline: 0,
             circuit_breakers_open: 0,
+            circuit_breakers: BTreeMap::new(),
+            connection_pool_size: 0,
+            resources: None,
             last_update_ts: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap_or_default()
@@ -115,6 +246,10 @@ pub struct TelemetryCollector {
     event_history: Arc<RwLock<Vec<(u64, TelemetryEvent)>>>,
     /// Print duration samples (for averaging, max 1000)
     print_durations: Arc<RwLock<Vec<u64>>>,
+    /// All print durations since start, bucketed for `/metrics`
+    duration_histogram: Arc<RwLock<DurationHistogram>>,
+    /// Last hardware status per printer ("online", "offline", "paper_out", ...)
+    printer_status: Arc<RwLock<HashMap<String, String>>>,
 }
 
 impl TelemetryCollector {
@@ -125,6 +260,8 @@ impl TelemetryCollector {
             metrics: Arc::new(RwLock::new(TelemetryMetrics::default())),
             event_history: Arc::new(RwLock::new(Vec::new())),
             print_durations: Arc::new(RwLock::new(Vec::new())),
+            duration_histogram: Arc::new(RwLock::new(DurationHistogram::default())),
+            printer_status: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -156,6 +293,7 @@ impl TelemetryCollector {
 
                 let sum: u64 = durations.iter().sum();
                 metrics.avg_print_duration_ms = sum / durations.len() as u64;
+                self.duration_histogram.write().await.observe(*duration_ms);
 
                 // Update success rate
                 let total = metrics.total_jobs_completed + metrics.total_jobs_failed;
@@ -193,18 +331,19 @@ impl TelemetryCollector {
                 metrics.queue_depth = *pending + *processing;
                 debug!("Queue snapshot - Depth: {}, Pending: {}, Processing: {}", metrics.queue_depth, pending, processing);
             }
-            TelemetryEvent::CircuitBreakerStateChanged { new_state, .. } => {
-                // Count open circuit breakers
-                // Note: This is simplified, in production you'd maintain a map of all breakers
-                if new_state == "open" {
+            TelemetryEvent::CircuitBreakerStateChanged { printer_id, old_state, new_state } => {
+                metrics.circuit_breakers.insert(printer_id.clone(), new_state.clone());
+                // Count breakers that are not closed (open or testing recovery)
+                if old_state == "closed" && new_state != "closed" {
                     metrics.circuit_breakers_open += 1;
-                } else if new_state == "closed" {
+                } else if old_state != "closed" && new_state == "closed" {
                     metrics.circuit_breakers_open = metrics.circuit_breakers_open.saturating_sub(1);
                 }
                 debug!("Circuit breakers open: {}", metrics.circuit_breakers_open);
             }
             TelemetryEvent::PrinterStatusChanged { printer_id, old_status, new_status } => {
                 debug!("Printer {} status: {} → {}", printer_id, old_status, new_status);
+                self.printer_status.write().await.insert(printer_id.clone(), new_status.clone());
             }
             TelemetryEvent::FailoverAttempted { job_id, primary_printer_id, backup_printer_id, success } => {
                 if *success {
@@ -219,7 +358,37 @@ impl TelemetryCollector {
                     );
                 }
             }
+            TelemetryEvent::PrinterDegrading { printer_id, reason, .. } => {
+                debug!("Printer {} degrading: {}", printer_id, reason);
+            }
+            TelemetryEvent::PrinterQuarantineChanged { printer_id, quarantined, reason } => {
+                if *quarantined {
+                    warn!("Printer {} quarantined ({})", printer_id, reason);
+                } else {
+                    info!("Printer {} back in rotation ({})", printer_id, reason);
+                }
+            }
+            TelemetryEvent::PrinterDegradedMinutes { printer_id, business_date, minutes } => {
+                info!("Printer {} breaker degraded {} min on {}", printer_id, minutes, business_date);
+            }
+            TelemetryEvent::PrinterAddressChanged { printer_id, old_address, new_address, method } => {
+                info!("Printer {} moved {} → {} (matched by {})", printer_id, old_address, new_address, method);
+            }
+            TelemetryEvent::QueueSaturationChanged { state, pending, oldest_pending_secs } => {
+                warn!(
+                    "Queue saturation now {} ({} pending, oldest waiting {}s)",
+                    state, pending, oldest_pending_secs
+                );
+            }
+            TelemetryEvent::ResourceUsage(usage) => {
+                debug!(
+                    "Resources - CPU: {:?}%, RSS: {:?} bytes, FDs: {:?}, tasks: {}",
+                    usage.cpu_percent, usage.rss_bytes, usage.open_fds, usage.tokio_tasks
+                );
+                metrics.resources = Some(usage.clone());
+            }
             TelemetryEvent::ConnectionPoolStats { active_connections, stale_removed } => {
+                metrics.connection_pool_size = *active_connections;
                 debug!("Connection pool: {} active, {} stale removed", active_connections, stale_removed);
             }
             _ => {}
@@ -228,6 +397,18 @@ impl TelemetryCollector {
         metrics.last_update_ts = timestamp;
         drop(metrics);
 
+        // Low-resource mode: routine snapshots only update the metrics above
+        if crate::low_resource::is_enabled()
+            && matches!(
+                event,
+                TelemetryEvent::QueueSnapshot { .. }
+                    | TelemetryEvent::ConnectionPoolStats { .. }
+                    | TelemetryEvent::ResourceUsage(_)
+            )
+        {
+            return;
+        }
+
         // Store event in history
         let mut history = self.event_history.write().await;
         history.push((timestamp, event));
@@ -243,6 +424,11 @@ impl TelemetryCollector {
         self.metrics.read().await.clone()
     }
 
+    /// Last known hardware status per printer (printers not polled yet are missing)
+    pub async fn printer_statuses(&self) -> HashMap<String, String> {
+        self.printer_status.read().await.clone()
+    }
+
     /// Get event history (last N events)
     pub async fn get_event_history(&self, limit: usize) -> Vec<(u64, TelemetryEvent)> {
         let history = self.event_history.read().await;
@@ -250,10 +436,22 @@ impl TelemetryCollector {
         history[start..].to_vec()
     }
 
-    /// Get metrics summary as JSON
+    /// Get metrics summary as JSON, with background task timings under `tasks` and
+    /// Edge Function call metrics under `edge`
     pub async fn get_metrics_json(&self) -> serde_json::Value {
         let metrics = self.get_metrics().await;
-        serde_json::to_value(&metrics).unwrap_or_default()
+        let mut value = serde_json::to_value(&metrics).unwrap_or_default();
+        if let Some(map) = value.as_object_mut() {
+            map.insert(
+                "tasks".to_string(),
+                serde_json::to_value(crate::task_metrics::snapshot()).unwrap_or_default(),
+            );
+            map.insert(
+                "edge".to_string(),
+                serde_json::to_value(crate::edge_metrics::snapshot()).unwrap_or_default(),
+            );
+        }
+        value
     }
 
     /// Reset all metrics (for testing)
@@ -267,6 +465,7 @@ impl TelemetryCollector {
 
         let mut durations = self.print_durations.write().await;
         durations.clear();
+        *self.duration_histogram.write().await = DurationHistogram::default();
 
         info!("Telemetry metrics reset");
     }
@@ -283,7 +482,7 @@ impl TelemetryCollector {
     pub async fn export_prometheus(&self) -> String {
         let metrics = self.get_metrics().await;
 
-        format!(
+        let mut out = format!(
             "# HELP printer_jobs_completed_total Total number of completed print jobs\n\
              # TYPE printer_jobs_completed_total counter\n\
              printer_jobs_completed_total {}\n\
@@ -323,7 +522,107 @@ impl TelemetryCollector {
             metrics.printers_online,
             metrics.printers_offline,
             metrics.circuit_breakers_open,
-        )
+        );
+
+        // Per printer: 0 closed, 1 half open (testing recovery), 2 open
+        out.push_str(
+            "\n# HELP printer_circuit_breaker_state Circuit breaker state per printer (0 closed, 1 half open, 2 open)\n\
+             # TYPE printer_circuit_breaker_state gauge\n",
+        );
+        for (printer_id, state) in &metrics.circuit_breakers {
+            let value = match state.as_str() {
+                "open" => 2,
+                "half_open" => 1,
+                _ => 0,
+            };
+            out.push_str(&format!("printer_circuit_breaker_state{{printer_id={:?}}} {}\n", printer_id, value));
+        }
+
+        out.push_str(&format!(
+            "\n# HELP printer_connection_pool_size Pooled network printer connections\n\
+             # TYPE printer_connection_pool_size gauge\n\
+             printer_connection_pool_size {}\n",
+            metrics.connection_pool_size
+        ));
+
+        out.push_str(&self.duration_histogram.read().await.export_prometheus());
+
+        // Daemon process gauges, only those the platform reports
+        if let Some(usage) = metrics.resources {
+            let gauges = [
+                ("daemon_cpu_percent", "Share of machine CPU used by the daemon", usage.cpu_percent.map(|c| c as f64)),
+                ("daemon_rss_bytes", "Resident memory of the daemon", usage.rss_bytes.map(|b| b as f64)),
+                ("daemon_open_fds", "Open file descriptors (handles on Windows)", usage.open_fds.map(|n| n as f64)),
+                ("daemon_tokio_tasks", "Alive tokio tasks", Some(usage.tokio_tasks as f64)),
+            ];
+            for (name, help, value) in gauges {
+                if let Some(value) = value {
+                    out.push_str(&format!("\n# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}\n"));
+                }
+            }
+        }
+
+        // Job payload drift: versions newer than this daemon, fields no mapping reads
+        if let Some(counts) = crate::payload_version::counts() {
+            out.push_str(&format!(
+                "\n# HELP printer_payload_unknown_version_total Jobs with a payload version newer than this daemon\n\
+                 # TYPE printer_payload_unknown_version_total counter\n\
+                 printer_payload_unknown_version_total {}\n",
+                counts.unknown_versions
+            ));
+            out.push_str(
+                "\n# HELP printer_payload_unknown_field_total Occurrences of job payload fields no mapping reads\n\
+                 # TYPE printer_payload_unknown_field_total counter\n",
+            );
+            for (field, count) in &counts.unknown_fields {
+                out.push_str(&format!("printer_payload_unknown_field_total{{field={:?}}} {}\n", field, count));
+            }
+        }
+
+        // Background task ticks, lock and semaphore waits, tokio scheduler
+        out.push_str(&crate::task_metrics::export_prometheus(&crate::task_metrics::snapshot()));
+        // Supabase Edge Function latency and errors per action
+        out.push_str(&crate::edge_metrics::export_prometheus());
+        out
+    }
+}
+
+/// Print durations since start, cumulative per `DURATION_BUCKETS` bound
+#[derive(Debug, Default)]
+struct DurationHistogram {
+    buckets: [u64; DURATION_BUCKETS.len()],
+    count: u64,
+    sum_ms: u64,
+}
+
+impl DurationHistogram {
+    fn observe(&mut self, duration_ms: u64) {
+        let secs = duration_ms as f64 / 1000.0;
+        for (bound, bucket) in DURATION_BUCKETS.iter().zip(self.buckets.iter_mut()) {
+            if secs <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum_ms += duration_ms;
+    }
+
+    fn export_prometheus(&self) -> String {
+        let mut out = String::from(
+            "\n# HELP printer_print_duration_seconds Time to send a job to the printer\n\
+             # TYPE printer_print_duration_seconds histogram\n",
+        );
+        for (bound, count) in DURATION_BUCKETS.iter().zip(self.buckets) {
+            out.push_str(&format!("printer_print_duration_seconds_bucket{{le=\"{}\"}} {}\n", bound, count));
+        }
+        out.push_str(&format!(
+            "printer_print_duration_seconds_bucket{{le=\"+Inf\"}} {count}\n\
+             printer_print_duration_seconds_sum {}\n\
+             printer_print_duration_seconds_count {count}\n",
+            self.sum_ms as f64 / 1000.0,
+            count = self.count,
+        ));
+        out
     }
 }
 
@@ -353,6 +652,9 @@ impl TelemetryReporter {
 
             loop {
                 interval.tick().await;
+                if !mode().shares_usage() {
+                    continue;
+                }
 
                 let metrics = collector.get_metrics().await;
 
@@ -366,6 +668,15 @@ impl TelemetryReporter {
                     metrics.printers_online,
                     metrics.printers_offline,
                 );
+                if let Some(ref usage) = metrics.resources {
+                    info!(
+                        "Telemetry Report - Daemon CPU: {} | RSS: {} | FDs: {} | Tasks: {}",
+                        usage.cpu_percent.map_or("n/a".to_string(), |c| format!("{:.1}%", c)),
+                        usage.rss_bytes.map_or("n/a".to_string(), |b| format!("{} MB", b / (1024 * 1024))),
+                        usage.open_fds.map_or("n/a".to_string(), |n| n.to_string()),
+                        usage.tokio_tasks,
+                    );
+                }
 
                 // TODO: Send to external monitoring system (Sentry, Prometheus, etc.)
                 // This is where you'd send metrics to your monitoring backend
@@ -378,6 +689,19 @@ impl TelemetryReporter {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_telemetry_mode_sharing() {
+        assert!(TelemetryMode::default().shares_usage());
+        assert!(!TelemetryMode::ErrorsOnly.shares_usage());
+        assert!(TelemetryMode::ErrorsOnly.shares_errors());
+        assert!(!TelemetryMode::Off.shares_errors());
+        assert_eq!(
+            serde_json::from_str::<TelemetryMode>("\"errors_only\"").unwrap(),
+            TelemetryMode::ErrorsOnly
+        );
+        assert_eq!(serde_json::to_value(TelemetryMode::Off).unwrap(), TelemetryMode::Off.as_str());
+    }
+
     #[tokio::test]
     async fn test_record_print_job_completed() {
         let collector = TelemetryCollector::new();
@@ -390,6 +714,7 @@ mod tests {
                 printer_id: "printer_1".to_string(),
                 duration_ms: 150,
                 retry_count: 0,
+                correlation_id: None,
             })
             .await;
 
@@ -411,6 +736,7 @@ mod tests {
                 printer_id: Some("printer_2".to_string()),
                 error: "Printer offline".to_string(),
                 retry_count: 3,
+                correlation_id: None,
             })
             .await;
 
@@ -433,6 +759,7 @@ mod tests {
                     printer_id: "printer_1".to_string(),
                     duration_ms: 100,
                     retry_count: 0,
+                    correlation_id: None,
                 })
                 .await;
         }
@@ -445,6 +772,7 @@ mod tests {
                 printer_id: Some("printer_1".to_string()),
                 error: "Test error".to_string(),
                 retry_count: 3,
+                correlation_id: None,
             })
             .await;
 
@@ -468,6 +796,7 @@ mod tests {
                     printer_id: "printer_1".to_string(),
                     duration_ms: 100,
                     retry_count: 0,
+                    correlation_id: None,
                 })
                 .await;
         }
@@ -490,10 +819,20 @@ mod tests {
                 printer_id: "printer_1".to_string(),
                 duration_ms: 200,
                 retry_count: 0,
+                correlation_id: None,
             })
             .await;
 
         collector.update_printer_counts(2, 1).await;
+        collector
+            .record_event(TelemetryEvent::ResourceUsage(crate::resources::ResourceUsage {
+                cpu_percent: None,
+                rss_bytes: Some(50_000_000),
+                open_fds: Some(40),
+                tokio_tasks: 31,
+                sampled_at: 0,
+            }))
+            .await;
 
         let prometheus = collector.export_prometheus().await;
 
@@ -501,5 +840,44 @@ mod tests {
         assert!(prometheus.contains("printer_avg_duration_ms 200"));
         assert!(prometheus.contains("printer_online 2"));
         assert!(prometheus.contains("printer_offline 1"));
+        assert!(prometheus.contains("daemon_rss_bytes 50000000"));
+        assert!(prometheus.contains("daemon_tokio_tasks 31"));
+        assert!(!prometheus.contains("daemon_cpu_percent"));
+    }
+
+    #[tokio::test]
+    async fn test_export_prometheus_histogram_and_breakers() {
+        let collector = TelemetryCollector::new();
+        for (i, duration_ms) in [80, 400, 3000].into_iter().enumerate() {
+            collector
+                .record_event(TelemetryEvent::PrintJobCompleted {
+                    job_id: format!("job_{}", i),
+                    order_number: format!("R001-000{}", i),
+                    station: "bar".to_string(),
+                    printer_id: "printer_1".to_string(),
+                    duration_ms,
+                    retry_count: 0,
+                    correlation_id: None,
+                })
+                .await;
+        }
+        collector
+            .record_event(TelemetryEvent::CircuitBreakerStateChanged {
+                printer_id: "printer_2".to_string(),
+                old_state: "closed".to_string(),
+                new_state: "open".to_string(),
+            })
+            .await;
+        collector
+            .record_event(TelemetryEvent::ConnectionPoolStats { active_connections: 2, stale_removed: 1 })
+            .await;
+
+        let out = collector.export_prometheus().await;
+        assert!(out.contains("printer_print_duration_seconds_bucket{le=\"0.1\"} 1\n"));
+        assert!(out.contains("printer_print_duration_seconds_bucket{le=\"0.5\"} 2\n"));
+        assert!(out.contains("printer_print_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.contains("printer_print_duration_seconds_sum 3.48\n"));
+        assert!(out.contains("printer_circuit_breaker_state{printer_id=\"printer_2\"} 2\n"));
+        assert!(out.contains("printer_connection_pool_size 2\n"));
     }
 }
@@ -0,0 +1,474 @@
+use crate::errors::{DaemonError, Result};
+use crate::escpos::{LinePricing, PrintItem, MAX_COPIES};
+use crate::queue::{priority, JobKind, PrintJob};
+use crate::status;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Configurable normalization of upstream order payloads into `PrintJob`s.
+///
+/// Each canonical field lists the source paths tried in order (first non-empty wins), so a
+/// new upstream shape is a config change instead of a code change. Defaults accept the
+/// Supabase `print_jobs` row shape plus common aliases.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformRules {
+    pub fields: FieldMappings,
+    pub item_fields: ItemFieldMappings,
+    pub modifiers: ModifierRules,
+    /// Station inferred from item categories when the payload has none
+    pub station_rules: Vec<StationRule>,
+    /// Last resort when neither the payload nor `station_rules` yield a station
+    pub default_station: Option<String>,
+}
+
+/// Job-level source paths (see `select` for the path syntax)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldMappings {
+    pub id: Vec<String>,
+    pub order_id: Vec<String>,
+    pub order_number: Vec<String>,
+    pub station: Vec<String>,
+    pub station_id: Vec<String>,
+    pub printer_id: Vec<String>,
+    pub items: Vec<String>,
+    pub table_number: Vec<String>,
+    pub customer_name: Vec<String>,
+    pub order_type: Vec<String>,
+    pub priority: Vec<String>,
+    pub timestamp: Vec<String>,
+    pub copies: Vec<String>,
+    pub correlation_id: Vec<String>,
+    /// Unix ms or RFC 3339
+    pub pickup_time: Vec<String>,
+    /// "order", "void" or "amend"
+    pub kind: Vec<String>,
+    pub original_job_id: Vec<String>,
+    /// Amend: items taken off the order (same item shape as `items`)
+    pub removed_items: Vec<String>,
+    /// Every station the order fans out to (string array; `*` to expand it)
+    pub order_stations: Vec<String>,
+}
+
+fn paths(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+impl Default for FieldMappings {
+    fn default() -> Self {
+        Self {
+            id: paths(&["id"]),
+            order_id: paths(&["order_id"]),
+            order_number: paths(&["order_number"]),
+            station: paths(&["station"]),
+            station_id: paths(&["station_id"]),
+            printer_id: paths(&["printer_id"]),
+            items: paths(&["items"]),
+            table_number: paths(&["table_number"]),
+            customer_name: paths(&["customer_name"]),
+            order_type: paths(&["order_type"]),
+            priority: paths(&["priority"]),
+            timestamp: paths(&["timestamp"]),
+            copies: paths(&["copies"]),
+            correlation_id: paths(&["correlation_id", "metadata/correlation_id"]),
+            pickup_time: paths(&["pickup_time", "metadata/pickup_time"]),
+            kind: paths(&["kind", "job_type"]),
+            original_job_id: paths(&["original_job_id", "metadata/original_job_id"]),
+            removed_items: paths(&["removed_items"]),
+            order_stations: paths(&["order_stations/*", "metadata/order_stations/*"]),
+        }
+    }
+}
+
+/// Item-level source paths, relative to each item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemFieldMappings {
+    pub name: Vec<String>,
+    pub quantity: Vec<String>,
+    pub modifiers: Vec<String>,
+    pub notes: Vec<String>,
+    pub category: Vec<String>,
+    pub image_url: Vec<String>,
+    /// Amounts below are in currency units (12.50), tax included
+    pub unit_price: Vec<String>,
+    pub line_total: Vec<String>,
+    pub discount: Vec<String>,
+    pub weight_grams: Vec<String>,
+    /// Percent (9, 21)
+    pub tax_rate: Vec<String>,
+}
+
+impl Default for ItemFieldMappings {
+    fn default() -> Self {
+        Self {
+            name: paths(&["name", "title"]),
+            quantity: paths(&["quantity", "qty", "count"]),
+            modifiers: paths(&["modifiers", "options", "selected_modifier_groups"]),
+            notes: paths(&["notes", "note", "special_instructions", "remark"]),
+            category: paths(&["category", "category_name", "course"]),
+            image_url: paths(&["image_url", "image", "thumbnail_url"]),
+            unit_price: paths(&["unit_price", "price"]),
+            line_total: paths(&["line_total", "total_price"]),
+            discount: paths(&["discount", "discount_amount"]),
+            weight_grams: paths(&["weight_grams", "weight_g"]),
+            tax_rate: paths(&["tax_rate", "vat_rate"]),
+        }
+    }
+}
+
+/// How nested modifier structures are flattened into print lines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModifierRules {
+    /// Keys holding a modifier's display name
+    pub name_fields: Vec<String>,
+    /// Keys holding nested modifiers (groups → options)
+    pub children_fields: Vec<String>,
+    /// Keys holding a modifier quantity; >1 is printed as "2x Name"
+    pub quantity_fields: Vec<String>,
+    /// Print "Group: Option" instead of just "Option"
+    pub prefix_group_name: bool,
+}
+
+impl Default for ModifierRules {
+    fn default() -> Self {
+        Self {
+            name_fields: paths(&["name", "title", "label"]),
+            children_fields: paths(&["options", "selected_items", "items", "modifiers"]),
+            quantity_fields: paths(&["quantity", "qty"]),
+            prefix_group_name: false,
+        }
+    }
+}
+
+impl ModifierRules {
+    /// Flatten strings, `{name}` objects and arbitrarily nested groups into print lines
+    pub fn flatten(&self, value: &Value) -> Vec<String> {
+        self.flatten_inner(value, None)
+    }
+
+    fn flatten_inner(&self, value: &Value, group: Option<&str>) -> Vec<String> {
+        match value {
+            Value::String(s) if !s.is_empty() => vec![with_group(group, s)],
+            Value::Array(items) => items.iter().flat_map(|v| self.flatten_inner(v, group)).collect(),
+            Value::Object(_) => {
+                let name = first_string(value, &self.name_fields);
+                let children = self
+                    .children_fields
+                    .iter()
+                    .find_map(|key| value.get(key.as_str()).filter(|c| c.is_array()));
+
+                if let Some(children) = children {
+                    let group = if self.prefix_group_name { name.as_deref().or(group) } else { group };
+                    return self.flatten_inner(children, group);
+                }
+
+                let Some(name) = name else {
+                    return Vec::new();
+                };
+                let quantity = first_string(value, &self.quantity_fields)
+                    .and_then(|q| q.parse::<u32>().ok())
+                    .unwrap_or(1);
+                let line = if quantity > 1 { format!("{}x {}", quantity, name) } else { name };
+                vec![with_group(group, &line)]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn with_group(group: Option<&str>, line: &str) -> String {
+    match group {
+        Some(g) => format!("{}: {}", g, line),
+        None => line.to_string(),
+    }
+}
+
+/// Maps item categories to a station (case-insensitive)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StationRule {
+    pub categories: Vec<String>,
+    pub station: String,
+}
+
+impl TransformRules {
+    /// First station rule matching any of the item categories, in item order
+    pub fn infer_station(&self, categories: &[String]) -> Option<String> {
+        categories.iter().find_map(|category| {
+            self.station_rules
+                .iter()
+                .find(|rule| rule.categories.iter().any(|c| c.eq_ignore_ascii_case(category)))
+                .map(|rule| rule.station.clone())
+        })
+    }
+
+    /// Parse one upstream item
+    fn parse_item(&self, item: &Value) -> Result<(PrintItem, Option<String>)> {
+        let fields = &self.item_fields;
+        let name = first_string(item, &fields.name)
+            .ok_or_else(|| DaemonError::Queue(format!("Item without name: {}", item)))?;
+        let quantity = first_string(item, &fields.quantity)
+            .and_then(|q| q.parse::<u32>().ok())
+            .unwrap_or(1);
+        let modifiers = fields
+            .modifiers
+            .iter()
+            .find_map(|path| select(item, path).into_iter().next())
+            .map(|m| self.modifiers.flatten(m))
+            .unwrap_or_default();
+
+        Ok((
+            PrintItem {
+                quantity,
+                name,
+                modifiers,
+                notes: first_string(item, &fields.notes),
+                image_url: first_string(item, &fields.image_url),
+                pricing: self.parse_pricing(item),
+            },
+            first_string(item, &fields.category),
+        ))
+    }
+
+    /// Prices and tax of one upstream item, None when it carries none
+    fn parse_pricing(&self, item: &Value) -> Option<LinePricing> {
+        let fields = &self.item_fields;
+        let number = |paths: &[String]| first_string(item, paths).and_then(|s| s.parse::<f64>().ok());
+        let cents = |paths: &[String]| number(paths).map(|amount| (amount * 100.0).round() as i64);
+
+        let pricing = LinePricing {
+            unit_price_cents: cents(&fields.unit_price),
+            weight_grams: number(&fields.weight_grams).map(|g| g.round() as u32),
+            line_total_cents: cents(&fields.line_total),
+            tax_rate: number(&fields.tax_rate),
+            discount_cents: cents(&fields.discount),
+        };
+        (pricing != LinePricing::default()).then_some(pricing)
+    }
+
+    /// Parse an items array (or single item) into print items plus their categories
+    pub fn parse_items(&self, items: &Value) -> Result<(Vec<PrintItem>, Vec<String>)> {
+        let list: Vec<&Value> = match items {
+            Value::Array(list) => list.iter().collect(),
+            other => vec![other],
+        };
+
+        let mut parsed = Vec::with_capacity(list.len());
+        let mut categories = Vec::new();
+        for item in list {
+            let (item, category) = self.parse_item(item)?;
+            parsed.push(item);
+            categories.extend(category);
+        }
+        Ok((parsed, categories))
+    }
+
+    /// Normalize an upstream job record into a `PrintJob`
+    pub fn to_print_job(&self, record: &Value, restaurant_id: &str) -> Result<PrintJob> {
+        let f = &self.fields;
+        let field = |paths: &[String]| first_string(record, paths);
+
+        let id = field(&f.id).ok_or_else(|| DaemonError::Queue("Missing job id".to_string()))?;
+        let order_number = field(&f.order_number)
+            .ok_or_else(|| DaemonError::Queue("Missing order_number".to_string()))?;
+
+        let items_json = f
+            .items
+            .iter()
+            .find_map(|path| select(record, path).into_iter().next())
+            .ok_or_else(|| DaemonError::Queue("Missing items".to_string()))?;
+        let (items, categories) = self.parse_items(items_json)?;
+        let removed_items = match f.removed_items.iter().flat_map(|path| select(record, path)).find(|v| !v.is_null()) {
+            Some(removed) => self.parse_items(removed)?.0,
+            None => Vec::new(),
+        };
+
+        let order_stations = f
+            .order_stations
+            .iter()
+            .map(|path| select(record, path).into_iter().filter_map(Value::as_str).map(str::to_string).collect::<Vec<_>>())
+            .find(|stations| !stations.is_empty())
+            .unwrap_or_default();
+
+        let station = field(&f.station)
+            .or_else(|| self.infer_station(&categories))
+            .or_else(|| self.default_station.clone())
+            .ok_or_else(|| DaemonError::Queue("Missing station".to_string()))?;
+
+        Ok(PrintJob {
+            id,
+            restaurant_id: restaurant_id.to_string(),
+            order_id: field(&f.order_id),
+            order_number,
+            station,
+            station_id: field(&f.station_id),
+            printer_id: field(&f.printer_id),
+            items,
+            table_number: field(&f.table_number),
+            customer_name: field(&f.customer_name),
+            order_type: field(&f.order_type),
+            priority: field(&f.priority)
+                .and_then(|p| p.parse::<u8>().ok())
+                .unwrap_or(priority::NORMAL),
+            timestamp: field(&f.timestamp)
+                .and_then(|t| t.parse::<i64>().ok())
+                .unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+            status: status::PENDING.to_string(),
+            retry_count: 0,
+            error_message: None,
+            copies: field(&f.copies)
+                .and_then(|c| c.parse::<u8>().ok())
+                .unwrap_or(1)
+                .clamp(1, MAX_COPIES),
+            // Empty: generated at enqueue
+            correlation_id: field(&f.correlation_id).unwrap_or_default(),
+            pickup_time: field(&f.pickup_time).and_then(|t| {
+                t.parse::<i64>()
+                    .ok()
+                    .or_else(|| chrono::DateTime::parse_from_rfc3339(&t).ok().map(|dt| dt.timestamp_millis()))
+            }),
+            kind: field(&f.kind).map_or(JobKind::Order, |kind| JobKind::from_name(&kind)),
+            original_job_id: field(&f.original_job_id),
+            removed_items,
+            order_stations,
+        })
+    }
+}
+
+/// Resolve a path against a JSON value.
+///
+/// Paths are `/`-separated keys or array indexes; `*` expands every element of an array
+/// (e.g. `selected_modifier_groups/*/selected_items/*/title`).
+pub fn select<'a>(value: &'a Value, path: &str) -> Vec<&'a Value> {
+    let mut current = vec![value];
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        let mut next = Vec::new();
+        for v in current {
+            match (segment, v) {
+                ("*", Value::Array(items)) => next.extend(items.iter()),
+                (_, Value::Object(map)) => next.extend(map.get(segment)),
+                (_, Value::Array(items)) => {
+                    if let Ok(idx) = segment.parse::<usize>() {
+                        next.extend(items.get(idx));
+                    }
+                }
+                _ => {}
+            }
+        }
+        current = next;
+    }
+    current
+}
+
+/// First value at `path` rendered as a string (numbers included)
+pub fn select_string(value: &Value, path: &str) -> Option<String> {
+    select(value, path).into_iter().find_map(|v| match v {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+/// First non-empty string among several candidate paths
+fn first_string(value: &Value, paths: &[String]) -> Option<String> {
+    paths.iter().find_map(|p| select_string(value, p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules_parse_supabase_row() {
+        let record = serde_json::json!({
+            "id": "job-1",
+            "order_id": "order-1",
+            "order_number": "R001-0042",
+            "station": "bar",
+            "printer_id": "printer-1",
+            "priority": 2,
+            "timestamp": 1700000000000i64,
+            "copies": 2,
+            "metadata": {
+                "correlation_id": "web-7f3a9c21",
+                "pickup_time": "2023-11-14T22:43:20Z",
+                "order_stations": ["bar", "kitchen"]
+            },
+            "items": [
+                { "quantity": 2, "name": "Beer", "modifiers": ["No foam"], "notes": null }
+            ]
+        });
+
+        let job = TransformRules::default().to_print_job(&record, "rest_123").unwrap();
+        assert_eq!(job.id, "job-1");
+        assert_eq!(job.station, "bar");
+        assert_eq!(job.priority, 2);
+        assert_eq!(job.timestamp, 1700000000000);
+        assert_eq!(job.copies, 2);
+        assert_eq!(job.correlation_id, "web-7f3a9c21");
+        assert_eq!(job.pickup_time, Some(1700001800000));
+        assert_eq!(job.order_stations, vec!["bar", "kitchen"]);
+        assert_eq!(job.items[0].quantity, 2);
+        assert_eq!(job.items[0].modifiers, vec!["No foam"]);
+        assert_eq!(job.items[0].notes, None);
+        assert_eq!(job.items[0].pricing, None);
+    }
+
+    #[test]
+    fn test_item_pricing_parsed_to_cents() {
+        let items = serde_json::json!([
+            { "name": "Cheese", "price": "24.90", "weight_grams": 350, "vat_rate": 9 },
+            { "name": "Wine", "quantity": 2, "unit_price": 6.5, "discount": 1, "tax_rate": 21 }
+        ]);
+
+        let (items, _) = TransformRules::default().parse_items(&items).unwrap();
+        let cheese = items[0].pricing.as_ref().unwrap();
+        assert_eq!(cheese.unit_price_cents, Some(2490));
+        assert_eq!(cheese.weight_grams, Some(350));
+        assert_eq!(cheese.tax_rate, Some(9.0));
+        assert_eq!(items[0].line_total(), Some(872));
+        assert_eq!(items[1].line_total(), Some(1200));
+    }
+
+    #[test]
+    fn test_nested_modifiers_are_flattened() {
+        let rules = ModifierRules {
+            prefix_group_name: true,
+            ..ModifierRules::default()
+        };
+        let modifiers = serde_json::json!([
+            { "name": "Sauce", "options": [{ "name": "Mayo", "quantity": 2 }, { "name": "Ketchup" }] },
+            "Extra crispy"
+        ]);
+
+        assert_eq!(
+            rules.flatten(&modifiers),
+            vec!["Sauce: 2x Mayo", "Sauce: Ketchup", "Extra crispy"]
+        );
+    }
+
+    #[test]
+    fn test_station_inferred_from_category() {
+        let rules = TransformRules {
+            station_rules: vec![
+                StationRule { categories: vec!["Drinks".to_string(), "Wine".to_string()], station: "bar".to_string() },
+                StationRule { categories: vec!["Mains".to_string()], station: "kitchen".to_string() },
+            ],
+            ..TransformRules::default()
+        };
+        let record = serde_json::json!({
+            "id": "job-2",
+            "order_number": "42",
+            "items": [{ "title": "Merlot", "qty": "1", "category": "wine" }]
+        });
+
+        let job = rules.to_print_job(&record, "rest_123").unwrap();
+        assert_eq!(job.station, "bar");
+        assert_eq!(job.items[0].name, "Merlot");
+
+        let no_station = TransformRules::default().to_print_job(&record, "rest_123");
+        assert!(no_station.is_err());
+    }
+}
@@ -0,0 +1,272 @@
+use crate::config::{ConnectionType, PrinterConfig};
+use crate::errors::{DaemonError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tracing::info;
+
+/// IPP path most printers serve (`ipp://host:631/ipp/print`)
+const IPP_PATH: &str = "/ipp/print";
+const IPP_PORT: u16 = 631;
+
+/// IPP request ids, unique per daemon run
+static IPP_REQUEST_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Epson ePOS-Print endpoint; `local_printer` is the device id of the printer itself
+const EPOS_PATH: &str = "/cgi-bin/epos/service.cgi?devid=local_printer&timeout=10000";
+
+/// A ticket going over HTTP waits at most this long (ePOS answers after printing)
+pub const HTTP_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// How the bytes of a ticket get to the printer. A printer lists the ones it accepts in
+/// `PrinterConfig.transports`, in the order they are tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transport {
+    /// The connection's own path: TCP 9100, USB bulk or BLE writes
+    Raw,
+    /// IPP Print-Job with the ESC/POS bytes as the document (network printers)
+    Ipp,
+    /// Epson ePOS-Print over HTTP, the bytes as a hex `<command>` (network printers)
+    Epos,
+}
+
+impl Transport {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Transport::Raw => "raw",
+            Transport::Ipp => "ipp",
+            Transport::Epos => "epos",
+        }
+    }
+}
+
+impl PrinterConfig {
+    /// Transports to try, in configured order. IPP and ePOS need a network printer and
+    /// are dropped on others; an empty list means raw only.
+    pub fn transport_ladder(&self) -> Vec<Transport> {
+        let network = matches!(self.connection_type, ConnectionType::Network);
+        let mut ladder: Vec<Transport> = Vec::new();
+        for transport in &self.transports {
+            if (network || *transport == Transport::Raw) && !ladder.contains(transport) {
+                ladder.push(*transport);
+            }
+        }
+        if ladder.is_empty() {
+            ladder.push(Transport::Raw);
+        }
+        ladder
+    }
+}
+
+/// Last transport that delivered a ticket, per printer. The next ticket starts there so
+/// a printer that only works over IPP doesn't pay the raw timeout every time. This is
+/// about one printer's paths; moving a job to another printer is failover's job.
+#[derive(Debug, Default)]
+pub struct TransportMemory {
+    working: HashMap<String, Transport>,
+}
+
+impl TransportMemory {
+    /// The printer's ladder with the last working transport moved to the front
+    pub fn order(&self, printer: &PrinterConfig) -> Vec<Transport> {
+        let mut ladder = printer.transport_ladder();
+        if let Some(working) = self.working.get(&printer.id) {
+            if let Some(pos) = ladder.iter().position(|t| t == working) {
+                let working = ladder.remove(pos);
+                ladder.insert(0, working);
+            }
+        }
+        ladder
+    }
+
+    /// Remember what worked
+    pub fn succeeded(&mut self, printer_id: &str, transport: Transport) {
+        let previous = self.working.insert(printer_id.to_string(), transport);
+        if previous.is_some_and(|p| p != transport) {
+            info!("Printer {} now prints over {}", printer_id, transport.as_str());
+        }
+    }
+
+    /// Last working transport per printer (`get_printer_transports`)
+    pub fn snapshot(&self) -> HashMap<String, Transport> {
+        self.working.clone()
+    }
+
+    pub fn forget(&mut self, printer_id: &str) {
+        self.working.remove(printer_id);
+    }
+}
+
+/// Host part of a network printer address ("192.168.1.50:9100" → "192.168.1.50")
+fn host(address: &str) -> &str {
+    address.rsplit_once(':').map(|(host, _)| host).unwrap_or(address)
+}
+
+/// IPP/1.1 Print-Job request with `data` as the document
+pub fn ipp_print_job(printer_uri: &str, request_id: u32, job_name: &str, data: &[u8]) -> Vec<u8> {
+    fn attribute(buf: &mut Vec<u8>, tag: u8, name: &str, value: &str) {
+        buf.push(tag);
+        buf.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        buf.extend_from_slice(name.as_bytes());
+        buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        buf.extend_from_slice(value.as_bytes());
+    }
+
+    let mut buf = Vec::with_capacity(256 + data.len());
+    buf.extend_from_slice(&[0x01, 0x01]); // version 1.1
+    buf.extend_from_slice(&0x0002u16.to_be_bytes()); // Print-Job
+    buf.extend_from_slice(&request_id.to_be_bytes());
+    buf.push(0x01); // operation-attributes-tag
+    attribute(&mut buf, 0x47, "attributes-charset", "utf-8");
+    attribute(&mut buf, 0x48, "attributes-natural-language", "en");
+    attribute(&mut buf, 0x45, "printer-uri", printer_uri);
+    attribute(&mut buf, 0x42, "requesting-user-name", "eatsome-printer-daemon");
+    attribute(&mut buf, 0x42, "job-name", job_name);
+    attribute(&mut buf, 0x49, "document-format", "application/octet-stream");
+    buf.push(0x03); // end-of-attributes-tag
+    buf.extend_from_slice(data);
+    buf
+}
+
+/// Status code of an IPP response; 0x0000-0x00FF are the successful ones
+pub fn ipp_status(response: &[u8]) -> Option<u16> {
+    response.get(2..4).map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+/// ePOS-Print SOAP envelope sending `data` unchanged
+pub fn epos_request(data: &[u8]) -> String {
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="utf-8"?>"#,
+            r#"<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/"><s:Body>"#,
+            r#"<epos-print xmlns="http://www.epson-pos.com/schemas/2011/03/epos-print">"#,
+            "<command>{}</command></epos-print></s:Body></s:Envelope>"
+        ),
+        hex::encode(data)
+    )
+}
+
+/// Outcome of an ePOS-Print response: Ok when printed, else the printer's error code
+pub fn epos_result(body: &str) -> std::result::Result<(), String> {
+    if body.contains(r#"success="true""#) {
+        return Ok(());
+    }
+    let code = body
+        .split_once(r#"code=""#)
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .map(|(code, _)| code)
+        .filter(|code| !code.is_empty())
+        .unwrap_or("no success in response");
+    Err(code.to_string())
+}
+
+/// Send a ticket with IPP Print-Job. Only says the printer accepted the job.
+pub async fn send_ipp(client: &reqwest::Client, address: &str, job_name: &str, data: &[u8]) -> Result<()> {
+    let host = host(address);
+    let uri = format!("ipp://{}:{}{}", host, IPP_PORT, IPP_PATH);
+    let request = ipp_print_job(&uri, IPP_REQUEST_ID.fetch_add(1, Ordering::Relaxed), job_name, data);
+    let response = client
+        .post(format!("http://{}:{}{}", host, IPP_PORT, IPP_PATH))
+        .header(reqwest::header::CONTENT_TYPE, "application/ipp")
+        .timeout(HTTP_TIMEOUT)
+        .body(request)
+        .send()
+        .await
+        .map_err(|e| DaemonError::Network(format!("IPP to {}: {}", host, e)))?;
+    if !response.status().is_success() {
+        return Err(DaemonError::Network(format!("IPP to {}: HTTP {}", host, response.status())));
+    }
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| DaemonError::Network(format!("IPP to {}: {}", host, e)))?;
+    match ipp_status(&body) {
+        Some(status) if status < 0x0100 => Ok(()),
+        Some(status) => Err(DaemonError::PrintJob(format!("IPP Print-Job refused by {} (status 0x{:04x})", host, status))),
+        None => Err(DaemonError::Network(format!("IPP to {}: truncated response", host))),
+    }
+}
+
+/// Send a ticket with ePOS-Print. The printer answers once it printed, so success is a
+/// confirmed print.
+pub async fn send_epos(client: &reqwest::Client, address: &str, data: &[u8]) -> Result<()> {
+    let host = host(address);
+    let response = client
+        .post(format!("http://{}{}", host, EPOS_PATH))
+        .header(reqwest::header::CONTENT_TYPE, "text/xml; charset=utf-8")
+        .header("SOAPAction", "\"\"")
+        .timeout(HTTP_TIMEOUT)
+        .body(epos_request(data))
+        .send()
+        .await
+        .map_err(|e| DaemonError::Network(format!("ePOS to {}: {}", host, e)))?;
+    if !response.status().is_success() {
+        return Err(DaemonError::Network(format!("ePOS to {}: HTTP {}", host, response.status())));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| DaemonError::Network(format!("ePOS to {}: {}", host, e)))?;
+    epos_result(&body).map_err(|code| DaemonError::PrintJob(format!("ePOS print failed on {}: {}", host, code)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PrinterCapabilities;
+
+    fn printer(connection_type: ConnectionType, transports: Vec<Transport>) -> PrinterConfig {
+        PrinterConfig {
+            id: "p1".to_string(),
+            name: "Kitchen".to_string(),
+            connection_type,
+            address: "192.168.1.50:9100".to_string(),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: None,
+            is_primary: true,
+            capabilities: PrinterCapabilities { cutter: true, drawer: false, qrcode: true, max_width: 48 },
+            paper_width_checked: true,
+            recovery: Default::default(),
+            compact: None,
+            transports,
+            serial: None,
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn test_ladder_order_and_memory() {
+        let usb = printer(ConnectionType::USB, vec![Transport::Ipp, Transport::Raw]);
+        assert_eq!(usb.transport_ladder(), vec![Transport::Raw]);
+        assert_eq!(printer(ConnectionType::Network, vec![]).transport_ladder(), vec![Transport::Raw]);
+
+        let network = printer(ConnectionType::Network, vec![Transport::Raw, Transport::Ipp, Transport::Raw, Transport::Epos]);
+        let mut memory = TransportMemory::default();
+        assert_eq!(memory.order(&network), vec![Transport::Raw, Transport::Ipp, Transport::Epos]);
+        memory.succeeded("p1", Transport::Epos);
+        assert_eq!(memory.order(&network), vec![Transport::Epos, Transport::Raw, Transport::Ipp]);
+
+        // Removed from the config: back to the configured order
+        let raw_only = printer(ConnectionType::Network, vec![Transport::Raw]);
+        assert_eq!(memory.order(&raw_only), vec![Transport::Raw]);
+    }
+
+    #[test]
+    fn test_ipp_and_epos_encoding() {
+        let request = ipp_print_job("ipp://10.0.0.5:631/ipp/print", 7, "42", &[0x1b, 0x40]);
+        assert_eq!(&request[..8], &[0x01, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x07]);
+        assert!(request.ends_with(&[0x03, 0x1b, 0x40]));
+        assert_eq!(ipp_status(&[0x01, 0x01, 0x00, 0x00, 0, 0, 0, 7]), Some(0x0000));
+        assert_eq!(ipp_status(&[0x01]), None);
+
+        assert!(epos_request(&[0x1b, 0x40]).contains("<command>1b40</command>"));
+        assert_eq!(epos_result(r#"<response success="true" code="" status="251658262"/>"#), Ok(()));
+        assert_eq!(
+            epos_result(r#"<response success="false" code="EPTR_COVER_OPEN" status="0"/>"#),
+            Err("EPTR_COVER_OPEN".to_string())
+        );
+    }
+}
@@ -0,0 +1,374 @@
+use crate::config::{ConnectionType, PrinterConfig};
+use crate::errors::DaemonError;
+use crate::i18n::{LocalizedMessage, Locale, MessageKey};
+use crate::printer::PrinterManager;
+use crate::serial;
+use crate::status::PrinterHwStatus;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tracing::info;
+
+/// Printer web UI port, probed next to the print port: when the print port doesn't
+/// answer, a web UI that does (even refusing) shows the host is up
+const WEB_UI_PORT: u16 = 80;
+
+/// Timeout for each TCP probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Failure classes the guided flow knows how to walk staff through
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureClass {
+    /// Host does not answer at all (powered off, wrong IP, other network)
+    Unreachable,
+    /// Host is up but the print port refuses connections
+    ConnectionRefused,
+    /// Connection or write timed out
+    Timeout,
+    PaperOut,
+    CoverOpen,
+    CutterError,
+    PrinterError,
+    /// USB device present but this process may not open it
+    UsbPermission,
+    /// USB device not attached / not enumerated
+    UsbNotFound,
+    /// No adapter or peripheral not advertising
+    BluetoothUnavailable,
+    /// Everything checked out but the test print still failed
+    PrintFailed,
+}
+
+/// Individual diagnostic checks, run in this order
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStep {
+    /// Is the device there at all (host answers / USB enumerated / BLE adapter)
+    Reachability,
+    /// Can we open the print channel (TCP port / USB handle)
+    PortProbe,
+    /// DLE EOT hardware status
+    StatusQuery,
+    /// Actual test receipt
+    TestPrint,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepOutcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepResult {
+    pub step: CheckStep,
+    pub outcome: StepOutcome,
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+/// Result of a troubleshooting run: what was checked, where it stopped, what staff should do next
+#[derive(Debug, Clone, Serialize)]
+pub struct TroubleshootReport {
+    pub printer_id: String,
+    pub steps: Vec<StepResult>,
+    pub failure: Option<FailureClass>,
+    pub next_action: LocalizedMessage,
+    pub resolved: bool,
+}
+
+/// Classify a transport/print error into a failure class
+pub fn classify_error(err: &DaemonError) -> FailureClass {
+    if let DaemonError::Usb(e) = err {
+        return match e {
+            rusb::Error::Access | rusb::Error::Busy => FailureClass::UsbPermission,
+            rusb::Error::NoDevice | rusb::Error::NotFound => FailureClass::UsbNotFound,
+            rusb::Error::Timeout => FailureClass::Timeout,
+            _ => FailureClass::PrintFailed,
+        };
+    }
+
+    let msg = err.to_string().to_lowercase();
+    if msg.contains("usb permission") || msg.contains("usb interface busy") {
+        FailureClass::UsbPermission
+    } else if msg.contains("usb device not found") {
+        FailureClass::UsbNotFound
+    } else if matches!(err, DaemonError::Bluetooth(_)) {
+        FailureClass::BluetoothUnavailable
+    } else if matches!(err, DaemonError::Serial(_)) && msg.contains("not found") {
+        FailureClass::Unreachable
+    } else if msg.contains("refused") {
+        FailureClass::ConnectionRefused
+    } else if msg.contains("timed out") || msg.contains("timeout") {
+        FailureClass::Timeout
+    } else if msg.contains("unreachable") || msg.contains("no route") {
+        FailureClass::Unreachable
+    } else {
+        FailureClass::PrintFailed
+    }
+}
+
+/// Classify a DLE EOT status; None when the printer reports healthy
+pub fn classify_hw_status(status: &PrinterHwStatus) -> Option<FailureClass> {
+    if !status.paper_present {
+        Some(FailureClass::PaperOut)
+    } else if status.cover_open {
+        Some(FailureClass::CoverOpen)
+    } else if status.cutter_error {
+        Some(FailureClass::CutterError)
+    } else if status.error || status.unrecoverable_error || !status.online {
+        Some(FailureClass::PrinterError)
+    } else {
+        None
+    }
+}
+
+/// Next human action for a failure class
+pub fn next_action(failure: Option<&FailureClass>) -> MessageKey {
+    match failure {
+        None => MessageKey::ActionNone,
+        Some(FailureClass::Unreachable) => MessageKey::ActionUnreachable,
+        Some(FailureClass::ConnectionRefused) => MessageKey::ActionConnectionRefused,
+        Some(FailureClass::Timeout) => MessageKey::ActionTimeout,
+        Some(FailureClass::PaperOut) => MessageKey::ActionPaperOut,
+        Some(FailureClass::CoverOpen) => MessageKey::ActionCoverOpen,
+        Some(FailureClass::CutterError) => MessageKey::ActionCutterError,
+        Some(FailureClass::PrinterError) => MessageKey::ActionPrinterError,
+        Some(FailureClass::UsbPermission) => MessageKey::ActionUsbPermission,
+        Some(FailureClass::UsbNotFound) => MessageKey::ActionUsbNotFound,
+        Some(FailureClass::BluetoothUnavailable) => MessageKey::ActionBluetoothUnavailable,
+        Some(FailureClass::PrintFailed) => MessageKey::ActionPrintFailed,
+    }
+}
+
+/// TCP connect with timeout, mapped into DaemonError::Network like the print path
+async fn tcp_probe(address: &str) -> Result<(), DaemonError> {
+    match tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect(address)).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(DaemonError::Network(format!("{}: {}", address, e))),
+        Err(_) => Err(DaemonError::Network(format!("Connection timed out to {}", address))),
+    }
+}
+
+/// Runs the checks in order and records each result
+struct Runner {
+    steps: Vec<StepResult>,
+}
+
+impl Runner {
+    fn record(&mut self, step: CheckStep, outcome: StepOutcome, detail: String, started: Instant) {
+        self.steps.push(StepResult {
+            step,
+            outcome,
+            detail,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+
+    fn skip(&mut self, step: CheckStep, detail: &str) {
+        self.record(step, StepOutcome::Skipped, detail.to_string(), Instant::now());
+    }
+}
+
+/// Walk through the diagnostic checks for one printer, stopping at the first failure.
+///
+/// `test_print` is the rendered test receipt, when staff asked for one.
+pub async fn troubleshoot(
+    pm: &PrinterManager,
+    printer: &PrinterConfig,
+    test_print: Option<&[u8]>,
+    locale: Locale,
+) -> TroubleshootReport {
+    info!("Troubleshooting printer {} ({})", printer.id, printer.address);
+    let mut runner = Runner { steps: Vec::new() };
+    let failure = run_checks(pm, printer, test_print, &mut runner).await;
+
+    TroubleshootReport {
+        printer_id: printer.id.clone(),
+        steps: runner.steps,
+        next_action: LocalizedMessage::new(locale, next_action(failure.as_ref()), &[]),
+        resolved: failure.is_none(),
+        failure,
+    }
+}
+
+async fn run_checks(
+    pm: &PrinterManager,
+    printer: &PrinterConfig,
+    test_print: Option<&[u8]>,
+    runner: &mut Runner,
+) -> Option<FailureClass> {
+    // 1 + 2. Reachability and port probe (transport specific)
+    match printer.connection_type {
+        ConnectionType::Network => {
+            let host = printer.address.rsplit_once(':').map(|(h, _)| h).unwrap_or(&printer.address);
+
+            // The print port decides: a printer with its web UI off or port 80 filtered
+            // still prints. Both are probed at once so a dead host costs one timeout.
+            let started = Instant::now();
+            let (print_port, web_ui) =
+                tokio::join!(tcp_probe(&printer.address), tcp_probe(&format!("{}:{}", host, WEB_UI_PORT)));
+            // Only a timeout/unreachable means the host is gone; a refusal proves it's up
+            let answers = |probe: &Result<(), DaemonError>| match probe {
+                Ok(()) => true,
+                Err(e) => e.to_string().to_lowercase().contains("refused"),
+            };
+            if !answers(&print_port) && !answers(&web_ui) {
+                let detail = print_port.err().map(|e| e.to_string()).unwrap_or_default();
+                runner.record(CheckStep::Reachability, StepOutcome::Failed, detail, started);
+                return Some(FailureClass::Unreachable);
+            }
+            runner.record(CheckStep::Reachability, StepOutcome::Passed, format!("{} answers", host), started);
+
+            if let Err(e) = print_port {
+                let class = classify_error(&e);
+                runner.record(CheckStep::PortProbe, StepOutcome::Failed, e.to_string(), started);
+                return Some(class);
+            }
+            runner.record(CheckStep::PortProbe, StepOutcome::Passed, format!("{} accepts connections", printer.address), started);
+        }
+        ConnectionType::USB => {
+            let started = Instant::now();
+            match pm.probe_usb(&printer.address).await {
+                Ok(()) => {
+                    runner.record(CheckStep::Reachability, StepOutcome::Passed, "USB device attached".to_string(), started);
+                    runner.record(CheckStep::PortProbe, StepOutcome::Passed, "USB device can be opened".to_string(), started);
+                }
+                Err(e) => {
+                    let class = classify_error(&e);
+                    let step = if class == FailureClass::UsbPermission {
+                        runner.record(CheckStep::Reachability, StepOutcome::Passed, "USB device attached".to_string(), started);
+                        CheckStep::PortProbe
+                    } else {
+                        CheckStep::Reachability
+                    };
+                    runner.record(step, StepOutcome::Failed, e.to_string(), started);
+                    return Some(class);
+                }
+            }
+        }
+        ConnectionType::Bluetooth => {
+            // BLE has no cheap connect probe; the test print is the real check
+            runner.skip(CheckStep::Reachability, "Not available for Bluetooth printers");
+            runner.skip(CheckStep::PortProbe, "Not available for Bluetooth printers");
+        }
+        ConnectionType::Serial => {
+            // The port exists; the status query below is the first to reach the printer
+            let started = Instant::now();
+            let address = printer.address.clone();
+            if !tokio::task::spawn_blocking(move || serial::port_present(&address)).await.unwrap_or(false) {
+                runner.record(CheckStep::Reachability, StepOutcome::Failed, format!("Serial port {} not found", printer.address), started);
+                return Some(FailureClass::Unreachable);
+            }
+            runner.record(CheckStep::Reachability, StepOutcome::Passed, format!("Serial port {} present", printer.address), started);
+            runner.skip(CheckStep::PortProbe, "Not available for serial printers");
+        }
+    }
+
+    // 3. Hardware status
+    let started = Instant::now();
+    match pm.poll_status(printer).await {
+        Ok(status) => {
+            if let Some(class) = classify_hw_status(&status) {
+                runner.record(CheckStep::StatusQuery, StepOutcome::Failed, format!("Printer reports {}", status.to_status_string()), started);
+                return Some(class);
+            }
+            runner.record(CheckStep::StatusQuery, StepOutcome::Passed, format!("Printer reports {}", status.to_status_string()), started);
+        }
+        Err(e) => {
+            // Not every printer answers DLE EOT; a failed query alone isn't conclusive
+            runner.record(CheckStep::StatusQuery, StepOutcome::Skipped, format!("Status query unsupported or failed: {}", e), started);
+        }
+    }
+
+    // 4. Test print
+    let Some(commands) = test_print else {
+        runner.skip(CheckStep::TestPrint, "Test print not requested");
+        return None;
+    };
+
+    let started = Instant::now();
+    match pm.test_print(&printer.id, commands).await {
+        Ok(()) => {
+            runner.record(CheckStep::TestPrint, StepOutcome::Passed, "Test receipt sent".to_string(), started);
+            None
+        }
+        Err(e) => {
+            let class = classify_error(&e);
+            runner.record(CheckStep::TestPrint, StepOutcome::Failed, e.to_string(), started);
+            Some(class)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_errors() {
+        assert_eq!(
+            classify_error(&DaemonError::Network("192.168.1.50:9100: Connection refused (os error 111)".into())),
+            FailureClass::ConnectionRefused
+        );
+        assert_eq!(
+            classify_error(&DaemonError::Network("Connection timed out to 192.168.1.50:9100".into())),
+            FailureClass::Timeout
+        );
+        assert_eq!(classify_error(&DaemonError::Usb(rusb::Error::Access)), FailureClass::UsbPermission);
+        assert_eq!(
+            classify_error(&DaemonError::Bluetooth("Peripheral not found: AA:BB".into())),
+            FailureClass::BluetoothUnavailable
+        );
+    }
+
+    #[test]
+    fn test_classify_hw_status() {
+        assert_eq!(classify_hw_status(&PrinterHwStatus::healthy()), None);
+
+        let paper_out = PrinterHwStatus::from_dle_eot(0x00, 0x00, 0x00, 0x60);
+        assert_eq!(classify_hw_status(&paper_out), Some(FailureClass::PaperOut));
+
+        let cover_open = PrinterHwStatus::from_dle_eot(0x00, 0x04, 0x00, 0x00);
+        assert_eq!(classify_hw_status(&cover_open), Some(FailureClass::CoverOpen));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_host_stops_at_first_step() {
+        let pm = match PrinterManager::new() {
+            Ok(pm) => pm,
+            Err(_) => return, // No USB context in this environment
+        };
+        let printer = PrinterConfig {
+            id: "p1".to_string(),
+            name: "Kitchen".to_string(),
+            connection_type: ConnectionType::Network,
+            // TEST-NET-1 (RFC 5737): guaranteed not to answer
+            address: "192.0.2.1:9100".to_string(),
+            mac_address: None,
+            protocol: "escpos".to_string(),
+            station: None,
+            is_primary: true,
+            capabilities: crate::config::PrinterCapabilities {
+                cutter: true,
+                drawer: false,
+                qrcode: true,
+                max_width: 48,
+            },
+            paper_width_checked: false,
+            recovery: Default::default(),
+            compact: None,
+            transports: vec![],
+            serial: None,
+            paused: false,
+        };
+
+        let report = troubleshoot(&pm, &printer, None, Locale::En).await;
+        assert!(!report.resolved);
+        assert_eq!(report.failure, Some(FailureClass::Unreachable));
+        assert_eq!(report.steps.len(), 1);
+    }
+}
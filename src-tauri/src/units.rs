@@ -0,0 +1,195 @@
+use crate::escpos::PrintItem;
+use crate::i18n::Locale;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+/// Weights and volumes in modifiers and notes ("0.5l", "250 g", "8 fl oz"): a number
+/// followed by a unit, whole words only
+static QUANTITY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(\d+(?:[.,]\d+)?)\s?(fl\.?\s?oz|ml|cl|dl|l|kg|g|oz|lbs?)\b").expect("valid regex")
+});
+
+const ML_PER_FL_OZ: f64 = 29.5735;
+const G_PER_OZ: f64 = 28.3495;
+const G_PER_LB: f64 = 453.592;
+
+/// Units quantities on tickets are printed in (`AppConfig.unit_system`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    /// ml / l, g / kg
+    #[default]
+    Metric,
+    /// fl oz, oz / lb
+    Imperial,
+}
+
+/// How quantities are written: unit system, and the decimal separator of the venue's
+/// locale ("0,5 l" in nl, "0.5 l" in en)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UnitFormat {
+    pub locale: Locale,
+    pub system: UnitSystem,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+    Millilitre,
+    Centilitre,
+    Decilitre,
+    Litre,
+    Gram,
+    Kilogram,
+    FluidOunce,
+    Ounce,
+    Pound,
+}
+
+impl Unit {
+    fn parse(unit: &str) -> Option<Self> {
+        let unit = unit.to_lowercase();
+        Some(match unit.as_str() {
+            "ml" => Unit::Millilitre,
+            "cl" => Unit::Centilitre,
+            "dl" => Unit::Decilitre,
+            "l" => Unit::Litre,
+            "g" => Unit::Gram,
+            "kg" => Unit::Kilogram,
+            "oz" => Unit::Ounce,
+            "lb" | "lbs" => Unit::Pound,
+            _ if unit.starts_with("fl") => Unit::FluidOunce,
+            _ => return None,
+        })
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Unit::Millilitre => "ml",
+            Unit::Centilitre => "cl",
+            Unit::Decilitre => "dl",
+            Unit::Litre => "l",
+            Unit::Gram => "g",
+            Unit::Kilogram => "kg",
+            Unit::FluidOunce => "fl oz",
+            Unit::Ounce => "oz",
+            Unit::Pound => "lb",
+        }
+    }
+
+    fn system(self) -> UnitSystem {
+        match self {
+            Unit::FluidOunce | Unit::Ounce | Unit::Pound => UnitSystem::Imperial,
+            _ => UnitSystem::Metric,
+        }
+    }
+
+    /// Millilitres for volumes, grams for weights
+    fn to_base(self, value: f64) -> f64 {
+        match self {
+            Unit::Millilitre | Unit::Gram => value,
+            Unit::Centilitre => value * 10.0,
+            Unit::Decilitre => value * 100.0,
+            Unit::Litre | Unit::Kilogram => value * 1000.0,
+            Unit::FluidOunce => value * ML_PER_FL_OZ,
+            Unit::Ounce => value * G_PER_OZ,
+            Unit::Pound => value * G_PER_LB,
+        }
+    }
+
+    fn is_volume(self) -> bool {
+        matches!(self, Unit::Millilitre | Unit::Centilitre | Unit::Decilitre | Unit::Litre | Unit::FluidOunce)
+    }
+}
+
+/// `value` with at most `decimals` decimals, trailing zeros dropped
+fn number(value: f64, decimals: usize, locale: Locale) -> String {
+    let mut text = format!("{:.*}", decimals, value);
+    if text.contains('.') {
+        text = text.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+    match locale {
+        Locale::Nl => text.replace('.', ","),
+        Locale::En => text,
+    }
+}
+
+/// One quantity in `format`: kept in its own unit when that is already in the right
+/// system, converted otherwise
+fn quantity(value: f64, unit: Unit, format: UnitFormat) -> String {
+    if unit.system() == format.system {
+        return format!("{} {}", number(value, 2, format.locale), unit.symbol());
+    }
+    let base = unit.to_base(value);
+    let (value, unit, decimals) = match (format.system, unit.is_volume()) {
+        (UnitSystem::Metric, true) if base >= 1000.0 => (base / 1000.0, Unit::Litre, 2),
+        (UnitSystem::Metric, true) => (base, Unit::Millilitre, 0),
+        (UnitSystem::Metric, false) if base >= 1000.0 => (base / 1000.0, Unit::Kilogram, 2),
+        (UnitSystem::Metric, false) => (base, Unit::Gram, 0),
+        (UnitSystem::Imperial, true) => (base / ML_PER_FL_OZ, Unit::FluidOunce, 1),
+        (UnitSystem::Imperial, false) if base >= G_PER_LB => (base / G_PER_LB, Unit::Pound, 2),
+        (UnitSystem::Imperial, false) => (base / G_PER_OZ, Unit::Ounce, 1),
+    };
+    format!("{} {}", number(value, decimals, format.locale), unit.symbol())
+}
+
+/// Rewrite the weights and volumes in a line of text in `format`; the rest stays as is
+pub fn localize(text: &str, format: UnitFormat) -> String {
+    QUANTITY
+        .replace_all(text, |caps: &Captures| {
+            let value = caps[1].replace(',', ".").parse::<f64>().ok();
+            match (value, Unit::parse(&caps[2])) {
+                (Some(value), Some(unit)) => quantity(value, unit, format),
+                _ => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Items with the quantities in their modifiers and notes written in `format`, for the
+/// ticket formatters
+pub fn localize_items(items: &[PrintItem], format: UnitFormat) -> Vec<PrintItem> {
+    items
+        .iter()
+        .map(|item| PrintItem {
+            modifiers: item.modifiers.iter().map(|m| localize(m, format)).collect(),
+            notes: item.notes.as_deref().map(|n| localize(n, format)),
+            ..item.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantities_follow_locale_and_system() {
+        let nl = UnitFormat { locale: Locale::Nl, system: UnitSystem::Metric };
+        let en = UnitFormat { locale: Locale::En, system: UnitSystem::Metric };
+        let us = UnitFormat { locale: Locale::En, system: UnitSystem::Imperial };
+
+        assert_eq!(localize("Bier 0.5l", nl), "Bier 0,5 l");
+        assert_eq!(localize("Beer 0,5 L", en), "Beer 0.5 l");
+        assert_eq!(localize("Beer 0.5l", us), "Beer 16.9 fl oz");
+        assert_eq!(localize("Steak 250g, extra 1.5 kg", us), "Steak 8.8 oz, extra 3.31 lb");
+        // Imperial to metric: whole millilitres and grams, litres and kilos from 1000 up
+        assert_eq!(localize("8 fl oz milk", nl), "237 ml milk");
+        assert_eq!(localize("2 lbs ribs", nl), "907 g ribs");
+        assert_eq!(localize("40 oz", en), "1.13 kg");
+
+        // Words that merely start with a unit, and bare numbers, are left alone
+        assert_eq!(localize("2 large, table 12", nl), "2 large, table 12");
+        assert_eq!(localize("1 glass", us), "1 glass");
+
+        let items: Vec<PrintItem> = serde_json::from_str(
+            r#"[{"quantity":1,"name":"Cola 330ml","modifiers":["33cl"],"notes":"serve 0.25l"}]"#,
+        )
+        .unwrap();
+        let localized = localize_items(&items, nl);
+        // The item name is the menu's own
+        assert_eq!(localized[0].name, "Cola 330ml");
+        assert_eq!(localized[0].modifiers, vec!["33 cl"]);
+        assert_eq!(localized[0].notes.as_deref(), Some("serve 0,25 l"));
+    }
+}
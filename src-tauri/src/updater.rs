@@ -11,13 +11,22 @@
  * and install via `pkexec dpkg -i` (graphical sudo prompt).
  */
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
 use tokio::sync::Mutex;
 use tokio::time::interval;
-use tracing::{info, warn, error};
-use tauri::{AppHandle, Emitter};
+use tracing::{debug, info, warn, error};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tauri_plugin_updater::UpdaterExt;
+use crate::config::AppConfig;
+use crate::i18n::{LocalizedMessage, MessageKey};
+use crate::notifications::{AlertKind, Notifier};
+use crate::queue::QueueManager;
+use crate::restarts;
 
 /// Update check interval (6 hours)
 const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
@@ -26,8 +35,309 @@ const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
 const UPDATER_ENDPOINT: &str =
     "https://github.com/eatsome-webapp/eatsome-printer-daemon/releases/latest/download/latest.json";
 
-/// Temp path for downloaded .deb updates
-const DEB_TEMP_PATH: &str = "/tmp/eatsome-printer-update.deb";
+/// How often a known update is downloaded in the background (or resumed, after the
+/// queue was busy or the line dropped)
+const PREFETCH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// How often a running download asks the queue whether tickets are waiting
+const BUSY_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Update downloads (`AppConfig.update_download`). Venue DSL lines drop long downloads
+/// and share the line with print traffic, so packages come in resumable pieces, paced,
+/// and in the background only while nothing is printing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateDownloadConfig {
+    /// Download rate cap in KiB/s; 0 = unlimited
+    pub max_kib_per_sec: u64,
+    /// Background downloads pause while tickets are pending or printing. A download
+    /// started with "Update now" doesn't wait.
+    pub idle_only: bool,
+}
+
+impl Default for UpdateDownloadConfig {
+    fn default() -> Self {
+        Self {
+            max_kib_per_sec: 256,
+            idle_only: true,
+        }
+    }
+}
+
+// ============================================================================
+// Resumable Downloads
+// ============================================================================
+
+/// An update package: where it is, its SHA-256 when latest.json lists one, and the
+/// release's minisign signature
+#[derive(Debug, Clone, PartialEq)]
+struct Package {
+    version: String,
+    url: String,
+    sha256: Option<String>,
+    signature: String,
+}
+
+/// Why a download stopped before the package was complete
+#[derive(Debug)]
+enum DownloadStop {
+    /// Tickets waiting: what arrived is kept and resumed later
+    Busy,
+    Failed(String),
+}
+
+impl std::fmt::Display for DownloadStop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DownloadStop::Busy => write!(f, "paused while tickets are printing"),
+            DownloadStop::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Packages are staged next to the queue database until installed
+fn staging_dir() -> PathBuf {
+    AppConfig::default().database_path().with_file_name("updates")
+}
+
+impl Package {
+    /// Staged file: version plus the package's own file name
+    fn path(&self) -> PathBuf {
+        let name = self
+            .url
+            .rsplit('/')
+            .next()
+            .and_then(|n| n.split('?').next())
+            .filter(|n| !n.is_empty())
+            .unwrap_or("package");
+        staging_dir().join(format!("{}-{}", self.version, name))
+    }
+}
+
+/// How long to wait before reading more so `bytes` over `elapsed` stays within the cap
+fn pace_delay(bytes: u64, elapsed: Duration, max_kib_per_sec: u64) -> Duration {
+    if max_kib_per_sec == 0 {
+        return Duration::ZERO;
+    }
+    let due = Duration::from_secs_f64(bytes as f64 / (max_kib_per_sec * 1024) as f64);
+    due.saturating_sub(elapsed)
+}
+
+/// Offset the response body starts at, given the partial file's length: a server that
+/// ignores the Range header sends the whole file again
+fn resume_offset(status: reqwest::StatusCode, partial_len: u64) -> Option<u64> {
+    match status {
+        reqwest::StatusCode::PARTIAL_CONTENT => Some(partial_len),
+        status if status.is_success() => Some(0),
+        _ => None,
+    }
+}
+
+async fn file_sha256(path: &Path) -> std::io::Result<String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut file = std::fs::File::open(path)?;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut file, &mut hasher)?;
+        Ok(hex::encode(hasher.finalize()))
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+/// True while tickets are waiting or printing
+async fn queue_busy(queue: &QueueManager) -> bool {
+    queue.get_health().await.map_or(false, |h| h.pending + h.printing > 0)
+}
+
+/// Download `package` into the staging directory and verify its checksum.
+///
+/// A partial file from an earlier attempt is resumed with an HTTP Range request.
+/// Reading is paced to `limits.max_kib_per_sec`. With `busy_queue` set, the download
+/// stops (keeping the partial file) as soon as tickets are waiting. A checksum mismatch
+/// deletes the file, so the next attempt starts over.
+async fn download_package(
+    package: &Package,
+    limits: &UpdateDownloadConfig,
+    busy_queue: Option<&QueueManager>,
+) -> Result<PathBuf, DownloadStop> {
+    let failed = |e: String| DownloadStop::Failed(e);
+    let path = package.path();
+    let partial = path.with_file_name(format!(
+        "{}.part",
+        path.file_name().and_then(|n| n.to_str()).unwrap_or("package")
+    ));
+
+    tokio::fs::create_dir_all(staging_dir())
+        .await
+        .map_err(|e| failed(format!("Failed to create {}: {}", staging_dir().display(), e)))?;
+    // Packages of other versions are of no use any more
+    if let Ok(mut entries) = tokio::fs::read_dir(staging_dir()).await {
+        let prefix = format!("{}-", package.version);
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if !entry.file_name().to_string_lossy().starts_with(&prefix) {
+                let _ = tokio::fs::remove_file(entry.path()).await;
+            }
+        }
+    }
+
+    if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+        return verify_package(package, path).await;
+    }
+
+    let partial_len = tokio::fs::metadata(&partial).await.map(|m| m.len()).unwrap_or(0);
+    let mut request = reqwest::Client::new()
+        .get(&package.url)
+        .header("User-Agent", "eatsome-printer-daemon");
+    if partial_len > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", partial_len));
+    }
+    let mut resp = request
+        .send()
+        .await
+        .map_err(|e| failed(format!("Download failed: {}", e)))?;
+
+    if resp.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && partial_len > 0 {
+        // Everything arrived last time, only the rename didn't happen
+        tokio::fs::rename(&partial, &path).await.map_err(|e| failed(e.to_string()))?;
+        return verify_package(package, path).await;
+    }
+    let offset = resume_offset(resp.status(), partial_len)
+        .ok_or_else(|| failed(format!("Download returned status {}", resp.status())))?;
+    if offset > 0 {
+        info!("Resuming update v{} download at {} bytes", package.version, offset);
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(offset > 0)
+        .truncate(offset == 0)
+        .open(&partial)
+        .await
+        .map_err(|e| failed(format!("Failed to open {}: {}", partial.display(), e)))?;
+
+    let started = Instant::now();
+    let mut received: u64 = 0;
+    let mut last_busy_check = Instant::now();
+    loop {
+        if let Some(queue) = busy_queue {
+            if last_busy_check.elapsed() >= BUSY_CHECK_INTERVAL {
+                last_busy_check = Instant::now();
+                if queue_busy(queue).await {
+                    let _ = file.flush().await;
+                    return Err(DownloadStop::Busy);
+                }
+            }
+        }
+
+        let chunk = match resp.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                // Keep what arrived; the next attempt resumes from there
+                let _ = file.flush().await;
+                return Err(failed(format!("Download interrupted after {} bytes: {}", offset + received, e)));
+            }
+        };
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| failed(format!("Failed to write {}: {}", partial.display(), e)))?;
+        received += chunk.len() as u64;
+
+        let delay = pace_delay(received, started.elapsed(), limits.max_kib_per_sec);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+    file.flush().await.map_err(|e| failed(e.to_string()))?;
+    drop(file);
+
+    tokio::fs::rename(&partial, &path).await.map_err(|e| failed(e.to_string()))?;
+    info!("Downloaded update v{} ({} bytes) to {}", package.version, offset + received, path.display());
+    verify_package(package, path).await
+}
+
+/// Check a complete package against its checksum; a bad one is deleted
+async fn verify_package(package: &Package, path: PathBuf) -> Result<PathBuf, DownloadStop> {
+    let Some(expected) = package.sha256.as_deref() else {
+        return Ok(path);
+    };
+    let actual = file_sha256(&path)
+        .await
+        .map_err(|e| DownloadStop::Failed(format!("Failed to read {}: {}", path.display(), e)))?;
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        let _ = tokio::fs::remove_file(&path).await;
+        return Err(DownloadStop::Failed(format!(
+            "Checksum mismatch for update v{} (expected {}, got {})",
+            package.version, expected, actual
+        )));
+    }
+    debug!("Update v{} checksum verified", package.version);
+    Ok(path)
+}
+
+/// The package of a Tauri updater release, when latest.json lists a checksum for it.
+/// Without one the plugin downloads it itself (its signature check needs the whole file
+/// in one go).
+fn tauri_package(update: &tauri_plugin_updater::Update) -> Option<Package> {
+    let url = update.download_url.to_string();
+    let sha256 = update.raw_json["platforms"]
+        .as_object()?
+        .values()
+        .find(|p| p["url"].as_str() == Some(url.as_str()))?["sha256"]
+        .as_str()?
+        .to_string();
+    Some(Package {
+        version: update.version.clone(),
+        url,
+        sha256: Some(sha256),
+        signature: update.signature.clone(),
+    })
+}
+
+/// The minisign pubkey releases are signed with (tauri.conf.json plugins.updater.pubkey)
+fn updater_pubkey(app: &AppHandle) -> Option<String> {
+    app.config().plugins.0.get("updater")?.get("pubkey")?.as_str().map(str::to_string)
+}
+
+/// Read a package we downloaded ourselves and check it against the release signature.
+/// Neither `Update::install` nor `dpkg -i` does, and the sha256 comes from the same
+/// latest.json as the URL.
+async fn read_signed(app: &AppHandle, package: &Package, path: &Path) -> Result<Vec<u8>, String> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let pubkey = updater_pubkey(app).ok_or("No updater pubkey configured")?;
+    if let Err(e) = verify_signature(&bytes, &package.signature, &pubkey) {
+        let _ = tokio::fs::remove_file(path).await;
+        return Err(e);
+    }
+    debug!("Update v{} signature verified", package.version);
+    Ok(bytes)
+}
+
+/// `signature` and `pubkey` as latest.json and tauri.conf.json carry them: base64 of
+/// the minisign text
+fn verify_signature(bytes: &[u8], signature: &str, pubkey: &str) -> Result<(), String> {
+    use base64::Engine;
+    use minisign_verify::{PublicKey, Signature};
+
+    let decode = |value: &str| {
+        base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .ok()
+            .and_then(|raw| String::from_utf8(raw).ok())
+    };
+    let key = decode(pubkey)
+        .and_then(|key| PublicKey::decode(&key).ok())
+        .ok_or("Updater pubkey is malformed")?;
+    let signature = decode(signature)
+        .and_then(|signature| Signature::decode(&signature).ok())
+        .ok_or("Release signature is malformed")?;
+    key.verify(bytes, &signature, true)
+        .map_err(|e| format!("Release signature check failed: {}", e))
+}
 
 // ============================================================================
 // Linux .deb Detection & Custom Update Flow
@@ -41,20 +351,14 @@ fn is_deb_install() -> bool {
     cfg!(target_os = "linux") && std::env::var("APPIMAGE").is_err()
 }
 
-/// Parsed update info from latest.json for .deb installs
-#[derive(Debug)]
-struct DebUpdateInfo {
-    version: String,
-    url: String,
-}
-
 /// Fetch latest.json and extract the linux-x86_64-deb platform entry.
 ///
 /// The Tauri updater generates a latest.json with platform keys like:
 /// - `linux-x86_64` (AppImage)
 /// - `linux-x86_64-deb` (.deb package)
-/// We specifically need the `-deb` variant.
-async fn fetch_deb_update_info() -> Result<Option<DebUpdateInfo>, String> {
+/// We specifically need the `-deb` variant, its `signature`, and its `sha256` when the
+/// release lists one.
+async fn fetch_deb_update_info() -> Result<Option<Package>, String> {
     let client = reqwest::Client::new();
     let resp = client
         .get(UPDATER_ENDPOINT)
@@ -93,48 +397,29 @@ async fn fetch_deb_update_info() -> Result<Option<DebUpdateInfo>, String> {
         .as_str()
         .ok_or("Missing 'url' in linux-x86_64-deb platform")?
         .to_string();
+    let signature = deb_entry["signature"]
+        .as_str()
+        .ok_or("Missing 'signature' in linux-x86_64-deb platform")?
+        .to_string();
+    let sha256 = deb_entry["sha256"].as_str().map(str::to_string);
 
-    Ok(Some(DebUpdateInfo { version, url }))
+    Ok(Some(Package { version, url, sha256, signature }))
 }
 
-/// Download the .deb file to a temp location and install via pkexec.
+/// Install a downloaded .deb via pkexec.
 ///
 /// `pkexec` shows a graphical PolicyKit sudo dialog — no terminal needed.
 /// Falls back to an error message if pkexec is unavailable.
-async fn install_deb_update(app: &AppHandle, info: &DebUpdateInfo) -> Result<(), String> {
-    info!("Downloading .deb update v{} from {}", info.version, info.url);
-
-    // Download .deb to temp file
-    let client = reqwest::Client::new();
-    let resp = client
-        .get(&info.url)
-        .header("User-Agent", "eatsome-printer-daemon")
-        .send()
-        .await
-        .map_err(|e| format!("Download failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        return Err(format!("Download returned status {}", resp.status()));
+async fn install_deb_update(app: &AppHandle, version: &str, deb_path: &Path) -> Result<(), String> {
+    if !deb_path.exists() {
+        return Err(format!("Update package {} is missing", deb_path.display()));
     }
 
-    let bytes = resp
-        .bytes()
-        .await
-        .map_err(|e| format!("Failed to read download: {}", e))?;
-
-    tokio::fs::write(DEB_TEMP_PATH, &bytes)
-        .await
-        .map_err(|e| format!("Failed to write {}: {}", DEB_TEMP_PATH, e))?;
-
-    info!(
-        "Downloaded {} bytes to {}",
-        bytes.len(),
-        DEB_TEMP_PATH
-    );
-
     // Install via pkexec dpkg -i (graphical sudo dialog)
     let output = tokio::process::Command::new("pkexec")
-        .args(["dpkg", "-i", DEB_TEMP_PATH])
+        .arg("dpkg")
+        .arg("-i")
+        .arg(deb_path)
         .output()
         .await
         .map_err(|e| {
@@ -145,19 +430,20 @@ async fn install_deb_update(app: &AppHandle, info: &DebUpdateInfo) -> Result<(),
             }
         })?;
 
-    // Clean up temp file (best-effort)
-    let _ = tokio::fs::remove_file(DEB_TEMP_PATH).await;
+    // Clean up the staged package (best-effort)
+    let _ = tokio::fs::remove_file(deb_path).await;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(format!("dpkg -i failed: {}", stderr));
     }
 
-    info!("Successfully installed .deb update v{}", info.version);
+    info!("Successfully installed .deb update v{}", version);
 
     // Restart the app
     let _ = app.emit("update-installed", ());
     tokio::time::sleep(Duration::from_millis(500)).await;
+    restarts::mark_stopping(restarts::StopReason::Update, chrono::Utc::now().timestamp());
     app.restart();
 }
 
@@ -169,19 +455,95 @@ async fn install_deb_update(app: &AppHandle, info: &DebUpdateInfo) -> Result<(),
 pub struct UpdateChecker {
     app: AppHandle,
     available_version: Arc<Mutex<Option<String>>>,
+    notifier: Arc<Notifier>,
+    /// Package of the available update, downloaded ahead of "Update now"
+    package: Arc<Mutex<Option<Package>>>,
+    /// Staged package that passed its checksum
+    staged: Arc<Mutex<Option<(Package, PathBuf)>>>,
+    queue: QueueManager,
+    config: Arc<Mutex<AppConfig>>,
 }
 
 impl UpdateChecker {
-    pub fn new(app: AppHandle) -> Self {
+    pub fn new(app: AppHandle, notifier: Arc<Notifier>, queue: QueueManager, config: Arc<Mutex<AppConfig>>) -> Self {
         Self {
             app,
             available_version: Arc::new(Mutex::new(None)),
+            notifier,
+            package: Arc::new(Mutex::new(None)),
+            staged: Arc::new(Mutex::new(None)),
+            queue,
+            config,
+        }
+    }
+
+    async fn limits(&self) -> UpdateDownloadConfig {
+        self.config.lock().await.update_download.clone()
+    }
+
+    /// Download the available update in the background, a piece at a time while the
+    /// queue is idle (`update_download.idle_only`)
+    async fn prefetch(&self) {
+        let Some(package) = self.package.lock().await.clone() else {
+            return;
+        };
+        if self.staged.lock().await.as_ref().is_some_and(|(staged, _)| *staged == package) {
+            return;
+        }
+        let limits = self.limits().await;
+        let busy_queue = limits.idle_only.then_some(&self.queue);
+        if let Some(queue) = busy_queue {
+            if queue_busy(queue).await {
+                debug!("Update v{} download waits for an idle queue", package.version);
+                return;
+            }
+        }
+        match download_package(&package, &limits, busy_queue).await {
+            Ok(path) => {
+                info!("Update v{} downloaded and verified, ready to install", package.version);
+                *self.staged.lock().await = Some((package, path));
+            }
+            Err(DownloadStop::Busy) => debug!("Update v{} download paused: tickets waiting", package.version),
+            Err(e) => warn!("Update v{} download stopped: {}", package.version, e),
+        }
+    }
+
+    /// Verified package for `package`: the staged one, or downloaded now (paced, but
+    /// not waiting for an idle queue: staff asked for it)
+    async fn fetch(&self, package: &Package) -> Result<PathBuf, String> {
+        if let Some((staged, path)) = self.staged.lock().await.clone() {
+            if staged == *package && path.exists() {
+                return Ok(path);
+            }
+        }
+        let path = download_package(package, &self.limits().await, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        *self.staged.lock().await = Some((package.clone(), path.clone()));
+        Ok(path)
+    }
+
+    /// Store the available version; desktop notification only the first time a version shows up
+    async fn set_available(&self, version: &str) {
+        let previous = self.available_version.lock().await.replace(version.to_string());
+        if previous.as_deref() != Some(version) {
+            let locale = self.config.lock().await.locale;
+            self.notifier
+                .notify(
+                    AlertKind::UpdateAvailable,
+                    Some(version),
+                    LocalizedMessage::new(locale, MessageKey::NotifyUpdateAvailableTitle, &[("version", version.to_string())])
+                        .message,
+                    LocalizedMessage::new(locale, MessageKey::NotifyUpdateAvailableBody, &[]).message,
+                )
+                .await;
         }
     }
 
     /// Start background update checker (notify-only, never auto-installs)
     pub async fn start(self: Arc<Self>) {
         info!("Starting update checker (notify-only mode, deb_install={})", is_deb_install());
+        let prefetcher = self.clone();
 
         tokio::spawn(async move {
             // First check after 60 seconds (let the app stabilize)
@@ -201,6 +563,14 @@ impl UpdateChecker {
                 }
             }
         });
+
+        tokio::spawn(async move {
+            let mut interval = interval(PREFETCH_INTERVAL);
+            loop {
+                interval.tick().await;
+                prefetcher.prefetch().await;
+            }
+        });
     }
 
     /// Check for updates — emit event to frontend if available.
@@ -225,10 +595,9 @@ impl UpdateChecker {
                 );
 
                 // Store the available version
-                {
-                    let mut ver = self.available_version.lock().await;
-                    *ver = Some(update.version.clone());
-                }
+                self.set_available(&update.version).await;
+                // Downloaded ahead only when it can be checked without the plugin
+                *self.package.lock().await = tauri_package(&update);
 
                 // Notify frontend
                 let _ = self.app.emit("update-available", serde_json::json!({
@@ -244,6 +613,7 @@ impl UpdateChecker {
                     let mut ver = self.available_version.lock().await;
                     *ver = None;
                 }
+                *self.package.lock().await = None;
             }
             Err(e) => {
                 warn!("Update check failed: {}", e);
@@ -263,20 +633,19 @@ impl UpdateChecker {
                     info.version
                 );
 
-                {
-                    let mut ver = self.available_version.lock().await;
-                    *ver = Some(info.version.clone());
-                }
+                self.set_available(&info.version).await;
 
                 let _ = self.app.emit("update-available", serde_json::json!({
                     "current_version": env!("CARGO_PKG_VERSION"),
                     "latest_version": info.version,
                 }));
+                *self.package.lock().await = Some(info);
             }
             Ok(None) => {
                 info!("No updates available (deb)");
                 let mut ver = self.available_version.lock().await;
                 *ver = None;
+                *self.package.lock().await = None;
             }
             Err(e) => {
                 warn!("Deb update check failed: {}", e);
@@ -344,10 +713,11 @@ pub async fn check_for_updates(app: AppHandle) -> Result<serde_json::Value, Stri
 
 /// Install update (triggered by user clicking "Update now")
 ///
-/// For .deb installs: downloads .deb and installs via pkexec dpkg -i
-/// For AppImage/macOS/Windows: uses Tauri's built-in download_and_install
+/// For .deb installs: installs the staged (or freshly downloaded) .deb via pkexec dpkg -i
+/// For AppImage/macOS/Windows: installs the staged package when latest.json lists its
+/// checksum, otherwise uses Tauri's built-in download_and_install
 #[tauri::command]
-pub async fn install_update(app: AppHandle) -> Result<String, String> {
+pub async fn install_update(app: AppHandle, checker: State<'_, Arc<UpdateChecker>>) -> Result<String, String> {
     info!("User-initiated update install");
 
     let _ = app.emit("update-installing", ());
@@ -358,7 +728,9 @@ pub async fn install_update(app: AppHandle) -> Result<String, String> {
             .await?
             .ok_or("No update available")?;
 
-        install_deb_update(&app, &info).await?;
+        let deb_path = checker.fetch(&info).await?;
+        read_signed(&app, &info, &deb_path).await?;
+        install_deb_update(&app, &info.version, &deb_path).await?;
         return Ok(format!("Updated to v{}", info.version));
     }
 
@@ -369,18 +741,29 @@ pub async fn install_update(app: AppHandle) -> Result<String, String> {
             let version = update.version.clone();
             info!("Downloading and installing v{}...", version);
 
-            match update.download_and_install(|_, _| {}, || {}).await {
+            let installed = match tauri_package(&update) {
+                Some(package) => match checker.fetch(&package).await {
+                    Ok(path) => read_signed(&app, &package, &path)
+                        .await
+                        .and_then(|bytes| update.install(bytes).map_err(|e| e.to_string())),
+                    Err(e) => Err(e),
+                },
+                None => update.download_and_install(|_, _| {}, || {}).await.map_err(|e| e.to_string()),
+            };
+
+            match installed {
                 Ok(_) => {
                     info!("Update v{} installed — restarting", version);
                     let _ = app.emit("update-installed", ());
 
                     // Short delay so the frontend can show "Restarting..."
                     tokio::time::sleep(Duration::from_millis(500)).await;
+                    restarts::mark_stopping(restarts::StopReason::Update, chrono::Utc::now().timestamp());
                     app.restart();
                 }
                 Err(e) => {
                     error!("Install failed: {}", e);
-                    let _ = app.emit("update-error", format!("{}", e));
+                    let _ = app.emit("update-error", e.clone());
                     Err(format!("Install failed: {}", e))
                 }
             }
@@ -393,3 +776,55 @@ pub async fn install_update(app: AppHandle) -> Result<String, String> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pacing_and_resume_offset() {
+        // 512 KiB at 256 KiB/s is due after 2s
+        assert_eq!(pace_delay(512 * 1024, Duration::from_millis(500), 256), Duration::from_millis(1500));
+        assert_eq!(pace_delay(512 * 1024, Duration::from_secs(3), 256), Duration::ZERO);
+        assert_eq!(pace_delay(u64::MAX, Duration::ZERO, 0), Duration::ZERO);
+
+        assert_eq!(resume_offset(reqwest::StatusCode::PARTIAL_CONTENT, 4096), Some(4096));
+        // Range ignored: the whole file comes again
+        assert_eq!(resume_offset(reqwest::StatusCode::OK, 4096), Some(0));
+        assert_eq!(resume_offset(reqwest::StatusCode::NOT_FOUND, 4096), None);
+
+        let package = Package {
+            version: "1.4.0".to_string(),
+            url: "https://github.com/x/releases/download/v1.4.0/eatsome_1.4.0_amd64.deb?raw=1".to_string(),
+            sha256: None,
+            signature: String::new(),
+        };
+        assert!(package.path().ends_with("1.4.0-eatsome_1.4.0_amd64.deb"));
+    }
+
+    #[test]
+    fn test_package_with_bad_signature_is_refused() {
+        use base64::Engine;
+        let b64 = &base64::engine::general_purpose::STANDARD;
+
+        let conf: serde_json::Value = serde_json::from_str(include_str!("../tauri.conf.json")).unwrap();
+        let pubkey = conf["plugins"]["updater"]["pubkey"].as_str().unwrap();
+
+        // Well-formed signature under the release key id, but not made by the key
+        let key_line = String::from_utf8(b64.decode(pubkey).unwrap()).unwrap();
+        let key_bin = b64.decode(key_line.lines().nth(1).unwrap()).unwrap();
+        let mut sig_bin = b"ED".to_vec();
+        sig_bin.extend_from_slice(&key_bin[2..10]);
+        sig_bin.extend_from_slice(&[0u8; 64]);
+        let forged = format!(
+            "untrusted comment: signature from tauri secret key\n{}\ntrusted comment: timestamp:0\tfile:eatsome.AppImage\n{}\n",
+            b64.encode(&sig_bin),
+            b64.encode([0u8; 64])
+        );
+        let err = verify_signature(b"package", &b64.encode(forged), pubkey).unwrap_err();
+        assert!(err.starts_with("Release signature check failed"), "{}", err);
+
+        assert_eq!(verify_signature(b"package", "not base64!", pubkey).unwrap_err(), "Release signature is malformed");
+        assert_eq!(verify_signature(b"package", "", "").unwrap_err(), "Updater pubkey is malformed");
+    }
+}
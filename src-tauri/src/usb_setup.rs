@@ -0,0 +1,268 @@
+use crate::errors::DaemonError;
+use crate::i18n::{LocalizedMessage, Locale, MessageKey};
+use crate::printer::PrinterManager;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+/// Rules shipped with the .deb/.rpm packages (known thermal printer vendors)
+const BUNDLED_UDEV_RULES: &str = include_str!("../udev-rules.conf");
+
+/// Installed location, same path the packages use so a reinstall overwrites it
+#[cfg(target_os = "linux")]
+const UDEV_RULES_PATH: &str = "/etc/udev/rules.d/60-eatsome-printer.rules";
+
+/// Run as root by pkexec: rules arrive on stdin, the destination as $1, so nothing
+/// the user can touch ends up in the script or on disk before it runs
+#[cfg(target_os = "linux")]
+const INSTALL_RULES_SCRIPT: &str =
+    "tee \"$1\" > /dev/null && chmod 0644 \"$1\" && udevadm control --reload-rules && udevadm trigger --subsystem-match=usb";
+
+/// macOS Privacy & Security pane (USB accessory approval lives there on Apple Silicon)
+#[cfg(target_os = "macos")]
+const MACOS_PRIVACY_PANE: &str = "x-apple.systempreferences:com.apple.preference.security?Privacy";
+
+/// Result of checking whether this process can open a USB printer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsbAccess {
+    Granted,
+    PermissionDenied,
+    NotFound,
+}
+
+/// Automated fix available on this platform
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsbFix {
+    /// Linux: install a udev rule (PolicyKit prompt)
+    InstallUdevRule,
+    /// macOS: open System Settings so the user can approve the accessory
+    OpenPrivacySettings,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsbPermissionCheck {
+    pub address: String,
+    pub access: UsbAccess,
+    pub vendor_id: Option<String>,
+    pub product_id: Option<String>,
+    pub fix: Option<UsbFix>,
+}
+
+/// Progress payload for the `usb-setup-progress` event
+#[derive(Debug, Clone, Serialize)]
+struct SetupProgress<'a> {
+    stage: &'a str,
+    #[serde(flatten)]
+    message: &'a LocalizedMessage,
+}
+
+/// Emits localized `usb-setup-progress` events for one fix run
+struct Progress<'a> {
+    app: &'a AppHandle,
+    locale: Locale,
+}
+
+impl Progress<'_> {
+    fn emit(&self, stage: &str, key: MessageKey, params: &[(&'static str, String)]) -> LocalizedMessage {
+        let message = LocalizedMessage::new(self.locale, key, params);
+        info!("USB setup [{}]: {}", stage, message);
+        let _ = self.app.emit("usb-setup-progress", SetupProgress { stage, message: &message });
+        message
+    }
+
+    /// Emit a `failed` stage and return the message as the command error
+    fn fail(&self, key: MessageKey, params: &[(&'static str, String)]) -> LocalizedMessage {
+        self.emit("failed", key, params)
+    }
+}
+
+/// Probe the device and report whether permissions are the problem
+pub async fn check(pm: &PrinterManager, address: &str) -> UsbPermissionCheck {
+    let ids = pm.usb_device_ids(address).await.ok();
+
+    let access = match pm.probe_usb(address).await {
+        Ok(()) => UsbAccess::Granted,
+        Err(DaemonError::Usb(rusb::Error::Access)) => UsbAccess::PermissionDenied,
+        Err(e) => {
+            warn!("USB probe for {} failed: {}", address, e);
+            if ids.is_some() {
+                // Device is there but can't be opened (busy/claimed): same user-facing fix
+                UsbAccess::PermissionDenied
+            } else {
+                UsbAccess::NotFound
+            }
+        }
+    };
+
+    let fix = if access == UsbAccess::PermissionDenied {
+        platform_fix()
+    } else {
+        None
+    };
+
+    UsbPermissionCheck {
+        address: address.to_string(),
+        access,
+        vendor_id: ids.map(|(v, _)| format!("{:04x}", v)),
+        product_id: ids.map(|(_, p)| format!("{:04x}", p)),
+        fix,
+    }
+}
+
+fn platform_fix() -> Option<UsbFix> {
+    if cfg!(target_os = "linux") {
+        Some(UsbFix::InstallUdevRule)
+    } else if cfg!(target_os = "macos") {
+        Some(UsbFix::OpenPrivacySettings)
+    } else {
+        None
+    }
+}
+
+/// Bundled rules, plus a rule for this exact device when its vendor isn't covered yet
+pub fn generate_udev_rules(vendor_id: u16, product_id: u16) -> String {
+    let vendor = format!("{:04x}", vendor_id);
+    let mut rules = BUNDLED_UDEV_RULES.trim_end().to_string();
+
+    if !rules.contains(&format!("ATTRS{{idVendor}}==\"{}\"", vendor)) {
+        rules.push_str(&format!(
+            "\n\n# Added by setup assistant\nSUBSYSTEM==\"usb\", ATTRS{{idVendor}}==\"{}\", ATTRS{{idProduct}}==\"{:04x}\", MODE=\"0666\", GROUP=\"lp\"",
+            vendor, product_id
+        ));
+    }
+
+    rules.push('\n');
+    rules
+}
+
+/// Apply the platform fix for a USB printer, reporting progress via `usb-setup-progress`,
+/// then re-check access. The manager lock is only held while probing, not during the
+/// (possibly long) elevation prompt.
+pub async fn fix(
+    app: &AppHandle,
+    pm: &PrinterManager,
+    address: &str,
+    locale: Locale,
+) -> Result<UsbPermissionCheck, LocalizedMessage> {
+    let progress = Progress { app, locale };
+    progress.emit("checking", MessageKey::UsbChecking, &[]);
+    let before = check(pm, address).await;
+
+    match (&before.access, &before.fix) {
+        (UsbAccess::Granted, _) => {
+            progress.emit("done", MessageKey::UsbAlreadyGranted, &[]);
+            return Ok(before);
+        }
+        (UsbAccess::NotFound, _) => {
+            return Err(progress.fail(MessageKey::UsbPrinterNotFound, &[("address", address.to_string())]));
+        }
+        (UsbAccess::PermissionDenied, None) => {
+            return Err(progress.fail(MessageKey::UsbNoAutomaticFix, &[]));
+        }
+        (UsbAccess::PermissionDenied, Some(_)) => {}
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        progress.emit("opening_settings", MessageKey::UsbOpeningSettings, &[]);
+        tokio::process::Command::new("open")
+            .arg(MACOS_PRIVACY_PANE)
+            .status()
+            .await
+            .map_err(|e| progress.fail(MessageKey::InternalError, &[("error", e.to_string())]))?;
+        // The user approves asynchronously; the UI re-checks with check_usb_permissions
+        progress.emit("waiting_for_user", MessageKey::UsbWaitingForUser, &[]);
+        Ok(before)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        #[cfg(target_os = "linux")]
+        {
+            // A rule for vendor 0000 would match nothing and leave the printer denied
+            let hex_id = |id: Option<&str>| id.and_then(|v| u16::from_str_radix(v, 16).ok());
+            let (Some(vendor_id), Some(product_id)) =
+                (hex_id(before.vendor_id.as_deref()), hex_id(before.product_id.as_deref()))
+            else {
+                return Err(progress.fail(MessageKey::UsbDeviceIdsUnknown, &[("address", address.to_string())]));
+            };
+            install_udev_rules(&progress, &generate_udev_rules(vendor_id, product_id)).await?;
+
+            // udev applies the new mode asynchronously after the trigger
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+
+        progress.emit("verifying", MessageKey::UsbVerifying, &[]);
+        let after = check(pm, address).await;
+        if after.access == UsbAccess::Granted {
+            progress.emit("done", MessageKey::UsbGranted, &[]);
+        } else {
+            progress.fail(MessageKey::UsbStillDenied, &[]);
+        }
+        Ok(after)
+    }
+}
+
+/// Write the rules via pkexec (graphical PolicyKit prompt) and reload udev
+#[cfg(target_os = "linux")]
+async fn install_udev_rules(progress: &Progress<'_>, rules: &str) -> Result<(), LocalizedMessage> {
+    use tokio::io::AsyncWriteExt;
+
+    progress.emit("installing_rule", MessageKey::UsbInstallingRule, &[]);
+
+    // Single pkexec call so the user only sees one password prompt
+    let mut child = tokio::process::Command::new("pkexec")
+        .args(["sh", "-c", INSTALL_RULES_SCRIPT, "sh", UDEV_RULES_PATH])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                let command = format!(
+                    "sudo tee {} <<'EOF'\n{}EOF\nsudo udevadm control --reload-rules && sudo udevadm trigger --subsystem-match=usb",
+                    UDEV_RULES_PATH, rules
+                );
+                progress.fail(MessageKey::PkexecNotFound, &[("command", command)])
+            } else {
+                progress.fail(MessageKey::InternalError, &[("error", format!("pkexec: {}", e))])
+            }
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A cancelled prompt closes the pipe early; the exit status reports that below
+        let _ = stdin.write_all(rules.as_bytes()).await;
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| progress.fail(MessageKey::InternalError, &[("error", format!("pkexec: {}", e))]))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(progress.fail(MessageKey::UsbRuleFailed, &[("error", stderr)]));
+    }
+
+    progress.emit("rule_installed", MessageKey::UsbRuleInstalled, &[]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_vendor_uses_bundled_rules() {
+        let rules = generate_udev_rules(0x04b8, 0x0e15);
+        assert_eq!(rules.trim_end(), BUNDLED_UDEV_RULES.trim_end());
+    }
+
+    #[test]
+    fn test_unknown_vendor_gets_device_rule() {
+        let rules = generate_udev_rules(0x0483, 0x5743);
+        assert!(rules.starts_with(BUNDLED_UDEV_RULES.trim_end()));
+        assert!(rules.contains("ATTRS{idVendor}==\"0483\", ATTRS{idProduct}==\"5743\""));
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::RwLock;
+use tracing::{info, warn};
+
+/// Zone from `AppConfig.timezone`; None: the computer's
+static CONFIGURED: RwLock<Option<Tz>> = RwLock::new(None);
+
+/// The computer's zone, read once at first use
+static DETECTED: Lazy<Option<Tz>> = Lazy::new(|| match iana_time_zone::get_timezone() {
+    Ok(name) => parse(&name).map_err(|e| warn!("{}", e)).ok(),
+    Err(e) => {
+        warn!("Could not detect the time zone, using UTC: {}", e);
+        None
+    }
+});
+
+/// Time zone setting for the settings screen (`get_timezone`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimezoneInfo {
+    /// Zone receipts and business dates use
+    pub timezone: String,
+    /// `AppConfig.timezone`, when set
+    pub configured: Option<String>,
+    /// The computer's zone
+    pub detected: Option<String>,
+}
+
+/// An IANA zone name ("Europe/Amsterdam")
+pub fn parse(name: &str) -> Result<Tz, String> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| format!("Unknown time zone '{}' (use a name such as Europe/Amsterdam)", name.trim()))
+}
+
+/// Apply `AppConfig.timezone` (config save / load). None or an unknown name: the
+/// computer's zone.
+pub fn set(configured: Option<&str>) {
+    let zone = configured.filter(|name| !name.trim().is_empty()).and_then(|name| {
+        parse(name).map_err(|e| warn!("Ignoring configured time zone: {}", e)).ok()
+    });
+    *CONFIGURED.write().unwrap_or_else(|e| e.into_inner()) = zone;
+    info!("Venue time zone: {}", self::zone());
+}
+
+/// The venue's zone: configured, else detected, else UTC
+pub fn zone() -> Tz {
+    CONFIGURED.read().unwrap_or_else(|e| e.into_inner()).or(*DETECTED).unwrap_or(Tz::UTC)
+}
+
+/// Current venue time
+pub fn now() -> DateTime<Tz> {
+    Utc::now().with_timezone(&zone())
+}
+
+/// Venue time of a Unix timestamp in seconds
+pub fn at_secs(unix_secs: i64) -> Option<DateTime<Tz>> {
+    in_zone(zone(), unix_secs.checked_mul(1000)?)
+}
+
+/// Venue time of a Unix timestamp in milliseconds (tickets, pickup times)
+pub fn at_millis(unix_ms: i64) -> Option<DateTime<Tz>> {
+    in_zone(zone(), unix_ms)
+}
+
+fn in_zone(zone: Tz, unix_ms: i64) -> Option<DateTime<Tz>> {
+    DateTime::from_timestamp_millis(unix_ms).map(|dt| dt.with_timezone(&zone))
+}
+
+pub fn info() -> TimezoneInfo {
+    TimezoneInfo {
+        timezone: zone().name().to_string(),
+        configured: CONFIGURED.read().unwrap_or_else(|e| e.into_inner()).map(|tz| tz.name().to_string()),
+        detected: DETECTED.map(|tz| tz.name().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zone_conversion() {
+        assert_eq!(parse(" Europe/Amsterdam ").unwrap(), Tz::Europe__Amsterdam);
+        assert!(parse("CEST").is_err());
+
+        // 2026-01-15 11:30 UTC: 12:30 in Amsterdam (winter), 06:30 in New York
+        let ms = 1_768_476_600_000;
+        let format = |zone| in_zone(zone, ms).unwrap().format("%H:%M").to_string();
+        assert_eq!(format(Tz::Europe__Amsterdam), "12:30");
+        assert_eq!(format(Tz::America__New_York), "06:30");
+        // Summer time: 2026-07-15 11:30 UTC is 13:30 in Amsterdam
+        assert_eq!(in_zone(Tz::Europe__Amsterdam, 1_784_115_000_000).unwrap().format("%H:%M").to_string(), "13:30");
+    }
+}
@@ -0,0 +1,585 @@
+use crate::api::ErrorResponse;
+use crate::config::AppConfig;
+use crate::escpos::PrintItem;
+use crate::queue::{priority, JobKind, PrintJob, QueueManager};
+use crate::status;
+use crate::transform::{select, select_string, TransformRules};
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tower_http::trace::TraceLayer;
+use tracing::{error, info, warn};
+
+/// Default LAN listener for platform webhooks (separate from the localhost-only API on 8043)
+pub const DEFAULT_WEBHOOK_ADDR: &str = "0.0.0.0:8044";
+
+/// Third-party order platform webhooks (off by default)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub enabled: bool,
+    pub bind_address: String,
+    pub platforms: Vec<WebhookPlatform>,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: DEFAULT_WEBHOOK_ADDR.to_string(),
+            platforms: Vec::new(),
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Refuse platforms without a secret: anyone could sign orders for them
+    pub fn validate(&self) -> Result<(), String> {
+        match self.platforms.iter().find(|p| p.secret.trim().is_empty()) {
+            Some(platform) => Err(format!("platform '{}' has no secret", platform.id)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// One order platform (or middleware) allowed to push orders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPlatform {
+    /// URL slug: orders are posted to `/webhooks/{id}`
+    pub id: String,
+    pub name: String,
+    /// Shared secret for the HMAC-SHA256 body signature
+    pub secret: String,
+    #[serde(default)]
+    pub signature: SignatureConfig,
+    /// Built-in mapping profile: "thuisbezorgd" or "ubereats"
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Custom mapping; takes precedence over `preset`
+    #[serde(default)]
+    pub mapping: Option<MappingProfile>,
+    /// Station used when the payload doesn't name one
+    #[serde(default = "default_station")]
+    pub default_station: String,
+}
+
+fn default_station() -> String {
+    "kitchen".to_string()
+}
+
+impl WebhookPlatform {
+    pub fn profile(&self) -> Option<MappingProfile> {
+        self.mapping
+            .clone()
+            .or_else(|| self.preset.as_deref().and_then(MappingProfile::preset))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureEncoding {
+    Hex,
+    Base64,
+}
+
+/// Where the signature lives and how it is encoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureConfig {
+    pub header: String,
+    pub encoding: SignatureEncoding,
+    /// Prefix stripped before decoding (e.g. "sha256=")
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+impl Default for SignatureConfig {
+    fn default() -> Self {
+        Self {
+            header: "x-signature".to_string(),
+            encoding: SignatureEncoding::Hex,
+            prefix: None,
+        }
+    }
+}
+
+/// Field paths into the platform's order JSON (path syntax: `transform::select`).
+/// Modifiers are flattened with the configured `ModifierRules`, so the modifier path may
+/// point at nested groups.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingProfile {
+    pub order_id: String,
+    pub order_number: String,
+    /// Path to the items array; item paths below are relative to each item
+    pub items: String,
+    pub item_name: String,
+    pub item_quantity: String,
+    #[serde(default)]
+    pub item_modifiers: Option<String>,
+    #[serde(default)]
+    pub item_notes: Option<String>,
+    /// Item category, used for station inference
+    #[serde(default)]
+    pub item_category: Option<String>,
+    #[serde(default)]
+    pub customer_name: Option<String>,
+    #[serde(default)]
+    pub order_type: Option<String>,
+    #[serde(default)]
+    pub table_number: Option<String>,
+    #[serde(default)]
+    pub station: Option<String>,
+}
+
+impl MappingProfile {
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "thuisbezorgd" => Some(Self {
+                order_id: "id".to_string(),
+                order_number: "publicReference".to_string(),
+                items: "products".to_string(),
+                item_name: "name".to_string(),
+                item_quantity: "count".to_string(),
+                item_modifiers: Some("sideDishes".to_string()),
+                item_notes: Some("remark".to_string()),
+                item_category: Some("category".to_string()),
+                customer_name: Some("customer/fullName".to_string()),
+                order_type: Some("orderType".to_string()),
+                table_number: None,
+                station: None,
+            }),
+            "ubereats" => Some(Self {
+                order_id: "id".to_string(),
+                order_number: "display_id".to_string(),
+                items: "cart/items".to_string(),
+                item_name: "title".to_string(),
+                item_quantity: "quantity".to_string(),
+                item_modifiers: Some("selected_modifier_groups".to_string()),
+                item_notes: Some("special_instructions".to_string()),
+                item_category: None,
+                customer_name: Some("eater/first_name".to_string()),
+                order_type: Some("type".to_string()),
+                table_number: None,
+                station: None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Order fields extracted from a platform payload
+#[derive(Debug, Clone)]
+pub struct MappedOrder {
+    pub order_id: String,
+    pub order_number: String,
+    pub items: Vec<PrintItem>,
+    pub customer_name: Option<String>,
+    pub order_type: Option<String>,
+    pub table_number: Option<String>,
+    pub station: Option<String>,
+}
+
+/// Translate a platform payload into print items using the mapping profile.
+/// Modifier flattening and station inference come from the shared transform rules.
+pub fn map_order(profile: &MappingProfile, payload: &Value, rules: &TransformRules) -> Result<MappedOrder, String> {
+    let order_id = select_string(payload, &profile.order_id)
+        .ok_or_else(|| format!("Missing order id at '{}'", profile.order_id))?;
+    let order_number = select_string(payload, &profile.order_number).unwrap_or_else(|| order_id.clone());
+
+    let mut categories = Vec::new();
+    let items: Vec<PrintItem> = select(payload, &profile.items)
+        .into_iter()
+        .flat_map(|v| match v {
+            Value::Array(items) => items.iter().collect::<Vec<_>>(),
+            other => vec![other],
+        })
+        .filter_map(|item| {
+            let name = select_string(item, &profile.item_name)?;
+            let quantity = select_string(item, &profile.item_quantity)
+                .and_then(|q| q.parse::<u32>().ok())
+                .unwrap_or(1);
+            let modifiers = profile
+                .item_modifiers
+                .as_deref()
+                .map(|path| select(item, path).into_iter().flat_map(|m| rules.modifiers.flatten(m)).collect())
+                .unwrap_or_default();
+            let notes = profile.item_notes.as_deref().and_then(|p| select_string(item, p));
+            categories.extend(profile.item_category.as_deref().and_then(|p| select_string(item, p)));
+            Some(PrintItem { quantity, name, modifiers, notes, image_url: None, pricing: None })
+        })
+        .collect();
+
+    if items.is_empty() {
+        return Err(format!("No items found at '{}'", profile.items));
+    }
+
+    let optional = |path: &Option<String>| path.as_deref().and_then(|p| select_string(payload, p));
+
+    Ok(MappedOrder {
+        order_id,
+        order_number,
+        items,
+        customer_name: optional(&profile.customer_name),
+        order_type: optional(&profile.order_type),
+        table_number: optional(&profile.table_number),
+        station: optional(&profile.station).or_else(|| rules.infer_station(&categories)),
+    })
+}
+
+/// Verify the HMAC-SHA256 signature of the raw request body (constant-time compare)
+pub fn verify_signature(platform: &WebhookPlatform, headers: &HeaderMap, body: &[u8]) -> bool {
+    let cfg = &platform.signature;
+    let Some(header) = headers.get(cfg.header.as_str()).and_then(|h| h.to_str().ok()) else {
+        return false;
+    };
+    let header = header.trim();
+    let encoded = match &cfg.prefix {
+        Some(prefix) => header.strip_prefix(prefix.as_str()).unwrap_or(header),
+        None => header,
+    };
+
+    let signature = match cfg.encoding {
+        SignatureEncoding::Hex => hex::decode(encoded).ok(),
+        SignatureEncoding::Base64 => base64::engine::general_purpose::STANDARD.decode(encoded).ok(),
+    };
+    let Some(signature) = signature else {
+        return false;
+    };
+
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(platform.secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Webhook server state
+#[derive(Clone)]
+pub struct WebhookState {
+    pub queue_manager: QueueManager,
+    /// Live config: platforms, restaurant and printers are read per request
+    pub config: Arc<Mutex<AppConfig>>,
+}
+
+/// Webhook acknowledgement
+#[derive(Debug, Serialize)]
+pub struct WebhookResponse {
+    pub job_id: String,
+    pub status: String,
+    pub order_number: String,
+}
+
+fn reject(status: StatusCode, error: &str, details: Option<String>) -> Response {
+    (
+        status,
+        Json(ErrorResponse {
+            error: error.to_string(),
+            details,
+        }),
+    )
+        .into_response()
+}
+
+/// Platform order types that should jump the queue
+fn priority_for(order_type: Option<&str>) -> u8 {
+    match order_type.map(|t| t.to_lowercase()) {
+        Some(t) if t.contains("deliver") || t.contains("pickup") || t.contains("takeaway") => priority::URGENT,
+        _ => priority::NORMAL,
+    }
+}
+
+/// POST /webhooks/:platform - Receive an order from a delivery platform
+async fn handle_webhook(
+    State(state): State<WebhookState>,
+    Path(platform_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let (platform, restaurant_id, printers, routing, rules) = {
+        let config = state.config.lock().await;
+        let platform = config.webhooks.platforms.iter().find(|p| p.id == platform_id).cloned();
+        (
+            platform,
+            config.restaurant_id.clone(),
+            config.printers.clone(),
+            config.routing.clone(),
+            config.transform.clone(),
+        )
+    };
+
+    let Some(platform) = platform else {
+        warn!("Webhook for unknown platform: {}", platform_id);
+        return reject(StatusCode::NOT_FOUND, "Unknown platform", None);
+    };
+
+    // Saved configs can't have one, hand-edited ones can
+    if platform.secret.trim().is_empty() {
+        warn!("Webhook platform {} has no secret, refusing its orders", platform.id);
+        return reject(StatusCode::UNAUTHORIZED, "Webhook secret not configured", None);
+    }
+
+    if !verify_signature(&platform, &headers, &body) {
+        warn!("Webhook signature verification failed for platform {}", platform.id);
+        return reject(StatusCode::UNAUTHORIZED, "Invalid signature", None);
+    }
+
+    let Some(restaurant_id) = restaurant_id else {
+        return reject(StatusCode::SERVICE_UNAVAILABLE, "Daemon not paired with a restaurant", None);
+    };
+
+    let Some(profile) = platform.profile() else {
+        error!("Webhook platform {} has no mapping profile", platform.id);
+        return reject(StatusCode::INTERNAL_SERVER_ERROR, "No mapping profile configured", None);
+    };
+
+    let payload: Value = match serde_json::from_slice(&body) {
+        Ok(v) => v,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, "Invalid JSON", Some(e.to_string())),
+    };
+
+    let order = match map_order(&profile, &payload, &rules) {
+        Ok(order) => order,
+        Err(e) => {
+            warn!("Webhook payload from {} could not be mapped: {}", platform.id, e);
+            return reject(StatusCode::UNPROCESSABLE_ENTITY, "Payload mapping failed", Some(e));
+        }
+    };
+
+    let station = order.station.clone().unwrap_or_else(|| platform.default_station.clone());
+    // Same routing as jobs the processor gets without a printer
+    let printer_id = crate::routing::Router::new(&routing, &printers).route(&station).map(|route| route.printer_id);
+
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let job = PrintJob {
+        id: job_id.clone(),
+        restaurant_id,
+        // Namespaced so ids from different platforms can't collide in deduplication
+        order_id: Some(format!("{}:{}", platform.id, order.order_id)),
+        order_number: order.order_number.clone(),
+        station,
+        station_id: None,
+        printer_id,
+        items: order.items,
+        table_number: order.table_number,
+        customer_name: order.customer_name,
+        priority: priority_for(order.order_type.as_deref()),
+        order_type: order.order_type,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        status: status::PENDING.to_string(),
+        retry_count: 0,
+        error_message: None,
+        copies: 1,
+        correlation_id: String::new(),
+        pickup_time: None,
+        kind: JobKind::Order,
+        original_job_id: None,
+        removed_items: vec![],
+        order_stations: vec![],
+    };
+
+    let queue = &state.queue_manager;
+    if let Err(e) = queue.enqueue(job).await {
+        error!("Failed to enqueue webhook order {} from {}: {}", order.order_number, platform.id, e);
+        return e.into_response();
+    }
+
+    info!("Webhook order {} from {} enqueued as job {}", order.order_number, platform.name, job_id);
+
+    Json(WebhookResponse {
+        job_id,
+        status: "queued".to_string(),
+        order_number: order.order_number,
+    })
+    .into_response()
+}
+
+/// Create webhook router
+pub fn create_router(state: WebhookState) -> Router {
+    Router::new()
+        .route("/webhooks/:platform", post(handle_webhook))
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+/// Start the webhook listener
+pub async fn start_webhook_server(
+    addr: &str,
+    state: WebhookState,
+) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let router = create_router(state);
+
+    info!("Starting webhook receiver on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    axum::serve(listener, router)
+        .await
+        .map_err(|e| {
+            error!("Webhook server error: {}", e);
+            e.into()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use std::path::PathBuf;
+    use tower::ServiceExt;
+
+    fn uber_platform() -> WebhookPlatform {
+        WebhookPlatform {
+            id: "ubereats".to_string(),
+            name: "Uber Eats".to_string(),
+            secret: "shh".to_string(),
+            signature: SignatureConfig {
+                header: "x-uber-signature".to_string(),
+                encoding: SignatureEncoding::Hex,
+                prefix: None,
+            },
+            preset: Some("ubereats".to_string()),
+            mapping: None,
+            default_station: "kitchen".to_string(),
+        }
+    }
+
+    fn uber_payload() -> Value {
+        serde_json::json!({
+            "id": "ue-123",
+            "display_id": "A1B2C",
+            "type": "DELIVERY_BY_UBER",
+            "eater": { "first_name": "Sanne" },
+            "cart": { "items": [
+                {
+                    "title": "Burger",
+                    "quantity": 2,
+                    "special_instructions": "no onions",
+                    "selected_modifier_groups": [
+                        { "selected_items": [{ "title": "Cheese" }, { "title": "Bacon" }] },
+                        { "selected_items": [{ "title": "Fries" }] }
+                    ]
+                },
+                { "title": "Cola", "quantity": 1 }
+            ]}
+        })
+    }
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn test_ubereats_preset_flattens_modifiers() {
+        let profile = MappingProfile::preset("ubereats").unwrap();
+        let order = map_order(&profile, &uber_payload(), &TransformRules::default()).unwrap();
+
+        assert_eq!(order.order_id, "ue-123");
+        assert_eq!(order.order_number, "A1B2C");
+        assert_eq!(order.customer_name.as_deref(), Some("Sanne"));
+        assert_eq!(order.items.len(), 2);
+        assert_eq!(order.items[0].quantity, 2);
+        assert_eq!(order.items[0].modifiers, vec!["Cheese", "Bacon", "Fries"]);
+        assert_eq!(order.items[0].notes.as_deref(), Some("no onions"));
+        assert!(order.items[1].modifiers.is_empty());
+    }
+
+    #[test]
+    fn test_signature_verification() {
+        let platform = uber_platform();
+        let body = b"{\"id\":\"1\"}";
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-uber-signature", sign("shh", body).parse().unwrap());
+        assert!(verify_signature(&platform, &headers, body));
+
+        headers.insert("x-uber-signature", sign("wrong", body).parse().unwrap());
+        assert!(!verify_signature(&platform, &headers, body));
+
+        assert!(!verify_signature(&platform, &HeaderMap::new(), body));
+    }
+
+    #[tokio::test]
+    async fn test_platform_without_secret_is_refused() {
+        let mut config = AppConfig {
+            restaurant_id: Some("rest_123".to_string()),
+            ..AppConfig::default()
+        };
+        config.webhooks.platforms.push(uber_platform());
+        assert!(config.webhooks.validate().is_ok());
+        config.webhooks.platforms[0].secret = " ".to_string();
+        assert_eq!(config.webhooks.validate(), Err("platform 'ubereats' has no secret".to_string()));
+
+        let queue_manager = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        let state = WebhookState {
+            queue_manager: queue_manager.clone(),
+            config: Arc::new(Mutex::new(config)),
+        };
+        let body = serde_json::to_vec(&uber_payload()).unwrap();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/webhooks/ubereats")
+            .header("content-type", "application/json")
+            .header("x-uber-signature", sign(" ", &body))
+            .body(Body::from(body))
+            .unwrap();
+        let response = create_router(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(queue_manager.get_pending_jobs(10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_enqueues_signed_order() {
+        let queue_manager = QueueManager::new(PathBuf::from(":memory:"), None).await.unwrap();
+        let mut config = AppConfig {
+            restaurant_id: Some("rest_123".to_string()),
+            ..AppConfig::default()
+        };
+        config.webhooks.platforms.push(uber_platform());
+        let state = WebhookState {
+            queue_manager: queue_manager.clone(),
+            config: Arc::new(Mutex::new(config)),
+        };
+
+        let body = serde_json::to_vec(&uber_payload()).unwrap();
+        let request = |signature: String| {
+            Request::builder()
+                .method("POST")
+                .uri("/webhooks/ubereats")
+                .header("content-type", "application/json")
+                .header("x-uber-signature", signature)
+                .body(Body::from(body.clone()))
+                .unwrap()
+        };
+
+        let response = create_router(state.clone())
+            .oneshot(request("deadbeef".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = create_router(state)
+            .oneshot(request(sign("shh", &body)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let pending = queue_manager.get_pending_jobs(10).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].order_id.as_deref(), Some("ubereats:ue-123"));
+        assert_eq!(pending[0].priority, priority::URGENT);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::config::ConnectionType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Writes smaller than this say little about throughput: latency dominates
+const MIN_SAMPLE_BYTES: usize = 2048;
+
+/// Weight of a new measurement in a printer's running throughput estimate
+const SAMPLE_WEIGHT: f64 = 0.3;
+
+/// A write may run this many times slower than the printer's estimate before it times out
+const SLOWDOWN_ALLOWED: f64 = 3.0;
+
+/// Bounds for raw write timeouts (`AppConfig.write_timeouts`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WriteTimeoutConfig {
+    /// Shortest timeout, for tiny tickets: a LAN printer that doesn't take 1 KB within
+    /// this is down (default 5s)
+    pub floor_secs: u64,
+    /// Longest timeout, for big raster receipts over BLE; stays under the 120s job
+    /// timeout so failover still gets a turn (default 90s)
+    pub ceiling_secs: u64,
+}
+
+impl Default for WriteTimeoutConfig {
+    fn default() -> Self {
+        Self { floor_secs: 5, ceiling_secs: 90 }
+    }
+}
+
+/// Throughput assumed for a printer that hasn't had a large write yet (bytes per second).
+/// Conservative: thermal printers take data about as fast as they print, BLE moves
+/// ~1-2 KB/s, a serial line at 9600 baud under 1 KB/s.
+fn assumed_throughput(connection: &ConnectionType) -> f64 {
+    match connection {
+        ConnectionType::Network => 20_000.0,
+        ConnectionType::USB => 20_000.0,
+        ConnectionType::Bluetooth => 1_000.0,
+        ConnectionType::Serial => 900.0,
+    }
+}
+
+/// Write timeouts sized to the payload. The flat 20s timeout cut off big raster
+/// receipts over BLE and left a dead LAN printer hanging a small ticket for 20s.
+/// A write gets the floor plus the time the payload takes at the printer's measured
+/// throughput, `SLOWDOWN_ALLOWED` times over, within the configured ceiling.
+#[derive(Debug, Default)]
+pub struct WriteTimeouts {
+    config: WriteTimeoutConfig,
+    /// Measured bytes per second per printer (running average of large writes)
+    throughput: HashMap<String, f64>,
+}
+
+impl WriteTimeouts {
+    pub fn set_config(&mut self, config: WriteTimeoutConfig) {
+        self.config = config;
+    }
+
+    /// Timeout for writing `bytes` to a printer (None: unregistered, direct test print)
+    pub fn timeout(&self, printer_id: Option<&str>, connection: &ConnectionType, bytes: usize) -> Duration {
+        let floor = self.config.floor_secs.max(1) as f64;
+        let ceiling = (self.config.ceiling_secs as f64).max(floor);
+        let throughput = printer_id
+            .and_then(|id| self.throughput.get(id).copied())
+            .unwrap_or_else(|| assumed_throughput(connection));
+        let secs = floor + bytes as f64 / throughput * SLOWDOWN_ALLOWED;
+        Duration::from_secs_f64(secs.min(ceiling))
+    }
+
+    /// Learn from a successful write
+    pub fn record(&mut self, printer_id: &str, bytes: usize, elapsed: Duration) {
+        if bytes < MIN_SAMPLE_BYTES || elapsed.is_zero() {
+            return;
+        }
+        let measured = bytes as f64 / elapsed.as_secs_f64();
+        self.throughput
+            .entry(printer_id.to_string())
+            .and_modify(|estimate| *estimate += (measured - *estimate) * SAMPLE_WEIGHT)
+            .or_insert(measured);
+    }
+
+    /// Forget a printer's estimate (its address or connection changed)
+    pub fn forget(&mut self, printer_id: &str) {
+        self.throughput.remove(printer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_follows_size_and_measured_throughput() {
+        let mut timeouts = WriteTimeouts::default();
+
+        // Small LAN ticket: about the floor; big BLE raster: hits the ceiling
+        let small = timeouts.timeout(Some("lan"), &ConnectionType::Network, 800);
+        assert!(small < Duration::from_secs(6));
+        assert_eq!(timeouts.timeout(Some("ble"), &ConnectionType::Bluetooth, 200_000), Duration::from_secs(90));
+        // 10 KB over BLE: the floor plus 10s at 1 KB/s, three times over
+        assert_eq!(timeouts.timeout(None, &ConnectionType::Bluetooth, 10_000), Duration::from_secs(35));
+
+        // A measured fast printer gets shorter timeouts; tiny writes don't count
+        timeouts.record("lan", 100, Duration::from_secs(10));
+        timeouts.record("lan", 100_000, Duration::from_millis(500));
+        assert_eq!(timeouts.timeout(Some("lan"), &ConnectionType::Network, 200_000), Duration::from_secs(8));
+        timeouts.record("lan", 100_000, Duration::from_secs(1));
+        // 200 KB/s → 0.3 of the way to 100 KB/s = 170 KB/s
+        let timeout = timeouts.timeout(Some("lan"), &ConnectionType::Network, 170_000);
+        assert!((timeout.as_secs_f64() - 8.0).abs() < 0.01);
+
+        timeouts.forget("lan");
+        timeouts.set_config(WriteTimeoutConfig { floor_secs: 2, ceiling_secs: 10 });
+        assert_eq!(timeouts.timeout(Some("lan"), &ConnectionType::Network, 1_000_000), Duration::from_secs(10));
+    }
+}
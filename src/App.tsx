@@ -15,6 +15,15 @@ interface AppConfig {
   printers: any[]
 }
 
+interface PairingState {
+  paired: boolean
+  restaurant_id: string | null
+  restaurant_code: string | null
+  paired_at: string | null
+  setup_complete: boolean
+  printer_count: number
+}
+
 interface UpdateInfo {
   current_version: string
   latest_version: string
@@ -23,6 +32,7 @@ interface UpdateInfo {
 function App() {
   const [loading, setLoading] = useState(true)
   const [showWizard, setShowWizard] = useState(false)
+  const [resumeRestaurantId, setResumeRestaurantId] = useState<string | undefined>()
   const [showResetConfirm, setShowResetConfirm] = useState(false)
   const [updateAvailable, setUpdateAvailable] = useState<UpdateInfo | null>(null)
   const [updateInstalling, setUpdateInstalling] = useState(false)
@@ -62,15 +72,19 @@ function App() {
 
   async function loadConfig() {
     try {
-      const cfg = await invoke<AppConfig>('get_config')
+      const pairing = await invoke<PairingState>('get_pairing_state')
 
-      // Show wizard if restaurant_id is not configured
-      if (!cfg.restaurant_id) {
+      // Show wizard if not paired, or resume it if it was closed after pairing
+      if (!pairing.paired || !pairing.restaurant_id) {
+        setResumeRestaurantId(undefined)
+        setShowWizard(true)
+      } else if (!pairing.setup_complete) {
+        setResumeRestaurantId(pairing.restaurant_id)
         setShowWizard(true)
       } else {
         // Auto-connect to Realtime on startup
         try {
-          await invoke('start_polling', { restaurantId: cfg.restaurant_id })
+          await invoke('start_polling', { restaurantId: pairing.restaurant_id })
         } catch (error) {
           console.warn('Auto-connect on startup failed:', error)
         }
@@ -86,6 +100,7 @@ function App() {
 
   async function handleWizardComplete() {
     setShowWizard(false)
+    setResumeRestaurantId(undefined)
     // loadConfig() already calls start_polling when restaurant_id is set
     await loadConfig()
   }
@@ -187,7 +202,7 @@ function App() {
     return (
       <>
         {updateBanner}
-        <SetupWizard onComplete={handleWizardComplete} />
+        <SetupWizard onComplete={handleWizardComplete} resumeRestaurantId={resumeRestaurantId} />
       </>
     )
   }
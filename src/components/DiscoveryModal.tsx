@@ -1,6 +1,6 @@
 import { useState, useEffect, useRef } from 'react'
 import { invoke } from '@tauri-apps/api/core'
-import { Printer, X, Loader2, Search, Usb, Wifi, Bluetooth, Check, AlertCircle } from 'lucide-react'
+import { Printer, X, Loader2, Search, Usb, Wifi, Bluetooth, Cable, Check, AlertCircle } from 'lucide-react'
 import './DiscoveryModal.css'
 
 export interface DiscoveredPrinter {
@@ -40,13 +40,13 @@ export default function DiscoveryModal({
     }
   }, [])
 
-  async function startScan() {
+  async function startScan(deep = false) {
     setPhase('scanning')
     setScanError(null)
     setSelectedIds(new Set())
 
     try {
-      const printers = await invoke<DiscoveredPrinter[]>('discover_printers', { force: true })
+      const printers = await invoke<DiscoveredPrinter[]>('discover_printers', { force: true, deep })
       if (unmountedRef.current) return
 
       if (printers.length === 0) {
@@ -109,6 +109,8 @@ export default function DiscoveryModal({
       case 'bluetooth':
       case 'ble':
         return <Bluetooth size={12} />
+      case 'serial':
+        return <Cable size={12} />
       default:
         return <Wifi size={12} />
     }
@@ -123,6 +125,8 @@ export default function DiscoveryModal({
       case 'bluetooth':
       case 'ble':
         return 'badge-conn-ble'
+      case 'serial':
+        return 'badge-conn-serial'
       default:
         return 'badge-conn-network'
     }
@@ -215,7 +219,7 @@ export default function DiscoveryModal({
               })}
             </div>
             <div className="modal-footer">
-              <button className="btn-sm btn-secondary" onClick={startScan}>
+              <button className="btn-sm btn-secondary" onClick={() => startScan()}>
                 <Search size={14} />
                 Scan Again
               </button>
@@ -252,10 +256,13 @@ export default function DiscoveryModal({
                 <li>For Bluetooth, make sure it's paired in system settings</li>
               </ul>
             </div>
-            <button className="btn-sm btn-secondary" onClick={startScan}>
+            <button className="btn-sm btn-secondary" onClick={() => startScan()}>
               <Search size={14} />
               Scan Again
             </button>
+            <button className="btn-text" onClick={() => startScan(true)}>
+              Deep scan (more ports, slower)
+            </button>
           </div>
         )}
       </div>
@@ -1,6 +1,9 @@
-import { useState, useEffect } from 'react'
+import { useState, useEffect, useRef } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { listen } from '@tauri-apps/api/event'
+import { getCurrentWindow } from '@tauri-apps/api/window'
+import { errorMessage as describeError } from '../messages'
+import type { LocalizedMessage } from '../messages'
 import {
   enable as enableAutostart,
   disable as disableAutostart,
@@ -24,6 +27,18 @@ import {
   Trash2,
   Download,
   Loader2,
+  Globe,
+  ShieldOff,
+  ShieldCheck,
+  Bell,
+  Wrench,
+  History,
+  StickyNote,
+  RotateCcw,
+  Gauge,
+  Signpost,
+  Pause,
+  Play,
 } from 'lucide-react'
 import ConfirmDialog from './ConfirmDialog'
 import DiscoveryModal from './DiscoveryModal'
@@ -38,6 +53,104 @@ interface AppConfig {
   supabase_url: string
   supabase_anon_key: string
   printers: PrinterConfig[]
+  notifications: NotificationConfig
+  low_resource_mode: boolean
+}
+
+interface LoadSuggestion {
+  cpu_percent: number
+  window_secs: number
+}
+
+interface SelfTestResult {
+  printer_id: string
+  printer_name: string
+  passed: boolean
+  printed: boolean
+  error: string | null
+  tested_at: number
+}
+
+interface UnmatchedStation {
+  job_id: string
+  order_number: string
+  received: string
+  station: string
+}
+
+interface DaemonRun {
+  id: number
+  version: string
+  started_at: number
+  stopped_at: number | null
+  stop_reason: 'clean' | 'update' | 'crash' | null
+}
+
+const STOP_LABELS: Record<NonNullable<DaemonRun['stop_reason']>, string> = {
+  clean: 'afgesloten',
+  update: 'update',
+  crash: 'gecrasht',
+}
+
+interface RecoveryGuide {
+  printer_id: string
+  printer_name: string
+  fault: 'cutter' | 'unrecoverable'
+  steps: LocalizedMessage[]
+  occurrences: number
+  recurring: boolean
+}
+
+interface RecoveryAttempt {
+  printer_id: string
+  fault: 'cutter' | 'unrecoverable' | null
+  recovered: boolean
+  status: string | null
+  automatic: boolean
+}
+
+type AlertKind =
+  | 'paper_out'
+  | 'cover_open'
+  | 'breaker_open'
+  | 'token_expiring'
+  | 'update_available'
+  | 'self_test_failed'
+  | 'mechanical_fault'
+type AlertAction = 'diagnostics' | 'settings' | 'update'
+
+interface NotificationConfig {
+  enabled: boolean
+  disabled: AlertKind[]
+  quiet_hours: { start_hour: number; end_hour: number } | null
+  repeat_minutes: number
+}
+
+interface Alert {
+  id: number
+  kind: AlertKind
+  title: string
+  body: string
+  action: AlertAction
+  subject: string | null
+  created_at: number
+  shown: boolean
+}
+
+const ALERT_KINDS: { kind: AlertKind; label: string }[] = [
+  { kind: 'paper_out', label: 'Papier op' },
+  { kind: 'cover_open', label: 'Klep open' },
+  { kind: 'breaker_open', label: 'Printer gepauzeerd' },
+  { kind: 'token_expiring', label: 'Koppeling verloopt' },
+  { kind: 'update_available', label: 'Update beschikbaar' },
+  { kind: 'self_test_failed', label: 'Printertest mislukt' },
+  { kind: 'mechanical_fault', label: 'Papierstoring' },
+]
+
+const ACTION_LABELS: Record<AlertAction, string> = {
+  diagnostics: 'Open diagnostics',
+  settings: 'Open settings',
+  update: 'View update',
 }
 
 interface PrinterConfig {
@@ -45,6 +158,7 @@ interface PrinterConfig {
   name: string
   connection_type: string
   address: string
+  mac_address?: string | null
   protocol: string
   station: string | null
   is_primary: boolean
@@ -54,6 +168,38 @@ interface PrinterConfig {
     qrcode: boolean
     max_width: number
   }
+  paper_width_checked?: boolean
+  recovery?: {
+    mode: RecoveryMode
+    max_jobs: number
+  }
+  compact?: boolean | null
+  transports?: Transport[]
+  paused?: boolean
+}
+
+type Transport = 'raw' | 'ipp' | 'epos'
+
+/** Transport ladders offered for network printers (tried left to right) */
+const TRANSPORT_LADDERS = ['raw', 'raw,ipp', 'raw,epos', 'raw,ipp,epos', 'epos,raw']
+
+type RecoveryMode = 'off' | 'auto' | 'confirm'
+
+interface RecoveryOffer {
+  printer_id: string
+  printer_name: string
+  outage_started: number
+  jobs: { job_id: string; order_number: string }[]
+  skipped: number
+}
+
+interface WidthDetection {
+  printer_id: string
+  model: string | null
+  width_mm: number | null
+  configured_mm: number
+  corrected: boolean
+  method: 'model' | 'unknown'
 }
 
 interface QueueStats {
@@ -62,6 +208,66 @@ interface QueueStats {
   processing: number
   completed: number
   failed: number
+  business_date?: string
+  completed_today?: number
+  failed_today?: number
+  avg_retries?: number
+  by_station?: JobBreakdown[]
+  by_printer?: JobBreakdown[]
+  hourly?: HourBucket[]
+  failure_reasons?: { class: string; count: number }[]
+}
+
+interface JobBreakdown {
+  key: string
+  total: number
+  pending: number
+  completed: number
+  failed: number
+  avg_retries: number
+}
+
+interface HourBucket {
+  hour: number
+  created: number
+  completed: number
+  failed: number
+}
+
+interface QuarantineStatus {
+  printer_id: string
+  reason: 'repeated_trips' | 'manual'
+  quarantined_secs: number
+  probes: number
+  consecutive_ok: number
+  release_after: number
+  next_probe_secs: number
+}
+
+interface MaintenanceWindow {
+  started_at: string
+  ends_at: string | null
+  reason: string | null
+}
+
+interface JobNote {
+  note: string
+  created_at: number
+}
+
+interface JobHistoryEntry {
+  id: string
+  order_id: string | null
+  order_number: string
+  station: string
+  printer_id: string | null
+  status: 'pending' | 'printing' | 'completed' | 'failed' | 'cancelled'
+  error_message: string | null
+  retry_count: number
+  created_at: number
+  completed_at: number | null
+  correlation_id: string
+  notes: JobNote[]
 }
 
 interface UpdateInfo {
@@ -69,6 +275,29 @@ interface UpdateInfo {
   latest_version: string
 }
 
+type EdgeStatus = 'unknown' | 'ok' | 'slow' | 'failing' | 'unauthorized'
+
+interface EdgeHealth {
+  status: EdgeStatus
+  recent_calls: number
+  p95_ms: number
+  error_rate: number
+  last_success_secs: number | null
+}
+
+interface ConnectionState {
+  state: 'connected' | 'disconnected'
+  edge: EdgeHealth
+}
+
+const EDGE_LABELS: Record<EdgeStatus, string | null> = {
+  unknown: null,
+  ok: null,
+  slow: 'Backend slow',
+  failing: 'Backend errors',
+  unauthorized: 'Pairing expired',
+}
+
 interface MainDashboardProps {
   onReset: () => void
   updateAvailable: UpdateInfo | null
@@ -85,17 +314,36 @@ export default function MainDashboard({
   const [config, setConfig] = useState<AppConfig | null>(null)
   const [queueStats, setQueueStats] = useState<QueueStats | null>(null)
   const [uptime, setUptime] = useState<number>(0)
+  const [restartHistory, setRestartHistory] = useState<DaemonRun[]>([])
   const [showSettings, setShowSettings] = useState(false)
   const [editRestaurantId, setEditRestaurantId] = useState('')
   const [connectionState, setConnectionState] = useState<'connected' | 'disconnected'>(
     'disconnected'
   )
+  const [edgeHealth, setEdgeHealth] = useState<EdgeHealth | null>(null)
   const [removePrinterId, setRemovePrinterId] = useState<string | null>(null)
   const [errorMessage, setErrorMessage] = useState<string | null>(null)
   const [updateChecking, setUpdateChecking] = useState(false)
   const [updateCheckResult, setUpdateCheckResult] = useState<'up-to-date' | 'error' | null>(null)
   const [showDiscovery, setShowDiscovery] = useState(false)
   const [autostartEnabled, setAutostartEnabled] = useState<boolean | null>(null)
+  const [quarantined, setQuarantined] = useState<Map<string, QuarantineStatus>>(new Map())
+  const [activeTransports, setActiveTransports] = useState<Record<string, Transport>>({})
+  const [alerts, setAlerts] = useState<Alert[]>([])
+  const [editNotifications, setEditNotifications] = useState<NotificationConfig | null>(null)
+  const [maintenance, setMaintenance] = useState<MaintenanceWindow | null>(null)
+  const [selfTest, setSelfTest] = useState<SelfTestResult[] | 'running' | null>(null)
+  const [maintenanceMinutes, setMaintenanceMinutes] = useState<number>(60)
+  const [recoveryOffers, setRecoveryOffers] = useState<RecoveryOffer[]>([])
+  const [jobHistory, setJobHistory] = useState<JobHistoryEntry[]>([])
+  const [noteJobId, setNoteJobId] = useState<string | null>(null)
+  const [noteDraft, setNoteDraft] = useState('')
+  const [loadSuggestion, setLoadSuggestion] = useState<LoadSuggestion | null>(null)
+  const [unmatchedStations, setUnmatchedStations] = useState<UnmatchedStation[]>([])
+  const [recoveryGuides, setRecoveryGuides] = useState<RecoveryGuide[]>([])
+  const [retryingRecovery, setRetryingRecovery] = useState<string | null>(null)
+  const [widthChecks, setWidthChecks] = useState<Map<string, WidthDetection>>(new Map())
+  const printersSectionRef = useRef<HTMLDivElement>(null)
   const [testPrintStates, setTestPrintStates] = useState<
     Map<string, 'idle' | 'printing' | 'success' | 'error'>
   >(new Map())
@@ -104,8 +352,16 @@ export default function MainDashboard({
     loadConfig()
     loadQueueStats()
     loadUptime()
+    loadRestartHistory()
     checkConnection()
     loadAutostartState()
+    loadQuarantine()
+    loadActiveTransports()
+    loadAlerts()
+    takeNotificationAction()
+    loadMaintenance()
+    loadJobHistory()
+    loadRecoveryOffers()
 
     const unlistenStats = listen<QueueStats>('queue-stats-updated', (event) => {
       setQueueStats(event.payload)
@@ -117,8 +373,77 @@ export default function MainDashboard({
       setErrorMessage(`Update mislukt: ${event.payload}`)
     })
 
+    // Quarantine and release are propagated as printer status changes
+    const unlistenPrinterStatus = listen('printer-status-changed', () => {
+      loadQuarantine()
+    })
+
+    const unlistenMaintenance = listen<MaintenanceWindow | null>('maintenance-changed', (event) => {
+      setMaintenance(event.payload)
+    })
+
+    // First-connect paper width check: show corrections, ask staff when unknown
+    const unlistenWidth = listen<WidthDetection>('paper-width-detected', (event) => {
+      const detection = event.payload
+      if (detection.corrected) loadConfig()
+      if (detection.corrected || detection.method === 'unknown') {
+        setWidthChecks((prev) => new Map(prev).set(detection.printer_id, detection))
+      }
+    })
+
+    // Paper replaced: failed tickets wait for confirmation, or are already requeued
+    const unlistenRecoveryOffer = listen<RecoveryOffer>('recovery-print-offered', (event) => {
+      setRecoveryOffers((prev) => [
+        event.payload,
+        ...prev.filter((o) => o.printer_id !== event.payload.printer_id),
+      ])
+    })
+    const unlistenRecoveryStart = listen<{ printer_id: string }>('recovery-print-started', (event) => {
+      setRecoveryOffers((prev) => prev.filter((o) => o.printer_id !== event.payload.printer_id))
+      loadJobHistory()
+    })
+
+    // Sustained high CPU: offer low-resource mode
+    const unlistenLoad = listen<LoadSuggestion>('low-resource-suggested', (event) => {
+      setLoadSuggestion(event.payload)
+    })
+
+    // Tickets for a station no printer or alias knows: one banner entry per station
+    const unlistenStation = listen<UnmatchedStation>('station-unmatched', (event) => {
+      setUnmatchedStations((prev) => [
+        event.payload,
+        ...prev.filter((u) => u.station !== event.payload.station),
+      ])
+    })
+
+    // Jam or cutter error the automatic reset didn't clear: walk staff through it
+    const unlistenGuide = listen<RecoveryGuide>('printer-recovery-guide', (event) => {
+      setRecoveryGuides((prev) => [
+        event.payload,
+        ...prev.filter((g) => g.printer_id !== event.payload.printer_id),
+      ])
+    })
+    const unlistenAttempt = listen<RecoveryAttempt>('printer-recovery-attempted', (event) => {
+      if (event.payload.recovered) {
+        setRecoveryGuides((prev) => prev.filter((g) => g.printer_id !== event.payload.printer_id))
+      }
+    })
+
+    const unlistenAlerts = listen<Alert>('notification-raised', (event) => {
+      setAlerts((prev) => [event.payload, ...prev.filter((a) => a.id !== event.payload.id)])
+    })
+
+    // Desktop notifications can't report clicks: follow up on the last one when the
+    // user brings the window to the front
+    const unlistenFocus = getCurrentWindow().onFocusChanged(({ payload: focused }) => {
+      if (focused) takeNotificationAction()
+    })
+
     const interval = setInterval(() => {
+      loadQuarantine()
+      loadActiveTransports()
       loadQueueStats()
+      loadJobHistory()
       loadUptime()
       checkConnection()
     }, 5000)
@@ -127,6 +452,17 @@ export default function MainDashboard({
       clearInterval(interval)
       unlistenStats.then((fn) => fn())
       unlistenError.then((fn) => fn())
+      unlistenPrinterStatus.then((fn) => fn())
+      unlistenAlerts.then((fn) => fn())
+      unlistenMaintenance.then((fn) => fn())
+      unlistenWidth.then((fn) => fn())
+      unlistenRecoveryOffer.then((fn) => fn())
+      unlistenRecoveryStart.then((fn) => fn())
+      unlistenLoad.then((fn) => fn())
+      unlistenStation.then((fn) => fn())
+      unlistenGuide.then((fn) => fn())
+      unlistenAttempt.then((fn) => fn())
+      unlistenFocus.then((fn) => fn())
     }
   }, [])
 
@@ -135,6 +471,7 @@ export default function MainDashboard({
       const cfg = await invoke<AppConfig>('get_config')
       setConfig(cfg)
       setEditRestaurantId(cfg.restaurant_id || '')
+      setEditNotifications(cfg.notifications)
     } catch (error) {
       console.error('Failed to load config:', error)
     }
@@ -163,6 +500,17 @@ export default function MainDashboard({
     }
   }
 
+  async function handleLowResourceToggle(enabled: boolean) {
+    try {
+      await invoke('set_low_resource_mode', { enabled })
+      setConfig((prev) => (prev ? { ...prev, low_resource_mode: enabled } : prev))
+      setLoadSuggestion(null)
+    } catch (error) {
+      console.error('Failed to set low-resource mode:', error)
+      setErrorMessage(`Failed to set low-resource mode: ${describeError(error)}`)
+    }
+  }
+
   async function loadQueueStats() {
     try {
       const stats = await invoke<QueueStats>('get_queue_stats')
@@ -172,12 +520,247 @@ export default function MainDashboard({
     }
   }
 
+  async function loadQuarantine() {
+    try {
+      const list = await invoke<QuarantineStatus[]>('get_quarantine_status')
+      setQuarantined(new Map(list.map((q) => [q.printer_id, q])))
+    } catch (error) {
+      console.error('Failed to load quarantine status:', error)
+    }
+  }
+
+  async function loadActiveTransports() {
+    try {
+      setActiveTransports(await invoke<Record<string, Transport>>('get_printer_transports'))
+    } catch (error) {
+      console.error('Failed to load printer transports:', error)
+    }
+  }
+
+  async function handleTransports(printerId: string, ladder: string) {
+    try {
+      await invoke('set_printer_transports', { printerId, transports: ladder.split(',') as Transport[] })
+      await loadConfig()
+    } catch (error) {
+      console.error('Failed to set transports:', error)
+      setErrorMessage(`Failed to set transports: ${describeError(error)}`)
+    }
+  }
+
+  async function loadMaintenance() {
+    try {
+      setMaintenance(await invoke<MaintenanceWindow | null>('get_maintenance'))
+    } catch (error) {
+      console.error('Failed to load maintenance state:', error)
+    }
+  }
+
+  async function handleStartMaintenance() {
+    try {
+      const started = await invoke<MaintenanceWindow>('start_maintenance', {
+        minutes: maintenanceMinutes > 0 ? maintenanceMinutes : null,
+      })
+      setMaintenance(started)
+      setShowSettings(false)
+    } catch (error) {
+      console.error('Failed to start maintenance:', error)
+      setErrorMessage(`Onderhoudsmodus starten mislukt: ${describeError(error)}`)
+    }
+  }
+
+  async function handleEndMaintenance() {
+    try {
+      await invoke('end_maintenance')
+      setMaintenance(null)
+    } catch (error) {
+      console.error('Failed to end maintenance:', error)
+      setErrorMessage(`Failed to end maintenance: ${describeError(error)}`)
+    }
+  }
+
+  async function handleRunSelfTest() {
+    setSelfTest('running')
+    try {
+      setSelfTest(await invoke<SelfTestResult[]>('run_self_test'))
+    } catch (error) {
+      console.error('Failed to run self-test:', error)
+      setSelfTest(null)
+      setErrorMessage(`Printertest mislukt: ${describeError(error)}`)
+    }
+  }
+
+  async function loadRecoveryOffers() {
+    try {
+      setRecoveryOffers(await invoke<RecoveryOffer[]>('get_recovery_offers'))
+    } catch (error) {
+      console.error('Failed to load recovery prints:', error)
+    }
+  }
+
+  async function handleRecoveryPrint(printerId: string, confirm: boolean) {
+    try {
+      await invoke(confirm ? 'confirm_recovery_print' : 'dismiss_recovery_print', { printerId })
+      setRecoveryOffers((prev) => prev.filter((o) => o.printer_id !== printerId))
+    } catch (error) {
+      console.error('Recovery print failed:', error)
+      setErrorMessage(`Recovery print failed: ${describeError(error)}`)
+    }
+  }
+
+  async function handleRecoveryMode(printerId: string, mode: RecoveryMode) {
+    try {
+      await invoke('set_recovery_mode', { printerId, mode })
+      await loadConfig()
+    } catch (error) {
+      console.error('Failed to set recovery mode:', error)
+      setErrorMessage(`Failed to set recovery mode: ${describeError(error)}`)
+    }
+  }
+
+  async function handleCompactLayout(printerId: string, value: string) {
+    try {
+      const compact = value === 'default' ? null : value === 'compact'
+      await invoke('set_printer_compact', { printerId, compact })
+      await loadConfig()
+    } catch (error) {
+      console.error('Failed to set ticket layout:', error)
+      setErrorMessage(`Failed to set ticket layout: ${describeError(error)}`)
+    }
+  }
+
+  async function handleRetryRecovery(printerId: string) {
+    setRetryingRecovery(printerId)
+    try {
+      const attempt = await invoke<RecoveryAttempt>('attempt_printer_recovery', { printerId })
+      if (attempt.recovered) {
+        setRecoveryGuides((prev) => prev.filter((g) => g.printer_id !== printerId))
+      } else {
+        setErrorMessage('De printer meldt nog steeds een storing. Controleer de stappen en probeer opnieuw.')
+      }
+    } catch (error) {
+      console.error('Printer recovery failed:', error)
+      setErrorMessage(`Printer herstellen mislukt: ${describeError(error)}`)
+    } finally {
+      setRetryingRecovery(null)
+    }
+  }
+
+  async function loadJobHistory() {
+    try {
+      setJobHistory(await invoke<JobHistoryEntry[]>('get_job_history', { limit: 10 }))
+    } catch (error) {
+      console.error('Failed to load job history:', error)
+    }
+  }
+
+  function startNote(jobId: string) {
+    setNoteJobId(jobId)
+    setNoteDraft('')
+  }
+
+  async function handleSaveNote() {
+    if (!noteJobId || !noteDraft.trim()) return
+    try {
+      const updated = await invoke<JobHistoryEntry>('annotate_job', {
+        jobId: noteJobId,
+        note: noteDraft,
+      })
+      setJobHistory((prev) => prev.map((j) => (j.id === updated.id ? updated : j)))
+      setNoteJobId(null)
+      setNoteDraft('')
+    } catch (error) {
+      console.error('Failed to add note:', error)
+      setErrorMessage(`Failed to add note: ${describeError(error)}`)
+    }
+  }
+
+  async function handleCancelJob(jobId: string) {
+    try {
+      const cancelled = await invoke<boolean>('cancel_job', { jobId })
+      if (!cancelled) setErrorMessage('This job already printed or failed')
+      loadJobHistory()
+    } catch (error) {
+      console.error('Failed to cancel job:', error)
+      setErrorMessage(`Failed to cancel job: ${describeError(error)}`)
+    }
+  }
+
+  async function loadAlerts() {
+    try {
+      setAlerts(await invoke<Alert[]>('get_notifications'))
+    } catch (error) {
+      console.error('Failed to load notifications:', error)
+    }
+  }
+
+  async function handleClearAlerts() {
+    try {
+      await invoke('clear_notifications')
+      setAlerts([])
+    } catch (error) {
+      console.error('Failed to clear notifications:', error)
+    }
+  }
+
+  async function takeNotificationAction() {
+    try {
+      const action = await invoke<AlertAction | null>('take_notification_action')
+      if (action) handleAlertAction(action)
+    } catch (error) {
+      console.error('Failed to read notification action:', error)
+    }
+  }
+
+  function handleAlertAction(action: AlertAction) {
+    if (action === 'diagnostics') {
+      setShowSettings(false)
+      printersSectionRef.current?.scrollIntoView({ behavior: 'smooth' })
+    } else {
+      setShowSettings(true)
+    }
+  }
+
+  function toggleAlertKind(kind: AlertKind, enabled: boolean) {
+    if (!editNotifications) return
+    const disabled = editNotifications.disabled.filter((k) => k !== kind)
+    setEditNotifications({
+      ...editNotifications,
+      disabled: enabled ? disabled : [...disabled, kind],
+    })
+  }
+
+  async function handleToggleQuarantine(printerId: string) {
+    try {
+      if (quarantined.has(printerId)) {
+        await invoke('release_printer', { printerId })
+      } else {
+        await invoke('quarantine_printer', { printerId })
+      }
+      await loadQuarantine()
+    } catch (error) {
+      console.error('Failed to change quarantine:', error)
+      setErrorMessage(`Failed to change quarantine: ${describeError(error)}`)
+    }
+  }
+
+  async function handleTogglePause(printer: PrinterConfig) {
+    try {
+      await invoke(printer.paused ? 'resume_printer' : 'pause_printer', { printerId: printer.id })
+      await loadConfig()
+    } catch (error) {
+      console.error('Failed to pause printer:', error)
+      setErrorMessage(`Failed to pause printer: ${describeError(error)}`)
+    }
+  }
+
   async function checkConnection() {
     try {
-      const state = await invoke<string>('get_connection_state')
-      setConnectionState(state as 'connected' | 'disconnected')
+      const state = await invoke<ConnectionState>('get_connection_state')
+      setConnectionState(state.state)
+      setEdgeHealth(state.edge)
     } catch (error) {
       setConnectionState('disconnected')
+      setEdgeHealth(null)
     }
   }
 
@@ -190,6 +773,27 @@ export default function MainDashboard({
     }
   }
 
+  async function loadRestartHistory() {
+    try {
+      setRestartHistory(await invoke<DaemonRun[]>('get_restart_history', { limit: 10 }))
+    } catch (error) {
+      console.error('Failed to load restart history:', error)
+    }
+  }
+
+  // Previous runs, one line each, for the uptime tooltip
+  function describeRestarts(): string {
+    return restartHistory
+      .slice(1)
+      .map((run) => {
+        const started = new Date(run.started_at * 1000).toLocaleString()
+        const stopped = run.stopped_at ? new Date(run.stopped_at * 1000).toLocaleString() : '?'
+        const reason = run.stop_reason ? STOP_LABELS[run.stop_reason] : '?'
+        return `v${run.version}: ${started} – ${stopped} (${reason})`
+      })
+      .join('\n')
+  }
+
   function formatUptime(seconds: number): string {
     if (seconds < 60) return `${seconds}s`
     if (seconds < 3600) return `${Math.floor(seconds / 60)}m`
@@ -205,6 +809,7 @@ export default function MainDashboard({
       const updatedConfig = {
         ...config,
         restaurant_id: editRestaurantId || null,
+        notifications: editNotifications ?? config.notifications,
       }
 
       await invoke('save_config', { config: updatedConfig })
@@ -216,7 +821,7 @@ export default function MainDashboard({
       }
     } catch (error) {
       console.error('Failed to save settings:', error)
-      setErrorMessage(`Failed to save settings: ${error}`)
+      setErrorMessage(`Failed to save settings: ${describeError(error)}`)
     }
   }
 
@@ -246,6 +851,43 @@ export default function MainDashboard({
     }
   }
 
+  function dismissWidthCheck(printerId: string) {
+    setWidthChecks((prev) => {
+      const next = new Map(prev)
+      next.delete(printerId)
+      return next
+    })
+  }
+
+  async function handlePrintWidthRuler(printerId: string) {
+    try {
+      await invoke('print_width_ruler', { printerId })
+    } catch (error) {
+      console.error('Width ruler print failed:', error)
+      setErrorMessage(`Width ruler print failed: ${describeError(error)}`)
+    }
+  }
+
+  async function handleSetPaperWidth(printerId: string, widthMm: number) {
+    try {
+      await invoke('set_paper_width', { printerId, widthMm })
+      dismissWidthCheck(printerId)
+      await loadConfig()
+    } catch (error) {
+      console.error('Failed to set paper width:', error)
+      setErrorMessage(`Failed to set paper width: ${describeError(error)}`)
+    }
+  }
+
+  async function handleOpenPrinterAdmin(printerId: string) {
+    try {
+      await invoke('open_printer_admin', { printerId })
+    } catch (error) {
+      console.error('Failed to open printer web page:', error)
+      setErrorMessage(`Failed to open printer web page: ${describeError(error)}`)
+    }
+  }
+
   function handleRemovePrinter(printerId: string) {
     setRemovePrinterId(printerId)
   }
@@ -301,7 +943,7 @@ export default function MainDashboard({
       await loadConfig()
     } catch (error) {
       console.error('Failed to add printers:', error)
-      setErrorMessage(`Failed to add printers: ${error}`)
+      setErrorMessage(`Failed to add printers: ${describeError(error)}`)
     }
   }
 
@@ -338,7 +980,7 @@ export default function MainDashboard({
       await checkConnection()
     } catch (error) {
       console.error('Failed to reconnect:', error)
-      setErrorMessage(`Failed to reconnect: ${error}`)
+      setErrorMessage(`Failed to reconnect: ${describeError(error)}`)
     }
   }
 
@@ -369,6 +1011,14 @@ export default function MainDashboard({
             {connectionState === 'connected' ? <Wifi size={14} /> : <WifiOff size={14} />}
             {connectionState === 'connected' ? 'Connected' : 'Disconnected'}
           </div>
+          {edgeHealth && EDGE_LABELS[edgeHealth.status] && (
+            <div
+              className="connection-badge backend-degraded"
+              title={`Backend p95 ${edgeHealth.p95_ms} ms, ${Math.round(edgeHealth.error_rate * 100)}% errors over the last ${edgeHealth.recent_calls} calls`}
+            >
+              {EDGE_LABELS[edgeHealth.status]}
+            </div>
+          )}
           <button className="btn-icon" onClick={() => setShowSettings(true)} title="Instellingen">
             <Settings size={18} />
           </button>
@@ -385,6 +1035,106 @@ export default function MainDashboard({
         </div>
       )}
 
+      {/* Maintenance Banner */}
+      {maintenance && (
+        <div className="maintenance-banner">
+          <Wrench size={14} />
+          <span>
+            Maintenance mode: printing paused
+            {maintenance.ends_at &&
+              ` until ${new Date(maintenance.ends_at).toLocaleTimeString([], {
+                hour: '2-digit',
+                minute: '2-digit',
+              })}`}
+            {maintenance.reason && <> &middot; {maintenance.reason}</>}
+          </span>
+          <button className="btn-sm btn-secondary" onClick={handleEndMaintenance}>
+            Resume now
+          </button>
+        </div>
+      )}
+
+      {/* Low-resource Suggestion */}
+      {loadSuggestion && !config.low_resource_mode && (
+        <div className="maintenance-banner">
+          <Gauge size={14} />
+          <span>
+            This computer has been at {Math.round(loadSuggestion.cpu_percent)}% CPU for{' '}
+            {Math.round(loadSuggestion.window_secs / 60)} minutes. Low-resource mode prints fewer
+            tickets at once and polls less often.
+          </span>
+          <button className="btn-sm btn-primary" onClick={() => handleLowResourceToggle(true)}>
+            Turn on
+          </button>
+          <button className="btn-sm btn-secondary" onClick={() => setLoadSuggestion(null)}>
+            Dismiss
+          </button>
+        </div>
+      )}
+
+      {/* Unknown Stations */}
+      {unmatchedStations.length > 0 && (
+        <div className="maintenance-banner">
+          <Signpost size={14} />
+          <span>
+            Unknown station{unmatchedStations.length === 1 ? '' : 's'}{' '}
+            {unmatchedStations.map((u) => `"${u.received.trim()}" (#${u.order_number})`).join(', ')}.
+            Set it on a printer or add a station alias in the settings.
+          </span>
+          <button className="btn-sm btn-secondary" onClick={() => setUnmatchedStations([])}>
+            Dismiss
+          </button>
+        </div>
+      )}
+
+      {/* Jam / cutter recovery */}
+      {recoveryGuides.map((guide) => (
+        <div key={guide.printer_id} className="recovery-banner jam-guide">
+          <Wrench size={14} />
+          <span>
+            <strong>
+              {guide.printer_name}: {guide.fault === 'cutter' ? 'cutter jammed' : 'paper jam or printer error'}
+            </strong>
+            <ol>
+              {guide.steps.map((step) => (
+                <li key={step.key}>{step.message}</li>
+              ))}
+            </ol>
+            {guide.recurring && (
+              <em>
+                {guide.occurrences} storingen deze week. Laat de printer nakijken.
+              </em>
+            )}
+          </span>
+          <button
+            className="btn-sm btn-primary"
+            disabled={retryingRecovery === guide.printer_id}
+            onClick={() => handleRetryRecovery(guide.printer_id)}
+          >
+            {retryingRecovery === guide.printer_id ? 'Checking…' : 'Try again'}
+          </button>
+        </div>
+      ))}
+
+      {/* Recovery Prints */}
+      {recoveryOffers.map((offer) => (
+        <div key={offer.printer_id} className="recovery-banner">
+          <RotateCcw size={14} />
+          <span>
+            {offer.printer_name} has paper again. Reprint {offer.jobs.length} ticket
+            {offer.jobs.length === 1 ? '' : 's'} that failed meanwhile (
+            {offer.jobs.map((j) => `#${j.order_number}`).join(', ')})?
+            {offer.skipped > 0 && <> {offer.skipped} older ticket(s) are not included.</>}
+          </span>
+          <button className="btn-sm btn-primary" onClick={() => handleRecoveryPrint(offer.printer_id, true)}>
+            Reprint
+          </button>
+          <button className="btn-sm btn-secondary" onClick={() => handleRecoveryPrint(offer.printer_id, false)}>
+            Skip
+          </button>
+        </div>
+      ))}
+
       {/* Stats Strip */}
       <div className="stats-strip">
         <div className="stat-cell">
@@ -399,18 +1149,23 @@ export default function MainDashboard({
         </div>
         <div className="stat-cell stat-success">
           <CircleCheck size={14} />
-          <span className="stat-val">{queueStats?.completed || 0}</span>
-          <span className="stat-lbl">Done</span>
+          <span className="stat-val">{queueStats?.completed_today || 0}</span>
+          <span className="stat-lbl">Done today</span>
         </div>
         <div className="stat-cell stat-danger">
           <CircleX size={14} />
-          <span className="stat-val">{queueStats?.failed || 0}</span>
-          <span className="stat-lbl">Failed</span>
+          <span className="stat-val">{queueStats?.failed_today || 0}</span>
+          <span className="stat-lbl">Failed today</span>
         </div>
-        <div className="stat-cell">
+        <div
+          className={`stat-cell ${restartHistory[1]?.stop_reason === 'crash' ? 'stat-danger' : ''}`}
+          title={describeRestarts() || undefined}
+        >
           <Timer size={14} />
           <span className="stat-val">{formatUptime(uptime)}</span>
-          <span className="stat-lbl">Uptime</span>
+          <span className="stat-lbl">
+            {restartHistory[1]?.stop_reason === 'crash' ? 'Uptime (na crash)' : 'Uptime'}
+          </span>
         </div>
         <div className="stat-cell">
           <BarChart3 size={14} />
@@ -419,8 +1174,73 @@ export default function MainDashboard({
         </div>
       </div>
 
+      {/* Where failures come from (last 7 days) */}
+      {(queueStats?.failure_reasons?.length ?? 0) > 0 && (
+        <div className="analytics-section">
+          <div className="section-header">
+            <h2>
+              <BarChart3 size={14} /> Failures
+            </h2>
+            <span className="analytics-meta">
+              avg. {(queueStats?.avg_retries ?? 0).toFixed(1)} retries per ticket
+            </span>
+          </div>
+          <div className="analytics-hourly" title="Tickets per hour, last 24h (red: failed)">
+            {queueStats?.hourly?.map((bucket) => {
+              const peak = Math.max(1, ...(queueStats.hourly ?? []).map((b) => b.created))
+              return (
+                <div key={bucket.hour} className="analytics-bar" style={{ height: `${(bucket.created / peak) * 100}%` }}>
+                  <div className="analytics-bar-failed" style={{ height: `${bucket.created ? (bucket.failed / bucket.created) * 100 : 0}%` }} />
+                </div>
+              )
+            })}
+          </div>
+          <div className="analytics-meta">
+            {[...(queueStats?.by_station ?? []), ...(queueStats?.by_printer ?? [])]
+              .filter((b) => b.failed > 0)
+              .slice(0, 4)
+              .map((b) => `${b.key}: ${b.failed}/${b.total} failed`)
+              .join(' · ')}
+          </div>
+          <div className="analytics-meta">
+            {queueStats?.failure_reasons?.map((r) => `${r.class.replace(/_/g, ' ')} (${r.count})`).join(', ')}
+          </div>
+        </div>
+      )}
+
+      {/* Alerts */}
+      {alerts.length > 0 && (
+        <div className="alerts-section">
+          <div className="section-header">
+            <h2>
+              <Bell size={14} /> Alerts
+            </h2>
+            <button className="btn-sm btn-secondary" onClick={handleClearAlerts}>
+              Clear
+            </button>
+          </div>
+          {alerts.slice(0, 5).map((alert) => (
+            <div key={alert.id} className={`alert-row alert-${alert.kind}`}>
+              <div className="alert-row-info">
+                <div className="alert-row-title">{alert.title}</div>
+                <div className="alert-row-meta">
+                  {new Date(alert.created_at * 1000).toLocaleTimeString([], {
+                    hour: '2-digit',
+                    minute: '2-digit',
+                  })}{' '}
+                  &middot; {alert.body}
+                </div>
+              </div>
+              <button className="btn-sm btn-secondary" onClick={() => handleAlertAction(alert.action)}>
+                {ACTION_LABELS[alert.action]}
+              </button>
+            </div>
+          ))}
+        </div>
+      )}
+
       {/* Printers Section */}
-      <div className="printers-section">
+      <div className="printers-section" ref={printersSectionRef}>
         <div className="section-header">
           <h2>Configured Printers</h2>
           <div className="section-actions">
@@ -449,11 +1269,19 @@ export default function MainDashboard({
                     <div className="printer-row-name">
                       {printer.name}
                       {printer.is_primary && <span className="badge-primary">Primary</span>}
+                      {quarantined.has(printer.id) && (
+                        <span className="badge-quarantine">Quarantined</span>
+                      )}
+                      {printer.paused && <span className="badge-quarantine">Paused</span>}
                     </div>
                     <div className="printer-row-meta">
                       {printer.connection_type.toUpperCase()}
                       {printer.address && <> &middot; {printer.address}</>}
+                      {activeTransports[printer.id] && activeTransports[printer.id] !== 'raw' && (
+                        <> &middot; via {activeTransports[printer.id].toUpperCase()}</>
+                      )}
                       {printer.station && <> &middot; {printer.station}</>} &middot;{' '}
+                      {printer.capabilities.max_width <= 32 ? '58mm' : '80mm'} &middot;{' '}
                       {[
                         printer.capabilities.cutter && 'Cutter',
                         printer.capabilities.qrcode && 'QR',
@@ -462,6 +1290,47 @@ export default function MainDashboard({
                         .filter(Boolean)
                         .join(', ')}
                     </div>
+                    {(() => {
+                      const q = quarantined.get(printer.id)
+                      if (!q) return null
+                      return (
+                        <div className="printer-row-quarantine">
+                          {q.reason === 'manual' ? 'Sidelined manually' : 'Kept failing'} &middot;
+                          probes {q.consecutive_ok}/{q.release_after} OK &middot; next check in{' '}
+                          {Math.ceil(q.next_probe_secs / 60)} min
+                        </div>
+                      )
+                    })()}
+                    {(() => {
+                      const check = widthChecks.get(printer.id)
+                      if (!check) return null
+                      if (check.corrected) {
+                        return (
+                          <div className="printer-row-width">
+                            Paper width set to {check.width_mm}mm ({check.model} reports{' '}
+                            {check.width_mm}mm, was {check.configured_mm}mm)
+                            <button className="btn-link" onClick={() => dismissWidthCheck(printer.id)}>
+                              OK
+                            </button>
+                          </div>
+                        )
+                      }
+                      return (
+                        <div className="printer-row-width">
+                          Paper width unknown &middot; print the ruler: one line up to 48 is 80mm,
+                          a break after 32 is 58mm.
+                          <button className="btn-link" onClick={() => handlePrintWidthRuler(printer.id)}>
+                            Print ruler
+                          </button>
+                          <button className="btn-link" onClick={() => handleSetPaperWidth(printer.id, 58)}>
+                            58mm
+                          </button>
+                          <button className="btn-link" onClick={() => handleSetPaperWidth(printer.id, 80)}>
+                            80mm
+                          </button>
+                        </div>
+                      )
+                    })()}
                   </div>
                 </div>
                 <div className="printer-row-actions">
@@ -484,6 +1353,69 @@ export default function MainDashboard({
                       </button>
                     )
                   })()}
+                  <select
+                    className="recovery-select"
+                    value={printer.recovery?.mode ?? 'confirm'}
+                    onChange={(e) => handleRecoveryMode(printer.id, e.target.value as RecoveryMode)}
+                    title="Reprint failed tickets after paper replacement"
+                  >
+                    <option value="confirm">Reprint: ask</option>
+                    <option value="auto">Reprint: auto</option>
+                    <option value="off">Reprint: off</option>
+                  </select>
+                  <select
+                    className="recovery-select"
+                    value={printer.compact == null ? 'default' : printer.compact ? 'compact' : 'normal'}
+                    onChange={(e) => handleCompactLayout(printer.id, e.target.value)}
+                    title="Ticket layout (compact: small font, less paper; default on Bluetooth)"
+                  >
+                    <option value="default">Layout: default</option>
+                    <option value="normal">Layout: normal</option>
+                    <option value="compact">Layout: compact</option>
+                  </select>
+                  {printer.connection_type === 'network' && (
+                    <select
+                      className="recovery-select"
+                      value={(printer.transports?.length ? printer.transports : ['raw']).join(',')}
+                      onChange={(e) => handleTransports(printer.id, e.target.value)}
+                      title="Transports tried in order when printing fails"
+                    >
+                      {TRANSPORT_LADDERS.map((ladder) => (
+                        <option key={ladder} value={ladder}>
+                          Via: {ladder.replace(/,/g, ' → ').toUpperCase()}
+                        </option>
+                      ))}
+                      {!!printer.transports?.length &&
+                        !TRANSPORT_LADDERS.includes(printer.transports.join(',')) && (
+                          <option value={printer.transports.join(',')}>
+                            Via: {printer.transports.join(' → ').toUpperCase()}
+                          </option>
+                        )}
+                    </select>
+                  )}
+                  <button
+                    className="btn-icon-sm"
+                    onClick={() => handleTogglePause(printer)}
+                    title={printer.paused ? 'Resume Printer' : 'Pause Printer (jobs wait)'}
+                  >
+                    {printer.paused ? <Play size={14} /> : <Pause size={14} />}
+                  </button>
+                  <button
+                    className="btn-icon-sm"
+                    onClick={() => handleToggleQuarantine(printer.id)}
+                    title={quarantined.has(printer.id) ? 'Return to Rotation' : 'Quarantine Printer'}
+                  >
+                    {quarantined.has(printer.id) ? <ShieldCheck size={14} /> : <ShieldOff size={14} />}
+                  </button>
+                  {printer.connection_type === 'network' && (
+                    <button
+                      className="btn-icon-sm"
+                      onClick={() => handleOpenPrinterAdmin(printer.id)}
+                      title="Open Printer Web Page"
+                    >
+                      <Globe size={14} />
+                    </button>
+                  )}
                   <button
                     className="btn-icon-sm btn-icon-danger"
                     onClick={() => handleRemovePrinter(printer.id)}
@@ -498,6 +1430,83 @@ export default function MainDashboard({
         </div>
       </div>
 
+      {/* Job History */}
+      {jobHistory.length > 0 && (
+        <div className="jobs-section">
+          <div className="section-header">
+            <h2>
+              <History size={14} /> Recent Jobs
+            </h2>
+          </div>
+          {jobHistory.map((job) => (
+            <div key={job.id} className={`job-row job-${job.status}`}>
+              <div className="job-row-main">
+                <div className="job-row-info">
+                  <div className="job-row-title">
+                    #{job.order_number} &middot; {job.station}
+                    <span className="job-row-status">{job.status}</span>
+                  </div>
+                  <div className="job-row-meta">
+                    {new Date(job.created_at * 1000).toLocaleTimeString([], {
+                      hour: '2-digit',
+                      minute: '2-digit',
+                    })}
+                    {job.printer_id && <> &middot; {job.printer_id}</>}
+                    <> &middot; ref {job.correlation_id.slice(0, 8)}</>
+                    {job.error_message && <> &middot; {job.error_message}</>}
+                  </div>
+                  {job.notes.map((n) => (
+                    <div key={n.created_at + n.note} className="job-row-note">
+                      <StickyNote size={11} /> {n.note}
+                    </div>
+                  ))}
+                </div>
+                {(job.status === 'pending' || job.status === 'printing') && (
+                  <button
+                    className="btn-icon-sm"
+                    onClick={() => handleCancelJob(job.id)}
+                    title="Cancel Job"
+                  >
+                    <X size={14} />
+                  </button>
+                )}
+                {noteJobId !== job.id && (
+                  <button
+                    className="btn-icon-sm"
+                    onClick={() => startNote(job.id)}
+                    title="Add Note"
+                  >
+                    <StickyNote size={14} />
+                  </button>
+                )}
+              </div>
+              {noteJobId === job.id && (
+                <div className="job-note-form">
+                  <input
+                    type="text"
+                    value={noteDraft}
+                    maxLength={500}
+                    placeholder="e.g. printer was unplugged"
+                    autoFocus
+                    onChange={(e) => setNoteDraft(e.target.value)}
+                    onKeyDown={(e) => {
+                      if (e.key === 'Enter') handleSaveNote()
+                      if (e.key === 'Escape') setNoteJobId(null)
+                    }}
+                  />
+                  <button className="btn-sm btn-primary" onClick={handleSaveNote} disabled={!noteDraft.trim()}>
+                    Save
+                  </button>
+                  <button className="btn-sm btn-secondary" onClick={() => setNoteJobId(null)}>
+                    Cancel
+                  </button>
+                </div>
+              )}
+            </div>
+          ))}
+        </div>
+      )}
+
       {/* Settings Modal */}
       {showSettings && (
         <div className="modal-overlay" onClick={() => setShowSettings(false)}>
@@ -587,6 +1596,143 @@ export default function MainDashboard({
                   <span className="toggle-slider"></span>
                 </label>
               </div>
+
+              <div className="settings-info-row">
+                <span className="settings-info-label">Zuinige modus (trage pc)</span>
+                <label className="toggle-switch">
+                  <input
+                    type="checkbox"
+                    checked={config.low_resource_mode}
+                    onChange={(e) => handleLowResourceToggle(e.target.checked)}
+                  />
+                  <span className="toggle-slider"></span>
+                </label>
+              </div>
+
+              <div className="settings-info-row">
+                <span className="settings-info-label">Printertest</span>
+                <div className="settings-version-row">
+                  {Array.isArray(selfTest) && (
+                    <span className="settings-version-number">
+                      {selfTest.filter((r) => r.passed).length}/{selfTest.length} in orde
+                      {selfTest
+                        .filter((r) => !r.passed)
+                        .map((r) => ` · ${r.printer_name}: ${r.error}`)
+                        .join('')}
+                    </span>
+                  )}
+                  <button
+                    className="btn-sm btn-secondary"
+                    disabled={selfTest === 'running'}
+                    onClick={handleRunSelfTest}
+                  >
+                    {selfTest === 'running' ? <Loader2 size={12} className="spin" /> : 'Nu testen'}
+                  </button>
+                </div>
+              </div>
+
+              <div className="settings-info-row">
+                <span className="settings-info-label">Onderhoudsmodus</span>
+                {maintenance ? (
+                  <button className="btn-sm btn-secondary" onClick={handleEndMaintenance}>
+                    Beëindigen
+                  </button>
+                ) : (
+                  <div className="settings-version-row">
+                    <select
+                      aria-label="Duur"
+                      value={maintenanceMinutes}
+                      onChange={(e) => setMaintenanceMinutes(Number(e.target.value))}
+                    >
+                      <option value={30}>30 min</option>
+                      <option value={60}>1 uur</option>
+                      <option value={120}>2 uur</option>
+                      <option value={0}>Tot ik stop</option>
+                    </select>
+                    <button className="btn-sm btn-secondary" onClick={handleStartMaintenance}>
+                      <Wrench size={12} />
+                      Starten
+                    </button>
+                  </div>
+                )}
+              </div>
+
+              {editNotifications && (
+                <>
+                  <div className="settings-info-row">
+                    <span className="settings-info-label">Meldingen</span>
+                    <label className="toggle-switch">
+                      <input
+                        type="checkbox"
+                        checked={editNotifications.enabled}
+                        onChange={(e) =>
+                          setEditNotifications({ ...editNotifications, enabled: e.target.checked })
+                        }
+                      />
+                      <span className="toggle-slider"></span>
+                    </label>
+                  </div>
+
+                  {editNotifications.enabled && (
+                    <div className="settings-notifications">
+                      {ALERT_KINDS.map(({ kind, label }) => (
+                        <label key={kind} className="settings-checkbox">
+                          <input
+                            type="checkbox"
+                            checked={!editNotifications.disabled.includes(kind)}
+                            onChange={(e) => toggleAlertKind(kind, e.target.checked)}
+                          />
+                          {label}
+                        </label>
+                      ))}
+
+                      <label className="settings-checkbox">
+                        <input
+                          type="checkbox"
+                          checked={editNotifications.quiet_hours !== null}
+                          onChange={(e) =>
+                            setEditNotifications({
+                              ...editNotifications,
+                              quiet_hours: e.target.checked ? { start_hour: 23, end_hour: 7 } : null,
+                            })
+                          }
+                        />
+                        Stille uren
+                      </label>
+                      {editNotifications.quiet_hours && (
+                        <div className="settings-quiet-hours">
+                          {(['start_hour', 'end_hour'] as const).map((field, i) => (
+                            <select
+                              key={field}
+                              aria-label={i === 0 ? 'Van' : 'Tot'}
+                              value={editNotifications.quiet_hours![field]}
+                              onChange={(e) =>
+                                setEditNotifications({
+                                  ...editNotifications,
+                                  quiet_hours: {
+                                    ...editNotifications.quiet_hours!,
+                                    [field]: Number(e.target.value),
+                                  },
+                                })
+                              }
+                            >
+                              {Array.from({ length: 24 }, (_, h) => (
+                                <option key={h} value={h}>
+                                  {String(h).padStart(2, '0')}:00
+                                </option>
+                              ))}
+                            </select>
+                          ))}
+                        </div>
+                      )}
+                      <p className="form-hint">
+                        Meldingen blijven in de lijst op het dashboard staan, ook als ze niet getoond
+                        worden.
+                      </p>
+                    </div>
+                  )}
+                </>
+              )}
             </div>
 
             <div className="modal-footer">
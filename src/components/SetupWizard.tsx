@@ -1,4 +1,4 @@
-import { useState } from 'react'
+import { useEffect, useState } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { enable as enableAutostart } from '@tauri-apps/plugin-autostart'
 import { Check } from 'lucide-react'
@@ -10,6 +10,8 @@ type WizardStep = 'connect' | 'success'
 
 interface SetupWizardProps {
   onComplete: () => void
+  /** Restaurant already paired by an earlier, interrupted run: skip straight to discovery */
+  resumeRestaurantId?: string
 }
 
 export interface DiscoveredPrinter {
@@ -21,20 +23,26 @@ export interface DiscoveredPrinter {
   capabilities?: any
 }
 
-export default function SetupWizard({ onComplete }: SetupWizardProps) {
+export default function SetupWizard({ onComplete, resumeRestaurantId }: SetupWizardProps) {
   const [currentStep, setCurrentStep] = useState<WizardStep>('connect')
   const [restaurantId, setRestaurantId] = useState<string>('')
   const [discoveredPrinters, setDiscoveredPrinters] = useState<DiscoveredPrinter[]>([])
 
-  const handleConnectComplete = async (token: string, resId: string) => {
+  useEffect(() => {
+    if (resumeRestaurantId) {
+      handleConnectComplete(resumeRestaurantId).catch((e) =>
+        console.error('Resuming setup failed:', e)
+      )
+    }
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [resumeRestaurantId])
+
+  const handleConnectComplete = async (resId: string) => {
     setRestaurantId(resId)
 
     try {
-      // Save auth to config
+      // Pairing (token + restaurant) was persisted by claim_pairing_code
       const config = await invoke<any>('get_config')
-      config.auth_token = token // printer_service_secret for pairing verification
-      config.restaurant_id = resId // restaurant_code like "W434N" — resolved to UUID by backend
-      await invoke('save_config', { config })
 
       // Auto-discover printers
       const printers = await invoke<DiscoveredPrinter[]>('discover_printers')
@@ -67,6 +75,16 @@ export default function SetupWizard({ onComplete }: SetupWizardProps) {
         console.warn('Failed to enable autostart:', e)
       }
 
+      // Print a "setup complete" receipt on each printer so staff can see pairing worked
+      try {
+        await invoke<string[]>('print_setup_complete')
+      } catch (e) {
+        console.warn('Failed to print setup receipt:', e)
+      }
+
+      // Pairing is only "done" once printers are saved; until then startup resumes here
+      await invoke('complete_setup')
+
       // Move to success step
       setCurrentStep('success')
     } catch (error) {
@@ -1,9 +1,10 @@
 import { useState } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { Store, AlertTriangle, Zap } from 'lucide-react'
+import { errorMessage } from '../../messages'
 
 interface ConnectStepProps {
-  onComplete: (token: string, restaurantId: string) => Promise<void>
+  onComplete: (restaurantId: string) => Promise<void>
 }
 
 export default function ConnectStep({ onComplete }: ConnectStepProps) {
@@ -43,9 +44,10 @@ export default function ConnectStep({ onComplete }: ConnectStepProps) {
         restaurantCode: string
       }>('claim_pairing_code', { code })
 
-      await onComplete(result.token, result.restaurantId)
+      // The daemon already persisted the pairing; the wizard only continues setup
+      await onComplete(result.restaurantId)
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err))
+      setError(errorMessage(err))
       setLoading(false)
     }
   }
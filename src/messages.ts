@@ -0,0 +1,32 @@
+/**
+ * Localized messages returned by the Tauri backend
+ *
+ * User-facing commands reject with `{ key, params, message }` (see src-tauri/src/i18n.rs).
+ * `message` is already rendered in the configured locale; `key` + `params` allow
+ * the UI to translate or special-case specific errors.
+ */
+
+export interface LocalizedMessage {
+  key: string
+  params: Record<string, string>
+  message: string
+}
+
+export function isLocalizedMessage(value: unknown): value is LocalizedMessage {
+  return (
+    typeof value === 'object' &&
+    value !== null &&
+    typeof (value as LocalizedMessage).key === 'string' &&
+    typeof (value as LocalizedMessage).message === 'string'
+  )
+}
+
+/**
+ * Human-readable text for any error thrown by `invoke`
+ * (localized message object, plain string, or Error)
+ */
+export function errorMessage(error: unknown): string {
+  if (isLocalizedMessage(error)) return error.message
+  if (error instanceof Error) return error.message
+  return String(error)
+}
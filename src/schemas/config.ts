@@ -10,7 +10,7 @@ import { z } from 'zod'
 /**
  * Connection Type Enum
  */
-export const ConnectionTypeSchema = z.enum(['usb', 'network', 'bluetooth'])
+export const ConnectionTypeSchema = z.enum(['usb', 'network', 'bluetooth', 'serial'])
 export type ConnectionType = z.infer<typeof ConnectionTypeSchema>
 
 /**
@@ -24,6 +24,18 @@ export const PrinterCapabilitiesSchema = z.object({
 })
 export type PrinterCapabilities = z.infer<typeof PrinterCapabilitiesSchema>
 
+/**
+ * Serial line settings (serial printers only; must match the printer's DIP switches)
+ */
+export const SerialSettingsSchema = z.object({
+  baud_rate: z.number().int().positive(),
+  data_bits: z.union([z.literal(7), z.literal(8)]),
+  parity: z.enum(['none', 'odd', 'even']),
+  stop_bits: z.union([z.literal(1), z.literal(2)]),
+  flow_control: z.enum(['none', 'software', 'hardware']),
+})
+export type SerialSettings = z.infer<typeof SerialSettingsSchema>
+
 /**
  * Printer Configuration
  */
@@ -32,10 +44,13 @@ export const PrinterConfigSchema = z.object({
   name: z.string(),
   connection_type: ConnectionTypeSchema,
   address: z.string(),
+  mac_address: z.string().nullable().optional(),
   protocol: z.string(),
   station: z.string().nullable(),
   is_primary: z.boolean(),
   capabilities: PrinterCapabilitiesSchema,
+  serial: SerialSettingsSchema.optional(),
+  paused: z.boolean().optional(),
 })
 export type PrinterConfig = z.infer<typeof PrinterConfigSchema>
 
@@ -50,6 +65,19 @@ export const AppConfigSchema = z.object({
   supabase_url: z.string().url(),
   supabase_anon_key: z.string(),
   printers: z.array(PrinterConfigSchema),
+  locale: z.enum(['nl', 'en']).default('nl'),
+  unit_system: z.enum(['metric', 'imperial']).optional(),
+  timezone: z.string().nullable().optional(),
+  pull_stations: z.array(z.string()).optional(),
+  incidents: z.object({ after_minutes: z.number().int().min(1) }).optional(),
+  ingest_dedup: z.object({ enabled: z.boolean(), ttl_minutes: z.number().int().min(1) }).optional(),
+  routing: z
+    .object({
+      overrides: z.array(z.object({ station: z.string(), printer_id: z.string() })).optional(),
+      rules: z.array(z.object({ station: z.string(), printer_id: z.string() })).optional(),
+      default_printer_id: z.string().nullable().optional(),
+    })
+    .optional(),
 })
 export type AppConfig = z.infer<typeof AppConfigSchema>
 